@@ -0,0 +1,83 @@
+//! Regression benchmarks for the repository tree walk behind `list_files`
+//! and `get_file_tree`. Set `WALK_BENCH_FILES` to size the generated tree
+//! (default 20 000 files; the target is 100 000 in well under a second).
+
+use std::fs;
+use std::path::Path;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+#[allow(dead_code)]
+#[path = "../src/walk.rs"]
+mod walk;
+
+use walk::{walk_builder, walk_parallel, DirArena};
+
+const FILES_PER_DIR: usize = 50;
+const DIRS_PER_LEVEL: usize = 10;
+
+/// Lay out `files` files across nested directories, plus an ignored
+/// `target/` the walk must skip.
+fn generate(root: &Path, files: usize) {
+    fs::write(root.join(".gitignore"), "target/\n").expect("write .gitignore");
+    let ignored = root.join("target");
+    fs::create_dir_all(&ignored).expect("create target");
+    for i in 0..FILES_PER_DIR {
+        fs::write(ignored.join(format!("build-{}.o", i)), "").expect("write ignored file");
+    }
+
+    for dir_index in 0..files.div_ceil(FILES_PER_DIR) {
+        let dir = root
+            .join(format!("pkg{}", dir_index % DIRS_PER_LEVEL))
+            .join(format!(
+                "mod{}",
+                dir_index / DIRS_PER_LEVEL % DIRS_PER_LEVEL
+            ))
+            .join(format!("leaf{}", dir_index));
+        fs::create_dir_all(&dir).expect("create directory");
+        let count = FILES_PER_DIR.min(files - dir_index * FILES_PER_DIR);
+        for file_index in 0..count {
+            fs::write(dir.join(format!("file{}.rs", file_index)), "fn main() {}\n")
+                .expect("write file");
+        }
+    }
+}
+
+fn bench_walk(c: &mut Criterion) {
+    let files = std::env::var("WALK_BENCH_FILES")
+        .ok()
+        .and_then(|files| files.parse().ok())
+        .unwrap_or(20_000);
+    let tree = tempfile::tempdir().expect("create temp dir");
+    generate(tree.path(), files);
+
+    let mut group = c.benchmark_group(format!("walk_{}_files", files));
+    group.sample_size(20);
+    group.bench_function("walk_parallel", |b| {
+        b.iter(|| {
+            let paths = walk_parallel(&walk_builder(tree.path(), true, false), |entry| {
+                Some(entry.path().to_path_buf())
+            })
+            .expect("walk");
+            assert!(paths.len() > files);
+            paths
+        })
+    });
+    group.bench_function("dir_arena", |b| {
+        b.iter(|| {
+            let arena = DirArena::walk(tree.path(), true, usize::MAX).expect("walk");
+            let leaves = arena
+                .entries
+                .iter()
+                .filter(|entry| !entry.is_dir && entry.children.is_empty() && entry.depth > 0)
+                .count();
+            // Hidden files like .gitignore are left out, as in the tree view.
+            assert_eq!(leaves, files);
+            arena
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_walk);
+criterion_main!(benches);
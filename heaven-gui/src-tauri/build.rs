@@ -1,3 +1,132 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use syn::{FnArg, Item, Pat, PatType, Type};
+
+/// Read-only commands `batch` may run, as registered in `generate_handler!`.
+/// Argument names are read from each command's signature, so this list is
+/// the only thing to keep up to date.
+const BATCH_COMMANDS: &[&str] = &[
+    "read_global_state",
+    "settings::get_settings",
+    "list_repositories",
+    "read_repository",
+    "list_workpads",
+    "read_workpad",
+    "list_commits",
+    "list_test_runs",
+    "read_test_run",
+    "list_ai_operations",
+    "read_ai_operation",
+    "dashboard::get_dashboard",
+    "cost::get_cost_report",
+    "lifecycle::get_workpad_transitions",
+    "archive::list_archived_workpads",
+    "patches::list_patches",
+    "workpad_templates::list_workpad_templates",
+    "workspaces::list_workspaces",
+    "workspaces::get_workspace_summary",
+    "storage::get_storage_usage",
+    "maintenance::get_maintenance_report",
+    "ledger::get_session_summary",
+    "sessions::list_sessions",
+    "promotion::list_promotions",
+    "promotion::list_promotion_queue",
+];
+
+/// Whether `ty` is state Tauri injects rather than an argument from `invoke`.
+fn is_managed(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(reference) => is_managed(&reference.elem),
+        Type::Path(path) => path.path.segments.last().map_or(false, |segment| {
+            ["Window", "State", "AppHandle"]
+                .iter()
+                .any(|managed| segment.ident == managed)
+        }),
+        _ => false,
+    }
+}
+
+/// Parameter names of the top-level `fn <name>` in `file`. Panics on any
+/// signature the dispatch table can't call with named arguments.
+fn parameters(file: &syn::File, name: &str) -> Option<Vec<String>> {
+    let function = file.items.iter().find_map(|item| match item {
+        Item::Fn(function) if function.sig.ident == name => Some(function),
+        _ => None,
+    })?;
+    Some(
+        function
+            .sig
+            .inputs
+            .iter()
+            .map(|input| match input {
+                FnArg::Typed(PatType { pat, ty, .. }) => {
+                    let Pat::Ident(param) = &**pat else {
+                        panic!("batch command {} destructures an argument", name);
+                    };
+                    if is_managed(ty) {
+                        panic!("batch command {} takes managed state or a window", name);
+                    }
+                    param.ident.to_string()
+                }
+                FnArg::Receiver(_) => panic!("batch command {} takes self", name),
+            })
+            .collect(),
+    )
+}
+
+/// `snake_case` to the camelCase key `invoke` sends.
+fn camel_case(name: &str) -> String {
+    let mut parts = name.split('_');
+    let mut camel = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            camel.extend(first.to_uppercase());
+            camel.push_str(chars.as_str());
+        }
+    }
+    camel
+}
+
+/// `batch::dispatch`, with one match arm per entry of `BATCH_COMMANDS`.
+fn generate_batch_dispatch() {
+    let mut arms = String::new();
+    for command in BATCH_COMMANDS {
+        let (module, name) = command.rsplit_once("::").unwrap_or(("main", command));
+        let file = Path::new("src").join(format!("{}.rs", module));
+        println!("cargo:rerun-if-changed={}", file.display());
+        let source = fs::read_to_string(&file)
+            .unwrap_or_else(|e| panic!("failed to read {}: {}", file.display(), e));
+        let syntax = syn::parse_file(&source)
+            .unwrap_or_else(|e| panic!("failed to parse {}: {}", file.display(), e));
+        let params = parameters(&syntax, name)
+            .unwrap_or_else(|| panic!("batch command {} not found in {}", name, file.display()));
+        let args: Vec<String> = params
+            .iter()
+            .map(|param| format!("arg(request, \"{}\")?", camel_case(param)))
+            .collect();
+        let path = if module == "main" { name } else { command };
+        arms.push_str(&format!(
+            "        \"{}\" => to_value(crate::{}({})),\n",
+            name,
+            path,
+            args.join(", ")
+        ));
+    }
+    let dispatch = format!(
+        "fn dispatch(request: &BatchRequest) -> Result<Value, String> {{\n    \
+         match request.command.as_str() {{\n{}        \
+         other => Err(format!(\"Command not available in a batch: {{}}\", other)),\n    \
+         }}\n}}\n",
+        arms
+    );
+    let out = Path::new(&env::var("OUT_DIR").expect("OUT_DIR is set")).join("batch_dispatch.rs");
+    fs::write(&out, dispatch).expect("failed to write batch dispatch table");
+}
+
 fn main() {
+    generate_batch_dispatch();
     tauri_build::build()
 }
@@ -0,0 +1,17 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code)]
+#[path = "../../src/unified_diff.rs"]
+mod unified_diff;
+
+use unified_diff::parse_changed_files;
+
+fuzz_target!(|data: &[u8]| {
+    // Patch files aren't always UTF-8; callers decode them lossily.
+    let diff = String::from_utf8_lossy(data);
+    let paths = parse_changed_files(&diff);
+    assert!(paths.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(paths.iter().all(|path| !path.is_empty()));
+});
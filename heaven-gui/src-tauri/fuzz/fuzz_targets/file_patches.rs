@@ -0,0 +1,21 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code)]
+#[path = "../../src/unified_diff.rs"]
+mod unified_diff;
+
+use unified_diff::{line_counts, parse_file_patches};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(diff) = std::str::from_utf8(data) else {
+        return;
+    };
+    let files = parse_file_patches(diff);
+    for file in &files {
+        assert!(file.old_path.is_some() || file.new_path.is_some());
+    }
+    let (added, removed) = line_counts(diff);
+    assert!(added + removed <= diff.lines().count());
+});
@@ -0,0 +1,426 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::ai_client::{post_json, RetryMetadata};
+use crate::ai_queue;
+use crate::cli_config::effective_settings;
+use crate::commands::{load_workpad, read_json, save_workpad, write_json};
+use crate::cost::{ensure_within_budget, ensure_within_route_budget};
+use crate::ledger;
+use crate::metrics;
+use crate::mock_ai;
+use crate::notifications;
+use crate::ollama;
+use crate::packs;
+use crate::privacy;
+use crate::statuses::AIOperationStatus;
+use crate::tokens::{count_message_tokens, count_tokens, pricing_for, ModelPricing};
+use crate::{get_state_dir, AIOperation};
+
+/// Connection details for an OpenAI-compatible chat completions endpoint
+/// (Abacus RouteLLM by default, matching the CLI's provider).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub(crate) struct AiSettings {
+    pub(crate) enabled: bool,
+    pub(crate) base_url: String,
+    pub(crate) api_key: Option<String>,
+    pub(crate) model: String,
+    pub(crate) max_tokens: u32,
+    pub(crate) temperature: f64,
+    pub(crate) request_timeout_secs: u64,
+    /// Price overrides keyed by model-name prefix.
+    pub(crate) pricing: HashMap<String, ModelPricing>,
+    /// Tokens of repository files to include with workpad prompts.
+    pub(crate) context_token_budget: usize,
+    /// Local Ollama server used when no API key is configured.
+    pub(crate) ollama_url: String,
+    pub(crate) ollama_model: String,
+    /// Client-side cap per provider host; 0 disables throttling.
+    pub(crate) requests_per_minute: u32,
+    /// Largest piece of a diff `summarize_diff` sends in one request.
+    pub(crate) summary_chunk_tokens: usize,
+    /// Provider requests allowed in flight at once; the rest queue.
+    pub(crate) max_concurrent_requests: usize,
+    /// Retries after the first attempt on 429/503 or failures to connect.
+    pub(crate) max_retries: u32,
+    pub(crate) retry_base_delay_ms: u64,
+    /// What is stored of prompts and responses: one of `HISTORY_MODES`.
+    pub(crate) history: String,
+    /// Per operation type ("chat", "generate_patch", "commit_message",
+    /// "review", ...) overrides of provider, model and budget.
+    pub(crate) routes: HashMap<String, ModelRoute>,
+    /// One of `PROVIDERS` for operations without a routed provider; unset
+    /// picks the cloud endpoint or Ollama by whether an API key is set.
+    pub(crate) provider: Option<String>,
+    /// Fixture files for the mock provider; defaults to `mock_ai` in the
+    /// Solo Git home.
+    pub(crate) mock_fixtures_dir: Option<String>,
+}
+
+pub(crate) const PROVIDERS: &[&str] = &["cloud", "ollama", "mock"];
+/// Overrides every configured provider, e.g. `SOLOGIT_AI_PROVIDER=mock` for
+/// integration tests and offline demos.
+const PROVIDER_ENV: &str = "SOLOGIT_AI_PROVIDER";
+
+/// Where one type of AI operation is sent, so routine work can go to a
+/// cheap model and planning to a strong one.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub(crate) struct ModelRoute {
+    /// One of `PROVIDERS`; unset uses the cloud endpoint when an API key
+    /// is configured and Ollama otherwise.
+    pub(crate) provider: Option<String>,
+    /// Used unless the caller names a model explicitly.
+    pub(crate) model: Option<String>,
+    /// Spend cap for this operation type per calendar month (UTC), on top
+    /// of the overall `cost.monthly_budget_usd`.
+    pub(crate) monthly_budget_usd: Option<f64>,
+}
+
+impl Default for AiSettings {
+    fn default() -> Self {
+        AiSettings {
+            enabled: true,
+            base_url: "https://routellm.abacus.ai/v1".to_string(),
+            api_key: None,
+            model: "gpt-4o".to_string(),
+            max_tokens: 2048,
+            temperature: 0.1,
+            request_timeout_secs: 120,
+            pricing: HashMap::new(),
+            context_token_budget: 8000,
+            ollama_url: "http://localhost:11434".to_string(),
+            ollama_model: "llama3.1".to_string(),
+            requests_per_minute: 30,
+            summary_chunk_tokens: 6000,
+            max_concurrent_requests: 2,
+            max_retries: 3,
+            retry_base_delay_ms: 500,
+            history: "full".to_string(),
+            routes: HashMap::new(),
+            provider: None,
+            mock_fixtures_dir: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct ChatMessage {
+    pub(crate) role: String,
+    pub(crate) content: String,
+}
+
+impl ChatMessage {
+    pub(crate) fn system(content: impl Into<String>) -> Self {
+        ChatMessage {
+            role: "system".to_string(),
+            content: content.into(),
+        }
+    }
+
+    pub(crate) fn user(content: impl Into<String>) -> Self {
+        ChatMessage {
+            role: "user".to_string(),
+            content: content.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Completion {
+    pub(crate) content: String,
+    pub(crate) model: String,
+    pub(crate) prompt_tokens: i32,
+    pub(crate) completion_tokens: i32,
+    pub(crate) cost_usd: f64,
+}
+
+pub(crate) fn ai_settings() -> AiSettings {
+    effective_settings().map(|s| s.ai).unwrap_or_default()
+}
+
+/// Send a chat completion to `provider`, or by default to the cloud
+/// endpoint when an API key is set and the local Ollama server otherwise.
+/// `SOLOGIT_AI_PROVIDER` takes precedence over both. Retry and throttling
+/// details are written to `retry` either way.
+pub(crate) fn complete(
+    messages: &[ChatMessage],
+    model: Option<&str>,
+    provider: Option<&str>,
+    operation_type: &str,
+    retry: &mut RetryMetadata,
+) -> Result<Completion, String> {
+    let ai = ai_settings();
+    if !ai.enabled {
+        return Err("AI features are disabled in settings".to_string());
+    }
+
+    let forced = std::env::var(PROVIDER_ENV)
+        .ok()
+        .filter(|provider| !provider.trim().is_empty());
+    let provider = forced
+        .as_deref()
+        .map(str::trim)
+        .or(provider)
+        .or(ai.provider.as_deref());
+    let api_key = ai.api_key.clone().filter(|key| !key.trim().is_empty());
+    match (provider, api_key) {
+        (Some("mock"), _) => mock_ai::chat(&ai, operation_type, messages),
+        (Some("ollama"), _) | (None, None) => ollama::chat(&ai, model, messages, retry),
+        (Some("cloud") | None, Some(api_key)) => {
+            let model = model.unwrap_or(&ai.model).to_string();
+            complete_cloud(&ai, &api_key, &model, messages, retry)
+        }
+        (Some("cloud"), None) => Err("The cloud provider needs ai.api_key".to_string()),
+        (Some(other), _) => Err(format!("Unknown AI provider: {}", other)),
+    }
+}
+
+fn complete_cloud(
+    settings: &AiSettings,
+    api_key: &str,
+    model: &str,
+    messages: &[ChatMessage],
+    retry: &mut RetryMetadata,
+) -> Result<Completion, String> {
+    let url = format!(
+        "{}/chat/completions",
+        settings.base_url.trim_end_matches('/')
+    );
+    let body = json!({
+        "model": model,
+        "messages": messages,
+        "max_tokens": settings.max_tokens,
+        "temperature": settings.temperature,
+    });
+    let authorization = format!("Bearer {}", api_key);
+
+    let response = post_json(
+        settings,
+        &url,
+        &[("Authorization", authorization.as_str())],
+        &body,
+        retry,
+    )?;
+
+    let content = response["choices"][0]["message"]["content"]
+        .as_str()
+        .ok_or_else(|| "AI provider returned no message content".to_string())?
+        .to_string();
+    let model = response["model"].as_str().unwrap_or(model).to_string();
+
+    // Trust the provider's usage report; count locally when it's missing.
+    let usage = &response["usage"];
+    let prompt_tokens = usage["prompt_tokens"]
+        .as_u64()
+        .map(|n| n as usize)
+        .unwrap_or_else(|| count_message_tokens(messages, &model));
+    let completion_tokens = usage["completion_tokens"]
+        .as_u64()
+        .map(|n| n as usize)
+        .unwrap_or_else(|| count_tokens(&content, &model));
+    let cost_usd = pricing_for(&model, &settings.pricing).cost(prompt_tokens, completion_tokens);
+
+    Ok(Completion {
+        content,
+        model,
+        prompt_tokens: prompt_tokens as i32,
+        completion_tokens: completion_tokens as i32,
+        cost_usd,
+    })
+}
+
+fn operation_path(operation_id: &str) -> std::path::PathBuf {
+    get_state_dir()
+        .join("ai_operations")
+        .join(format!("{}.json", operation_id))
+}
+
+pub(crate) fn load_operation(operation_id: &str) -> Result<AIOperation, String> {
+    match read_json(&operation_path(operation_id))? {
+        Some(operation) => Ok(operation),
+        None => packs::read_packed("ai_operations", "operation_id", operation_id)?
+            .ok_or_else(|| format!("AI operation not found: {}", operation_id)),
+    }
+}
+
+/// Persist an operation, redacting the stored copy per `ai.history`;
+/// callers keep the full text in memory.
+pub(crate) fn save_operation(operation: &AIOperation) -> Result<(), String> {
+    let mut stored = operation.clone();
+    privacy::redact_operation(&mut stored, &ai_settings().history);
+    write_json(&operation_path(&operation.operation_id), &stored)
+}
+
+/// Run `messages` through the provider and persist the outcome as an
+/// AIOperation, linking it to the workpad and adding its cost to the global
+/// totals. `ai.routes` picks the provider and model for `operation_type`
+/// (an explicit `model` still wins) and may cap its monthly spend. The
+/// request waits its turn in the AI queue. Provider failures are
+/// recorded as failed operations and cancellations as cancelled ones; in
+/// both cases no completion is returned.
+pub(crate) fn run_operation(
+    workpad_id: Option<String>,
+    operation_type: &str,
+    prompt: &str,
+    messages: &[ChatMessage],
+    model: Option<&str>,
+) -> Result<(AIOperation, Option<Completion>), String> {
+    let route = ai_settings()
+        .routes
+        .remove(operation_type)
+        .unwrap_or_default();
+    // Every AI entry point comes through here, so this is the one budget gate.
+    ensure_within_budget()?;
+    ensure_within_route_budget(operation_type, route.monthly_budget_usd)?;
+    let model = model.or(route.model.as_deref());
+    let operation_id = format!("op-{}", Uuid::new_v4().simple());
+    let started_at = Utc::now().to_rfc3339();
+    let mut retry = RetryMetadata::default();
+    let (result, cancelled) =
+        match ai_queue::acquire(&operation_id, operation_type, workpad_id.as_deref()) {
+            Ok(slot) => {
+                let result = complete(
+                    messages,
+                    model,
+                    route.provider.as_deref(),
+                    operation_type,
+                    &mut retry,
+                );
+                (result, slot.finish())
+            }
+            Err(error) => (Err(error), true),
+        };
+
+    let mut operation = AIOperation {
+        operation_id,
+        workpad_id: workpad_id.clone(),
+        operation_type: operation_type.to_string(),
+        status: AIOperationStatus::Completed,
+        model: model
+            .map(str::to_string)
+            .unwrap_or_else(|| ai_settings().model),
+        prompt: prompt.to_string(),
+        response: None,
+        cost_usd: 0.0,
+        tokens_used: 0,
+        started_at,
+        completed_at: Some(Utc::now().to_rfc3339()),
+        error: None,
+        patch_id: None,
+        retry: Some(retry).filter(RetryMetadata::is_notable),
+        redaction: None,
+        messages: Vec::new(),
+        retry_of: None,
+    };
+    match &result {
+        // A request cancelled mid-flight was still paid for.
+        Ok(completion) => {
+            operation.model = completion.model.clone();
+            operation.cost_usd = completion.cost_usd;
+            operation.tokens_used = completion.prompt_tokens + completion.completion_tokens;
+            if cancelled {
+                operation.status = AIOperationStatus::Cancelled;
+                operation.error = Some("Cancelled".to_string());
+            } else {
+                operation.response = Some(completion.content.clone());
+            }
+        }
+        Err(error) => {
+            operation.status = if cancelled {
+                AIOperationStatus::Cancelled
+            } else {
+                AIOperationStatus::Failed
+            };
+            operation.error = Some(error.clone());
+        }
+    }
+    if operation.status != AIOperationStatus::Completed {
+        operation.messages = messages.to_vec();
+    }
+    save_operation(&operation)?;
+
+    if let Some(wp_id) = &workpad_id {
+        let mut workpad = load_workpad(wp_id)?;
+        workpad
+            .ai_operations
+            .insert(0, operation.operation_id.clone());
+        save_workpad(workpad)?;
+    }
+
+    ledger::record(
+        "ai_operation",
+        Some(&operation.operation_id),
+        operation.cost_usd,
+    )?;
+
+    notifications::ai_operation_finished(&operation);
+    metrics::record_ai_operation(&operation);
+    Ok((operation, result.ok().filter(|_| !cancelled)))
+}
+
+/// Send a failed or cancelled operation's stored conversation again, as a
+/// new operation of the same type. Only the provider call is repeated:
+/// whatever would have consumed the original response (a chat session, a
+/// patch proposal) is not updated.
+#[tauri::command]
+pub(crate) fn retry_ai_operation(operation_id: String) -> Result<AIOperation, String> {
+    let original = load_operation(&operation_id)?;
+    if !matches!(
+        original.status,
+        AIOperationStatus::Failed | AIOperationStatus::Cancelled
+    ) {
+        return Err(format!(
+            "Only failed or cancelled operations can be retried; {} is {}",
+            operation_id, original.status
+        ));
+    }
+    if original.messages.is_empty() {
+        return Err(format!(
+            "{} has no stored conversation to retry",
+            operation_id
+        ));
+    }
+    if original.redaction.is_some() {
+        return Err(format!(
+            "{} was stored redacted and can't be retried",
+            operation_id
+        ));
+    }
+    let (mut operation, _) = run_operation(
+        original.workpad_id.clone(),
+        &original.operation_type,
+        &original.prompt,
+        &original.messages,
+        Some(&original.model),
+    )?;
+    operation.retry_of = Some(operation_id);
+    save_operation(&operation)?;
+    Ok(operation)
+}
+
+/// Pull the body out of a fenced code block if the model wrapped its answer
+/// in one, otherwise return the text unchanged.
+pub(crate) fn strip_code_fence(text: &str) -> String {
+    let trimmed = text.trim();
+    if let Some(start) = trimmed.find("```") {
+        let after = &trimmed[start + 3..];
+        let body_start = after.find('\n').map(|i| i + 1).unwrap_or(0);
+        let body = &after[body_start..];
+        if let Some(end) = body.rfind("```") {
+            return body[..end].to_string();
+        }
+    }
+    trimmed.to_string()
+}
+
+/// Parse a JSON object out of a model response, tolerating code fences.
+pub(crate) fn parse_json_response(text: &str) -> Result<Value, String> {
+    serde_json::from_str(&strip_code_fence(text))
+        .map_err(|e| format!("AI response was not valid JSON: {}", e))
+}
@@ -0,0 +1,170 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::ai::AiSettings;
+
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// What happened on the way to a provider response, kept on the AIOperation
+/// so slow or failed requests can be explained.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub(crate) struct RetryMetadata {
+    pub(crate) attempts: u32,
+    /// Time spent waiting for a rate-limit slot.
+    pub(crate) throttled_ms: u64,
+    /// Time spent backing off between attempts.
+    pub(crate) backoff_ms: u64,
+    pub(crate) retries: Vec<RetryAttempt>,
+}
+
+impl RetryMetadata {
+    /// Whether there is anything worth recording beyond a single clean attempt.
+    pub(crate) fn is_notable(&self) -> bool {
+        self.attempts > 1 || self.throttled_ms > 0
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct RetryAttempt {
+    attempt: u32,
+    status: Option<u16>,
+    reason: String,
+    delay_ms: u64,
+}
+
+fn limiter() -> &'static Mutex<HashMap<String, VecDeque<Instant>>> {
+    static LIMITER: OnceLock<Mutex<HashMap<String, VecDeque<Instant>>>> = OnceLock::new();
+    LIMITER.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Block until a request to `key` fits in the sliding one-minute window.
+/// Returns how long we waited.
+fn acquire_slot(key: &str, requests_per_minute: u32) -> Duration {
+    if requests_per_minute == 0 {
+        return Duration::ZERO;
+    }
+
+    let started = Instant::now();
+    loop {
+        let wait = {
+            let mut windows = limiter().lock().unwrap_or_else(|e| e.into_inner());
+            let sent = windows.entry(key.to_string()).or_default();
+            let now = Instant::now();
+            while sent
+                .front()
+                .is_some_and(|at| now.duration_since(*at) >= WINDOW)
+            {
+                sent.pop_front();
+            }
+            if sent.len() < requests_per_minute as usize {
+                sent.push_back(now);
+                return started.elapsed();
+            }
+            sent.front()
+                .map(|oldest| WINDOW.saturating_sub(now.duration_since(*oldest)))
+                .unwrap_or(WINDOW)
+        };
+        thread::sleep(wait.max(Duration::from_millis(10)));
+    }
+}
+
+/// Exponential backoff with up to 50% random jitter, capped at MAX_BACKOFF.
+fn backoff(base: Duration, attempt: u32) -> Duration {
+    let exponential = base
+        .saturating_mul(1u32 << attempt.min(10))
+        .min(MAX_BACKOFF);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    let jitter = exponential.mul_f64((nanos % 1000) as f64 / 2000.0);
+    (exponential + jitter).min(MAX_BACKOFF)
+}
+
+fn retry_after(response: &ureq::Response) -> Option<Duration> {
+    response
+        .header("Retry-After")
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(|secs| Duration::from_secs(secs).min(MAX_BACKOFF))
+}
+
+/// Only statuses that say the request wasn't processed; retrying anything
+/// else could bill a completion twice.
+fn is_retryable(status: u16) -> bool {
+    status == 429 || status == 503
+}
+
+/// Whether the request failed before it reached the provider.
+fn never_sent(error: &ureq::Transport) -> bool {
+    matches!(
+        error.kind(),
+        ureq::ErrorKind::Dns | ureq::ErrorKind::ConnectionFailed
+    )
+}
+
+/// Rate-limited JSON POST, retried on 429/503 and on failures to connect.
+/// `retry` is filled in whether or not the request ultimately succeeds.
+pub(crate) fn post_json(
+    settings: &AiSettings,
+    url: &str,
+    headers: &[(&str, &str)],
+    body: &Value,
+    retry: &mut RetryMetadata,
+) -> Result<Value, String> {
+    let agent = ureq::AgentBuilder::new()
+        .timeout(Duration::from_secs(settings.request_timeout_secs))
+        .user_agent("heaven-gui")
+        .build();
+    let limiter_key = url.split('/').take(3).collect::<Vec<_>>().join("/");
+    let base_delay = Duration::from_millis(settings.retry_base_delay_ms);
+
+    loop {
+        retry.throttled_ms +=
+            acquire_slot(&limiter_key, settings.requests_per_minute).as_millis() as u64;
+        retry.attempts += 1;
+        let attempt = retry.attempts;
+
+        let mut request = agent.post(url);
+        for (name, value) in headers {
+            request = request.set(name, value);
+        }
+
+        let (status, reason, hint) = match request.send_json(body) {
+            Ok(response) => {
+                return response
+                    .into_json::<Value>()
+                    .map_err(|e| format!("Failed to parse response: {}", e));
+            }
+            Err(ureq::Error::Status(code, response)) => {
+                let hint = retry_after(&response);
+                let text = response.into_string().unwrap_or_default();
+                if !is_retryable(code) || attempt > settings.max_retries {
+                    return Err(format!("{} returned {}: {}", url, code, text.trim()));
+                }
+                (Some(code), format!("HTTP {}", code), hint)
+            }
+            Err(ureq::Error::Transport(error)) => {
+                if !never_sent(&error) || attempt > settings.max_retries {
+                    return Err(format!("Request failed: {}", error));
+                }
+                (None, error.to_string(), None)
+            }
+        };
+
+        let delay = hint.unwrap_or_else(|| backoff(base_delay, attempt - 1));
+        retry.backoff_ms += delay.as_millis() as u64;
+        retry.retries.push(RetryAttempt {
+            attempt,
+            status,
+            reason,
+            delay_ms: delay.as_millis() as u64,
+        });
+        thread::sleep(delay);
+    }
+}
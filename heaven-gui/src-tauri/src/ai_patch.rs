@@ -0,0 +1,215 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::ai::{
+    ai_settings, load_operation, run_operation, save_operation, strip_code_fence, ChatMessage,
+};
+use crate::audit::audited;
+use crate::commands::{apply_patch, load_workpad, read_json, workpad_checkout_dir, write_json};
+use crate::context::build_context;
+use crate::git::{run_git_with_env, workpad_diff};
+use crate::logging::warn_on_err;
+use crate::patches::list_patches;
+use crate::statuses::AIOperationStatus;
+use crate::unified_diff::parse_file_patches;
+use crate::{get_state_dir, AIOperation, WorkpadState};
+
+const PATCH_SYSTEM_PROMPT: &str = "You are a code generation assistant working inside a git \
+repository. Reply with a single unified diff (as produced by `git diff`) relative to the \
+current state of the branch, with a/ and b/ path prefixes. Do not include explanations.";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct ProposedFileChange {
+    path: String,
+    additions: usize,
+    deletions: usize,
+}
+
+/// A model-generated diff awaiting user confirmation.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct AiPatchProposal {
+    operation_id: String,
+    workpad_id: String,
+    diff: String,
+    files: Vec<ProposedFileChange>,
+    /// Whether `git apply --check` accepted the diff against the branch.
+    valid: bool,
+    validation_error: Option<String>,
+    created_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    accepted_patch_id: Option<String>,
+}
+
+fn proposal_path(operation_id: &str) -> PathBuf {
+    get_state_dir()
+        .join("ai_patches")
+        .join(format!("{}.json", operation_id))
+}
+
+fn preview_files(diff: &str) -> Vec<ProposedFileChange> {
+    parse_file_patches(diff)
+        .into_iter()
+        .map(|file| ProposedFileChange {
+            path: file.path().to_string(),
+            additions: file.additions,
+            deletions: file.deletions,
+        })
+        .collect()
+}
+
+/// Check that `diff` applies cleanly to the head of the workpad branch,
+/// whatever the checkout currently has checked out.
+fn validate_diff(workpad: &WorkpadState, diff: &str) -> Result<(), String> {
+    if !diff.lines().any(|line| line.starts_with("@@")) {
+        return Err("Response does not contain a unified diff".to_string());
+    }
+
+    let checkout = workpad_checkout_dir(workpad)?;
+    let scratch = env::temp_dir().join(format!("sologit_ai_patch_{}", Uuid::new_v4().simple()));
+    let temp_path = scratch.with_extension("diff");
+    let index_path = scratch.with_extension("index");
+    fs::write(&temp_path, diff).map_err(|e| format!("Failed to write temporary patch: {}", e))?;
+    let result = match (temp_path.to_str(), index_path.to_str()) {
+        (Some(patch), Some(index)) => {
+            let env = [("GIT_INDEX_FILE", index)];
+            let branch = format!("refs/heads/{}", workpad.branch_name);
+            run_git_with_env(&checkout, &["read-tree", &branch], &env).and_then(|_| {
+                run_git_with_env(&checkout, &["apply", "--check", "--cached", patch], &env)
+                    .map(|_| ())
+            })
+        }
+        _ => Err("Failed to encode temporary patch path".to_string()),
+    };
+    warn_on_err(
+        "Failed to remove temporary patch",
+        fs::remove_file(&temp_path),
+    );
+    if index_path.exists() {
+        warn_on_err(
+            "Failed to remove temporary index",
+            fs::remove_file(&index_path),
+        );
+    }
+    result
+}
+
+/// Ask the provider for a patch implementing `prompt` on top of the
+/// workpad's current changes, and store it for review.
+pub(crate) fn propose_patch(workpad_id: &str, prompt: &str) -> Result<AIOperation, String> {
+    let workpad = load_workpad(workpad_id)?;
+    let current_diff = workpad_diff(&workpad)?;
+
+    let context = build_context(&workpad, prompt, ai_settings().context_token_budget)?;
+
+    let mut request = format!("Task:\n{}\n", prompt.trim());
+    if !context.block().is_empty() {
+        request.push_str(&format!("\nRelevant files:\n\n{}", context.block()));
+    }
+    if !current_diff.trim().is_empty() {
+        request.push_str(&format!(
+            "\nChanges already made on this workpad:\n```diff\n{}```\n",
+            current_diff
+        ));
+    }
+    let messages = [
+        ChatMessage::system(PATCH_SYSTEM_PROMPT),
+        ChatMessage::user(request),
+    ];
+
+    let (mut operation, completion) = run_operation(
+        Some(workpad_id.to_string()),
+        "generate_patch",
+        prompt,
+        &messages,
+        None,
+    )?;
+    let Some(completion) = completion else {
+        return Ok(operation);
+    };
+
+    let diff = strip_code_fence(&completion.content);
+    let diff = if diff.ends_with('\n') {
+        diff
+    } else {
+        diff + "\n"
+    };
+    let validation = validate_diff(&workpad, &diff);
+
+    let proposal = AiPatchProposal {
+        operation_id: operation.operation_id.clone(),
+        workpad_id: workpad_id.to_string(),
+        files: preview_files(&diff),
+        diff,
+        valid: validation.is_ok(),
+        validation_error: validation.err(),
+        created_at: Utc::now().to_rfc3339(),
+        accepted_patch_id: None,
+    };
+    write_json(&proposal_path(&operation.operation_id), &proposal)?;
+
+    operation.status = if proposal.valid {
+        AIOperationStatus::PendingReview
+    } else {
+        AIOperationStatus::Failed
+    };
+    operation.error = proposal.validation_error.clone();
+    save_operation(&operation)?;
+    Ok(operation)
+}
+
+#[tauri::command]
+pub(crate) fn get_ai_patch_proposal(operation_id: String) -> Result<AiPatchProposal, String> {
+    read_json(&proposal_path(&operation_id))?
+        .ok_or_else(|| format!("No patch proposal for operation {}", operation_id))
+}
+
+#[tauri::command]
+pub(crate) fn accept_ai_patch(
+    operation_id: String,
+    message: Option<String>,
+) -> Result<WorkpadState, String> {
+    audited(
+        "accept_ai_patch",
+        "ai_operation",
+        Some(operation_id.clone()),
+        None,
+        move || accept_patch(operation_id, message),
+    )
+}
+
+fn accept_patch(operation_id: String, message: Option<String>) -> Result<WorkpadState, String> {
+    let mut proposal = get_ai_patch_proposal(operation_id.clone())?;
+    if let Some(patch_id) = &proposal.accepted_patch_id {
+        return Err(format!("Proposal already applied as {}", patch_id));
+    }
+    if !proposal.valid {
+        return Err(format!(
+            "Proposal did not validate: {}",
+            proposal.validation_error.clone().unwrap_or_default()
+        ));
+    }
+
+    let mut operation = load_operation(&operation_id)?;
+    let message = message
+        .filter(|m| !m.trim().is_empty())
+        .unwrap_or_else(|| format!("AI: {}", operation.prompt.lines().next().unwrap_or("")));
+
+    let workpad = apply_patch(proposal.workpad_id.clone(), message, proposal.diff.clone())?;
+
+    let patch_id = list_patches(proposal.workpad_id.clone())?
+        .last()
+        .map(|patch| patch.patch_id().to_string());
+    proposal.accepted_patch_id = patch_id.clone();
+    write_json(&proposal_path(&operation_id), &proposal)?;
+
+    operation.status = AIOperationStatus::Completed;
+    operation.patch_id = patch_id;
+    save_operation(&operation)?;
+
+    Ok(workpad)
+}
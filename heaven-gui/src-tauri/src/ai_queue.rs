@@ -0,0 +1,251 @@
+use std::collections::HashSet;
+use std::sync::{Condvar, Mutex, MutexGuard, OnceLock};
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::Serialize;
+use tauri::Manager;
+
+use crate::ai::ai_settings;
+use crate::logging::warn_on_err;
+
+/// Priority levels, highest first.
+pub(crate) const PRIORITIES: &[&str] = &["interactive", "batch"];
+/// Operation types someone is actively waiting on; everything else queues
+/// behind them.
+const INTERACTIVE_TYPES: &[&str] = &["chat", "prompt", "explain"];
+/// Waiters re-check the concurrency limit this often, so a settings change
+/// applies without a slot being released.
+const RECHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+static APP: OnceLock<tauri::AppHandle> = OnceLock::new();
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct QueuedOperation {
+    operation_id: String,
+    operation_type: String,
+    workpad_id: Option<String>,
+    priority: String,
+    /// 1-based place among waiting operations; absent once running.
+    position: Option<usize>,
+    enqueued_at: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct AiQueueSnapshot {
+    max_concurrent: usize,
+    running: Vec<QueuedOperation>,
+    waiting: Vec<QueuedOperation>,
+}
+
+#[derive(Default)]
+struct QueueState {
+    /// Kept in dispatch order: by priority, then arrival.
+    waiting: Vec<(usize, u64, QueuedOperation)>,
+    running: Vec<QueuedOperation>,
+    cancelled: HashSet<String>,
+    next_seq: u64,
+}
+
+/// Coordinates provider requests across the app: at most
+/// `ai.max_concurrent_requests` run at once and interactive operations are
+/// dispatched before batch ones.
+#[derive(Default)]
+pub(crate) struct AiQueue {
+    state: Mutex<QueueState>,
+    available: Condvar,
+}
+
+/// A running operation's hold on the queue, released on drop.
+pub(crate) struct QueueSlot {
+    queue: Option<&'static AiQueue>,
+    operation_id: String,
+}
+
+pub(crate) fn init(app: &tauri::AppHandle) {
+    APP.set(app.clone()).ok(); // Already initialised.
+}
+
+fn managed() -> Option<&'static AiQueue> {
+    APP.get().map(|app| app.state::<AiQueue>().inner())
+}
+
+fn max_concurrent() -> usize {
+    ai_settings().max_concurrent_requests.max(1)
+}
+
+fn rank(priority: &str) -> usize {
+    PRIORITIES
+        .iter()
+        .position(|p| *p == priority)
+        .unwrap_or(PRIORITIES.len())
+}
+
+fn priority_of(operation_type: &str) -> &'static str {
+    if INTERACTIVE_TYPES.contains(&operation_type) {
+        "interactive"
+    } else {
+        "batch"
+    }
+}
+
+impl AiQueue {
+    fn lock(&self) -> MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn snapshot(state: &QueueState) -> AiQueueSnapshot {
+        AiQueueSnapshot {
+            max_concurrent: max_concurrent(),
+            running: state.running.clone(),
+            waiting: state
+                .waiting
+                .iter()
+                .enumerate()
+                .map(|(index, (_, _, operation))| QueuedOperation {
+                    position: Some(index + 1),
+                    ..operation.clone()
+                })
+                .collect(),
+        }
+    }
+
+    fn publish(state: &QueueState) {
+        if let Some(app) = APP.get() {
+            warn_on_err(
+                "Failed to emit ai-queue-changed",
+                app.emit_all("ai-queue-changed", Self::snapshot(state)),
+            );
+        }
+    }
+
+    /// Wait for a slot. Fails if the operation is cancelled while queued.
+    fn acquire(&'static self, operation: QueuedOperation) -> Result<QueueSlot, String> {
+        let operation_id = operation.operation_id.clone();
+        let mut state = self.lock();
+        let rank = rank(&operation.priority);
+        let seq = state.next_seq;
+        state.next_seq += 1;
+        let at = state
+            .waiting
+            .iter()
+            .position(|(r, s, _)| (*r, *s) > (rank, seq))
+            .unwrap_or(state.waiting.len());
+        state.waiting.insert(at, (rank, seq, operation));
+        Self::publish(&state);
+
+        loop {
+            if state.cancelled.remove(&operation_id) {
+                state
+                    .waiting
+                    .retain(|(_, _, queued)| queued.operation_id != operation_id);
+                Self::publish(&state);
+                self.available.notify_all();
+                return Err("Cancelled before it started".to_string());
+            }
+            let next = state
+                .waiting
+                .first()
+                .is_some_and(|(_, _, queued)| queued.operation_id == operation_id);
+            if next && state.running.len() < max_concurrent() {
+                let (_, _, mut operation) = state.waiting.remove(0);
+                operation.position = None;
+                state.running.push(operation);
+                Self::publish(&state);
+                // The new head of the line may fit too.
+                self.available.notify_all();
+                return Ok(QueueSlot {
+                    queue: Some(self),
+                    operation_id,
+                });
+            }
+            state = self
+                .available
+                .wait_timeout(state, RECHECK_INTERVAL)
+                .map(|(state, _)| state)
+                .unwrap_or_else(|e| e.into_inner().0);
+        }
+    }
+
+    fn cancel(&self, operation_id: &str) -> Result<(), String> {
+        let mut state = self.lock();
+        let queued = state
+            .waiting
+            .iter()
+            .any(|(_, _, queued)| queued.operation_id == operation_id)
+            || state
+                .running
+                .iter()
+                .any(|running| running.operation_id == operation_id);
+        if !queued {
+            return Err(format!(
+                "AI operation {} is not queued or running",
+                operation_id
+            ));
+        }
+        state.cancelled.insert(operation_id.to_string());
+        self.available.notify_all();
+        Ok(())
+    }
+}
+
+impl QueueSlot {
+    /// Release the slot, reporting whether the operation was cancelled
+    /// while it ran.
+    pub(crate) fn finish(self) -> bool {
+        self.queue
+            .is_some_and(|queue| queue.lock().cancelled.remove(&self.operation_id))
+    }
+}
+
+impl Drop for QueueSlot {
+    fn drop(&mut self) {
+        if let Some(queue) = self.queue {
+            let mut state = queue.lock();
+            state
+                .running
+                .retain(|running| running.operation_id != self.operation_id);
+            AiQueue::publish(&state);
+            queue.available.notify_all();
+        }
+    }
+}
+
+/// Queue an operation for a provider slot. Requests made before the app
+/// is set up run unqueued.
+pub(crate) fn acquire(
+    operation_id: &str,
+    operation_type: &str,
+    workpad_id: Option<&str>,
+) -> Result<QueueSlot, String> {
+    let Some(queue) = managed() else {
+        return Ok(QueueSlot {
+            queue: None,
+            operation_id: operation_id.to_string(),
+        });
+    };
+    queue.acquire(QueuedOperation {
+        operation_id: operation_id.to_string(),
+        operation_type: operation_type.to_string(),
+        workpad_id: workpad_id.map(str::to_string),
+        priority: priority_of(operation_type).to_string(),
+        position: None,
+        enqueued_at: Utc::now().to_rfc3339(),
+    })
+}
+
+#[tauri::command]
+pub(crate) fn get_ai_queue(queue: tauri::State<'_, AiQueue>) -> Result<AiQueueSnapshot, String> {
+    Ok(AiQueue::snapshot(&queue.lock()))
+}
+
+/// Cancel a queued or running AI operation. A queued one never reaches the
+/// provider; a running one finishes its request, but the response is
+/// discarded and the operation recorded as cancelled.
+#[tauri::command]
+pub(crate) fn cancel_ai_operation(
+    queue: tauri::State<'_, AiQueue>,
+    operation_id: String,
+) -> Result<(), String> {
+    queue.cancel(&operation_id)
+}
@@ -0,0 +1,502 @@
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get};
+use axum::{Json, Router};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::audit::{as_actor, audited};
+use crate::http::agent;
+use crate::lifecycle::WorkpadStatus;
+use crate::paths::is_plain_id;
+use crate::{
+    get_settings, list_repositories, list_test_runs, list_workpads, read_repository, read_test_run,
+    read_workpad, write_settings, WorkpadFilter,
+};
+
+/// Events forwarded to subscribers, by their Tauri event name. Producers
+/// call `forward` next to emitting the event; Tauri listeners only see
+/// triggered events, not emitted ones.
+const FORWARDED_EVENTS: &[&str] = &[
+    "ci-status-changed",
+    "pull-request-changed",
+    "repo-dirty-changed",
+    "test-watch-run-started",
+    "test-watch-run-finished",
+    "ai-budget-warning",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub(crate) struct ApiSettings {
+    /// Serve the API on 127.0.0.1; read at startup.
+    pub(crate) enabled: bool,
+    pub(crate) port: u16,
+    /// Bearer token clients must send; generated on first start.
+    pub(crate) token: Option<String>,
+}
+
+impl Default for ApiSettings {
+    fn default() -> Self {
+        ApiSettings {
+            enabled: false,
+            port: 7878,
+            token: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct Subscription {
+    subscription_id: String,
+    callback_url: String,
+    /// Empty means every forwarded event.
+    #[serde(default)]
+    events: Vec<String>,
+    #[serde(default)]
+    created_at: String,
+}
+
+struct ApiState {
+    token: String,
+    subscriptions: Mutex<Vec<Subscription>>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct ApiServerStatus {
+    running: bool,
+    url: Option<String>,
+    subscriptions: usize,
+}
+
+static SERVER: OnceLock<(u16, Arc<ApiState>)> = OnceLock::new();
+
+fn json_result<T: Serialize>(result: Result<T, String>) -> Response {
+    match result {
+        Ok(value) => Json(value).into_response(),
+        Err(error) if error.contains("not found") => {
+            (StatusCode::NOT_FOUND, Json(json!({ "error": error }))).into_response()
+        }
+        Err(error) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": error })),
+        )
+            .into_response(),
+    }
+}
+
+/// Compare without short-circuiting, so response timing doesn't reveal how
+/// much of a guessed token was right.
+fn tokens_match(presented: &str, expected: &str) -> bool {
+    presented.len() == expected.len()
+        && presented
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn invalid_id(id: &str) -> Response {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(json!({ "error": format!("Invalid id: {}", id) })),
+    )
+        .into_response()
+}
+
+async fn require_token(
+    State(state): State<Arc<ApiState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let presented = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    if !presented.is_some_and(|presented| tokens_match(presented, &state.token)) {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({ "error": "Missing or invalid bearer token" })),
+        )
+            .into_response();
+    }
+    next.run(request).await
+}
+
+async fn health() -> Json<Value> {
+    Json(json!({ "status": "ok", "version": env!("CARGO_PKG_VERSION") }))
+}
+
+async fn repos() -> Response {
+    json_result(list_repositories(None))
+}
+
+async fn repo(Path(repo_id): Path<String>) -> Response {
+    if !is_plain_id(&repo_id) {
+        return invalid_id(&repo_id);
+    }
+    json_result(read_repository(repo_id))
+}
+
+#[derive(Debug, Deserialize)]
+struct WorkpadQuery {
+    repo_id: Option<String>,
+    status: Option<WorkpadStatus>,
+    tag: Option<String>,
+    q: Option<String>,
+    sort_by: Option<String>,
+}
+
+async fn workpads(Query(query): Query<WorkpadQuery>) -> Response {
+    let filter = WorkpadFilter {
+        status: query.status,
+        tag: query.tag,
+        query: query.q,
+        sort_by: query.sort_by,
+        ..WorkpadFilter::default()
+    };
+    json_result(list_workpads(query.repo_id, Some(filter), None))
+}
+
+async fn workpad(Path(workpad_id): Path<String>) -> Response {
+    if !is_plain_id(&workpad_id) {
+        return invalid_id(&workpad_id);
+    }
+    json_result(read_workpad(workpad_id))
+}
+
+#[derive(Debug, Deserialize)]
+struct TestRunQuery {
+    workpad_id: Option<String>,
+}
+
+async fn test_runs(Query(query): Query<TestRunQuery>) -> Response {
+    json_result(list_test_runs(query.workpad_id, None))
+}
+
+async fn test_run(Path(run_id): Path<String>) -> Response {
+    if !is_plain_id(&run_id) {
+        return invalid_id(&run_id);
+    }
+    json_result(read_test_run(run_id))
+}
+
+fn is_loopback_url(url: &str) -> bool {
+    ["http://127.0.0.1", "http://localhost", "http://[::1]"]
+        .iter()
+        .any(|prefix| {
+            url.strip_prefix(prefix)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with([':', '/']))
+        })
+}
+
+#[derive(Debug, Deserialize)]
+struct NewSubscription {
+    callback_url: String,
+    #[serde(default)]
+    events: Vec<String>,
+}
+
+async fn list_subscriptions(State(state): State<Arc<ApiState>>) -> Response {
+    json_result(
+        state
+            .subscriptions
+            .lock()
+            .map(|subscriptions| subscriptions.clone())
+            .map_err(|_| "Subscription list poisoned".to_string()),
+    )
+}
+
+async fn subscribe(
+    State(state): State<Arc<ApiState>>,
+    Json(request): Json<NewSubscription>,
+) -> Response {
+    if !is_loopback_url(&request.callback_url) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "callback_url must be an http://localhost address" })),
+        )
+            .into_response();
+    }
+    if let Some(unknown) = request
+        .events
+        .iter()
+        .find(|event| !FORWARDED_EVENTS.contains(&event.as_str()))
+    {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": format!("Unknown event {}", unknown), "events": FORWARDED_EVENTS })),
+        )
+            .into_response();
+    }
+
+    let subscription = Subscription {
+        subscription_id: format!("sub_{}", &Uuid::new_v4().simple().to_string()[..8]),
+        callback_url: request.callback_url,
+        events: request.events,
+        created_at: Utc::now().to_rfc3339(),
+    };
+    json_result(as_actor("api", || {
+        audited("subscribe", "subscription", None, None, || {
+            state
+                .subscriptions
+                .lock()
+                .map(|mut subscriptions| {
+                    subscriptions.push(subscription.clone());
+                    subscription
+                })
+                .map_err(|_| "Subscription list poisoned".to_string())
+        })
+    }))
+}
+
+async fn unsubscribe(
+    State(state): State<Arc<ApiState>>,
+    Path(subscription_id): Path<String>,
+) -> Response {
+    let removed = as_actor("api", || {
+        audited(
+            "unsubscribe",
+            "subscription",
+            Some(subscription_id.clone()),
+            None,
+            || {
+                let mut subscriptions = state
+                    .subscriptions
+                    .lock()
+                    .map_err(|_| "Subscription list poisoned".to_string())?;
+                let before = subscriptions.len();
+                subscriptions.retain(|s| s.subscription_id != subscription_id);
+                if before == subscriptions.len() {
+                    return Err(format!("Subscription not found: {}", subscription_id));
+                }
+                Ok(())
+            },
+        )
+    });
+    match removed {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(error) => json_result::<()>(Err(error)),
+    }
+}
+
+/// Send `payload` to the subscribers of `event` if the server is running.
+pub(crate) fn forward<T: Serialize>(event: &str, payload: &T) {
+    debug_assert!(
+        FORWARDED_EVENTS.contains(&event),
+        "{} is not forwarded",
+        event
+    );
+    if let Some((_, state)) = SERVER.get() {
+        dispatch(state, event, serde_json::to_value(payload).ok());
+    }
+}
+
+/// POST `payload` to every subscriber interested in `event`, off the caller's thread.
+fn dispatch(
+    state: &ApiState,
+    event: &str,
+    payload: Option<Value>,
+) -> Option<thread::JoinHandle<()>> {
+    let targets: Vec<String> = match state.subscriptions.lock() {
+        Ok(subscriptions) => subscriptions
+            .iter()
+            .filter(|s| s.events.is_empty() || s.events.iter().any(|e| e == event))
+            .map(|s| s.callback_url.clone())
+            .collect(),
+        Err(_) => return None,
+    };
+    if targets.is_empty() {
+        return None;
+    }
+
+    let body = json!({
+        "event": event,
+        "payload": payload,
+        "sent_at": Utc::now().to_rfc3339(),
+    });
+    Some(thread::spawn(move || {
+        let agent = agent();
+        for url in targets {
+            if let Err(e) = agent.post(&url).send_json(body.clone()) {
+                tracing::warn!("Failed to deliver {} to {}: {}", body["event"], url, e);
+            }
+        }
+    }))
+}
+
+fn router(state: Arc<ApiState>) -> Router {
+    let protected = Router::new()
+        .route("/repos", get(repos))
+        .route("/repos/:repo_id", get(repo))
+        .route("/workpads", get(workpads))
+        .route("/workpads/:workpad_id", get(workpad))
+        .route("/test-runs", get(test_runs))
+        .route("/test-runs/:run_id", get(test_run))
+        .route("/subscriptions", get(list_subscriptions).post(subscribe))
+        .route("/subscriptions/:subscription_id", delete(unsubscribe))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_token))
+        .with_state(state);
+    Router::new().route("/health", get(health)).merge(protected)
+}
+
+/// Start the API server if enabled in settings. Changes to the settings take
+/// effect on the next launch.
+pub(crate) fn start() {
+    let mut settings = match get_settings() {
+        Ok(settings) => settings,
+        Err(e) => {
+            tracing::warn!("API server not started: {}", e);
+            return;
+        }
+    };
+    let api = settings.integrations.api.clone();
+    if !api.enabled {
+        return;
+    }
+
+    let token = match api.token.filter(|token| !token.is_empty()) {
+        Some(token) => token,
+        None => {
+            let token = Uuid::new_v4().simple().to_string();
+            settings.integrations.api.token = Some(token.clone());
+            if let Err(e) = write_settings(&settings) {
+                tracing::warn!("API server not started: failed to store token: {}", e);
+                return;
+            }
+            token
+        }
+    };
+
+    let state = Arc::new(ApiState {
+        token,
+        subscriptions: Mutex::new(Vec::new()),
+    });
+    let port = api.port;
+    let router = router(state.clone());
+    tauri::async_runtime::spawn(async move {
+        let listener = match tokio::net::TcpListener::bind(("127.0.0.1", port)).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                tracing::error!("API server failed to bind 127.0.0.1:{}: {}", port, e);
+                return;
+            }
+        };
+        if SERVER.set((port, state)).is_err() {
+            return;
+        }
+        tracing::info!("API server listening on http://127.0.0.1:{}", port);
+        if let Err(e) = axum::serve(listener, router).await {
+            tracing::error!("API server stopped: {}", e);
+        }
+    });
+}
+
+#[tauri::command]
+pub(crate) fn get_api_server_status() -> Result<ApiServerStatus, String> {
+    Ok(match SERVER.get() {
+        Some((port, state)) => ApiServerStatus {
+            running: true,
+            url: Some(format!("http://127.0.0.1:{}", port)),
+            subscriptions: state.subscriptions.lock().map(|s| s.len()).unwrap_or(0),
+        },
+        None => ApiServerStatus {
+            running: false,
+            url: None,
+            subscriptions: 0,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    use super::*;
+
+    fn state_with(callback_url: String, events: Vec<String>) -> ApiState {
+        ApiState {
+            token: "token".to_string(),
+            subscriptions: Mutex::new(vec![Subscription {
+                subscription_id: "sub_test".to_string(),
+                callback_url,
+                events,
+                created_at: String::new(),
+            }]),
+        }
+    }
+
+    /// Accept one request, answer 204 and return its head and JSON body.
+    fn receive_post(listener: &TcpListener) -> (String, Value) {
+        let (mut stream, _) = listener.accept().expect("accept");
+        let mut request = Vec::new();
+        let mut buffer = [0u8; 1024];
+        let (head, total) = loop {
+            let read = stream.read(&mut buffer).expect("read");
+            request.extend_from_slice(&buffer[..read]);
+            let text = String::from_utf8_lossy(&request).to_string();
+            if let Some(end) = text.find("\r\n\r\n") {
+                let head = text[..end].to_string();
+                let length = head
+                    .lines()
+                    .find_map(|line| {
+                        let line = line.to_ascii_lowercase();
+                        let value = line.strip_prefix("content-length:")?;
+                        value.trim().parse::<usize>().ok()
+                    })
+                    .unwrap_or(0);
+                break (head, end + 4 + length);
+            }
+        };
+        while request.len() < total {
+            let read = stream.read(&mut buffer).expect("read");
+            request.extend_from_slice(&buffer[..read]);
+        }
+        stream
+            .write_all(b"HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n")
+            .expect("respond");
+        let body = serde_json::from_slice(&request[head.len() + 4..total]).expect("JSON body");
+        (head, body)
+    }
+
+    #[test]
+    fn subscribers_receive_forwarded_events() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let url = format!(
+            "http://127.0.0.1:{}/hook",
+            listener.local_addr().unwrap().port()
+        );
+        let state = state_with(url, vec!["ci-status-changed".to_string()]);
+
+        let delivery = dispatch(
+            &state,
+            "ci-status-changed",
+            Some(json!({ "state": "success" })),
+        )
+        .expect("a subscriber to deliver to");
+        let (head, body) = receive_post(&listener);
+        delivery.join().expect("delivery thread");
+
+        assert!(head.starts_with("POST /hook "), "{}", head);
+        assert_eq!(body["event"], "ci-status-changed");
+        assert_eq!(body["payload"]["state"], "success");
+    }
+
+    #[test]
+    fn events_outside_the_subscription_are_not_sent() {
+        let state = state_with(
+            "http://127.0.0.1:9/hook".to_string(),
+            vec!["ci-status-changed".to_string()],
+        );
+        assert!(dispatch(&state, "repo-dirty-changed", None).is_none());
+    }
+}
@@ -0,0 +1,178 @@
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+use chrono::Utc;
+
+use crate::audit::{as_actor, audited, summarize};
+use crate::commands::{
+    load_repository, load_workpad, read_json, save_repository, save_workpad, write_json,
+};
+use crate::lifecycle::{transition, WorkpadStatus};
+use crate::logging::warn_on_err;
+use crate::state_cache::state_cache;
+use crate::timestamps;
+use crate::{get_settings, get_state_dir, list_workpads, WorkpadFilter, WorkpadState};
+
+const POLICY_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+fn archive_dir() -> PathBuf {
+    get_state_dir().join("archive").join("workpads")
+}
+
+fn archived_path(workpad_id: &str) -> PathBuf {
+    archive_dir().join(format!("{}.json", workpad_id))
+}
+
+/// Move a workpad record out of `state/workpads` and drop it from its
+/// repository's workpad list.
+fn move_to_archive(mut workpad: WorkpadState) -> Result<WorkpadState, String> {
+    workpad.archived_from = Some(workpad.status);
+    transition(&mut workpad, WorkpadStatus::Archived)?;
+    workpad.updated_at = Utc::now().to_rfc3339();
+    write_json(&archived_path(&workpad.workpad_id), &workpad)?;
+
+    let live = get_state_dir()
+        .join("workpads")
+        .join(format!("{}.json", workpad.workpad_id));
+    fs::remove_file(&live).map_err(|e| format!("Failed to remove {}: {}", live.display(), e))?;
+    state_cache().invalidate_path(&live);
+
+    if let Ok(mut repo) = load_repository(&workpad.repo_id) {
+        let before = repo.workpads.len();
+        repo.workpads.retain(|id| *id != workpad.workpad_id);
+        if repo.workpads.len() != before {
+            save_repository(repo)?;
+        }
+    }
+    Ok(workpad)
+}
+
+#[tauri::command]
+pub(crate) fn archive_workpad(workpad_id: String) -> Result<WorkpadState, String> {
+    let before = load_workpad(&workpad_id)
+        .ok()
+        .and_then(|workpad| summarize(&workpad));
+    audited(
+        "archive_workpad",
+        "workpad",
+        Some(workpad_id.clone()),
+        before,
+        move || move_to_archive(load_workpad(&workpad_id)?),
+    )
+}
+
+/// Bring an archived workpad back into the active list.
+#[tauri::command]
+pub(crate) fn restore_archived_workpad(workpad_id: String) -> Result<WorkpadState, String> {
+    audited(
+        "restore_archived_workpad",
+        "workpad",
+        Some(workpad_id.clone()),
+        None,
+        move || {
+            let path = archived_path(&workpad_id);
+            let mut workpad: WorkpadState = read_json(&path)?
+                .ok_or_else(|| format!("Archived workpad not found: {}", workpad_id))?;
+            // Archives written before `archived_from` existed fall back to
+            // promoted or active.
+            let restored =
+                workpad
+                    .archived_from
+                    .take()
+                    .unwrap_or(if workpad.promoted_at.is_some() {
+                        WorkpadStatus::Promoted
+                    } else {
+                        WorkpadStatus::Active
+                    });
+            transition(&mut workpad, restored)?;
+
+            let mut repo = load_repository(&workpad.repo_id)?;
+            let workpad = save_workpad(workpad)?;
+            if !repo.workpads.contains(&workpad.workpad_id) {
+                repo.workpads.push(workpad.workpad_id.clone());
+                save_repository(repo)?;
+            }
+            fs::remove_file(&path)
+                .map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+            Ok(workpad)
+        },
+    )
+}
+
+#[tauri::command]
+pub(crate) fn list_archived_workpads(
+    repo_id: Option<String>,
+    filter: Option<WorkpadFilter>,
+) -> Result<Vec<WorkpadState>, String> {
+    let filter = filter.unwrap_or_default();
+    let dir = archive_dir();
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut workpads = Vec::new();
+    for entry in fs::read_dir(&dir).map_err(|e| format!("Failed to read archive: {}", e))? {
+        let path = entry
+            .map_err(|e| format!("Failed to read archive: {}", e))?
+            .path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        let workpad: WorkpadState = match read_json(&path) {
+            Ok(Some(workpad)) => workpad,
+            Ok(None) => continue,
+            Err(e) => {
+                tracing::warn!("Skipping {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        if repo_id.as_ref().is_some_and(|id| *id != workpad.repo_id) {
+            continue;
+        }
+        if filter.matches(&workpad) {
+            workpads.push(workpad);
+        }
+    }
+    filter.sort(&mut workpads);
+    Ok(workpads)
+}
+
+/// Archive promoted workpads older than the configured age. Returns the
+/// archived workpad IDs.
+pub(crate) fn apply_archive_policy() -> Result<Vec<String>, String> {
+    let Some(days) = get_settings()?.workpads.archive_promoted_after_days else {
+        return Ok(Vec::new());
+    };
+    let cutoff = Utc::now() - chrono::Duration::days(i64::from(days));
+
+    let mut archived = Vec::new();
+    for workpad in list_workpads(None, None, None)? {
+        if workpad.status != WorkpadStatus::Promoted {
+            continue;
+        }
+        let promoted_at = workpad.promoted_at.as_deref().and_then(timestamps::parse);
+        if promoted_at.is_some_and(|at| at < cutoff) {
+            let workpad_id = workpad.workpad_id.clone();
+            match move_to_archive(workpad) {
+                Ok(_) => archived.push(workpad_id),
+                Err(e) => tracing::warn!("Failed to archive {}: {}", workpad_id, e),
+            }
+        }
+    }
+    if !archived.is_empty() {
+        tracing::info!("Archived {} promoted workpads", archived.len());
+    }
+    Ok(archived)
+}
+
+/// Background loop applying the archive policy a few times a day.
+pub(crate) fn start_archive_policy() {
+    thread::spawn(|| {
+        as_actor("scheduler", || loop {
+            warn_on_err("Archive policy failed", apply_archive_policy());
+            thread::sleep(POLICY_INTERVAL);
+        })
+    });
+}
@@ -0,0 +1,260 @@
+use std::cell::Cell;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+use crate::{get_state_dir, metrics, timestamps};
+
+const DEFAULT_PAGE_SIZE: usize = 50;
+
+/// Keys whose values are never written to the audit log.
+const SECRET_MARKERS: &[&str] = &["token", "key", "secret", "password"];
+
+thread_local! {
+    /// Who commands audited on this thread are attributed to.
+    static ACTOR: Cell<&'static str> = const { Cell::new("gui") };
+}
+
+/// One mutating command, appended to `state/audit/<date>.jsonl`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct AuditEntry {
+    entry_id: String,
+    timestamp: String,
+    /// "gui" for commands invoked from this app, "api" for the local HTTP
+    /// API, "scheduler" for background jobs and "cli" for evogitctl.
+    actor: String,
+    action: String,
+    entity_type: String,
+    entity_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    repo_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    workpad_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    before: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    after: Option<Value>,
+    success: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub(crate) struct AuditFilter {
+    action: Option<String>,
+    entity_type: Option<String>,
+    entity_id: Option<String>,
+    repo_id: Option<String>,
+    workpad_id: Option<String>,
+    actor: Option<String>,
+    success: Option<bool>,
+    /// RFC 3339 bounds, inclusive.
+    since: Option<String>,
+    until: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct AuditPage {
+    entries: Vec<AuditEntry>,
+    page: usize,
+    page_size: usize,
+    total: usize,
+}
+
+fn audit_dir() -> PathBuf {
+    get_state_dir().join("audit")
+}
+
+/// Reduce a record to its scalar fields (and the length of any lists) so
+/// entries stay small, dropping anything that looks like a credential.
+pub(crate) fn summarize<T: Serialize>(value: &T) -> Option<Value> {
+    fn is_secret(key: &str) -> bool {
+        let key = key.to_lowercase();
+        SECRET_MARKERS.iter().any(|marker| key.contains(marker))
+    }
+
+    match serde_json::to_value(value).ok()? {
+        Value::Null => None,
+        Value::Object(map) => {
+            let mut summary = Map::new();
+            for (key, value) in map {
+                if is_secret(&key) {
+                    continue;
+                }
+                match value {
+                    Value::Array(items) => {
+                        summary.insert(format!("{}_count", key), Value::from(items.len()));
+                    }
+                    Value::Object(_) => {}
+                    scalar => {
+                        summary.insert(key, scalar);
+                    }
+                }
+            }
+            Some(Value::Object(summary))
+        }
+        Value::Array(items) => Some(Value::from(items.len())),
+        scalar => Some(scalar),
+    }
+}
+
+fn field(summary: &Option<Value>, key: &str) -> Option<String> {
+    summary
+        .as_ref()
+        .and_then(|value| value.get(key))
+        .and_then(|value| value.as_str())
+        .map(str::to_string)
+}
+
+fn append(entry: &AuditEntry) -> Result<(), String> {
+    let dir = audit_dir();
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let path = dir.join(format!("{}.jsonl", &entry.timestamp[..10]));
+    let line = serde_json::to_string(entry)
+        .map_err(|e| format!("Failed to serialize audit entry: {}", e))?;
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| writeln!(file, "{}", line))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Run `run` with the commands it audits attributed to `actor` rather than
+/// the GUI.
+pub(crate) fn as_actor<T>(actor: &'static str, run: impl FnOnce() -> T) -> T {
+    let previous = ACTOR.with(|current| current.replace(actor));
+    let result = run();
+    ACTOR.with(|current| current.set(previous));
+    result
+}
+
+/// Run a mutating command and record what it did. `before` is a summary of
+/// the entity prior to the change; the result (or error) becomes `after`.
+pub(crate) fn audited<T: Serialize>(
+    action: &str,
+    entity_type: &str,
+    entity_id: Option<String>,
+    before: Option<Value>,
+    run: impl FnOnce() -> Result<T, String>,
+) -> Result<T, String> {
+    let started = Instant::now();
+    let result = run();
+    metrics::record_command(action, started.elapsed(), result.is_ok());
+    let after = result.as_ref().ok().and_then(summarize);
+    let id_key = match entity_type {
+        "repository" => "repo_id".to_string(),
+        "ai_operation" => "operation_id".to_string(),
+        "chat_session" => "session_id".to_string(),
+        "prompt_template" => "template_id".to_string(),
+        other => format!("{}_id", other),
+    };
+
+    let entry = AuditEntry {
+        entry_id: format!("audit-{}", Uuid::new_v4().simple()),
+        timestamp: Utc::now().to_rfc3339(),
+        actor: ACTOR.with(Cell::get).to_string(),
+        action: action.to_string(),
+        entity_type: entity_type.to_string(),
+        entity_id: entity_id
+            .or_else(|| field(&after, &id_key))
+            .or_else(|| field(&before, &id_key)),
+        repo_id: field(&after, "repo_id").or_else(|| field(&before, "repo_id")),
+        workpad_id: field(&after, "workpad_id").or_else(|| field(&before, "workpad_id")),
+        before,
+        after,
+        success: result.is_ok(),
+        error: result.as_ref().err().cloned(),
+    };
+    if let Err(error) = append(&entry) {
+        tracing::error!("audit log write failed: {}", error);
+    }
+    result
+}
+
+/// Whether `timestamp` is on the right side of an optional bound: at or
+/// after it when `newer`, at or before it otherwise. A record without a
+/// readable timestamp falls outside any bound.
+pub(crate) fn in_range(timestamp: &str, bound: &Option<String>, newer: bool) -> bool {
+    let Some(bound) = bound.as_deref().and_then(timestamps::parse) else {
+        return true;
+    };
+    match timestamps::parse(timestamp) {
+        Some(at) if newer => at >= bound,
+        Some(at) => at <= bound,
+        None => false,
+    }
+}
+
+fn matches(entry: &AuditEntry, filter: &AuditFilter) -> bool {
+    let eq = |wanted: &Option<String>, actual: Option<&String>| {
+        wanted.is_none() || wanted.as_ref() == actual
+    };
+    eq(&filter.action, Some(&entry.action))
+        && eq(&filter.entity_type, Some(&entry.entity_type))
+        && eq(&filter.entity_id, entry.entity_id.as_ref())
+        && eq(&filter.repo_id, entry.repo_id.as_ref())
+        && eq(&filter.workpad_id, entry.workpad_id.as_ref())
+        && eq(&filter.actor, Some(&entry.actor))
+        && (filter.success.is_none() || filter.success == Some(entry.success))
+        && in_range(&entry.timestamp, &filter.since, true)
+        && in_range(&entry.timestamp, &filter.until, false)
+}
+
+/// Newest-first page of audit entries matching `filters`.
+#[tauri::command]
+pub(crate) fn query_audit_log(
+    filters: Option<AuditFilter>,
+    page: Option<usize>,
+    page_size: Option<usize>,
+) -> Result<AuditPage, String> {
+    let filter = filters.unwrap_or_default();
+    let page = page.unwrap_or(0);
+    let page_size = page_size.unwrap_or(DEFAULT_PAGE_SIZE).max(1);
+
+    let dir = audit_dir();
+    let mut days: Vec<PathBuf> = if dir.exists() {
+        fs::read_dir(&dir)
+            .map_err(|e| format!("Failed to read audit log: {}", e))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("jsonl"))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    days.sort();
+    days.reverse();
+
+    let mut matched = Vec::new();
+    for day in days {
+        let contents = fs::read_to_string(&day)
+            .map_err(|e| format!("Failed to read {}: {}", day.display(), e))?;
+        let mut entries: Vec<AuditEntry> = contents
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .filter(|entry| matches(entry, &filter))
+            .collect();
+        entries.reverse();
+        matched.extend(entries);
+    }
+
+    let total = matched.len();
+    let entries = matched
+        .into_iter()
+        .skip(page * page_size)
+        .take(page_size)
+        .collect();
+    Ok(AuditPage {
+        entries,
+        page,
+        page_size,
+        total,
+    })
+}
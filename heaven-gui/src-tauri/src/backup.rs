@@ -0,0 +1,269 @@
+use std::fs::{self, File};
+use std::path::{Component, Path, PathBuf};
+
+use chrono::Utc;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+
+use crate::audit::audited;
+use crate::migrations::{self, backup_state, parse_version, CURRENT_SCHEMA_VERSION};
+use crate::profiles::active_home;
+use crate::repair::validate_state;
+use crate::{get_repos_dir, get_state_dir};
+
+const MANIFEST_NAME: &str = "manifest.json";
+const BACKUP_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct BackupManifest {
+    format_version: u32,
+    schema_version: String,
+    app_version: String,
+    created_at: String,
+    includes_repos: bool,
+    file_count: usize,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct BackupResult {
+    archive_path: String,
+    manifest: BackupManifest,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct ImportSummary {
+    mode: String,
+    manifest: BackupManifest,
+    files_restored: usize,
+    files_skipped: usize,
+    /// Where the previous state was moved in replace mode.
+    previous_state_backup: Option<String>,
+}
+
+fn count_files(dir: &Path) -> usize {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|entry| {
+                    let path = entry.path();
+                    if path.is_dir() {
+                        count_files(&path)
+                    } else {
+                        1
+                    }
+                })
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+/// Only `state/` and `data/repos/` are restored, and never outside them.
+fn archive_target(entry_path: &Path) -> Option<PathBuf> {
+    let safe = entry_path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)));
+    let allowed = entry_path.starts_with("state") || entry_path.starts_with("data/repos");
+    if safe && allowed {
+        Some(active_home().join(entry_path))
+    } else {
+        None
+    }
+}
+
+fn read_manifest(archive: &Path) -> Result<BackupManifest, String> {
+    let file =
+        File::open(archive).map_err(|e| format!("Failed to open {}: {}", archive.display(), e))?;
+    let mut reader = tar::Archive::new(GzDecoder::new(file));
+    for entry in reader
+        .entries()
+        .map_err(|e| format!("Failed to read backup: {}", e))?
+    {
+        let entry = entry.map_err(|e| format!("Failed to read backup: {}", e))?;
+        let is_manifest = entry
+            .path()
+            .map(|path| path.as_ref() == Path::new(MANIFEST_NAME))
+            .unwrap_or(false);
+        if is_manifest {
+            return serde_json::from_reader(entry)
+                .map_err(|e| format!("Invalid backup manifest: {}", e));
+        }
+    }
+    Err("Backup archive has no manifest".to_string())
+}
+
+/// Write a gzipped tar of `~/.sologit/state` (and optionally the managed
+/// repositories) to `destination`. A directory destination gets a
+/// timestamped file name.
+#[tauri::command]
+pub(crate) fn export_state_backup(
+    destination: String,
+    include_repos: Option<bool>,
+) -> Result<BackupResult, String> {
+    let include_repos = include_repos.unwrap_or(false);
+    let mut archive_path = PathBuf::from(destination.trim());
+    if archive_path.is_dir() {
+        archive_path = archive_path.join(format!(
+            "sologit-backup-{}.tar.gz",
+            Utc::now().format("%Y%m%dT%H%M%S")
+        ));
+    }
+
+    let state_dir = get_state_dir();
+    let repos_dir = get_repos_dir();
+    let mut file_count = count_files(&state_dir);
+    if include_repos {
+        file_count += count_files(&repos_dir);
+    }
+    let manifest = BackupManifest {
+        format_version: BACKUP_FORMAT_VERSION,
+        schema_version: CURRENT_SCHEMA_VERSION.to_string(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: Utc::now().to_rfc3339(),
+        includes_repos: include_repos,
+        file_count,
+    };
+
+    let file = File::create(&archive_path)
+        .map_err(|e| format!("Failed to create {}: {}", archive_path.display(), e))?;
+    let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+
+    let manifest_bytes = serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(manifest_bytes.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(Utc::now().timestamp().max(0) as u64);
+    header.set_cksum();
+    builder
+        .append_data(&mut header, MANIFEST_NAME, manifest_bytes.as_slice())
+        .map_err(|e| format!("Failed to write manifest: {}", e))?;
+
+    if state_dir.exists() {
+        builder
+            .append_dir_all("state", &state_dir)
+            .map_err(|e| format!("Failed to archive state: {}", e))?;
+    }
+    if include_repos && repos_dir.exists() {
+        builder
+            .append_dir_all("data/repos", &repos_dir)
+            .map_err(|e| format!("Failed to archive repositories: {}", e))?;
+    }
+    builder
+        .into_inner()
+        .and_then(|encoder| encoder.finish())
+        .map_err(|e| format!("Failed to finish backup: {}", e))?;
+
+    Ok(BackupResult {
+        archive_path: archive_path.display().to_string(),
+        manifest,
+    })
+}
+
+/// Restore a backup made by `export_state_backup`. "merge" only adds files
+/// that don't exist locally; "replace" moves the current state aside first.
+#[tauri::command]
+pub(crate) fn import_state_backup(archive: String, mode: String) -> Result<ImportSummary, String> {
+    audited("import_state_backup", "state", None, None, move || {
+        let archive = PathBuf::from(archive.trim());
+        let replace = match mode.as_str() {
+            "merge" => false,
+            "replace" => true,
+            other => {
+                return Err(format!(
+                    "Unknown import mode {}; expected merge or replace",
+                    other
+                ))
+            }
+        };
+
+        let manifest = read_manifest(&archive)?;
+        if manifest.format_version > BACKUP_FORMAT_VERSION {
+            return Err(format!(
+                "Backup format {} is newer than this build supports",
+                manifest.format_version
+            ));
+        }
+        if parse_version(&manifest.schema_version)?.0 > parse_version(CURRENT_SCHEMA_VERSION)?.0 {
+            return Err(format!(
+                "Backup was written by schema version {}, which is newer than {}",
+                manifest.schema_version, CURRENT_SCHEMA_VERSION
+            ));
+        }
+
+        let mut previous_state_backup = None;
+        if replace {
+            let state_dir = get_state_dir();
+            if state_dir.exists() {
+                let backup = backup_state(&state_dir, "pre-import")?;
+                fs::remove_dir_all(&state_dir)
+                    .map_err(|e| format!("Failed to clear state: {}", e))?;
+                previous_state_backup = Some(backup.display().to_string());
+            }
+            let repos_dir = get_repos_dir();
+            if manifest.includes_repos && repos_dir.exists() {
+                let aside = repos_dir.with_file_name(format!(
+                    "repos-pre-import-{}",
+                    Utc::now().format("%Y%m%dT%H%M%S")
+                ));
+                fs::rename(&repos_dir, &aside)
+                    .map_err(|e| format!("Failed to move repositories aside: {}", e))?;
+            }
+        }
+
+        let file = File::open(&archive)
+            .map_err(|e| format!("Failed to open {}: {}", archive.display(), e))?;
+        let mut reader = tar::Archive::new(GzDecoder::new(file));
+        let mut files_restored = 0;
+        let mut files_skipped = 0;
+        for entry in reader
+            .entries()
+            .map_err(|e| format!("Failed to read backup: {}", e))?
+        {
+            let mut entry = entry.map_err(|e| format!("Failed to read backup: {}", e))?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let entry_path = entry
+                .path()
+                .map_err(|e| format!("Invalid path in backup: {}", e))?
+                .into_owned();
+            if entry_path == Path::new(MANIFEST_NAME) {
+                continue;
+            }
+            let target = match archive_target(&entry_path) {
+                Some(target) => target,
+                None => {
+                    tracing::warn!("skipping unexpected backup entry {}", entry_path.display());
+                    files_skipped += 1;
+                    continue;
+                }
+            };
+            if target.exists() && !replace {
+                files_skipped += 1;
+                continue;
+            }
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+            }
+            entry
+                .unpack(&target)
+                .map_err(|e| format!("Failed to restore {}: {}", entry_path.display(), e))?;
+            files_restored += 1;
+        }
+
+        // Older backups are brought up to date and checked like any state.
+        migrations::migrate_state()?;
+        validate_state()?;
+
+        Ok(ImportSummary {
+            mode,
+            manifest,
+            files_restored,
+            files_skipped,
+            previous_state_backup,
+        })
+    })
+}
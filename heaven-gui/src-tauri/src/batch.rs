@@ -0,0 +1,93 @@
+use std::thread;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+const MAX_BATCH_SIZE: usize = 50;
+
+#[derive(Debug, Deserialize, Clone)]
+pub(crate) struct BatchRequest {
+    command: String,
+    /// Arguments as they would be passed to `invoke`, camelCase keys included.
+    #[serde(default)]
+    args: Value,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct BatchResult {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Argument `name` of `request`, deserialized the way Tauri does for
+/// `invoke`: a missing key reads as null, so optional arguments can be left out.
+fn arg<T: DeserializeOwned>(request: &BatchRequest, name: &str) -> Result<T, String> {
+    let value = request.args.get(name).cloned().unwrap_or(Value::Null);
+    serde_json::from_value(value)
+        .map_err(|e| format!("Invalid argument {} for {}: {}", name, request.command, e))
+}
+
+fn to_value<T: Serialize>(result: Result<T, String>) -> Result<Value, String> {
+    serde_json::to_value(result?).map_err(|e| format!("Failed to serialize result: {}", e))
+}
+
+// Read-only commands that can appear in a batch, generated by build.rs from
+// `BATCH_COMMANDS` and the commands' own signatures. Commands needing
+// managed state or a window aren't included.
+include!(concat!(env!("OUT_DIR"), "/batch_dispatch.rs"));
+
+fn run(request: &BatchRequest) -> BatchResult {
+    match dispatch(request) {
+        Ok(data) => BatchResult {
+            ok: true,
+            data: Some(data),
+            error: None,
+        },
+        Err(error) => BatchResult {
+            ok: false,
+            data: None,
+            error: Some(error),
+        },
+    }
+}
+
+/// Run several read commands in one IPC round trip. Results come back in
+/// request order; one failing entry doesn't affect the others.
+#[tauri::command]
+pub(crate) fn batch(
+    requests: Vec<BatchRequest>,
+    concurrent: Option<bool>,
+) -> Result<Vec<BatchResult>, String> {
+    if requests.len() > MAX_BATCH_SIZE {
+        return Err(format!(
+            "Batch of {} requests exceeds the limit of {}",
+            requests.len(),
+            MAX_BATCH_SIZE
+        ));
+    }
+    if !concurrent.unwrap_or(false) {
+        return Ok(requests.iter().map(run).collect());
+    }
+
+    Ok(thread::scope(|scope| {
+        let handles: Vec<_> = requests
+            .iter()
+            .map(|request| scope.spawn(move || run(request)))
+            .collect();
+        handles
+            .into_iter()
+            .zip(&requests)
+            .map(|(handle, request)| {
+                handle.join().unwrap_or_else(|_| BatchResult {
+                    ok: false,
+                    data: None,
+                    error: Some(format!("{} panicked", request.command)),
+                })
+            })
+            .collect()
+    }))
+}
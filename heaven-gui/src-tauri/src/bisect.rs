@@ -0,0 +1,267 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::commands::{read_json, write_json};
+use crate::git::{format_git_time, open_repository, resolve_commit, run_git, short_sha};
+use crate::logging::warn_on_err;
+use crate::statuses::{BisectStatus, TestRunStatus};
+use crate::testing::run_repo_target;
+use crate::worktrees;
+use crate::{get_state_dir, list_commits, CommitNode};
+
+/// Bisects stop after this many test runs even if git hasn't converged.
+const MAX_STEPS: usize = 64;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct BisectStep {
+    step: usize,
+    sha: String,
+    /// "good", "bad" or "skip"
+    verdict: String,
+    run_id: Option<String>,
+    error: Option<String>,
+    /// Revisions git still has to test after this step, when it reports it.
+    remaining: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct BisectProgressEvent {
+    bisect_id: String,
+    #[serde(flatten)]
+    step: BisectStep,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct BisectReport {
+    bisect_id: String,
+    repo_id: String,
+    good_sha: String,
+    bad_sha: String,
+    test_target: String,
+    status: BisectStatus,
+    steps: Vec<BisectStep>,
+    culprit: Option<CommitNode>,
+    error: Option<String>,
+    started_at: String,
+    finished_at: Option<String>,
+}
+
+/// Repositories with a bisect in progress; one at a time keeps test runs
+/// from competing.
+fn active() -> &'static Mutex<HashSet<String>> {
+    static ACTIVE: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+    ACTIVE.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+fn report_path(bisect_id: &str) -> PathBuf {
+    get_state_dir()
+        .join("bisects")
+        .join(format!("{}.json", bisect_id))
+}
+
+/// Parse "Bisecting: 3 revisions left to test after this (roughly 2 steps)".
+fn remaining(output: &str) -> Option<u32> {
+    output
+        .lines()
+        .find_map(|line| line.strip_prefix("Bisecting: "))
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|count| count.parse().ok())
+}
+
+/// "<sha> is the first bad commit"
+fn first_bad(output: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        line.strip_suffix(" is the first bad commit")
+            .map(|sha| sha.trim().to_string())
+    })
+}
+
+/// The cached graph node for `sha`, or one built from the repository when
+/// the cache doesn't have it.
+fn commit_node(repo_id: &str, sha: &str) -> Result<CommitNode, String> {
+    if let Some(node) = list_commits(repo_id.to_string(), Some(i32::MAX))?
+        .into_iter()
+        .find(|node| node.sha == sha)
+    {
+        return Ok(node);
+    }
+    let repo = open_repository(repo_id)?;
+    let commit = resolve_commit(&repo, sha)?;
+    Ok(CommitNode {
+        sha: commit.id().to_string(),
+        short_sha: short_sha(commit.id()),
+        message: commit.summary().unwrap_or_default().to_string(),
+        author: commit.author().name().unwrap_or_default().to_string(),
+        timestamp: format_git_time(commit.time()),
+        parent_sha: commit.parent_id(0).ok().map(|id| id.to_string()),
+        workpad_id: None,
+        test_status: None,
+        ci_status: None,
+        is_trunk: false,
+        promotion_id: None,
+        test_run_id: None,
+        cost_usd: None,
+    })
+}
+
+fn bisect(
+    window: &tauri::Window,
+    report: &mut BisectReport,
+    repo_dir: &Path,
+) -> Result<Option<String>, String> {
+    let mut output = run_git(
+        repo_dir,
+        &["bisect", "start", &report.bad_sha, &report.good_sha, "--"],
+    )?;
+
+    while report.steps.len() < MAX_STEPS {
+        if let Some(culprit) = first_bad(&output) {
+            return Ok(Some(culprit));
+        }
+        let sha = run_git(repo_dir, &["rev-parse", "HEAD"])?
+            .trim()
+            .to_string();
+        let (verdict, run_id, error) =
+            match run_repo_target(&report.repo_id, repo_dir, &report.test_target) {
+                Ok(run) if run.status == TestRunStatus::Passed => ("good", Some(run.run_id), None),
+                Ok(run) => ("bad", Some(run.run_id), None),
+                // The target couldn't run at all here (e.g. doesn't build yet).
+                Err(error) => ("skip", None, Some(error)),
+            };
+        output = run_git(repo_dir, &["bisect", verdict])?;
+
+        let step = BisectStep {
+            step: report.steps.len() + 1,
+            sha,
+            verdict: verdict.to_string(),
+            run_id,
+            error,
+            remaining: remaining(&output),
+        };
+        report.steps.push(step.clone());
+        write_json(&report_path(&report.bisect_id), report)?;
+        warn_on_err(
+            "Failed to emit bisect-progress",
+            window.emit(
+                "bisect-progress",
+                BisectProgressEvent {
+                    bisect_id: report.bisect_id.clone(),
+                    step,
+                },
+            ),
+        );
+
+        if output.contains("only skipped commits left to test") {
+            return Ok(None);
+        }
+    }
+    Ok(first_bad(&output))
+}
+
+fn run_bisect(window: tauri::Window, mut report: BisectReport) {
+    // A worktree of its own, so the shared checkout (and whatever the CLI
+    // is doing in it) is never moved.
+    let checkout = worktrees::add_scratch(&report.repo_id, &report.bisect_id, &report.bad_sha);
+    let outcome = checkout.as_ref().map_err(String::clone).and_then(|dir| {
+        let outcome = bisect(&window, &mut report, dir);
+        warn_on_err("Failed to reset bisect", run_git(dir, &["bisect", "reset"]));
+        outcome
+    });
+    if let Ok(dir) = &checkout {
+        warn_on_err(
+            "Failed to remove bisect worktree",
+            worktrees::remove_scratch(&report.repo_id, dir),
+        );
+    }
+
+    match outcome.and_then(|culprit| {
+        culprit
+            .map(|sha| commit_node(&report.repo_id, &sha))
+            .transpose()
+    }) {
+        Ok(Some(node)) => {
+            report.status = BisectStatus::Found;
+            report.culprit = Some(node);
+        }
+        Ok(None) => {
+            report.status = BisectStatus::Failed;
+            report.error = Some("Bisect could not isolate a single commit".to_string());
+        }
+        Err(error) => {
+            report.status = BisectStatus::Failed;
+            report.error = Some(error);
+        }
+    }
+    report.finished_at = Some(Utc::now().to_rfc3339());
+    warn_on_err(
+        "Failed to save bisect report",
+        write_json(&report_path(&report.bisect_id), &report),
+    );
+    if let Ok(mut active) = active().lock() {
+        active.remove(&report.repo_id);
+    }
+    warn_on_err(
+        "Failed to emit bisect-finished",
+        window.emit("bisect-finished", &report),
+    );
+}
+
+/// Find the first commit between `good_sha` and `bad_sha` that fails
+/// `test_target`. Runs in the background, emitting `bisect-progress` per
+/// step and `bisect-finished` with the final report.
+#[tauri::command]
+pub(crate) fn start_bisect(
+    window: tauri::Window,
+    repo_id: String,
+    good_sha: String,
+    bad_sha: String,
+    test_target: String,
+) -> Result<BisectReport, String> {
+    {
+        let repo = open_repository(&repo_id)?;
+        resolve_commit(&repo, &good_sha)?;
+        resolve_commit(&repo, &bad_sha)?;
+    }
+    let report = BisectReport {
+        bisect_id: format!("bisect-{}", Uuid::new_v4().simple()),
+        repo_id,
+        good_sha,
+        bad_sha,
+        test_target,
+        status: BisectStatus::Running,
+        steps: Vec::new(),
+        culprit: None,
+        error: None,
+        started_at: Utc::now().to_rfc3339(),
+        finished_at: None,
+    };
+    if !active()
+        .lock()
+        .map_err(|_| "Bisect registry is poisoned".to_string())?
+        .insert(report.repo_id.clone())
+    {
+        return Err(format!("A bisect is already running in {}", report.repo_id));
+    }
+    if let Err(error) = write_json(&report_path(&report.bisect_id), &report) {
+        if let Ok(mut active) = active().lock() {
+            active.remove(&report.repo_id);
+        }
+        return Err(error);
+    }
+    {
+        let report = report.clone();
+        thread::spawn(move || run_bisect(window, report));
+    }
+    Ok(report)
+}
+
+#[tauri::command]
+pub(crate) fn get_bisect(bisect_id: String) -> Result<BisectReport, String> {
+    read_json(&report_path(&bisect_id))?.ok_or_else(|| format!("Bisect not found: {}", bisect_id))
+}
@@ -0,0 +1,176 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::git::{format_git_time, open_repository, resolve_commit, short_sha};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct BlameLine {
+    line_number: usize,
+    content: String,
+    sha: String,
+    short_sha: String,
+    author: String,
+    author_email: String,
+    timestamp: String,
+    summary: String,
+}
+
+/// Files whose blame is kept; the least recently used is dropped first.
+const BLAME_CACHE_CAPACITY: usize = 64;
+
+type BlameKey = (String, String, String);
+
+/// Blame results keyed by (repo_id, file_path, resolved commit sha). Entries
+/// never go stale because the key pins an immutable commit.
+#[derive(Default)]
+pub(crate) struct BlameCache {
+    entries: Mutex<BlameEntries>,
+}
+
+#[derive(Default)]
+struct BlameEntries {
+    lines: HashMap<BlameKey, Vec<BlameLine>>,
+    /// Keys from least to most recently used.
+    order: VecDeque<BlameKey>,
+}
+
+impl BlameEntries {
+    fn touch(&mut self, key: &BlameKey) {
+        if let Some(position) = self.order.iter().position(|k| k == key) {
+            if let Some(key) = self.order.remove(position) {
+                self.order.push_back(key);
+            }
+        }
+    }
+
+    fn get(&mut self, key: &BlameKey) -> Option<Vec<BlameLine>> {
+        let lines = self.lines.get(key)?.clone();
+        self.touch(key);
+        Some(lines)
+    }
+
+    fn insert(&mut self, key: BlameKey, lines: Vec<BlameLine>) {
+        if self.lines.insert(key.clone(), lines).is_some() {
+            self.touch(&key);
+            return;
+        }
+        self.order.push_back(key);
+        while self.order.len() > BLAME_CACHE_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.lines.remove(&oldest);
+            }
+        }
+    }
+}
+
+struct CommitInfo {
+    author: String,
+    author_email: String,
+    timestamp: String,
+    summary: String,
+}
+
+fn compute_blame(
+    repo: &git2::Repository,
+    commit: &git2::Commit,
+    file_path: &str,
+) -> Result<Vec<BlameLine>, String> {
+    let path = Path::new(file_path);
+    let blob = commit
+        .tree()
+        .and_then(|tree| tree.get_path(path))
+        .and_then(|entry| entry.to_object(repo))
+        .and_then(|object| object.peel_to_blob())
+        .map_err(|e| {
+            format!(
+                "File not found at {}: {}",
+                short_sha(commit.id()),
+                e.message()
+            )
+        })?;
+    if blob.is_binary() {
+        return Err(format!("Cannot blame binary file: {}", file_path));
+    }
+
+    let mut options = git2::BlameOptions::new();
+    options.newest_commit(commit.id());
+    let blame = repo
+        .blame_file(path, Some(&mut options))
+        .map_err(|e| format!("Failed to blame {}: {}", file_path, e.message()))?;
+
+    let mut commits: HashMap<git2::Oid, CommitInfo> = HashMap::new();
+    let text = String::from_utf8_lossy(blob.content());
+    let mut lines = Vec::new();
+
+    for (index, content) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let oid = match blame.get_line(line_number) {
+            Some(hunk) => hunk.final_commit_id(),
+            None => continue,
+        };
+
+        if !commits.contains_key(&oid) {
+            let info = match repo.find_commit(oid) {
+                Ok(found) => CommitInfo {
+                    author: found.author().name().unwrap_or("").to_string(),
+                    author_email: found.author().email().unwrap_or("").to_string(),
+                    timestamp: format_git_time(found.time()),
+                    summary: found.summary().unwrap_or("").to_string(),
+                },
+                Err(_) => CommitInfo {
+                    author: String::new(),
+                    author_email: String::new(),
+                    timestamp: String::new(),
+                    summary: String::new(),
+                },
+            };
+            commits.insert(oid, info);
+        }
+        let info = &commits[&oid];
+
+        lines.push(BlameLine {
+            line_number,
+            content: content.to_string(),
+            sha: oid.to_string(),
+            short_sha: short_sha(oid),
+            author: info.author.clone(),
+            author_email: info.author_email.clone(),
+            timestamp: info.timestamp.clone(),
+            summary: info.summary.clone(),
+        });
+    }
+
+    Ok(lines)
+}
+
+#[tauri::command]
+pub(crate) fn get_file_blame(
+    cache: tauri::State<'_, BlameCache>,
+    repo_id: String,
+    file_path: String,
+    rev: Option<String>,
+) -> Result<Vec<BlameLine>, String> {
+    let repo = open_repository(&repo_id)?;
+    let commit = resolve_commit(&repo, rev.as_deref().unwrap_or("HEAD"))?;
+    let key = (repo_id, file_path.clone(), commit.id().to_string());
+
+    if let Some(cached) = cache
+        .entries
+        .lock()
+        .map_err(|_| "Blame cache is poisoned".to_string())?
+        .get(&key)
+    {
+        return Ok(cached);
+    }
+
+    let lines = compute_blame(&repo, &commit, &file_path)?;
+    cache
+        .entries
+        .lock()
+        .map_err(|_| "Blame cache is poisoned".to_string())?
+        .insert(key, lines.clone());
+    Ok(lines)
+}
@@ -0,0 +1,228 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::audit::{audited, summarize};
+use crate::commands::{load_repository, read_json, save_repository, write_json};
+use crate::dashboard::is_open;
+use crate::git::{format_git_time, open_repository, resolve_commit, short_sha};
+use crate::paths;
+use crate::{list_workpads, RepositoryState, WorkpadState};
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct BranchInfo {
+    name: String,
+    sha: String,
+    short_sha: String,
+    is_head: bool,
+    is_trunk: bool,
+    /// The workpad working on this branch, if any.
+    workpad_id: Option<String>,
+    upstream: Option<String>,
+    last_commit_at: String,
+}
+
+fn open_workpads(repo_id: &str) -> Result<Vec<WorkpadState>, String> {
+    Ok(list_workpads(Some(repo_id.to_string()), None, None)?
+        .into_iter()
+        .filter(is_open)
+        .collect())
+}
+
+/// Point the CLI git engine at the new trunk too: it promotes onto the
+/// `trunk_branch` in `data/metadata/repositories.json`, not the state record.
+fn set_engine_trunk(repo_id: &str, branch: &str) -> Result<(), String> {
+    let path = paths::current().metadata_dir().join("repositories.json");
+    let Some(mut repositories) = read_json::<Value>(&path)? else {
+        return Ok(());
+    };
+    let Some(repository) = repositories.get_mut(repo_id).and_then(Value::as_object_mut) else {
+        return Ok(());
+    };
+    repository.insert(
+        "trunk_branch".to_string(),
+        Value::String(branch.to_string()),
+    );
+    write_json(&path, &repositories)
+}
+
+fn find_branch<'r>(repo: &'r git2::Repository, name: &str) -> Result<git2::Branch<'r>, String> {
+    repo.find_branch(name, git2::BranchType::Local)
+        .map_err(|_| format!("Branch not found: {}", name))
+}
+
+#[tauri::command]
+pub(crate) fn list_branches(repo_id: String) -> Result<Vec<BranchInfo>, String> {
+    let state = load_repository(&repo_id)?;
+    let repo = open_repository(&repo_id)?;
+    let workpads = list_workpads(Some(repo_id), None, None)?;
+
+    let mut branches = Vec::new();
+    for entry in repo
+        .branches(Some(git2::BranchType::Local))
+        .map_err(|e| format!("Failed to list branches: {}", e.message()))?
+    {
+        let (branch, _) = entry.map_err(|e| format!("Failed to read branch: {}", e.message()))?;
+        let Some(name) = branch.name().ok().flatten().map(str::to_string) else {
+            continue;
+        };
+        let Ok(commit) = branch.get().peel_to_commit() else {
+            continue;
+        };
+        let upstream = branch
+            .upstream()
+            .ok()
+            .and_then(|upstream| upstream.name().ok().flatten().map(str::to_string));
+        branches.push(BranchInfo {
+            sha: commit.id().to_string(),
+            short_sha: short_sha(commit.id()),
+            is_head: branch.is_head(),
+            is_trunk: name == state.trunk_branch,
+            workpad_id: workpads
+                .iter()
+                .find(|workpad| workpad.branch_name == name)
+                .map(|workpad| workpad.workpad_id.clone()),
+            upstream,
+            last_commit_at: format_git_time(commit.time()),
+            name,
+        });
+    }
+    branches.sort_by(|a, b| {
+        b.is_trunk
+            .cmp(&a.is_trunk)
+            .then_with(|| b.last_commit_at.cmp(&a.last_commit_at))
+    });
+    Ok(branches)
+}
+
+/// Create `name` at `start_point` (the trunk tip by default).
+#[tauri::command]
+pub(crate) fn create_branch(
+    repo_id: String,
+    name: String,
+    start_point: Option<String>,
+) -> Result<BranchInfo, String> {
+    let name = name.trim().to_string();
+    if !git2::Branch::name_is_valid(&name).unwrap_or(false) {
+        return Err(format!("Invalid branch name: {}", name));
+    }
+    audited(
+        "create_branch",
+        "repository",
+        Some(repo_id.clone()),
+        None,
+        move || {
+            let state = load_repository(&repo_id)?;
+            let repo = open_repository(&repo_id)?;
+            let start = start_point.unwrap_or(state.trunk_branch);
+            let commit = resolve_commit(&repo, &start)?;
+            repo.branch(&name, &commit, false)
+                .map_err(|e| format!("Failed to create branch {}: {}", name, e.message()))?;
+            list_branches(repo_id)?
+                .into_iter()
+                .find(|branch| branch.name == name)
+                .ok_or_else(|| format!("Branch not found: {}", name))
+        },
+    )
+}
+
+/// Delete a local branch. The trunk, the checked-out branch and branches of
+/// open workpads are refused; branches not merged into trunk need `force`.
+#[tauri::command]
+pub(crate) fn delete_branch(
+    repo_id: String,
+    name: String,
+    force: Option<bool>,
+) -> Result<(), String> {
+    audited(
+        "delete_branch",
+        "repository",
+        Some(repo_id.clone()),
+        None,
+        move || {
+            let state = load_repository(&repo_id)?;
+            if name == state.trunk_branch {
+                return Err(format!("Cannot delete the trunk branch {}", name));
+            }
+            if let Some(workpad) = open_workpads(&repo_id)?
+                .into_iter()
+                .find(|workpad| workpad.branch_name == name)
+            {
+                return Err(format!(
+                    "Branch {} belongs to open workpad {}",
+                    name, workpad.workpad_id
+                ));
+            }
+
+            let repo = open_repository(&repo_id)?;
+            let mut branch = find_branch(&repo, &name)?;
+            if branch.is_head() {
+                return Err(format!("Cannot delete the checked-out branch {}", name));
+            }
+            if !force.unwrap_or(false) {
+                let tip = branch
+                    .get()
+                    .peel_to_commit()
+                    .map_err(|e| e.message().to_string())?
+                    .id();
+                let trunk = resolve_commit(&repo, &state.trunk_branch)?.id();
+                let merged = tip == trunk || repo.graph_descendant_of(trunk, tip).unwrap_or(false);
+                if !merged {
+                    return Err(format!(
+                        "Branch {} is not merged into {}; delete with force to discard it",
+                        name, state.trunk_branch
+                    ));
+                }
+            }
+            branch
+                .delete()
+                .map_err(|e| format!("Failed to delete branch {}: {}", name, e.message()))
+        },
+    )
+}
+
+/// Make `branch` the repository's trunk. Refused when an open workpad's
+/// base commit isn't on the new trunk, since it could then never promote.
+#[tauri::command]
+pub(crate) fn set_trunk_branch(repo_id: String, branch: String) -> Result<RepositoryState, String> {
+    let before = load_repository(&repo_id)
+        .ok()
+        .and_then(|repo| summarize(&repo));
+    audited(
+        "set_trunk_branch",
+        "repository",
+        Some(repo_id.clone()),
+        before,
+        move || {
+            let mut state = load_repository(&repo_id)?;
+            let repo = open_repository(&repo_id)?;
+            let tip = find_branch(&repo, &branch)?
+                .get()
+                .peel_to_commit()
+                .map_err(|e| format!("Branch {} has no commits: {}", branch, e.message()))?
+                .id();
+
+            let orphaned: Vec<String> = open_workpads(&repo_id)?
+                .into_iter()
+                .filter(|workpad| {
+                    let on_trunk = git2::Oid::from_str(&workpad.base_commit).is_ok_and(|base| {
+                        base == tip || repo.graph_descendant_of(tip, base).unwrap_or(false)
+                    });
+                    !on_trunk
+                })
+                .map(|workpad| workpad.workpad_id)
+                .collect();
+            if !orphaned.is_empty() {
+                return Err(format!(
+                    "Open workpads are not based on {}: {}",
+                    branch,
+                    orphaned.join(", ")
+                ));
+            }
+
+            set_engine_trunk(&repo_id, &branch)?;
+            state.trunk_branch = branch;
+            state.current_commit = Some(tip.to_string());
+            save_repository(state)
+        },
+    )
+}
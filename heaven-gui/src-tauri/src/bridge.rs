@@ -0,0 +1,447 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::process::{Child, ChildStdin, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::commands::cli_command;
+use crate::logging::warn_on_err;
+use crate::profiles::active_home;
+
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+/// After a failed start, fall back to one-shot CLI calls for this long.
+const RETRY_BACKOFF: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Serialize)]
+struct BridgeRequest<'a> {
+    id: u64,
+    method: &'a str,
+    params: &'a Value,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct RemoteError {
+    #[serde(default)]
+    code: String,
+    pub(crate) message: String,
+    /// Status a failed `cli` request's command exited with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) exit_code: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BridgeResponse {
+    id: u64,
+    #[serde(default)]
+    result: Option<Value>,
+    #[serde(default)]
+    error: Option<RemoteError>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum BridgeError {
+    /// `evogitctl serve --json` could not be started or failed the handshake.
+    Unavailable(String),
+    Timeout {
+        method: String,
+        after: Duration,
+    },
+    /// The server exited while the request was in flight.
+    Disconnected,
+    Remote(RemoteError),
+    Protocol(String),
+}
+
+impl fmt::Display for BridgeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BridgeError::Unavailable(reason) => {
+                write!(f, "Solo Git bridge unavailable: {}", reason)
+            }
+            BridgeError::Timeout { method, after } => write!(
+                f,
+                "Solo Git bridge request {} timed out after {}s",
+                method,
+                after.as_secs()
+            ),
+            BridgeError::Disconnected => f.write_str("Solo Git bridge disconnected"),
+            BridgeError::Remote(error) if error.code.is_empty() => f.write_str(&error.message),
+            BridgeError::Remote(error) => write!(f, "{}: {}", error.code, error.message),
+            BridgeError::Protocol(message) => {
+                write!(f, "Solo Git bridge protocol error: {}", message)
+            }
+        }
+    }
+}
+
+impl From<BridgeError> for String {
+    fn from(error: BridgeError) -> String {
+        error.to_string()
+    }
+}
+
+/// One running `evogitctl serve --json` process. Requests and responses are
+/// newline-delimited JSON matched by `id`, so calls can overlap.
+struct Connection {
+    child: Mutex<Child>,
+    stdin: Mutex<ChildStdin>,
+    pending: Arc<Mutex<HashMap<u64, Sender<BridgeResponse>>>>,
+    alive: Arc<AtomicBool>,
+    home: PathBuf,
+    version: Option<String>,
+}
+
+impl Connection {
+    fn spawn() -> Result<Connection, BridgeError> {
+        let home = active_home();
+        let mut child = cli_command()
+            .args(["serve", "--json"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| BridgeError::Unavailable(format!("failed to execute evogitctl: {}", e)))?;
+
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| BridgeError::Protocol("no stdin".to_string()))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| BridgeError::Protocol("no stdout".to_string()))?;
+        let stderr = child.stderr.take();
+
+        let pending: Arc<Mutex<HashMap<u64, Sender<BridgeResponse>>>> = Arc::default();
+        let alive = Arc::new(AtomicBool::new(true));
+        {
+            let pending = pending.clone();
+            let alive = alive.clone();
+            thread::spawn(move || {
+                for line in BufReader::new(stdout).lines() {
+                    let Ok(line) = line else { break };
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    match serde_json::from_str::<BridgeResponse>(&line) {
+                        Ok(response) => {
+                            let waiter =
+                                pending.lock().ok().and_then(|mut p| p.remove(&response.id));
+                            // A waiter that already timed out has dropped its receiver.
+                            if let Some(waiter) = waiter {
+                                waiter.send(response).ok();
+                            }
+                        }
+                        Err(_) => tracing::debug!("bridge: {}", line),
+                    }
+                }
+                alive.store(false, Ordering::SeqCst);
+                // Dropping the senders wakes every waiter with a disconnect.
+                if let Ok(mut pending) = pending.lock() {
+                    pending.clear();
+                }
+            });
+        }
+        if let Some(stderr) = stderr {
+            thread::spawn(move || {
+                for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                    tracing::debug!("bridge stderr: {}", line);
+                }
+            });
+        }
+
+        let mut connection = Connection {
+            child: Mutex::new(child),
+            stdin: Mutex::new(stdin),
+            pending,
+            alive,
+            home,
+            version: None,
+        };
+        let hello = connection
+            .send(
+                0,
+                "hello",
+                &json!({ "client": "heaven-gui", "version": env!("CARGO_PKG_VERSION") }),
+                HANDSHAKE_TIMEOUT,
+            )
+            .map_err(|e| {
+                connection.kill();
+                BridgeError::Unavailable(format!("handshake failed: {}", e))
+            })?;
+        connection.version = hello["version"].as_str().map(str::to_string);
+        Ok(connection)
+    }
+
+    fn send(
+        &self,
+        id: u64,
+        method: &str,
+        params: &Value,
+        timeout: Duration,
+    ) -> Result<Value, BridgeError> {
+        if !self.alive.load(Ordering::SeqCst) {
+            return Err(BridgeError::Disconnected);
+        }
+        let (sender, receiver) = mpsc::channel();
+        self.pending
+            .lock()
+            .map_err(|_| BridgeError::Protocol("pending table poisoned".to_string()))?
+            .insert(id, sender);
+
+        let line = serde_json::to_string(&BridgeRequest { id, method, params })
+            .map_err(|e| BridgeError::Protocol(e.to_string()))?;
+        let written = self
+            .stdin
+            .lock()
+            .map_err(|_| BridgeError::Protocol("stdin poisoned".to_string()))
+            .and_then(|mut stdin| {
+                writeln!(stdin, "{}", line)
+                    .and_then(|_| stdin.flush())
+                    .map_err(|_| BridgeError::Disconnected)
+            });
+        if let Err(error) = written {
+            self.forget(id);
+            return Err(error);
+        }
+
+        let response = match receiver.recv_timeout(timeout) {
+            Ok(response) => response,
+            Err(RecvTimeoutError::Timeout) => {
+                self.forget(id);
+                return Err(BridgeError::Timeout {
+                    method: method.to_string(),
+                    after: timeout,
+                });
+            }
+            Err(RecvTimeoutError::Disconnected) => return Err(BridgeError::Disconnected),
+        };
+        match response.error {
+            Some(error) => Err(BridgeError::Remote(error)),
+            None => Ok(response.result.unwrap_or(Value::Null)),
+        }
+    }
+
+    fn forget(&self, id: u64) {
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.remove(&id);
+        }
+    }
+
+    fn pid(&self) -> Option<u32> {
+        self.child.lock().ok().map(|child| child.id())
+    }
+
+    fn kill(&self) {
+        self.alive.store(false, Ordering::SeqCst);
+        if let Ok(mut child) = self.child.lock() {
+            if matches!(child.try_wait(), Ok(None)) {
+                warn_on_err("Failed to stop Solo Git bridge", child.kill());
+            }
+            warn_on_err("Failed to reap Solo Git bridge", child.wait());
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.kill();
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Bridge {
+    connection: Mutex<Option<Arc<Connection>>>,
+    next_id: AtomicU64,
+    restarts: AtomicU32,
+    /// Why the last start failed, and until when to stop retrying.
+    unavailable: Mutex<Option<(String, Instant)>>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct BridgeStatus {
+    connected: bool,
+    pid: Option<u32>,
+    server_version: Option<String>,
+    home: Option<String>,
+    restarts: u32,
+    unavailable_reason: Option<String>,
+}
+
+static BRIDGE: OnceLock<Bridge> = OnceLock::new();
+
+pub(crate) fn bridge() -> &'static Bridge {
+    BRIDGE.get_or_init(Bridge::default)
+}
+
+impl Bridge {
+    /// Current connection, (re)starting the server when it has exited or the
+    /// active profile changed since it was started.
+    fn connection(&self) -> Result<Arc<Connection>, BridgeError> {
+        let mut slot = self
+            .connection
+            .lock()
+            .map_err(|_| BridgeError::Protocol("connection lock poisoned".to_string()))?;
+        if let Some(existing) = slot.as_ref() {
+            if existing.alive.load(Ordering::SeqCst) && existing.home == active_home() {
+                return Ok(existing.clone());
+            }
+            self.restarts.fetch_add(1, Ordering::SeqCst);
+        }
+        *slot = None;
+
+        if let Ok(unavailable) = self.unavailable.lock() {
+            if let Some((reason, until)) = unavailable.as_ref() {
+                if Instant::now() < *until {
+                    return Err(BridgeError::Unavailable(reason.clone()));
+                }
+            }
+        }
+
+        match Connection::spawn() {
+            Ok(connection) => {
+                let connection = Arc::new(connection);
+                *slot = Some(connection.clone());
+                if let Ok(mut unavailable) = self.unavailable.lock() {
+                    *unavailable = None;
+                }
+                tracing::info!("Solo Git bridge connected (pid {:?})", connection.pid());
+                Ok(connection)
+            }
+            Err(error) => {
+                tracing::warn!("{}", error);
+                if let Ok(mut unavailable) = self.unavailable.lock() {
+                    *unavailable = Some((error.to_string(), Instant::now() + RETRY_BACKOFF));
+                }
+                Err(error)
+            }
+        }
+    }
+
+    /// Send one request, reconnecting once if the server went away.
+    pub(crate) fn request(
+        &self,
+        method: &str,
+        params: &Value,
+        timeout: Duration,
+    ) -> Result<Value, BridgeError> {
+        for attempt in 0..2 {
+            let connection = self.connection()?;
+            let id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+            match connection.send(id, method, params, timeout) {
+                Err(BridgeError::Disconnected) if attempt == 0 => {
+                    tracing::warn!("Solo Git bridge disconnected; reconnecting");
+                    continue;
+                }
+                result => return result,
+            }
+        }
+        Err(BridgeError::Disconnected)
+    }
+
+    pub(crate) fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: impl Serialize,
+        timeout: Option<Duration>,
+    ) -> Result<T, BridgeError> {
+        let params =
+            serde_json::to_value(params).map_err(|e| BridgeError::Protocol(e.to_string()))?;
+        let result = self.request(method, &params, timeout.unwrap_or(DEFAULT_TIMEOUT))?;
+        serde_json::from_value(result)
+            .map_err(|e| BridgeError::Protocol(format!("{} result: {}", method, e)))
+    }
+
+    /// Run a CLI invocation inside the server process. `None` means the
+    /// bridge is unavailable and the caller should spawn `evogitctl` itself.
+    /// A timed-out command keeps running in the server, so the server is
+    /// dropped and restarted on next use.
+    pub(crate) fn try_cli(
+        &self,
+        args: &[String],
+        timeout: Duration,
+    ) -> Option<Result<String, BridgeError>> {
+        #[derive(Deserialize)]
+        struct CliResult {
+            #[serde(default)]
+            stdout: String,
+        }
+        match self.call::<CliResult>("cli", json!({ "args": args }), Some(timeout)) {
+            Ok(result) => Some(Ok(result.stdout)),
+            Err(BridgeError::Unavailable(_)) => None,
+            Err(error @ BridgeError::Timeout { .. }) => {
+                self.shutdown();
+                Some(Err(error))
+            }
+            Err(error) => Some(Err(error)),
+        }
+    }
+
+    fn shutdown(&self) {
+        if let Ok(mut slot) = self.connection.lock() {
+            *slot = None;
+        }
+    }
+
+    fn status(&self) -> BridgeStatus {
+        let connection = self.connection.lock().ok().and_then(|slot| slot.clone());
+        let connected = connection
+            .as_ref()
+            .is_some_and(|connection| connection.alive.load(Ordering::SeqCst));
+        BridgeStatus {
+            connected,
+            pid: connection.as_ref().and_then(|connection| connection.pid()),
+            server_version: connection
+                .as_ref()
+                .and_then(|connection| connection.version.clone()),
+            home: connection
+                .as_ref()
+                .map(|connection| connection.home.display().to_string()),
+            restarts: self.restarts.load(Ordering::SeqCst),
+            unavailable_reason: self
+                .unavailable
+                .lock()
+                .ok()
+                .and_then(|unavailable| unavailable.as_ref().map(|(reason, _)| reason.clone())),
+        }
+    }
+}
+
+#[tauri::command]
+pub(crate) fn get_bridge_status() -> Result<BridgeStatus, String> {
+    Ok(bridge().status())
+}
+
+/// Drop the current server and clear the start backoff, then reconnect.
+#[tauri::command]
+pub(crate) fn restart_bridge() -> Result<BridgeStatus, String> {
+    let bridge = bridge();
+    bridge.shutdown();
+    if let Ok(mut unavailable) = bridge.unavailable.lock() {
+        *unavailable = None;
+    }
+    bridge.connection()?;
+    Ok(bridge.status())
+}
+
+#[tauri::command]
+pub(crate) fn bridge_request(
+    method: String,
+    params: Option<Value>,
+    timeout_secs: Option<u64>,
+) -> Result<Value, String> {
+    let timeout = timeout_secs
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_TIMEOUT);
+    Ok(bridge().request(&method, &params.unwrap_or(Value::Null), timeout)?)
+}
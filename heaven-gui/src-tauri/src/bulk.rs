@@ -0,0 +1,82 @@
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use crate::archive::archive_workpad;
+use crate::commands::{delete_workpad, run_tests};
+use crate::{TestRun, WorkpadState};
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct BulkItemResult<T> {
+    workpad_id: String,
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct BulkOutcome<T> {
+    succeeded: usize,
+    failed: usize,
+    results: Vec<BulkItemResult<T>>,
+}
+
+/// Run `op` for each distinct ID in order, collecting per-item results
+/// instead of stopping at the first failure.
+fn for_each_workpad<T>(
+    ids: Vec<String>,
+    mut op: impl FnMut(String) -> Result<T, String>,
+) -> BulkOutcome<T> {
+    let mut seen = HashSet::new();
+    let mut results = Vec::new();
+    for workpad_id in ids {
+        let workpad_id = workpad_id.trim().to_string();
+        if workpad_id.is_empty() || !seen.insert(workpad_id.clone()) {
+            continue;
+        }
+        let result = op(workpad_id.clone());
+        results.push(match result {
+            Ok(value) => BulkItemResult {
+                workpad_id,
+                ok: true,
+                value: Some(value),
+                error: None,
+            },
+            Err(error) => BulkItemResult {
+                workpad_id,
+                ok: false,
+                value: None,
+                error: Some(error),
+            },
+        });
+    }
+
+    let succeeded = results.iter().filter(|result| result.ok).count();
+    BulkOutcome {
+        succeeded,
+        failed: results.len() - succeeded,
+        results,
+    }
+}
+
+#[tauri::command]
+pub(crate) fn bulk_delete_workpads(ids: Vec<String>) -> Result<BulkOutcome<()>, String> {
+    Ok(for_each_workpad(ids, delete_workpad))
+}
+
+#[tauri::command]
+pub(crate) fn bulk_archive_workpads(ids: Vec<String>) -> Result<BulkOutcome<WorkpadState>, String> {
+    Ok(for_each_workpad(ids, archive_workpad))
+}
+
+#[tauri::command]
+pub(crate) fn bulk_run_tests(
+    ids: Vec<String>,
+    target: String,
+) -> Result<BulkOutcome<TestRun>, String> {
+    Ok(for_each_workpad(ids, |workpad_id| {
+        run_tests(workpad_id, target.clone())
+    }))
+}
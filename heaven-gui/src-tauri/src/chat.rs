@@ -0,0 +1,257 @@
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::ai::{ai_settings, run_operation, ChatMessage};
+use crate::audit::audited;
+use crate::commands::{load_repository, load_workpad, read_json, write_json};
+use crate::context::build_context;
+use crate::get_state_dir;
+use crate::paths::is_plain_id;
+use crate::tokens::count_message_tokens;
+
+const CHAT_SYSTEM_PROMPT: &str = "You are the Solo Git assistant, helping a solo developer \
+plan, write and debug code in their repository. Be concise and concrete.";
+/// Earlier turns beyond this many tokens are left out of the request; the
+/// session itself keeps them.
+const HISTORY_TOKEN_BUDGET: usize = 16_000;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct ChatSessionMessage {
+    role: String,
+    content: String,
+    timestamp: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    operation_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    model: Option<String>,
+    #[serde(default)]
+    cost_usd: f64,
+    #[serde(default)]
+    tokens_used: i32,
+    /// Set once `redact_ai_history` has scrubbed `content`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    redaction: Option<String>,
+}
+
+/// A persisted AI conversation, optionally attached to a workpad.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct ChatSession {
+    session_id: String,
+    repo_id: String,
+    workpad_id: Option<String>,
+    title: String,
+    messages: Vec<ChatSessionMessage>,
+    total_cost_usd: f64,
+    total_tokens: i64,
+    created_at: String,
+    updated_at: String,
+}
+
+pub(crate) fn sessions_dir() -> PathBuf {
+    get_state_dir().join("chat_sessions")
+}
+
+/// Session IDs come from the frontend; only ones this module could have
+/// made map to a file.
+fn session_path(session_id: &str) -> Result<PathBuf, String> {
+    if !session_id.starts_with("chat-") || !is_plain_id(session_id) {
+        return Err(format!("Invalid chat session ID: {}", session_id));
+    }
+    Ok(sessions_dir().join(format!("{}.json", session_id)))
+}
+
+fn load_session(session_id: &str) -> Result<ChatSession, String> {
+    read_json(&session_path(session_id)?)?
+        .ok_or_else(|| format!("Chat session not found: {}", session_id))
+}
+
+fn save_session(mut session: ChatSession) -> Result<ChatSession, String> {
+    session.updated_at = Utc::now().to_rfc3339();
+    write_json(&session_path(&session.session_id)?, &session)?;
+    Ok(session)
+}
+
+/// The system prompt, with files from the session's workpad relevant to
+/// `prompt` when it has one.
+fn system_prompt(session: &ChatSession, prompt: &str) -> String {
+    let Some(workpad_id) = &session.workpad_id else {
+        return CHAT_SYSTEM_PROMPT.to_string();
+    };
+    let context = load_workpad(workpad_id)
+        .and_then(|workpad| build_context(&workpad, prompt, ai_settings().context_token_budget));
+    match context {
+        Ok(context) if !context.block().is_empty() => format!(
+            "{}\n\nRelevant files from the workpad:\n\n{}",
+            CHAT_SYSTEM_PROMPT,
+            context.block()
+        ),
+        Ok(_) => CHAT_SYSTEM_PROMPT.to_string(),
+        Err(e) => {
+            // Chat still works without the files.
+            tracing::warn!("Failed to build chat context: {}", e);
+            CHAT_SYSTEM_PROMPT.to_string()
+        }
+    }
+}
+
+/// The most recent messages that fit `HISTORY_TOKEN_BUDGET`, oldest first.
+/// The latest message is always sent.
+fn recent_history(messages: &[ChatSessionMessage]) -> Vec<ChatMessage> {
+    let model = ai_settings().model;
+    let mut recent: Vec<ChatMessage> = Vec::new();
+    let mut used = 0;
+    for message in messages.iter().rev() {
+        let message = ChatMessage {
+            role: message.role.clone(),
+            content: message.content.clone(),
+        };
+        let tokens = count_message_tokens(std::slice::from_ref(&message), &model);
+        if !recent.is_empty() && used + tokens > HISTORY_TOKEN_BUDGET {
+            break;
+        }
+        used += tokens;
+        recent.push(message);
+    }
+    recent.reverse();
+    recent
+}
+
+fn message(role: &str, content: &str) -> ChatSessionMessage {
+    ChatSessionMessage {
+        role: role.to_string(),
+        content: content.to_string(),
+        timestamp: Utc::now().to_rfc3339(),
+        operation_id: None,
+        model: None,
+        cost_usd: 0.0,
+        tokens_used: 0,
+        redaction: None,
+    }
+}
+
+#[tauri::command]
+pub(crate) fn create_chat_session(
+    repo_id: String,
+    workpad_id: Option<String>,
+    title: Option<String>,
+) -> Result<ChatSession, String> {
+    audited(
+        "create_chat_session",
+        "chat_session",
+        None,
+        None,
+        move || new_session(repo_id, workpad_id, title),
+    )
+}
+
+fn new_session(
+    repo_id: String,
+    workpad_id: Option<String>,
+    title: Option<String>,
+) -> Result<ChatSession, String> {
+    load_repository(&repo_id)?;
+    if let Some(wp_id) = &workpad_id {
+        let workpad = load_workpad(wp_id)?;
+        if workpad.repo_id != repo_id {
+            return Err(format!(
+                "Workpad {} does not belong to repository {}",
+                wp_id, repo_id
+            ));
+        }
+    }
+
+    let now = Utc::now().to_rfc3339();
+    save_session(ChatSession {
+        session_id: format!("chat-{}", Uuid::new_v4().simple()),
+        repo_id,
+        workpad_id,
+        title: title
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .unwrap_or_else(|| "New conversation".to_string()),
+        messages: Vec::new(),
+        total_cost_usd: 0.0,
+        total_tokens: 0,
+        created_at: now.clone(),
+        updated_at: now,
+    })
+}
+
+#[tauri::command]
+pub(crate) fn get_chat_session(session_id: String) -> Result<ChatSession, String> {
+    load_session(&session_id)
+}
+
+/// Add a user message, send the recent conversation (and, for a workpad
+/// session, the relevant files) to the provider and append its reply. The
+/// user message is kept even if the request fails.
+#[tauri::command]
+pub(crate) fn append_chat_message(
+    session_id: String,
+    content: String,
+) -> Result<ChatSession, String> {
+    if content.trim().is_empty() {
+        return Err("Message cannot be empty".to_string());
+    }
+    let mut session = load_session(&session_id)?;
+    session.messages.push(message("user", &content));
+    let mut session = save_session(session)?;
+
+    let mut history = vec![ChatMessage::system(system_prompt(&session, &content))];
+    history.extend(recent_history(&session.messages));
+
+    let (operation, completion) =
+        run_operation(session.workpad_id.clone(), "chat", &content, &history, None)?;
+    let completion = completion.ok_or_else(|| {
+        operation
+            .error
+            .clone()
+            .unwrap_or_else(|| "AI request failed".to_string())
+    })?;
+
+    let mut reply = message("assistant", &completion.content);
+    reply.operation_id = Some(operation.operation_id.clone());
+    reply.model = Some(operation.model.clone());
+    reply.cost_usd = operation.cost_usd;
+    reply.tokens_used = operation.tokens_used;
+
+    session.total_cost_usd += operation.cost_usd;
+    session.total_tokens += operation.tokens_used as i64;
+    session.messages.push(reply);
+    save_session(session)
+}
+
+#[tauri::command]
+pub(crate) fn list_chat_sessions(
+    repo_id: Option<String>,
+    workpad_id: Option<String>,
+) -> Result<Vec<ChatSession>, String> {
+    let dir = sessions_dir();
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut sessions = Vec::new();
+    for entry in fs::read_dir(&dir).map_err(|e| format!("Failed to read chat sessions: {}", e))? {
+        let path = entry
+            .map_err(|e| format!("Failed to read chat sessions: {}", e))?
+            .path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        if let Some(session) = read_json::<ChatSession>(&path)? {
+            let repo_matches = repo_id.is_none() || repo_id.as_ref() == Some(&session.repo_id);
+            let workpad_matches = workpad_id.is_none() || session.workpad_id == workpad_id;
+            if repo_matches && workpad_matches {
+                sessions.push(session);
+            }
+        }
+    }
+
+    sessions.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    Ok(sessions)
+}
@@ -0,0 +1,175 @@
+use std::path::PathBuf;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::audit::{audited, summarize};
+use crate::commands::{load_workpad, read_json, save_workpad, workpad_checkout_dir, write_json};
+use crate::git::run_git;
+use crate::lifecycle::WorkpadStatus;
+use crate::{get_state_dir, WorkpadState};
+
+/// Restorable point in a workpad's history: the git commit plus the workpad
+/// metadata as it was right after the patch that produced it.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct Checkpoint {
+    pub(crate) checkpoint_id: String,
+    workpad_id: String,
+    commit_sha: String,
+    tag_name: Option<String>,
+    message: String,
+    created_at: String,
+    status: WorkpadStatus,
+    patches_applied: i32,
+    files_changed: Vec<String>,
+    test_runs: Vec<String>,
+}
+
+fn checkpoints_path(workpad_id: &str) -> PathBuf {
+    get_state_dir()
+        .join("checkpoints")
+        .join(format!("{}.json", workpad_id))
+}
+
+/// The implicit checkpoint at the workpad's base commit.
+fn base_checkpoint(workpad: &WorkpadState) -> Checkpoint {
+    Checkpoint {
+        checkpoint_id: "t0".to_string(),
+        workpad_id: workpad.workpad_id.clone(),
+        commit_sha: workpad.base_commit.clone(),
+        tag_name: None,
+        message: "Workpad created".to_string(),
+        created_at: workpad.created_at.clone(),
+        status: WorkpadStatus::Active,
+        patches_applied: 0,
+        files_changed: Vec::new(),
+        test_runs: Vec::new(),
+    }
+}
+
+fn load_recorded(workpad_id: &str) -> Result<Vec<Checkpoint>, String> {
+    Ok(read_json(&checkpoints_path(workpad_id))?.unwrap_or_default())
+}
+
+/// Record a checkpoint for the workpad's current branch head. The CLI tags
+/// each applied patch as `<branch>@tN`; reuse that tag when present.
+pub(crate) fn record_checkpoint(
+    workpad: &WorkpadState,
+    message: &str,
+) -> Result<Checkpoint, String> {
+    let checkout = workpad_checkout_dir(workpad)?;
+    let mut checkpoints = load_recorded(&workpad.workpad_id)?;
+
+    let commit_sha = run_git(
+        &checkout,
+        &["rev-parse", &format!("refs/heads/{}", workpad.branch_name)],
+    )?
+    .trim()
+    .to_string();
+
+    let checkpoint_id = format!("t{}", checkpoints.len() + 1);
+    let tag_name = format!("{}@{}", workpad.branch_name, checkpoint_id);
+    // After a rollback the id is reused, so an existing tag may still point
+    // at a dropped checkpoint; move it rather than trusting it.
+    let tagged = run_git(
+        &checkout,
+        &[
+            "rev-parse",
+            "-q",
+            "--verify",
+            &format!("refs/tags/{}^{{commit}}", tag_name),
+        ],
+    )
+    .map(|sha| sha.trim().to_string())
+    .ok();
+    if tagged.as_deref() != Some(commit_sha.as_str()) {
+        run_git(&checkout, &["tag", "-f", &tag_name, &commit_sha])?;
+    }
+
+    let checkpoint = Checkpoint {
+        checkpoint_id,
+        workpad_id: workpad.workpad_id.clone(),
+        commit_sha,
+        tag_name: Some(tag_name),
+        message: message.to_string(),
+        created_at: Utc::now().to_rfc3339(),
+        status: workpad.status,
+        patches_applied: workpad.patches_applied,
+        files_changed: workpad.files_changed.clone(),
+        test_runs: workpad.test_runs.clone(),
+    };
+
+    checkpoints.push(checkpoint.clone());
+    write_json(&checkpoints_path(&workpad.workpad_id), &checkpoints)?;
+    Ok(checkpoint)
+}
+
+/// All checkpoints including the implicit base, oldest first.
+pub(crate) fn workpad_checkpoints(workpad: &WorkpadState) -> Result<Vec<Checkpoint>, String> {
+    let mut checkpoints = vec![base_checkpoint(workpad)];
+    checkpoints.extend(load_recorded(&workpad.workpad_id)?);
+    Ok(checkpoints)
+}
+
+/// Reset the workpad branch and metadata to `checkpoint_id`. Later
+/// checkpoints are dropped from the timeline; their test runs and patch
+/// records stay on disk.
+pub(crate) fn restore_checkpoint(
+    workpad: WorkpadState,
+    checkpoint_id: &str,
+) -> Result<WorkpadState, String> {
+    let checkpoints = workpad_checkpoints(&workpad)?;
+    let position = checkpoints
+        .iter()
+        .position(|c| c.checkpoint_id == checkpoint_id)
+        .ok_or_else(|| {
+            format!(
+                "Checkpoint {} not found in workpad {}",
+                checkpoint_id, workpad.workpad_id
+            )
+        })?;
+    let target = checkpoints[position].clone();
+
+    let checkout = workpad_checkout_dir(&workpad)?;
+    run_git(&checkout, &["checkout", &workpad.branch_name])?;
+    run_git(&checkout, &["reset", "--hard", &target.commit_sha])?;
+
+    // Keep only recorded checkpoints up to and including the target (the base
+    // checkpoint at index 0 is implicit and never stored).
+    let kept: Vec<Checkpoint> = checkpoints[1..=position].to_vec();
+    write_json(&checkpoints_path(&workpad.workpad_id), &kept)?;
+
+    let mut workpad = workpad;
+    workpad.status = target.status;
+    workpad.current_commit = Some(target.commit_sha);
+    workpad.patches_applied = target.patches_applied;
+    workpad.files_changed = target.files_changed;
+    workpad.test_runs = target.test_runs;
+    save_workpad(workpad)
+}
+
+#[tauri::command]
+pub(crate) fn list_checkpoints(workpad_id: String) -> Result<Vec<Checkpoint>, String> {
+    let workpad = load_workpad(&workpad_id)?;
+    workpad_checkpoints(&workpad)
+}
+
+#[tauri::command]
+pub(crate) fn rollback_to_checkpoint(
+    workpad_id: String,
+    checkpoint_id: String,
+) -> Result<WorkpadState, String> {
+    let before = load_workpad(&workpad_id)
+        .ok()
+        .and_then(|workpad| summarize(&workpad));
+    audited(
+        "rollback_to_checkpoint",
+        "workpad",
+        Some(workpad_id.clone()),
+        before,
+        move || {
+            let workpad = load_workpad(&workpad_id)?;
+            restore_checkpoint(workpad, &checkpoint_id)
+        },
+    )
+}
@@ -0,0 +1,366 @@
+use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::Manager;
+
+use crate::api_server;
+use crate::audit::as_actor;
+use crate::commands::{read_json, resolve_repo_path, write_json};
+use crate::git::remote_location;
+use crate::github;
+use crate::http::{agent, encode_component, json_response};
+use crate::lifecycle::WorkpadStatus;
+use crate::logging::warn_on_err;
+use crate::{
+    get_settings, get_state_dir, list_repositories, list_workpads, PromotionRecord, RepositoryState,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub(crate) struct CiSettings {
+    pub(crate) enabled: bool,
+    pub(crate) github_token: Option<String>,
+    pub(crate) gitlab_token: Option<String>,
+    pub(crate) gitlab_url: String,
+    pub(crate) remote: String,
+    pub(crate) poll_interval_secs: u64,
+}
+
+impl Default for CiSettings {
+    fn default() -> Self {
+        CiSettings {
+            enabled: false,
+            github_token: None,
+            gitlab_token: None,
+            gitlab_url: "https://gitlab.com".to_string(),
+            remote: "origin".to_string(),
+            poll_interval_secs: 120,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct CiStatus {
+    repo_id: String,
+    git_ref: String,
+    provider: String,
+    /// "passed", "failed", "running", "pending" or "unknown"
+    status: String,
+    message: Option<String>,
+    url: Option<String>,
+    sha: Option<String>,
+    updated_at: String,
+}
+
+impl CiStatus {
+    pub(crate) fn passed(&self) -> bool {
+        self.status == "passed"
+    }
+
+    fn same_outcome(&self, other: &CiStatus) -> bool {
+        self.status == other.status && self.sha == other.sha
+    }
+}
+
+fn ci_state_path(repo_id: &str) -> std::path::PathBuf {
+    get_state_dir().join("ci").join(format!("{}.json", repo_id))
+}
+
+fn load_ci_state(repo_id: &str) -> Result<HashMap<String, CiStatus>, String> {
+    Ok(read_json(&ci_state_path(repo_id))?.unwrap_or_default())
+}
+
+fn store_ci_status(status: &CiStatus) -> Result<(), String> {
+    let mut state = load_ci_state(&status.repo_id)?;
+    state.insert(status.git_ref.clone(), status.clone());
+    write_json(&ci_state_path(&status.repo_id), &state)
+}
+
+fn github_status(slug: &str, git_ref: &str, token: Option<&str>) -> Result<CiStatus, String> {
+    let mut request = agent()
+        .get(&format!(
+            "https://api.github.com/repos/{}/actions/runs",
+            slug
+        ))
+        .set("Accept", "application/vnd.github+json")
+        .query("branch", git_ref)
+        .query("per_page", "1");
+    if let Some(token) = token {
+        request = request.set("Authorization", &format!("Bearer {}", token));
+    }
+
+    let data = json_response(request.call())?;
+    let run = &data["workflow_runs"][0];
+    let status = match (run["status"].as_str(), run["conclusion"].as_str()) {
+        (None, _) => "unknown",
+        (Some("completed"), Some("success")) => "passed",
+        (Some("completed"), Some("skipped")) | (Some("completed"), Some("neutral")) => "passed",
+        (Some("completed"), _) => "failed",
+        (Some("in_progress"), _) => "running",
+        (Some(_), _) => "pending",
+    };
+
+    Ok(CiStatus {
+        repo_id: String::new(),
+        git_ref: git_ref.to_string(),
+        provider: "github".to_string(),
+        status: status.to_string(),
+        message: run["name"]
+            .as_str()
+            .map(|name| format!("{} ({})", name, run["conclusion"].as_str().unwrap_or("-"))),
+        url: run["html_url"].as_str().map(|s| s.to_string()),
+        sha: run["head_sha"].as_str().map(|s| s.to_string()),
+        updated_at: Utc::now().to_rfc3339(),
+    })
+}
+
+fn gitlab_status(
+    base_url: &str,
+    slug: &str,
+    git_ref: &str,
+    token: Option<&str>,
+) -> Result<CiStatus, String> {
+    let mut request = agent()
+        .get(&format!(
+            "{}/api/v4/projects/{}/pipelines",
+            base_url.trim_end_matches('/'),
+            encode_component(slug)
+        ))
+        .query("ref", git_ref)
+        .query("per_page", "1");
+    if let Some(token) = token {
+        request = request.set("PRIVATE-TOKEN", token);
+    }
+
+    let data = json_response(request.call())?;
+    let pipeline = &data[0];
+    let status = match pipeline["status"].as_str() {
+        None => "unknown",
+        Some("success") | Some("skipped") => "passed",
+        Some("failed") | Some("canceled") => "failed",
+        Some("running") => "running",
+        Some(_) => "pending",
+    };
+
+    Ok(CiStatus {
+        repo_id: String::new(),
+        git_ref: git_ref.to_string(),
+        provider: "gitlab".to_string(),
+        status: status.to_string(),
+        message: pipeline["status"]
+            .as_str()
+            .map(|s| format!("Pipeline {}", s)),
+        url: pipeline["web_url"].as_str().map(|s| s.to_string()),
+        sha: pipeline["sha"].as_str().map(|s| s.to_string()),
+        updated_at: Utc::now().to_rfc3339(),
+    })
+}
+
+/// Query the CI provider behind the repository's remote for `git_ref`.
+pub(crate) fn fetch_ci_status(
+    settings: &CiSettings,
+    repo_id: &str,
+    git_ref: &str,
+) -> Result<CiStatus, String> {
+    let repo_dir = resolve_repo_path(repo_id)?;
+    let remote = remote_location(&repo_dir, &settings.remote)?;
+
+    let gitlab_host = settings
+        .gitlab_url
+        .split("://")
+        .nth(1)
+        .unwrap_or(&settings.gitlab_url)
+        .trim_end_matches('/')
+        .to_lowercase();
+
+    let mut status = if remote.host.contains("github") {
+        github_status(&remote.slug, git_ref, settings.github_token.as_deref())?
+    } else if remote.host.contains("gitlab") || remote.host == gitlab_host {
+        let base = if remote.host == gitlab_host {
+            settings.gitlab_url.clone()
+        } else {
+            format!("https://{}", remote.host)
+        };
+        gitlab_status(
+            &base,
+            &remote.slug,
+            git_ref,
+            settings.gitlab_token.as_deref(),
+        )?
+    } else {
+        return Err(format!("No CI provider known for host {}", remote.host));
+    };
+
+    status.repo_id = repo_id.to_string();
+    Ok(status)
+}
+
+/// Copy a CI outcome onto the promotion records whose commit it ran on.
+fn update_promotion_records(status: &CiStatus) -> Result<(), String> {
+    let sha = match &status.sha {
+        Some(sha) => sha,
+        None => return Ok(()),
+    };
+    let dir = get_state_dir().join("promotions");
+    if !dir.exists() {
+        return Ok(());
+    }
+
+    for entry in std::fs::read_dir(&dir).map_err(|e| e.to_string())? {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("json") {
+            continue;
+        }
+        let Some(mut record) = read_json::<PromotionRecord>(&path)? else {
+            continue;
+        };
+        if record.repo_id != status.repo_id || record.commit_hash.as_ref() != Some(sha) {
+            continue;
+        }
+        let ci_status = Some(status.status.clone());
+        if record.ci_status != ci_status || record.ci_message != status.message {
+            record.ci_status = ci_status;
+            record.ci_message = status.message.clone();
+            write_json(&path, &record)?;
+        }
+    }
+    Ok(())
+}
+
+/// Mark the matching commit in the commit cache with its CI outcome.
+fn update_commit_cache(repo_id: &str, status: &CiStatus) -> Result<(), String> {
+    let sha = match &status.sha {
+        Some(sha) => sha,
+        None => return Ok(()),
+    };
+    let path = get_state_dir()
+        .join("commits")
+        .join(format!("{}.json", repo_id));
+    let mut data: Value = match read_json(&path)? {
+        Some(data) => data,
+        None => return Ok(()),
+    };
+
+    let mut changed = false;
+    if let Some(commits) = data["commits"].as_array_mut() {
+        for commit in commits.iter_mut() {
+            if commit["sha"].as_str() == Some(sha.as_str()) {
+                commit["ci_status"] = Value::String(status.status.clone());
+                changed = true;
+            }
+        }
+    }
+
+    if changed {
+        write_json(&path, &data)?;
+    }
+    Ok(())
+}
+
+fn poll_once(app: &tauri::AppHandle, settings: &CiSettings) -> Result<(), String> {
+    // One failing repository shouldn't stop the others from being polled.
+    for repo in list_repositories(None)? {
+        if let Err(e) = poll_repo(app, settings, &repo) {
+            tracing::warn!("CI poll failed for {}: {}", repo.repo_id, e);
+        }
+    }
+    Ok(())
+}
+
+fn poll_repo(
+    app: &tauri::AppHandle,
+    settings: &CiSettings,
+    repo: &RepositoryState,
+) -> Result<(), String> {
+    if settings.github_token.is_some() {
+        for workpad in github::sync_open_pull_requests(&repo.repo_id)? {
+            warn_on_err(
+                "Failed to emit pull-request-changed",
+                app.emit_all("pull-request-changed", &workpad),
+            );
+            api_server::forward("pull-request-changed", &workpad);
+        }
+    }
+
+    // Promoted workpads land on trunk, whose runs fill in their records.
+    let mut refs = vec![repo.trunk_branch.clone()];
+    for workpad in list_workpads(Some(repo.repo_id.clone()), None, None)? {
+        if !matches!(
+            workpad.status,
+            WorkpadStatus::Promoted | WorkpadStatus::Deleted
+        ) {
+            refs.push(workpad.branch_name);
+        }
+    }
+
+    let previous = load_ci_state(&repo.repo_id)?;
+    for git_ref in refs {
+        let status = match fetch_ci_status(settings, &repo.repo_id, &git_ref) {
+            Ok(status) => status,
+            // Repos without a recognised remote simply have no CI.
+            Err(_) => continue,
+        };
+
+        let changed = previous
+            .get(&git_ref)
+            .map(|old| !old.same_outcome(&status))
+            .unwrap_or(true);
+        if !changed {
+            continue;
+        }
+
+        store_ci_status(&status)?;
+        update_promotion_records(&status)?;
+        update_commit_cache(&repo.repo_id, &status)?;
+        warn_on_err(
+            "Failed to emit ci-status-changed",
+            app.emit_all("ci-status-changed", status.clone()),
+        );
+        api_server::forward("ci-status-changed", &status);
+    }
+    Ok(())
+}
+
+/// Background loop polling CI for trunk and open workpad branches.
+pub(crate) fn start_ci_poller(app: tauri::AppHandle) {
+    thread::spawn(move || {
+        as_actor("scheduler", || loop {
+            let settings = get_settings().map(|s| s.git.ci).unwrap_or_default();
+            if settings.enabled {
+                warn_on_err("CI poll failed", poll_once(&app, &settings));
+            }
+            thread::sleep(Duration::from_secs(settings.poll_interval_secs.max(30)));
+        })
+    });
+}
+
+#[tauri::command]
+pub(crate) fn get_ci_status(
+    repo_id: String,
+    git_ref: String,
+    force_refresh: Option<bool>,
+) -> Result<CiStatus, String> {
+    let settings = get_settings()?.git.ci;
+
+    if !force_refresh.unwrap_or(false) {
+        if let Some(cached) = load_ci_state(&repo_id)?.remove(&git_ref) {
+            let fresh = DateTime::parse_from_rfc3339(&cached.updated_at)
+                .map(|at| {
+                    Utc::now().signed_duration_since(at.with_timezone(&Utc))
+                        < chrono::Duration::seconds(settings.poll_interval_secs as i64)
+                })
+                .unwrap_or(false);
+            if fresh {
+                return Ok(cached);
+            }
+        }
+    }
+
+    let status = fetch_ci_status(&settings, &repo_id, &git_ref)?;
+    store_ci_status(&status)?;
+    Ok(status)
+}
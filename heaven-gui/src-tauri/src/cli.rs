@@ -0,0 +1,360 @@
+use std::fmt;
+use std::io::{BufRead, BufReader, Read};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use serde_json::Value;
+use tauri::Manager;
+
+use crate::bridge::{bridge, BridgeError};
+use crate::commands::cli_command;
+use crate::logging::warn_on_err;
+use crate::sandbox::kill_tree;
+
+/// Long enough for a full test run; callers with tighter bounds pass their own.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// How long to keep reading after `evogitctl` exits. Anything it left running
+/// in the background holds the pipes open, and nothing it says after this
+/// belongs to the command.
+const OUTPUT_GRACE: Duration = Duration::from_secs(2);
+
+/// Top-level `evogitctl` commands `run_cli_passthrough` may run. Excluded are
+/// the ones that take over a terminal (`tui`, `heaven`, `interactive`,
+/// `edit`), start another GUI or the bridge server.
+const PASSTHROUGH_COMMANDS: &[&str] = &[
+    "version",
+    "hello",
+    "shortcuts",
+    "config",
+    "repo",
+    "pad",
+    "test",
+    "ci",
+    "undo",
+    "redo",
+    "history",
+    "pair",
+    "workpad-integrated",
+    "ai",
+    "heaven-history",
+];
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub(crate) struct CliOutput {
+    pub(crate) stdout: String,
+    pub(crate) stderr: String,
+    pub(crate) exit_code: Option<i32>,
+    pub(crate) duration_ms: u64,
+    /// Parsed stdout when the caller asked for JSON.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) json: Option<Value>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum CliError {
+    /// `evogitctl` is not on PATH or could not be started.
+    NotInstalled(String),
+    /// Exit code 2: click rejected the arguments.
+    Usage {
+        args: String,
+        stderr: String,
+    },
+    Failed {
+        args: String,
+        code: i32,
+        stderr: String,
+    },
+    /// Terminated by a signal.
+    Killed {
+        args: String,
+    },
+    Timeout {
+        args: String,
+        after: Duration,
+    },
+    /// JSON was asked for but stdout isn't JSON.
+    Parse {
+        args: String,
+        error: String,
+    },
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::NotInstalled(reason) => write!(f, "Failed to execute evogitctl: {}", reason),
+            CliError::Usage { args, stderr } => {
+                write!(f, "evogitctl {} rejected its arguments: {}", args, stderr)
+            }
+            CliError::Failed { args, stderr, .. } => {
+                write!(f, "evogitctl {} failed: {}", args, stderr)
+            }
+            CliError::Killed { args } => write!(f, "evogitctl {} was killed", args),
+            CliError::Timeout { args, after } => {
+                write!(f, "evogitctl {} timed out after {}s", args, after.as_secs())
+            }
+            CliError::Parse { args, error } => {
+                write!(f, "evogitctl {} returned invalid JSON: {}", args, error)
+            }
+        }
+    }
+}
+
+impl From<CliError> for String {
+    fn from(error: CliError) -> String {
+        error.to_string()
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct CliOutputLine {
+    stream_id: String,
+    /// "stdout" or "stderr"
+    stream: &'static str,
+    line: String,
+}
+
+#[derive(Default)]
+pub(crate) struct CliOptions {
+    /// Parse stdout as JSON. The arguments must ask for JSON themselves,
+    /// e.g. a subcommand's `--json` flag; the CLI has no global switch.
+    pub(crate) parse_json: bool,
+    pub(crate) timeout: Option<Duration>,
+    /// Emit each output line as a "cli-output" event tagged with this ID.
+    pub(crate) stream: Option<(tauri::AppHandle, String)>,
+}
+
+/// Run `evogitctl` with the given arguments and default options.
+pub(crate) fn run_cli_command(args: Vec<String>, parse_json: bool) -> Result<CliOutput, CliError> {
+    run_cli(
+        args,
+        CliOptions {
+            parse_json,
+            ..CliOptions::default()
+        },
+    )
+}
+
+fn collect_lines(
+    reader: impl Read + Send + 'static,
+    stream: &'static str,
+    sink: Arc<Mutex<String>>,
+    events: Option<(tauri::AppHandle, String)>,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        for line in BufReader::new(reader).lines().map_while(Result::ok) {
+            if let Some((app, stream_id)) = &events {
+                warn_on_err(
+                    "Failed to emit cli-output",
+                    app.emit_all(
+                        "cli-output",
+                        CliOutputLine {
+                            stream_id: stream_id.clone(),
+                            stream,
+                            line: line.clone(),
+                        },
+                    ),
+                );
+            }
+            if let Ok(mut sink) = sink.lock() {
+                sink.push_str(&line);
+                sink.push('\n');
+            }
+        }
+    })
+}
+
+pub(crate) fn run_cli(args: Vec<String>, options: CliOptions) -> Result<CliOutput, CliError> {
+    let joined = args.join(" ");
+    let started = Instant::now();
+    let timeout = options.timeout.unwrap_or(DEFAULT_TIMEOUT);
+
+    // The resident server can't stream, so streamed runs always spawn.
+    if options.stream.is_none() {
+        if let Some(result) = bridge().try_cli(&args, timeout) {
+            let stdout = result.map_err(|error| bridge_error(&joined, error))?;
+            return finish(
+                &joined,
+                options.parse_json,
+                CliOutput {
+                    stdout,
+                    exit_code: Some(0),
+                    duration_ms: started.elapsed().as_millis() as u64,
+                    ..CliOutput::default()
+                },
+            );
+        }
+    }
+
+    let mut command = cli_command();
+    command
+        .args(args.iter())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    // Its own process group, so a timeout also stops whatever it spawned.
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    let mut child = command
+        .spawn()
+        .map_err(|e| CliError::NotInstalled(e.to_string()))?;
+
+    let stdout = Arc::new(Mutex::new(String::new()));
+    let stderr = Arc::new(Mutex::new(String::new()));
+    let mut readers = Vec::new();
+    if let Some(pipe) = child.stdout.take() {
+        readers.push(collect_lines(
+            pipe,
+            "stdout",
+            stdout.clone(),
+            options.stream.clone(),
+        ));
+    }
+    if let Some(pipe) = child.stderr.take() {
+        readers.push(collect_lines(
+            pipe,
+            "stderr",
+            stderr.clone(),
+            options.stream.clone(),
+        ));
+    }
+
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if started.elapsed() >= timeout => {
+                kill_tree(&mut child);
+                warn_on_err("Failed to reap evogitctl", child.wait());
+                tracing::warn!("evogitctl {} timed out after {:?}", joined, timeout);
+                return Err(CliError::Timeout {
+                    args: joined,
+                    after: timeout,
+                });
+            }
+            Ok(None) => thread::sleep(POLL_INTERVAL),
+            Err(e) => return Err(CliError::NotInstalled(e.to_string())),
+        }
+    };
+    let grace = Instant::now() + OUTPUT_GRACE;
+    while readers.iter().any(|reader| !reader.is_finished()) && Instant::now() < grace {
+        thread::sleep(POLL_INTERVAL);
+    }
+    for reader in readers {
+        if !reader.is_finished() {
+            tracing::warn!(
+                "evogitctl {} left a process holding its output open; not waiting for it",
+                joined
+            );
+            continue;
+        }
+        warn_on_err(
+            "evogitctl output reader panicked",
+            reader.join().map_err(|_| "join failed"),
+        );
+    }
+
+    let take = |buffer: &Arc<Mutex<String>>| {
+        buffer
+            .lock()
+            .map(|mut buffer| std::mem::take(&mut *buffer))
+            .unwrap_or_default()
+    };
+    let output = CliOutput {
+        stdout: take(&stdout),
+        stderr: take(&stderr),
+        exit_code: status.code(),
+        duration_ms: started.elapsed().as_millis() as u64,
+        json: None,
+    };
+
+    match status.code() {
+        Some(0) => finish(&joined, options.parse_json, output),
+        code => {
+            tracing::warn!("evogitctl {} exited with {}", joined, status);
+            let stderr = output.stderr.trim().to_string();
+            Err(match code {
+                Some(2) => CliError::Usage {
+                    args: joined,
+                    stderr,
+                },
+                Some(code) => CliError::Failed {
+                    args: joined,
+                    code,
+                    stderr,
+                },
+                None => CliError::Killed { args: joined },
+            })
+        }
+    }
+}
+
+/// The `CliError` a spawned `evogitctl` would have produced for a failed
+/// bridge call.
+fn bridge_error(args: &str, error: BridgeError) -> CliError {
+    let args = args.to_string();
+    match error {
+        BridgeError::Timeout { after, .. } => CliError::Timeout { args, after },
+        BridgeError::Remote(remote) => match remote.exit_code {
+            Some(2) => CliError::Usage {
+                args,
+                stderr: remote.message,
+            },
+            code => CliError::Failed {
+                args,
+                code: code.unwrap_or(1),
+                stderr: remote.message,
+            },
+        },
+        BridgeError::Disconnected => CliError::Killed { args },
+        BridgeError::Unavailable(reason) => CliError::NotInstalled(reason),
+        BridgeError::Protocol(error) => CliError::Parse { args, error },
+    }
+}
+
+fn finish(args: &str, parse_json: bool, mut output: CliOutput) -> Result<CliOutput, CliError> {
+    if parse_json {
+        output.json =
+            Some(
+                serde_json::from_str(output.stdout.trim()).map_err(|e| CliError::Parse {
+                    args: args.to_string(),
+                    error: e.to_string(),
+                })?,
+            );
+    }
+    Ok(output)
+}
+
+/// Run an `evogitctl` command for CLI-only features; the first argument must
+/// be one of `PASSTHROUGH_COMMANDS`. With a `stream_id`, output lines arrive
+/// as "cli-output" events while it runs.
+#[tauri::command]
+pub(crate) fn run_cli_passthrough(
+    app: tauri::AppHandle,
+    args: Vec<String>,
+    parse_json: Option<bool>,
+    timeout_secs: Option<u64>,
+    stream_id: Option<String>,
+) -> Result<CliOutput, String> {
+    let Some(command) = args.first() else {
+        return Err("No evogitctl command given".to_string());
+    };
+    if !PASSTHROUGH_COMMANDS.contains(&command.as_str()) {
+        return Err(format!(
+            "evogitctl {} can't be run from the GUI; expected one of {}",
+            command,
+            PASSTHROUGH_COMMANDS.join(", ")
+        ));
+    }
+    Ok(run_cli(
+        args,
+        CliOptions {
+            parse_json: parse_json.unwrap_or(false),
+            timeout: timeout_secs.map(Duration::from_secs),
+            stream: stream_id.map(|id| (app, id)),
+        },
+    )?)
+}
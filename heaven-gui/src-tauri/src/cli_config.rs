@@ -0,0 +1,243 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::commands::read_json;
+use crate::{get_settings, get_settings_path, profiles, settings, Settings};
+
+/// A setting both the GUI and the CLI know about. `gui` and `cli` are JSON
+/// pointers into `gui_settings.json` and `config.yaml`; `gui` is `None` for
+/// keys only the CLI uses.
+struct SharedKey {
+    key: &'static str,
+    gui: Option<&'static str>,
+    cli: &'static [&'static str],
+    env: Option<&'static str>,
+}
+
+/// Alternatives in `cli` are tried in order: the `ai.models` layout first,
+/// then the flat `models` one older CLI versions wrote.
+const SHARED_KEYS: &[SharedKey] = &[
+    SharedKey {
+        key: "ai.api_key",
+        gui: Some("/ai/api_key"),
+        cli: &["/abacus/api_key"],
+        env: Some("ABACUS_API_KEY"),
+    },
+    SharedKey {
+        key: "ai.base_url",
+        gui: Some("/ai/base_url"),
+        cli: &["/abacus/endpoint"],
+        env: Some("ABACUS_API_ENDPOINT"),
+    },
+    SharedKey {
+        key: "ai.model",
+        gui: Some("/ai/model"),
+        cli: &["/ai/models/coding/primary", "/models/coding_model"],
+        env: None,
+    },
+    SharedKey {
+        key: "ai.max_tokens",
+        gui: Some("/ai/max_tokens"),
+        cli: &["/ai/models/coding/max_tokens", "/models/coding_max_tokens"],
+        env: None,
+    },
+    SharedKey {
+        key: "ai.temperature",
+        gui: Some("/ai/temperature"),
+        cli: &[
+            "/ai/models/coding/temperature",
+            "/models/coding_temperature",
+        ],
+        env: None,
+    },
+    SharedKey {
+        key: "cost.warning_threshold",
+        gui: Some("/cost/warning_threshold"),
+        cli: &["/budget/alert_threshold"],
+        env: None,
+    },
+    SharedKey {
+        key: "budget.daily_usd_cap",
+        gui: None,
+        cli: &["/budget/daily_usd_cap"],
+        env: Some("DAILY_USD_CAP"),
+    },
+    SharedKey {
+        key: "repos_path",
+        gui: None,
+        cli: &["/repos_path"],
+        env: Some("SOLOGIT_REPOS_PATH"),
+    },
+];
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct EffectiveValue {
+    key: String,
+    value: Value,
+    /// "environment", "gui_settings", "cli_config" or "default"
+    source: String,
+    /// The file or variable the value was read from, when not a default.
+    origin: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct EffectiveConfig {
+    cli_config_path: String,
+    cli_config_found: bool,
+    /// Set when `config.yaml` exists but couldn't be parsed.
+    cli_config_error: Option<String>,
+    values: Vec<EffectiveValue>,
+}
+
+/// The CLI's config file, where `evogitctl` would look for it.
+pub(crate) fn config_path() -> PathBuf {
+    match env::var("SOLOGIT_CONFIG_PATH") {
+        Ok(path) if !path.trim().is_empty() => PathBuf::from(path.trim()),
+        _ => profiles::active_home().join("config.yaml"),
+    }
+}
+
+/// `config.yaml` as JSON, or `None` when there isn't one.
+fn read_cli_config() -> Result<Option<Value>, String> {
+    let path = config_path();
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    let value: Value = serde_yaml::from_str(&contents)
+        .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+    // An empty file parses as null, which the CLI treats as no overrides.
+    Ok(Some(value).filter(|value| !value.is_null()))
+}
+
+/// Environment values arrive as strings; the budget cap is parsed the way
+/// the CLI's `float()` would.
+fn env_value(name: &str) -> Option<Value> {
+    let raw = env::var(name).ok().filter(|raw| !raw.trim().is_empty())?;
+    if name == "DAILY_USD_CAP" {
+        return raw.trim().parse::<f64>().ok().map(Value::from);
+    }
+    Some(Value::String(raw))
+}
+
+fn cli_value(cli: Option<&Value>, pointers: &[&str]) -> Option<Value> {
+    let cli = cli?;
+    pointers
+        .iter()
+        .find_map(|pointer| cli.pointer(pointer))
+        .filter(|value| !value.is_null())
+        .cloned()
+}
+
+/// Resolve every shared key: environment over values saved in the GUI's
+/// settings file, over `config.yaml`, over the GUI's defaults.
+fn resolve(gui_file: Option<&Value>, cli: Option<&Value>, defaults: &Value) -> Vec<EffectiveValue> {
+    let settings_path = get_settings_path().display().to_string();
+    let cli_path = config_path().display().to_string();
+    SHARED_KEYS
+        .iter()
+        .map(|shared| {
+            let from_env = shared
+                .env
+                .and_then(|name| env_value(name).map(|value| (value, name.to_string())));
+            let from_gui = shared
+                .gui
+                .and_then(|pointer| gui_file?.pointer(pointer))
+                .filter(|value| !value.is_null())
+                .cloned();
+            let (value, source, origin) = if let Some((value, name)) = from_env {
+                (value, "environment", Some(name))
+            } else if let Some(value) = from_gui {
+                (value, "gui_settings", Some(settings_path.clone()))
+            } else if let Some(value) = cli_value(cli, shared.cli) {
+                (value, "cli_config", Some(cli_path.clone()))
+            } else {
+                let value = shared
+                    .gui
+                    .and_then(|pointer| defaults.pointer(pointer))
+                    .cloned()
+                    .unwrap_or(Value::Null);
+                (value, "default", None)
+            };
+            EffectiveValue {
+                key: shared.key.to_string(),
+                value,
+                source: source.to_string(),
+                origin,
+            }
+        })
+        .collect()
+}
+
+fn gui_file() -> Option<Value> {
+    read_json::<Value>(&get_settings_path()).ok().flatten()
+}
+
+/// Fill in `settings` from the environment and `config.yaml` where the GUI
+/// hasn't saved its own value, so the AI client and the CLI agree on model
+/// and credentials. Values that don't fit the GUI's types are skipped.
+fn apply_to(settings: Settings) -> Settings {
+    let cli = match read_cli_config() {
+        Ok(cli) => cli,
+        Err(e) => {
+            tracing::warn!("Ignoring CLI config: {}", e);
+            None
+        }
+    };
+    let Ok(mut merged) = serde_json::to_value(&settings) else {
+        return settings;
+    };
+    let resolved = resolve(gui_file().as_ref(), cli.as_ref(), &merged);
+    let mut changed = false;
+    for (shared, effective) in SHARED_KEYS.iter().zip(&resolved) {
+        let Some(pointer) = shared.gui else {
+            continue;
+        };
+        if effective.source == "environment" || effective.source == "cli_config" {
+            if let Some(slot) = merged.pointer_mut(pointer) {
+                *slot = effective.value.clone();
+                changed = true;
+            }
+        }
+    }
+    if !changed {
+        return settings;
+    }
+    match serde_json::from_value::<Settings>(merged) {
+        Ok(resolved) if settings::validate(&resolved).is_ok() => resolved,
+        _ => {
+            tracing::warn!("CLI config values don't fit the GUI settings; ignoring them");
+            settings
+        }
+    }
+}
+
+/// Settings as the app runs with them: `gui_settings.json` plus values from
+/// the environment and `config.yaml`. Read-only: anything that saves settings
+/// starts from `get_settings()`, or the API key from the environment and a
+/// snapshot of `config.yaml` would end up in the GUI's file.
+pub(crate) fn effective_settings() -> Result<Settings, String> {
+    get_settings().map(apply_to)
+}
+
+/// Each shared setting's effective value and where it came from.
+#[tauri::command]
+pub(crate) fn get_effective_config() -> Result<EffectiveConfig, String> {
+    let (cli, cli_config_error) = match read_cli_config() {
+        Ok(cli) => (cli, None),
+        Err(e) => (None, Some(e)),
+    };
+    let defaults = serde_json::to_value(Settings::default())
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    Ok(EffectiveConfig {
+        cli_config_path: config_path().display().to_string(),
+        cli_config_found: cli.is_some(),
+        cli_config_error,
+        values: resolve(gui_file().as_ref(), cli.as_ref(), &defaults),
+    })
+}
@@ -1,7 +1,7 @@
-use std::collections::HashSet;
+use std::collections::HashMap;
 use std::env;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use chrono::Utc;
@@ -10,12 +10,24 @@ use serde::Serialize;
 use serde_json::{json, Map, Value};
 use uuid::Uuid;
 
+use crate::audit::{audited, summarize};
+use crate::cli::run_cli_command;
+use crate::lifecycle::{self, WorkpadStatus};
+use crate::logging::warn_on_err;
+use crate::state_cache::state_cache;
+use crate::statuses::PromotionDecision;
 use crate::{
-    get_state_dir, list_test_runs, AIOperation, GlobalState, PromotionRecord, RepositoryState,
-    TestRun, WorkpadState,
+    ai, ai_patch, checkpoints, conflicts, cost, dependency_audit, diff_summary, hooks, ledger,
+    metrics, migrations, notifications, patches, paths, profiles, review, secrets, signing,
+    templates, webhooks, worktrees,
+};
+use crate::{
+    get_repos_dir, get_settings, get_state_dir, list_test_runs, write_settings, AIOperation,
+    GlobalState, PromotionRecord, RepositoryState, Settings, TestRun, WorkpadState,
+    WORKPAD_PRIORITIES,
 };
 
-fn read_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, String> {
+pub(crate) fn read_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, String> {
     if !path.exists() {
         return Ok(None);
     }
@@ -27,7 +39,7 @@ fn read_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, String> {
     Ok(Some(value))
 }
 
-fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+pub(crate) fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)
             .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
@@ -39,68 +51,91 @@ fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
     fs::write(&tmp_path, contents)
         .map_err(|e| format!("Failed to write {}: {}", tmp_path.display(), e))?;
     match fs::rename(&tmp_path, path) {
-        Ok(_) => Ok(()),
+        Ok(_) => {
+            state_cache().invalidate_path(path);
+            Ok(())
+        }
         Err(e) => {
-            // Attempt to clean up the temporary file, ignore any error from remove
-            let _ = fs::remove_file(&tmp_path);
+            // Attempt to clean up the temporary file; the rename error is what matters
+            warn_on_err(
+                "Failed to remove temporary file",
+                fs::remove_file(&tmp_path),
+            );
             Err(format!("Failed to persist {}: {}", path.display(), e))
         }
     }
 }
 
-fn run_cli_command(args: Vec<String>) -> Result<String, String> {
-    let mut command = Command::new("evogitctl");
-    command.args(args.iter());
-
+/// `evogitctl` with the active profile's home and config in its environment.
+pub(crate) fn cli_command() -> Command {
+    let mut command = Command::new(paths::current().cli());
     if let Ok(config_path) = env::var("SOLOGIT_CONFIG_PATH") {
         command.env("SOLOGIT_CONFIG_PATH", config_path);
+    } else if profiles::active_profile_name() != profiles::DEFAULT_PROFILE {
+        let config_path = profiles::active_home().join("config.yaml");
+        command.env("SOLOGIT_CONFIG_PATH", config_path);
     }
-
-    let output = command
-        .output()
-        .map_err(|e| format!("Failed to execute evogitctl: {}", e))?;
-
-    if output.status.success() {
-        Ok(String::from_utf8_lossy(&output.stdout).to_string())
-    } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        Err(format!(
-            "evogitctl {} failed: {}",
-            args.join(" "),
-            stderr.trim()
-        ))
-    }
+    command.env("SOLOGIT_HOME", profiles::active_home());
+    command.envs(signing::git_config_env());
+    command
 }
 
-fn load_global_state() -> Result<GlobalState, String> {
+pub(crate) fn load_global_state() -> Result<GlobalState, String> {
     let path = get_state_dir().join("global.json");
-    Ok(
-        read_json::<GlobalState>(&path)?.unwrap_or_else(|| GlobalState {
-            version: "0.4.0".to_string(),
-            last_updated: Utc::now().to_rfc3339(),
-            active_repo: None,
-            active_workpad: None,
-            session_start: Utc::now().to_rfc3339(),
-            total_operations: 0,
-            total_cost_usd: 0.0,
-        }),
-    )
+    let mut global = match read_json::<GlobalState>(&path)? {
+        Some(global) => global,
+        None => {
+            // Persist the default so `session_start`, which names the
+            // session's ledger, stays the same across calls.
+            let global = GlobalState {
+                version: migrations::CURRENT_SCHEMA_VERSION.to_string(),
+                last_updated: Utc::now().to_rfc3339(),
+                active_repo: None,
+                active_workpad: None,
+                session_start: Utc::now().to_rfc3339(),
+                total_operations: 0,
+                total_cost_usd: 0.0,
+            };
+            write_json(&path, &global)?;
+            global
+        }
+    };
+    ledger::apply_totals(&mut global)?;
+    Ok(global)
 }
 
-fn save_global_state(mut state: GlobalState) -> Result<(), String> {
+pub(crate) fn save_global_state(mut state: GlobalState) -> Result<(), String> {
     state.last_updated = Utc::now().to_rfc3339();
     let path = get_state_dir().join("global.json");
     write_json(&path, &state)
 }
 
-fn load_repository(repo_id: &str) -> Result<RepositoryState, String> {
+pub(crate) fn load_repository(repo_id: &str) -> Result<RepositoryState, String> {
     let path = get_state_dir()
         .join("repositories")
         .join(format!("{}.json", repo_id));
     read_json(&path)?.ok_or_else(|| format!("Repository not found: {}", repo_id))
 }
 
-fn save_repository(mut repo: RepositoryState) -> Result<RepositoryState, String> {
+/// Resolve the working directory of a repository, preferring the path recorded
+/// in state (repositories created with `--path` live outside the data dir).
+pub(crate) fn resolve_repo_path(repo_id: &str) -> Result<PathBuf, String> {
+    let managed = get_repos_dir().join(repo_id);
+    if let Ok(repo) = load_repository(repo_id) {
+        let recorded = PathBuf::from(&repo.path);
+        if recorded.is_dir() {
+            return Ok(recorded);
+        }
+    }
+
+    if managed.is_dir() {
+        Ok(managed)
+    } else {
+        Err(format!("Repository directory not found: {}", repo_id))
+    }
+}
+
+pub(crate) fn save_repository(mut repo: RepositoryState) -> Result<RepositoryState, String> {
     repo.updated_at = Utc::now().to_rfc3339();
     let path = get_state_dir()
         .join("repositories")
@@ -109,14 +144,14 @@ fn save_repository(mut repo: RepositoryState) -> Result<RepositoryState, String>
     Ok(repo)
 }
 
-fn load_workpad(workpad_id: &str) -> Result<WorkpadState, String> {
+pub(crate) fn load_workpad(workpad_id: &str) -> Result<WorkpadState, String> {
     let path = get_state_dir()
         .join("workpads")
         .join(format!("{}.json", workpad_id));
     read_json(&path)?.ok_or_else(|| format!("Workpad not found: {}", workpad_id))
 }
 
-fn save_workpad(mut workpad: WorkpadState) -> Result<WorkpadState, String> {
+pub(crate) fn save_workpad(mut workpad: WorkpadState) -> Result<WorkpadState, String> {
     workpad.updated_at = Utc::now().to_rfc3339();
     let path = get_state_dir()
         .join("workpads")
@@ -125,21 +160,12 @@ fn save_workpad(mut workpad: WorkpadState) -> Result<WorkpadState, String> {
     Ok(workpad)
 }
 
-fn parse_changed_files(diff: &str) -> Vec<String> {
-    let mut files: HashSet<String> = HashSet::new();
-    for line in diff.lines() {
-        if let Some(rest) = line.strip_prefix("+++ b/") {
-            files.insert(rest.trim().to_string());
-        } else if let Some(rest) = line.strip_prefix("--- a/") {
-            files.insert(rest.trim().to_string());
-        }
-    }
-    let mut list: Vec<String> = files.into_iter().collect();
-    list.sort();
-    list
+/// Directory where a workpad's files are checked out for tests and tools.
+pub(crate) fn workpad_checkout_dir(workpad: &WorkpadState) -> Result<PathBuf, String> {
+    worktrees::checkout_for(workpad)
 }
 
-fn merge_json(target: &mut Map<String, Value>, updates: Map<String, Value>) {
+pub(crate) fn merge_json(target: &mut Map<String, Value>, updates: Map<String, Value>) {
     for (key, value) in updates {
         match (target.get_mut(&key), value) {
             (Some(Value::Object(target_map)), Value::Object(update_map)) => {
@@ -154,18 +180,27 @@ fn merge_json(target: &mut Map<String, Value>, updates: Map<String, Value>) {
 
 #[tauri::command]
 pub(crate) fn create_workpad(repo_id: String, title: String) -> Result<WorkpadState, String> {
+    audited("create_workpad", "workpad", None, None, move || {
+        start_workpad(repo_id, title)
+    })
+}
+
+fn start_workpad(repo_id: String, title: String) -> Result<WorkpadState, String> {
     let trimmed = title.trim();
     if trimmed.is_empty() {
         return Err("Workpad title cannot be empty".to_string());
     }
 
-    run_cli_command(vec![
-        "workpad-integrated".to_string(),
-        "create".to_string(),
-        trimmed.to_string(),
-        "--repo".to_string(),
-        repo_id.clone(),
-    ])?;
+    run_cli_command(
+        vec![
+            "workpad-integrated".to_string(),
+            "create".to_string(),
+            trimmed.to_string(),
+            "--repo".to_string(),
+            repo_id.clone(),
+        ],
+        false,
+    )?;
 
     let global = load_global_state()?;
     let workpad_id = global
@@ -175,34 +210,163 @@ pub(crate) fn create_workpad(repo_id: String, title: String) -> Result<WorkpadSt
     load_workpad(&workpad_id)
 }
 
+/// Replace a workpad's tags, priority and description. Omitted fields are
+/// left unchanged.
+#[tauri::command]
+pub(crate) fn update_workpad_metadata(
+    workpad_id: String,
+    tags: Option<Vec<String>>,
+    priority: Option<String>,
+    description: Option<String>,
+) -> Result<WorkpadState, String> {
+    let before = load_workpad(&workpad_id)
+        .ok()
+        .and_then(|workpad| summarize(&workpad));
+    audited(
+        "update_workpad_metadata",
+        "workpad",
+        Some(workpad_id.clone()),
+        before,
+        move || {
+            let mut workpad = load_workpad(&workpad_id)?;
+
+            if let Some(tags) = tags {
+                let mut cleaned: Vec<String> = Vec::new();
+                for tag in tags {
+                    let tag = tag.trim().to_lowercase();
+                    if !tag.is_empty() && !cleaned.contains(&tag) {
+                        cleaned.push(tag);
+                    }
+                }
+                workpad.tags = cleaned;
+            }
+            if let Some(priority) = priority {
+                let priority = priority.trim().to_lowercase();
+                if !WORKPAD_PRIORITIES.contains(&priority.as_str()) {
+                    return Err(format!(
+                        "Unknown priority {} (expected one of {})",
+                        priority,
+                        WORKPAD_PRIORITIES.join(", ")
+                    ));
+                }
+                workpad.priority = Some(priority);
+            }
+            if let Some(description) = description {
+                workpad.description = description.trim().to_string();
+            }
+
+            save_workpad(workpad)
+        },
+    )
+}
+
+/// Run `target` against the workpad. An empty target falls back to the
+/// target set by the template the workpad was created from.
 #[tauri::command]
 pub(crate) fn run_tests(workpad_id: String, target: String) -> Result<TestRun, String> {
-    let trimmed = target.trim();
+    audited(
+        "run_tests",
+        "workpad",
+        Some(workpad_id.clone()),
+        None,
+        move || run_target(workpad_id, target),
+    )
+}
+
+fn run_target(workpad_id: String, target: String) -> Result<TestRun, String> {
+    let workpad = load_workpad(&workpad_id)?;
+    let trimmed = match target.trim() {
+        "" => workpad.test_target.as_deref().map(str::trim).unwrap_or(""),
+        target => target,
+    };
     if trimmed.is_empty() {
         return Err("Test target cannot be empty".to_string());
     }
-
-    run_cli_command(vec![
-        "test".to_string(),
-        "run".to_string(),
-        workpad_id.clone(),
-        "--target".to_string(),
-        trimmed.to_string(),
-    ])?;
-
-    let mut runs = list_test_runs(Some(workpad_id.clone()))?;
-    runs.into_iter()
+    lifecycle::ensure_transition(&workpad, WorkpadStatus::Testing)?;
+    // The CLI runs in the workpad's worktree when one holds its branch.
+    workpad_checkout_dir(&workpad)?;
+
+    run_cli_command(
+        vec![
+            "test".to_string(),
+            "run".to_string(),
+            workpad_id.clone(),
+            "--target".to_string(),
+            trimmed.to_string(),
+        ],
+        false,
+    )?;
+
+    // Bypass the cache: the watcher may not have seen the new run yet.
+    let run = list_test_runs(Some(workpad_id.clone()), Some(true))?
+        .into_iter()
         .next()
-        .ok_or_else(|| "No test runs recorded".to_string())
+        .ok_or_else(|| "No test runs recorded".to_string())?;
+    webhooks::fire_test_run(&run);
+    notifications::test_run_finished(&run);
+    metrics::record_test_run(&run);
+    Ok(run)
 }
 
 #[tauri::command]
 pub(crate) fn promote_workpad(workpad_id: String) -> Result<PromotionRecord, String> {
-    run_cli_command(vec![
-        "workpad-integrated".to_string(),
-        "promote".to_string(),
-        workpad_id.clone(),
-    ])?;
+    let before = load_workpad(&workpad_id)
+        .ok()
+        .and_then(|workpad| summarize(&workpad));
+    let promoted_id = workpad_id.clone();
+    let result = audited(
+        "promote_workpad",
+        "workpad",
+        Some(workpad_id.clone()),
+        before,
+        move || promote_through_cli(workpad_id),
+    );
+
+    match &result {
+        Ok(record) => webhooks::fire("promotion", json!({ "promoted": true, "record": record })),
+        Err(error) => webhooks::fire(
+            "promotion",
+            json!({ "promoted": false, "workpad_id": promoted_id, "error": error }),
+        ),
+    }
+    notifications::promotion_finished(&promoted_id, &result);
+    result
+}
+
+fn promote_through_cli(workpad_id: String) -> Result<PromotionRecord, String> {
+    let workpad = load_workpad(&workpad_id)?;
+    lifecycle::ensure_promotable(&workpad)?;
+    secrets::ensure_clean(&workpad)?;
+    let git_settings = get_settings()?.git;
+    if let Some(Err(e)) =
+        dependency_audit::gate_result(&workpad.repo_id, &git_settings.dependency_audit)
+    {
+        return Err(format!("Promotion blocked: {}", e));
+    }
+    if let Some(Err(e)) = review::gate_result(&workpad, &git_settings.review) {
+        return Err(format!("Promotion blocked: {}", e));
+    }
+    hooks::run_hooks(&workpad, "promote", None)?;
+    let summary = diff_summary::current_summary(&workpad);
+    worktrees::remove_worktree(&workpad)?;
+
+    run_cli_command(
+        vec![
+            "workpad-integrated".to_string(),
+            "promote".to_string(),
+            workpad_id.clone(),
+        ],
+        false,
+    )?;
+
+    // The CLI marks the workpad promoted; the time is what archiving,
+    // release notes and stats key on.
+    let mut promoted = load_workpad(&workpad_id)?;
+    if promoted.promoted_at.is_none() {
+        promoted.status = WorkpadStatus::Promoted;
+        promoted.promoted_at = Some(Utc::now().to_rfc3339());
+        save_workpad(promoted)?;
+    }
 
     // Attempt to locate the most recent promotion record for this workpad
     let promotions_dir = get_state_dir().join("promotions");
@@ -239,12 +403,18 @@ pub(crate) fn promote_workpad(workpad_id: String) -> Result<PromotionRecord, Str
         record_id: format!("pr-{}", Uuid::new_v4().simple()),
         repo_id: workpad.repo_id.clone(),
         workpad_id: workpad.workpad_id.clone(),
-        decision: "manual".to_string(),
+        decision: PromotionDecision::Manual,
         can_promote: true,
         auto_promote_requested: false,
         promoted: true,
         commit_hash: workpad.current_commit.clone(),
-        message: format!("Workpad '{}' promoted to trunk", workpad.title),
+        message: match summary {
+            Some(summary) => format!(
+                "Workpad '{}' promoted to trunk\n\n{}",
+                workpad.title, summary
+            ),
+            None => format!("Workpad '{}' promoted to trunk", workpad.title),
+        },
         test_run_id: workpad.test_runs.first().cloned(),
         ci_status: None,
         ci_message: None,
@@ -259,10 +429,31 @@ pub(crate) fn apply_patch(
     workpad_id: String,
     message: String,
     diff: String,
+) -> Result<WorkpadState, String> {
+    let before = load_workpad(&workpad_id)
+        .ok()
+        .and_then(|workpad| summarize(&workpad));
+    audited(
+        "apply_patch",
+        "workpad",
+        Some(workpad_id.clone()),
+        before,
+        move || apply_workpad_patch(workpad_id, message, diff),
+    )
+}
+
+fn apply_workpad_patch(
+    workpad_id: String,
+    message: String,
+    diff: String,
 ) -> Result<WorkpadState, String> {
     if diff.trim().is_empty() {
         return Err("Patch diff cannot be empty".to_string());
     }
+    let workpad = load_workpad(&workpad_id)?;
+    lifecycle::ensure_transition(&workpad, WorkpadStatus::Active)?;
+    hooks::run_hooks(&workpad, "apply_patch", Some(&diff))?;
+    workpad_checkout_dir(&workpad)?;
 
     let trimmed_message = message.trim();
     let final_message = if trimmed_message.is_empty() {
@@ -272,7 +463,7 @@ pub(crate) fn apply_patch(
     };
 
     let temp_path = env::temp_dir().join(format!("sologit_patch_{}.diff", Uuid::new_v4().simple()));
-    fs::write(&temp_path, diff).map_err(|e| format!("Failed to write temporary patch: {}", e))?;
+    fs::write(&temp_path, &diff).map_err(|e| format!("Failed to write temporary patch: {}", e))?;
 
     let patch_arg = temp_path
         .to_str()
@@ -289,22 +480,96 @@ pub(crate) fn apply_patch(
         final_message.to_string(),
     ];
 
-    let result = run_cli_command(cli_args);
-
-    let _ = fs::remove_file(&temp_path);
-    result?;
+    let result = run_cli_command(cli_args, false);
+
+    if let Err(error) = result {
+        // Fall back to a three-way apply so conflicts can be resolved in the GUI.
+        let workpad = load_workpad(&workpad_id)?;
+        let outcome = conflicts::apply_three_way(&workpad, final_message, &diff, &temp_path, None);
+        warn_on_err(
+            "Failed to remove temporary patch",
+            fs::remove_file(&temp_path),
+        );
+        return match outcome {
+            Ok(files) if files.is_empty() => load_workpad(&workpad_id),
+            Ok(files) => Err(format!(
+                "Patch conflicts in {} file(s); resolve them with resolve_conflict",
+                files.len()
+            )),
+            Err(_) => Err(error.into()),
+        };
+    }
+    warn_on_err(
+        "Failed to remove temporary patch",
+        fs::remove_file(&temp_path),
+    );
 
-    load_workpad(&workpad_id)
+    let workpad = load_workpad(&workpad_id)?;
+    let checkpoint = checkpoints::record_checkpoint(&workpad, final_message)?;
+    patches::store_patch(
+        &workpad_id,
+        final_message,
+        &diff,
+        Some(checkpoint.checkpoint_id),
+    )?;
+    Ok(workpad)
 }
 
 #[tauri::command]
 pub(crate) fn trigger_ai_operation(
+    window: tauri::Window,
+    workpad_id: String,
+    prompt: String,
+    operation_type: Option<String>,
+    template_id: Option<String>,
+    variables: Option<HashMap<String, String>>,
+) -> Result<AIOperation, String> {
+    audited(
+        "trigger_ai_operation",
+        "ai_operation",
+        None,
+        None,
+        move || {
+            run_ai_operation(
+                window,
+                workpad_id,
+                prompt,
+                operation_type,
+                template_id,
+                variables,
+            )
+        },
+    )
+}
+
+fn run_ai_operation(
+    window: tauri::Window,
     workpad_id: String,
     prompt: String,
+    operation_type: Option<String>,
+    template_id: Option<String>,
+    variables: Option<HashMap<String, String>>,
 ) -> Result<AIOperation, String> {
+    let prompt = match template_id {
+        Some(template_id) => {
+            let mut values = variables.unwrap_or_default();
+            values.insert("prompt".to_string(), prompt);
+            let workpad = Some(workpad_id.as_str()).filter(|id| !id.trim().is_empty());
+            templates::render_template(&template_id, workpad, values)?
+        }
+        None => prompt,
+    };
     if prompt.trim().is_empty() {
         return Err("Prompt cannot be empty".to_string());
     }
+    if operation_type.as_deref() == Some("generate_patch") {
+        if workpad_id.trim().is_empty() {
+            return Err("Patch generation requires a workpad".to_string());
+        }
+        let operation = ai_patch::propose_patch(&workpad_id, &prompt)?;
+        cost::notify_budget(&window);
+        return Ok(operation);
+    }
 
     let workpad_opt = if workpad_id.trim().is_empty() {
         None
@@ -316,53 +581,41 @@ pub(crate) fn trigger_ai_operation(
         load_workpad(wp_id)?;
     }
 
-    let operation_id = format!("op-{}", Uuid::new_v4().simple());
-    let started_at = Utc::now();
-    let tokens_used = (prompt.len() as f64 / 4.0).ceil() as i32;
-    let cost = (tokens_used as f64) * 0.00002;
-
-    let operation = AIOperation {
-        operation_id: operation_id.clone(),
-        workpad_id: workpad_opt.clone(),
-        operation_type: "prompt".to_string(),
-        status: "completed".to_string(),
-        model: "gpt-4".to_string(),
-        prompt: prompt.clone(),
-        response: Some("AI orchestration placeholder response".to_string()),
-        cost_usd: cost,
-        tokens_used,
-        started_at: started_at.to_rfc3339(),
-        completed_at: Some((started_at + chrono::Duration::seconds(1)).to_rfc3339()),
-        error: None,
-    };
-
-    let path = get_state_dir()
-        .join("ai_operations")
-        .join(format!("{}.json", operation.operation_id));
-    write_json(&path, &operation)?;
-
-    if let Some(wp_id) = &workpad_opt {
-        let mut workpad = load_workpad(wp_id)?;
-        workpad.ai_operations.insert(0, operation_id.clone());
-        let _ = save_workpad(workpad)?;
-    }
-
-    let mut global = load_global_state()?;
-    global.total_operations += 1;
-    global.total_cost_usd += cost;
-    save_global_state(global)?;
+    let messages = [ai::ChatMessage::user(prompt.clone())];
+    let (operation, _) = ai::run_operation(workpad_opt, "prompt", &prompt, &messages, None)?;
+    cost::notify_budget(&window);
 
     Ok(operation)
 }
 
 #[tauri::command]
 pub(crate) fn delete_workpad(workpad_id: String) -> Result<(), String> {
-    run_cli_command(vec![
-        "workpad-integrated".to_string(),
-        "delete".to_string(),
-        workpad_id,
-        "--force".to_string(),
-    ])?;
+    let before = load_workpad(&workpad_id)
+        .ok()
+        .and_then(|workpad| summarize(&workpad));
+    audited(
+        "delete_workpad",
+        "workpad",
+        Some(workpad_id.clone()),
+        before,
+        move || remove_workpad(workpad_id),
+    )
+}
+
+fn remove_workpad(workpad_id: String) -> Result<(), String> {
+    let workpad = load_workpad(&workpad_id)?;
+    lifecycle::ensure_transition(&workpad, WorkpadStatus::Deleted)?;
+    worktrees::remove_worktree(&workpad)?;
+
+    run_cli_command(
+        vec![
+            "workpad-integrated".to_string(),
+            "delete".to_string(),
+            workpad_id,
+            "--force".to_string(),
+        ],
+        false,
+    )?;
     Ok(())
 }
 
@@ -370,58 +623,97 @@ pub(crate) fn delete_workpad(workpad_id: String) -> Result<(), String> {
 pub(crate) fn rollback_workpad(
     workpad_id: String,
     reason: Option<String>,
+    checkpoint_id: Option<String>,
 ) -> Result<WorkpadState, String> {
-    let mut workpad = load_workpad(&workpad_id)?;
-    workpad.status = "draft".to_string();
-    workpad.current_commit = Some(workpad.base_commit.clone());
-    workpad.patches_applied = 0;
-    workpad.files_changed.clear();
-    workpad.test_runs.clear();
+    let before = load_workpad(&workpad_id)
+        .ok()
+        .and_then(|workpad| summarize(&workpad));
+    audited(
+        "rollback_workpad",
+        "workpad",
+        Some(workpad_id.clone()),
+        before,
+        move || roll_back(workpad_id, reason, checkpoint_id),
+    )
+}
+
+fn roll_back(
+    workpad_id: String,
+    reason: Option<String>,
+    checkpoint_id: Option<String>,
+) -> Result<WorkpadState, String> {
+    let workpad = load_workpad(&workpad_id)?;
+    lifecycle::ensure_transition(&workpad, WorkpadStatus::Active)?;
+
+    // Default to undoing the most recent checkpoint.
+    let target = match checkpoint_id {
+        Some(id) => id,
+        None => {
+            let timeline = checkpoints::workpad_checkpoints(&workpad)?;
+            let previous = timeline.len().saturating_sub(2);
+            timeline[previous].checkpoint_id.clone()
+        }
+    };
 
     if let Some(reason) = reason {
         let log_path = get_state_dir()
             .join("workpads")
             .join(format!("{}-rollback.log", workpad.workpad_id));
         let entry = format!("{} :: {}\n", Utc::now().to_rfc3339(), reason);
-        let _ = fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&log_path)
-            .and_then(|mut file| {
-                use std::io::Write;
-                file.write_all(entry.as_bytes())
-            });
+        warn_on_err(
+            "Failed to record rollback reason",
+            fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&log_path)
+                .and_then(|mut file| {
+                    use std::io::Write;
+                    file.write_all(entry.as_bytes())
+                }),
+        );
     }
 
-    let workpad = save_workpad(workpad)?;
+    let workpad = checkpoints::restore_checkpoint(workpad, &target)?;
 
     let mut global = load_global_state()?;
-    global.total_operations += 1;
     global.active_workpad = Some(workpad.workpad_id.clone());
     save_global_state(global)?;
+    ledger::record("rollback", Some(&workpad.workpad_id), 0.0)?;
 
     Ok(workpad)
 }
 
+/// Apply a partial settings update, e.g. `{"editor": {"font_size": 16}}`.
+/// Nested objects are merged; the result is validated before it is saved.
 #[tauri::command]
 pub(crate) fn update_config(updates: Value) -> Result<Value, String> {
-    let config_path = get_state_dir().join("config.json");
-    let mut config = read_json::<Value>(&config_path)?
-        .unwrap_or_else(|| json!({"theme": "dark", "auto_save": true }));
+    let current = get_settings()?;
+    let before = summarize(&current);
+    audited("update_config", "settings", None, before, move || {
+        merge_config(current, updates)
+    })
+}
 
+fn merge_config(current: Settings, updates: Value) -> Result<Value, String> {
     let updates_obj = updates
         .as_object()
         .ok_or_else(|| "Configuration updates must be a JSON object".to_string())?
         .clone();
 
-    if let Value::Object(ref mut target) = config {
+    let mut merged = serde_json::to_value(&current)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    if let Value::Object(ref mut target) = merged {
         merge_json(target, updates_obj);
-    } else {
-        config = Value::Object(updates_obj);
     }
-
-    write_json(&config_path, &config)?;
-    Ok(config)
+    let mut settings: Settings =
+        serde_json::from_value(merged).map_err(|e| format!("Invalid settings update: {}", e))?;
+    webhooks::restore_secrets(
+        &mut settings.integrations.webhooks,
+        &current.integrations.webhooks,
+    );
+    write_settings(&settings)?;
+    webhooks::redact_secrets(&mut settings.integrations.webhooks);
+    serde_json::to_value(&settings).map_err(|e| format!("Failed to serialize settings: {}", e))
 }
 
 #[tauri::command]
@@ -429,6 +721,12 @@ pub(crate) fn create_repository(
     name: String,
     path: Option<String>,
 ) -> Result<RepositoryState, String> {
+    audited("create_repository", "repository", None, None, move || {
+        init_repository(name, path)
+    })
+}
+
+fn init_repository(name: String, path: Option<String>) -> Result<RepositoryState, String> {
     let trimmed = name.trim();
     if trimmed.is_empty() {
         return Err("Repository name cannot be empty".to_string());
@@ -446,7 +744,7 @@ pub(crate) fn create_repository(
         args.push(custom);
     }
 
-    run_cli_command(args)?;
+    run_cli_command(args, false)?;
 
     // CLI commands set the active repository to the one created.
     let global = load_global_state()?;
@@ -459,6 +757,22 @@ pub(crate) fn create_repository(
 
 #[tauri::command]
 pub(crate) fn delete_repository(repo_id: String) -> Result<(), String> {
-    run_cli_command(vec!["repo".to_string(), "delete".to_string(), repo_id])?;
+    let before = load_repository(&repo_id)
+        .ok()
+        .and_then(|repo| summarize(&repo));
+    audited(
+        "delete_repository",
+        "repository",
+        Some(repo_id.clone()),
+        before,
+        move || remove_repository(repo_id),
+    )
+}
+
+fn remove_repository(repo_id: String) -> Result<(), String> {
+    run_cli_command(
+        vec!["repo".to_string(), "delete".to_string(), repo_id],
+        false,
+    )?;
     Ok(())
 }
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+
+use crate::promotion::load_promotions;
+use crate::timestamps;
+use crate::{list_ai_operations, list_test_runs, list_workpads, CommitNode, TestRun};
+
+/// Fill in which workpad produced each commit, the test run that validated
+/// it and what its workpad cost in AI spend. Values already in the cache
+/// (written by the CLI) are kept.
+///
+/// A promotion record names only the tip it fast-forwarded trunk to, so the
+/// commits beneath it are attributed to the same workpad by following
+/// parents until reaching the commit the workpad branched from, another
+/// promotion or a commit older than the workpad itself.
+pub(crate) fn correlate(repo_id: &str, commits: &mut [CommitNode]) -> Result<(), String> {
+    let promotions: HashMap<String, _> = load_promotions()?
+        .into_iter()
+        .filter(|record| record.repo_id == repo_id && record.promoted)
+        .filter_map(|record| Some((record.commit_hash.clone()?, record)))
+        .collect();
+    // Where each workpad starts: its base commit and creation time.
+    let bounds: HashMap<String, (String, Option<DateTime<Utc>>)> =
+        list_workpads(Some(repo_id.to_string()), None, None)?
+            .into_iter()
+            .map(|workpad| {
+                let created_at = timestamps::parse(&workpad.created_at);
+                (workpad.workpad_id, (workpad.base_commit, created_at))
+            })
+            .collect();
+    // Newest first, so the first match per key is the latest.
+    let runs = list_test_runs(None, None)?;
+    let mut costs: HashMap<String, f64> = HashMap::new();
+    for operation in list_ai_operations(None, None, None)? {
+        if let Some(workpad_id) = operation.workpad_id {
+            *costs.entry(workpad_id).or_default() += operation.cost_usd;
+        }
+    }
+
+    let index: HashMap<String, usize> = commits
+        .iter()
+        .enumerate()
+        .map(|(i, commit)| (commit.sha.clone(), i))
+        .collect();
+    for tip in 0..commits.len() {
+        let Some(record) = promotions.get(&commits[tip].sha) else {
+            continue;
+        };
+        commits[tip].promotion_id = Some(record.record_id.clone());
+        commits[tip].cost_usd = costs.get(&record.workpad_id).copied();
+        if commits[tip].test_run_id.is_none() {
+            commits[tip].test_run_id = record.test_run_id.clone();
+        }
+
+        let (base_commit, since) = match bounds.get(&record.workpad_id) {
+            Some((base_commit, since)) => (Some(base_commit.as_str()), *since),
+            None => (None, None),
+        };
+        let mut current = Some(tip);
+        // Bounded in case a hand-edited cache has a parent cycle.
+        for _ in 0..commits.len() {
+            let Some(i) = current else {
+                break;
+            };
+            let commit = &mut commits[i];
+            if i != tip
+                && (promotions.contains_key(&commit.sha)
+                    || base_commit == Some(commit.sha.as_str()))
+            {
+                break;
+            }
+            let before_workpad = since.is_some_and(|since| {
+                timestamps::parse(&commit.timestamp).is_some_and(|at| at < since)
+            });
+            if before_workpad {
+                break;
+            }
+            if commit.workpad_id.is_none() {
+                commit.workpad_id = Some(record.workpad_id.clone());
+            }
+            current = commit
+                .parent_sha
+                .as_ref()
+                .and_then(|parent| index.get(parent).copied());
+        }
+    }
+
+    for commit in commits.iter_mut() {
+        let validated_by: Option<&TestRun> = match &commit.test_run_id {
+            Some(run_id) => runs.iter().find(|run| run.run_id == *run_id),
+            None => runs
+                .iter()
+                .find(|run| run.commit_sha.as_deref() == Some(commit.sha.as_str())),
+        };
+        if let Some(run) = validated_by {
+            commit.test_run_id = Some(run.run_id.clone());
+            if commit.test_status.is_none() {
+                commit.test_status = Some(run.status.to_string());
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use std::collections::HashSet;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::ai::{parse_json_response, run_operation, ChatMessage};
+use crate::commands::load_workpad;
+use crate::git::workpad_diff;
+use crate::text::clip;
+
+/// Upper bound on diff text sent to the model; larger diffs are truncated.
+const MAX_DIFF_CHARS: usize = 60_000;
+
+const COMMIT_SYSTEM_PROMPT: &str = "You write git commit messages in the Conventional Commits \
+format: `type(optional scope): summary`, where type is one of feat, fix, docs, style, \
+refactor, perf, test, build, ci, chore or revert. Keep the summary under 72 characters, in \
+the imperative mood. Optionally add a blank line and a short body. Reply with JSON only: \
+{\"message\": \"...\", \"alternatives\": [\"...\", \"...\"]}";
+
+const COMMIT_TYPES: &[&str] = &[
+    "feat", "fix", "docs", "style", "refactor", "perf", "test", "build", "ci", "chore", "revert",
+];
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct CommitMessageSuggestion {
+    operation_id: String,
+    message: String,
+    alternatives: Vec<String>,
+    cost_usd: f64,
+}
+
+/// Whether the subject line follows `type(scope)!: summary`.
+fn is_conventional(message: &str) -> bool {
+    let subject = message.lines().next().unwrap_or("");
+    let Some((prefix, summary)) = subject.split_once(": ") else {
+        return false;
+    };
+    let prefix = prefix.trim_end_matches('!');
+    let kind = prefix.split('(').next().unwrap_or(prefix);
+    let scope_ok = match prefix.find('(') {
+        Some(_) => prefix.ends_with(')'),
+        None => true,
+    };
+    COMMIT_TYPES.contains(&kind) && scope_ok && !summary.trim().is_empty()
+}
+
+fn truncate_diff(diff: &str) -> String {
+    let clipped = clip(diff, MAX_DIFF_CHARS);
+    if clipped.len() == diff.len() {
+        return diff.to_string();
+    }
+    format!("{}\n[diff truncated]\n", clipped)
+}
+
+#[tauri::command]
+pub(crate) fn suggest_commit_message(
+    workpad_id: String,
+) -> Result<CommitMessageSuggestion, String> {
+    let workpad = load_workpad(&workpad_id)?;
+    let diff = workpad_diff(&workpad)?;
+    if diff.trim().is_empty() {
+        return Err(format!("Workpad {} has no changes", workpad_id));
+    }
+
+    let messages = [
+        ChatMessage::system(COMMIT_SYSTEM_PROMPT),
+        ChatMessage::user(format!(
+            "Workpad: {}\n\n```diff\n{}```",
+            workpad.title,
+            truncate_diff(&diff)
+        )),
+    ];
+    let (operation, completion) = run_operation(
+        Some(workpad_id.clone()),
+        "commit_message",
+        &format!("Suggest commit message for {}", workpad_id),
+        &messages,
+        None,
+    )?;
+    let completion = completion.ok_or_else(|| {
+        operation
+            .error
+            .clone()
+            .unwrap_or_else(|| "AI request failed".to_string())
+    })?;
+
+    let response = parse_json_response(&completion.content)?;
+    let as_message = |value: &Value| value.as_str().map(|s| s.trim().to_string());
+    let mut candidates: Vec<String> = as_message(&response["message"]).into_iter().collect();
+    if let Some(alternatives) = response["alternatives"].as_array() {
+        candidates.extend(alternatives.iter().filter_map(as_message));
+    }
+
+    // Prefer well-formed messages but keep the rest as fallbacks.
+    let mut seen = HashSet::new();
+    candidates.retain(|candidate| !candidate.is_empty() && seen.insert(candidate.clone()));
+    candidates.sort_by_key(|candidate| !is_conventional(candidate));
+    if candidates.is_empty() {
+        return Err("AI response did not include a commit message".to_string());
+    }
+    let message = candidates.remove(0);
+
+    Ok(CommitMessageSuggestion {
+        operation_id: operation.operation_id,
+        message,
+        alternatives: candidates,
+        cost_usd: operation.cost_usd,
+    })
+}
@@ -0,0 +1,188 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::commands::{load_repository, load_workpad};
+use crate::git::{open_repository, resolve_commit};
+use crate::WorkpadState;
+
+/// How far a workpad branch has moved relative to trunk.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct TrunkDivergence {
+    workpad_id: String,
+    merge_base: String,
+    ahead: usize,
+    behind: usize,
+    files_changed: Vec<String>,
+}
+
+/// Trunk line range touched by a workpad's changes to a file.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct HunkRange {
+    start: u32,
+    lines: u32,
+}
+
+/// Hunks in the same file whose trunk ranges overlap or touch, i.e. where
+/// promoting one workpad will make the other fail to merge cleanly.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct HunkCollision {
+    file_path: String,
+    hunk_a: HunkRange,
+    hunk_b: HunkRange,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct WorkpadComparison {
+    trunk_branch: String,
+    a: TrunkDivergence,
+    b: TrunkDivergence,
+    overlapping_files: Vec<String>,
+    conflicting_hunks: Vec<HunkCollision>,
+    /// Workpad that is cheaper to promote first: fewer commits behind trunk,
+    /// then fewer files touched.
+    suggested_first: String,
+}
+
+struct BranchChanges {
+    divergence: TrunkDivergence,
+    hunks: BTreeMap<String, Vec<HunkRange>>,
+}
+
+fn branch_changes(
+    repo: &git2::Repository,
+    workpad: &WorkpadState,
+    trunk: &str,
+) -> Result<BranchChanges, String> {
+    let trunk_commit = resolve_commit(repo, trunk)?;
+    let tip = resolve_commit(repo, &format!("refs/heads/{}", workpad.branch_name))?;
+    let base_oid = repo
+        .merge_base(trunk_commit.id(), tip.id())
+        .map_err(|e| format!("No merge base for {}: {}", workpad.branch_name, e.message()))?;
+    let (ahead, behind) = repo
+        .graph_ahead_behind(tip.id(), trunk_commit.id())
+        .map_err(|e| format!("Failed to count divergence: {}", e.message()))?;
+
+    let base_tree = repo
+        .find_commit(base_oid)
+        .and_then(|commit| commit.tree())
+        .map_err(|e| format!("Failed to read merge base: {}", e.message()))?;
+    let tip_tree = tip
+        .tree()
+        .map_err(|e| format!("Failed to read tree: {}", e.message()))?;
+
+    let mut options = git2::DiffOptions::new();
+    options.context_lines(0);
+    let diff = repo
+        .diff_tree_to_tree(Some(&base_tree), Some(&tip_tree), Some(&mut options))
+        .map_err(|e| format!("Failed to diff {}: {}", workpad.branch_name, e.message()))?;
+
+    let hunks: RefCell<BTreeMap<String, Vec<HunkRange>>> = RefCell::new(BTreeMap::new());
+    let file_key = |delta: &git2::DiffDelta| {
+        delta
+            .old_file()
+            .path()
+            .or_else(|| delta.new_file().path())
+            .map(|p| p.to_string_lossy().to_string())
+    };
+    diff.foreach(
+        &mut |delta, _| {
+            if let Some(path) = file_key(&delta) {
+                hunks.borrow_mut().entry(path).or_default();
+            }
+            true
+        },
+        None,
+        Some(&mut |delta, hunk| {
+            if let Some(path) = file_key(&delta) {
+                hunks.borrow_mut().entry(path).or_default().push(HunkRange {
+                    start: hunk.old_start(),
+                    lines: hunk.old_lines(),
+                });
+            }
+            true
+        }),
+        None,
+    )
+    .map_err(|e| format!("Failed to walk diff: {}", e.message()))?;
+
+    let hunks = hunks.into_inner();
+    Ok(BranchChanges {
+        divergence: TrunkDivergence {
+            workpad_id: workpad.workpad_id.clone(),
+            merge_base: base_oid.to_string(),
+            ahead,
+            behind,
+            files_changed: hunks.keys().cloned().collect(),
+        },
+        hunks,
+    })
+}
+
+/// Whether two trunk ranges overlap or are adjacent. Pure insertions have
+/// zero length, so they collide with anything touching the same line.
+fn ranges_collide(a: &HunkRange, b: &HunkRange) -> bool {
+    let a_end = a.start + a.lines.max(1);
+    let b_end = b.start + b.lines.max(1);
+    a.start <= b_end && b.start <= a_end
+}
+
+#[tauri::command]
+pub(crate) fn compare_workpads(
+    workpad_id_a: String,
+    workpad_id_b: String,
+) -> Result<WorkpadComparison, String> {
+    let workpad_a = load_workpad(&workpad_id_a)?;
+    let workpad_b = load_workpad(&workpad_id_b)?;
+    if workpad_a.repo_id != workpad_b.repo_id {
+        return Err(format!(
+            "Workpads {} and {} belong to different repositories",
+            workpad_id_a, workpad_id_b
+        ));
+    }
+
+    let repository = load_repository(&workpad_a.repo_id)?;
+    let repo = open_repository(&workpad_a.repo_id)?;
+    let trunk = format!("refs/heads/{}", repository.trunk_branch);
+
+    let a = branch_changes(&repo, &workpad_a, &trunk)?;
+    let b = branch_changes(&repo, &workpad_b, &trunk)?;
+
+    let mut overlapping_files = Vec::new();
+    let mut conflicting_hunks = Vec::new();
+    for (path, hunks_a) in &a.hunks {
+        let Some(hunks_b) = b.hunks.get(path) else {
+            continue;
+        };
+        overlapping_files.push(path.clone());
+        for hunk_a in hunks_a {
+            for hunk_b in hunks_b
+                .iter()
+                .filter(|hunk_b| ranges_collide(hunk_a, hunk_b))
+            {
+                conflicting_hunks.push(HunkCollision {
+                    file_path: path.clone(),
+                    hunk_a: hunk_a.clone(),
+                    hunk_b: hunk_b.clone(),
+                });
+            }
+        }
+    }
+
+    let cost = |d: &TrunkDivergence| (d.behind, d.files_changed.len());
+    let suggested_first = if cost(&b.divergence) < cost(&a.divergence) {
+        workpad_id_b
+    } else {
+        workpad_id_a
+    };
+
+    Ok(WorkpadComparison {
+        trunk_branch: repository.trunk_branch,
+        a: a.divergence,
+        b: b.divergence,
+        overlapping_files,
+        conflicting_hunks,
+        suggested_first,
+    })
+}
@@ -0,0 +1,425 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::audit::audited;
+use crate::checkpoints::record_checkpoint;
+use crate::commands::{load_workpad, read_json, save_workpad, workpad_checkout_dir, write_json};
+use crate::git::run_git;
+use crate::lifecycle::{transition, WorkpadStatus};
+use crate::logging::warn_on_err;
+use crate::patches::{link_cherry_pick, store_patch};
+use crate::signing;
+use crate::unified_diff::parse_changed_files;
+use crate::{get_state_dir, WorkpadState};
+
+/// One `<<<<<<< ... >>>>>>>` region in a conflicted file. Line numbers are
+/// 1-based and refer to the marker-annotated working copy.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct ConflictHunk {
+    index: usize,
+    start_line: usize,
+    end_line: usize,
+    ours: String,
+    base: Option<String>,
+    theirs: String,
+}
+
+/// A file left unmerged by a three-way patch application, with the full
+/// text of each side so the GUI can render a merge view.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct FileConflict {
+    file_path: String,
+    ours: Option<String>,
+    base: Option<String>,
+    theirs: Option<String>,
+    hunks: Vec<ConflictHunk>,
+    resolved: bool,
+}
+
+/// Patch application paused on conflicts, persisted until resolved or aborted.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct PendingConflicts {
+    workpad_id: String,
+    message: String,
+    diff: String,
+    created_at: String,
+    files: Vec<FileConflict>,
+    /// Commit or patch being cherry-picked, linked from the resulting patch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cherry_picked_from: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "strategy", rename_all = "lowercase")]
+pub(crate) enum ConflictResolution {
+    Ours,
+    Theirs,
+    Base,
+    Manual { content: String },
+}
+
+fn pending_path(workpad_id: &str) -> PathBuf {
+    get_state_dir()
+        .join("conflicts")
+        .join(format!("{}.json", workpad_id))
+}
+
+fn load_pending(workpad_id: &str) -> Result<PendingConflicts, String> {
+    read_json(&pending_path(workpad_id))?
+        .ok_or_else(|| format!("Workpad {} has no pending conflicts", workpad_id))
+}
+
+fn stage_text(checkout: &Path, stage: u8, file_path: &str) -> Option<String> {
+    run_git(checkout, &["show", &format!(":{}:{}", stage, file_path)]).ok()
+}
+
+/// Split diff3-style conflict markers out of a working-tree file.
+fn parse_conflict_hunks(content: &str) -> Vec<ConflictHunk> {
+    enum Section {
+        Outside,
+        Ours,
+        Base,
+        Theirs,
+    }
+
+    let mut hunks = Vec::new();
+    let mut section = Section::Outside;
+    let mut start_line = 0;
+    let (mut ours, mut base, mut theirs) = (String::new(), None::<String>, String::new());
+
+    for (number, line) in content.lines().enumerate().map(|(i, l)| (i + 1, l)) {
+        match section {
+            Section::Outside if line.starts_with("<<<<<<<") => {
+                section = Section::Ours;
+                start_line = number;
+                ours.clear();
+                theirs.clear();
+                base = None;
+            }
+            Section::Ours if line.starts_with("|||||||") => {
+                section = Section::Base;
+                base = Some(String::new());
+            }
+            Section::Ours | Section::Base if line.starts_with("=======") => {
+                section = Section::Theirs;
+            }
+            Section::Theirs if line.starts_with(">>>>>>>") => {
+                hunks.push(ConflictHunk {
+                    index: hunks.len(),
+                    start_line,
+                    end_line: number,
+                    ours: ours.clone(),
+                    base: base.take(),
+                    theirs: theirs.clone(),
+                });
+                section = Section::Outside;
+            }
+            Section::Ours => {
+                ours.push_str(line);
+                ours.push('\n');
+            }
+            Section::Base => {
+                if let Some(base) = base.as_mut() {
+                    base.push_str(line);
+                    base.push('\n');
+                }
+            }
+            Section::Theirs => {
+                theirs.push_str(line);
+                theirs.push('\n');
+            }
+            Section::Outside => {}
+        }
+    }
+    hunks
+}
+
+fn unmerged_files(checkout: &Path) -> Result<Vec<String>, String> {
+    Ok(
+        run_git(checkout, &["diff", "--name-only", "-z", "--diff-filter=U"])?
+            .split('\0')
+            .filter(|path| !path.is_empty())
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
+/// Retry a failed patch with `git apply --3way`. Returns the conflicts it
+/// left behind (persisted for `resolve_conflict`), or an empty list if the
+/// patch applied cleanly and was committed.
+pub(crate) fn apply_three_way(
+    workpad: &WorkpadState,
+    message: &str,
+    diff: &str,
+    patch_file: &Path,
+    cherry_picked_from: Option<&str>,
+) -> Result<Vec<FileConflict>, String> {
+    let checkout = workpad_checkout_dir(workpad)?;
+    let patch_arg = patch_file
+        .to_str()
+        .ok_or_else(|| format!("Invalid patch path: {}", patch_file.display()))?;
+
+    run_git(&checkout, &["checkout", &workpad.branch_name])?;
+    let applied = run_git(
+        &checkout,
+        &[
+            "-c",
+            "merge.conflictStyle=diff3",
+            "apply",
+            "--3way",
+            patch_arg,
+        ],
+    );
+
+    let unmerged = unmerged_files(&checkout)?;
+    if unmerged.is_empty() {
+        applied?;
+        run_git(&checkout, &["add", "-A"])?;
+        signing::commit(&checkout, message)?;
+        complete(
+            workpad.workpad_id.clone(),
+            message,
+            diff,
+            cherry_picked_from,
+        )?;
+        return Ok(Vec::new());
+    }
+
+    let files: Vec<FileConflict> = unmerged
+        .into_iter()
+        .map(|file_path| {
+            let working = fs::read_to_string(checkout.join(&file_path)).unwrap_or_default();
+            FileConflict {
+                ours: stage_text(&checkout, 2, &file_path),
+                base: stage_text(&checkout, 1, &file_path),
+                theirs: stage_text(&checkout, 3, &file_path),
+                hunks: parse_conflict_hunks(&working),
+                file_path,
+                resolved: false,
+            }
+        })
+        .collect();
+
+    let pending = PendingConflicts {
+        workpad_id: workpad.workpad_id.clone(),
+        message: message.to_string(),
+        diff: diff.to_string(),
+        created_at: Utc::now().to_rfc3339(),
+        files: files.clone(),
+        cherry_picked_from: cherry_picked_from.map(str::to_string),
+    };
+    write_json(&pending_path(&workpad.workpad_id), &pending)?;
+    Ok(files)
+}
+
+/// Record the commit that finished a patch application on the workpad.
+fn complete(
+    workpad_id: String,
+    message: &str,
+    diff: &str,
+    cherry_picked_from: Option<&str>,
+) -> Result<WorkpadState, String> {
+    let mut workpad = load_workpad(&workpad_id)?;
+    let checkout = workpad_checkout_dir(&workpad)?;
+    let head = run_git(&checkout, &["rev-parse", "HEAD"])?
+        .trim()
+        .to_string();
+
+    workpad.current_commit = Some(head);
+    transition(&mut workpad, WorkpadStatus::Active)?;
+    workpad.patches_applied += 1;
+    for file in parse_changed_files(diff) {
+        if !workpad.files_changed.contains(&file) {
+            workpad.files_changed.push(file);
+        }
+    }
+    let workpad = save_workpad(workpad)?;
+
+    let checkpoint = record_checkpoint(&workpad, message)?;
+    let patch = store_patch(&workpad_id, message, diff, Some(checkpoint.checkpoint_id))?;
+    if let Some(source) = cherry_picked_from {
+        link_cherry_pick(patch, source)?;
+    }
+    Ok(workpad)
+}
+
+#[tauri::command]
+pub(crate) fn get_conflicts(workpad_id: String) -> Result<Option<PendingConflicts>, String> {
+    read_json(&pending_path(&workpad_id))
+}
+
+#[tauri::command]
+pub(crate) fn resolve_conflict(
+    workpad_id: String,
+    file_path: String,
+    resolution: ConflictResolution,
+) -> Result<PendingConflicts, String> {
+    audited(
+        "resolve_conflict",
+        "workpad",
+        Some(workpad_id.clone()),
+        None,
+        move || resolve_file(workpad_id, file_path, resolution),
+    )
+}
+
+fn resolve_file(
+    workpad_id: String,
+    file_path: String,
+    resolution: ConflictResolution,
+) -> Result<PendingConflicts, String> {
+    let mut pending = load_pending(&workpad_id)?;
+    let workpad = load_workpad(&workpad_id)?;
+    let checkout = workpad_checkout_dir(&workpad)?;
+
+    let conflict = pending
+        .files
+        .iter_mut()
+        .find(|file| file.file_path == file_path)
+        .ok_or_else(|| format!("{} is not conflicted in workpad {}", file_path, workpad_id))?;
+
+    let content = match resolution {
+        ConflictResolution::Ours => conflict.ours.clone(),
+        ConflictResolution::Theirs => conflict.theirs.clone(),
+        ConflictResolution::Base => conflict.base.clone(),
+        ConflictResolution::Manual { content } => Some(content),
+    };
+
+    // A missing side means the file does not exist in that version.
+    match content {
+        Some(content) => {
+            fs::write(checkout.join(&file_path), content)
+                .map_err(|e| format!("Failed to write {}: {}", file_path, e))?;
+            run_git(&checkout, &["add", "--", &file_path])?;
+        }
+        None => {
+            run_git(
+                &checkout,
+                &["rm", "-q", "--ignore-unmatch", "--", &file_path],
+            )?;
+        }
+    }
+    conflict.resolved = true;
+
+    if pending.files.iter().all(|file| file.resolved) && unmerged_files(&checkout)?.is_empty() {
+        signing::commit(&checkout, &pending.message)?;
+        complete(
+            workpad_id.clone(),
+            &pending.message,
+            &pending.diff,
+            pending.cherry_picked_from.as_deref(),
+        )?;
+        warn_on_err(
+            "Failed to clear pending conflicts",
+            fs::remove_file(pending_path(&workpad_id)),
+        );
+    } else {
+        write_json(&pending_path(&workpad_id), &pending)?;
+    }
+
+    Ok(pending)
+}
+
+#[tauri::command]
+pub(crate) fn abort_conflicts(workpad_id: String) -> Result<WorkpadState, String> {
+    audited(
+        "abort_conflicts",
+        "workpad",
+        Some(workpad_id.clone()),
+        None,
+        move || abort_pending(workpad_id),
+    )
+}
+
+/// Sequencer operations git can leave half-finished, keyed by the state file
+/// or directory that marks them in progress.
+const IN_PROGRESS: [(&str, &str); 5] = [
+    ("rebase-merge", "rebase"),
+    ("rebase-apply", "rebase"),
+    ("CHERRY_PICK_HEAD", "cherry-pick"),
+    ("REVERT_HEAD", "revert"),
+    ("MERGE_HEAD", "merge"),
+];
+
+fn operation_in_progress(checkout: &Path) -> Result<Option<&'static str>, String> {
+    for (marker, operation) in IN_PROGRESS {
+        let path = run_git(checkout, &["rev-parse", "--git-path", marker])?;
+        if checkout.join(path.trim()).exists() {
+            return Ok(Some(operation));
+        }
+    }
+    Ok(None)
+}
+
+/// Put `paths` back the way HEAD has them: unstaged, restored, and deleted
+/// if HEAD doesn't have them (files the patch added).
+fn reset_to_head(checkout: &Path, paths: &[String]) -> Result<(), String> {
+    let mut args = vec!["reset", "-q", "HEAD", "--"];
+    args.extend(paths.iter().map(String::as_str));
+    run_git(checkout, &args)?;
+
+    let mut args = vec!["ls-tree", "-r", "-z", "--name-only", "HEAD", "--"];
+    args.extend(paths.iter().map(String::as_str));
+    let in_head = run_git(checkout, &args)?;
+    let in_head: Vec<&str> = in_head
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .collect();
+    if !in_head.is_empty() {
+        let mut args = vec!["checkout", "-f", "HEAD", "--"];
+        args.extend(in_head.iter().copied());
+        run_git(checkout, &args)?;
+    }
+    for path in paths
+        .iter()
+        .filter(|path| !in_head.contains(&path.as_str()))
+    {
+        let file = checkout.join(path);
+        if file.is_file() {
+            fs::remove_file(&file)
+                .map_err(|e| format!("Failed to remove {}: {}", file.display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+fn abort_pending(workpad_id: String) -> Result<WorkpadState, String> {
+    let pending = read_json::<PendingConflicts>(&pending_path(&workpad_id))?;
+    let workpad = load_workpad(&workpad_id)?;
+    let checkout = workpad_checkout_dir(&workpad)?;
+    let operation = operation_in_progress(&checkout)?;
+    if pending.is_none() && operation.is_none() {
+        return Err(format!("Workpad {} has no pending conflicts", workpad_id));
+    }
+
+    if let Some(operation) = operation {
+        run_git(&checkout, &[operation, "--abort"])?;
+    }
+    // `git apply --3way` leaves no sequencer state; reset every file the
+    // patch touched, including hunks that applied cleanly.
+    let mut paths = unmerged_files(&checkout)?;
+    if let Some(conflicts) = &pending {
+        let touched = parse_changed_files(&conflicts.diff)
+            .into_iter()
+            .chain(conflicts.files.iter().map(|file| file.file_path.clone()));
+        for path in touched {
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+    }
+    if !paths.is_empty() {
+        reset_to_head(&checkout, &paths)?;
+    }
+
+    if pending.is_some() {
+        warn_on_err(
+            "Failed to clear pending conflicts",
+            fs::remove_file(pending_path(&workpad_id)),
+        );
+    }
+    Ok(workpad)
+}
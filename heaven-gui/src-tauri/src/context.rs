@@ -0,0 +1,292 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::ai::ai_settings;
+use crate::commands::{load_workpad, workpad_checkout_dir};
+use crate::git::{run_git, workpad_diff};
+use crate::text::clip;
+use crate::tokens::count_tokens;
+use crate::unified_diff::parse_changed_files;
+use crate::WorkpadState;
+
+/// Files larger than this are never inlined whole.
+const MAX_FILE_BYTES: usize = 200_000;
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct ContextFile {
+    path: String,
+    /// "mentioned", "changed", "import" or "test"
+    reason: String,
+    tokens: usize,
+    included: bool,
+    truncated: bool,
+}
+
+/// Exactly what would be prepended to a prompt for a workpad.
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct AiContext {
+    workpad_id: String,
+    token_budget: usize,
+    estimated_tokens: usize,
+    files: Vec<ContextFile>,
+    context: String,
+}
+
+impl AiContext {
+    pub(crate) fn block(&self) -> &str {
+        &self.context
+    }
+}
+
+fn language(path: &str) -> &'static str {
+    match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+        Some("rs") => "rust",
+        Some("py") => "python",
+        Some("ts") | Some("tsx") => "typescript",
+        Some("js") | Some("jsx") | Some("mjs") => "javascript",
+        Some("go") => "go",
+        Some("json") => "json",
+        Some("toml") => "toml",
+        Some("yaml") | Some("yml") => "yaml",
+        Some("md") => "markdown",
+        _ => "",
+    }
+}
+
+fn stem(path: &str) -> Option<String> {
+    Path::new(path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .map(|s| s.to_string())
+}
+
+/// Local modules referenced by `import`/`use` statements in `source`,
+/// resolved against the tracked file list.
+fn imported_files(path: &str, source: &str, tracked: &[String]) -> Vec<String> {
+    let dir = Path::new(path).parent().unwrap_or(Path::new(""));
+    let mut candidates: Vec<String> = Vec::new();
+
+    for line in source.lines().map(str::trim) {
+        if let Some(rest) = line
+            .strip_prefix("from ")
+            .and_then(|rest| rest.split_once(" import").map(|(module, _)| module))
+            .or_else(|| line.strip_prefix("import "))
+        {
+            // Python: dotted module path; JS/TS handled below via quotes.
+            let module = rest.split([',', ' ']).next().unwrap_or("");
+            if !module.is_empty() && !module.contains(['\'', '"']) {
+                candidates.push(format!(
+                    "{}.py",
+                    module.trim_start_matches('.').replace('.', "/")
+                ));
+            }
+        }
+        if line.starts_with("import ") || line.contains(" from ") || line.contains("require(") {
+            if let Some(spec) = line
+                .split(['\'', '"'])
+                .nth(1)
+                .filter(|spec| spec.starts_with('.'))
+            {
+                let joined = dir.join(spec);
+                let base = joined.to_string_lossy().replace("/./", "/");
+                for ext in ["", ".ts", ".tsx", ".js", ".jsx", "/index.ts", "/index.js"] {
+                    candidates.push(format!("{}{}", base, ext));
+                }
+            }
+        }
+        if let Some(module) = line
+            .strip_prefix("mod ")
+            .or_else(|| line.strip_prefix("pub mod "))
+            .or_else(|| line.strip_prefix("pub(crate) mod "))
+            .and_then(|rest| rest.strip_suffix(';'))
+        {
+            candidates.push(
+                dir.join(format!("{}.rs", module))
+                    .to_string_lossy()
+                    .to_string(),
+            );
+            candidates.push(
+                dir.join(module)
+                    .join("mod.rs")
+                    .to_string_lossy()
+                    .to_string(),
+            );
+        }
+        if let Some(rest) = line.strip_prefix("use crate::") {
+            let module = rest.split([':', ';', '{']).next().unwrap_or("");
+            candidates.push(format!("{}.rs", module));
+        }
+    }
+
+    let mut found = Vec::new();
+    for candidate in candidates {
+        let candidate = candidate.trim_start_matches("./").to_string();
+        if let Some(file) = tracked
+            .iter()
+            .find(|file| *file == &candidate || file.ends_with(&format!("/{}", candidate)))
+        {
+            if file != path && !found.contains(file) {
+                found.push(file.clone());
+            }
+        }
+    }
+    found
+}
+
+fn is_test_file(path: &str) -> bool {
+    let lower = path.to_lowercase();
+    lower.contains("/tests/")
+        || lower.starts_with("tests/")
+        || lower.contains("test_")
+        || lower.contains("_test.")
+        || lower.contains(".test.")
+        || lower.contains(".spec.")
+}
+
+/// Test files whose name refers to one of the changed files.
+fn related_tests(changed: &[String], tracked: &[String]) -> Vec<String> {
+    let stems: HashSet<String> = changed.iter().filter_map(|path| stem(path)).collect();
+    tracked
+        .iter()
+        .filter(|path| is_test_file(path) && !changed.contains(*path))
+        .filter(|path| {
+            stem(path).is_some_and(|test_stem| {
+                let bare = test_stem
+                    .trim_start_matches("test_")
+                    .trim_end_matches("_test")
+                    .split('.')
+                    .next()
+                    .unwrap_or("")
+                    .to_string();
+                stems.contains(&bare)
+            })
+        })
+        .cloned()
+        .collect()
+}
+
+fn truncate_to_tokens(text: &str, tokens: usize) -> String {
+    let clipped = clip(text, tokens * 4);
+    match clipped.rfind('\n') {
+        Some(newline) => clipped[..newline].to_string(),
+        None => clipped.to_string(),
+    }
+}
+
+/// Select files relevant to `prompt` on `workpad` and pack them into a
+/// context block that fits `token_budget`, highest priority first:
+/// files named in the prompt, changed files, their imports, then tests.
+pub(crate) fn build_context(
+    workpad: &WorkpadState,
+    prompt: &str,
+    token_budget: usize,
+) -> Result<AiContext, String> {
+    let checkout = workpad_checkout_dir(workpad)?;
+    let model = ai_settings().model;
+    let tracked: Vec<String> = run_git(&checkout, &["ls-files", "-z"])?
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    let mut changed = parse_changed_files(&workpad_diff(workpad).unwrap_or_default());
+    for file in &workpad.files_changed {
+        if !changed.contains(file) {
+            changed.push(file.clone());
+        }
+    }
+    changed.retain(|path| tracked.contains(path));
+
+    let mut ordered: Vec<(String, &'static str)> = Vec::new();
+    let mut push = |path: String, reason: &'static str| {
+        if !ordered.iter().any(|(existing, _)| existing == &path) {
+            ordered.push((path, reason));
+        }
+    };
+    for word in prompt.split(|c: char| c.is_whitespace() || "`'\",()".contains(c)) {
+        let word = word.trim_end_matches(['.', ':', ';']);
+        if !word.is_empty() && tracked.iter().any(|path| path == word) {
+            push(word.to_string(), "mentioned");
+        }
+    }
+    for path in &changed {
+        push(path.clone(), "changed");
+    }
+    for path in &changed {
+        if let Ok(source) = fs::read_to_string(checkout.join(path)) {
+            for import in imported_files(path, &source, &tracked) {
+                push(import, "import");
+            }
+        }
+    }
+    for path in related_tests(&changed, &tracked) {
+        push(path, "test");
+    }
+
+    let mut files = Vec::new();
+    let mut context = String::new();
+    let mut used = 0;
+    for (path, reason) in ordered {
+        let Ok(source) = fs::read_to_string(checkout.join(&path)) else {
+            continue;
+        };
+        let tokens = count_tokens(&source, &model);
+        let remaining = token_budget.saturating_sub(used);
+        let mut entry = ContextFile {
+            path: path.clone(),
+            reason: reason.to_string(),
+            tokens,
+            included: false,
+            truncated: false,
+        };
+
+        // Only the highest-priority files are worth sending partially.
+        let body = if tokens <= remaining && source.len() <= MAX_FILE_BYTES {
+            Some(source)
+        } else if matches!(reason, "mentioned" | "changed") && remaining >= 256 {
+            entry.truncated = true;
+            Some(truncate_to_tokens(&source, remaining - 64))
+        } else {
+            None
+        };
+
+        if let Some(body) = body {
+            let block = format!(
+                "### {} ({})\n```{}\n{}\n```\n\n",
+                path,
+                reason,
+                language(&path),
+                body.trim_end()
+            );
+            used += count_tokens(&block, &model);
+            entry.included = true;
+            context.push_str(&block);
+        }
+        files.push(entry);
+    }
+
+    Ok(AiContext {
+        workpad_id: workpad.workpad_id.clone(),
+        token_budget,
+        estimated_tokens: used,
+        files,
+        context,
+    })
+}
+
+#[tauri::command]
+pub(crate) fn preview_ai_context(
+    workpad_id: String,
+    prompt: String,
+    token_budget: Option<usize>,
+) -> Result<AiContext, String> {
+    let workpad = load_workpad(&workpad_id)?;
+    build_context(
+        &workpad,
+        &prompt,
+        token_budget.unwrap_or(ai_settings().context_token_budget),
+    )
+}
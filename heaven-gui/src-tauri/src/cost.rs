@@ -0,0 +1,330 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::api_server;
+use crate::audit::audited;
+use crate::cli_config::effective_settings;
+use crate::commands::{read_json, write_json};
+use crate::logging::warn_on_err;
+use crate::notifications;
+use crate::timestamps;
+use crate::webhooks;
+use crate::{get_settings, get_state_dir, list_ai_operations, write_settings, AIOperation};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub(crate) struct CostSettings {
+    /// Hard limit on AI spend per calendar month (UTC). `None` disables it.
+    pub(crate) monthly_budget_usd: Option<f64>,
+    /// Fraction of the budget at which a warning event is emitted.
+    pub(crate) warning_threshold: f64,
+}
+
+impl Default for CostSettings {
+    fn default() -> Self {
+        CostSettings {
+            monthly_budget_usd: None,
+            warning_threshold: 0.8,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct CostGroup {
+    key: String,
+    cost_usd: f64,
+    tokens_used: i64,
+    operations: usize,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct BudgetStatus {
+    monthly_budget_usd: Option<f64>,
+    spent_this_month_usd: f64,
+    remaining_usd: Option<f64>,
+    warning_threshold: f64,
+    warning: bool,
+    exceeded: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct CostReport {
+    period: String,
+    group_by: String,
+    since: Option<String>,
+    total_cost_usd: f64,
+    total_tokens: i64,
+    operations: usize,
+    groups: Vec<CostGroup>,
+    budget: BudgetStatus,
+}
+
+fn month_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+        .single()
+        .unwrap_or(now)
+}
+
+fn period_start(period: &str, now: DateTime<Utc>) -> Result<Option<DateTime<Utc>>, String> {
+    match period {
+        "day" => Ok(Some(now - Duration::days(1))),
+        "week" => Ok(Some(now - Duration::days(7))),
+        "month" => Ok(Some(month_start(now))),
+        "all" => Ok(None),
+        other => Err(format!(
+            "Unknown period {}; expected day, week, month or all",
+            other
+        )),
+    }
+}
+
+fn started(operation: &AIOperation) -> Option<DateTime<Utc>> {
+    timestamps::parse(&operation.started_at)
+}
+
+fn group_key(operation: &AIOperation, group_by: &str) -> Result<String, String> {
+    match group_by {
+        "model" => Ok(operation.model.clone()),
+        "workpad" => Ok(operation
+            .workpad_id
+            .clone()
+            .unwrap_or_else(|| "(none)".to_string())),
+        "day" => Ok(operation.started_at.chars().take(10).collect()),
+        "operation_type" => Ok(operation.operation_type.clone()),
+        other => Err(format!(
+            "Unknown grouping {}; expected model, workpad, day or operation_type",
+            other
+        )),
+    }
+}
+
+fn budget_status(operations: &[AIOperation]) -> BudgetStatus {
+    let settings = effective_settings().map(|s| s.cost).unwrap_or_default();
+    let since = month_start(Utc::now());
+    let spent: f64 = operations
+        .iter()
+        .filter(|op| started(op).is_some_and(|at| at >= since))
+        .map(|op| op.cost_usd)
+        .sum();
+
+    let (remaining, warning, exceeded) = match settings.monthly_budget_usd {
+        Some(budget) => (
+            Some((budget - spent).max(0.0)),
+            spent >= budget * settings.warning_threshold,
+            spent >= budget,
+        ),
+        None => (None, false, false),
+    };
+
+    BudgetStatus {
+        monthly_budget_usd: settings.monthly_budget_usd,
+        spent_this_month_usd: spent,
+        remaining_usd: remaining,
+        warning_threshold: settings.warning_threshold,
+        warning,
+        exceeded,
+    }
+}
+
+/// Refuse new AI work once the monthly budget is used up.
+pub(crate) fn ensure_within_budget() -> Result<(), String> {
+    let status = budget_status(&list_ai_operations(None, None, None)?);
+    if status.exceeded {
+        return Err(format!(
+            "Monthly AI budget of ${:.2} exhausted (${:.2} spent); raise the budget in settings to continue",
+            status.monthly_budget_usd.unwrap_or_default(),
+            status.spent_this_month_usd
+        ));
+    }
+    Ok(())
+}
+
+/// Refuse new AI work of `operation_type` once its own monthly budget from
+/// `ai.routes` is used up.
+pub(crate) fn ensure_within_route_budget(
+    operation_type: &str,
+    budget_usd: Option<f64>,
+) -> Result<(), String> {
+    let Some(budget) = budget_usd else {
+        return Ok(());
+    };
+    let since = month_start(Utc::now());
+    let spent: f64 = list_ai_operations(None, None, None)?
+        .iter()
+        .filter(|op| op.operation_type == operation_type)
+        .filter(|op| started(op).is_some_and(|at| at >= since))
+        .map(|op| op.cost_usd)
+        .sum();
+    if spent >= budget {
+        return Err(format!(
+            "Monthly budget of ${:.2} for {} operations exhausted (${:.2} spent)",
+            budget, operation_type, spent
+        ));
+    }
+    Ok(())
+}
+
+/// Month each budget alert last went out in, keyed by alert name. Persisted
+/// so a restart doesn't repeat this month's alerts.
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct BudgetAlerts {
+    sent: BTreeMap<String, String>,
+}
+
+/// Serialises read-modify-write of `budget_alerts.json`.
+static BUDGET_ALERTS: Mutex<()> = Mutex::new(());
+
+fn budget_alerts_path() -> PathBuf {
+    get_state_dir().join("budget_alerts.json")
+}
+
+fn update_budget_alerts(update: impl FnOnce(&mut BudgetAlerts) -> bool) {
+    let Ok(_guard) = BUDGET_ALERTS.lock() else {
+        return;
+    };
+    let path = budget_alerts_path();
+    let mut alerts: BudgetAlerts = read_json(&path).ok().flatten().unwrap_or_default();
+    if update(&mut alerts) {
+        warn_on_err("Failed to record budget alert", write_json(&path, &alerts));
+    }
+}
+
+/// Run `send` unless `alert` already went out this month. The month is only
+/// recorded when `send` reports that the alert was delivered, so an alert
+/// suppressed by settings still fires once they allow it.
+fn alert_once_per_month(alert: &str, send: impl FnOnce() -> bool) {
+    let month = Utc::now().format("%Y-%m").to_string();
+    update_budget_alerts(|alerts| {
+        if alerts.sent.get(alert) == Some(&month) || !send() {
+            return false;
+        }
+        alerts.sent.insert(alert.to_string(), month);
+        true
+    });
+}
+
+/// Let budget alerts fire again this month, e.g. after the budget changed.
+pub(crate) fn reset_budget_alerts() {
+    update_budget_alerts(|alerts| {
+        let changed = !alerts.sent.is_empty();
+        alerts.sent.clear();
+        changed
+    });
+}
+
+/// Emit "ai-budget-warning" when spend crosses the warning threshold. The
+/// warning fires again if spend drops back under it (e.g. the budget was
+/// raised) and later crosses it again.
+pub(crate) fn notify_budget(window: &tauri::Window) {
+    if let Ok(operations) = list_ai_operations(None, None, None) {
+        let status = budget_status(&operations);
+        if status.warning {
+            alert_once_per_month("warning", || {
+                warn_on_err(
+                    "Failed to emit ai-budget-warning",
+                    window.emit("ai-budget-warning", &status),
+                );
+                api_server::forward("ai-budget-warning", &status);
+                webhooks::fire_budget_threshold(serde_json::json!(status));
+                true
+            });
+        } else {
+            update_budget_alerts(|alerts| alerts.sent.remove("warning").is_some());
+        }
+        if status.exceeded {
+            alert_once_per_month("exceeded", || {
+                notifications::budget_exceeded(
+                    status.spent_this_month_usd,
+                    status.monthly_budget_usd,
+                )
+            });
+        }
+    }
+}
+
+#[tauri::command]
+pub(crate) fn get_cost_report(
+    period: Option<String>,
+    group_by: Option<String>,
+) -> Result<CostReport, String> {
+    let period = period.unwrap_or_else(|| "month".to_string());
+    let group_by = group_by.unwrap_or_else(|| "model".to_string());
+    let since = period_start(&period, Utc::now())?;
+
+    let operations = list_ai_operations(None, None, None)?;
+    let mut groups: BTreeMap<String, CostGroup> = BTreeMap::new();
+    let (mut total_cost_usd, mut total_tokens, mut count) = (0.0, 0i64, 0usize);
+
+    for operation in &operations {
+        if let Some(since) = since {
+            if !started(operation).is_some_and(|at| at >= since) {
+                continue;
+            }
+        }
+        let key = group_key(operation, &group_by)?;
+        let group = groups.entry(key.clone()).or_insert_with(|| CostGroup {
+            key,
+            cost_usd: 0.0,
+            tokens_used: 0,
+            operations: 0,
+        });
+        group.cost_usd += operation.cost_usd;
+        group.tokens_used += operation.tokens_used as i64;
+        group.operations += 1;
+
+        total_cost_usd += operation.cost_usd;
+        total_tokens += operation.tokens_used as i64;
+        count += 1;
+    }
+
+    let mut groups: Vec<CostGroup> = groups.into_values().collect();
+    if group_by != "day" {
+        groups.sort_by(|a, b| b.cost_usd.total_cmp(&a.cost_usd));
+    }
+
+    Ok(CostReport {
+        period,
+        group_by,
+        since: since.map(|dt| dt.to_rfc3339()),
+        total_cost_usd,
+        total_tokens,
+        operations: count,
+        groups,
+        budget: budget_status(&operations),
+    })
+}
+
+#[tauri::command]
+pub(crate) fn set_ai_budget(
+    monthly_budget_usd: Option<f64>,
+    warning_threshold: Option<f64>,
+) -> Result<CostSettings, String> {
+    audited("set_ai_budget", "settings", None, None, move || {
+        store_budget(monthly_budget_usd, warning_threshold)
+    })
+}
+
+fn store_budget(
+    monthly_budget_usd: Option<f64>,
+    warning_threshold: Option<f64>,
+) -> Result<CostSettings, String> {
+    if monthly_budget_usd.is_some_and(|budget| budget < 0.0) {
+        return Err("Budget cannot be negative".to_string());
+    }
+    if warning_threshold.is_some_and(|t| !(0.0..=1.0).contains(&t)) {
+        return Err("Warning threshold must be between 0 and 1".to_string());
+    }
+
+    let mut settings = get_settings()?;
+    settings.cost.monthly_budget_usd = monthly_budget_usd;
+    if let Some(threshold) = warning_threshold {
+        settings.cost.warning_threshold = threshold;
+    }
+    let cost = settings.cost.clone();
+    write_settings(&settings)?;
+    Ok(cost)
+}
@@ -0,0 +1,258 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::commands::{load_workpad, read_json, write_json};
+use crate::{get_state_dir, read_test_run};
+
+/// Report files looked for (relative to the test working directory) after a run.
+const REPORT_CANDIDATES: &[(&str, &str)] = &[
+    ("lcov.info", "lcov"),
+    ("coverage/lcov.info", "lcov"),
+    ("coverage.xml", "cobertura"),
+    ("tarpaulin-report.json", "tarpaulin"),
+    ("target/tarpaulin/tarpaulin-report.json", "tarpaulin"),
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct FileCoverage {
+    file_path: String,
+    covered_lines: Vec<u32>,
+    uncovered_lines: Vec<u32>,
+    percent: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct CoverageReport {
+    run_id: String,
+    format: String,
+    source: String,
+    generated_at: String,
+    percent: f64,
+    files: Vec<FileCoverage>,
+}
+
+/// line -> hit count, per file
+type LineHits = BTreeMap<String, BTreeMap<u32, u64>>;
+
+fn attr<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let needle = format!(" {}=\"", name);
+    let start = tag.find(&needle)? + needle.len();
+    let end = tag[start..].find('"')? + start;
+    Some(&tag[start..end])
+}
+
+fn parse_lcov(contents: &str) -> LineHits {
+    let mut hits = LineHits::new();
+    let mut current: Option<String> = None;
+
+    for line in contents.lines() {
+        if let Some(path) = line.strip_prefix("SF:") {
+            current = Some(path.trim().to_string());
+        } else if let Some(data) = line.strip_prefix("DA:") {
+            let mut parts = data.split(',');
+            let number = parts.next().and_then(|n| n.trim().parse::<u32>().ok());
+            let count = parts.next().and_then(|n| n.trim().parse::<u64>().ok());
+            if let (Some(file), Some(number), Some(count)) = (&current, number, count) {
+                *hits
+                    .entry(file.clone())
+                    .or_default()
+                    .entry(number)
+                    .or_insert(0) += count;
+            }
+        } else if line.trim() == "end_of_record" {
+            current = None;
+        }
+    }
+
+    hits
+}
+
+/// coverage.py / Cobertura XML: `<class filename=...>` containing `<line number= hits=/>`.
+fn parse_cobertura(contents: &str) -> LineHits {
+    let mut hits = LineHits::new();
+    let mut current: Option<String> = None;
+
+    for tag in contents.split('<').skip(1) {
+        if tag.starts_with("class ") {
+            current = attr(tag, "filename").map(|f| f.to_string());
+        } else if tag.starts_with("/class") {
+            current = None;
+        } else if tag.starts_with("line ") {
+            let number = attr(tag, "number").and_then(|n| n.parse::<u32>().ok());
+            let count = attr(tag, "hits").and_then(|n| n.parse::<u64>().ok());
+            if let (Some(file), Some(number), Some(count)) = (&current, number, count) {
+                *hits
+                    .entry(file.clone())
+                    .or_default()
+                    .entry(number)
+                    .or_insert(0) += count;
+            }
+        }
+    }
+
+    hits
+}
+
+/// cargo-tarpaulin JSON: `files[].path` is a list of components, `traces[]`
+/// carry `line` and `stats.Line` hit counts.
+fn parse_tarpaulin(contents: &str) -> Result<LineHits, String> {
+    let data: Value = serde_json::from_str(contents)
+        .map_err(|e| format!("Failed to parse tarpaulin report: {}", e))?;
+    let mut hits = LineHits::new();
+
+    for file in data["files"].as_array().cloned().unwrap_or_default() {
+        let components: Vec<String> = file["path"]
+            .as_array()
+            .map(|parts| {
+                parts
+                    .iter()
+                    .filter_map(|p| p.as_str().map(|s| s.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        if components.is_empty() {
+            continue;
+        }
+        let path: PathBuf = components.iter().collect();
+        let entry = hits.entry(path.to_string_lossy().to_string()).or_default();
+
+        for trace in file["traces"].as_array().cloned().unwrap_or_default() {
+            if let Some(line) = trace["line"].as_u64() {
+                let count = trace["stats"]["Line"].as_u64().unwrap_or(0);
+                *entry.entry(line as u32).or_insert(0) += count;
+            }
+        }
+    }
+
+    Ok(hits)
+}
+
+/// Make report paths relative to the repository so they match the file tree.
+fn normalize_path(path: &str, checkout: &Path) -> String {
+    let as_path = Path::new(path);
+    if let Ok(relative) = as_path.strip_prefix(checkout) {
+        return relative.to_string_lossy().to_string();
+    }
+    if let Ok(relative) = as_path.strip_prefix("/workspace") {
+        return relative.to_string_lossy().to_string();
+    }
+    path.trim_start_matches("./").to_string()
+}
+
+fn build_report(
+    run_id: &str,
+    format: &str,
+    source: &str,
+    hits: LineHits,
+    checkout: &Path,
+) -> CoverageReport {
+    let mut total_covered = 0usize;
+    let mut total_lines = 0usize;
+
+    let files = hits
+        .into_iter()
+        .map(|(path, lines)| {
+            let (covered, uncovered): (Vec<(u32, u64)>, Vec<(u32, u64)>) =
+                lines.into_iter().partition(|(_, count)| *count > 0);
+            total_covered += covered.len();
+            total_lines += covered.len() + uncovered.len();
+
+            let line_count = covered.len() + uncovered.len();
+            FileCoverage {
+                file_path: normalize_path(&path, checkout),
+                percent: if line_count == 0 {
+                    0.0
+                } else {
+                    covered.len() as f64 * 100.0 / line_count as f64
+                },
+                covered_lines: covered.into_iter().map(|(line, _)| line).collect(),
+                uncovered_lines: uncovered.into_iter().map(|(line, _)| line).collect(),
+            }
+        })
+        .collect();
+
+    CoverageReport {
+        run_id: run_id.to_string(),
+        format: format.to_string(),
+        source: source.to_string(),
+        generated_at: Utc::now().to_rfc3339(),
+        percent: if total_lines == 0 {
+            0.0
+        } else {
+            total_covered as f64 * 100.0 / total_lines as f64
+        },
+        files,
+    }
+}
+
+fn coverage_path(run_id: &str) -> PathBuf {
+    get_state_dir()
+        .join("coverage")
+        .join(format!("{}.json", run_id))
+}
+
+/// Look for coverage reports written during a run (newer than `since`) and
+/// store the parsed per-file coverage next to the TestRun.
+pub(crate) fn ingest_run_coverage(
+    run_id: &str,
+    checkout: &Path,
+    working_dir: &Path,
+    since: SystemTime,
+) -> Result<Option<CoverageReport>, String> {
+    for (relative, format) in REPORT_CANDIDATES {
+        let path = working_dir.join(relative);
+        let fresh = fs::metadata(&path)
+            .and_then(|meta| meta.modified())
+            .map(|modified| modified >= since)
+            .unwrap_or(false);
+        if !fresh {
+            continue;
+        }
+
+        let contents = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let hits = match *format {
+            "lcov" => parse_lcov(&contents),
+            "cobertura" => parse_cobertura(&contents),
+            _ => parse_tarpaulin(&contents)?,
+        };
+
+        let report = build_report(run_id, format, relative, hits, checkout);
+        write_json(&coverage_path(run_id), &report)?;
+        return Ok(Some(report));
+    }
+
+    Ok(None)
+}
+
+#[tauri::command]
+pub(crate) fn get_file_coverage(
+    repo_id: String,
+    run_id: String,
+    file_path: String,
+) -> Result<Option<FileCoverage>, String> {
+    // Only serve coverage for runs of a workpad in `repo_id`.
+    let run = read_test_run(run_id.clone())?;
+    let in_repo = match &run.workpad_id {
+        Some(workpad_id) => load_workpad(workpad_id)?.repo_id == repo_id,
+        None => false,
+    };
+    if !in_repo {
+        return Err(format!("Test run {} not found in {}", run_id, repo_id));
+    }
+
+    let report: CoverageReport = read_json(&coverage_path(&run_id))?
+        .ok_or_else(|| format!("No coverage recorded for run {} in {}", run_id, repo_id))?;
+
+    let wanted = file_path.trim_start_matches("./");
+    Ok(report
+        .files
+        .into_iter()
+        .find(|file| file.file_path == wanted))
+}
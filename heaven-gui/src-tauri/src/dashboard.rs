@@ -0,0 +1,115 @@
+use std::collections::{BTreeMap, HashSet};
+
+use serde::Serialize;
+
+use crate::commands::load_global_state;
+use crate::lifecycle::WorkpadStatus;
+use crate::{
+    list_ai_operations, list_commits, list_repositories, list_test_runs, list_workpads,
+    AIOperation, CommitNode, GlobalState, RepositoryState, TestRun, WorkpadState,
+};
+
+const RECENT_LIMIT: usize = 10;
+
+/// Everything the home screen needs in one payload.
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct DashboardSummary {
+    global: GlobalState,
+    repository_count: usize,
+    active_repo: Option<RepositoryState>,
+    active_workpad: Option<WorkpadState>,
+    recent_commits: Vec<CommitNode>,
+    latest_test_runs: Vec<TestRun>,
+    recent_ai_operations: Vec<AIOperation>,
+    /// Spend on AI operations in scope (all repositories when none is selected).
+    total_cost_usd: f64,
+    workpads_by_status: BTreeMap<String, usize>,
+    open_workpads: Vec<WorkpadState>,
+}
+
+pub(crate) fn is_open(workpad: &WorkpadState) -> bool {
+    !matches!(
+        workpad.status,
+        WorkpadStatus::Promoted | WorkpadStatus::Deleted | WorkpadStatus::Archived
+    )
+}
+
+#[tauri::command]
+pub(crate) fn get_dashboard(repo_id: Option<String>) -> Result<DashboardSummary, String> {
+    let global = load_global_state()?;
+    let repositories = list_repositories(None)?;
+    let scope = repo_id.or_else(|| global.active_repo.clone());
+
+    let active_repo = scope
+        .as_ref()
+        .and_then(|id| repositories.iter().find(|repo| &repo.repo_id == id))
+        .cloned();
+
+    // One pass over the workpads collects everything derived from them.
+    let mut in_scope: HashSet<String> = HashSet::new();
+    let mut workpads_by_status: BTreeMap<String, usize> = BTreeMap::new();
+    let mut active_workpad = None;
+    let mut open_workpads = Vec::new();
+    for workpad in list_workpads(scope.clone(), None, None)? {
+        *workpads_by_status
+            .entry(workpad.status.to_string())
+            .or_default() += 1;
+        in_scope.insert(workpad.workpad_id.clone());
+        if global.active_workpad.as_ref() == Some(&workpad.workpad_id) {
+            active_workpad = Some(workpad.clone());
+        }
+        if is_open(&workpad) {
+            open_workpads.push(workpad);
+        }
+    }
+    let scoped = |workpad_id: &Option<String>| {
+        scope.is_none()
+            || workpad_id
+                .as_deref()
+                .is_some_and(|id| in_scope.contains(id))
+    };
+
+    let latest_test_runs: Vec<TestRun> = list_test_runs(None, None)?
+        .into_iter()
+        .filter(|run| scoped(&run.workpad_id))
+        .take(RECENT_LIMIT)
+        .collect();
+
+    // Unscoped, the ledger total in `global` already covers every operation,
+    // including ones maintenance has since pruned.
+    let mut total_cost_usd = if scope.is_none() {
+        global.total_cost_usd
+    } else {
+        0.0
+    };
+    let mut recent_ai_operations = Vec::new();
+    for operation in list_ai_operations(None, None, None)? {
+        if !scoped(&operation.workpad_id) {
+            continue;
+        }
+        if scope.is_some() {
+            total_cost_usd += operation.cost_usd;
+        }
+        if recent_ai_operations.len() < RECENT_LIMIT {
+            recent_ai_operations.push(operation);
+        }
+    }
+
+    let recent_commits = match &active_repo {
+        Some(repo) => list_commits(repo.repo_id.clone(), Some(RECENT_LIMIT as i32))?,
+        None => Vec::new(),
+    };
+
+    Ok(DashboardSummary {
+        repository_count: repositories.len(),
+        active_repo,
+        active_workpad,
+        recent_commits,
+        latest_test_runs,
+        recent_ai_operations,
+        total_cost_usd,
+        workpads_by_status,
+        open_workpads,
+        global,
+    })
+}
@@ -0,0 +1,411 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::commands::{read_json, resolve_repo_path, write_json};
+use crate::get_state_dir;
+use crate::http::{agent, encode_component, json_response};
+
+const OSV_API: &str = "https://api.osv.dev/v1";
+/// OSV's limit for one `querybatch` call.
+const OSV_BATCH_SIZE: usize = 1000;
+/// Ordered most to least severe; "unknown" sorts last.
+pub(crate) const SEVERITIES: &[&str] = &["critical", "high", "moderate", "low"];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub(crate) struct DependencyAuditSettings {
+    /// Add the latest audit to the promotion gate checks.
+    pub(crate) gate: bool,
+    /// Vulnerabilities at or above this severity fail the gate.
+    pub(crate) block_severity: String,
+}
+
+impl Default for DependencyAuditSettings {
+    fn default() -> Self {
+        DependencyAuditSettings {
+            gate: false,
+            block_severity: "high".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Package {
+    /// OSV ecosystem name: "crates.io", "npm" or "PyPI".
+    ecosystem: &'static str,
+    name: String,
+    version: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct Vulnerability {
+    id: String,
+    aliases: Vec<String>,
+    ecosystem: String,
+    package: String,
+    version: String,
+    /// "critical", "high", "moderate", "low" or "unknown"
+    severity: String,
+    summary: Option<String>,
+    fixed_versions: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub(crate) struct SeverityCounts {
+    critical: usize,
+    high: usize,
+    moderate: usize,
+    low: usize,
+    unknown: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct DependencyAudit {
+    repo_id: String,
+    /// Lockfiles and manifests that were read, relative to the repository.
+    sources: Vec<String>,
+    packages_scanned: usize,
+    /// requirements.txt entries without an exact `==` pin; OSV can't match
+    /// them to a version.
+    unpinned: Vec<String>,
+    counts: SeverityCounts,
+    vulnerabilities: Vec<Vulnerability>,
+    audited_at: String,
+}
+
+impl SeverityCounts {
+    fn add(&mut self, severity: &str) {
+        match severity {
+            "critical" => self.critical += 1,
+            "high" => self.high += 1,
+            "moderate" => self.moderate += 1,
+            "low" => self.low += 1,
+            _ => self.unknown += 1,
+        }
+    }
+
+    /// Findings at `severity` or worse.
+    fn at_least(&self, severity: &str) -> usize {
+        let counts = [self.critical, self.high, self.moderate, self.low];
+        let rank = SEVERITIES
+            .iter()
+            .position(|s| *s == severity)
+            .unwrap_or(SEVERITIES.len() - 1);
+        counts[..=rank].iter().sum()
+    }
+}
+
+fn report_path(repo_id: &str) -> PathBuf {
+    get_state_dir()
+        .join("dependency_audits")
+        .join(format!("{}.json", repo_id))
+}
+
+/// Registry packages from a Cargo.lock. Path and git dependencies have no
+/// advisories on crates.io and are skipped.
+fn cargo_lock(contents: &str) -> Vec<Package> {
+    let mut packages = Vec::new();
+    for block in contents.split("[[package]]").skip(1) {
+        let mut name = None;
+        let mut version = None;
+        let mut registry = false;
+        for line in block.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim().trim_matches('"');
+            match key.trim() {
+                "name" => name = Some(value.to_string()),
+                "version" => version = Some(value.to_string()),
+                "source" => registry = value.starts_with("registry+"),
+                _ => {}
+            }
+        }
+        if let (Some(name), Some(version), true) = (name, version, registry) {
+            packages.push(Package {
+                ecosystem: "crates.io",
+                name,
+                version,
+            });
+        }
+    }
+    packages
+}
+
+/// Installed packages from a package-lock.json, lockfile v2/v3 `packages`
+/// or v1 nested `dependencies`.
+fn package_lock(contents: &str) -> Result<Vec<Package>, String> {
+    let lock: Value = serde_json::from_str(contents)
+        .map_err(|e| format!("Failed to parse package-lock.json: {}", e))?;
+    let mut packages = Vec::new();
+    if let Some(entries) = lock["packages"].as_object() {
+        for (path, entry) in entries {
+            // "" is the project itself; linked workspaces have no version.
+            let Some(name) = path
+                .rsplit("node_modules/")
+                .next()
+                .filter(|_| !path.is_empty())
+            else {
+                continue;
+            };
+            if let Some(version) = entry["version"].as_str() {
+                packages.push(Package {
+                    ecosystem: "npm",
+                    name: entry["name"].as_str().unwrap_or(name).to_string(),
+                    version: version.to_string(),
+                });
+            }
+        }
+    } else {
+        let mut pending = vec![&lock["dependencies"]];
+        while let Some(dependencies) = pending.pop() {
+            let Some(dependencies) = dependencies.as_object() else {
+                continue;
+            };
+            for (name, entry) in dependencies {
+                if let Some(version) = entry["version"].as_str() {
+                    packages.push(Package {
+                        ecosystem: "npm",
+                        name: name.clone(),
+                        version: version.to_string(),
+                    });
+                }
+                pending.push(&entry["dependencies"]);
+            }
+        }
+    }
+    Ok(packages)
+}
+
+/// Pinned requirements, plus the names of any that aren't pinned.
+fn requirements(contents: &str) -> (Vec<Package>, Vec<String>) {
+    let mut packages = Vec::new();
+    let mut unpinned = Vec::new();
+    for line in contents.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        // Options (-r, -e, --hash) and URLs aren't packages we can look up.
+        if line.is_empty() || line.starts_with('-') || line.contains("://") {
+            continue;
+        }
+        let line = line.split(';').next().unwrap_or_default().trim();
+        match line.split_once("==") {
+            Some((name, version)) => packages.push(Package {
+                ecosystem: "PyPI",
+                name: name.split('[').next().unwrap_or(name).trim().to_string(),
+                version: version.trim().to_string(),
+            }),
+            None => unpinned.push(line.to_string()),
+        }
+    }
+    (packages, unpinned)
+}
+
+/// Every package the repository's lockfiles pin, with the files read.
+fn collect_packages(root: &Path) -> Result<(Vec<Package>, Vec<String>, Vec<String>), String> {
+    let mut packages = BTreeSet::new();
+    let mut sources = Vec::new();
+    let mut unpinned = Vec::new();
+    for file in ["Cargo.lock", "package-lock.json", "requirements.txt"] {
+        let path = root.join(file);
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(format!("Failed to read {}: {}", file, e)),
+        };
+        let found = match file {
+            "Cargo.lock" => cargo_lock(&contents),
+            "package-lock.json" => package_lock(&contents)?,
+            _ => {
+                let (found, loose) = requirements(&contents);
+                unpinned.extend(loose);
+                found
+            }
+        };
+        packages.extend(found);
+        sources.push(file.to_string());
+    }
+    Ok((packages.into_iter().collect(), sources, unpinned))
+}
+
+/// OSV ids affecting each package, in the order given.
+fn query_osv(agent: &ureq::Agent, packages: &[Package]) -> Result<Vec<Vec<String>>, String> {
+    let mut ids = Vec::with_capacity(packages.len());
+    for chunk in packages.chunks(OSV_BATCH_SIZE) {
+        let queries: Vec<Value> = chunk
+            .iter()
+            .map(|package| {
+                json!({
+                    "package": { "name": package.name, "ecosystem": package.ecosystem },
+                    "version": package.version,
+                })
+            })
+            .collect();
+        let response = json_response(
+            agent
+                .post(&format!("{}/querybatch", OSV_API))
+                .send_json(json!({ "queries": queries })),
+        )?;
+        let results = response["results"]
+            .as_array()
+            .ok_or("OSV returned no results")?;
+        for result in results {
+            ids.push(
+                result["vulns"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|vuln| vuln["id"].as_str().map(str::to_string))
+                    .collect(),
+            );
+        }
+    }
+    Ok(ids)
+}
+
+/// GitHub advisories carry a qualitative severity; other sources that only
+/// give a CVSS vector are reported as "unknown".
+fn severity_of(vuln: &Value) -> String {
+    let declared = vuln["database_specific"]["severity"].as_str().or_else(|| {
+        vuln["affected"]
+            .as_array()?
+            .iter()
+            .find_map(|affected| affected["ecosystem_specific"]["severity"].as_str())
+    });
+    match declared.map(str::to_lowercase).as_deref() {
+        Some("critical") => "critical",
+        Some("high") => "high",
+        Some("moderate") | Some("medium") => "moderate",
+        Some("low") => "low",
+        _ => "unknown",
+    }
+    .to_string()
+}
+
+fn fixed_versions(vuln: &Value, package: &Package) -> Vec<String> {
+    let mut fixed = BTreeSet::new();
+    for affected in vuln["affected"].as_array().into_iter().flatten() {
+        if affected["package"]["name"].as_str() != Some(package.name.as_str()) {
+            continue;
+        }
+        for range in affected["ranges"].as_array().into_iter().flatten() {
+            for event in range["events"].as_array().into_iter().flatten() {
+                if let Some(version) = event["fixed"].as_str() {
+                    fixed.insert(version.to_string());
+                }
+            }
+        }
+    }
+    fixed.into_iter().collect()
+}
+
+/// Look up the repository's locked dependencies in the OSV database and
+/// store the findings.
+#[tauri::command]
+pub(crate) fn audit_dependencies(repo_id: String) -> Result<DependencyAudit, String> {
+    let root = resolve_repo_path(&repo_id)?;
+    let (packages, sources, unpinned) = collect_packages(&root)?;
+    if sources.is_empty() {
+        return Err(
+            "No Cargo.lock, package-lock.json or requirements.txt found in the repository"
+                .to_string(),
+        );
+    }
+
+    let agent = agent();
+    let matches = query_osv(&agent, &packages)?;
+    // The same advisory often covers several packages; fetch it once.
+    let mut details: HashMap<String, Value> = HashMap::new();
+    let mut vulnerabilities = Vec::new();
+    let mut counts = SeverityCounts::default();
+    for (package, ids) in packages.iter().zip(matches) {
+        for id in ids {
+            if !details.contains_key(&id) {
+                let vuln = json_response(
+                    agent
+                        .get(&format!("{}/vulns/{}", OSV_API, encode_component(&id)))
+                        .call(),
+                )?;
+                details.insert(id.clone(), vuln);
+            }
+            let vuln = &details[&id];
+            let severity = severity_of(vuln);
+            counts.add(&severity);
+            vulnerabilities.push(Vulnerability {
+                aliases: vuln["aliases"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|alias| alias.as_str().map(str::to_string))
+                    .collect(),
+                ecosystem: package.ecosystem.to_string(),
+                package: package.name.clone(),
+                version: package.version.clone(),
+                severity,
+                summary: vuln["summary"].as_str().map(str::to_string),
+                fixed_versions: fixed_versions(vuln, package),
+                id,
+            });
+        }
+    }
+    let rank = |severity: &str| {
+        SEVERITIES
+            .iter()
+            .position(|s| *s == severity)
+            .unwrap_or(SEVERITIES.len())
+    };
+    vulnerabilities.sort_by(|a, b| {
+        rank(&a.severity)
+            .cmp(&rank(&b.severity))
+            .then_with(|| a.package.cmp(&b.package))
+    });
+
+    let report = DependencyAudit {
+        repo_id: repo_id.clone(),
+        sources,
+        packages_scanned: packages.len(),
+        unpinned,
+        counts,
+        vulnerabilities,
+        audited_at: Utc::now().to_rfc3339(),
+    };
+    write_json(&report_path(&repo_id), &report)?;
+    Ok(report)
+}
+
+/// The last stored audit for a repository, if it has been audited.
+#[tauri::command]
+pub(crate) fn get_dependency_audit(repo_id: String) -> Result<Option<DependencyAudit>, String> {
+    read_json(&report_path(&repo_id))
+}
+
+/// Gate result from the last stored audit, or `None` when the gate is off.
+pub(crate) fn gate_result(
+    repo_id: &str,
+    settings: &DependencyAuditSettings,
+) -> Option<Result<String, String>> {
+    if !settings.gate {
+        return None;
+    }
+    let report = match read_json::<DependencyAudit>(&report_path(repo_id)) {
+        Ok(Some(report)) => report,
+        Ok(None) => return Some(Err("Dependencies haven't been audited".to_string())),
+        Err(e) => return Some(Err(e)),
+    };
+    let blocking = report.counts.at_least(&settings.block_severity);
+    Some(if blocking == 0 {
+        Ok(format!(
+            "No {} or worse vulnerabilities in {} package(s)",
+            settings.block_severity, report.packages_scanned
+        ))
+    } else {
+        Err(format!(
+            "{} {} or worse vulnerabilities in dependencies",
+            blocking, settings.block_severity
+        ))
+    })
+}
@@ -0,0 +1,451 @@
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Read};
+use std::net::{SocketAddr, TcpStream};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use uuid::Uuid;
+
+use crate::audit::audited;
+use crate::commands::{load_workpad, resolve_repo_path, workpad_checkout_dir};
+use crate::logging::warn_on_err;
+
+/// Output lines kept per process for `get_dev_process_logs`.
+const LOG_CAPACITY: usize = 2000;
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// How long a stopped process gets to exit before it is killed.
+const STOP_GRACE: Duration = Duration::from_secs(5);
+const MAX_BACKOFF_SECS: u64 = 30;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub(crate) struct RestartPolicy {
+    /// "never", "on_failure" or "always"
+    mode: String,
+    max_restarts: u32,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        RestartPolicy {
+            mode: "on_failure".to_string(),
+            max_restarts: 5,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct DevProcessInfo {
+    proc_id: String,
+    repo_id: String,
+    workpad_id: Option<String>,
+    command: String,
+    cwd: String,
+    /// "running", "restarting", "stopped", "exited" or "crashed"
+    status: String,
+    pid: Option<u32>,
+    /// Ports the process announced in its output and that accept
+    /// connections.
+    ports: Vec<u16>,
+    restart_policy: RestartPolicy,
+    restarts: u32,
+    exit_code: Option<i32>,
+    started_at: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct DevProcessLine {
+    proc_id: String,
+    /// "stdout" or "stderr"
+    stream: String,
+    line: String,
+}
+
+struct DevProcess {
+    info: DevProcessInfo,
+    /// Ports seen in the output, confirmed when listed.
+    announced_ports: Vec<u16>,
+    logs: VecDeque<DevProcessLine>,
+    stop: Arc<AtomicBool>,
+}
+
+fn processes() -> &'static Mutex<HashMap<String, DevProcess>> {
+    static PROCESSES: OnceLock<Mutex<HashMap<String, DevProcess>>> = OnceLock::new();
+    PROCESSES.get_or_init(Mutex::default)
+}
+
+fn lock_processes() -> Result<MutexGuard<'static, HashMap<String, DevProcess>>, String> {
+    processes()
+        .lock()
+        .map_err(|_| "Dev process table poisoned".to_string())
+}
+
+fn port_patterns() -> &'static [Regex; 2] {
+    static PATTERNS: OnceLock<[Regex; 2]> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        [
+            Regex::new(
+                r"(?i)(?:localhost|127\.0\.0\.1|0\.0\.0\.0|\[::1?\]|[\w-]+\.local):(\d{2,5})\b",
+            )
+            .expect("valid address pattern"),
+            Regex::new(r"(?i)\b(?:port|listening on)\s*:?\s*(\d{2,5})\b")
+                .expect("valid port pattern"),
+        ]
+    })
+}
+
+fn ansi_escapes() -> &'static Regex {
+    static ANSI: OnceLock<Regex> = OnceLock::new();
+    ANSI.get_or_init(|| Regex::new(r"\x1b\[[0-9;?]*[A-Za-z]").expect("valid escape pattern"))
+}
+
+/// Ports a line of dev server output announces, e.g. Vite's
+/// "Local: http://localhost:5173/".
+fn announced_ports(line: &str) -> Vec<u16> {
+    let plain = ansi_escapes().replace_all(line, "");
+    port_patterns()
+        .iter()
+        .flat_map(|pattern| pattern.captures_iter(&plain))
+        .filter_map(|captures| captures[1].parse::<u16>().ok())
+        .filter(|port| *port >= 80)
+        .collect()
+}
+
+fn is_listening(port: u16) -> bool {
+    let address = SocketAddr::from(([127, 0, 0, 1], port));
+    TcpStream::connect_timeout(&address, Duration::from_millis(200)).is_ok()
+}
+
+fn shell_command(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.args(["-c", command]);
+        // Its own process group, so stopping `npm run dev` stops the
+        // server npm started too.
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut shell, 0);
+        shell
+    }
+}
+
+/// Ask the process (group) to exit, then kill it if it doesn't in time.
+fn terminate(child: &mut Child) {
+    let pid = child.id().to_string();
+    let asked = if cfg!(windows) {
+        Command::new("taskkill").args(["/PID", &pid, "/T"]).status()
+    } else {
+        Command::new("kill")
+            .args(["-TERM", &format!("-{}", pid)])
+            .status()
+    };
+    warn_on_err("Failed to signal dev process", asked);
+    let deadline = Instant::now() + STOP_GRACE;
+    while Instant::now() < deadline {
+        if matches!(child.try_wait(), Ok(Some(_))) {
+            return;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+    warn_on_err("Failed to kill dev process", child.kill());
+    warn_on_err("Failed to reap dev process", child.wait());
+}
+
+fn update(app: &tauri::AppHandle, proc_id: &str, change: impl FnOnce(&mut DevProcessInfo)) {
+    let info = lock_processes().ok().and_then(|mut processes| {
+        let process = processes.get_mut(proc_id)?;
+        change(&mut process.info);
+        Some(process.info.clone())
+    });
+    if let Some(info) = info {
+        warn_on_err(
+            "Failed to emit dev-process-changed",
+            app.emit_all("dev-process-changed", info),
+        );
+    }
+}
+
+fn collect_output(
+    app: tauri::AppHandle,
+    proc_id: String,
+    stream: &'static str,
+    reader: impl Read + Send + 'static,
+) {
+    thread::spawn(move || {
+        for line in BufReader::new(reader).lines().map_while(Result::ok) {
+            let entry = DevProcessLine {
+                proc_id: proc_id.clone(),
+                stream: stream.to_string(),
+                line,
+            };
+            if let Ok(mut processes) = lock_processes() {
+                if let Some(process) = processes.get_mut(&proc_id) {
+                    for port in announced_ports(&entry.line) {
+                        if !process.announced_ports.contains(&port) {
+                            process.announced_ports.push(port);
+                        }
+                    }
+                    if process.logs.len() == LOG_CAPACITY {
+                        process.logs.pop_front();
+                    }
+                    process.logs.push_back(entry.clone());
+                }
+            }
+            warn_on_err(
+                "Failed to emit dev-process-output",
+                app.emit_all("dev-process-output", entry),
+            );
+        }
+    });
+}
+
+/// Run the command until it is stopped, restarting it as the policy
+/// allows with a growing delay between attempts.
+fn supervise(app: tauri::AppHandle, proc_id: String, command: String, cwd: PathBuf) {
+    let Some((stop, policy)) = lock_processes().ok().and_then(|processes| {
+        let process = processes.get(&proc_id)?;
+        Some((process.stop.clone(), process.info.restart_policy.clone()))
+    }) else {
+        return;
+    };
+    thread::spawn(move || {
+        let mut restarts = 0;
+        loop {
+            let spawned = shell_command(&command)
+                .current_dir(&cwd)
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn();
+            let mut child = match spawned {
+                Ok(child) => child,
+                Err(e) => {
+                    tracing::warn!("Failed to start dev process {}: {}", command, e);
+                    update(&app, &proc_id, |info| info.status = "crashed".to_string());
+                    return;
+                }
+            };
+            if let Some(stdout) = child.stdout.take() {
+                collect_output(app.clone(), proc_id.clone(), "stdout", stdout);
+            }
+            if let Some(stderr) = child.stderr.take() {
+                collect_output(app.clone(), proc_id.clone(), "stderr", stderr);
+            }
+            let pid = child.id();
+            update(&app, &proc_id, |info| {
+                info.status = "running".to_string();
+                info.pid = Some(pid);
+                info.exit_code = None;
+                info.restarts = restarts;
+                info.started_at = Utc::now().to_rfc3339();
+            });
+
+            let exit_code = loop {
+                if stop.load(Ordering::SeqCst) {
+                    terminate(&mut child);
+                    update(&app, &proc_id, |info| {
+                        info.status = "stopped".to_string();
+                        info.pid = None;
+                    });
+                    return;
+                }
+                match child.try_wait() {
+                    Ok(Some(status)) => break status.code(),
+                    Ok(None) => thread::sleep(POLL_INTERVAL),
+                    Err(e) => {
+                        tracing::warn!("Failed to wait for dev process {}: {}", proc_id, e);
+                        break None;
+                    }
+                }
+            };
+
+            let failed = exit_code != Some(0);
+            let restart = match policy.mode.as_str() {
+                "always" => true,
+                "on_failure" => failed,
+                _ => false,
+            } && restarts < policy.max_restarts;
+            if let Ok(mut processes) = lock_processes() {
+                if let Some(process) = processes.get_mut(&proc_id) {
+                    process.announced_ports.clear();
+                }
+            }
+            update(&app, &proc_id, |info| {
+                info.exit_code = exit_code;
+                info.pid = None;
+                info.ports.clear();
+                info.status = match (restart, failed) {
+                    (true, _) => "restarting",
+                    (false, true) => "crashed",
+                    (false, false) => "exited",
+                }
+                .to_string();
+            });
+            if !restart {
+                return;
+            }
+            restarts += 1;
+            let backoff =
+                Instant::now() + Duration::from_secs(u64::from(restarts).min(MAX_BACKOFF_SECS));
+            while Instant::now() < backoff {
+                if stop.load(Ordering::SeqCst) {
+                    update(&app, &proc_id, |info| info.status = "stopped".to_string());
+                    return;
+                }
+                thread::sleep(POLL_INTERVAL);
+            }
+        }
+    });
+}
+
+/// Confirm announced ports against what actually accepts connections.
+fn refresh_ports(process: &mut DevProcess) {
+    if process.info.pid.is_some() {
+        process.info.ports = process
+            .announced_ports
+            .iter()
+            .copied()
+            .filter(|port| is_listening(*port))
+            .collect();
+    }
+}
+
+/// Start a long-running command, such as `npm run dev`, in the repository
+/// or a workpad checkout and keep it running per `restart_policy`.
+#[tauri::command]
+pub(crate) fn start_dev_process(
+    app: tauri::AppHandle,
+    repo_id: String,
+    command: String,
+    workpad_id: Option<String>,
+    restart_policy: Option<RestartPolicy>,
+) -> Result<DevProcessInfo, String> {
+    if command.trim().is_empty() {
+        return Err("Command cannot be empty".to_string());
+    }
+    let restart_policy = restart_policy.unwrap_or_default();
+    if !["never", "on_failure", "always"].contains(&restart_policy.mode.as_str()) {
+        return Err(format!("Unknown restart policy: {}", restart_policy.mode));
+    }
+    audited(
+        "start_dev_process",
+        "repository",
+        Some(repo_id.clone()),
+        None,
+        move || {
+            let cwd = match &workpad_id {
+                Some(workpad_id) => {
+                    let workpad = load_workpad(workpad_id)?;
+                    if workpad.repo_id != repo_id {
+                        return Err(format!(
+                            "Workpad {} doesn't belong to {}",
+                            workpad_id, repo_id
+                        ));
+                    }
+                    workpad_checkout_dir(&workpad)?
+                }
+                None => resolve_repo_path(&repo_id)?,
+            };
+            let info = DevProcessInfo {
+                proc_id: format!("proc-{}", Uuid::new_v4().simple()),
+                repo_id,
+                workpad_id,
+                command: command.trim().to_string(),
+                cwd: cwd.display().to_string(),
+                status: "running".to_string(),
+                pid: None,
+                ports: Vec::new(),
+                restart_policy,
+                restarts: 0,
+                exit_code: None,
+                started_at: Utc::now().to_rfc3339(),
+            };
+            lock_processes()?.insert(
+                info.proc_id.clone(),
+                DevProcess {
+                    info: info.clone(),
+                    announced_ports: Vec::new(),
+                    logs: VecDeque::new(),
+                    stop: Arc::new(AtomicBool::new(false)),
+                },
+            );
+            supervise(app, info.proc_id.clone(), info.command.clone(), cwd);
+            Ok(info)
+        },
+    )
+}
+
+/// Stop a dev process. It stays listed, with its log tail, until cleared;
+/// the final status arrives as a "dev-process-changed" event.
+#[tauri::command]
+pub(crate) fn stop_dev_process(proc_id: String) -> Result<DevProcessInfo, String> {
+    let mut processes = lock_processes()?;
+    let process = processes
+        .get_mut(&proc_id)
+        .ok_or_else(|| format!("Dev process not found: {}", proc_id))?;
+    process.stop.store(true, Ordering::SeqCst);
+    Ok(process.info.clone())
+}
+
+#[tauri::command]
+pub(crate) fn list_dev_processes(repo_id: Option<String>) -> Result<Vec<DevProcessInfo>, String> {
+    let mut processes = lock_processes()?;
+    let mut infos: Vec<DevProcessInfo> = processes
+        .values_mut()
+        .filter(|process| repo_id.is_none() || repo_id.as_deref() == Some(&process.info.repo_id))
+        .map(|process| {
+            refresh_ports(process);
+            process.info.clone()
+        })
+        .collect();
+    infos.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+    Ok(infos)
+}
+
+/// Whether a dev process is running, or about to restart, in `workpad_id`'s
+/// checkout.
+pub(crate) fn is_running_for(workpad_id: &str) -> Result<bool, String> {
+    Ok(lock_processes()?.values().any(|process| {
+        process.info.workpad_id.as_deref() == Some(workpad_id)
+            && matches!(process.info.status.as_str(), "running" | "restarting")
+    }))
+}
+
+/// The last `lines` output lines of a dev process, oldest first.
+#[tauri::command]
+pub(crate) fn get_dev_process_logs(
+    proc_id: String,
+    lines: Option<usize>,
+) -> Result<Vec<DevProcessLine>, String> {
+    let processes = lock_processes()?;
+    let process = processes
+        .get(&proc_id)
+        .ok_or_else(|| format!("Dev process not found: {}", proc_id))?;
+    let lines = lines.unwrap_or(200).min(process.logs.len());
+    Ok(process
+        .logs
+        .iter()
+        .skip(process.logs.len() - lines)
+        .cloned()
+        .collect())
+}
+
+/// Drop stopped and finished processes from the list.
+#[tauri::command]
+pub(crate) fn clear_dev_processes() -> Result<usize, String> {
+    let mut processes = lock_processes()?;
+    let before = processes.len();
+    processes.retain(|_, process| matches!(process.info.status.as_str(), "running" | "restarting"));
+    Ok(before - processes.len())
+}
@@ -0,0 +1,161 @@
+use std::cell::RefCell;
+
+use serde::{Deserialize, Serialize};
+
+use crate::git::{open_repository, resolve_commit};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct DiffLine {
+    /// "+", "-" or " "
+    origin: String,
+    content: String,
+    old_lineno: Option<u32>,
+    new_lineno: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct DiffHunk {
+    header: String,
+    old_start: u32,
+    old_lines: u32,
+    new_start: u32,
+    new_lines: u32,
+    lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct FileDiff {
+    old_path: Option<String>,
+    new_path: Option<String>,
+    /// "added", "deleted", "modified", "renamed", "copied" or "typechange"
+    status: String,
+    is_binary: bool,
+    additions: usize,
+    deletions: usize,
+    hunks: Vec<DiffHunk>,
+}
+
+fn delta_status(status: git2::Delta) -> &'static str {
+    match status {
+        git2::Delta::Added | git2::Delta::Untracked => "added",
+        git2::Delta::Deleted => "deleted",
+        git2::Delta::Renamed => "renamed",
+        git2::Delta::Copied => "copied",
+        git2::Delta::Typechange => "typechange",
+        _ => "modified",
+    }
+}
+
+fn path_string(file: &git2::DiffFile) -> Option<String> {
+    file.path().map(|p| p.to_string_lossy().to_string())
+}
+
+/// Convert a libgit2 diff into per-file hunks with line-level detail.
+pub(crate) fn structure_diff(diff: &git2::Diff) -> Result<Vec<FileDiff>, String> {
+    // foreach hands out separate callbacks that all need the output list.
+    let files: RefCell<Vec<FileDiff>> = RefCell::new(Vec::new());
+
+    diff.foreach(
+        &mut |delta, _| {
+            files.borrow_mut().push(FileDiff {
+                old_path: path_string(&delta.old_file()),
+                new_path: path_string(&delta.new_file()),
+                status: delta_status(delta.status()).to_string(),
+                is_binary: delta.flags().is_binary(),
+                additions: 0,
+                deletions: 0,
+                hunks: Vec::new(),
+            });
+            true
+        },
+        Some(&mut |_, _| {
+            if let Some(file) = files.borrow_mut().last_mut() {
+                file.is_binary = true;
+            }
+            true
+        }),
+        Some(&mut |_, hunk| {
+            if let Some(file) = files.borrow_mut().last_mut() {
+                file.hunks.push(DiffHunk {
+                    header: String::from_utf8_lossy(hunk.header())
+                        .trim_end()
+                        .to_string(),
+                    old_start: hunk.old_start(),
+                    old_lines: hunk.old_lines(),
+                    new_start: hunk.new_start(),
+                    new_lines: hunk.new_lines(),
+                    lines: Vec::new(),
+                });
+            }
+            true
+        }),
+        Some(&mut |_, _, line| {
+            let origin = line.origin();
+            if !matches!(origin, '+' | '-' | ' ') {
+                return true;
+            }
+            if let Some(file) = files.borrow_mut().last_mut() {
+                match origin {
+                    '+' => file.additions += 1,
+                    '-' => file.deletions += 1,
+                    _ => {}
+                }
+                if let Some(hunk) = file.hunks.last_mut() {
+                    hunk.lines.push(DiffLine {
+                        origin: origin.to_string(),
+                        content: String::from_utf8_lossy(line.content())
+                            .trim_end_matches('\n')
+                            .to_string(),
+                        old_lineno: line.old_lineno(),
+                        new_lineno: line.new_lineno(),
+                    });
+                }
+            }
+            true
+        }),
+    )
+    .map_err(|e| format!("Failed to walk diff: {}", e.message()))?;
+
+    Ok(files.into_inner())
+}
+
+/// Diff two revisions with rename detection, optionally limited to `paths`.
+pub(crate) fn diff_revisions(
+    repo: &git2::Repository,
+    from_rev: &str,
+    to_rev: &str,
+    paths: &[String],
+) -> Result<Vec<FileDiff>, String> {
+    let from_tree = resolve_commit(repo, from_rev)?
+        .tree()
+        .map_err(|e| e.message().to_string())?;
+    let to_tree = resolve_commit(repo, to_rev)?
+        .tree()
+        .map_err(|e| e.message().to_string())?;
+
+    let mut options = git2::DiffOptions::new();
+    for path in paths {
+        options.pathspec(path);
+    }
+    let mut diff = repo
+        .diff_tree_to_tree(Some(&from_tree), Some(&to_tree), Some(&mut options))
+        .map_err(|e| format!("Failed to diff {}..{}: {}", from_rev, to_rev, e.message()))?;
+
+    let mut find = git2::DiffFindOptions::new();
+    find.renames(true);
+    diff.find_similar(Some(&mut find))
+        .map_err(|e| format!("Failed to detect renames: {}", e.message()))?;
+
+    structure_diff(&diff)
+}
+
+#[tauri::command]
+pub(crate) fn diff_refs(
+    repo_id: String,
+    from_rev: String,
+    to_rev: String,
+    paths: Option<Vec<String>>,
+) -> Result<Vec<FileDiff>, String> {
+    let repo = open_repository(&repo_id)?;
+    diff_revisions(&repo, &from_rev, &to_rev, &paths.unwrap_or_default())
+}
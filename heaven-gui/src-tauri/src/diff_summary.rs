@@ -0,0 +1,217 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::ai::{ai_settings, run_operation, ChatMessage};
+use crate::commands::{load_workpad, resolve_repo_path, save_workpad};
+use crate::git::{run_git, workpad_diff};
+use crate::tokens::count_tokens;
+use crate::WorkpadState;
+
+const CHUNK_SYSTEM_PROMPT: &str = "You summarize one part of a larger code change for a \
+reviewer. For each file, say in one or two short bullet points what changed and, where the \
+diff makes it clear, why. Don't speculate beyond the diff.";
+
+const DESCRIBE_SYSTEM_PROMPT: &str = "You write the description of a code change for a pull \
+request. Start with a one-line summary in the imperative mood, then a blank line, a short \
+overview paragraph and a bulleted list of the notable changes grouped by area. Plain \
+Markdown, no headings.";
+
+/// A reviewable description of a workpad's changes.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct DiffSummary {
+    pub(crate) summary: String,
+    /// Branch tip the summary describes; it is stale once the branch moves.
+    pub(crate) head: String,
+    /// How many pieces the diff was split into; 1 when it fit in one request.
+    chunks: usize,
+    operation_ids: Vec<String>,
+    cost_usd: f64,
+    created_at: String,
+}
+
+/// Per-file sections of a unified diff, each starting at its
+/// `diff --git` line.
+fn file_sections(diff: &str) -> Vec<&str> {
+    let mut starts: Vec<usize> = diff
+        .match_indices("diff --git ")
+        .map(|(at, _)| at)
+        .filter(|at| *at == 0 || diff.as_bytes()[at - 1] == b'\n')
+        .collect();
+    if starts.first() != Some(&0) {
+        starts.insert(0, 0);
+    }
+    starts
+        .iter()
+        .zip(starts.iter().skip(1).chain([&diff.len()]))
+        .map(|(start, end)| &diff[*start..*end])
+        .filter(|section| !section.trim().is_empty())
+        .collect()
+}
+
+/// Split one file's diff by lines into pieces under `budget` tokens. Each
+/// continuation repeats the file header so it can be read on its own.
+fn split_section(section: &str, budget: usize, model: &str) -> Vec<String> {
+    let header = section.lines().next().unwrap_or_default();
+    let mut pieces = Vec::new();
+    let mut current = String::new();
+    let mut tokens = 0;
+    for line in section.split_inclusive('\n') {
+        let line_tokens = count_tokens(line, model);
+        if !current.is_empty() && tokens + line_tokens > budget {
+            pieces.push(std::mem::take(&mut current));
+            current = format!("{} (continued)\n", header);
+            tokens = count_tokens(&current, model);
+        }
+        current.push_str(line);
+        tokens += line_tokens;
+    }
+    if !current.is_empty() {
+        pieces.push(current);
+    }
+    pieces
+}
+
+/// Group whole files into chunks of at most `budget` tokens, splitting
+/// only files that don't fit on their own.
+fn chunk_diff(diff: &str, budget: usize, model: &str) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut tokens = 0;
+    for section in file_sections(diff) {
+        let section_tokens = count_tokens(section, model);
+        let pieces = if section_tokens > budget {
+            split_section(section, budget, model)
+                .into_iter()
+                .map(|piece| {
+                    let piece_tokens = count_tokens(&piece, model);
+                    (piece, piece_tokens)
+                })
+                .collect()
+        } else {
+            vec![(section.to_string(), section_tokens)]
+        };
+        for (piece, piece_tokens) in pieces {
+            if !current.is_empty() && tokens + piece_tokens > budget {
+                chunks.push(std::mem::take(&mut current));
+                tokens = 0;
+            }
+            current.push_str(&piece);
+            tokens += piece_tokens;
+        }
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Send one summarization request, recording its operation on `summary`.
+fn ask(
+    workpad: &WorkpadState,
+    prompt: &str,
+    system: &str,
+    content: String,
+    summary: &mut DiffSummary,
+) -> Result<String, String> {
+    let messages = [ChatMessage::system(system), ChatMessage::user(content)];
+    let (operation, completion) = run_operation(
+        Some(workpad.workpad_id.clone()),
+        "diff_summary",
+        prompt,
+        &messages,
+        None,
+    )?;
+    summary.operation_ids.push(operation.operation_id.clone());
+    summary.cost_usd += operation.cost_usd;
+    completion
+        .map(|completion| completion.content.trim().to_string())
+        .ok_or_else(|| {
+            operation
+                .error
+                .clone()
+                .unwrap_or_else(|| "AI request failed".to_string())
+        })
+}
+
+pub(crate) fn workpad_head(workpad: &WorkpadState) -> Result<String, String> {
+    let repo_dir = resolve_repo_path(&workpad.repo_id)?;
+    Ok(run_git(
+        &repo_dir,
+        &["rev-parse", &format!("refs/heads/{}", workpad.branch_name)],
+    )?
+    .trim()
+    .to_string())
+}
+
+/// The stored summary, if it still describes the workpad's branch.
+pub(crate) fn current_summary(workpad: &WorkpadState) -> Option<String> {
+    let summary = workpad.diff_summary.as_ref()?;
+    let head = workpad_head(workpad).ok()?;
+    (summary.head == head).then(|| summary.summary.clone())
+}
+
+/// Describe a workpad's changes for review. Diffs over
+/// `ai.summary_chunk_tokens` are split by file (and by lines within very
+/// large files), each chunk is summarized separately and the summaries are
+/// merged into one description. The result is stored on the workpad and
+/// used as the default pull request body.
+#[tauri::command]
+pub(crate) fn summarize_diff(workpad_id: String) -> Result<DiffSummary, String> {
+    let workpad = load_workpad(&workpad_id)?;
+    let diff = workpad_diff(&workpad)?;
+    if diff.trim().is_empty() {
+        return Err(format!("Workpad {} has no changes", workpad_id));
+    }
+    let ai = ai_settings();
+    let chunks = chunk_diff(&diff, ai.summary_chunk_tokens.max(1), &ai.model);
+
+    let mut summary = DiffSummary {
+        summary: String::new(),
+        head: workpad_head(&workpad)?,
+        chunks: chunks.len(),
+        operation_ids: Vec::new(),
+        cost_usd: 0.0,
+        created_at: Utc::now().to_rfc3339(),
+    };
+    let context = if chunks.len() == 1 {
+        format!("Workpad: {}\n\n```diff\n{}```", workpad.title, chunks[0])
+    } else {
+        let total = chunks.len();
+        let mut parts = Vec::with_capacity(total);
+        for (index, chunk) in chunks.iter().enumerate() {
+            let part = ask(
+                &workpad,
+                &format!(
+                    "Summarize part {} of {} of {}",
+                    index + 1,
+                    total,
+                    workpad_id
+                ),
+                CHUNK_SYSTEM_PROMPT,
+                format!("Part {} of {}:\n\n```diff\n{}```", index + 1, total, chunk),
+                &mut summary,
+            )?;
+            parts.push(format!("Part {}:\n{}", index + 1, part));
+        }
+        format!(
+            "Workpad: {}\n\nSummaries of the {} parts of the change:\n\n{}",
+            workpad.title,
+            total,
+            parts.join("\n\n")
+        )
+    };
+    let description = ask(
+        &workpad,
+        &format!("Describe changes in {}", workpad_id),
+        DESCRIBE_SYSTEM_PROMPT,
+        context,
+        &mut summary,
+    )?;
+    summary.summary = description;
+
+    // Reload: the operations were linked to the workpad while this ran.
+    let mut workpad = load_workpad(&workpad_id)?;
+    workpad.diff_summary = Some(summary.clone());
+    save_workpad(workpad)?;
+    Ok(summary)
+}
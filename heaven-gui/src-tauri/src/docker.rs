@@ -0,0 +1,232 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use uuid::Uuid;
+
+use crate::get_state_dir;
+use crate::logging::warn_on_err;
+use crate::sandbox::{execute, LineSink, SandboxConfig, SandboxOutput};
+
+/// Dockerfiles that, when present in the checkout, are built into the
+/// repository's test image instead of pulling `docker_image`.
+const TEST_DOCKERFILES: &[&str] = &["Dockerfile.test", ".sologit/test.Dockerfile"];
+
+fn docker(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("docker")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to execute docker: {}", e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(format!(
+            "docker {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+fn repo_image_tag(repo_id: &str) -> String {
+    let sanitized: String = repo_id
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("sologit-test/{}:latest", sanitized)
+}
+
+/// Build the repository's test image if it ships a test Dockerfile, otherwise
+/// make sure the configured image is available locally. Returns the image ref.
+pub(crate) fn prepare_test_image(
+    repo_id: &str,
+    config: &SandboxConfig,
+    checkout: &Path,
+) -> Result<String, String> {
+    let dockerfile = TEST_DOCKERFILES
+        .iter()
+        .map(|name| checkout.join(name))
+        .find(|path| path.is_file());
+
+    if let Some(dockerfile) = dockerfile {
+        let tag = repo_image_tag(repo_id);
+        let dockerfile_str = dockerfile
+            .to_str()
+            .ok_or_else(|| format!("Invalid Dockerfile path: {}", dockerfile.display()))?;
+        let context = checkout
+            .to_str()
+            .ok_or_else(|| format!("Invalid checkout path: {}", checkout.display()))?;
+        docker(&["build", "-q", "-t", &tag, "-f", dockerfile_str, context])?;
+        return Ok(tag);
+    }
+
+    configured_image(config)
+}
+
+/// `docker_image`, pulled if it isn't available locally yet.
+fn configured_image(config: &SandboxConfig) -> Result<String, String> {
+    let image = config.docker_image.clone().ok_or_else(|| {
+        "Docker backend requires docker_image or a Dockerfile.test in the repository".to_string()
+    })?;
+
+    if docker(&["image", "inspect", &image]).is_err() {
+        docker(&["pull", &image])?;
+    }
+    Ok(image)
+}
+
+/// Content-addressed identifier for an image, preferring the registry digest.
+pub(crate) fn image_digest(image: &str) -> Option<String> {
+    docker(&[
+        "image",
+        "inspect",
+        "--format",
+        "{{if .RepoDigests}}{{index .RepoDigests 0}}{{else}}{{.Id}}{{end}}",
+        image,
+    ])
+    .ok()
+    .filter(|digest| !digest.is_empty())
+}
+
+fn scratch_dir(run_id: &str) -> PathBuf {
+    get_state_dir().join("scratch").join(run_id)
+}
+
+/// Where a container run's `coverage/` and `target/` end up, laid out like
+/// the working directory so coverage reports can be read from it.
+pub(crate) fn output_dir(run_id: &str) -> PathBuf {
+    scratch_dir(run_id).join("out")
+}
+
+/// Drop a finished run's scratch volume.
+pub(crate) fn remove_scratch(run_id: &str) {
+    let scratch = scratch_dir(run_id);
+    if scratch.exists() {
+        warn_on_err(
+            "Failed to remove scratch volume",
+            fs::remove_dir_all(&scratch),
+        );
+    }
+}
+
+/// Run `argv` in `cwd` in a container from the configured `docker_image`,
+/// for hooks and tools. Formatters edit the checkout in place, so unlike a
+/// test run it is mounted writable.
+pub(crate) fn run_sandboxed(
+    config: &SandboxConfig,
+    argv: &[String],
+    cwd: &Path,
+    sink: Option<LineSink>,
+) -> Result<SandboxOutput, String> {
+    let image = configured_image(config)?;
+    let run_id = Uuid::new_v4().simple().to_string();
+    let result = run_in_container(&run_id, &image, config, argv, cwd, ".", true, sink);
+    remove_scratch(&run_id);
+    result
+}
+
+/// Run `argv` in a throwaway container with the checkout mounted at
+/// /workspace and a writable scratch volume for caches and build output.
+/// Unless `writable`, the checkout is read-only and coverage written to the
+/// working directory's `coverage/` lands in `output_dir` instead. Callers
+/// drop the volume with `remove_scratch`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn run_in_container(
+    run_id: &str,
+    image: &str,
+    config: &SandboxConfig,
+    argv: &[String],
+    checkout: &Path,
+    working_dir: &str,
+    writable: bool,
+    sink: Option<LineSink>,
+) -> Result<SandboxOutput, String> {
+    let scratch = scratch_dir(run_id);
+    fs::create_dir_all(&scratch)
+        .map_err(|e| format!("Failed to create {}: {}", scratch.display(), e))?;
+
+    let container_name = format!("sologit-{}", run_id);
+    let workdir = if working_dir == "." {
+        "/workspace".to_string()
+    } else {
+        format!("/workspace/{}", working_dir)
+    };
+
+    let workspace_mount = format!(
+        "{}:/workspace{}",
+        checkout.display(),
+        if writable { "" } else { ":ro" }
+    );
+    let scratch_mount = format!("{}:/scratch", scratch.display());
+
+    let mut command = Command::new("docker");
+    command.args([
+        "run",
+        "--rm",
+        "--name",
+        container_name.as_str(),
+        "-v",
+        workspace_mount.as_str(),
+        "-v",
+        scratch_mount.as_str(),
+        "-w",
+        workdir.as_str(),
+        "-e",
+        "HOME=/scratch",
+        "-e",
+        "XDG_CACHE_HOME=/scratch/cache",
+        "-e",
+        "CARGO_TARGET_DIR=/scratch/out/target",
+        "-e",
+        "PYTHONDONTWRITEBYTECODE=1",
+        "-e",
+        "npm_config_cache=/scratch/npm",
+    ]);
+    if !writable {
+        let coverage = output_dir(run_id).join("coverage");
+        fs::create_dir_all(&coverage)
+            .map_err(|e| format!("Failed to create {}: {}", coverage.display(), e))?;
+        // The mount point has to exist in the read-only checkout.
+        let mount_point = checkout.join(working_dir).join("coverage");
+        fs::create_dir_all(&mount_point)
+            .map_err(|e| format!("Failed to create {}: {}", mount_point.display(), e))?;
+        command
+            .arg("-v")
+            .arg(format!("{}:{}/coverage", coverage.display(), workdir));
+    }
+    if let Some(memory) = config.memory_mb {
+        command.arg(format!("--memory={}m", memory));
+    }
+    if let Some(cpus) = config.cpus {
+        command.arg(format!("--cpus={}", cpus));
+    }
+    if !config.allow_network {
+        command.arg("--network=none");
+    }
+    for key in &config.env_allowlist {
+        if key != "PATH" && key != "HOME" && env::var(key).is_ok() {
+            command.arg("-e").arg(key);
+        }
+    }
+    command.arg(image).args(argv);
+
+    let result = execute(command, config.timeout(), sink);
+
+    if matches!(&result, Ok(output) if output.timed_out) {
+        // Killing the docker client leaves the container running.
+        warn_on_err(
+            "Failed to kill timed-out container",
+            docker(&["kill", &container_name]),
+        );
+    }
+    result
+}
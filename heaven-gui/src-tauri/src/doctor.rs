@@ -0,0 +1,302 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use chrono::Utc;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::cli_config::effective_settings;
+use crate::http::{agent, json_response};
+use crate::{
+    get_state_dir, AIOperation, GlobalState, PromotionRecord, RepositoryState, TestRun,
+    WorkpadState,
+};
+use crate::{ollama, paths};
+
+const MIN_GIT_VERSION: (u32, u32) = (2, 25);
+const MIN_CLI_VERSION: (u32, u32) = (0, 4);
+
+/// Directories the CLI creates on first run and the GUI expects to read.
+const STATE_DIRS: &[&str] = &[
+    "repositories",
+    "workpads",
+    "test_runs",
+    "ai_operations",
+    "promotions",
+];
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct DoctorCheck {
+    name: String,
+    /// "ok", "warning" or "error"
+    severity: String,
+    message: String,
+    fix: Option<String>,
+}
+
+impl DoctorCheck {
+    fn ok(name: &str, message: impl Into<String>) -> Self {
+        DoctorCheck {
+            name: name.to_string(),
+            severity: "ok".to_string(),
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    fn warning(name: &str, message: impl Into<String>, fix: impl Into<String>) -> Self {
+        DoctorCheck {
+            severity: "warning".to_string(),
+            fix: Some(fix.into()),
+            ..DoctorCheck::ok(name, message)
+        }
+    }
+
+    fn error(name: &str, message: impl Into<String>, fix: impl Into<String>) -> Self {
+        DoctorCheck {
+            severity: "error".to_string(),
+            fix: Some(fix.into()),
+            ..DoctorCheck::ok(name, message)
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct DoctorReport {
+    healthy: bool,
+    warnings: usize,
+    errors: usize,
+    checks: Vec<DoctorCheck>,
+    generated_at: String,
+}
+
+/// Pull "major.minor" out of output like "git version 2.43.0" or "evogitctl 0.4.1".
+fn parse_version(output: &str) -> Option<(u32, u32)> {
+    let token = output.split_whitespace().find(|word| {
+        word.trim_start_matches('v')
+            .starts_with(|c: char| c.is_ascii_digit())
+    })?;
+    let mut parts = token
+        .trim_start_matches('v')
+        .split(|c: char| !c.is_ascii_digit())
+        .filter_map(|part| part.parse::<u32>().ok());
+    Some((parts.next()?, parts.next().unwrap_or(0)))
+}
+
+fn check_tool(name: &str, binary: &Path, minimum: (u32, u32), install_hint: &str) -> DoctorCheck {
+    let output = match Command::new(binary).arg("--version").output() {
+        Ok(output) if output.status.success() => output,
+        Ok(output) => {
+            return DoctorCheck::error(
+                name,
+                format!(
+                    "{} --version failed: {}",
+                    binary.display(),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+                install_hint,
+            )
+        }
+        Err(e) => {
+            return DoctorCheck::error(
+                name,
+                format!("Could not run {}: {}", binary.display(), e),
+                install_hint,
+            )
+        }
+    };
+
+    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    match parse_version(&text) {
+        Some(version) if version >= minimum => DoctorCheck::ok(name, text),
+        Some((major, minor)) => DoctorCheck::error(
+            name,
+            format!(
+                "{} {}.{} is older than the supported {}.{}",
+                binary.display(),
+                major,
+                minor,
+                minimum.0,
+                minimum.1
+            ),
+            install_hint,
+        ),
+        None => DoctorCheck::warning(
+            name,
+            format!(
+                "Could not determine {} version from \"{}\"",
+                binary.display(),
+                text
+            ),
+            install_hint,
+        ),
+    }
+}
+
+fn check_state_layout(state_dir: &Path) -> DoctorCheck {
+    if !state_dir.exists() {
+        return DoctorCheck::error(
+            "state_layout",
+            format!("State directory {} does not exist", state_dir.display()),
+            "Initialize a repository with `evogitctl repo init` to create it",
+        );
+    }
+
+    let missing: Vec<&str> = STATE_DIRS
+        .iter()
+        .copied()
+        .filter(|dir| !state_dir.join(dir).is_dir())
+        .collect();
+    if missing.is_empty() {
+        DoctorCheck::ok(
+            "state_layout",
+            format!("{} is laid out correctly", state_dir.display()),
+        )
+    } else {
+        DoctorCheck::warning(
+            "state_layout",
+            format!("Missing state directories: {}", missing.join(", ")),
+            "These are created on demand; run any evogitctl command to initialise them",
+        )
+    }
+}
+
+/// Try to deserialize every JSON file in `dir` as `T`.
+fn check_records<T: DeserializeOwned>(name: &str, dir: &Path) -> DoctorCheck {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return DoctorCheck::ok(name, "No records"),
+    };
+
+    let mut valid = 0;
+    let mut invalid = Vec::new();
+    for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+        if path.extension().and_then(|s| s.to_str()) != Some("json") {
+            continue;
+        }
+        let parsed = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| serde_json::from_str::<T>(&contents).map_err(|e| e.to_string()));
+        match parsed {
+            Ok(_) => valid += 1,
+            Err(error) => invalid.push(format!(
+                "{}: {}",
+                path.file_name().unwrap_or_default().to_string_lossy(),
+                error
+            )),
+        }
+    }
+
+    if invalid.is_empty() {
+        DoctorCheck::ok(name, format!("{} records valid", valid))
+    } else {
+        DoctorCheck::error(
+            name,
+            format!(
+                "{} of {} records failed to parse: {}",
+                invalid.len(),
+                valid + invalid.len(),
+                invalid.join("; ")
+            ),
+            "Restore the files from a backup or remove them",
+        )
+    }
+}
+
+fn check_global_state(state_dir: &Path) -> DoctorCheck {
+    let path = state_dir.join("global.json");
+    if !path.exists() {
+        return DoctorCheck::ok("global_state", "No global state yet; defaults will be used");
+    }
+    let parsed = fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|contents| {
+            serde_json::from_str::<GlobalState>(&contents).map_err(|e| e.to_string())
+        });
+    match parsed {
+        Ok(state) => DoctorCheck::ok("global_state", format!("Schema version {}", state.version)),
+        Err(error) => DoctorCheck::error(
+            "global_state",
+            format!("global.json is invalid: {}", error),
+            "Delete global.json; it is recreated with defaults on next use",
+        ),
+    }
+}
+
+fn check_ai_provider() -> DoctorCheck {
+    let ai = effective_settings().unwrap_or_default().ai;
+    if !ai.enabled {
+        return DoctorCheck::ok("ai_provider", "AI features are disabled");
+    }
+
+    match ai.api_key.as_deref().filter(|key| !key.trim().is_empty()) {
+        Some(api_key) => {
+            let url = format!("{}/models", ai.base_url.trim_end_matches('/'));
+            let request = agent()
+                .get(&url)
+                .set("Authorization", &format!("Bearer {}", api_key));
+            match json_response(request.call()) {
+                Ok(_) => DoctorCheck::ok("ai_provider", format!("Connected to {}", ai.base_url)),
+                Err(error) => DoctorCheck::error(
+                    "ai_provider",
+                    error,
+                    "Check the API key and base URL in AI settings",
+                ),
+            }
+        }
+        None => match ollama::installed_models(&ai) {
+            Ok(models) if models.is_empty() => DoctorCheck::warning(
+                "ai_provider",
+                format!("Ollama at {} has no models installed", ai.ollama_url),
+                format!("Pull a model, e.g. `ollama pull {}`", ai.ollama_model),
+            ),
+            Ok(models) => DoctorCheck::ok(
+                "ai_provider",
+                format!("Ollama reachable with {} models", models.len()),
+            ),
+            Err(error) => DoctorCheck::warning(
+                "ai_provider",
+                error,
+                "Start Ollama or configure a cloud API key in AI settings",
+            ),
+        },
+    }
+}
+
+#[tauri::command]
+pub(crate) fn run_doctor() -> Result<DoctorReport, String> {
+    let state_dir = get_state_dir();
+    let checks = vec![
+        check_state_layout(&state_dir),
+        check_global_state(&state_dir),
+        check_records::<RepositoryState>("repositories", &state_dir.join("repositories")),
+        check_records::<WorkpadState>("workpads", &state_dir.join("workpads")),
+        check_records::<TestRun>("test_runs", &state_dir.join("test_runs")),
+        check_records::<AIOperation>("ai_operations", &state_dir.join("ai_operations")),
+        check_records::<PromotionRecord>("promotions", &state_dir.join("promotions")),
+        check_tool(
+            "git",
+            Path::new("git"),
+            MIN_GIT_VERSION,
+            "Install or upgrade git from https://git-scm.com",
+        ),
+        check_tool(
+            "evogitctl",
+            paths::current().cli(),
+            MIN_CLI_VERSION,
+            "Install the CLI with `pip install -e .` from the Solo-Git checkout",
+        ),
+        check_ai_provider(),
+    ];
+
+    let warnings = checks.iter().filter(|c| c.severity == "warning").count();
+    let errors = checks.iter().filter(|c| c.severity == "error").count();
+    Ok(DoctorReport {
+        healthy: errors == 0,
+        warnings,
+        errors,
+        checks,
+        generated_at: Utc::now().to_rfc3339(),
+    })
+}
@@ -0,0 +1,166 @@
+use std::fs;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::ai::{ai_settings, run_operation, ChatMessage};
+use crate::commands::{load_global_state, load_repository, load_workpad};
+use crate::files::repo_file_path;
+use crate::languages::guess_language;
+use crate::tokens::count_tokens;
+
+/// Larger selections are cut; explaining thousands of lines isn't useful.
+const MAX_SELECTION_LINES: usize = 400;
+
+const EXPLAIN_SYSTEM_PROMPT: &str = "You explain code to a developer reading it in their \
+editor. Explain what the selected lines do, how they fit into the surrounding code and \
+anything non-obvious (edge cases, side effects, performance). Answer the developer's \
+question if they asked one. Be concise; use short paragraphs or bullets and refer to \
+identifiers in backticks.";
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct SelectionExplanation {
+    operation_id: String,
+    /// Workpad the operation was recorded against, if one was active.
+    workpad_id: Option<String>,
+    explanation: String,
+    /// Lines of the file sent as context around the selection.
+    context_start_line: usize,
+    context_end_line: usize,
+    cost_usd: f64,
+}
+
+/// The widest line range around the selection whose text fits `budget`
+/// tokens, grown evenly above and below it.
+fn context_window(
+    lines: &[&str],
+    start: usize,
+    end: usize,
+    budget: usize,
+    model: &str,
+) -> (usize, usize) {
+    let mut used: usize = lines[start..end]
+        .iter()
+        .map(|line| count_tokens(line, model) + 1)
+        .sum();
+    let (mut from, mut to) = (start, end);
+    loop {
+        let mut grew = false;
+        if from > 0 {
+            let cost = count_tokens(lines[from - 1], model) + 1;
+            if used + cost <= budget {
+                from -= 1;
+                used += cost;
+                grew = true;
+            }
+        }
+        if to < lines.len() {
+            let cost = count_tokens(lines[to], model) + 1;
+            if used + cost <= budget {
+                to += 1;
+                used += cost;
+                grew = true;
+            }
+        }
+        if !grew {
+            return (from, to);
+        }
+    }
+}
+
+fn numbered(lines: &[&str], from: usize, to: usize) -> String {
+    lines[from..to]
+        .iter()
+        .enumerate()
+        .map(|(offset, line)| format!("{:>5} | {}\n", from + offset + 1, line))
+        .collect()
+}
+
+/// Explain lines `start_line..=end_line` (1-based) of a repository file,
+/// with as much of the surrounding file as `ai.context_token_budget`
+/// allows. The operation is recorded against the active workpad when it
+/// belongs to this repository.
+#[tauri::command]
+pub(crate) fn explain_selection(
+    repo_id: String,
+    file_path: String,
+    start_line: usize,
+    end_line: usize,
+    question: Option<String>,
+) -> Result<SelectionExplanation, String> {
+    if start_line == 0 || end_line < start_line {
+        return Err(format!(
+            "Invalid selection: lines {}-{}",
+            start_line, end_line
+        ));
+    }
+    load_repository(&repo_id)?;
+    let path = repo_file_path(&repo_id, &file_path)?;
+    let source =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", file_path, e))?;
+    let lines: Vec<&str> = source.lines().collect();
+    if start_line > lines.len() {
+        return Err(format!("{} has only {} lines", file_path, lines.len()));
+    }
+    let start = start_line - 1;
+    let end = end_line.min(lines.len()).min(start + MAX_SELECTION_LINES);
+
+    let ai = ai_settings();
+    let (from, to) = context_window(&lines, start, end, ai.context_token_budget, &ai.model);
+    let language = guess_language(Path::new(&file_path)).unwrap_or_default();
+    let mut request = format!("File: {} ({})\n\n", file_path, language);
+    if from < start {
+        request.push_str(&format!(
+            "Code before the selection:\n```\n{}```\n\n",
+            numbered(&lines, from, start)
+        ));
+    }
+    request.push_str(&format!(
+        "Selected lines {}-{}:\n```\n{}```\n\n",
+        start + 1,
+        end,
+        numbered(&lines, start, end)
+    ));
+    if end < to {
+        request.push_str(&format!(
+            "Code after the selection:\n```\n{}```\n\n",
+            numbered(&lines, end, to)
+        ));
+    }
+    let question = question
+        .map(|q| q.trim().to_string())
+        .filter(|q| !q.is_empty());
+    match &question {
+        Some(question) => request.push_str(&format!("Question: {}\n", question)),
+        None => request.push_str("Explain the selected lines.\n"),
+    }
+
+    let workpad_id = load_global_state()?
+        .active_workpad
+        .filter(|id| load_workpad(id).is_ok_and(|workpad| workpad.repo_id == repo_id));
+    let messages = [
+        ChatMessage::system(EXPLAIN_SYSTEM_PROMPT),
+        ChatMessage::user(request),
+    ];
+    let prompt = match &question {
+        Some(question) => format!("{}:{}-{}: {}", file_path, start + 1, end, question),
+        None => format!("Explain {}:{}-{}", file_path, start + 1, end),
+    };
+    let (operation, completion) =
+        run_operation(workpad_id.clone(), "explain", &prompt, &messages, None)?;
+    let completion = completion.ok_or_else(|| {
+        operation
+            .error
+            .clone()
+            .unwrap_or_else(|| "AI request failed".to_string())
+    })?;
+
+    Ok(SelectionExplanation {
+        operation_id: operation.operation_id,
+        workpad_id,
+        explanation: completion.content.trim().to_string(),
+        context_start_line: from + 1,
+        context_end_line: to,
+        cost_usd: operation.cost_usd,
+    })
+}
@@ -0,0 +1,240 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::ai::{parse_json_response, run_operation, ChatMessage};
+use crate::commands::{load_workpad, read_json, workpad_checkout_dir, write_json};
+use crate::files::contained_path;
+use crate::git::workpad_diff;
+use crate::statuses::TestRunStatus;
+use crate::text::clip;
+use crate::{get_state_dir, read_test_run, TestCaseResult};
+
+const MAX_FAILURES: usize = 10;
+const MAX_SNIPPETS: usize = 8;
+const SNIPPET_CONTEXT: usize = 8;
+const MAX_OUTPUT_CHARS: usize = 4_000;
+const MAX_DIFF_CHARS: usize = 30_000;
+
+const ANALYSIS_SYSTEM_PROMPT: &str = "You diagnose failing tests. Given the failures, the \
+source around the reported locations and the change under test, explain the most likely root \
+cause and propose a fix. Reply with JSON only: {\"root_cause\": \"...\", \"suggested_fix\": \
+\"...\", \"confidence\": \"low|medium|high\", \"related_files\": [\"...\"]}";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct SourceSnippet {
+    file_path: String,
+    start_line: usize,
+    focus_line: usize,
+    content: String,
+}
+
+/// AI diagnosis of a failed TestRun, stored at `state/test_analyses/<run_id>.json`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct TestFailureAnalysis {
+    run_id: String,
+    operation_id: String,
+    failing_tests: Vec<String>,
+    root_cause: String,
+    suggested_fix: String,
+    confidence: String,
+    related_files: Vec<String>,
+    snippets: Vec<SourceSnippet>,
+    created_at: String,
+}
+
+fn analysis_path(run_id: &str) -> PathBuf {
+    get_state_dir()
+        .join("test_analyses")
+        .join(format!("{}.json", run_id))
+}
+
+/// Find `path:line` and Python `File "path", line N` references in failure output.
+fn source_locations(text: &str) -> Vec<(String, usize)> {
+    let mut locations = Vec::new();
+
+    for line in text.lines() {
+        if let Some(rest) = line.trim_start().strip_prefix("File \"") {
+            if let Some((path, tail)) = rest.split_once("\", line ") {
+                let number: String = tail.chars().take_while(|c| c.is_ascii_digit()).collect();
+                if let Ok(number) = number.parse() {
+                    locations.push((path.to_string(), number));
+                }
+            }
+        }
+
+        for token in line.split(|c: char| c.is_whitespace() || "()[]'\",".contains(c)) {
+            let mut parts = token.split(':');
+            let (Some(path), Some(number)) = (parts.next(), parts.next()) else {
+                continue;
+            };
+            let has_extension = Path::new(path).extension().is_some();
+            if let (true, Ok(number)) = (has_extension, number.parse::<usize>()) {
+                locations.push((path.to_string(), number));
+            }
+        }
+    }
+
+    locations.dedup();
+    locations
+}
+
+fn read_snippet(checkout: &Path, file_path: &str, line: usize) -> Option<SourceSnippet> {
+    // Absolute paths from tracebacks only count if they point into the checkout.
+    let relative = if Path::new(file_path).is_absolute() {
+        Path::new(file_path)
+            .strip_prefix(checkout)
+            .ok()?
+            .to_string_lossy()
+            .to_string()
+    } else {
+        file_path.trim_start_matches("./").to_string()
+    };
+    // Traceback paths come from test output; don't follow them out of the checkout.
+    let path = contained_path(checkout, &relative).ok()?;
+    let contents = fs::read_to_string(&path).ok()?;
+    let lines: Vec<&str> = contents.lines().collect();
+    if line == 0 || line > lines.len() {
+        return None;
+    }
+
+    let start = line.saturating_sub(SNIPPET_CONTEXT).max(1);
+    let end = (line + SNIPPET_CONTEXT).min(lines.len());
+    Some(SourceSnippet {
+        file_path: relative,
+        start_line: start,
+        focus_line: line,
+        content: lines[start - 1..end].join("\n"),
+    })
+}
+
+fn describe_failure(test: &TestCaseResult) -> String {
+    let mut text = format!("### {}\n", test.test_id);
+    if let Some(error) = &test.error {
+        text.push_str(clip(error, MAX_OUTPUT_CHARS));
+        text.push('\n');
+    }
+    if !test.output.is_empty() {
+        text.push_str(clip(&test.output, MAX_OUTPUT_CHARS));
+        text.push('\n');
+    }
+    text
+}
+
+#[tauri::command]
+pub(crate) fn analyze_test_failure(run_id: String) -> Result<TestFailureAnalysis, String> {
+    let run = read_test_run(run_id.clone())?;
+    let failing: Vec<&TestCaseResult> = run
+        .tests
+        .iter()
+        .filter(|test| test.status == TestRunStatus::Failed)
+        .take(MAX_FAILURES)
+        .collect();
+    if failing.is_empty() && run.status != TestRunStatus::Failed {
+        return Err(format!("Test run {} has no failures to analyze", run_id));
+    }
+
+    let workpad = run.workpad_id.as_deref().map(load_workpad).transpose()?;
+    let (checkout, diff) = match &workpad {
+        Some(workpad) => (
+            Some(workpad_checkout_dir(workpad)?),
+            workpad_diff(workpad).unwrap_or_default(),
+        ),
+        None => (None, String::new()),
+    };
+
+    let failures: String = failing.iter().map(|test| describe_failure(test)).collect();
+    let snippets: Vec<SourceSnippet> = match &checkout {
+        Some(checkout) => source_locations(&failures)
+            .into_iter()
+            .filter_map(|(path, line)| read_snippet(checkout, &path, line))
+            .take(MAX_SNIPPETS)
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let mut request = format!(
+        "Test target: {}\nRun status: {} ({} failed of {})\n\n## Failures\n{}",
+        run.target,
+        run.status,
+        run.failed,
+        run.total_tests,
+        if failures.is_empty() {
+            "(no per-test details were captured)\n".to_string()
+        } else {
+            failures
+        }
+    );
+    for snippet in &snippets {
+        request.push_str(&format!(
+            "\n## {} (from line {}, failure at {})\n```\n{}\n```\n",
+            snippet.file_path, snippet.start_line, snippet.focus_line, snippet.content
+        ));
+    }
+    if !diff.trim().is_empty() {
+        request.push_str(&format!(
+            "\n## Change under test\n```diff\n{}```\n",
+            clip(&diff, MAX_DIFF_CHARS)
+        ));
+    }
+
+    let messages = [
+        ChatMessage::system(ANALYSIS_SYSTEM_PROMPT),
+        ChatMessage::user(request),
+    ];
+    let (operation, completion) = run_operation(
+        run.workpad_id.clone(),
+        "test_analysis",
+        &format!("Analyze failures in test run {}", run_id),
+        &messages,
+        None,
+    )?;
+    let completion = completion.ok_or_else(|| {
+        operation
+            .error
+            .clone()
+            .unwrap_or_else(|| "AI request failed".to_string())
+    })?;
+
+    // Fall back to the raw answer if the model ignored the JSON format.
+    let response = parse_json_response(&completion.content).unwrap_or_default();
+    let text = |key: &str| response[key].as_str().unwrap_or("").trim().to_string();
+    let root_cause = match text("root_cause") {
+        cause if cause.is_empty() => completion.content.trim().to_string(),
+        cause => cause,
+    };
+
+    let analysis = TestFailureAnalysis {
+        run_id: run_id.clone(),
+        operation_id: operation.operation_id,
+        failing_tests: failing.iter().map(|test| test.test_id.clone()).collect(),
+        root_cause,
+        suggested_fix: text("suggested_fix"),
+        confidence: match text("confidence") {
+            confidence if confidence.is_empty() => "low".to_string(),
+            confidence => confidence,
+        },
+        related_files: response["related_files"]
+            .as_array()
+            .map(|files| {
+                files
+                    .iter()
+                    .filter_map(|file| file.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        snippets,
+        created_at: Utc::now().to_rfc3339(),
+    };
+    write_json(&analysis_path(&run_id), &analysis)?;
+    Ok(analysis)
+}
+
+#[tauri::command]
+pub(crate) fn get_test_failure_analysis(
+    run_id: String,
+) -> Result<Option<TestFailureAnalysis>, String> {
+    read_json(&analysis_path(&run_id))
+}
@@ -0,0 +1,568 @@
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use git2::{Status, StatusOptions};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::audit::audited;
+use crate::git::open_repository;
+use crate::languages::guess_language;
+use crate::logging::warn_on_err;
+use crate::walk::{walk_builder, DirArena};
+use crate::{get_repos_dir, get_settings};
+
+/// Whether listings should hide paths matched by `.gitignore`.
+pub(crate) fn respect_gitignore() -> bool {
+    get_settings()
+        .map(|settings| settings.editor.respect_gitignore)
+        .unwrap_or(true)
+}
+
+/// Immediate children of `dir` as `(path, is_dir)`, filtered like the tree.
+pub(crate) fn read_dir_filtered(
+    dir: &Path,
+    gitignore: bool,
+) -> Result<Vec<(PathBuf, bool)>, String> {
+    let mut entries = Vec::new();
+    for entry in walk_builder(dir, gitignore, false)
+        .max_depth(Some(1))
+        .build()
+    {
+        let entry = entry.map_err(|e| e.to_string())?;
+        if entry.depth() == 0 {
+            continue;
+        }
+        let is_dir = entry.file_type().is_some_and(|kind| kind.is_dir());
+        entries.push((entry.into_path(), is_dir));
+    }
+    Ok(entries)
+}
+
+impl DirArena {
+    /// `FileMeta` for every entry down to `max_depth`, computed across
+    /// threads since each one costs a few syscalls.
+    pub(crate) fn metas(
+        &self,
+        root: &Path,
+        statuses: &HashMap<String, String>,
+        max_depth: usize,
+    ) -> Vec<Option<FileMeta>> {
+        let workers = std::thread::available_parallelism().map_or(4, |n| n.get());
+        let chunk = self.entries.len().div_ceil(workers).max(1);
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = self
+                .entries
+                .chunks(chunk)
+                .map(|entries| {
+                    let handle = scope.spawn(move || {
+                        entries
+                            .iter()
+                            .map(|entry| {
+                                if entry.depth == 0 || entry.depth > max_depth {
+                                    return None;
+                                }
+                                let rel_path =
+                                    entry.path.strip_prefix(root).ok()?.to_string_lossy();
+                                Some(FileMeta::for_path(
+                                    root,
+                                    &entry.path,
+                                    &rel_path,
+                                    entry.is_dir,
+                                    statuses,
+                                ))
+                            })
+                            .collect::<Vec<_>>()
+                    });
+                    (entries.len(), handle)
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|(len, handle)| {
+                    // Keep indices aligned even if a worker panicked.
+                    handle.join().unwrap_or_else(|_| vec![None; len])
+                })
+                .collect()
+        })
+    }
+}
+
+/// Compile user-supplied glob patterns; `None` when there are none.
+pub(crate) fn build_globset(patterns: Option<Vec<String>>) -> Result<Option<GlobSet>, String> {
+    let patterns = match patterns {
+        Some(patterns) if !patterns.is_empty() => patterns,
+        _ => return Ok(None),
+    };
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(&pattern).map_err(|e| format!("Invalid glob {}: {}", pattern, e))?);
+    }
+    builder
+        .build()
+        .map(Some)
+        .map_err(|e| format!("Invalid glob set: {}", e))
+}
+
+/// Filtered directory listings keyed by canonical directory path, held in
+/// Tauri managed state. The repository status watcher invalidates entries
+/// as files change.
+#[derive(Default)]
+pub(crate) struct DirectoryCache {
+    entries: Mutex<HashMap<(PathBuf, bool), Vec<(PathBuf, bool)>>>,
+}
+
+impl DirectoryCache {
+    pub(crate) fn list(&self, dir: &Path, gitignore: bool) -> Result<Vec<(PathBuf, bool)>, String> {
+        let key = (
+            dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf()),
+            gitignore,
+        );
+        if let Some(cached) = self.entries.lock().ok().and_then(|e| e.get(&key).cloned()) {
+            return Ok(cached);
+        }
+        let listing = read_dir_filtered(dir, gitignore)?;
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert(key, listing.clone());
+        }
+        Ok(listing)
+    }
+
+    /// Store the listings a tree walk already produced: every directory no
+    /// deeper than `max_depth`, whose children the walk saw in full.
+    pub(crate) fn seed(&self, arena: &DirArena, gitignore: bool, max_depth: usize) {
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+        for entry in &arena.entries {
+            if !entry.is_dir || entry.depth > max_depth {
+                continue;
+            }
+            let listing = entry
+                .children
+                .iter()
+                .map(|&child| {
+                    let child = &arena.entries[child];
+                    (child.path.clone(), child.is_dir)
+                })
+                .collect();
+            let dir = entry
+                .path
+                .canonicalize()
+                .unwrap_or_else(|_| entry.path.clone());
+            entries.insert((dir, gitignore), listing);
+        }
+    }
+
+    /// Drop listings affected by `changed`: each path's parent directory,
+    /// and everything below a directory whose `.gitignore` changed.
+    pub(crate) fn invalidate(&self, changed: &[PathBuf]) {
+        let mut entries = match self.entries.lock() {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+        for path in changed {
+            let parent = path.parent().unwrap_or(path);
+            if path.file_name().is_some_and(|name| name == ".gitignore") {
+                entries.retain(|(dir, _), _| !dir.starts_with(parent));
+            } else {
+                entries.retain(|(dir, _), _| dir != parent && dir != path);
+            }
+        }
+    }
+}
+
+/// Per-node details for badges in the file tree.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub(crate) struct FileMeta {
+    #[serde(default)]
+    pub(crate) size: Option<u64>,
+    #[serde(default)]
+    pub(crate) modified_at: Option<String>,
+    #[serde(default)]
+    pub(crate) language: Option<String>,
+    /// "modified", "staged", "untracked", "conflicted" or "ignored"
+    #[serde(default)]
+    pub(crate) git_status: Option<String>,
+    /// "file", "directory" or "symlink"
+    #[serde(default)]
+    pub(crate) kind: String,
+    /// Where a symlink points, as written in the link.
+    #[serde(default)]
+    pub(crate) link_target: Option<String>,
+    /// False for dangling links and links that resolve outside the repository.
+    #[serde(default)]
+    pub(crate) link_in_repo: Option<bool>,
+    /// Unix permission bits; `None` on platforms without them.
+    #[serde(default)]
+    pub(crate) mode: Option<u32>,
+    #[serde(default)]
+    pub(crate) executable: bool,
+    #[serde(default)]
+    pub(crate) readonly: bool,
+}
+
+fn status_label(flags: Status) -> Option<&'static str> {
+    if flags.is_conflicted() {
+        Some("conflicted")
+    } else if flags.is_ignored() {
+        Some("ignored")
+    } else if flags.is_wt_new() {
+        Some("untracked")
+    } else if flags.intersects(
+        Status::WT_MODIFIED | Status::WT_DELETED | Status::WT_RENAMED | Status::WT_TYPECHANGE,
+    ) {
+        Some("modified")
+    } else if flags.intersects(
+        Status::INDEX_NEW
+            | Status::INDEX_MODIFIED
+            | Status::INDEX_DELETED
+            | Status::INDEX_RENAMED
+            | Status::INDEX_TYPECHANGE,
+    ) {
+        Some("staged")
+    } else {
+        None
+    }
+}
+
+/// Git status of every changed or ignored path, keyed by repo-relative
+/// path. Ignored directories appear once, with a trailing `/`.
+pub(crate) fn git_status_map(repo_id: &str) -> HashMap<String, String> {
+    let repo = match open_repository(repo_id) {
+        Ok(repo) => repo,
+        Err(_) => return HashMap::new(),
+    };
+    let mut options = StatusOptions::new();
+    options
+        .include_untracked(true)
+        .include_ignored(true)
+        .recurse_ignored_dirs(false)
+        .exclude_submodules(true);
+    let statuses = match repo.statuses(Some(&mut options)) {
+        Ok(statuses) => statuses,
+        Err(_) => return HashMap::new(),
+    };
+    statuses
+        .iter()
+        .filter_map(|entry| {
+            let label = status_label(entry.status())?;
+            Some((entry.path()?.to_string(), label.to_string()))
+        })
+        .collect()
+}
+
+fn status_for(statuses: &HashMap<String, String>, rel_path: &str, is_dir: bool) -> Option<String> {
+    if !is_dir {
+        return statuses.get(rel_path).cloned();
+    }
+    let prefix = format!("{}/", rel_path.trim_end_matches('/'));
+    if let Some(status) = statuses.get(&prefix) {
+        return Some(status.clone());
+    }
+    // A directory is "modified" when anything non-ignored inside it changed.
+    statuses
+        .iter()
+        .any(|(path, status)| path.starts_with(&prefix) && status != "ignored")
+        .then(|| "modified".to_string())
+}
+
+#[cfg(unix)]
+fn unix_mode(metadata: &std::fs::Metadata) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(metadata.permissions().mode() & 0o7777)
+}
+
+#[cfg(not(unix))]
+fn unix_mode(_metadata: &std::fs::Metadata) -> Option<u32> {
+    None
+}
+
+/// Resolve a symlink and report whether it stays inside `root`.
+pub(crate) fn resolve_link(root: &Path, link: &Path) -> Option<PathBuf> {
+    let resolved = link.canonicalize().ok()?;
+    let root = root.canonicalize().ok()?;
+    resolved.starts_with(&root).then_some(resolved)
+}
+
+impl FileMeta {
+    pub(crate) fn for_path(
+        root: &Path,
+        path: &Path,
+        rel_path: &str,
+        is_dir: bool,
+        statuses: &HashMap<String, String>,
+    ) -> FileMeta {
+        let metadata = std::fs::symlink_metadata(path).ok();
+        let is_link = metadata
+            .as_ref()
+            .is_some_and(|metadata| metadata.file_type().is_symlink());
+        let mode = metadata.as_ref().and_then(unix_mode);
+        FileMeta {
+            size: metadata
+                .as_ref()
+                .filter(|_| !is_dir)
+                .map(|metadata| metadata.len()),
+            modified_at: metadata
+                .as_ref()
+                .and_then(|metadata| metadata.modified().ok())
+                .map(|time| DateTime::<Utc>::from(time).to_rfc3339()),
+            language: if is_dir || is_link {
+                None
+            } else {
+                guess_language(path).map(str::to_string)
+            },
+            git_status: status_for(statuses, rel_path, is_dir),
+            kind: if is_link {
+                "symlink"
+            } else if is_dir {
+                "directory"
+            } else {
+                "file"
+            }
+            .to_string(),
+            link_target: if is_link {
+                std::fs::read_link(path)
+                    .ok()
+                    .map(|target| target.to_string_lossy().to_string())
+            } else {
+                None
+            },
+            link_in_repo: is_link.then(|| resolve_link(root, path).is_some()),
+            executable: mode.is_some_and(|mode| mode & 0o111 != 0),
+            readonly: metadata
+                .as_ref()
+                .is_some_and(|metadata| metadata.permissions().readonly()),
+            mode,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct PathStat {
+    path: String,
+    is_directory: bool,
+    #[serde(flatten)]
+    meta: FileMeta,
+    created_at: Option<String>,
+    /// Only counted for text files under 1 MiB.
+    line_count: Option<usize>,
+    binary: bool,
+}
+
+const LINE_COUNT_LIMIT: u64 = 1024 * 1024;
+
+/// Resolve a repo-relative path, refusing anything that climbs out of it.
+fn relative_path(rel_path: &str) -> Result<&Path, String> {
+    let relative = Path::new(rel_path.trim_start_matches('/'));
+    if relative
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(format!("Path escapes the repository: {}", rel_path));
+    }
+    Ok(relative)
+}
+
+pub(crate) fn repo_file_path(repo_id: &str, rel_path: &str) -> Result<PathBuf, String> {
+    Ok(get_repos_dir().join(repo_id).join(relative_path(rel_path)?))
+}
+
+/// An existing path under `root`, resolved through any symlinks and
+/// refused if it ends up outside `root`.
+pub(crate) fn contained_path(root: &Path, rel_path: &str) -> Result<PathBuf, String> {
+    let resolved = root
+        .join(relative_path(rel_path)?)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve {}: {}", rel_path, e))?;
+    let root = root
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve {}: {}", root.display(), e))?;
+    if !resolved.starts_with(&root) {
+        return Err(format!("Path escapes the repository: {}", rel_path));
+    }
+    Ok(resolved)
+}
+
+#[tauri::command]
+pub(crate) fn stat_path(repo_id: String, path: String) -> Result<PathStat, String> {
+    let full_path = repo_file_path(&repo_id, &path)?;
+    let metadata = std::fs::symlink_metadata(&full_path)
+        .map_err(|e| format!("Failed to stat {}: {}", path, e))?;
+    let is_dir = metadata.is_dir();
+    let rel_path = path.trim_matches('/').to_string();
+
+    let (line_count, binary) = if metadata.is_file() && metadata.len() <= LINE_COUNT_LIMIT {
+        match std::fs::read(&full_path) {
+            Ok(bytes) if bytes.contains(&0) => (None, true),
+            Ok(bytes) => {
+                let newlines = bytes.iter().filter(|b| **b == b'\n').count();
+                let unterminated = bytes.last().is_some_and(|b| *b != b'\n');
+                (Some(newlines + usize::from(unterminated)), false)
+            }
+            Err(_) => (None, false),
+        }
+    } else {
+        (None, false)
+    };
+
+    Ok(PathStat {
+        meta: FileMeta::for_path(
+            &get_repos_dir().join(&repo_id),
+            &full_path,
+            &rel_path,
+            is_dir,
+            &git_status_map(&repo_id),
+        ),
+        created_at: metadata
+            .created()
+            .ok()
+            .map(|time| DateTime::<Utc>::from(time).to_rfc3339()),
+        path: rel_path,
+        is_directory: is_dir,
+        line_count,
+        binary,
+    })
+}
+
+/// Write `content` to a repository file. Writes go through a temp file and
+/// rename, carrying the original permission bits across; symlinks are
+/// written through only when they resolve inside the repository.
+#[tauri::command]
+pub(crate) fn write_file(
+    repo_id: String,
+    file_path: String,
+    content: String,
+) -> Result<PathStat, String> {
+    let entity_id = format!("{}:{}", repo_id, file_path);
+    audited("write_file", "file", Some(entity_id), None, move || {
+        write_repo_file(repo_id, file_path, content)
+    })
+}
+
+fn write_repo_file(
+    repo_id: String,
+    file_path: String,
+    content: String,
+) -> Result<PathStat, String> {
+    let root = get_repos_dir().join(&repo_id);
+    let mut target = repo_file_path(&repo_id, &file_path)?;
+    let is_link = std::fs::symlink_metadata(&target)
+        .map(|metadata| metadata.file_type().is_symlink())
+        .unwrap_or(false);
+    if is_link {
+        target = resolve_link(&root, &target).ok_or_else(|| {
+            format!(
+                "Refusing to write through symlink {}: it points outside the repository",
+                file_path
+            )
+        })?;
+    }
+
+    let parent = target
+        .parent()
+        .ok_or_else(|| format!("Invalid file path: {}", file_path))?;
+    let file_name = target
+        .file_name()
+        .ok_or_else(|| format!("Invalid file path: {}", file_path))?
+        .to_os_string();
+    // A directory on the way may be a symlink out of the repository: check
+    // the deepest one that exists before creating anything under it, and the
+    // real parent once it does.
+    let root = root
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve {}: {}", root.display(), e))?;
+    let inside = |dir: &Path| {
+        dir.canonicalize()
+            .map_err(|e| format!("Failed to resolve {}: {}", dir.display(), e))
+            .and_then(|dir| {
+                if dir.starts_with(&root) {
+                    Ok(dir)
+                } else {
+                    Err(format!("Path escapes the repository: {}", file_path))
+                }
+            })
+    };
+    if let Some(existing) = parent.ancestors().find(|dir| dir.exists()) {
+        inside(existing)?;
+    }
+    std::fs::create_dir_all(parent)
+        .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    let parent = inside(parent)?;
+    let target = parent.join(&file_name);
+    let permissions = std::fs::metadata(&target).ok().map(|m| m.permissions());
+
+    let tmp_path = parent.join(format!(
+        ".{}.sologit-tmp",
+        target.file_name().unwrap_or_default().to_string_lossy()
+    ));
+    std::fs::write(&tmp_path, content)
+        .map_err(|e| format!("Failed to write {}: {}", file_path, e))?;
+    if let Some(permissions) = permissions {
+        std::fs::set_permissions(&tmp_path, permissions)
+            .map_err(|e| format!("Failed to preserve permissions on {}: {}", file_path, e))?;
+    }
+    std::fs::rename(&tmp_path, &target).map_err(|e| {
+        warn_on_err(
+            "Failed to remove temporary file",
+            std::fs::remove_file(&tmp_path),
+        );
+        format!("Failed to write {}: {}", file_path, e)
+    })?;
+
+    stat_path(repo_id, file_path)
+}
+
+/// Set or clear the executable bits (for everyone who can read the file).
+#[tauri::command]
+pub(crate) fn set_file_executable(
+    repo_id: String,
+    file_path: String,
+    executable: bool,
+) -> Result<PathStat, String> {
+    let path = repo_file_path(&repo_id, &file_path)?;
+    let entity_id = format!("{}:{}", repo_id, file_path);
+    audited(
+        "set_file_executable",
+        "file",
+        Some(entity_id),
+        None,
+        move || {
+            let metadata = std::fs::symlink_metadata(&path)
+                .map_err(|e| format!("Failed to stat {}: {}", file_path, e))?;
+            if !metadata.is_file() {
+                return Err(format!("{} is not a regular file", file_path));
+            }
+            set_executable_bits(&path, &metadata, executable)
+                .map_err(|e| format!("Failed to change permissions on {}: {}", file_path, e))?;
+            stat_path(repo_id, file_path)
+        },
+    )
+}
+
+#[cfg(unix)]
+fn set_executable_bits(
+    path: &Path,
+    metadata: &std::fs::Metadata,
+    executable: bool,
+) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = metadata.permissions().mode();
+    let mode = if executable {
+        mode | ((mode & 0o444) >> 2)
+    } else {
+        mode & !0o111
+    };
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode)).map_err(|e| e.to_string())
+}
+
+#[cfg(not(unix))]
+fn set_executable_bits(
+    _path: &Path,
+    _metadata: &std::fs::Metadata,
+    _executable: bool,
+) -> Result<(), String> {
+    Err("Executable bits are not supported on this platform".to_string())
+}
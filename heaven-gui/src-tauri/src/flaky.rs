@@ -0,0 +1,137 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::statuses::TestRunStatus;
+use crate::{list_test_runs, list_workpads, TestRun};
+
+/// A test that produced different outcomes for the same commit.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct FlakyTest {
+    test_id: String,
+    name: String,
+    passes: i32,
+    failures: i32,
+    /// Number of pass<->fail transitions across the chronological history.
+    flips: i32,
+    /// Commits on which the test both passed and failed.
+    commits: Vec<String>,
+    last_seen: String,
+    /// Share of observed runs that disagree with the majority outcome (0..0.5).
+    flakiness: f64,
+}
+
+#[derive(Default)]
+struct Observations {
+    name: String,
+    passes: i32,
+    failures: i32,
+    flips: i32,
+    last_status: Option<TestRunStatus>,
+    last_seen: String,
+    /// commit -> outcomes seen on that commit
+    by_commit: BTreeMap<String, HashSet<TestRunStatus>>,
+}
+
+fn repo_test_runs(repo_id: &str) -> Result<Vec<TestRun>, String> {
+    let workpad_ids: HashSet<String> = list_workpads(Some(repo_id.to_string()), None, None)?
+        .into_iter()
+        .map(|workpad| workpad.workpad_id)
+        .collect();
+
+    Ok(list_test_runs(None, None)?
+        .into_iter()
+        .filter(|run| {
+            run.workpad_id
+                .as_ref()
+                .is_some_and(|id| workpad_ids.contains(id))
+        })
+        .collect())
+}
+
+/// Correlate per-test outcomes across a repository's runs and return the
+/// tests that alternate pass/fail on identical commits.
+pub(crate) fn detect_flaky_tests(runs: &[TestRun]) -> Vec<FlakyTest> {
+    let mut ordered: Vec<&TestRun> = runs.iter().collect();
+    ordered.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+
+    let mut observations: HashMap<String, Observations> = HashMap::new();
+    for run in ordered {
+        for case in &run.tests {
+            if !matches!(case.status, TestRunStatus::Passed | TestRunStatus::Failed) {
+                continue;
+            }
+
+            let entry = observations.entry(case.test_id.clone()).or_default();
+            entry.name = case.name.clone();
+            if case.status == TestRunStatus::Passed {
+                entry.passes += 1;
+            } else {
+                entry.failures += 1;
+            }
+            if entry.last_status.is_some_and(|s| s != case.status) {
+                entry.flips += 1;
+            }
+            entry.last_status = Some(case.status);
+            entry.last_seen = run.started_at.clone();
+
+            if let Some(sha) = &run.commit_sha {
+                entry
+                    .by_commit
+                    .entry(sha.clone())
+                    .or_default()
+                    .insert(case.status);
+            }
+        }
+    }
+
+    let mut flaky: Vec<FlakyTest> = observations
+        .into_iter()
+        .filter_map(|(test_id, obs)| {
+            let commits: Vec<String> = obs
+                .by_commit
+                .iter()
+                .filter(|(_, outcomes)| outcomes.len() > 1)
+                .map(|(sha, _)| sha.clone())
+                .collect();
+            if commits.is_empty() {
+                return None;
+            }
+
+            let total = (obs.passes + obs.failures) as f64;
+            Some(FlakyTest {
+                test_id,
+                name: obs.name,
+                passes: obs.passes,
+                failures: obs.failures,
+                flips: obs.flips,
+                commits,
+                last_seen: obs.last_seen,
+                flakiness: obs.passes.min(obs.failures) as f64 / total,
+            })
+        })
+        .collect();
+
+    flaky.sort_by(|a, b| {
+        b.flakiness
+            .partial_cmp(&a.flakiness)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.test_id.cmp(&b.test_id))
+    });
+    flaky
+}
+
+/// Ids of tests currently considered flaky in a repository, for quarantining.
+pub(crate) fn flaky_test_ids(repo_id: &str) -> Result<HashSet<String>, String> {
+    let runs = repo_test_runs(repo_id)?;
+    Ok(detect_flaky_tests(&runs)
+        .into_iter()
+        .map(|test| test.test_id)
+        .collect())
+}
+
+#[tauri::command]
+pub(crate) fn get_flaky_tests(repo_id: String) -> Result<Vec<FlakyTest>, String> {
+    let runs = repo_test_runs(&repo_id)?;
+    Ok(detect_flaky_tests(&runs))
+}
@@ -0,0 +1,114 @@
+use std::path::Path;
+use std::process::Command;
+
+use chrono::{TimeZone, Utc};
+
+use crate::commands::{resolve_repo_path, workpad_checkout_dir};
+use crate::WorkpadState;
+
+/// Run a git subcommand in `repo_dir`, returning stdout on success.
+pub(crate) fn run_git(repo_dir: &Path, args: &[&str]) -> Result<String, String> {
+    run_git_with_env(repo_dir, args, &[])
+}
+
+pub(crate) fn run_git_with_env(
+    repo_dir: &Path,
+    args: &[&str],
+    envs: &[(&str, &str)],
+) -> Result<String, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo_dir)
+        .args(args)
+        .envs(envs.iter().copied())
+        .output()
+        .map_err(|e| format!("Failed to execute git: {}", e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Host and project path of a remote, e.g. ("github.com", "owner/repo").
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RemoteLocation {
+    pub(crate) host: String,
+    pub(crate) slug: String,
+}
+
+/// Parse scp-style (`git@host:owner/repo.git`) and URL-style remotes.
+pub(crate) fn parse_remote_url(url: &str) -> Option<RemoteLocation> {
+    let url = url.trim();
+    let (host, path) = if let Some(rest) = url.split_once("://").map(|(_, rest)| rest) {
+        let rest = rest.rsplit_once('@').map(|(_, r)| r).unwrap_or(rest);
+        let (host, path) = rest.split_once('/')?;
+        (host.split(':').next().unwrap_or(host), path)
+    } else {
+        let rest = url.rsplit_once('@').map(|(_, r)| r).unwrap_or(url);
+        rest.split_once(':')?
+    };
+
+    let slug = path.trim_matches('/').trim_end_matches(".git");
+    if host.is_empty() || !slug.contains('/') {
+        return None;
+    }
+
+    Some(RemoteLocation {
+        host: host.to_lowercase(),
+        slug: slug.to_string(),
+    })
+}
+
+pub(crate) fn remote_location(repo_dir: &Path, remote: &str) -> Result<RemoteLocation, String> {
+    let url = run_git(repo_dir, &["remote", "get-url", remote])?;
+    parse_remote_url(&url).ok_or_else(|| format!("Unrecognised remote URL: {}", url.trim()))
+}
+
+/// Open a managed repository with libgit2.
+pub(crate) fn open_repository(repo_id: &str) -> Result<git2::Repository, String> {
+    let repo_dir = resolve_repo_path(repo_id)?;
+    git2::Repository::open(&repo_dir)
+        .map_err(|e| format!("Failed to open repository {}: {}", repo_id, e.message()))
+}
+
+/// Resolve a revision expression (sha, branch, `HEAD~2`, ...) to a commit.
+pub(crate) fn resolve_commit<'r>(
+    repo: &'r git2::Repository,
+    rev: &str,
+) -> Result<git2::Commit<'r>, String> {
+    repo.revparse_single(rev)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|e| format!("Unknown revision {}: {}", rev, e.message()))
+}
+
+pub(crate) fn format_git_time(time: git2::Time) -> String {
+    Utc.timestamp_opt(time.seconds(), 0)
+        .single()
+        .map(|dt| dt.to_rfc3339())
+        .unwrap_or_default()
+}
+
+pub(crate) fn short_sha(oid: git2::Oid) -> String {
+    oid.to_string().chars().take(7).collect()
+}
+
+/// Everything a workpad has changed relative to its base commit.
+pub(crate) fn workpad_diff(workpad: &WorkpadState) -> Result<String, String> {
+    let checkout = workpad_checkout_dir(workpad)?;
+    run_git(
+        &checkout,
+        &[
+            "diff",
+            &format!(
+                "{}..refs/heads/{}",
+                workpad.base_commit, workpad.branch_name
+            ),
+        ],
+    )
+}
@@ -0,0 +1,195 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::audit::audited;
+use crate::commands::{load_repository, load_workpad, resolve_repo_path, save_workpad};
+use crate::diff_summary::current_summary;
+use crate::git::{remote_location, run_git, RemoteLocation};
+use crate::http::{agent, json_response};
+use crate::{get_settings, list_workpads, WorkpadState};
+
+/// Pull request opened for a workpad branch on GitHub.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct PullRequestInfo {
+    number: u64,
+    url: String,
+    /// "open", "closed" or "merged"
+    state: String,
+    head: String,
+    base: String,
+    created_at: String,
+    updated_at: String,
+}
+
+fn github_target(repo_id: &str) -> Result<(RemoteLocation, String), String> {
+    let settings = get_settings()?.git.ci;
+    let token = settings
+        .github_token
+        .clone()
+        .filter(|t| !t.trim().is_empty())
+        .ok_or_else(|| "A GitHub token is required (Settings > CI)".to_string())?;
+
+    let repo_dir = resolve_repo_path(repo_id)?;
+    let remote = remote_location(&repo_dir, &settings.remote)?;
+    if !remote.host.contains("github") {
+        return Err(format!(
+            "Remote '{}' is not a GitHub repository ({})",
+            settings.remote, remote.host
+        ));
+    }
+    Ok((remote, token))
+}
+
+fn pull_state(data: &Value) -> String {
+    if data["merged"].as_bool().unwrap_or(false) || !data["merged_at"].is_null() {
+        "merged".to_string()
+    } else {
+        data["state"].as_str().unwrap_or("open").to_string()
+    }
+}
+
+#[tauri::command]
+pub(crate) fn create_pull_request(
+    workpad_id: String,
+    title: String,
+    body: Option<String>,
+) -> Result<WorkpadState, String> {
+    audited(
+        "create_pull_request",
+        "workpad",
+        Some(workpad_id.clone()),
+        None,
+        move || open_pull_request(workpad_id, title, body),
+    )
+}
+
+fn open_pull_request(
+    workpad_id: String,
+    title: String,
+    body: Option<String>,
+) -> Result<WorkpadState, String> {
+    let mut workpad = load_workpad(&workpad_id)?;
+    if let Some(existing) = &workpad.pull_request {
+        if existing.state == "open" {
+            return Err(format!(
+                "Workpad already has an open pull request: {}",
+                existing.url
+            ));
+        }
+    }
+
+    let repo = load_repository(&workpad.repo_id)?;
+    let (remote, token) = github_target(&workpad.repo_id)?;
+    let remote_name = get_settings()?.git.ci.remote;
+    let repo_dir = resolve_repo_path(&workpad.repo_id)?;
+
+    // Fall back to the workpad's diff summary when it is current.
+    let body = body
+        .filter(|body| !body.trim().is_empty())
+        .or_else(|| current_summary(&workpad))
+        .unwrap_or_default();
+
+    let trimmed_title = title.trim();
+    let final_title = if trimmed_title.is_empty() {
+        workpad.title.as_str()
+    } else {
+        trimmed_title
+    };
+
+    run_git(
+        &repo_dir,
+        &["push", "--set-upstream", &remote_name, &workpad.branch_name],
+    )?;
+
+    let data = json_response(
+        agent()
+            .post(&format!(
+                "https://api.github.com/repos/{}/pulls",
+                remote.slug
+            ))
+            .set("Accept", "application/vnd.github+json")
+            .set("Authorization", &format!("Bearer {}", token))
+            .send_json(json!({
+                "title": final_title,
+                "body": body,
+                "head": workpad.branch_name,
+                "base": repo.trunk_branch,
+            })),
+    )?;
+
+    let now = Utc::now().to_rfc3339();
+    workpad.pull_request = Some(PullRequestInfo {
+        number: data["number"]
+            .as_u64()
+            .ok_or_else(|| "GitHub did not return a pull request number".to_string())?,
+        url: data["html_url"].as_str().unwrap_or_default().to_string(),
+        state: pull_state(&data),
+        head: workpad.branch_name.clone(),
+        base: repo.trunk_branch.clone(),
+        created_at: now.clone(),
+        updated_at: now,
+    });
+
+    save_workpad(workpad)
+}
+
+/// Re-read a workpad's pull request from GitHub and record its merge state.
+/// The workpad is only saved when the state changed.
+pub(crate) fn sync_pull_request(workpad: WorkpadState) -> Result<WorkpadState, String> {
+    let number = match &workpad.pull_request {
+        Some(pr) => pr.number,
+        None => return Ok(workpad),
+    };
+
+    let (remote, token) = github_target(&workpad.repo_id)?;
+    let data = json_response(
+        agent()
+            .get(&format!(
+                "https://api.github.com/repos/{}/pulls/{}",
+                remote.slug, number
+            ))
+            .set("Accept", "application/vnd.github+json")
+            .set("Authorization", &format!("Bearer {}", token))
+            .call(),
+    )?;
+
+    let state = pull_state(&data);
+    let mut workpad = workpad;
+    match workpad.pull_request.as_mut() {
+        Some(pr) if pr.state != state => {
+            pr.state = state;
+            pr.updated_at = Utc::now().to_rfc3339();
+        }
+        _ => return Ok(workpad),
+    }
+    save_workpad(workpad)
+}
+
+#[tauri::command]
+pub(crate) fn refresh_pull_request(workpad_id: String) -> Result<WorkpadState, String> {
+    sync_pull_request(load_workpad(&workpad_id)?)
+}
+
+/// Refresh every open pull request in a repository; used by the CI poller.
+pub(crate) fn sync_open_pull_requests(repo_id: &str) -> Result<Vec<WorkpadState>, String> {
+    let mut changed = Vec::new();
+    for workpad in list_workpads(Some(repo_id.to_string()), None, None)? {
+        let before = match &workpad.pull_request {
+            Some(pr) if pr.state == "open" => pr.state.clone(),
+            _ => continue,
+        };
+        let workpad_id = workpad.workpad_id.clone();
+        let updated = match sync_pull_request(workpad) {
+            Ok(updated) => updated,
+            Err(e) => {
+                tracing::warn!("Failed to sync pull request for {}: {}", workpad_id, e);
+                continue;
+            }
+        };
+        if updated.pull_request.as_ref().map(|pr| pr.state.as_str()) != Some(before.as_str()) {
+            changed.push(updated);
+        }
+    }
+    Ok(changed)
+}
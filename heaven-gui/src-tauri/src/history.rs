@@ -0,0 +1,162 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::git::{format_git_time, open_repository, short_sha};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct FileHistoryEntry {
+    sha: String,
+    short_sha: String,
+    summary: String,
+    author: String,
+    timestamp: String,
+    /// Path of the file in this commit.
+    path: String,
+    /// Set when the file was renamed in this commit.
+    previous_path: Option<String>,
+    /// "added", "modified" or "renamed"
+    change_type: String,
+    additions: usize,
+    deletions: usize,
+}
+
+fn entry_id(tree: &git2::Tree, path: &str) -> Option<git2::Oid> {
+    tree.get_path(Path::new(path)).ok().map(|entry| entry.id())
+}
+
+/// Added/deleted line counts for `path` within `diff`.
+fn line_stats(diff: &git2::Diff, path: &str) -> (usize, usize) {
+    for index in 0..diff.deltas().len() {
+        let matches = diff
+            .get_delta(index)
+            .and_then(|delta| delta.new_file().path().map(|p| p == Path::new(path)))
+            .unwrap_or(false);
+        if !matches {
+            continue;
+        }
+        if let Ok(Some(patch)) = git2::Patch::from_diff(diff, index) {
+            if let Ok((_, additions, deletions)) = patch.line_stats() {
+                return (additions, deletions);
+            }
+        }
+    }
+    (0, 0)
+}
+
+/// Find the source path if `path` was produced by a rename in `diff`.
+fn renamed_from(diff: &mut git2::Diff, path: &str) -> Result<Option<String>, String> {
+    let mut find = git2::DiffFindOptions::new();
+    find.renames(true);
+    diff.find_similar(Some(&mut find))
+        .map_err(|e| format!("Failed to detect renames: {}", e.message()))?;
+
+    Ok(diff.deltas().find_map(|delta| {
+        let is_target = delta.new_file().path() == Some(Path::new(path));
+        if is_target && delta.status() == git2::Delta::Renamed {
+            delta
+                .old_file()
+                .path()
+                .map(|p| p.to_string_lossy().to_string())
+        } else {
+            None
+        }
+    }))
+}
+
+pub(crate) fn file_history(
+    repo: &git2::Repository,
+    start: git2::Oid,
+    file_path: &str,
+    limit: usize,
+) -> Result<Vec<FileHistoryEntry>, String> {
+    let mut revwalk = repo.revwalk().map_err(|e| e.message().to_string())?;
+    revwalk
+        .set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)
+        .map_err(|e| e.message().to_string())?;
+    revwalk.push(start).map_err(|e| e.message().to_string())?;
+    // Follow first parents only so merges don't duplicate side-branch history.
+    revwalk
+        .simplify_first_parent()
+        .map_err(|e| e.message().to_string())?;
+
+    let mut path = file_path.trim_start_matches("./").to_string();
+    let mut entries = Vec::new();
+
+    for oid in revwalk {
+        let oid = oid.map_err(|e| e.message().to_string())?;
+        let commit = repo.find_commit(oid).map_err(|e| e.message().to_string())?;
+        let tree = commit.tree().map_err(|e| e.message().to_string())?;
+
+        let current = match entry_id(&tree, &path) {
+            Some(id) => id,
+            None => continue,
+        };
+        let parent_tree = commit.parent(0).ok().and_then(|p| p.tree().ok());
+        let previous = parent_tree.as_ref().and_then(|t| entry_id(t, &path));
+        if previous == Some(current) {
+            continue;
+        }
+
+        let mut diff = repo
+            .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)
+            .map_err(|e| format!("Failed to diff {}: {}", short_sha(oid), e.message()))?;
+
+        let (previous_path, change_type) = if previous.is_some() {
+            (None, "modified")
+        } else if parent_tree.is_some() {
+            match renamed_from(&mut diff, &path)? {
+                Some(old) => (Some(old), "renamed"),
+                None => (None, "added"),
+            }
+        } else {
+            (None, "added")
+        };
+        let (additions, deletions) = line_stats(&diff, &path);
+
+        let author = commit.author();
+        entries.push(FileHistoryEntry {
+            sha: oid.to_string(),
+            short_sha: short_sha(oid),
+            summary: commit.summary().unwrap_or("").to_string(),
+            author: author.name().unwrap_or("").to_string(),
+            timestamp: format_git_time(commit.time()),
+            path: path.clone(),
+            previous_path: previous_path.clone(),
+            change_type: change_type.to_string(),
+            additions,
+            deletions,
+        });
+
+        if entries.len() >= limit {
+            break;
+        }
+        match (change_type, previous_path) {
+            ("renamed", Some(old)) => path = old,
+            ("added", _) => break,
+            _ => {}
+        }
+    }
+
+    Ok(entries)
+}
+
+#[tauri::command]
+pub(crate) fn get_file_history(
+    repo_id: String,
+    file_path: String,
+    limit: Option<i32>,
+) -> Result<Vec<FileHistoryEntry>, String> {
+    let repo = open_repository(&repo_id)?;
+    let head = repo
+        .head()
+        .ok()
+        .and_then(|head| head.target())
+        .ok_or_else(|| "Repository has no commits".to_string())?;
+    file_history(
+        &repo,
+        head,
+        &file_path,
+        limit.unwrap_or(100).max(1) as usize,
+    )
+}
@@ -0,0 +1,385 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::audit::audited;
+use crate::commands::{
+    load_repository, read_json, save_repository, workpad_checkout_dir, write_json,
+};
+use crate::git::run_git;
+use crate::logging::warn_on_err;
+use crate::sandbox::{run_sandboxed, sandbox_config_for};
+use crate::statuses::TestRunStatus;
+use crate::testing::run_repo_target;
+use crate::tools::{combined_output, parse_issues, ToolIssue};
+use crate::{get_settings, get_state_dir, WorkpadState};
+
+const HOOK_KINDS: &[&str] = &["format", "lint", "test", "command"];
+const HOOK_STAGES: &[&str] = &["apply_patch", "promote"];
+const DEFAULT_PAGE_SIZE: usize = 50;
+
+/// One step of a repository's pre-commit pipeline. Steps run in order.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct HookStep {
+    name: String,
+    /// "format" and "lint" run the named entry from the tool settings;
+    /// "test" runs a discovered test target; "command" runs `argv`.
+    kind: String,
+    /// Formatter or linter name, or the test target.
+    #[serde(default)]
+    tool: Option<String>,
+    #[serde(default)]
+    argv: Vec<String>,
+    /// Which of "apply_patch" and "promote" the step runs on; both when
+    /// empty.
+    #[serde(default)]
+    stages: Vec<String>,
+    /// Record a failure without aborting the operation.
+    #[serde(default)]
+    allow_failure: bool,
+    #[serde(default = "enabled_default")]
+    enabled: bool,
+}
+
+fn enabled_default() -> bool {
+    true
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct HookStepResult {
+    name: String,
+    kind: String,
+    passed: bool,
+    allow_failure: bool,
+    exit_code: Option<i32>,
+    /// Why the step failed, in one line.
+    message: Option<String>,
+    issues: Vec<ToolIssue>,
+    test_run_id: Option<String>,
+    output: String,
+    duration_ms: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct HookRun {
+    run_id: String,
+    repo_id: String,
+    workpad_id: String,
+    stage: String,
+    passed: bool,
+    steps: Vec<HookStepResult>,
+    created_at: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct HookRunPage {
+    items: Vec<HookRun>,
+    page: usize,
+    page_size: usize,
+    total: usize,
+}
+
+impl HookStep {
+    fn runs_on(&self, stage: &str) -> bool {
+        self.enabled && (self.stages.is_empty() || self.stages.iter().any(|s| s == stage))
+    }
+}
+
+fn runs_dir() -> PathBuf {
+    get_state_dir().join("hook_runs")
+}
+
+fn validate_steps(steps: &[HookStep]) -> Result<(), String> {
+    let mut problems = Vec::new();
+    for step in steps {
+        if !HOOK_KINDS.contains(&step.kind.as_str()) {
+            problems.push(format!(
+                "{}: kind must be one of {}",
+                step.name,
+                HOOK_KINDS.join(", ")
+            ));
+        }
+        if step.kind == "command" && step.argv.is_empty() {
+            problems.push(format!("{}: a command step needs argv", step.name));
+        }
+        if step.kind != "command" && step.tool.is_none() {
+            problems.push(format!("{}: a {} step needs a tool", step.name, step.kind));
+        }
+        if let Some(stage) = step
+            .stages
+            .iter()
+            .find(|s| !HOOK_STAGES.contains(&s.as_str()))
+        {
+            problems.push(format!("{}: unknown stage {}", step.name, stage));
+        }
+    }
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Invalid hooks: {}", problems.join("; ")))
+    }
+}
+
+/// Run one step in `dir`, filling in `result`. Returning `Err` is a
+/// failure to run the step at all.
+fn execute_step(
+    step: &HookStep,
+    repo_id: &str,
+    dir: &Path,
+    result: &mut HookStepResult,
+) -> Result<(), String> {
+    let tool = step.tool.clone().unwrap_or_default();
+    if step.kind == "test" {
+        let run = run_repo_target(repo_id, dir, &tool)?;
+        result.test_run_id = Some(run.run_id.clone());
+        if run.status != TestRunStatus::Passed {
+            result.passed = false;
+            result.message = Some(format!(
+                "{} of {} tests failed",
+                run.failed, run.total_tests
+            ));
+        }
+        return Ok(());
+    }
+
+    let tools = get_settings()?.tools;
+    let (argv, format) = match step.kind.as_str() {
+        "format" => tools.formatters.get(&tool).map(|t| (t.argv.clone(), None)),
+        "lint" => tools
+            .linters
+            .get(&tool)
+            .map(|t| (t.argv.clone(), Some(t.output.clone()))),
+        _ => Some((step.argv.clone(), None)),
+    }
+    .ok_or_else(|| format!("No {} tool named {} is configured", step.kind, tool))?;
+
+    let output = run_sandboxed(&sandbox_config_for(repo_id), &argv, dir)?;
+    result.exit_code = output.exit_code;
+    result.output = combined_output(&output);
+    if let Some(format) = &format {
+        result.issues = parse_issues(format, &output, dir);
+    }
+    let errors = result
+        .issues
+        .iter()
+        .filter(|issue| issue.is_error())
+        .count();
+    let message = if output.timed_out {
+        Some("Timed out".to_string())
+    } else if step.kind == "format" {
+        // A formatter passes only if it had nothing to rewrite; the index
+        // holds the tree being checked.
+        let changed = run_git(dir, &["diff", "--name-only"])?;
+        let files: Vec<&str> = changed.lines().collect();
+        (!files.is_empty()).then(|| format!("Would reformat {}", files.join(", ")))
+    } else if errors > 0 {
+        Some(format!("{} error(s)", errors))
+    } else if !output.success() {
+        Some(format!("Exited with {:?}", output.exit_code))
+    } else {
+        None
+    };
+    if message.is_some() {
+        result.passed = false;
+        result.message = message;
+    }
+    Ok(())
+}
+
+fn run_step(step: &HookStep, repo_id: &str, dir: &Path) -> HookStepResult {
+    let started = Instant::now();
+    let mut result = HookStepResult {
+        name: step.name.clone(),
+        kind: step.kind.clone(),
+        passed: true,
+        allow_failure: step.allow_failure,
+        exit_code: None,
+        message: None,
+        issues: Vec::new(),
+        test_run_id: None,
+        output: String::new(),
+        duration_ms: 0,
+    };
+    if let Err(e) = execute_step(step, repo_id, dir, &mut result) {
+        result.passed = false;
+        result.message = Some(e);
+    }
+    result.duration_ms = started.elapsed().as_millis() as i64;
+    result
+}
+
+/// Put the checkout back to its branch tip, dropping anything the hooks or
+/// the staged patch left behind.
+fn restore(dir: &Path) {
+    warn_on_err(
+        "Failed to reset checkout after hooks",
+        run_git(dir, &["reset", "--hard", "HEAD"]),
+    );
+    warn_on_err(
+        "Failed to clean checkout after hooks",
+        run_git(dir, &["clean", "-fd"]),
+    );
+}
+
+/// Run the repository's hooks for `stage` in the workpad's checkout, with
+/// `patch` applied on top when given, and record the outcome. Fails with a
+/// summary naming the stored run when a step fails without
+/// `allow_failure`.
+pub(crate) fn run_hooks(
+    workpad: &WorkpadState,
+    stage: &str,
+    patch: Option<&str>,
+) -> Result<Option<HookRun>, String> {
+    let steps: Vec<HookStep> = load_repository(&workpad.repo_id)?
+        .hooks
+        .into_iter()
+        .filter(|step| step.runs_on(stage))
+        .collect();
+    if steps.is_empty() {
+        return Ok(None);
+    }
+    let dir = workpad_checkout_dir(workpad)?;
+    if !run_git(&dir, &["status", "--porcelain"])?.trim().is_empty() {
+        return Err(format!(
+            "{} has uncommitted changes; hooks need a clean checkout",
+            dir.display()
+        ));
+    }
+    // The patch is staged, not committed, so the workpad branch never
+    // moves; the index is the tree under test.
+    if let Some(patch) = patch {
+        let path = env::temp_dir().join(format!("sologit_hook_{}.diff", Uuid::new_v4().simple()));
+        fs::write(&path, patch).map_err(|e| format!("Failed to write patch: {}", e))?;
+        let applied = run_git(&dir, &["apply", "--index", &path.to_string_lossy()]);
+        warn_on_err("Failed to remove temporary patch", fs::remove_file(&path));
+        if let Err(e) = applied {
+            restore(&dir);
+            return Err(format!("Patch doesn't apply for hooks: {}", e));
+        }
+    }
+
+    let mut results = Vec::with_capacity(steps.len());
+    for step in &steps {
+        let result = run_step(step, &workpad.repo_id, &dir);
+        let stop = !result.passed && !result.allow_failure;
+        results.push(result);
+        // Each step sees the same tree, not the previous step's edits.
+        warn_on_err(
+            "Failed to reset checkout between hooks",
+            run_git(&dir, &["checkout", "--", "."]),
+        );
+        warn_on_err(
+            "Failed to clean checkout between hooks",
+            run_git(&dir, &["clean", "-fd"]),
+        );
+        if stop {
+            break;
+        }
+    }
+    restore(&dir);
+
+    let run = HookRun {
+        run_id: format!("hookrun-{}", Uuid::new_v4().simple()),
+        repo_id: workpad.repo_id.clone(),
+        workpad_id: workpad.workpad_id.clone(),
+        stage: stage.to_string(),
+        passed: results.iter().all(|r| r.passed || r.allow_failure),
+        steps: results,
+        created_at: Utc::now().to_rfc3339(),
+    };
+    write_json(&runs_dir().join(format!("{}.json", run.run_id)), &run)?;
+    if run.passed {
+        return Ok(Some(run));
+    }
+    let failures: Vec<String> = run
+        .steps
+        .iter()
+        .filter(|step| !step.passed && !step.allow_failure)
+        .map(|step| {
+            format!(
+                "{} ({})",
+                step.name,
+                step.message.as_deref().unwrap_or("failed")
+            )
+        })
+        .collect();
+    Err(format!(
+        "Hooks failed: {}; see hook run {}",
+        failures.join(", "),
+        run.run_id
+    ))
+}
+
+#[tauri::command]
+pub(crate) fn get_repo_hooks(repo_id: String) -> Result<Vec<HookStep>, String> {
+    Ok(load_repository(&repo_id)?.hooks)
+}
+
+/// Replace the repository's hook pipeline.
+#[tauri::command]
+pub(crate) fn set_repo_hooks(
+    repo_id: String,
+    hooks: Vec<HookStep>,
+) -> Result<Vec<HookStep>, String> {
+    validate_steps(&hooks)?;
+    audited(
+        "set_repo_hooks",
+        "repository",
+        Some(repo_id.clone()),
+        None,
+        move || {
+            let mut repo = load_repository(&repo_id)?;
+            repo.hooks = hooks;
+            Ok(save_repository(repo)?.hooks)
+        },
+    )
+}
+
+/// Recorded hook runs, newest first.
+#[tauri::command]
+pub(crate) fn list_hook_runs(
+    repo_id: Option<String>,
+    workpad_id: Option<String>,
+    page: Option<usize>,
+    page_size: Option<usize>,
+) -> Result<HookRunPage, String> {
+    let mut runs = Vec::new();
+    if let Ok(entries) = fs::read_dir(runs_dir()) {
+        for entry in entries.flatten() {
+            if let Some(run) = read_json::<HookRun>(&entry.path())? {
+                let matches = (repo_id.is_none()
+                    || repo_id.as_deref() == Some(run.repo_id.as_str()))
+                    && (workpad_id.is_none()
+                        || workpad_id.as_deref() == Some(run.workpad_id.as_str()));
+                if matches {
+                    runs.push(run);
+                }
+            }
+        }
+    }
+    runs.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    let page = page.unwrap_or(0);
+    let page_size = page_size.unwrap_or(DEFAULT_PAGE_SIZE).max(1);
+    let total = runs.len();
+    Ok(HookRunPage {
+        items: runs
+            .into_iter()
+            .skip(page * page_size)
+            .take(page_size)
+            .collect(),
+        page,
+        page_size,
+        total,
+    })
+}
+
+#[tauri::command]
+pub(crate) fn get_hook_run(run_id: String) -> Result<HookRun, String> {
+    read_json(&runs_dir().join(format!("{}.json", run_id)))?
+        .ok_or_else(|| format!("Hook run not found: {}", run_id))
+}
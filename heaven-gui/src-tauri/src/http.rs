@@ -0,0 +1,43 @@
+use std::time::Duration;
+
+use serde_json::Value;
+
+/// Shared blocking HTTP agent for outbound integrations.
+pub(crate) fn agent() -> ureq::Agent {
+    ureq::AgentBuilder::new()
+        .timeout(Duration::from_secs(15))
+        .user_agent("heaven-gui")
+        .build()
+}
+
+/// Decode a JSON response, turning non-2xx statuses into readable errors.
+pub(crate) fn json_response(result: Result<ureq::Response, ureq::Error>) -> Result<Value, String> {
+    match result {
+        Ok(response) => response
+            .into_json::<Value>()
+            .map_err(|e| format!("Failed to parse response: {}", e)),
+        Err(ureq::Error::Status(code, response)) => {
+            let url = response.get_url().to_string();
+            Err(format!(
+                "{} returned {}: {}",
+                url,
+                code,
+                response.into_string().unwrap_or_default()
+            ))
+        }
+        Err(e) => Err(format!("Request failed: {}", e)),
+    }
+}
+
+/// Percent-encode a single path or query component.
+pub(crate) fn encode_component(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
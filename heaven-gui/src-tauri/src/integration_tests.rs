@@ -0,0 +1,135 @@
+use std::path::Path;
+
+use crate::commands::{
+    apply_patch, create_repository, create_workpad, load_repository, load_workpad, promote_workpad,
+    run_tests,
+};
+use crate::commit_message::suggest_commit_message;
+use crate::git::run_git;
+use crate::ledger;
+use crate::lifecycle::WorkpadStatus;
+use crate::statuses::TestRunStatus;
+use crate::test_harness::TestEnv;
+use crate::{get_repos_dir, get_state_dir, WorkpadState};
+
+const HELLO_PATCH: &str = "diff --git a/hello.txt b/hello.txt
+new file mode 100644
+--- /dev/null
++++ b/hello.txt
+@@ -0,0 +1 @@
++hello
+";
+
+fn new_workpad(title: &str) -> WorkpadState {
+    let repo = create_repository("demo".to_string(), None).expect("create repository");
+    create_workpad(repo.repo_id, title.to_string()).expect("create workpad")
+}
+
+#[test]
+fn state_lives_in_the_temp_home() {
+    let env = TestEnv::new();
+    assert!(env.contains(&get_state_dir()));
+    assert!(env.contains(&get_repos_dir()));
+
+    let workpad = new_workpad("Isolated");
+    let repo = load_repository(&workpad.repo_id).expect("load repository");
+    assert!(env.contains(Path::new(&repo.path)));
+    assert!(env
+        .home()
+        .join("state/workpads")
+        .join(format!("{}.json", workpad.workpad_id))
+        .is_file());
+}
+
+#[test]
+fn create_apply_test_promote() {
+    let _env = TestEnv::new();
+    let workpad = new_workpad("Add greeting");
+    assert_eq!(workpad.status, WorkpadStatus::Active);
+
+    let workpad = apply_patch(
+        workpad.workpad_id.clone(),
+        "Add hello".to_string(),
+        HELLO_PATCH.to_string(),
+    )
+    .expect("apply patch");
+    assert_eq!(workpad.patches_applied, 1);
+    assert_eq!(workpad.files_changed, vec!["hello.txt".to_string()]);
+
+    let run = run_tests(workpad.workpad_id.clone(), "fast".to_string()).expect("run tests");
+    assert_eq!(run.status, TestRunStatus::Passed);
+
+    let record = promote_workpad(workpad.workpad_id.clone()).expect("promote");
+    assert!(record.promoted);
+    assert_eq!(record.test_run_id.as_deref(), Some(run.run_id.as_str()));
+
+    let repo = load_repository(&workpad.repo_id).expect("load repository");
+    let trunk =
+        run_git(Path::new(&repo.path), &["rev-parse", &repo.trunk_branch]).expect("trunk head");
+    assert_eq!(record.commit_hash.as_deref(), Some(trunk.trim()));
+    assert!(Path::new(&repo.path).join("hello.txt").is_file());
+    assert_eq!(
+        load_workpad(&workpad.workpad_id)
+            .expect("load workpad")
+            .status,
+        WorkpadStatus::Promoted
+    );
+}
+
+#[test]
+fn promotion_needs_a_passing_run() {
+    let _env = TestEnv::new();
+    let workpad = new_workpad("Broken change");
+    apply_patch(
+        workpad.workpad_id.clone(),
+        "Add hello".to_string(),
+        HELLO_PATCH.to_string(),
+    )
+    .expect("apply patch");
+
+    let error = promote_workpad(workpad.workpad_id.clone()).expect_err("untested promotion");
+    assert!(error.contains("passing test run"), "{}", error);
+
+    let run = run_tests(workpad.workpad_id.clone(), "fail".to_string()).expect("run tests");
+    assert_eq!(run.status, TestRunStatus::Failed);
+    let error = promote_workpad(workpad.workpad_id.clone()).expect_err("failed promotion");
+    assert!(error.contains("passing test run"), "{}", error);
+}
+
+#[test]
+fn empty_patch_is_rejected() {
+    let _env = TestEnv::new();
+    let workpad = new_workpad("Nothing");
+    let error = apply_patch(workpad.workpad_id, String::new(), "  \n".to_string())
+        .expect_err("empty patch");
+    assert_eq!(error, "Patch diff cannot be empty");
+}
+
+#[test]
+fn ai_operations_use_the_mock_provider() {
+    let _env = TestEnv::new();
+    let workpad = new_workpad("Add greeting");
+    apply_patch(
+        workpad.workpad_id.clone(),
+        "Add hello".to_string(),
+        HELLO_PATCH.to_string(),
+    )
+    .expect("apply patch");
+
+    let suggestion = suggest_commit_message(workpad.workpad_id.clone()).expect("suggest");
+    let suggestion = serde_json::to_value(suggestion).expect("serialize suggestion");
+    assert_eq!(suggestion["message"], "chore: update files");
+    assert_eq!(suggestion["cost_usd"], 0.0);
+}
+
+#[test]
+fn ledger_session_survives_a_missing_global_state() {
+    let _env = TestEnv::new();
+    assert!(!get_state_dir().join("global.json").exists());
+
+    ledger::record("review", None, 0.5).expect("record");
+    ledger::record("review", None, 0.25).expect("record");
+    let summary = ledger::get_session_summary().expect("summary");
+    assert_eq!(summary.total_operations, 2);
+    assert_eq!(summary.total_cost_usd, 0.75);
+}
@@ -0,0 +1,336 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::audit::audited;
+use crate::{get_settings, write_settings};
+
+const PRESETS: &[&str] = &["default", "vim", "vscode"];
+
+/// Modifiers in canonical order. "Cmd" is Command on macOS and Ctrl
+/// elsewhere, matching `formatShortcut` in the frontend.
+const MODIFIERS: &[&str] = &["Cmd", "Ctrl", "Alt", "Shift"];
+
+const NAMED_KEYS: &[&str] = &[
+    "Space",
+    "Enter",
+    "Escape",
+    "Tab",
+    "Backspace",
+    "Delete",
+    "Up",
+    "Down",
+    "Left",
+    "Right",
+    "Home",
+    "End",
+    "PageUp",
+    "PageDown",
+    "F1",
+    "F2",
+    "F3",
+    "F4",
+    "F5",
+    "F6",
+    "F7",
+    "F8",
+    "F9",
+    "F10",
+    "F11",
+    "F12",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub(crate) struct KeybindingSettings {
+    pub(crate) preset: String,
+    /// Per-action chords layered over the preset; an empty chord unbinds.
+    pub(crate) overrides: BTreeMap<String, String>,
+}
+
+impl Default for KeybindingSettings {
+    fn default() -> Self {
+        KeybindingSettings {
+            preset: "default".to_string(),
+            overrides: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct Keybinding {
+    action: String,
+    chord: Option<String>,
+    /// "preset" or "custom"
+    source: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct KeybindingConflict {
+    chord: String,
+    actions: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct KeybindingMap {
+    preset: String,
+    presets: Vec<String>,
+    bindings: Vec<Keybinding>,
+    conflicts: Vec<KeybindingConflict>,
+}
+
+fn preset_bindings(preset: &str) -> Vec<(&'static str, &'static str)> {
+    match preset {
+        "vim" => vec![
+            ("commandPalette.open", "Space Space"),
+            ("quickOpen", "Space F"),
+            ("panel.toggleLeft", "Space E"),
+            ("panel.toggleRight", "Space J"),
+            ("file.save", "Space W"),
+            ("editor.format", "= ="),
+            ("editor.toggleComment", "G C"),
+            ("explorer.search", "Space /"),
+            ("tests.run", "Space T"),
+            ("voice.record", "Ctrl+Space"),
+        ],
+        "vscode" => vec![
+            ("commandPalette.open", "Cmd+Shift+P"),
+            ("quickOpen", "Cmd+P"),
+            ("panel.toggleLeft", "Cmd+B"),
+            ("panel.toggleRight", "Cmd+J"),
+            ("file.save", "Cmd+S"),
+            ("editor.format", "Alt+Shift+F"),
+            ("editor.toggleComment", "Cmd+/"),
+            ("explorer.search", "Cmd+Shift+F"),
+            ("tests.run", "Cmd+Shift+T"),
+            ("voice.record", "Ctrl+Space"),
+        ],
+        _ => vec![
+            ("commandPalette.open", "Cmd+K"),
+            ("quickOpen", "Cmd+P"),
+            ("panel.toggleLeft", "Cmd+B"),
+            ("panel.toggleRight", "Cmd+J"),
+            ("file.save", "Cmd+S"),
+            ("editor.format", "Alt+Shift+F"),
+            ("editor.toggleComment", "Cmd+/"),
+            ("explorer.search", "Cmd+F"),
+            ("tests.run", "Cmd+Shift+T"),
+            ("voice.record", "Ctrl+Space"),
+        ],
+    }
+}
+
+fn normalize_stroke(stroke: &str) -> Result<String, String> {
+    let mut modifiers = Vec::new();
+    let mut key = None;
+    for part in stroke.split('+').map(str::trim) {
+        let modifier = match part.to_lowercase().as_str() {
+            "cmd" | "command" | "meta" | "super" | "mod" => Some("Cmd"),
+            "ctrl" | "control" => Some("Ctrl"),
+            "alt" | "option" | "opt" => Some("Alt"),
+            "shift" => Some("Shift"),
+            _ => None,
+        };
+        match modifier {
+            Some(modifier) if !modifiers.contains(&modifier) => modifiers.push(modifier),
+            Some(modifier) => return Err(format!("{} is repeated in {}", modifier, stroke)),
+            None if key.is_some() => {
+                return Err(format!("{} has more than one non-modifier key", stroke))
+            }
+            None if part.chars().count() == 1 => key = Some(part.to_uppercase()),
+            None => {
+                let named = NAMED_KEYS
+                    .iter()
+                    .find(|name| name.eq_ignore_ascii_case(part))
+                    .ok_or_else(|| format!("Unknown key {} in {}", part, stroke))?;
+                key = Some(named.to_string());
+            }
+        }
+    }
+
+    let key = key.ok_or_else(|| format!("{} has no key, only modifiers", stroke))?;
+    let mut parts: Vec<String> = MODIFIERS
+        .iter()
+        .filter(|modifier| modifiers.contains(modifier))
+        .map(|modifier| modifier.to_string())
+        .collect();
+    parts.push(key);
+    Ok(parts.join("+"))
+}
+
+/// Canonicalise a chord such as "shift+ctrl+p" or a sequence like "g c"
+/// to "Ctrl+Shift+P" / "G C" so equal chords compare equal.
+pub(crate) fn normalize_chord(chord: &str) -> Result<String, String> {
+    let strokes = chord
+        .split_whitespace()
+        .map(normalize_stroke)
+        .collect::<Result<Vec<_>, _>>()?;
+    if strokes.is_empty() {
+        return Err("Chord cannot be empty".to_string());
+    }
+    Ok(strokes.join(" "))
+}
+
+fn effective_bindings(settings: &KeybindingSettings) -> Vec<Keybinding> {
+    let mut bindings: BTreeMap<String, Keybinding> = preset_bindings(&settings.preset)
+        .into_iter()
+        .map(|(action, chord)| {
+            (
+                action.to_string(),
+                Keybinding {
+                    action: action.to_string(),
+                    chord: Some(chord.to_string()),
+                    source: "preset".to_string(),
+                },
+            )
+        })
+        .collect();
+    for (action, chord) in &settings.overrides {
+        bindings.insert(
+            action.clone(),
+            Keybinding {
+                action: action.clone(),
+                chord: Some(chord.clone()).filter(|chord| !chord.is_empty()),
+                source: "custom".to_string(),
+            },
+        );
+    }
+    bindings.into_values().collect()
+}
+
+/// Chords bound to more than one action, including sequences shadowed by
+/// a shorter binding that is a prefix of them.
+fn find_conflicts(bindings: &[Keybinding]) -> Vec<KeybindingConflict> {
+    let mut by_chord: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for binding in bindings {
+        let chord = match &binding.chord {
+            Some(chord) => chord,
+            None => continue,
+        };
+        for other in bindings {
+            if other.action == binding.action {
+                continue;
+            }
+            let shadows = other.chord.as_ref().is_some_and(|prefix| {
+                chord == prefix || chord.starts_with(&format!("{} ", prefix))
+            });
+            if shadows {
+                let actions = by_chord.entry(chord.clone()).or_default();
+                for action in [&binding.action, &other.action] {
+                    if !actions.contains(action) {
+                        actions.push(action.clone());
+                    }
+                }
+            }
+        }
+    }
+    by_chord
+        .into_iter()
+        .map(|(chord, mut actions)| {
+            actions.sort();
+            KeybindingConflict { chord, actions }
+        })
+        .collect()
+}
+
+fn keybinding_map(settings: &KeybindingSettings) -> KeybindingMap {
+    let bindings = effective_bindings(settings);
+    KeybindingMap {
+        preset: settings.preset.clone(),
+        presets: PRESETS.iter().map(|preset| preset.to_string()).collect(),
+        conflicts: find_conflicts(&bindings),
+        bindings,
+    }
+}
+
+#[tauri::command]
+pub(crate) fn get_keybindings() -> Result<KeybindingMap, String> {
+    Ok(keybinding_map(&get_settings()?.keybindings))
+}
+
+/// Bind `action` to `chord` (an empty chord unbinds it). Rejected if the
+/// chord would collide with another action's binding.
+#[tauri::command]
+pub(crate) fn set_keybinding(action: String, chord: String) -> Result<KeybindingMap, String> {
+    audited(
+        "set_keybinding",
+        "keybinding",
+        Some(action.clone()),
+        None,
+        move || {
+            let action = action.trim().to_string();
+            if action.is_empty() {
+                return Err("Action cannot be empty".to_string());
+            }
+            let chord = if chord.trim().is_empty() {
+                String::new()
+            } else {
+                normalize_chord(&chord)?
+            };
+
+            let mut settings = get_settings()?;
+            let mut candidate = settings.keybindings.clone();
+            candidate.overrides.insert(action.clone(), chord);
+
+            let existing: Vec<String> = find_conflicts(&effective_bindings(&settings.keybindings))
+                .into_iter()
+                .map(|conflict| conflict.chord)
+                .collect();
+            let introduced: Vec<KeybindingConflict> =
+                find_conflicts(&effective_bindings(&candidate))
+                    .into_iter()
+                    .filter(|conflict| {
+                        conflict.actions.contains(&action) && !existing.contains(&conflict.chord)
+                    })
+                    .collect();
+            if let Some(conflict) = introduced.first() {
+                return Err(format!(
+                    "{} conflicts with {} on {}",
+                    action,
+                    conflict
+                        .actions
+                        .iter()
+                        .filter(|other| **other != action)
+                        .cloned()
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    conflict.chord
+                ));
+            }
+
+            settings.keybindings = candidate;
+            write_settings(&settings)?;
+            Ok(keybinding_map(&settings.keybindings))
+        },
+    )
+}
+
+/// Switch to a preset scheme. Custom overrides are kept unless `reset`.
+#[tauri::command]
+pub(crate) fn set_keybinding_preset(
+    preset: String,
+    reset: Option<bool>,
+) -> Result<KeybindingMap, String> {
+    audited(
+        "set_keybinding_preset",
+        "keybinding",
+        Some(preset.clone()),
+        None,
+        move || {
+            if !PRESETS.contains(&preset.as_str()) {
+                return Err(format!(
+                    "Unknown keybinding preset {}; expected one of {}",
+                    preset,
+                    PRESETS.join(", ")
+                ));
+            }
+            let mut settings = get_settings()?;
+            settings.keybindings.preset = preset;
+            if reset.unwrap_or(false) {
+                settings.keybindings.overrides.clear();
+            }
+            write_settings(&settings)?;
+            Ok(keybinding_map(&settings.keybindings))
+        },
+    )
+}
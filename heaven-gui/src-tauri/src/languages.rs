@@ -0,0 +1,358 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::commands::{read_json, write_json};
+use crate::files::repo_file_path;
+use crate::get_state_dir;
+use crate::git::{open_repository, resolve_commit};
+
+struct Language {
+    /// Editor mode id, as used for syntax highlighting.
+    id: &'static str,
+    name: &'static str,
+    /// "programming", "markup", "data" or "prose", as linguist classifies it.
+    kind: &'static str,
+    color: &'static str,
+    extensions: &'static [&'static str],
+    filenames: &'static [&'static str],
+    interpreters: &'static [&'static str],
+}
+
+const fn lang(
+    id: &'static str,
+    name: &'static str,
+    kind: &'static str,
+    color: &'static str,
+    extensions: &'static [&'static str],
+) -> Language {
+    Language {
+        id,
+        name,
+        kind,
+        color,
+        extensions,
+        filenames: &[],
+        interpreters: &[],
+    }
+}
+
+const LANGUAGES: &[Language] = &[
+    Language {
+        interpreters: &["python", "python2", "python3"],
+        ..lang(
+            "python",
+            "Python",
+            "programming",
+            "#3572A5",
+            &["py", "pyi", "pyw"],
+        )
+    },
+    lang("rust", "Rust", "programming", "#dea584", &["rs"]),
+    lang(
+        "typescript",
+        "TypeScript",
+        "programming",
+        "#3178c6",
+        &["ts", "mts", "cts"],
+    ),
+    lang("typescriptreact", "TSX", "programming", "#3178c6", &["tsx"]),
+    Language {
+        interpreters: &["node", "nodejs", "deno", "bun"],
+        ..lang(
+            "javascript",
+            "JavaScript",
+            "programming",
+            "#f1e05a",
+            &["js", "mjs", "cjs"],
+        )
+    },
+    lang("javascriptreact", "JSX", "programming", "#f1e05a", &["jsx"]),
+    lang("go", "Go", "programming", "#00ADD8", &["go"]),
+    lang("java", "Java", "programming", "#b07219", &["java"]),
+    lang("kotlin", "Kotlin", "programming", "#A97BFF", &["kt", "kts"]),
+    lang("c", "C", "programming", "#555555", &["c", "h"]),
+    lang(
+        "cpp",
+        "C++",
+        "programming",
+        "#f34b7d",
+        &["cc", "cpp", "cxx", "hpp", "hh"],
+    ),
+    lang("csharp", "C#", "programming", "#178600", &["cs"]),
+    Language {
+        filenames: &["Gemfile", "Rakefile"],
+        interpreters: &["ruby"],
+        ..lang("ruby", "Ruby", "programming", "#701516", &["rb"])
+    },
+    Language {
+        interpreters: &["php"],
+        ..lang("php", "PHP", "programming", "#4F5D95", &["php"])
+    },
+    lang("swift", "Swift", "programming", "#F05138", &["swift"]),
+    Language {
+        interpreters: &["lua"],
+        ..lang("lua", "Lua", "programming", "#000080", &["lua"])
+    },
+    Language {
+        interpreters: &["perl"],
+        ..lang("perl", "Perl", "programming", "#0298c3", &["pl", "pm"])
+    },
+    Language {
+        interpreters: &["sh", "bash", "zsh", "dash", "ksh"],
+        ..lang(
+            "shell",
+            "Shell",
+            "programming",
+            "#89e051",
+            &["sh", "bash", "zsh"],
+        )
+    },
+    Language {
+        filenames: &["Dockerfile", "Containerfile"],
+        ..lang(
+            "dockerfile",
+            "Dockerfile",
+            "programming",
+            "#384d54",
+            &["dockerfile"],
+        )
+    },
+    Language {
+        filenames: &["Makefile", "makefile", "GNUmakefile"],
+        interpreters: &["make"],
+        ..lang("makefile", "Makefile", "programming", "#427819", &["mk"])
+    },
+    lang("sql", "SQL", "data", "#e38c00", &["sql"]),
+    lang("json", "JSON", "data", "#292929", &["json"]),
+    lang("toml", "TOML", "data", "#9c4221", &["toml"]),
+    lang("yaml", "YAML", "data", "#cb171e", &["yaml", "yml"]),
+    lang("xml", "XML", "data", "#0060ac", &["xml"]),
+    lang(
+        "markdown",
+        "Markdown",
+        "prose",
+        "#083fa1",
+        &["md", "markdown"],
+    ),
+    lang("html", "HTML", "markup", "#e34c26", &["html", "htm"]),
+    lang("css", "CSS", "markup", "#663399", &["css"]),
+    lang("scss", "SCSS", "markup", "#c6538c", &["scss"]),
+    lang("vue", "Vue", "markup", "#41b883", &["vue"]),
+];
+
+/// Path segments and suffixes excluded from repository statistics, as
+/// linguist excludes vendored and generated code.
+const VENDORED_DIRS: &[&str] = &[
+    "node_modules",
+    "vendor",
+    "third_party",
+    "dist",
+    "build",
+    "target",
+    ".git",
+];
+const GENERATED_SUFFIXES: &[&str] = &[".min.js", ".min.css", ".map", ".lock"];
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct LanguageInfo {
+    id: String,
+    name: String,
+    kind: String,
+    color: String,
+    /// "filename", "extension" or "shebang"
+    detected_by: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct LanguageShare {
+    id: String,
+    name: String,
+    color: String,
+    bytes: u64,
+    files: usize,
+    percentage: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct LanguageStats {
+    repo_id: String,
+    /// Commit the statistics were computed at; they're reused until HEAD
+    /// moves.
+    commit_sha: String,
+    total_bytes: u64,
+    languages: Vec<LanguageShare>,
+}
+
+impl Language {
+    fn info(&self, detected_by: &str) -> LanguageInfo {
+        LanguageInfo {
+            id: self.id.to_string(),
+            name: self.name.to_string(),
+            kind: self.kind.to_string(),
+            color: self.color.to_string(),
+            detected_by: detected_by.to_string(),
+        }
+    }
+}
+
+fn by_path(path: &Path) -> Option<(&'static Language, &'static str)> {
+    let name = path.file_name()?.to_str()?;
+    if let Some(language) = LANGUAGES.iter().find(|l| l.filenames.contains(&name)) {
+        return Some((language, "filename"));
+    }
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    LANGUAGES
+        .iter()
+        .find(|l| l.extensions.contains(&extension.as_str()))
+        .map(|language| (language, "extension"))
+}
+
+/// `#!/usr/bin/env python3` or `#!/bin/bash -e` to the interpreter's
+/// language, ignoring version suffixes such as `python3.11`.
+fn by_shebang(first_line: &str) -> Option<&'static Language> {
+    let command = first_line.strip_prefix("#!")?;
+    let mut words = command.split_whitespace();
+    let mut program = words.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = words.find(|word| !word.starts_with('-'))?;
+    }
+    let find = |name: &str| LANGUAGES.iter().find(|l| l.interpreters.contains(&name));
+    find(program)
+        .or_else(|| find(program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.')))
+}
+
+/// Best-effort language guess from the file name.
+pub(crate) fn guess_language(path: &Path) -> Option<&'static str> {
+    by_path(path).map(|(language, _)| language.id)
+}
+
+fn is_vendored(path: &str) -> bool {
+    path.split('/')
+        .any(|segment| VENDORED_DIRS.contains(&segment))
+        || GENERATED_SUFFIXES
+            .iter()
+            .any(|suffix| path.ends_with(suffix))
+}
+
+fn stats_path(repo_id: &str) -> PathBuf {
+    get_state_dir()
+        .join("language_stats")
+        .join(format!("{}.json", repo_id))
+}
+
+/// Byte counts per language over the tree at `commit`. Files without a
+/// recognised name are checked for a shebang.
+fn compute_stats(
+    repo: &git2::Repository,
+    repo_id: &str,
+    commit: &git2::Commit,
+) -> Result<LanguageStats, String> {
+    let tree = commit.tree().map_err(|e| e.message().to_string())?;
+    let odb = repo.odb().map_err(|e| e.message().to_string())?;
+    let mut shares: HashMap<&'static str, (u64, usize)> = HashMap::new();
+
+    tree.walk(git2::TreeWalkMode::PreOrder, |root, entry| {
+        if entry.kind() != Some(git2::ObjectType::Blob) {
+            return git2::TreeWalkResult::Ok;
+        }
+        let Some(name) = entry.name() else {
+            return git2::TreeWalkResult::Ok;
+        };
+        let path = format!("{}{}", root, name);
+        if is_vendored(&path) {
+            return git2::TreeWalkResult::Ok;
+        }
+        let language = by_path(Path::new(&path))
+            .map(|(language, _)| language)
+            .or_else(|| {
+                if Path::new(&path).extension().is_some() {
+                    return None;
+                }
+                let blob = repo.find_blob(entry.id()).ok()?;
+                let first_line = blob.content().split(|b| *b == b'\n').next()?;
+                by_shebang(std::str::from_utf8(first_line).ok()?)
+            });
+        // Like GitHub's breakdown, only code and markup count.
+        let Some(language) = language.filter(|l| matches!(l.kind, "programming" | "markup")) else {
+            return git2::TreeWalkResult::Ok;
+        };
+        if let Ok((size, _)) = odb.read_header(entry.id()) {
+            let share = shares.entry(language.id).or_default();
+            share.0 += size as u64;
+            share.1 += 1;
+        }
+        git2::TreeWalkResult::Ok
+    })
+    .map_err(|e| format!("Failed to walk tree: {}", e.message()))?;
+
+    let total_bytes: u64 = shares.values().map(|(bytes, _)| bytes).sum();
+    let mut languages: Vec<LanguageShare> = shares
+        .into_iter()
+        .filter_map(|(id, (bytes, files))| {
+            let language = LANGUAGES.iter().find(|l| l.id == id)?;
+            Some(LanguageShare {
+                id: id.to_string(),
+                name: language.name.to_string(),
+                color: language.color.to_string(),
+                bytes,
+                files,
+                percentage: if total_bytes == 0 {
+                    0.0
+                } else {
+                    (bytes as f64 / total_bytes as f64 * 1000.0).round() / 10.0
+                },
+            })
+        })
+        .collect();
+    languages.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+
+    Ok(LanguageStats {
+        repo_id: repo_id.to_string(),
+        commit_sha: commit.id().to_string(),
+        total_bytes,
+        languages,
+    })
+}
+
+/// The language of a file in the repository, from its name or, failing
+/// that, its shebang line. `None` when neither is recognised.
+#[tauri::command]
+pub(crate) fn detect_language(
+    repo_id: String,
+    file_path: String,
+) -> Result<Option<LanguageInfo>, String> {
+    let path = repo_file_path(&repo_id, &file_path)?;
+    if let Some((language, detected_by)) = by_path(&path) {
+        return Ok(Some(language.info(detected_by)));
+    }
+    let Ok(file) = File::open(&path) else {
+        return Ok(None);
+    };
+    let mut first_line = String::new();
+    // Binary files simply fail to read as a line.
+    if BufReader::new(file).read_line(&mut first_line).is_err() {
+        return Ok(None);
+    }
+    Ok(by_shebang(first_line.trim_end()).map(|language| language.info("shebang")))
+}
+
+/// Per-language byte counts for the repository at HEAD, cached until HEAD
+/// moves.
+#[tauri::command]
+pub(crate) fn get_language_stats(repo_id: String) -> Result<LanguageStats, String> {
+    let repo = open_repository(&repo_id)?;
+    let head = resolve_commit(&repo, "HEAD")?;
+    let path = stats_path(&repo_id);
+    if let Some(cached) = read_json::<LanguageStats>(&path)? {
+        if cached.commit_sha == head.id().to_string() {
+            return Ok(cached);
+        }
+    }
+    let stats = compute_stats(&repo, &repo_id, &head)?;
+    write_json(&path, &stats)?;
+    Ok(stats)
+}
@@ -0,0 +1,163 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::commands::load_global_state;
+use crate::{get_state_dir, GlobalState};
+
+/// One counted operation. Entries are only ever appended, one line per
+/// write, so concurrent writers (the app's command and scheduler threads)
+/// can't lose each other's updates the way read-modify-write counters did.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct LedgerEntry {
+    timestamp: String,
+    /// "ai_operation", "rollback", ...
+    kind: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    entity_id: Option<String>,
+    #[serde(default)]
+    cost_usd: f64,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub(crate) struct SessionSummary {
+    session_start: String,
+    pub(crate) total_operations: usize,
+    pub(crate) total_cost_usd: f64,
+    operations_by_kind: BTreeMap<String, usize>,
+    cost_by_kind: BTreeMap<String, f64>,
+    first_operation_at: Option<String>,
+    last_operation_at: Option<String>,
+}
+
+fn ledger_dir() -> PathBuf {
+    get_state_dir().join("ledger")
+}
+
+/// Ledger file for the session that started at `session_start`.
+fn ledger_path(session_start: &str) -> PathBuf {
+    let key: String = session_start
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    ledger_dir().join(format!("{}.jsonl", key))
+}
+
+/// Summary of a ledger file up to `offset`, so each read only parses the
+/// lines appended since the last one.
+struct Tail {
+    offset: u64,
+    summary: SessionSummary,
+}
+
+static TAILS: Mutex<Option<HashMap<PathBuf, Tail>>> = Mutex::new(None);
+
+impl SessionSummary {
+    fn add(&mut self, entry: LedgerEntry) {
+        self.total_operations += 1;
+        self.total_cost_usd += entry.cost_usd;
+        *self
+            .operations_by_kind
+            .entry(entry.kind.clone())
+            .or_default() += 1;
+        *self.cost_by_kind.entry(entry.kind).or_default() += entry.cost_usd;
+        if self.first_operation_at.is_none() {
+            self.first_operation_at = Some(entry.timestamp.clone());
+        }
+        self.last_operation_at = Some(entry.timestamp);
+    }
+}
+
+/// Append an operation to the current session's ledger.
+pub(crate) fn record(kind: &str, entity_id: Option<&str>, cost_usd: f64) -> Result<(), String> {
+    let global = load_global_state()?;
+    let entry = LedgerEntry {
+        timestamp: Utc::now().to_rfc3339(),
+        kind: kind.to_string(),
+        entity_id: entity_id.map(str::to_string),
+        cost_usd,
+    };
+    let dir = ledger_dir();
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let path = ledger_path(&global.session_start);
+    let line = serde_json::to_string(&entry)
+        .map_err(|e| format!("Failed to serialize ledger entry: {}", e))?;
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(format!("{}\n", line).as_bytes()))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Aggregate the ledger of the session that started at `session_start`.
+pub(crate) fn summarize(session_start: &str) -> Result<SessionSummary, String> {
+    let path = ledger_path(session_start);
+    let fresh = || Tail {
+        offset: 0,
+        summary: SessionSummary {
+            session_start: session_start.to_string(),
+            ..SessionSummary::default()
+        },
+    };
+    let mut file = match fs::File::open(&path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(fresh().summary),
+        Err(e) => return Err(format!("Failed to open {}: {}", path.display(), e)),
+    };
+    let len = file
+        .metadata()
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+        .len();
+
+    let mut tails = TAILS
+        .lock()
+        .map_err(|_| "Ledger cache poisoned".to_string())?;
+    let tail = tails
+        .get_or_insert_with(HashMap::new)
+        .entry(path.clone())
+        .or_insert_with(fresh);
+    if len < tail.offset {
+        // Rewritten (e.g. restored from a backup); start over.
+        *tail = fresh();
+    }
+
+    let mut appended = Vec::new();
+    file.seek(SeekFrom::Start(tail.offset))
+        .and_then(|_| file.read_to_end(&mut appended))
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    // Leave a line still being written for the next read. A line torn by a
+    // crash mid-write is skipped rather than failing the whole session.
+    let complete = appended
+        .iter()
+        .rposition(|&byte| byte == b'\n')
+        .map_or(0, |newline| newline + 1);
+    for line in appended[..complete].split(|&byte| byte == b'\n') {
+        if let Ok(entry) = serde_json::from_slice::<LedgerEntry>(line) {
+            tail.summary.add(entry);
+        }
+    }
+    tail.offset += complete as u64;
+    Ok(tail.summary.clone())
+}
+
+/// Counts and spend for the current session, derived from its ledger.
+#[tauri::command]
+pub(crate) fn get_session_summary() -> Result<SessionSummary, String> {
+    summarize(&load_global_state()?.session_start)
+}
+
+/// Overwrite the stored counters of `global` with the ones derived from its
+/// session's ledger. The fields stay in `global.json` for the CLI's schema
+/// but are no longer incremented in place.
+pub(crate) fn apply_totals(global: &mut GlobalState) -> Result<(), String> {
+    let summary = summarize(&global.session_start)?;
+    global.total_operations = i32::try_from(summary.total_operations).unwrap_or(i32::MAX);
+    global.total_cost_usd = summary.total_cost_usd;
+    Ok(())
+}
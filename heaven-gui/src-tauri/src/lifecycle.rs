@@ -0,0 +1,156 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::commands::load_workpad;
+use crate::WorkpadState;
+
+/// Workpad lifecycle. The string forms match the Python `WorkpadStatus`
+/// values (the enum names are accepted too), plus "archived" for records
+/// moved out of the main list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum WorkpadStatus {
+    #[serde(alias = "ACTIVE")]
+    Active,
+    #[serde(alias = "TESTING")]
+    Testing,
+    #[serde(alias = "PASSED")]
+    Passed,
+    #[serde(alias = "FAILED")]
+    Failed,
+    #[serde(alias = "PROMOTED")]
+    Promoted,
+    #[serde(alias = "DELETED")]
+    Deleted,
+    Archived,
+}
+
+use WorkpadStatus::*;
+
+impl WorkpadStatus {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Active => "active",
+            Testing => "testing",
+            Passed => "passed",
+            Failed => "failed",
+            Promoted => "promoted",
+            Deleted => "deleted",
+            Archived => "archived",
+        }
+    }
+
+    /// Statuses reachable from `self` in one step.
+    pub(crate) fn next_states(self) -> &'static [WorkpadStatus] {
+        match self {
+            Active => &[Testing, Passed, Failed, Deleted, Archived],
+            Testing => &[Passed, Failed, Active, Deleted],
+            Passed => &[Testing, Active, Promoted, Deleted, Archived],
+            Failed => &[Testing, Active, Deleted, Archived],
+            Promoted => &[Archived],
+            Deleted => &[],
+            // Restoring returns a workpad to the status it was archived from.
+            Archived => &[Active, Passed, Failed, Promoted],
+        }
+    }
+
+    pub(crate) fn can_transition_to(self, next: WorkpadStatus) -> bool {
+        self == next || self.next_states().contains(&next)
+    }
+}
+
+impl fmt::Display for WorkpadStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum LifecycleError {
+    IllegalTransition {
+        workpad_id: String,
+        from: WorkpadStatus,
+        to: WorkpadStatus,
+    },
+    NoPassingRun {
+        workpad_id: String,
+    },
+}
+
+impl fmt::Display for LifecycleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LifecycleError::IllegalTransition {
+                workpad_id,
+                from,
+                to,
+            } => write!(
+                f,
+                "Workpad {} cannot move from {} to {}",
+                workpad_id, from, to
+            ),
+            LifecycleError::NoPassingRun { workpad_id } => write!(
+                f,
+                "Workpad {} needs a passing test run before it can be promoted",
+                workpad_id
+            ),
+        }
+    }
+}
+
+impl From<LifecycleError> for String {
+    fn from(error: LifecycleError) -> String {
+        error.to_string()
+    }
+}
+
+pub(crate) fn ensure_transition(
+    workpad: &WorkpadState,
+    to: WorkpadStatus,
+) -> Result<(), LifecycleError> {
+    let from = workpad.status;
+    if from.can_transition_to(to) {
+        Ok(())
+    } else {
+        Err(LifecycleError::IllegalTransition {
+            workpad_id: workpad.workpad_id.clone(),
+            from,
+            to,
+        })
+    }
+}
+
+/// Move `workpad` to `to`, rejecting transitions outside the table.
+pub(crate) fn transition(
+    workpad: &mut WorkpadState,
+    to: WorkpadStatus,
+) -> Result<(), LifecycleError> {
+    ensure_transition(workpad, to)?;
+    workpad.status = to;
+    Ok(())
+}
+
+/// Promotion needs the workpad in "passed"; both test runners set it there
+/// when a run passes.
+pub(crate) fn ensure_promotable(workpad: &WorkpadState) -> Result<(), String> {
+    match workpad.status {
+        Passed => Ok(()),
+        Active | Testing | Failed => Err(LifecycleError::NoPassingRun {
+            workpad_id: workpad.workpad_id.clone(),
+        }
+        .into()),
+        from => Err(LifecycleError::IllegalTransition {
+            workpad_id: workpad.workpad_id.clone(),
+            from,
+            to: Promoted,
+        }
+        .into()),
+    }
+}
+
+#[tauri::command]
+pub(crate) fn get_workpad_transitions(workpad_id: String) -> Result<Vec<WorkpadStatus>, String> {
+    let workpad = load_workpad(&workpad_id)?;
+    Ok(workpad.status.next_states().to_vec())
+}
@@ -0,0 +1,178 @@
+use std::fmt::Display;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use serde::Serialize;
+use serde_json::Value;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{fmt, reload, EnvFilter, Registry};
+
+use crate::profiles::sologit_root;
+use crate::{get_settings, write_settings};
+
+const LOG_PREFIX: &str = "heaven-gui";
+const MAX_LOG_FILES: usize = 7;
+pub(crate) const LEVELS: &[&str] = &["trace", "debug", "info", "warn", "error"];
+
+static FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct LogEntry {
+    timestamp: String,
+    level: String,
+    target: String,
+    message: String,
+    fields: Value,
+}
+
+pub(crate) fn get_logs_dir() -> PathBuf {
+    sologit_root().join("logs")
+}
+
+fn filter_for(level: &str) -> EnvFilter {
+    // Keep chatty dependencies at warn unless explicitly asked for.
+    EnvFilter::try_new(format!("warn,heaven_gui={}", level))
+        .unwrap_or_else(|_| EnvFilter::new("warn,heaven_gui=info"))
+}
+
+/// Install the global subscriber: JSON lines in daily-rotated files under
+/// `~/.sologit/logs` plus human-readable stderr. Keep the guard alive for
+/// the life of the app so buffered lines are flushed on exit.
+pub(crate) fn init() -> Option<WorkerGuard> {
+    let level = get_settings()
+        .map(|settings| settings.telemetry.log_level)
+        .unwrap_or_else(|_| "info".to_string());
+    let (filter, handle) = reload::Layer::new(filter_for(&level));
+
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_PREFIX)
+        .filename_suffix("log")
+        .max_log_files(MAX_LOG_FILES)
+        .build(get_logs_dir());
+    let (file_layer, guard) = match appender {
+        Ok(appender) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            (Some(fmt::layer().json().with_writer(writer)), Some(guard))
+        }
+        Err(e) => {
+            eprintln!("Failed to open log directory: {}", e);
+            (None, None)
+        }
+    };
+
+    let installed = tracing_subscriber::registry()
+        .with(filter)
+        .with(file_layer)
+        .with(fmt::layer().with_writer(std::io::stderr))
+        .try_init();
+    if installed.is_ok() {
+        FILTER.get_or_init(|| handle);
+    }
+    guard
+}
+
+/// Log a failure from a best-effort operation instead of dropping it.
+pub(crate) fn warn_on_err<T, E: Display>(context: &str, result: Result<T, E>) {
+    if let Err(error) = result {
+        tracing::warn!("{}: {}", context, error);
+    }
+}
+
+fn level_rank(level: &str) -> usize {
+    LEVELS
+        .iter()
+        .position(|candidate| candidate.eq_ignore_ascii_case(level))
+        .unwrap_or(0)
+}
+
+fn parse_line(line: &str) -> Option<LogEntry> {
+    let mut value: Value = serde_json::from_str(line).ok()?;
+    let mut fields = value.get_mut("fields").map(Value::take).unwrap_or_default();
+    let message = fields
+        .as_object_mut()
+        .and_then(|fields| fields.remove("message"))
+        .and_then(|message| message.as_str().map(str::to_string))
+        .unwrap_or_default();
+    let text = |key: &str| value[key].as_str().unwrap_or_default().to_string();
+
+    Some(LogEntry {
+        timestamp: text("timestamp"),
+        level: text("level").to_lowercase(),
+        target: text("target"),
+        message,
+        fields,
+    })
+}
+
+/// Most recent log lines at or above `level`, newest first.
+#[tauri::command]
+pub(crate) fn get_recent_logs(
+    level: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<LogEntry>, String> {
+    let min_rank = level_rank(level.as_deref().unwrap_or("info"));
+    let limit = limit.unwrap_or(200);
+
+    let dir = get_logs_dir();
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut files: Vec<PathBuf> = fs::read_dir(&dir)
+        .map_err(|e| format!("Failed to read logs: {}", e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(LOG_PREFIX))
+        })
+        .collect();
+    files.sort();
+
+    let mut entries = Vec::new();
+    for file in files.iter().rev() {
+        let contents = fs::read_to_string(file)
+            .map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+        for entry in contents.lines().rev().filter_map(parse_line) {
+            if level_rank(&entry.level) >= min_rank {
+                entries.push(entry);
+                if entries.len() >= limit {
+                    return Ok(entries);
+                }
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// Switch the live filter without touching settings.
+pub(crate) fn apply_level(level: &str) -> Result<(), String> {
+    if let Some(handle) = FILTER.get() {
+        handle
+            .reload(filter_for(level))
+            .map_err(|e| format!("Failed to apply log level: {}", e))?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) fn set_log_level(level: String) -> Result<String, String> {
+    let level = level.trim().to_lowercase();
+    if !LEVELS.contains(&level.as_str()) {
+        return Err(format!(
+            "Unknown log level {}; expected one of {}",
+            level,
+            LEVELS.join(", ")
+        ));
+    }
+
+    apply_level(&level)?;
+    let mut settings = get_settings()?;
+    settings.telemetry.log_level = level.clone();
+    write_settings(&settings)?;
+    tracing::info!("log level set to {}", level);
+    Ok(level)
+}
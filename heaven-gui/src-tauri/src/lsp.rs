@@ -0,0 +1,355 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::Manager;
+use uuid::Uuid;
+
+use crate::commands::{load_workpad, resolve_repo_path, workpad_checkout_dir};
+use crate::get_settings;
+use crate::logging::warn_on_err;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct ServerCommand {
+    pub(crate) command: String,
+    #[serde(default)]
+    pub(crate) args: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub(crate) struct LspSettings {
+    pub(crate) enabled: bool,
+    /// Server per editor language id.
+    pub(crate) servers: HashMap<String, ServerCommand>,
+    /// Per-repository overrides keyed by repo_id, then language id.
+    pub(crate) repos: HashMap<String, HashMap<String, ServerCommand>>,
+    /// How many times a crashed server is restarted before giving up.
+    pub(crate) max_restarts: u32,
+}
+
+fn server(command: &str, args: &[&str]) -> ServerCommand {
+    ServerCommand {
+        command: command.to_string(),
+        args: args.iter().map(|arg| arg.to_string()).collect(),
+    }
+}
+
+impl Default for LspSettings {
+    fn default() -> Self {
+        let typescript = server("typescript-language-server", &["--stdio"]);
+        LspSettings {
+            enabled: false,
+            servers: HashMap::from([
+                ("rust".to_string(), server("rust-analyzer", &[])),
+                (
+                    "python".to_string(),
+                    server("pyright-langserver", &["--stdio"]),
+                ),
+                ("typescript".to_string(), typescript.clone()),
+                ("typescriptreact".to_string(), typescript.clone()),
+                ("javascript".to_string(), typescript.clone()),
+                ("javascriptreact".to_string(), typescript),
+                ("go".to_string(), server("gopls", &[])),
+            ]),
+            repos: HashMap::new(),
+            max_restarts: 3,
+        }
+    }
+}
+
+impl LspSettings {
+    fn command_for(&self, repo_id: &str, language: &str) -> Option<&ServerCommand> {
+        self.repos
+            .get(repo_id)
+            .and_then(|servers| servers.get(language))
+            .or_else(|| self.servers.get(language))
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct LanguageServerInfo {
+    server_id: String,
+    repo_id: String,
+    workpad_id: Option<String>,
+    language: String,
+    command: ServerCommand,
+    /// Workspace root the server was started in.
+    root: String,
+    pid: u32,
+    /// "running", "restarted", "crashed" or "stopped"
+    status: String,
+    restarts: u32,
+    started_at: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct LspMessage {
+    server_id: String,
+    message: Value,
+}
+
+struct Server {
+    info: LanguageServerInfo,
+    child: Child,
+    stdin: ChildStdin,
+}
+
+fn servers() -> &'static Mutex<HashMap<String, Server>> {
+    static SERVERS: OnceLock<Mutex<HashMap<String, Server>>> = OnceLock::new();
+    SERVERS.get_or_init(Mutex::default)
+}
+
+fn lock_servers() -> Result<std::sync::MutexGuard<'static, HashMap<String, Server>>, String> {
+    servers()
+        .lock()
+        .map_err(|_| "Language server table poisoned".to_string())
+}
+
+/// Read one `Content-Length` framed JSON-RPC message. `None` at end of
+/// stream.
+fn read_message(reader: &mut impl BufRead) -> Result<Option<Value>, String> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).map_err(|e| e.to_string())? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let length = length.ok_or("Message without Content-Length")?;
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).map_err(|e| e.to_string())?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|e| format!("Invalid JSON-RPC message: {}", e))
+}
+
+fn write_message(stdin: &mut ChildStdin, message: &Value) -> Result<(), String> {
+    let body = serde_json::to_vec(message).map_err(|e| e.to_string())?;
+    write!(stdin, "Content-Length: {}\r\n\r\n", body.len())
+        .and_then(|_| stdin.write_all(&body))
+        .and_then(|_| stdin.flush())
+        .map_err(|e| format!("Failed to write to language server: {}", e))
+}
+
+fn spawn(command: &ServerCommand, root: &Path) -> Result<(Child, ChildStdin, ChildStdout), String> {
+    let mut child = Command::new(&command.command)
+        .args(&command.args)
+        .current_dir(root)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to start {}: {}", command.command, e))?;
+    let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+        warn_on_err("Failed to kill language server", child.kill());
+        return Err(format!("{} has no stdio pipes", command.command));
+    };
+    if let Some(stderr) = child.stderr.take() {
+        let name = command.command.clone();
+        thread::spawn(move || {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                tracing::debug!("{}: {}", name, line);
+            }
+        });
+    }
+    Ok((child, stdin, stdout))
+}
+
+fn emit_changed(app: &tauri::AppHandle, info: &LanguageServerInfo) {
+    warn_on_err(
+        "Failed to emit lsp-server-changed",
+        app.emit_all("lsp-server-changed", info),
+    );
+}
+
+/// Forward the server's messages to the webview until its stdout closes,
+/// then restart it unless it was stopped or has crashed too often. The
+/// webview must send `initialize` again after a "restarted" event.
+fn supervise(app: tauri::AppHandle, server_id: String, stdout: ChildStdout) {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(stdout);
+        loop {
+            match read_message(&mut reader) {
+                Ok(Some(message)) => warn_on_err(
+                    "Failed to emit lsp-message",
+                    app.emit_all(
+                        "lsp-message",
+                        LspMessage {
+                            server_id: server_id.clone(),
+                            message,
+                        },
+                    ),
+                ),
+                Ok(None) => break,
+                Err(e) => {
+                    tracing::warn!("Language server {}: {}", server_id, e);
+                    break;
+                }
+            }
+        }
+
+        // Stopped servers are removed before they're killed.
+        let Some(mut server) = servers()
+            .lock()
+            .ok()
+            .and_then(|mut servers| servers.remove(&server_id))
+        else {
+            return;
+        };
+        // After a framing error the process may still be running.
+        server.child.kill().ok();
+        let status = server.child.wait();
+        let max_restarts = get_settings()
+            .map(|s| s.editor.lsp)
+            .unwrap_or_default()
+            .max_restarts;
+        tracing::warn!("Language server {} exited: {:?}", server_id, status);
+        if server.info.restarts >= max_restarts {
+            server.info.status = "crashed".to_string();
+            emit_changed(&app, &server.info);
+            return;
+        }
+        match spawn(&server.info.command, Path::new(&server.info.root)) {
+            Ok((child, stdin, stdout)) => {
+                server.info.pid = child.id();
+                server.info.restarts += 1;
+                server.info.status = "restarted".to_string();
+                server.info.started_at = Utc::now().to_rfc3339();
+                emit_changed(&app, &server.info);
+                server.child = child;
+                server.stdin = stdin;
+                if let Ok(mut servers) = servers().lock() {
+                    servers.insert(server_id.clone(), server);
+                }
+                supervise(app, server_id, stdout);
+            }
+            Err(e) => {
+                tracing::warn!("Failed to restart language server {}: {}", server_id, e);
+                server.info.status = "crashed".to_string();
+                emit_changed(&app, &server.info);
+            }
+        }
+    });
+}
+
+fn workspace_root(repo_id: &str, workpad_id: Option<&str>) -> Result<PathBuf, String> {
+    match workpad_id {
+        Some(workpad_id) => {
+            let workpad = load_workpad(workpad_id)?;
+            if workpad.repo_id != repo_id {
+                return Err(format!(
+                    "Workpad {} doesn't belong to {}",
+                    workpad_id, repo_id
+                ));
+            }
+            workpad_checkout_dir(&workpad)
+        }
+        None => resolve_repo_path(repo_id),
+    }
+}
+
+/// Start the configured language server for `language` in the repository,
+/// or in a workpad's checkout. A server already running for the same root
+/// and language is reused. Messages from it arrive as "lsp-message" events.
+#[tauri::command]
+pub(crate) fn start_language_server(
+    app: tauri::AppHandle,
+    repo_id: String,
+    language: String,
+    workpad_id: Option<String>,
+) -> Result<LanguageServerInfo, String> {
+    let settings = get_settings()?.editor.lsp;
+    if !settings.enabled {
+        return Err("Language servers are disabled in settings".to_string());
+    }
+    let command = settings
+        .command_for(&repo_id, &language)
+        .cloned()
+        .ok_or_else(|| format!("No language server configured for {}", language))?;
+    let root = workspace_root(&repo_id, workpad_id.as_deref())?;
+    let root = root.display().to_string();
+
+    let mut servers = lock_servers()?;
+    if let Some(running) = servers
+        .values()
+        .find(|server| server.info.root == root && server.info.language == language)
+    {
+        return Ok(running.info.clone());
+    }
+    let (child, stdin, stdout) = spawn(&command, Path::new(&root))?;
+    let info = LanguageServerInfo {
+        server_id: format!("lsp-{}", Uuid::new_v4().simple()),
+        repo_id,
+        workpad_id,
+        language,
+        command,
+        root,
+        pid: child.id(),
+        status: "running".to_string(),
+        restarts: 0,
+        started_at: Utc::now().to_rfc3339(),
+    };
+    servers.insert(
+        info.server_id.clone(),
+        Server {
+            info: info.clone(),
+            child,
+            stdin,
+        },
+    );
+    drop(servers);
+    supervise(app, info.server_id.clone(), stdout);
+    Ok(info)
+}
+
+/// Send a JSON-RPC request or notification from the editor to a server.
+#[tauri::command]
+pub(crate) fn send_lsp_message(server_id: String, message: Value) -> Result<(), String> {
+    let mut servers = lock_servers()?;
+    let server = servers
+        .get_mut(&server_id)
+        .ok_or_else(|| format!("Language server not running: {}", server_id))?;
+    write_message(&mut server.stdin, &message)
+}
+
+#[tauri::command]
+pub(crate) fn stop_language_server(app: tauri::AppHandle, server_id: String) -> Result<(), String> {
+    let mut server = lock_servers()?
+        .remove(&server_id)
+        .ok_or_else(|| format!("Language server not running: {}", server_id))?;
+    // Servers that honour `exit` get a chance to clean up; the rest are
+    // killed below either way.
+    let exit = serde_json::json!({ "jsonrpc": "2.0", "method": "exit" });
+    write_message(&mut server.stdin, &exit).ok();
+    server.child.kill().ok();
+    warn_on_err("Failed to reap language server", server.child.wait());
+    server.info.status = "stopped".to_string();
+    emit_changed(&app, &server.info);
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) fn list_language_servers() -> Result<Vec<LanguageServerInfo>, String> {
+    let mut servers: Vec<LanguageServerInfo> = lock_servers()?
+        .values()
+        .map(|server| server.info.clone())
+        .collect();
+    servers.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+    Ok(servers)
+}
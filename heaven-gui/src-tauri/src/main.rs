@@ -2,11 +2,111 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::fs;
 use std::path::PathBuf;
-use std::process::Command;
 
+use audit::audited;
+
+mod ai;
+mod ai_client;
+mod ai_patch;
+mod ai_queue;
+mod api_server;
+mod archive;
+mod audit;
+mod backup;
+mod batch;
+mod bisect;
+mod blame;
+mod branches;
+mod bridge;
+mod bulk;
+mod chat;
+mod checkpoints;
+mod ci;
+mod cli;
+mod cli_config;
 mod commands;
+mod commit_graph;
+mod commit_message;
+mod compare;
+mod conflicts;
+mod context;
+mod cost;
+mod coverage;
+mod dashboard;
+mod dependency_audit;
+mod dev_processes;
+mod diff;
+mod diff_summary;
+mod docker;
+mod doctor;
+mod explain;
+mod failure_analysis;
+mod files;
+mod flaky;
+mod git;
+mod github;
+mod history;
+mod hooks;
+mod http;
+#[cfg(test)]
+mod integration_tests;
+mod keybindings;
+mod languages;
+mod ledger;
+mod lifecycle;
+mod logging;
+mod lsp;
+mod maintenance;
+mod metrics;
+mod migrations;
+mod mock_ai;
+mod notifications;
+mod ollama;
+mod packs;
+mod patch_validation;
+mod patches;
+mod paths;
+mod privacy;
+mod profiles;
+mod promotion;
+mod recent;
+mod repair;
+mod repo_stats;
+mod repo_status;
+mod reverts;
+mod review;
+mod sandbox;
+mod secrets;
+mod sessions;
+mod settings;
+mod signing;
+mod snapshots;
+mod staging;
+mod state_cache;
+mod statuses;
+mod storage;
+mod symbols;
+mod tags;
+mod templates;
+mod terminal;
+#[cfg(test)]
+mod test_harness;
+mod testing;
+mod text;
+mod timestamps;
+mod tokens;
+mod tools;
+mod transfer;
+mod unified_diff;
+mod walk;
+mod watcher;
+mod webhooks;
+mod workpad_templates;
+mod workspaces;
+mod worktrees;
 
 // ============================================================================
 // Data Structures (matching Python state schema)
@@ -34,6 +134,8 @@ pub(crate) struct RepositoryState {
     updated_at: String,
     workpads: Vec<String>,
     total_commits: i32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    hooks: Vec<hooks::HookStep>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -41,7 +143,7 @@ pub(crate) struct WorkpadState {
     workpad_id: String,
     repo_id: String,
     title: String,
-    status: String,
+    status: lifecycle::WorkpadStatus,
     branch_name: String,
     base_commit: String,
     current_commit: Option<String>,
@@ -52,6 +154,128 @@ pub(crate) struct WorkpadState {
     ai_operations: Vec<String>,
     patches_applied: i32,
     files_changed: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pull_request: Option<github::PullRequestInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    template_id: Option<String>,
+    /// Target suggested by the template the workpad was created from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    test_target: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    checklist: Vec<workpad_templates::ChecklistItem>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    /// One of `WORKPAD_PRIORITIES`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    priority: Option<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    description: String,
+    /// Latest AI description of the workpad's changes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    diff_summary: Option<diff_summary::DiffSummary>,
+    /// Status the workpad had when it was archived, restored with it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    archived_from: Option<lifecycle::WorkpadStatus>,
+}
+
+pub(crate) const WORKPAD_PRIORITIES: &[&str] = &["low", "normal", "high", "urgent"];
+
+fn priority_rank(priority: Option<&str>) -> usize {
+    let priority = priority.unwrap_or("normal");
+    WORKPAD_PRIORITIES
+        .iter()
+        .position(|p| *p == priority)
+        .unwrap_or(1)
+}
+
+/// Optional narrowing and ordering for `list_workpads`.
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(default)]
+pub(crate) struct WorkpadFilter {
+    status: Option<lifecycle::WorkpadStatus>,
+    tag: Option<String>,
+    /// Case-insensitive match against title, description, branch and tags.
+    query: Option<String>,
+    /// Bounds on `created_at`, inclusive.
+    created_after: Option<String>,
+    created_before: Option<String>,
+    /// "created" (default), "updated", "priority" or "title".
+    sort_by: Option<String>,
+    /// Defaults to descending for everything but "title".
+    descending: Option<bool>,
+}
+
+impl WorkpadFilter {
+    fn matches(&self, workpad: &WorkpadState) -> bool {
+        if self.status.is_some_and(|s| s != workpad.status) {
+            return false;
+        }
+        if let Some(tag) = &self.tag {
+            if !workpad.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                return false;
+            }
+        }
+        if let Some(query) = self.query.as_ref().map(|q| q.trim().to_lowercase()) {
+            let haystack = format!(
+                "{} {} {} {}",
+                workpad.title,
+                workpad.description,
+                workpad.branch_name,
+                workpad.tags.join(" ")
+            )
+            .to_lowercase();
+            if !query.is_empty() && !haystack.contains(&query) {
+                return false;
+            }
+        }
+        let created = timestamps::parse(&workpad.created_at);
+        let outside = |bound: &Option<String>, before: bool| {
+            let bound = bound.as_deref().and_then(timestamps::parse);
+            match (bound, created) {
+                (Some(bound), Some(created)) => {
+                    if before {
+                        created > bound
+                    } else {
+                        created < bound
+                    }
+                }
+                (Some(_), None) => true,
+                (None, _) => false,
+            }
+        };
+        !outside(&self.created_after, false) && !outside(&self.created_before, true)
+    }
+
+    fn sort(&self, workpads: &mut [WorkpadState]) {
+        let sort_by = self.sort_by.as_deref().unwrap_or("created");
+        match sort_by {
+            "updated" => workpads.sort_by(|a, b| a.updated_at.cmp(&b.updated_at)),
+            "priority" => workpads.sort_by(|a, b| {
+                priority_rank(a.priority.as_deref())
+                    .cmp(&priority_rank(b.priority.as_deref()))
+                    .then_with(|| a.created_at.cmp(&b.created_at))
+            }),
+            "title" => workpads.sort_by_key(|w| w.title.to_lowercase()),
+            _ => workpads.sort_by(|a, b| a.created_at.cmp(&b.created_at)),
+        }
+        if self.descending.unwrap_or(sort_by != "title") {
+            workpads.reverse();
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct TestCaseResult {
+    test_id: String,
+    name: String,
+    status: statuses::TestRunStatus,
+    duration_ms: i32,
+    #[serde(default)]
+    output: String,
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    timestamp: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -59,7 +283,7 @@ pub(crate) struct TestRun {
     run_id: String,
     workpad_id: Option<String>,
     target: String,
-    status: String,
+    status: statuses::TestRunStatus,
     started_at: String,
     completed_at: Option<String>,
     total_tests: i32,
@@ -67,6 +291,14 @@ pub(crate) struct TestRun {
     failed: i32,
     skipped: i32,
     duration_ms: i32,
+    /// Digest of the container image the run executed in (docker backend only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    image_digest: Option<String>,
+    /// Commit the checkout was at when the run started.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    commit_sha: Option<String>,
+    #[serde(default)]
+    tests: Vec<TestCaseResult>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -74,7 +306,7 @@ pub(crate) struct AIOperation {
     operation_id: String,
     workpad_id: Option<String>,
     operation_type: String,
-    status: String,
+    status: statuses::AIOperationStatus,
     model: String,
     prompt: String,
     response: Option<String>,
@@ -83,6 +315,22 @@ pub(crate) struct AIOperation {
     started_at: String,
     completed_at: Option<String>,
     error: Option<String>,
+    /// Patch produced by accepting this operation's proposal.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    patch_id: Option<String>,
+    /// Throttling and retries incurred while talking to the provider.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    retry: Option<ai_client::RetryMetadata>,
+    /// "summary" or "hash" when the prompt and response were redacted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    redaction: Option<String>,
+    /// What was sent to the provider, kept on failed and cancelled
+    /// operations so they can be retried.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    messages: Vec<ai::ChatMessage>,
+    /// The failed operation this one retried.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    retry_of: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -97,6 +345,14 @@ pub(crate) struct CommitNode {
     test_status: Option<String>,
     ci_status: Option<String>,
     is_trunk: bool,
+    /// Filled in by `commit_graph::correlate`: the promotion that landed the
+    /// commit, the run that validated it and the AI spend of its workpad.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    promotion_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    test_run_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cost_usd: Option<f64>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -104,16 +360,65 @@ pub(crate) struct FileNode {
     name: String,
     path: String,
     is_directory: bool,
+    /// `None` for files and for directories beyond the requested depth.
     children: Option<Vec<FileNode>>,
+    #[serde(default)]
+    has_children: bool,
+    /// Visible entries in a directory, known even when it is collapsed.
+    #[serde(default)]
+    child_count: Option<usize>,
+    #[serde(flatten)]
+    meta: files::FileMeta,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct Settings {
-    theme: String,
-    font_size: i32,
-    auto_save: bool,
-    show_line_numbers: bool,
-    enable_ai: bool,
+    #[serde(default = "settings::default_schema_version")]
+    schema_version: u32,
+    #[serde(default)]
+    editor: settings::EditorSettings,
+    #[serde(default)]
+    ai: ai::AiSettings,
+    #[serde(default)]
+    cost: cost::CostSettings,
+    #[serde(default)]
+    tests: settings::TestSettings,
+    #[serde(default)]
+    git: settings::GitSettings,
+    #[serde(default)]
+    workpads: settings::WorkpadSettings,
+    #[serde(default)]
+    integrations: settings::IntegrationSettings,
+    #[serde(default)]
+    maintenance: maintenance::MaintenanceSettings,
+    #[serde(default)]
+    notifications: notifications::NotificationSettings,
+    #[serde(default)]
+    telemetry: settings::TelemetrySettings,
+    #[serde(default)]
+    keybindings: keybindings::KeybindingSettings,
+    #[serde(default)]
+    tools: tools::ToolSettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            schema_version: settings::SETTINGS_SCHEMA_VERSION,
+            editor: settings::EditorSettings::default(),
+            ai: ai::AiSettings::default(),
+            cost: cost::CostSettings::default(),
+            tests: settings::TestSettings::default(),
+            git: settings::GitSettings::default(),
+            workpads: settings::WorkpadSettings::default(),
+            integrations: settings::IntegrationSettings::default(),
+            maintenance: maintenance::MaintenanceSettings::default(),
+            notifications: notifications::NotificationSettings::default(),
+            telemetry: settings::TelemetrySettings::default(),
+            keybindings: keybindings::KeybindingSettings::default(),
+            tools: tools::ToolSettings::default(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -121,7 +426,7 @@ pub(crate) struct PromotionRecord {
     record_id: String,
     repo_id: String,
     workpad_id: String,
-    decision: String,
+    decision: statuses::PromotionDecision,
     can_promote: bool,
     auto_promote_requested: bool,
     promoted: bool,
@@ -138,13 +443,11 @@ pub(crate) struct PromotionRecord {
 // ============================================================================
 
 pub(crate) fn get_state_dir() -> PathBuf {
-    let home = dirs::home_dir().expect("Could not find home directory");
-    home.join(".sologit").join("state")
+    paths::current().state_dir()
 }
 
 pub(crate) fn get_repos_dir() -> PathBuf {
-    let home = dirs::home_dir().expect("Could not find home directory");
-    home.join(".sologit").join("data").join("repos")
+    paths::current().repos_dir()
 }
 
 // ============================================================================
@@ -153,51 +456,14 @@ pub(crate) fn get_repos_dir() -> PathBuf {
 
 #[tauri::command]
 fn read_global_state() -> Result<GlobalState, String> {
-    let state_path = get_state_dir().join("global.json");
-
-    if !state_path.exists() {
-        // Return default state if file doesn't exist
-        return Ok(GlobalState {
-            version: "0.4.0".to_string(),
-            last_updated: chrono::Utc::now().to_rfc3339(),
-            active_repo: None,
-            active_workpad: None,
-            session_start: chrono::Utc::now().to_rfc3339(),
-            total_operations: 0,
-            total_cost_usd: 0.0,
-        });
-    }
-
-    let contents = fs::read_to_string(state_path)
-        .map_err(|e| format!("Failed to read global state: {}", e))?;
-
-    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse global state: {}", e))
+    commands::load_global_state()
 }
 
 #[tauri::command]
-fn list_repositories() -> Result<Vec<RepositoryState>, String> {
-    let repos_dir = get_state_dir().join("repositories");
-
-    if !repos_dir.exists() {
-        return Ok(Vec::new());
-    }
-
-    let mut repos = Vec::new();
-
-    for entry in fs::read_dir(repos_dir).map_err(|e| e.to_string())? {
-        let entry = entry.map_err(|e| e.to_string())?;
-        let path = entry.path();
-
-        if path.extension().and_then(|s| s.to_str()) == Some("json") {
-            let contents = fs::read_to_string(&path)
-                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
-
-            let repo: RepositoryState = serde_json::from_str(&contents)
-                .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
-
-            repos.push(repo);
-        }
-    }
+fn list_repositories(force_refresh: Option<bool>) -> Result<Vec<RepositoryState>, String> {
+    let mut repos = state_cache::state_cache()
+        .records::<RepositoryState>("repositories", force_refresh.unwrap_or(false))?
+        .to_vec();
 
     // Sort by created_at descending
     repos.sort_by(|a, b| b.created_at.cmp(&a.created_at));
@@ -206,16 +472,14 @@ fn list_repositories() -> Result<Vec<RepositoryState>, String> {
 
 #[tauri::command]
 fn verify_cli_install() -> Result<String, String> {
-    let output = Command::new("evogitctl")
-        .arg("--version")
-        .output()
-        .map_err(|e| format!("Failed to execute evogitctl: {}", e))?;
-
-    if output.status.success() {
-        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
-    } else {
-        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
-    }
+    let output = cli::run_cli(
+        vec!["--version".to_string()],
+        cli::CliOptions {
+            timeout: Some(std::time::Duration::from_secs(15)),
+            ..cli::CliOptions::default()
+        },
+    )?;
+    Ok(output.stdout.trim().to_string())
 }
 
 #[tauri::command]
@@ -235,35 +499,22 @@ fn read_repository(repo_id: String) -> Result<RepositoryState, String> {
 }
 
 #[tauri::command]
-fn list_workpads(repo_id: Option<String>) -> Result<Vec<WorkpadState>, String> {
-    let workpads_dir = get_state_dir().join("workpads");
-
-    if !workpads_dir.exists() {
-        return Ok(Vec::new());
-    }
-
-    let mut workpads = Vec::new();
-
-    for entry in fs::read_dir(workpads_dir).map_err(|e| e.to_string())? {
-        let entry = entry.map_err(|e| e.to_string())?;
-        let path = entry.path();
-
-        if path.extension().and_then(|s| s.to_str()) == Some("json") {
-            let contents = fs::read_to_string(&path)
-                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
-
-            let workpad: WorkpadState = serde_json::from_str(&contents)
-                .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
-
-            // Filter by repo_id if provided
-            if repo_id.is_none() || repo_id.as_ref() == Some(&workpad.repo_id) {
-                workpads.push(workpad);
-            }
-        }
-    }
-
-    // Sort by created_at descending
-    workpads.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+fn list_workpads(
+    repo_id: Option<String>,
+    filter: Option<WorkpadFilter>,
+    force_refresh: Option<bool>,
+) -> Result<Vec<WorkpadState>, String> {
+    let filter = filter.unwrap_or_default();
+    let mut workpads: Vec<WorkpadState> = state_cache::state_cache()
+        .records::<WorkpadState>("workpads", force_refresh.unwrap_or(false))?
+        .iter()
+        // Filter by repo_id if provided
+        .filter(|workpad| repo_id.is_none() || repo_id.as_ref() == Some(&workpad.repo_id))
+        .filter(|workpad| filter.matches(workpad))
+        .cloned()
+        .collect();
+
+    filter.sort(&mut workpads);
     Ok(workpads)
 }
 
@@ -303,36 +554,23 @@ fn list_commits(repo_id: String, limit: Option<i32>) -> Result<Vec<CommitNode>,
         serde_json::from_value(data["commits"].clone()).unwrap_or_default();
 
     let limit = limit.unwrap_or(100) as usize;
-    Ok(commits.into_iter().take(limit).collect())
+    let mut commits: Vec<CommitNode> = commits.into_iter().take(limit).collect();
+    commit_graph::correlate(&repo_id, &mut commits)?;
+    Ok(commits)
 }
 
 #[tauri::command]
-fn list_test_runs(workpad_id: Option<String>) -> Result<Vec<TestRun>, String> {
-    let tests_dir = get_state_dir().join("test_runs");
-
-    if !tests_dir.exists() {
-        return Ok(Vec::new());
-    }
-
-    let mut test_runs = Vec::new();
-
-    for entry in fs::read_dir(tests_dir).map_err(|e| e.to_string())? {
-        let entry = entry.map_err(|e| e.to_string())?;
-        let path = entry.path();
-
-        if path.extension().and_then(|s| s.to_str()) == Some("json") {
-            let contents = fs::read_to_string(&path)
-                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
-
-            let test_run: TestRun = serde_json::from_str(&contents)
-                .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
-
-            // Filter by workpad_id if provided
-            if workpad_id.is_none() || test_run.workpad_id.as_ref() == workpad_id.as_ref() {
-                test_runs.push(test_run);
-            }
-        }
-    }
+fn list_test_runs(
+    workpad_id: Option<String>,
+    force_refresh: Option<bool>,
+) -> Result<Vec<TestRun>, String> {
+    let mut test_runs: Vec<TestRun> = state_cache::state_cache()
+        .records::<TestRun>("test_runs", force_refresh.unwrap_or(false))?
+        .iter()
+        // Filter by workpad_id if provided
+        .filter(|run| workpad_id.is_none() || run.workpad_id.as_ref() == workpad_id.as_ref())
+        .cloned()
+        .collect();
 
     // Sort by started_at descending
     test_runs.sort_by(|a, b| b.started_at.cmp(&a.started_at));
@@ -356,32 +594,28 @@ fn read_test_run(run_id: String) -> Result<TestRun, String> {
 }
 
 #[tauri::command]
-fn list_ai_operations(workpad_id: Option<String>) -> Result<Vec<AIOperation>, String> {
-    let ai_ops_dir = get_state_dir().join("ai_operations");
-
-    if !ai_ops_dir.exists() {
-        return Ok(Vec::new());
-    }
-
-    let mut operations = Vec::new();
-
-    for entry in fs::read_dir(ai_ops_dir).map_err(|e| e.to_string())? {
-        let entry = entry.map_err(|e| e.to_string())?;
-        let path = entry.path();
-
-        if path.extension().and_then(|s| s.to_str()) == Some("json") {
-            let contents = fs::read_to_string(&path)
-                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
-
-            let operation: AIOperation = serde_json::from_str(&contents)
-                .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
-
-            // Filter by workpad_id if provided
-            if workpad_id.is_none() || operation.workpad_id.as_ref() == workpad_id.as_ref() {
-                operations.push(operation);
-            }
-        }
-    }
+fn list_ai_operations(
+    workpad_id: Option<String>,
+    status: Option<statuses::AIOperationStatus>,
+    force_refresh: Option<bool>,
+) -> Result<Vec<AIOperation>, String> {
+    let loose = state_cache::state_cache()
+        .records::<AIOperation>("ai_operations", force_refresh.unwrap_or(false))?
+        .to_vec();
+    // Operations maintenance packed away still count, e.g. towards cost
+    // totals; a loose copy left by an interrupted pack wins.
+    let unpacked: HashSet<String> = loose.iter().map(|op| op.operation_id.clone()).collect();
+    let packed = packs::all_packed::<AIOperation>("ai_operations")?
+        .into_iter()
+        .filter(|op| !unpacked.contains(&op.operation_id));
+    let mut operations: Vec<AIOperation> = loose
+        .into_iter()
+        .chain(packed)
+        // Filter by workpad_id if provided
+        .filter(|op| workpad_id.is_none() || op.workpad_id.as_ref() == workpad_id.as_ref())
+        // e.g. "failed" to find operations worth retrying
+        .filter(|op| status.is_none() || status == Some(op.status))
+        .collect();
 
     // Sort by started_at descending
     operations.sort_by(|a, b| b.started_at.cmp(&a.started_at));
@@ -390,18 +624,8 @@ fn list_ai_operations(workpad_id: Option<String>) -> Result<Vec<AIOperation>, St
 
 #[tauri::command]
 fn read_ai_operation(operation_id: String) -> Result<AIOperation, String> {
-    let operation_path = get_state_dir()
-        .join("ai_operations")
-        .join(format!("{}.json", operation_id));
-
-    if !operation_path.exists() {
-        return Err(format!("AI operation not found: {}", operation_id));
-    }
-
-    let contents = fs::read_to_string(operation_path)
-        .map_err(|e| format!("Failed to read AI operation: {}", e))?;
-
-    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse AI operation: {}", e))
+    // Falls back to the monthly packs for operations archived by maintenance.
+    ai::load_operation(&operation_id)
 }
 
 // ============================================================================
@@ -419,85 +643,104 @@ fn read_file(repo_id: String, file_path: String) -> Result<String, String> {
     fs::read_to_string(full_path).map_err(|e| format!("Failed to read file: {}", e))
 }
 
+/// Files in a repository, relative to its root. `include`/`exclude` are
+/// glob patterns (e.g. `src/**/*.rs`) applied to those relative paths.
 #[tauri::command]
-fn list_repository_files(repo_id: String) -> Result<Vec<String>, String> {
+fn list_repository_files(
+    repo_id: String,
+    include: Option<Vec<String>>,
+    exclude: Option<Vec<String>>,
+) -> Result<Vec<String>, String> {
     let repo_dir = get_repos_dir().join(&repo_id);
 
     if !repo_dir.exists() {
         return Err(format!("Repository directory not found: {}", repo_id));
     }
 
-    fn collect_files(dir: &std::path::Path, base: &std::path::Path) -> Result<Vec<String>, String> {
-        let mut files = Vec::new();
-
-        for entry in fs::read_dir(dir).map_err(|e| e.to_string())? {
-            let entry = entry.map_err(|e| e.to_string())?;
-            let path = entry.path();
-
-            // Skip .git directory
-            if path.file_name().and_then(|n| n.to_str()) == Some(".git") {
-                continue;
+    let include = files::build_globset(include)?;
+    let exclude = files::build_globset(exclude)?;
+    let mut listed = walk::walk_parallel(
+        &walk::walk_builder(&repo_dir, files::respect_gitignore(), true),
+        |entry| {
+            if !entry.file_type().is_some_and(|kind| kind.is_file()) {
+                return None;
             }
-
-            if path.is_file() {
-                let rel_path = path
-                    .strip_prefix(base)
-                    .map_err(|e| e.to_string())?
-                    .to_string_lossy()
-                    .to_string();
-                files.push(rel_path);
-            } else if path.is_dir() {
-                files.extend(collect_files(&path, base)?);
-            }
-        }
-
-        Ok(files)
-    }
-
-    let mut files = collect_files(&repo_dir, &repo_dir)?;
-    files.sort();
-    Ok(files)
+            let rel_path = entry
+                .path()
+                .strip_prefix(&repo_dir)
+                .ok()?
+                .to_string_lossy()
+                .to_string();
+            let included = include.as_ref().map_or(true, |set| set.is_match(&rel_path));
+            let excluded = exclude.as_ref().is_some_and(|set| set.is_match(&rel_path));
+            (included && !excluded).then_some(rel_path)
+        },
+    )?;
+
+    listed.sort_unstable();
+    Ok(listed)
 }
 
+const DEFAULT_TREE_DEPTH: usize = 3;
+
+/// Directory tree down to `max_depth` levels (default 3). Deeper directories
+/// come back collapsed with `has_children`/`child_count` set; expand them
+/// with `get_directory_contents`.
 #[tauri::command]
-fn get_file_tree(repo_id: String) -> Result<Vec<FileNode>, String> {
+fn get_file_tree(
+    app: tauri::AppHandle,
+    cache: tauri::State<'_, files::DirectoryCache>,
+    status: tauri::State<'_, repo_status::RepoStatusRegistry>,
+    repo_id: String,
+    max_depth: Option<usize>,
+) -> Result<Vec<FileNode>, String> {
     let repo_dir = get_repos_dir().join(&repo_id);
 
     if !repo_dir.exists() {
         return Err(format!("Repository directory not found: {}", repo_id));
     }
 
-    fn build_tree(dir: &std::path::Path, base: &std::path::Path) -> Result<Vec<FileNode>, String> {
+    fn build_tree(
+        arena: &walk::DirArena,
+        metas: &mut [Option<files::FileMeta>],
+        dir: usize,
+        base: &std::path::Path,
+        depth: usize,
+    ) -> Vec<FileNode> {
         let mut nodes = Vec::new();
 
-        for entry in fs::read_dir(dir).map_err(|e| e.to_string())? {
-            let entry = entry.map_err(|e| e.to_string())?;
-            let path = entry.path();
-
-            // Skip .git directory and hidden files
-            let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-            if file_name == ".git" || file_name.starts_with('.') {
-                continue;
-            }
-
-            let rel_path = path
+        for &id in &arena.entries[dir].children {
+            let entry = &arena.entries[id];
+            let file_name = entry
+                .path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("");
+            let rel_path = entry
+                .path
                 .strip_prefix(base)
-                .map_err(|e| e.to_string())?
+                .unwrap_or(&entry.path)
                 .to_string_lossy()
                 .to_string();
 
-            let is_dir = path.is_dir();
-            let children = if is_dir {
-                Some(build_tree(&path, base)?)
+            let (children, child_count) = if !entry.is_dir {
+                (None, None)
+            } else if depth > 1 {
+                let children = build_tree(arena, metas, id, base, depth - 1);
+                let count = children.len();
+                (Some(children), Some(count))
             } else {
-                None
+                (None, Some(entry.children.len()))
             };
 
             nodes.push(FileNode {
                 name: file_name.to_string(),
+                meta: metas[id].take().unwrap_or_default(),
                 path: rel_path,
-                is_directory: is_dir,
+                is_directory: entry.is_dir,
                 children,
+                has_children: child_count.unwrap_or(0) > 0,
+                child_count,
             });
         }
 
@@ -508,38 +751,65 @@ fn get_file_tree(repo_id: String) -> Result<Vec<FileNode>, String> {
             _ => a.name.cmp(&b.name),
         });
 
-        Ok(nodes)
+        nodes
     }
 
-    build_tree(&repo_dir, &repo_dir)
+    // Keep the listing cache fresh as files change.
+    logging::warn_on_err(
+        "Failed to watch repository",
+        repo_status::ensure_watch(&app, &status, &repo_id),
+    );
+    let depth = max_depth.unwrap_or(DEFAULT_TREE_DEPTH).max(1);
+    let gitignore = files::respect_gitignore();
+    // One level deeper than shown, so collapsed directories know their size.
+    let arena = walk::DirArena::walk(&repo_dir, gitignore, depth + 1)?;
+    cache.seed(&arena, gitignore, depth);
+    let mut metas = arena.metas(&repo_dir, &files::git_status_map(&repo_id), depth);
+    Ok(build_tree(&arena, &mut metas, 0, &repo_dir, depth))
 }
 
 #[tauri::command]
-fn get_directory_contents(repo_id: String, dir_path: String) -> Result<Vec<FileNode>, String> {
+fn get_directory_contents(
+    cache: tauri::State<'_, files::DirectoryCache>,
+    repo_id: String,
+    dir_path: String,
+) -> Result<Vec<FileNode>, String> {
     let full_path = get_repos_dir().join(&repo_id).join(&dir_path);
 
     if !full_path.exists() || !full_path.is_dir() {
         return Err(format!("Directory not found: {}", dir_path));
     }
 
+    let gitignore = files::respect_gitignore();
+    let statuses = files::git_status_map(&repo_id);
     let mut nodes = Vec::new();
 
-    for entry in fs::read_dir(&full_path).map_err(|e| e.to_string())? {
-        let entry = entry.map_err(|e| e.to_string())?;
-        let path = entry.path();
-
+    for (path, is_dir) in cache.list(&full_path, gitignore)? {
         let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-        if file_name.starts_with('.') {
-            continue;
-        }
+        let child_count = if is_dir {
+            Some(cache.list(&path, gitignore)?.len())
+        } else {
+            None
+        };
 
-        let is_dir = path.is_dir();
+        let rel_path = format!("{}/{}", dir_path.trim_matches('/'), file_name)
+            .trim_start_matches('/')
+            .to_string();
 
         nodes.push(FileNode {
             name: file_name.to_string(),
+            meta: files::FileMeta::for_path(
+                &get_repos_dir().join(&repo_id),
+                &path,
+                &rel_path,
+                is_dir,
+                &statuses,
+            ),
             path: format!("{}/{}", dir_path, file_name),
             is_directory: is_dir,
             children: None,
+            has_children: child_count.unwrap_or(0) > 0,
+            child_count,
         });
     }
 
@@ -554,33 +824,15 @@ fn get_directory_contents(repo_id: String, dir_path: String) -> Result<Vec<FileN
 }
 
 pub(crate) fn get_settings_path() -> PathBuf {
-    let home = dirs::home_dir().expect("Could not find home directory");
-    home.join(".sologit").join("gui_settings.json")
+    profiles::active_home().join("gui_settings.json")
 }
 
-#[tauri::command]
 fn get_settings() -> Result<Settings, String> {
-    let settings_path = get_settings_path();
-
-    if !settings_path.exists() {
-        // Return default settings
-        return Ok(Settings {
-            theme: "dark".to_string(),
-            font_size: 14,
-            auto_save: true,
-            show_line_numbers: true,
-            enable_ai: true,
-        });
-    }
-
-    let contents =
-        fs::read_to_string(settings_path).map_err(|e| format!("Failed to read settings: {}", e))?;
-
-    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse settings: {}", e))
+    settings::load_settings()
 }
 
-#[tauri::command]
-fn save_settings(settings: Settings) -> Result<(), String> {
+pub(crate) fn write_settings(settings: &Settings) -> Result<(), String> {
+    settings::validate(settings)?;
     let settings_path = get_settings_path();
 
     // Create directory if it doesn't exist
@@ -589,12 +841,21 @@ fn save_settings(settings: Settings) -> Result<(), String> {
             .map_err(|e| format!("Failed to create settings directory: {}", e))?;
     }
 
-    let contents = serde_json::to_string_pretty(&settings)
+    let contents = serde_json::to_string_pretty(settings)
         .map_err(|e| format!("Failed to serialize settings: {}", e))?;
 
     fs::write(settings_path, contents).map_err(|e| format!("Failed to write settings: {}", e))
 }
 
+#[tauri::command]
+fn save_settings(mut settings: Settings) -> Result<(), String> {
+    audited("save_settings", "settings", None, None, move || {
+        let saved = get_settings()?.integrations.webhooks;
+        webhooks::restore_secrets(&mut settings.integrations.webhooks, &saved);
+        write_settings(&settings)
+    })
+}
+
 #[tauri::command]
 fn ai_chat(
     repo_id: String,
@@ -602,16 +863,18 @@ fn ai_chat(
     prompt: String,
     model: String,
 ) -> Result<serde_json::Value, String> {
-    // This is a stub that returns a simulated response
-    // In production, this would call the actual Solo Git AI orchestrator
-    // For now, we'll return a placeholder response
+    commands::load_repository(&repo_id)?;
+
+    let messages = [ai::ChatMessage::user(prompt.clone())];
+    let requested = Some(model.as_str()).filter(|m| !m.trim().is_empty());
+    let (operation, _) = ai::run_operation(workpad_id, "chat", &prompt, &messages, requested)?;
 
     Ok(serde_json::json!({
-        "content": "AI integration is being implemented. This feature will connect to the Solo Git AI orchestrator to provide planning, code generation, and debugging assistance.",
-        "model": model,
-        "cost_usd": 0.0,
-        "tokens_used": 0,
-        "error": "AI chat functionality requires integration with the Solo Git backend. Please use the CLI commands for AI features until GUI integration is complete."
+        "content": operation.response.clone().unwrap_or_default(),
+        "model": operation.model,
+        "cost_usd": operation.cost_usd,
+        "tokens_used": operation.tokens_used,
+        "error": operation.error,
     }))
 }
 
@@ -620,7 +883,30 @@ fn ai_chat(
 // ============================================================================
 
 fn main() {
+    let _log_guard = logging::init();
+    tracing::info!("heaven-gui starting");
+
     tauri::Builder::default()
+        .manage(testing::TestWatchRegistry::default())
+        .manage(blame::BlameCache::default())
+        .manage(repo_status::RepoStatusRegistry::default())
+        .manage(files::DirectoryCache::default())
+        .manage(ai_queue::AiQueue::default())
+        .setup(|app| {
+            // Refuse to start rather than misread state from a newer build.
+            migrations::migrate_state()?;
+            logging::warn_on_err("State validation failed", repair::validate_state());
+            ci::start_ci_poller(app.handle());
+            archive::start_archive_policy();
+            maintenance::start_maintenance_scheduler();
+            promotion::start_promotion_queue(app.handle());
+            state_cache::start_state_watcher();
+            settings::start_settings_watcher(app.handle());
+            api_server::start();
+            notifications::init(&app.handle());
+            ai_queue::init(&app.handle());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             // State management
             read_global_state,
@@ -634,27 +920,199 @@ fn main() {
             list_ai_operations,
             read_ai_operation,
             verify_cli_install,
+            batch::batch,
+            privacy::redact_ai_history,
+            metrics::get_metrics,
+            ledger::get_session_summary,
+            sessions::start_session,
+            sessions::end_session,
+            sessions::list_sessions,
+            signing::verify_commit_signature,
+            branches::list_branches,
+            branches::create_branch,
+            branches::delete_branch,
+            branches::set_trunk_branch,
+            tags::list_tags,
+            tags::create_tag,
+            tags::generate_changelog,
+            bridge::get_bridge_status,
+            bridge::restart_bridge,
+            bridge::bridge_request,
+            api_server::get_api_server_status,
+            webhooks::list_webhooks,
+            webhooks::register_webhook,
+            webhooks::delete_webhook,
+            webhooks::test_webhook,
+            maintenance::get_maintenance_report,
+            maintenance::run_maintenance,
+            storage::get_storage_usage,
+            cli::run_cli_passthrough,
+            doctor::run_doctor,
+            repair::repair_state,
+            backup::export_state_backup,
+            backup::import_state_backup,
+            transfer::export_workpad,
+            transfer::import_workpad,
+            profiles::list_profiles,
+            profiles::switch_profile,
+            recent::record_recent,
+            recent::list_recent,
+            recent::quick_open,
+            repo_status::get_repo_status,
+            repo_status::stop_repo_status_watch,
+            dashboard::get_dashboard,
             // File operations
             read_file,
             list_repository_files,
             get_file_tree,
             get_directory_contents,
+            files::stat_path,
+            files::write_file,
+            files::set_file_executable,
             // Settings
-            get_settings,
+            settings::get_settings,
             save_settings,
             // AI operations
             ai_chat,
+            chat::create_chat_session,
+            chat::get_chat_session,
+            chat::append_chat_message,
+            chat::list_chat_sessions,
+            context::preview_ai_context,
+            cost::get_cost_report,
+            cost::set_ai_budget,
+            tokens::estimate_cost,
+            ollama::list_ollama_models,
+            ollama::pull_ollama_model,
+            templates::list_prompt_templates,
+            templates::save_prompt_template,
+            templates::delete_prompt_template,
+            ai_patch::get_ai_patch_proposal,
+            ai_patch::accept_ai_patch,
+            commit_message::suggest_commit_message,
+            failure_analysis::analyze_test_failure,
+            failure_analysis::get_test_failure_analysis,
+            audit::query_audit_log,
+            logging::get_recent_logs,
+            logging::set_log_level,
+            keybindings::get_keybindings,
+            keybindings::set_keybinding,
+            keybindings::set_keybinding_preset,
             // Write operations
             commands::create_repository,
             commands::delete_repository,
             commands::create_workpad,
+            commands::update_workpad_metadata,
+            lifecycle::get_workpad_transitions,
+            archive::archive_workpad,
+            archive::restore_archived_workpad,
+            archive::list_archived_workpads,
+            bulk::bulk_delete_workpads,
+            bulk::bulk_archive_workpads,
+            bulk::bulk_run_tests,
+            workspaces::list_workspaces,
+            workspaces::create_workspace,
+            workspaces::add_repo_to_workspace,
+            workspaces::remove_repo_from_workspace,
+            workspaces::delete_workspace,
+            workspaces::get_workspace_summary,
+            workpad_templates::list_workpad_templates,
+            workpad_templates::save_workpad_template,
+            workpad_templates::delete_workpad_template,
+            workpad_templates::create_workpad_from_template,
+            workpad_templates::set_checklist_item,
             commands::apply_patch,
             commands::run_tests,
             commands::promote_workpad,
             commands::delete_workpad,
             commands::rollback_workpad,
+            checkpoints::list_checkpoints,
+            checkpoints::rollback_to_checkpoint,
+            patches::list_patches,
+            patches::get_patch,
+            patches::revert_patch,
+            patches::cherry_pick,
+            patch_validation::validate_patch,
+            reverts::revert_commit,
+            bisect::start_bisect,
+            bisect::get_bisect,
+            worktrees::list_worktrees,
+            worktrees::prune_worktrees,
+            promotion::preview_promotion,
+            promotion::queue_promotion,
+            promotion::list_promotion_queue,
+            promotion::cancel_queued_promotion,
+            promotion::list_promotions,
+            promotion::get_promotion,
+            cli_config::get_effective_config,
+            staging::get_working_diff,
+            staging::stage_hunk,
+            staging::unstage_hunk,
+            languages::detect_language,
+            languages::get_language_stats,
+            symbols::get_file_symbols,
+            lsp::start_language_server,
+            lsp::send_lsp_message,
+            lsp::stop_language_server,
+            lsp::list_language_servers,
+            secrets::scan_workpad,
+            dependency_audit::audit_dependencies,
+            dependency_audit::get_dependency_audit,
+            tools::run_formatter,
+            tools::run_linter,
+            hooks::get_repo_hooks,
+            hooks::set_repo_hooks,
+            hooks::list_hook_runs,
+            hooks::get_hook_run,
+            testing::get_test_run_log,
+            terminal::open_terminal,
+            terminal::write_terminal,
+            terminal::resize_terminal,
+            terminal::close_terminal,
+            terminal::list_terminals,
+            dev_processes::start_dev_process,
+            dev_processes::stop_dev_process,
+            dev_processes::list_dev_processes,
+            dev_processes::get_dev_process_logs,
+            dev_processes::clear_dev_processes,
+            repo_stats::get_repo_stats,
+            ai_queue::get_ai_queue,
+            ai_queue::cancel_ai_operation,
+            ai::retry_ai_operation,
+            diff_summary::summarize_diff,
+            review::request_ai_review,
+            review::list_reviews,
+            review::get_review,
+            explain::explain_selection,
+            conflicts::get_conflicts,
+            conflicts::resolve_conflict,
+            conflicts::abort_conflicts,
+            compare::compare_workpads,
             commands::trigger_ai_operation,
             commands::update_config,
+            // Testing
+            testing::detect_test_targets,
+            testing::run_test_target,
+            testing::start_test_watch,
+            testing::stop_test_watch,
+            testing::list_test_watches,
+            flaky::get_flaky_tests,
+            coverage::get_file_coverage,
+            // CI
+            ci::get_ci_status,
+            github::create_pull_request,
+            github::refresh_pull_request,
+            // Git history
+            blame::get_file_blame,
+            history::get_file_history,
+            diff::diff_refs,
+            // Snapshots
+            snapshots::snapshot_workpad,
+            snapshots::list_snapshots,
+            snapshots::restore_snapshot,
+            sandbox::list_sandbox_backends,
+            sandbox::get_sandbox_config,
+            sandbox::set_sandbox_config,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,356 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::audit::{as_actor, audited};
+use crate::commands::{read_json, write_json};
+use crate::dashboard::is_open;
+use crate::logging::{get_logs_dir, warn_on_err};
+use crate::packs;
+use crate::state_cache::state_cache;
+use crate::storage::invalidate_storage_usage;
+use crate::{get_settings, get_state_dir, list_workpads};
+
+/// How often the scheduler wakes to see whether a run is due.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const TASKS: &[&str] = &[
+    "patches",
+    "test_runs",
+    "ai_operations",
+    "packs",
+    "commit_cache",
+    "logs",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub(crate) struct MaintenanceSettings {
+    pub(crate) enabled: bool,
+    pub(crate) interval_hours: u32,
+    /// Retention in days for each category; `None` keeps everything.
+    pub(crate) patch_retention_days: Option<u32>,
+    pub(crate) test_run_retention_days: Option<u32>,
+    pub(crate) ai_operation_retention_days: Option<u32>,
+    pub(crate) log_retention_days: Option<u32>,
+    /// Compress AI operations older than this into monthly packs.
+    pub(crate) pack_ai_operations_after_days: Option<u32>,
+    /// Commits kept per repository in the commit cache.
+    pub(crate) commit_cache_limit: Option<usize>,
+}
+
+impl Default for MaintenanceSettings {
+    fn default() -> Self {
+        MaintenanceSettings {
+            enabled: true,
+            interval_hours: 24,
+            patch_retention_days: Some(90),
+            test_run_retention_days: Some(90),
+            ai_operation_retention_days: Some(180),
+            pack_ai_operations_after_days: Some(30),
+            log_retention_days: Some(14),
+            commit_cache_limit: Some(1000),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub(crate) struct TaskReport {
+    task: String,
+    files_removed: usize,
+    bytes_reclaimed: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct MaintenanceReport {
+    ran_at: String,
+    duration_ms: u64,
+    bytes_reclaimed: u64,
+    tasks: Vec<TaskReport>,
+}
+
+fn report_path() -> PathBuf {
+    get_state_dir().join("maintenance.json")
+}
+
+fn cutoff(days: u32) -> SystemTime {
+    SystemTime::now() - Duration::from_secs(u64::from(days) * 24 * 60 * 60)
+}
+
+fn remove(path: &Path, task: &mut TaskReport) {
+    let size = fs::metadata(path).map(|meta| meta.len()).unwrap_or(0);
+    match fs::remove_file(path) {
+        Ok(()) => {
+            state_cache().invalidate_path(path);
+            task.files_removed += 1;
+            task.bytes_reclaimed += size;
+        }
+        Err(e) => tracing::warn!("Failed to remove {}: {}", path.display(), e),
+    }
+}
+
+fn modified_before(path: &Path, before: SystemTime) -> bool {
+    fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .is_ok_and(|modified| modified < before)
+}
+
+/// Delete `<id>.json` records in `dir` older than the cutoff, with any
+/// sibling files sharing the stem (e.g. a patch's `.diff`). Records still
+/// attached to an open workpad are kept regardless of age.
+fn prune_records(
+    task: &str,
+    dir: &Path,
+    days: Option<u32>,
+    open_workpads: &HashSet<String>,
+) -> TaskReport {
+    let mut report = TaskReport {
+        task: task.to_string(),
+        ..TaskReport::default()
+    };
+    let (Some(days), Ok(entries)) = (days, fs::read_dir(dir)) else {
+        return report;
+    };
+    let before = cutoff(days);
+
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.extension().and_then(|s| s.to_str()) != Some("json")
+            || !modified_before(&path, before)
+        {
+            continue;
+        }
+        let record: Option<Value> = read_json(&path).ok().flatten();
+        let workpad_id = record
+            .as_ref()
+            .and_then(|record| record["workpad_id"].as_str());
+        if workpad_id.is_some_and(|id| open_workpads.contains(id)) {
+            continue;
+        }
+        if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+            for extension in ["diff", "log"] {
+                let sibling = dir.join(format!("{}.{}", stem, extension));
+                if sibling.exists() {
+                    remove(&sibling, &mut report);
+                }
+            }
+        }
+        remove(&path, &mut report);
+    }
+    report
+}
+
+fn rotate_logs(days: Option<u32>) -> TaskReport {
+    let mut report = TaskReport {
+        task: "logs".to_string(),
+        ..TaskReport::default()
+    };
+    let (Some(days), Ok(entries)) = (days, fs::read_dir(get_logs_dir())) else {
+        return report;
+    };
+    let before = cutoff(days);
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.is_file() && modified_before(&path, before) {
+            remove(&path, &mut report);
+        }
+    }
+    report
+}
+
+fn pack_ai_operations(days: Option<u32>) -> TaskReport {
+    let mut report = TaskReport {
+        task: "packs".to_string(),
+        ..TaskReport::default()
+    };
+    let Some(days) = days else {
+        return report;
+    };
+    match packs::pack_records("ai_operations", "operation_id", days) {
+        Ok(packed) => {
+            report.files_removed = packed.records_packed;
+            report.bytes_reclaimed = packed.bytes_before.saturating_sub(packed.bytes_after);
+        }
+        Err(e) => report.error = Some(e),
+    }
+    report
+}
+
+/// Trim each repository's commit cache to `limit` entries and drop caches for
+/// repositories that no longer exist.
+fn compact_commit_cache(limit: Option<usize>) -> TaskReport {
+    let mut report = TaskReport {
+        task: "commit_cache".to_string(),
+        ..TaskReport::default()
+    };
+    let Ok(entries) = fs::read_dir(get_state_dir().join("commits")) else {
+        return report;
+    };
+    let repositories = get_state_dir().join("repositories");
+
+    for path in entries.flatten().map(|entry| entry.path()) {
+        let Some(repo_id) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        if path.extension().and_then(|s| s.to_str()) != Some("json") {
+            continue;
+        }
+        if !repositories.join(format!("{}.json", repo_id)).exists() {
+            remove(&path, &mut report);
+            continue;
+        }
+        let Some(limit) = limit else {
+            continue;
+        };
+        let mut data: Value = match read_json(&path) {
+            Ok(Some(data)) => data,
+            _ => continue,
+        };
+        let Some(commits) = data["commits"].as_array_mut() else {
+            continue;
+        };
+        if commits.len() <= limit {
+            continue;
+        }
+        commits.truncate(limit);
+        let size = |path: &Path| fs::metadata(path).map(|meta| meta.len()).unwrap_or(0);
+        let before = size(&path);
+        match write_json(&path, &data) {
+            Ok(()) => report.bytes_reclaimed += before.saturating_sub(size(&path)),
+            Err(e) => report.error = Some(e),
+        }
+    }
+    report
+}
+
+/// Run the maintenance tasks, or only those named in `only`.
+fn run(only: Option<&[String]>) -> Result<MaintenanceReport, String> {
+    let started = Instant::now();
+    let ran_at = Utc::now().to_rfc3339();
+    let settings = get_settings()?.maintenance;
+    let open_workpads: HashSet<String> = list_workpads(None, None, None)?
+        .into_iter()
+        .filter(is_open)
+        .map(|workpad| workpad.workpad_id)
+        .collect();
+
+    let state_dir = get_state_dir();
+    let wanted = |task: &str| only.map_or(true, |only| only.iter().any(|t| t == task));
+    let mut tasks = Vec::new();
+    if wanted("patches") {
+        tasks.push(prune_records(
+            "patches",
+            &state_dir.join("patches"),
+            settings.patch_retention_days,
+            &open_workpads,
+        ));
+    }
+    if wanted("test_runs") {
+        tasks.push(prune_records(
+            "test_runs",
+            &state_dir.join("test_runs"),
+            settings.test_run_retention_days,
+            &open_workpads,
+        ));
+    }
+    if wanted("ai_operations") {
+        tasks.push(prune_records(
+            "ai_operations",
+            &state_dir.join("ai_operations"),
+            settings.ai_operation_retention_days,
+            &open_workpads,
+        ));
+    }
+    if wanted("packs") {
+        tasks.push(pack_ai_operations(settings.pack_ai_operations_after_days));
+    }
+    if wanted("commit_cache") {
+        tasks.push(compact_commit_cache(settings.commit_cache_limit));
+    }
+    if wanted("logs") {
+        tasks.push(rotate_logs(settings.log_retention_days));
+    }
+
+    let report = MaintenanceReport {
+        ran_at,
+        duration_ms: started.elapsed().as_millis() as u64,
+        bytes_reclaimed: tasks.iter().map(|task| task.bytes_reclaimed).sum(),
+        tasks,
+    };
+    // Targeted runs don't reset the schedule for the full one.
+    if only.is_none() {
+        write_json(&report_path(), &report)?;
+    }
+    invalidate_storage_usage();
+    tracing::info!(
+        "Maintenance reclaimed {} bytes in {}ms",
+        report.bytes_reclaimed,
+        report.duration_ms
+    );
+    Ok(report)
+}
+
+fn is_due(interval_hours: u32) -> bool {
+    let last = read_json::<MaintenanceReport>(&report_path())
+        .ok()
+        .flatten()
+        .and_then(|report| DateTime::parse_from_rfc3339(&report.ran_at).ok());
+    match last {
+        Some(last) => {
+            Utc::now() - last.with_timezone(&Utc)
+                >= chrono::Duration::hours(i64::from(interval_hours))
+        }
+        None => true,
+    }
+}
+
+/// Run maintenance whenever the configured interval has elapsed since the
+/// last recorded run, including runs from previous sessions.
+pub(crate) fn start_maintenance_scheduler() {
+    thread::spawn(|| {
+        as_actor("scheduler", || loop {
+            match get_settings() {
+                Ok(settings) if settings.maintenance.enabled => {
+                    if is_due(settings.maintenance.interval_hours) {
+                        warn_on_err("Scheduled maintenance failed", run(None));
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Skipping maintenance: {}", e),
+            }
+            thread::sleep(CHECK_INTERVAL);
+        })
+    });
+}
+
+/// The most recent maintenance run, if any has happened yet.
+#[tauri::command]
+pub(crate) fn get_maintenance_report() -> Result<Option<MaintenanceReport>, String> {
+    read_json(&report_path())
+}
+
+/// Run maintenance now. `tasks` limits it to some of "patches", "test_runs",
+/// "ai_operations", "commit_cache" and "logs", e.g. to clean up whichever
+/// category `get_storage_usage` shows has grown.
+#[tauri::command]
+pub(crate) fn run_maintenance(tasks: Option<Vec<String>>) -> Result<MaintenanceReport, String> {
+    if let Some(unknown) = tasks
+        .iter()
+        .flatten()
+        .find(|task| !TASKS.contains(&task.as_str()))
+    {
+        return Err(format!(
+            "Unknown maintenance task {} (expected one of {})",
+            unknown,
+            TASKS.join(", ")
+        ));
+    }
+    audited("run_maintenance", "maintenance", None, None, move || {
+        run(tasks.as_deref())
+    })
+}
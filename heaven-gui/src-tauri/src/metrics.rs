@@ -0,0 +1,313 @@
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::statuses::AIOperationStatus;
+use crate::{get_settings, get_state_dir, AIOperation, TestRun};
+
+/// Daily files older than this are removed when a new day starts.
+const RETENTION_DAYS: i64 = 90;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Sample {
+    Command {
+        name: String,
+        duration_ms: u64,
+        ok: bool,
+    },
+    TestRun {
+        target: String,
+        status: String,
+        duration_ms: u64,
+    },
+    AiOperation {
+        model: String,
+        cost_usd: f64,
+        tokens: i64,
+        ok: bool,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct MetricPoint {
+    timestamp: DateTime<Utc>,
+    #[serde(flatten)]
+    sample: Sample,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub(crate) struct CommandStats {
+    name: String,
+    count: usize,
+    errors: usize,
+    avg_ms: f64,
+    p95_ms: u64,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub(crate) struct TestStats {
+    runs: usize,
+    failed: usize,
+    avg_duration_ms: f64,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub(crate) struct AiStats {
+    operations: usize,
+    failed: usize,
+    cost_usd: f64,
+    tokens: i64,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub(crate) struct MetricsBucket {
+    start: String,
+    commands: usize,
+    errors: usize,
+    test_runs: usize,
+    cost_usd: f64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct MetricsSummary {
+    enabled: bool,
+    range: String,
+    since: String,
+    commands: Vec<CommandStats>,
+    tests: TestStats,
+    ai: AiStats,
+    /// Failed commands, test runs and AI operations over all of them.
+    error_rate: f64,
+    series: Vec<MetricsBucket>,
+}
+
+fn metrics_dir() -> PathBuf {
+    get_state_dir().join("metrics")
+}
+
+fn day_path(day: NaiveDate) -> PathBuf {
+    metrics_dir().join(format!("{}.jsonl", day.format("%Y-%m-%d")))
+}
+
+fn enabled() -> bool {
+    get_settings()
+        .map(|settings| settings.telemetry.metrics_enabled)
+        .unwrap_or(false)
+}
+
+fn prune_old_days(today: NaiveDate) {
+    let Ok(entries) = fs::read_dir(metrics_dir()) else {
+        return;
+    };
+    let oldest = today - Duration::days(RETENTION_DAYS);
+    for path in entries.flatten().map(|entry| entry.path()) {
+        let day = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| NaiveDate::parse_from_str(stem, "%Y-%m-%d").ok());
+        if day.is_some_and(|day| day < oldest) {
+            if let Err(e) = fs::remove_file(&path) {
+                tracing::warn!("Failed to remove {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+/// Append a sample to today's file. Does nothing unless metrics are enabled;
+/// failures are logged, never surfaced to the command being measured.
+fn record(sample: Sample) {
+    if !enabled() {
+        return;
+    }
+    let point = MetricPoint {
+        timestamp: Utc::now(),
+        sample,
+    };
+    let today = point.timestamp.date_naive();
+    let path = day_path(today);
+    let new_day = !path.exists();
+
+    let written = fs::create_dir_all(metrics_dir())
+        .and_then(|_| OpenOptions::new().create(true).append(true).open(&path))
+        .and_then(|mut file| {
+            let line = serde_json::to_string(&point).map_err(std::io::Error::other)?;
+            writeln!(file, "{}", line)
+        });
+    match written {
+        Ok(()) if new_day => prune_old_days(today),
+        Ok(()) => {}
+        Err(e) => tracing::warn!("Failed to record metric: {}", e),
+    }
+}
+
+pub(crate) fn record_command(name: &str, duration: std::time::Duration, ok: bool) {
+    record(Sample::Command {
+        name: name.to_string(),
+        duration_ms: duration.as_millis() as u64,
+        ok,
+    });
+}
+
+pub(crate) fn record_test_run(run: &TestRun) {
+    record(Sample::TestRun {
+        target: run.target.clone(),
+        status: run.status.to_string(),
+        duration_ms: u64::try_from(run.duration_ms).unwrap_or(0),
+    });
+}
+
+pub(crate) fn record_ai_operation(operation: &AIOperation) {
+    record(Sample::AiOperation {
+        model: operation.model.clone(),
+        cost_usd: operation.cost_usd,
+        tokens: i64::from(operation.tokens_used),
+        ok: operation.status != AIOperationStatus::Failed,
+    });
+}
+
+fn load_points(since: DateTime<Utc>) -> Vec<MetricPoint> {
+    let mut points = Vec::new();
+    let mut day = since.date_naive();
+    let today = Utc::now().date_naive();
+    while day <= today {
+        if let Ok(file) = fs::File::open(day_path(day)) {
+            points.extend(
+                BufReader::new(file)
+                    .lines()
+                    .map_while(Result::ok)
+                    .filter_map(|line| serde_json::from_str::<MetricPoint>(&line).ok())
+                    .filter(|point| point.timestamp >= since),
+            );
+        }
+        day += Duration::days(1);
+    }
+    points
+}
+
+fn percentile(sorted: &[u64], pct: f64) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = ((sorted.len() as f64) * pct).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Aggregate local metrics for the performance panel over "hour", "day"
+/// (default), "week" or "month". Nothing here ever leaves the machine.
+#[tauri::command]
+pub(crate) fn get_metrics(range: Option<String>) -> Result<MetricsSummary, String> {
+    let range = range.unwrap_or_else(|| "day".to_string());
+    let (span, bucket) = match range.as_str() {
+        "hour" => (Duration::hours(1), Duration::minutes(5)),
+        "day" => (Duration::days(1), Duration::hours(1)),
+        "week" => (Duration::weeks(1), Duration::hours(6)),
+        "month" => (Duration::days(30), Duration::days(1)),
+        other => {
+            return Err(format!(
+                "Unknown range {} (expected hour, day, week or month)",
+                other
+            ))
+        }
+    };
+    let since = Utc::now() - span;
+    let points = load_points(since);
+
+    let mut commands: BTreeMap<String, (CommandStats, Vec<u64>)> = BTreeMap::new();
+    let mut tests = TestStats::default();
+    let mut ai = AiStats::default();
+    let mut test_duration_total = 0u64;
+    let (mut total, mut failures) = (0usize, 0usize);
+    let bucket_count = (span.num_seconds() / bucket.num_seconds()) as usize;
+    let mut series: Vec<MetricsBucket> = (0..bucket_count)
+        .map(|i| MetricsBucket {
+            start: (since + bucket * i as i32).to_rfc3339(),
+            ..MetricsBucket::default()
+        })
+        .collect();
+
+    for point in &points {
+        let slot = ((point.timestamp - since).num_seconds() / bucket.num_seconds()) as usize;
+        let slot = &mut series[slot.min(bucket_count - 1)];
+        total += 1;
+        match &point.sample {
+            Sample::Command {
+                name,
+                duration_ms,
+                ok,
+            } => {
+                let (stats, durations) = commands.entry(name.clone()).or_default();
+                stats.count += 1;
+                durations.push(*duration_ms);
+                slot.commands += 1;
+                if !ok {
+                    stats.errors += 1;
+                    slot.errors += 1;
+                    failures += 1;
+                }
+            }
+            Sample::TestRun {
+                status,
+                duration_ms,
+                ..
+            } => {
+                tests.runs += 1;
+                test_duration_total += duration_ms;
+                slot.test_runs += 1;
+                if status == "failed" || status == "error" {
+                    tests.failed += 1;
+                    failures += 1;
+                }
+            }
+            Sample::AiOperation {
+                cost_usd,
+                tokens,
+                ok,
+                ..
+            } => {
+                ai.operations += 1;
+                ai.cost_usd += cost_usd;
+                ai.tokens += tokens;
+                slot.cost_usd += cost_usd;
+                if !ok {
+                    ai.failed += 1;
+                    failures += 1;
+                }
+            }
+        }
+    }
+    if tests.runs > 0 {
+        tests.avg_duration_ms = test_duration_total as f64 / tests.runs as f64;
+    }
+
+    let mut commands: Vec<CommandStats> = commands
+        .into_iter()
+        .map(|(name, (mut stats, mut durations))| {
+            durations.sort_unstable();
+            stats.name = name;
+            stats.avg_ms = durations.iter().sum::<u64>() as f64 / durations.len() as f64;
+            stats.p95_ms = percentile(&durations, 0.95);
+            stats
+        })
+        .collect();
+    commands.sort_by(|a, b| b.count.cmp(&a.count));
+
+    Ok(MetricsSummary {
+        enabled: enabled(),
+        range,
+        since: since.to_rfc3339(),
+        commands,
+        tests,
+        ai,
+        error_rate: if total == 0 {
+            0.0
+        } else {
+            failures as f64 / total as f64
+        },
+        series,
+    })
+}
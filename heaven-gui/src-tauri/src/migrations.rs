@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use chrono::Utc;
+use serde_json::Value;
+
+use crate::ai::AiSettings;
+use crate::commands::{load_global_state, read_json, save_global_state, write_json};
+use crate::tokens::{pricing_for, ModelPricing};
+use crate::{get_settings_path, get_state_dir};
+
+/// Schema version written by this build; matches `GlobalState.version` in
+/// the CLI's `sologit/state/schema.py`.
+pub(crate) const CURRENT_SCHEMA_VERSION: &str = "1.0.0";
+
+struct Migration {
+    /// Records at a version below this are upgraded by `apply`.
+    to: (u32, u32, u32),
+    description: &'static str,
+    apply: fn(&Path) -> Result<(), String>,
+}
+
+const MIGRATIONS: &[Migration] = &[Migration {
+    to: (1, 0, 0),
+    description: "Fill CLI defaults missing from records written by GUI 0.4",
+    apply: migrate_0_4_to_1_0,
+}];
+
+pub(crate) fn parse_version(version: &str) -> Result<(u32, u32, u32), String> {
+    let mut parts = version
+        .trim()
+        .trim_start_matches('v')
+        .split('.')
+        .map(|part| part.parse::<u32>());
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(Ok(major)), Some(Ok(minor)), patch) => {
+            Ok((major, minor, patch.and_then(Result::ok).unwrap_or(0)))
+        }
+        _ => Err(format!("Unrecognised state schema version: {}", version)),
+    }
+}
+
+/// Apply `update` to every JSON record in `state/<kind>`, rewriting only
+/// the ones it reports as changed.
+fn rewrite_records(
+    state_dir: &Path,
+    kind: &str,
+    update: impl Fn(&mut serde_json::Map<String, Value>) -> bool,
+) -> Result<(), String> {
+    let dir = state_dir.join(kind);
+    if !dir.exists() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(&dir).map_err(|e| e.to_string())? {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("json") {
+            continue;
+        }
+        // Unparseable files are left for validation to quarantine.
+        let mut record: Value = match read_json(&path) {
+            Ok(Some(record)) => record,
+            _ => continue,
+        };
+        if record.as_object_mut().map(&update).unwrap_or(false) {
+            write_json(&path, &record)?;
+        }
+    }
+    Ok(())
+}
+
+fn fill_missing(record: &mut serde_json::Map<String, Value>, defaults: &[(&str, Value)]) -> bool {
+    let mut changed = false;
+    for (key, value) in defaults {
+        if !record.contains_key(*key) {
+            record.insert(key.to_string(), value.clone());
+            changed = true;
+        }
+    }
+    changed
+}
+
+fn migrate_0_4_to_1_0(state_dir: &Path) -> Result<(), String> {
+    rewrite_records(state_dir, "repositories", |record| {
+        fill_missing(
+            record,
+            &[
+                ("trunk_branch", Value::from("main")),
+                ("workpads", Value::Array(Vec::new())),
+                ("total_commits", Value::from(0)),
+            ],
+        )
+    })?;
+    rewrite_records(state_dir, "workpads", |record| {
+        fill_missing(
+            record,
+            &[
+                ("test_runs", Value::Array(Vec::new())),
+                ("ai_operations", Value::Array(Vec::new())),
+                ("patches_applied", Value::from(0)),
+                ("files_changed", Value::Array(Vec::new())),
+            ],
+        )
+    })
+}
+
+/// Move the flat `ai.input_cost_per_1k` / `ai.output_cost_per_1k` prices
+/// from settings written before per-model pricing into an `ai.pricing`
+/// override for the configured model. Settings are versioned separately from
+/// state, so this runs on every start and is a no-op once the keys are gone.
+fn migrate_flat_pricing(settings_path: &Path) -> Result<(), String> {
+    let mut settings: Value = match read_json(settings_path)? {
+        Some(settings) => settings,
+        None => return Ok(()),
+    };
+    let Some(ai) = settings.get_mut("ai").and_then(Value::as_object_mut) else {
+        return Ok(());
+    };
+    let input = ai.remove("input_cost_per_1k");
+    let output = ai.remove("output_cost_per_1k");
+    if input.is_none() && output.is_none() {
+        return Ok(());
+    }
+
+    let defaults = AiSettings::default();
+    let model = ai
+        .get("model")
+        .and_then(Value::as_str)
+        .unwrap_or(&defaults.model)
+        .to_string();
+    let builtin = pricing_for(&model, &HashMap::new());
+    let price = |value: Option<Value>, fallback: f64| {
+        value.as_ref().and_then(Value::as_f64).unwrap_or(fallback)
+    };
+    let pricing = ModelPricing {
+        input_per_1k: price(input, builtin.input_per_1k),
+        output_per_1k: price(output, builtin.output_per_1k),
+    };
+    if let Value::Object(overrides) = ai
+        .entry("pricing")
+        .or_insert_with(|| Value::Object(serde_json::Map::new()))
+    {
+        if !overrides.contains_key(&model) {
+            let pricing = serde_json::to_value(pricing).map_err(|e| e.to_string())?;
+            overrides.insert(model.clone(), pricing);
+        }
+    }
+    tracing::info!("moved flat AI pricing to ai.pricing.{}", model);
+    write_json(settings_path, &settings)
+}
+
+/// Copy the state tree aside before migrating it.
+pub(crate) fn backup_state(
+    state_dir: &Path,
+    from_version: &str,
+) -> Result<std::path::PathBuf, String> {
+    let backup_dir = state_dir
+        .parent()
+        .unwrap_or(state_dir)
+        .join("backups")
+        .join(format!(
+            "state-{}-{}",
+            from_version,
+            Utc::now().format("%Y%m%dT%H%M%S")
+        ));
+    copy_dir(state_dir, &backup_dir)?;
+    Ok(backup_dir)
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<(), String> {
+    fs::create_dir_all(to).map_err(|e| format!("Failed to create {}: {}", to.display(), e))?;
+    for entry in fs::read_dir(from).map_err(|e| e.to_string())? {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let target = to.join(entry.file_name());
+        if entry.file_type().map_err(|e| e.to_string())?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)
+                .map_err(|e| format!("Failed to back up {}: {}", entry.path().display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Bring the on-disk state up to `CURRENT_SCHEMA_VERSION`, backing it up
+/// first. Fails without touching anything when the state was written by a
+/// newer major version than this build understands.
+pub(crate) fn migrate_state() -> Result<(), String> {
+    migrate_flat_pricing(&get_settings_path())?;
+    let state_dir = get_state_dir();
+    if !state_dir.join("global.json").exists() {
+        // Fresh install: nothing on disk predates this build.
+        return Ok(());
+    }
+
+    let mut global = load_global_state()?;
+    let on_disk = parse_version(&global.version)?;
+    let current = parse_version(CURRENT_SCHEMA_VERSION)?;
+
+    if on_disk.0 > current.0 {
+        return Err(format!(
+            "State in {} was written by schema version {}, which is newer than the {} \
+             supported by this build. Upgrade Heaven GUI before opening it.",
+            state_dir.display(),
+            global.version,
+            CURRENT_SCHEMA_VERSION
+        ));
+    }
+    if on_disk >= current {
+        return Ok(());
+    }
+
+    let backup = backup_state(&state_dir, &global.version)?;
+    tracing::info!(
+        "migrating state from {} to {} (backup at {})",
+        global.version,
+        CURRENT_SCHEMA_VERSION,
+        backup.display()
+    );
+    for migration in MIGRATIONS.iter().filter(|m| on_disk < m.to) {
+        tracing::info!("applying migration: {}", migration.description);
+        (migration.apply)(&state_dir)
+            .map_err(|e| format!("Migration \"{}\" failed: {}", migration.description, e))?;
+    }
+
+    global.version = CURRENT_SCHEMA_VERSION.to_string();
+    save_global_state(global)
+}
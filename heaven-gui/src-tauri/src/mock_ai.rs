@@ -0,0 +1,129 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::ai::{AiSettings, ChatMessage, Completion};
+use crate::profiles;
+use crate::tokens::{count_message_tokens, count_tokens};
+
+const MOCK_MODEL: &str = "mock";
+
+/// Replies for operation types whose callers parse JSON, so every feature
+/// works against the mock without fixtures.
+const DEFAULT_RESPONSES: &[(&str, &str)] = &[
+    (
+        "commit_message",
+        r#"{"message": "chore: update files", "alternatives": []}"#,
+    ),
+    (
+        "review",
+        r#"{"summary": "Mock review: no findings.", "comments": []}"#,
+    ),
+    (
+        "test_analysis",
+        r#"{"root_cause": "Mock analysis: no root cause identified.", "suggested_fix": "", "confidence": "low", "related_files": []}"#,
+    ),
+];
+
+/// One canned reply. Every condition that is set must match; fixtures are
+/// tried in file name order and the first match wins.
+#[derive(Debug, Deserialize, Clone)]
+struct MockFixture {
+    operation_type: Option<String>,
+    /// Text the last user message must contain.
+    contains: Option<String>,
+    /// Sent as-is when a string, serialized otherwise.
+    response: Value,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FixtureFile {
+    One(MockFixture),
+    Many(Vec<MockFixture>),
+}
+
+fn fixtures_dir(settings: &AiSettings) -> PathBuf {
+    match &settings.mock_fixtures_dir {
+        Some(dir) => PathBuf::from(dir),
+        None => profiles::active_home().join("mock_ai"),
+    }
+}
+
+/// Every `*.json` fixture in the fixtures directory, in file name order. A
+/// missing directory just means no fixtures.
+fn load_fixtures(settings: &AiSettings) -> Result<Vec<MockFixture>, String> {
+    let Ok(entries) = fs::read_dir(fixtures_dir(settings)) else {
+        return Ok(Vec::new());
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+
+    let mut fixtures = Vec::new();
+    for path in paths {
+        let text = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        match serde_json::from_str(&text)
+            .map_err(|e| format!("Invalid mock fixture {}: {}", path.display(), e))?
+        {
+            FixtureFile::One(fixture) => fixtures.push(fixture),
+            FixtureFile::Many(many) => fixtures.extend(many),
+        }
+    }
+    Ok(fixtures)
+}
+
+fn response_text(response: &Value) -> String {
+    match response {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Answer from the fixture files, falling back to a fixed reply for the
+/// operation type. The same request always gets the same answer, nothing
+/// leaves the machine and the completion is free.
+pub(crate) fn chat(
+    settings: &AiSettings,
+    operation_type: &str,
+    messages: &[ChatMessage],
+) -> Result<Completion, String> {
+    let request = messages
+        .iter()
+        .rev()
+        .find(|message| message.role == "user")
+        .map(|message| message.content.as_str())
+        .unwrap_or_default();
+    let fixture = load_fixtures(settings)?.into_iter().find(|fixture| {
+        fixture
+            .operation_type
+            .as_deref()
+            .is_none_or(|wanted| wanted == operation_type)
+            && fixture
+                .contains
+                .as_deref()
+                .is_none_or(|needle| request.contains(needle))
+    });
+    let content = match fixture {
+        Some(fixture) => response_text(&fixture.response),
+        None => DEFAULT_RESPONSES
+            .iter()
+            .find(|(kind, _)| *kind == operation_type)
+            .map(|(_, response)| response.to_string())
+            .unwrap_or_else(|| format!("Mock {} response.", operation_type)),
+    };
+
+    Ok(Completion {
+        prompt_tokens: count_message_tokens(messages, MOCK_MODEL) as i32,
+        completion_tokens: count_tokens(&content, MOCK_MODEL) as i32,
+        content,
+        model: MOCK_MODEL.to_string(),
+        cost_usd: 0.0,
+    })
+}
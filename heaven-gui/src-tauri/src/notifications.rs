@@ -0,0 +1,109 @@
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+use tauri::api::notification::Notification;
+
+use crate::logging::warn_on_err;
+use crate::statuses::{AIOperationStatus, TestRunStatus};
+use crate::{get_settings, AIOperation, PromotionRecord, TestRun};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub(crate) struct NotificationSettings {
+    pub(crate) test_runs: bool,
+    pub(crate) ai_operations: bool,
+    pub(crate) promotions: bool,
+    pub(crate) budget: bool,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        NotificationSettings {
+            test_runs: true,
+            ai_operations: false,
+            promotions: true,
+            budget: true,
+        }
+    }
+}
+
+/// Bundle identifier the OS attributes notifications to; set in setup.
+static IDENTIFIER: OnceLock<String> = OnceLock::new();
+pub(crate) fn init(app: &tauri::AppHandle) {
+    IDENTIFIER
+        .set(app.config().tauri.bundle.identifier.clone())
+        .ok(); // Already initialised.
+}
+
+/// Show a notification if `enabled` allows it. Returns whether it was shown.
+fn show(enabled: impl FnOnce(&NotificationSettings) -> bool, title: &str, body: &str) -> bool {
+    let Some(identifier) = IDENTIFIER.get() else {
+        return false;
+    };
+    match get_settings() {
+        Ok(settings) if enabled(&settings.notifications) => {
+            match Notification::new(identifier).title(title).body(body).show() {
+                Ok(()) => true,
+                Err(e) => {
+                    tracing::warn!("Failed to show notification: {}", e);
+                    false
+                }
+            }
+        }
+        Ok(_) => false,
+        Err(e) => {
+            tracing::warn!("Skipping notification: {}", e);
+            false
+        }
+    }
+}
+
+pub(crate) fn test_run_finished(run: &TestRun) {
+    let title = match run.status {
+        TestRunStatus::Passed => "Tests passed",
+        TestRunStatus::Failed => "Tests failed",
+        _ => "Test run finished",
+    };
+    let body = match &run.workpad_id {
+        Some(workpad_id) => format!(
+            "{} on {}: {} passed, {} failed",
+            run.target, workpad_id, run.passed, run.failed
+        ),
+        None => format!(
+            "{}: {} passed, {} failed",
+            run.target, run.passed, run.failed
+        ),
+    };
+    show(|settings| settings.test_runs, title, &body);
+}
+
+pub(crate) fn ai_operation_finished(operation: &AIOperation) {
+    let title = match operation.status {
+        AIOperationStatus::Failed => "AI operation failed",
+        AIOperationStatus::Cancelled => "AI operation cancelled",
+        _ => "AI operation completed",
+    };
+    show(
+        |settings| settings.ai_operations,
+        title,
+        &format!("{} ({})", operation.operation_type, operation.model),
+    );
+}
+
+pub(crate) fn promotion_finished(workpad_id: &str, result: &Result<PromotionRecord, String>) {
+    let (title, body) = match result {
+        Ok(record) => ("Workpad promoted", record.message.clone()),
+        Err(error) => ("Promotion failed", format!("{}: {}", workpad_id, error)),
+    };
+    show(|settings| settings.promotions, title, &body);
+}
+
+/// Returns whether the notification was shown; `cost::notify_budget` only
+/// sends it once a month.
+pub(crate) fn budget_exceeded(spent_usd: f64, budget_usd: Option<f64>) -> bool {
+    let body = match budget_usd {
+        Some(budget) => format!("${:.2} spent of ${:.2} this month", spent_usd, budget),
+        None => format!("${:.2} spent this month", spent_usd),
+    };
+    show(|settings| settings.budget, "AI budget exceeded", &body)
+}
@@ -0,0 +1,183 @@
+use std::io::{BufRead, BufReader};
+use std::thread;
+use std::time::Duration;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::ai::{ai_settings, AiSettings, ChatMessage, Completion};
+use crate::ai_client::{post_json, RetryMetadata};
+use crate::http::{agent, json_response};
+use crate::logging::warn_on_err;
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct OllamaModel {
+    name: String,
+    size_bytes: u64,
+    modified_at: String,
+    family: Option<String>,
+    parameter_size: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct PullProgress {
+    model: String,
+    status: String,
+    completed: Option<u64>,
+    total: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct PullFinished {
+    model: String,
+    error: Option<String>,
+}
+
+fn endpoint(settings: &AiSettings, path: &str) -> String {
+    format!("{}{}", settings.ollama_url.trim_end_matches('/'), path)
+}
+
+pub(crate) fn installed_models(settings: &AiSettings) -> Result<Vec<OllamaModel>, String> {
+    let response = json_response(agent().get(&endpoint(settings, "/api/tags")).call())
+        .map_err(|e| format!("Ollama is not reachable at {}: {}", settings.ollama_url, e))?;
+
+    Ok(response["models"]
+        .as_array()
+        .map(|models| {
+            models
+                .iter()
+                .map(|model| OllamaModel {
+                    name: model["name"].as_str().unwrap_or_default().to_string(),
+                    size_bytes: model["size"].as_u64().unwrap_or(0),
+                    modified_at: model["modified_at"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                    family: model["details"]["family"].as_str().map(str::to_string),
+                    parameter_size: model["details"]["parameter_size"]
+                        .as_str()
+                        .map(str::to_string),
+                })
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+/// Whether `requested` names an installed model, with or without a tag.
+fn is_installed(models: &[OllamaModel], requested: &str) -> bool {
+    models
+        .iter()
+        .any(|model| model.name == requested || model.name.split(':').next() == Some(requested))
+}
+
+/// Run a chat completion against the local server. Local inference is free,
+/// so the completion is recorded at zero cost.
+pub(crate) fn chat(
+    settings: &AiSettings,
+    model: Option<&str>,
+    messages: &[ChatMessage],
+    retry: &mut RetryMetadata,
+) -> Result<Completion, String> {
+    // Cloud model names (e.g. from the model picker) fall back to the
+    // configured local model.
+    let model = match model {
+        Some(requested) if is_installed(&installed_models(settings)?, requested) => requested,
+        _ => settings.ollama_model.as_str(),
+    };
+
+    let body = json!({
+        "model": model,
+        "messages": messages,
+        "stream": false,
+        "options": {
+            "temperature": settings.temperature,
+            "num_predict": settings.max_tokens,
+        },
+    });
+    let response = post_json(
+        settings,
+        &endpoint(settings, "/api/chat"),
+        &[],
+        &body,
+        retry,
+    )?;
+
+    let content = response["message"]["content"]
+        .as_str()
+        .ok_or_else(|| "Ollama returned no message content".to_string())?
+        .to_string();
+
+    Ok(Completion {
+        content,
+        model: response["model"].as_str().unwrap_or(model).to_string(),
+        prompt_tokens: response["prompt_eval_count"].as_i64().unwrap_or(0) as i32,
+        completion_tokens: response["eval_count"].as_i64().unwrap_or(0) as i32,
+        cost_usd: 0.0,
+    })
+}
+
+fn pull(window: &tauri::Window, settings: &AiSettings, model: &str) -> Result<(), String> {
+    // Pulls can take many minutes; only the connection has a timeout.
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(Duration::from_secs(15))
+        .user_agent("heaven-gui")
+        .build();
+    let response = agent
+        .post(&endpoint(settings, "/api/pull"))
+        .send_json(json!({ "name": model, "stream": true }))
+        .map_err(|e| format!("Failed to pull {}: {}", model, e))?;
+
+    for line in BufReader::new(response.into_reader()).lines() {
+        let line = line.map_err(|e| format!("Failed to read pull progress: {}", e))?;
+        let Ok(event) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        if let Some(error) = event["error"].as_str() {
+            return Err(error.to_string());
+        }
+        warn_on_err(
+            "Failed to emit ollama-pull-progress",
+            window.emit(
+                "ollama-pull-progress",
+                PullProgress {
+                    model: model.to_string(),
+                    status: event["status"].as_str().unwrap_or_default().to_string(),
+                    completed: event["completed"].as_u64(),
+                    total: event["total"].as_u64(),
+                },
+            ),
+        );
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) fn list_ollama_models() -> Result<Vec<OllamaModel>, String> {
+    installed_models(&ai_settings())
+}
+
+/// Start pulling `model` in the background. Progress arrives as
+/// "ollama-pull-progress" events and completion as "ollama-pull-finished".
+#[tauri::command]
+pub(crate) fn pull_ollama_model(window: tauri::Window, model: String) -> Result<(), String> {
+    let model = model.trim().to_string();
+    if model.is_empty() {
+        return Err("Model name cannot be empty".to_string());
+    }
+
+    let settings = ai_settings();
+    thread::spawn(move || {
+        let result = pull(&window, &settings, &model);
+        warn_on_err(
+            "Failed to emit ollama-pull-finished",
+            window.emit(
+                "ollama-pull-finished",
+                PullFinished {
+                    model,
+                    error: result.err(),
+                },
+            ),
+        );
+    });
+    Ok(())
+}
@@ -0,0 +1,288 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use chrono::{Duration, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::commands::{read_json, write_json};
+use crate::get_state_dir;
+use crate::state_cache::state_cache;
+use crate::timestamps;
+
+const COMPRESSION_LEVEL: i32 = 19;
+
+/// Which monthly pack each packed record lives in.
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct PackIndex {
+    records: HashMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub(crate) struct PackReport {
+    entity: String,
+    records_packed: usize,
+    /// Size of the loose files packed.
+    bytes_before: u64,
+    /// How much the packs grew to hold them.
+    bytes_after: u64,
+    months: Vec<String>,
+}
+
+fn packs_dir(entity: &str) -> PathBuf {
+    get_state_dir().join("packs").join(entity)
+}
+
+fn index_path(entity: &str) -> PathBuf {
+    packs_dir(entity).join("index.json")
+}
+
+fn pack_path(entity: &str, month: &str) -> PathBuf {
+    packs_dir(entity).join(format!("{}.jsonl.zst", month))
+}
+
+fn load_index(entity: &str) -> Result<PackIndex, String> {
+    Ok(read_json(&index_path(entity))?.unwrap_or_default())
+}
+
+fn read_pack(path: &Path) -> Result<Vec<Value>, String> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let file =
+        fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let decoder = zstd::stream::read::Decoder::new(file)
+        .map_err(|e| format!("Failed to decompress {}: {}", path.display(), e))?;
+    let mut records = Vec::new();
+    for line in BufReader::new(decoder).lines() {
+        let line = line.map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        records.push(
+            serde_json::from_str(&line)
+                .map_err(|e| format!("Corrupt record in {}: {}", path.display(), e))?,
+        );
+    }
+    Ok(records)
+}
+
+fn write_pack(path: &Path, records: &[Value]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let tmp_path = path.with_extension("zst.tmp");
+    let file = fs::File::create(&tmp_path)
+        .map_err(|e| format!("Failed to create {}: {}", tmp_path.display(), e))?;
+    let mut encoder = zstd::stream::write::Encoder::new(file, COMPRESSION_LEVEL)
+        .map_err(|e| format!("Failed to compress {}: {}", path.display(), e))?;
+    for record in records {
+        serde_json::to_writer(&mut encoder, record)
+            .and_then(|_| encoder.write_all(b"\n").map_err(serde_json::Error::io))
+            .map_err(|e| format!("Failed to write {}: {}", tmp_path.display(), e))?;
+    }
+    encoder
+        .finish()
+        .map_err(|e| format!("Failed to write {}: {}", tmp_path.display(), e))?;
+    fs::rename(&tmp_path, path).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Size and modification time of every pack file, to tell when they changed.
+type PackSignature = Vec<(PathBuf, u64, Option<SystemTime>)>;
+
+/// Decoded packs per entity, reused until a pack file changes.
+static PACKED: Mutex<Vec<(String, PackSignature, Vec<Value>)>> = Mutex::new(Vec::new());
+
+fn pack_files(entity: &str) -> PackSignature {
+    let Ok(entries) = fs::read_dir(packs_dir(entity)) else {
+        return Vec::new();
+    };
+    let mut files: PackSignature = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.to_string_lossy().ends_with(".jsonl.zst"))
+        .map(|path| {
+            let metadata = fs::metadata(&path).ok();
+            let len = metadata.as_ref().map_or(0, |m| m.len());
+            let modified = metadata.and_then(|m| m.modified().ok());
+            (path, len, modified)
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// Every packed record of `entity`. Records this version can't read are
+/// skipped with a warning, like loose ones.
+pub(crate) fn all_packed<T: DeserializeOwned>(entity: &str) -> Result<Vec<T>, String> {
+    let signature = pack_files(entity);
+    let mut cache = PACKED
+        .lock()
+        .map_err(|_| "Pack cache is poisoned".to_string())?;
+    let cached = cache
+        .iter()
+        .find(|(cached, cached_signature, _)| cached == entity && *cached_signature == signature);
+    let values = match cached {
+        Some((_, _, values)) => values.clone(),
+        None => {
+            let mut values = Vec::new();
+            for (path, _, _) in &signature {
+                values.extend(read_pack(path)?);
+            }
+            cache.retain(|(cached, _, _)| cached != entity);
+            cache.push((entity.to_string(), signature, values.clone()));
+            values
+        }
+    };
+    drop(cache);
+
+    Ok(values
+        .into_iter()
+        .filter_map(|value| match serde_json::from_value(value) {
+            Ok(record) => Some(record),
+            Err(e) => {
+                tracing::warn!("Skipping packed {} record: {}", entity, e);
+                None
+            }
+        })
+        .collect())
+}
+
+/// IDs of every packed record of `entity`.
+pub(crate) fn packed_ids(entity: &str) -> HashSet<String> {
+    load_index(entity)
+        .map(|index| index.records.into_keys().collect())
+        .unwrap_or_default()
+}
+
+/// Look `id` up in the packs for `entity`; `Ok(None)` when it isn't packed.
+pub(crate) fn read_packed<T: DeserializeOwned>(
+    entity: &str,
+    id_field: &str,
+    id: &str,
+) -> Result<Option<T>, String> {
+    let index = load_index(entity)?;
+    let Some(month) = index.records.get(id) else {
+        return Ok(None);
+    };
+    let record = read_pack(&pack_path(entity, month))?
+        .into_iter()
+        .find(|record| record[id_field].as_str() == Some(id));
+    record
+        .map(|record| {
+            serde_json::from_value(record)
+                .map_err(|e| format!("Failed to parse packed record {}: {}", id, e))
+        })
+        .transpose()
+}
+
+/// Rewrite every pack of `entity`, passing each record to `update`, which
+/// returns whether it changed it. Returns the number of records changed.
+pub(crate) fn rewrite_packed(
+    entity: &str,
+    mut update: impl FnMut(&mut Value) -> bool,
+) -> Result<usize, String> {
+    let Ok(entries) = fs::read_dir(packs_dir(entity)) else {
+        return Ok(0);
+    };
+    let mut changed = 0;
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if !path.to_string_lossy().ends_with(".jsonl.zst") {
+            continue;
+        }
+        let mut records = read_pack(&path)?;
+        let before = changed;
+        for record in records.iter_mut() {
+            if update(record) {
+                changed += 1;
+            }
+        }
+        if changed != before {
+            write_pack(&path, &records)?;
+        }
+    }
+    Ok(changed)
+}
+
+/// Move loose `<id>.json` records of `entity` whose `started_at` is older
+/// than `days` into per-month packs. Packs and index are written before
+/// the loose files go, so an interrupted run leaves duplicates, never gaps.
+pub(crate) fn pack_records(entity: &str, id_field: &str, days: u32) -> Result<PackReport, String> {
+    let mut report = PackReport {
+        entity: entity.to_string(),
+        ..PackReport::default()
+    };
+    let dir = get_state_dir().join(entity);
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return Ok(report);
+    };
+    let cutoff = Utc::now() - Duration::days(i64::from(days));
+
+    let mut by_month: BTreeMap<String, Vec<(PathBuf, Value)>> = BTreeMap::new();
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.extension().and_then(|s| s.to_str()) != Some("json") {
+            continue;
+        }
+        let Ok(Some(record)) = read_json::<Value>(&path) else {
+            continue;
+        };
+        let started = record["started_at"].as_str().and_then(timestamps::parse);
+        match started {
+            Some(started) if started < cutoff && record[id_field].is_string() => {
+                let month = started.format("%Y-%m").to_string();
+                by_month.entry(month).or_default().push((path, record));
+            }
+            _ => {}
+        }
+    }
+    if by_month.is_empty() {
+        return Ok(report);
+    }
+
+    let mut index = load_index(entity)?;
+    for (month, loose) in &by_month {
+        let path = pack_path(entity, month);
+        let mut records = read_pack(&path)?;
+        let mut present: HashSet<String> = records
+            .iter()
+            .filter_map(|record| record[id_field].as_str().map(str::to_string))
+            .collect();
+        for (loose_path, record) in loose {
+            report.bytes_before += fs::metadata(loose_path).map(|m| m.len()).unwrap_or(0);
+            let Some(id) = record[id_field].as_str() else {
+                continue;
+            };
+            if present.insert(id.to_string()) {
+                records.push(record.clone());
+            }
+            index.records.insert(id.to_string(), month.clone());
+        }
+        let previous = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        write_pack(&path, &records)?;
+        let written = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        report.bytes_after += written.saturating_sub(previous);
+        report.months.push(month.clone());
+    }
+    write_json(&index_path(entity), &index)?;
+
+    for (loose_path, _) in by_month.values().flatten() {
+        match fs::remove_file(loose_path) {
+            Ok(()) => report.records_packed += 1,
+            Err(e) => tracing::warn!("Failed to remove {}: {}", loose_path.display(), e),
+        }
+    }
+    state_cache().invalidate(entity);
+    tracing::info!(
+        "Packed {} {} records into {} monthly packs",
+        report.records_packed,
+        entity,
+        report.months.len()
+    );
+    Ok(report)
+}
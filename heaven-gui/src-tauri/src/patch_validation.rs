@@ -0,0 +1,207 @@
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::{Component, Path};
+
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::commands::{load_workpad, workpad_checkout_dir};
+use crate::git::run_git;
+use crate::lifecycle::{self, WorkpadStatus};
+use crate::logging::warn_on_err;
+use crate::unified_diff::{parse_file_patches, FileChange, FilePatch};
+
+/// Outcome for one file of a patch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum FileCheck {
+    /// Applies to an existing file.
+    Ok,
+    /// Creates a file that isn't in the workpad yet.
+    New,
+    /// Changes, renames or deletes a file the workpad doesn't have.
+    Missing,
+    /// Creates a file that already exists.
+    AlreadyExists,
+    /// The file exists but the hunks don't apply to it.
+    Conflict,
+    /// The section itself is broken: no hunks or an unsafe path.
+    Invalid,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct FileValidation {
+    path: String,
+    /// Source of a rename or copy.
+    old_path: Option<String>,
+    change: FileChange,
+    status: FileCheck,
+    /// What went wrong, for anything but `ok` and `new`.
+    message: Option<String>,
+    additions: usize,
+    deletions: usize,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct PatchValidation {
+    /// Whether `apply_patch` would apply the diff cleanly.
+    valid: bool,
+    files: Vec<FileValidation>,
+    /// Problems not tied to a single file.
+    errors: Vec<String>,
+}
+
+/// Paths must stay inside the checkout: relative, without `..`.
+fn is_safe_path(path: &str) -> bool {
+    Path::new(path)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+/// `git apply --check` against the checkout, returning its error lines.
+fn dry_run(checkout: &Path, diff: &str) -> Result<Vec<String>, String> {
+    let temp_path =
+        env::temp_dir().join(format!("sologit_validate_{}.diff", Uuid::new_v4().simple()));
+    fs::write(&temp_path, diff).map_err(|e| format!("Failed to write temporary patch: {}", e))?;
+    let result = temp_path
+        .to_str()
+        .ok_or_else(|| "Failed to encode temporary patch path".to_string())
+        .map(|path| run_git(checkout, &["apply", "--check", "--verbose", path]));
+    warn_on_err(
+        "Failed to remove temporary patch",
+        fs::remove_file(&temp_path),
+    );
+    Ok(match result? {
+        Ok(_) => Vec::new(),
+        // run_git reports "git <args> failed: <stderr>".
+        Err(error) => error
+            .split_once(" failed: ")
+            .map_or(error.as_str(), |(_, stderr)| stderr)
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("error: "))
+            .map(str::to_string)
+            .collect(),
+    })
+}
+
+/// Status of one file from the tree contents and the dry run's errors,
+/// which name files as `<path>: ...` or `patch failed: <path>:<line>`.
+fn check_file(
+    file: &FilePatch,
+    tracked: &HashSet<String>,
+    errors: &mut Vec<String>,
+) -> (FileCheck, Option<String>) {
+    let path = file.path();
+    let source = file.old_path.as_deref().unwrap_or(path);
+    if !is_safe_path(path) || !is_safe_path(source) {
+        return (
+            FileCheck::Invalid,
+            Some("Path points outside the repository".to_string()),
+        );
+    }
+    let needs_hunks =
+        file.change == FileChange::Modified && !file.is_binary && file.old_mode == file.new_mode;
+    if needs_hunks && file.additions + file.deletions == 0 {
+        return (
+            FileCheck::Invalid,
+            Some("No changes for this file".to_string()),
+        );
+    }
+
+    let mentions = |line: &str| {
+        [path, source].iter().any(|name| {
+            line.starts_with(&format!("{}:", name)) || line.contains(&format!(" {}:", name))
+        })
+    };
+    let own: Vec<String> = errors
+        .iter()
+        .filter(|line| mentions(line.as_str()))
+        .cloned()
+        .collect();
+    errors.retain(|line| !mentions(line.as_str()));
+
+    let exists = |name: &str| tracked.contains(name);
+    match file.change {
+        FileChange::Added if exists(path) => (
+            FileCheck::AlreadyExists,
+            Some(format!("{} already exists", path)),
+        ),
+        FileChange::Added if own.is_empty() => (FileCheck::New, None),
+        FileChange::Added => (FileCheck::Conflict, Some(own.join("\n"))),
+        _ if !exists(source) => (
+            FileCheck::Missing,
+            Some(format!("{} is not in the workpad", source)),
+        ),
+        FileChange::Renamed | FileChange::Copied if exists(path) => (
+            FileCheck::AlreadyExists,
+            Some(format!("{} already exists", path)),
+        ),
+        _ if own.is_empty() => (FileCheck::Ok, None),
+        _ => (FileCheck::Conflict, Some(own.join("\n"))),
+    }
+}
+
+/// Check a diff against a workpad without changing anything: that it
+/// parses, that the files it changes exist (and the ones it creates don't)
+/// and that `git apply --check` accepts it. Run before `apply_patch` so
+/// problems can be shown per file.
+#[tauri::command]
+pub(crate) fn validate_patch(workpad_id: String, diff: String) -> Result<PatchValidation, String> {
+    let workpad = load_workpad(&workpad_id)?;
+    let mut errors = Vec::new();
+    if let Err(e) = lifecycle::ensure_transition(&workpad, WorkpadStatus::Active) {
+        errors.push(e.to_string());
+    }
+    let patches = parse_file_patches(&diff);
+    if diff.trim().is_empty() {
+        errors.push("Patch diff cannot be empty".to_string());
+    } else if patches.is_empty() {
+        errors.push("Not a unified diff: no file headers found".to_string());
+    }
+    if patches.is_empty() {
+        return Ok(PatchValidation {
+            valid: false,
+            files: Vec::new(),
+            errors,
+        });
+    }
+
+    let checkout = workpad_checkout_dir(&workpad)?;
+    let tracked: HashSet<String> = run_git(&checkout, &["ls-files", "-z"])?
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(str::to_string)
+        .collect();
+    let mut apply_errors = dry_run(&checkout, &diff)?;
+
+    let files: Vec<FileValidation> = patches
+        .iter()
+        .map(|file| {
+            let (status, message) = check_file(file, &tracked, &mut apply_errors);
+            FileValidation {
+                path: file.path().to_string(),
+                old_path: file
+                    .old_path
+                    .clone()
+                    .filter(|old| file.new_path.is_some() && Some(old) != file.new_path.as_ref()),
+                change: file.change,
+                status,
+                message,
+                additions: file.additions,
+                deletions: file.deletions,
+            }
+        })
+        .collect();
+    // Whatever git reported that isn't about one of the files.
+    errors.extend(apply_errors);
+
+    Ok(PatchValidation {
+        valid: errors.is_empty()
+            && files
+                .iter()
+                .all(|file| matches!(file.status, FileCheck::Ok | FileCheck::New)),
+        files,
+        errors,
+    })
+}
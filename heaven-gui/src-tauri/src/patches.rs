@@ -0,0 +1,357 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::audit::{audited, summarize};
+use crate::checkpoints::record_checkpoint;
+use crate::commands::{load_workpad, read_json, save_workpad, workpad_checkout_dir, write_json};
+use crate::conflicts::{apply_three_way, FileConflict};
+use crate::git::{open_repository, resolve_commit, run_git};
+use crate::lifecycle::{self, WorkpadStatus};
+use crate::logging::warn_on_err;
+use crate::signing;
+use crate::unified_diff::{line_counts, parse_changed_files};
+use crate::{get_state_dir, WorkpadState};
+
+/// Index entry for a diff stored under `state/patches/<patch_id>.diff`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct PatchRecord {
+    patch_id: String,
+    workpad_id: String,
+    message: String,
+    created_at: String,
+    files: Vec<String>,
+    additions: usize,
+    deletions: usize,
+    /// Checkpoint created when this patch was applied.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    checkpoint_id: Option<String>,
+    /// Patch this one reverses, for revert commits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reverts: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reverted_by: Option<String>,
+    /// Commit SHA or patch id this patch was cherry-picked from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cherry_picked_from: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct CherryPickResult {
+    workpad: WorkpadState,
+    /// The new patch on the target workpad; `None` while conflicts remain.
+    patch: Option<PatchRecord>,
+    conflicts: Vec<FileConflict>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct PatchDetail {
+    #[serde(flatten)]
+    record: PatchRecord,
+    diff: String,
+}
+
+impl PatchRecord {
+    pub(crate) fn patch_id(&self) -> &str {
+        &self.patch_id
+    }
+}
+
+fn patches_dir() -> PathBuf {
+    get_state_dir().join("patches")
+}
+
+fn diff_path(patch_id: &str) -> PathBuf {
+    patches_dir().join(format!("{}.diff", patch_id))
+}
+
+fn record_path(patch_id: &str) -> PathBuf {
+    patches_dir().join(format!("{}.json", patch_id))
+}
+
+fn load_record(patch_id: &str) -> Result<PatchRecord, String> {
+    match read_json(&record_path(patch_id))? {
+        Some(record) => Ok(record),
+        None => record_from_diff(patch_id),
+    }
+}
+
+/// Index entry for a stored diff whose `.json` sidecar is missing. The owning
+/// workpad is unknown, so `workpad_id` is left empty.
+fn record_from_diff(patch_id: &str) -> Result<PatchRecord, String> {
+    let path = diff_path(patch_id);
+    if !path.exists() {
+        return Err(format!("Patch {} not found", patch_id));
+    }
+    let diff = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read patch {}: {}", patch_id, e))?;
+    let created_at = fs::metadata(&path)
+        .and_then(|meta| meta.modified())
+        .map(|modified| DateTime::<Utc>::from(modified).to_rfc3339())
+        .unwrap_or_default();
+    let (additions, deletions) = line_counts(&diff);
+    Ok(PatchRecord {
+        patch_id: patch_id.to_string(),
+        workpad_id: String::new(),
+        message: patch_id.to_string(),
+        created_at,
+        files: parse_changed_files(&diff),
+        additions,
+        deletions,
+        checkpoint_id: None,
+        reverts: None,
+        reverted_by: None,
+        cherry_picked_from: None,
+    })
+}
+
+/// Persist an applied diff alongside its metadata.
+pub(crate) fn store_patch(
+    workpad_id: &str,
+    message: &str,
+    diff: &str,
+    checkpoint_id: Option<String>,
+) -> Result<PatchRecord, String> {
+    let patch_id = format!("patch-{}", Uuid::new_v4().simple());
+    let dir = patches_dir();
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    fs::write(diff_path(&patch_id), diff).map_err(|e| format!("Failed to write patch: {}", e))?;
+
+    let (additions, deletions) = line_counts(diff);
+    let record = PatchRecord {
+        patch_id: patch_id.clone(),
+        workpad_id: workpad_id.to_string(),
+        message: message.to_string(),
+        created_at: Utc::now().to_rfc3339(),
+        files: parse_changed_files(diff),
+        additions,
+        deletions,
+        checkpoint_id,
+        reverts: None,
+        reverted_by: None,
+        cherry_picked_from: None,
+    };
+    write_json(&record_path(&patch_id), &record)?;
+    Ok(record)
+}
+
+pub(crate) fn link_cherry_pick(mut record: PatchRecord, source: &str) -> Result<(), String> {
+    record.cherry_picked_from = Some(source.to_string());
+    write_json(&record_path(&record.patch_id), &record)
+}
+
+fn workpad_patches(workpad_id: &str) -> Result<Vec<PatchRecord>, String> {
+    let dir = patches_dir();
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut patches = Vec::new();
+    for entry in fs::read_dir(&dir).map_err(|e| format!("Failed to read patches: {}", e))? {
+        let path = entry
+            .map_err(|e| format!("Failed to read patches: {}", e))?
+            .path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("diff") {
+            continue;
+        }
+        let Some(patch_id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        // Diffs without a sidecar can't be attributed, so every workpad sees them.
+        let record = load_record(patch_id)?;
+        if record.workpad_id == workpad_id || record.workpad_id.is_empty() {
+            patches.push(record);
+        }
+    }
+    patches.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+    Ok(patches)
+}
+
+/// Reverse-apply `patch` in the checkout and commit the result on the
+/// workpad branch.
+fn reverse_apply(workpad: &WorkpadState, patch: &Path, message: &str) -> Result<String, String> {
+    let checkout = workpad_checkout_dir(workpad)?;
+    let patch_arg = patch
+        .to_str()
+        .ok_or_else(|| format!("Invalid patch path: {}", patch.display()))?;
+
+    run_git(&checkout, &["checkout", &workpad.branch_name])?;
+    run_git(&checkout, &["apply", "--check", "-R", patch_arg])
+        .map_err(|e| format!("Patch no longer reverses cleanly: {}", e))?;
+    run_git(&checkout, &["apply", "-R", "--index", patch_arg])?;
+    signing::commit(&checkout, message)?;
+    Ok(run_git(&checkout, &["rev-parse", "HEAD"])?
+        .trim()
+        .to_string())
+}
+
+#[tauri::command]
+pub(crate) fn list_patches(workpad_id: String) -> Result<Vec<PatchRecord>, String> {
+    workpad_patches(&workpad_id)
+}
+
+#[tauri::command]
+pub(crate) fn get_patch(patch_id: String) -> Result<PatchDetail, String> {
+    let record = load_record(&patch_id)?;
+    let diff = fs::read_to_string(diff_path(&patch_id))
+        .map_err(|e| format!("Failed to read patch {}: {}", patch_id, e))?;
+    Ok(PatchDetail { record, diff })
+}
+
+#[tauri::command]
+pub(crate) fn revert_patch(workpad_id: String, patch_id: String) -> Result<WorkpadState, String> {
+    let before = load_workpad(&workpad_id)
+        .ok()
+        .and_then(|workpad| summarize(&workpad));
+    audited(
+        "revert_patch",
+        "workpad",
+        Some(workpad_id.clone()),
+        before,
+        move || revert_applied_patch(workpad_id, patch_id),
+    )
+}
+
+fn revert_applied_patch(workpad_id: String, patch_id: String) -> Result<WorkpadState, String> {
+    let mut original = load_record(&patch_id)?;
+    if original.workpad_id.is_empty() {
+        original.workpad_id = workpad_id.clone();
+    }
+    if original.workpad_id != workpad_id {
+        return Err(format!(
+            "Patch {} does not belong to workpad {}",
+            patch_id, workpad_id
+        ));
+    }
+    if let Some(existing) = &original.reverted_by {
+        return Err(format!(
+            "Patch {} was already reverted by {}",
+            patch_id, existing
+        ));
+    }
+
+    let diff = fs::read_to_string(diff_path(&patch_id))
+        .map_err(|e| format!("Failed to read patch {}: {}", patch_id, e))?;
+    let message = format!("Revert \"{}\"", original.message);
+
+    let mut workpad = load_workpad(&workpad_id)?;
+    let commit = reverse_apply(&workpad, &diff_path(&patch_id), &message)?;
+
+    workpad.current_commit = Some(commit);
+    workpad.patches_applied += 1;
+    for file in &original.files {
+        if !workpad.files_changed.contains(file) {
+            workpad.files_changed.push(file.clone());
+        }
+    }
+    let workpad = save_workpad(workpad)?;
+    let checkpoint = record_checkpoint(&workpad, &message)?;
+
+    // Store the revert as its own patch so it can be browsed (and reverted).
+    let reverse = run_git(
+        &workpad_checkout_dir(&workpad)?,
+        &["show", "--format=", "HEAD"],
+    )
+    .unwrap_or(diff);
+    let mut revert = store_patch(
+        &workpad_id,
+        &message,
+        &reverse,
+        Some(checkpoint.checkpoint_id),
+    )?;
+    revert.reverts = Some(patch_id.clone());
+    write_json(&record_path(&revert.patch_id), &revert)?;
+
+    original.reverted_by = Some(revert.patch_id);
+    write_json(&record_path(&patch_id), &original)?;
+
+    Ok(workpad)
+}
+
+/// Diff and commit message of `source`: a stored patch id or a commit SHA
+/// in the target's repository.
+fn cherry_pick_source(target: &WorkpadState, source: &str) -> Result<(String, String), String> {
+    if source.starts_with("patch-") {
+        let record = load_record(source)?;
+        if record.workpad_id == target.workpad_id {
+            return Err(format!(
+                "Patch {} already belongs to workpad {}",
+                source, target.workpad_id
+            ));
+        }
+        let diff = fs::read_to_string(diff_path(source))
+            .map_err(|e| format!("Failed to read patch {}: {}", source, e))?;
+        return Ok((diff, record.message));
+    }
+
+    let repo = open_repository(&target.repo_id)?;
+    let commit = resolve_commit(&repo, source)?;
+    if commit.parent_count() > 1 {
+        return Err(format!("Cannot cherry-pick merge commit {}", source));
+    }
+    let sha = commit.id().to_string();
+    let diff = run_git(
+        &workpad_checkout_dir(target)?,
+        &["show", "--format=", "--binary", &sha],
+    )?;
+    Ok((diff, commit.summary().unwrap_or(&sha).to_string()))
+}
+
+/// Apply a commit or another workpad's patch to `target_workpad_id`. A
+/// conflicting pick is left pending for `resolve_conflict`, and the patch
+/// is linked to its source once it completes.
+#[tauri::command]
+pub(crate) fn cherry_pick(
+    target_workpad_id: String,
+    source: String,
+) -> Result<CherryPickResult, String> {
+    let before = load_workpad(&target_workpad_id)
+        .ok()
+        .and_then(|workpad| summarize(&workpad));
+    audited(
+        "cherry_pick",
+        "workpad",
+        Some(target_workpad_id.clone()),
+        before,
+        move || {
+            let target = load_workpad(&target_workpad_id)?;
+            lifecycle::ensure_transition(&target, WorkpadStatus::Active)?;
+            let (diff, message) = cherry_pick_source(&target, &source)?;
+            if diff.trim().is_empty() {
+                return Err(format!("{} has no changes to cherry-pick", source));
+            }
+
+            let message = format!("Cherry-pick \"{}\"", message);
+            let patch_file = env::temp_dir().join(format!(
+                "sologit_cherry_pick_{}.diff",
+                Uuid::new_v4().simple()
+            ));
+            fs::write(&patch_file, &diff)
+                .map_err(|e| format!("Failed to write temporary patch: {}", e))?;
+            let outcome = apply_three_way(&target, &message, &diff, &patch_file, Some(&source));
+            warn_on_err(
+                "Failed to remove temporary patch",
+                fs::remove_file(&patch_file),
+            );
+            let conflicts = outcome?;
+
+            let patch = if conflicts.is_empty() {
+                workpad_patches(&target_workpad_id)?
+                    .into_iter()
+                    .rev()
+                    .find(|patch| patch.cherry_picked_from.as_deref() == Some(source.as_str()))
+            } else {
+                None
+            };
+            Ok(CherryPickResult {
+                workpad: load_workpad(&target_workpad_id)?,
+                patch,
+                conflicts,
+            })
+        },
+    )
+}
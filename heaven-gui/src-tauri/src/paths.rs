@@ -0,0 +1,99 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use crate::profiles;
+
+/// Used unless `$SOLOGIT_CLI` names another executable.
+const DEFAULT_CLI: &str = "evogitctl";
+
+/// Replaces the environment-derived paths while set.
+static OVERRIDE: RwLock<Option<Paths>> = RwLock::new(None);
+
+/// Where the app keeps its files and which CLI it drives. Normally derived
+/// from the active profile and the environment; integration tests inject
+/// their own so every command runs against a temporary home.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Paths {
+    home: PathBuf,
+    cli: PathBuf,
+}
+
+impl Paths {
+    pub(crate) fn new(home: impl Into<PathBuf>, cli: impl Into<PathBuf>) -> Self {
+        Paths {
+            home: home.into(),
+            cli: cli.into(),
+        }
+    }
+
+    /// Root holding `state/`, `data/` and the settings files.
+    pub(crate) fn home(&self) -> &Path {
+        &self.home
+    }
+
+    pub(crate) fn state_dir(&self) -> PathBuf {
+        self.home.join("state")
+    }
+
+    pub(crate) fn repos_dir(&self) -> PathBuf {
+        self.home.join("data").join("repos")
+    }
+
+    /// The CLI git engine's own `repositories.json` and `workpads.json`.
+    pub(crate) fn metadata_dir(&self) -> PathBuf {
+        self.home.join("data").join("metadata")
+    }
+
+    /// The `evogitctl` executable.
+    pub(crate) fn cli(&self) -> &Path {
+        &self.cli
+    }
+}
+
+/// IDs from the frontend or the API become state file names; only letters,
+/// digits, '-' and '_' are allowed so none can step outside its directory.
+pub(crate) fn is_plain_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+pub(crate) fn overridden() -> Option<Paths> {
+    OVERRIDE.read().ok().and_then(|paths| paths.clone())
+}
+
+/// Point the whole app at `paths`, or back at the environment with `None`.
+#[cfg(test)]
+pub(crate) fn set_override(paths: Option<Paths>) {
+    if let Ok(mut current) = OVERRIDE.write() {
+        *current = paths;
+    }
+}
+
+/// The injected paths, or the active profile's home and `$SOLOGIT_CLI`.
+pub(crate) fn current() -> Paths {
+    overridden().unwrap_or_else(|| {
+        let cli = env::var("SOLOGIT_CLI")
+            .ok()
+            .filter(|cli| !cli.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_CLI.to_string());
+        Paths::new(profiles::active_home(), cli)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_ids_cannot_leave_their_directory() {
+        assert!(is_plain_id("tpl-3f2a_b"));
+        assert!(!is_plain_id(""));
+        assert!(!is_plain_id("../settings"));
+        assert!(!is_plain_id("a/b"));
+        assert!(!is_plain_id("a\\b"));
+        assert!(!is_plain_id(".."));
+    }
+}
@@ -0,0 +1,176 @@
+use std::fs;
+
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::audit::audited;
+use crate::chat;
+use crate::commands::{read_json, write_json};
+use crate::packs;
+use crate::state_cache::state_cache;
+use crate::timestamps;
+use crate::{get_state_dir, AIOperation};
+
+/// How much of each prompt and response `ai.history` keeps on disk.
+pub(crate) const HISTORY_MODES: &[&str] = &["full", "summary", "hash"];
+const SUMMARY_CHARS: usize = 200;
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub(crate) struct RedactionReport {
+    operations_redacted: usize,
+    packed_operations_redacted: usize,
+    chat_messages_redacted: usize,
+}
+
+fn redact_text(text: &str, mode: &str) -> String {
+    match mode {
+        "summary" => {
+            let total = text.chars().count();
+            if total <= SUMMARY_CHARS {
+                return text.to_string();
+            }
+            let head: String = text.chars().take(SUMMARY_CHARS).collect();
+            format!(
+                "{}… [{} more characters redacted]",
+                head,
+                total - SUMMARY_CHARS
+            )
+        }
+        "hash" => {
+            let digest = Sha256::digest(text.as_bytes());
+            let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+            format!("sha256:{}", hex)
+        }
+        _ => text.to_string(),
+    }
+}
+
+/// Apply `mode` to an operation about to be stored. Already-redacted
+/// operations are left alone so hashes aren't hashed again.
+pub(crate) fn redact_operation(operation: &mut AIOperation, mode: &str) {
+    if mode == "full" || operation.redaction.is_some() {
+        return;
+    }
+    operation.prompt = redact_text(&operation.prompt, mode);
+    operation.response = operation
+        .response
+        .as_deref()
+        .map(|response| redact_text(response, mode));
+    for message in &mut operation.messages {
+        message.content = redact_text(&message.content, mode);
+    }
+    operation.redaction = Some(mode.to_string());
+}
+
+/// The same as `redact_operation`, for records handled as raw JSON.
+fn redact_value(record: &mut Value, mode: &str) -> bool {
+    if !record["redaction"].is_null() {
+        return false;
+    }
+    for field in ["prompt", "response"] {
+        if let Some(text) = record[field].as_str() {
+            record[field] = Value::String(redact_text(text, mode));
+        }
+    }
+    if let Some(messages) = record["messages"].as_array_mut() {
+        for message in messages {
+            if let Some(text) = message["content"].as_str() {
+                message["content"] = Value::String(redact_text(text, mode));
+            }
+        }
+    }
+    record["redaction"] = Value::String(mode.to_string());
+    true
+}
+
+fn before(record: &Value, field: &str, cutoff: DateTime<Utc>) -> bool {
+    record[field]
+        .as_str()
+        .and_then(timestamps::parse)
+        .is_some_and(|at| at < cutoff)
+}
+
+/// Scrub the messages of a chat session sent before `cutoff`; returns how
+/// many were changed.
+fn redact_chat_session(session: &mut Value, cutoff: DateTime<Utc>, mode: &str) -> usize {
+    let Some(messages) = session["messages"].as_array_mut() else {
+        return 0;
+    };
+    let mut redacted = 0;
+    for message in messages {
+        if !message["redaction"].is_null() || !before(message, "timestamp", cutoff) {
+            continue;
+        }
+        if let Some(text) = message["content"].as_str() {
+            message["content"] = Value::String(redact_text(text, mode));
+        }
+        message["redaction"] = Value::String(mode.to_string());
+        redacted += 1;
+    }
+    redacted
+}
+
+/// Scrub prompts and responses of AI operations started before
+/// `before_date` (RFC 3339 or YYYY-MM-DD), including packed ones, and chat
+/// messages sent before it. `mode` is "summary" or "hash" (the default).
+#[tauri::command]
+pub(crate) fn redact_ai_history(
+    before_date: String,
+    mode: Option<String>,
+) -> Result<RedactionReport, String> {
+    let mode = mode.unwrap_or_else(|| "hash".to_string());
+    if !matches!(mode.as_str(), "summary" | "hash") {
+        return Err(format!(
+            "Unknown redaction mode {} (expected summary or hash)",
+            mode
+        ));
+    }
+    let cutoff = timestamps::parse(&before_date)
+        .or_else(|| {
+            NaiveDate::parse_from_str(&before_date, "%Y-%m-%d")
+                .ok()
+                .map(|date| date.and_time(NaiveTime::MIN).and_utc())
+        })
+        .ok_or_else(|| format!("Invalid date: {}", before_date))?;
+
+    audited("redact_ai_history", "ai_operation", None, None, move || {
+        let mut report = RedactionReport::default();
+        let dir = get_state_dir().join("ai_operations");
+        if let Ok(entries) = fs::read_dir(&dir) {
+            for path in entries.flatten().map(|entry| entry.path()) {
+                if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                    continue;
+                }
+                let Some(mut record) = read_json::<Value>(&path)? else {
+                    continue;
+                };
+                if before(&record, "started_at", cutoff) && redact_value(&mut record, &mode) {
+                    write_json(&path, &record)?;
+                    report.operations_redacted += 1;
+                }
+            }
+        }
+        report.packed_operations_redacted = packs::rewrite_packed("ai_operations", |record| {
+            before(record, "started_at", cutoff) && redact_value(record, &mode)
+        })?;
+        if let Ok(entries) = fs::read_dir(chat::sessions_dir()) {
+            for path in entries.flatten().map(|entry| entry.path()) {
+                if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                    continue;
+                }
+                let Some(mut session) = read_json::<Value>(&path)? else {
+                    continue;
+                };
+                let redacted = redact_chat_session(&mut session, cutoff, &mode);
+                if redacted > 0 {
+                    write_json(&path, &session)?;
+                    report.chat_messages_redacted += redacted;
+                }
+            }
+        }
+        state_cache().invalidate("ai_operations");
+        Ok(report)
+    })
+}
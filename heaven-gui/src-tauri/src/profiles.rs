@@ -0,0 +1,180 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::audit::audited;
+use crate::commands::{read_json, write_json};
+use crate::{migrations, paths, repair};
+
+pub(crate) const DEFAULT_PROFILE: &str = "default";
+
+/// Resolved home of the active profile, cleared whenever it changes.
+static ACTIVE_HOME: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+struct ProfileEntry {
+    /// Custom root (e.g. a synced drive); defaults to `profiles/<name>`.
+    path: Option<String>,
+    created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct ProfileRegistry {
+    active: String,
+    #[serde(default)]
+    profiles: BTreeMap<String, ProfileEntry>,
+}
+
+impl Default for ProfileRegistry {
+    fn default() -> Self {
+        ProfileRegistry {
+            active: DEFAULT_PROFILE.to_string(),
+            profiles: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct ProfileInfo {
+    name: String,
+    path: String,
+    active: bool,
+    initialized: bool,
+}
+
+/// `$SOLOGIT_HOME`, or `~/.sologit` when unset.
+pub(crate) fn sologit_root() -> PathBuf {
+    match env::var("SOLOGIT_HOME") {
+        Ok(home) if !home.trim().is_empty() => PathBuf::from(home.trim()),
+        _ => {
+            let home = dirs::home_dir().expect("Could not find home directory");
+            home.join(".sologit")
+        }
+    }
+}
+
+fn registry_path() -> PathBuf {
+    sologit_root().join("profiles.json")
+}
+
+fn load_registry() -> Result<ProfileRegistry, String> {
+    Ok(read_json(&registry_path())?.unwrap_or_default())
+}
+
+fn profile_home(registry: &ProfileRegistry, name: &str) -> PathBuf {
+    if name == DEFAULT_PROFILE {
+        // The default profile keeps the original layout so existing
+        // installs and the CLI see the same files.
+        return sologit_root();
+    }
+    registry
+        .profiles
+        .get(name)
+        .and_then(|entry| entry.path.as_ref())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| sologit_root().join("profiles").join(name))
+}
+
+/// Root holding the active profile's `state/`, `data/` and settings, or
+/// the injected home while `paths::set_override` is in effect.
+pub(crate) fn active_home() -> PathBuf {
+    if let Some(paths) = paths::overridden() {
+        return paths.home().to_path_buf();
+    }
+    if let Some(home) = ACTIVE_HOME.read().ok().and_then(|cached| cached.clone()) {
+        return home;
+    }
+    let registry = load_registry().unwrap_or_default();
+    let home = profile_home(&registry, &registry.active);
+    if let Ok(mut cached) = ACTIVE_HOME.write() {
+        *cached = Some(home.clone());
+    }
+    home
+}
+
+pub(crate) fn active_profile_name() -> String {
+    load_registry()
+        .map(|registry| registry.active)
+        .unwrap_or_else(|_| DEFAULT_PROFILE.to_string())
+}
+
+fn profile_info(registry: &ProfileRegistry, name: &str) -> ProfileInfo {
+    let home = profile_home(registry, name);
+    ProfileInfo {
+        name: name.to_string(),
+        initialized: home.join("state").is_dir(),
+        path: home.display().to_string(),
+        active: registry.active == name,
+    }
+}
+
+#[tauri::command]
+pub(crate) fn list_profiles() -> Result<Vec<ProfileInfo>, String> {
+    let registry = load_registry()?;
+    let mut names: Vec<String> = vec![DEFAULT_PROFILE.to_string()];
+    names.extend(
+        registry
+            .profiles
+            .keys()
+            .filter(|name| name.as_str() != DEFAULT_PROFILE)
+            .cloned(),
+    );
+    Ok(names
+        .iter()
+        .map(|name| profile_info(&registry, name))
+        .collect())
+}
+
+/// Make `name` the active profile, creating it (optionally rooted at
+/// `path`) if it doesn't exist yet.
+#[tauri::command]
+pub(crate) fn switch_profile(name: String, path: Option<String>) -> Result<ProfileInfo, String> {
+    audited(
+        "switch_profile",
+        "profile",
+        Some(name.clone()),
+        None,
+        move || {
+            let name = name.trim().to_string();
+            if !paths::is_plain_id(&name) {
+                return Err(
+                    "Profile names may only contain letters, digits, '-' and '_'".to_string(),
+                );
+            }
+
+            let mut registry = load_registry()?;
+            if name != DEFAULT_PROFILE {
+                let entry = registry
+                    .profiles
+                    .entry(name.clone())
+                    .or_insert_with(|| ProfileEntry {
+                        path: None,
+                        created_at: Utc::now().to_rfc3339(),
+                    });
+                if let Some(path) = path.filter(|p| !p.trim().is_empty()) {
+                    entry.path = Some(path.trim().to_string());
+                }
+            }
+
+            let home = profile_home(&registry, &name);
+            fs::create_dir_all(home.join("state"))
+                .map_err(|e| format!("Failed to create {}: {}", home.display(), e))?;
+            registry.active = name.clone();
+            write_json(&registry_path(), &registry)?;
+            if let Ok(mut cached) = ACTIVE_HOME.write() {
+                *cached = Some(home);
+            }
+            tracing::info!("switched to profile {}", name);
+
+            // A profile on a synced drive may have been written by another build.
+            migrations::migrate_state()?;
+            repair::validate_state()?;
+            Ok(profile_info(&registry, &name))
+        },
+    )
+}
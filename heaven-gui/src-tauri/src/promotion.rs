@@ -0,0 +1,575 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+use chrono::{Local, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use uuid::Uuid;
+
+use crate::audit::{as_actor, audited, in_range};
+use crate::ci::get_ci_status;
+use crate::commands::{load_repository, load_workpad, promote_workpad, read_json, write_json};
+use crate::dashboard::is_open;
+use crate::dependency_audit;
+use crate::diff::{structure_diff, FileDiff};
+use crate::git::{open_repository, resolve_commit};
+use crate::lifecycle;
+use crate::logging::warn_on_err;
+use crate::review;
+use crate::secrets;
+use crate::statuses::{PromotionDecision, QueuedPromotionStatus};
+use crate::{get_settings, get_state_dir, PromotionRecord};
+
+/// How often the scheduler re-checks queued promotions.
+const QUEUE_INTERVAL: Duration = Duration::from_secs(60);
+const DEFAULT_PAGE_SIZE: usize = 50;
+/// Checks the CLI's promotion gate makes with the rules `evogitctl pad
+/// promote` uses (`require_tests`, `require_all_tests_pass`,
+/// `require_fast_forward`). The rest only gate promotion from the GUI.
+const CLI_GATE_CHECKS: &[&str] = &["status", "fast_forward"];
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct GateCheck {
+    name: String,
+    passed: bool,
+    message: String,
+    /// Whether the CLI's promotion gate also makes this check.
+    cli_gate: bool,
+}
+
+/// What `promote_workpad` would do right now, worked out without touching
+/// the repository or state.
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct PromotionPreview {
+    workpad_id: String,
+    trunk_branch: String,
+    trunk_sha: String,
+    workpad_sha: String,
+    /// Approve or reject, as the CLI's promotion gate would decide from the
+    /// `cli_gate` checks alone.
+    decision: PromotionDecision,
+    /// Whether every check passes, including the GUI-only ones.
+    can_promote: bool,
+    fast_forward: bool,
+    /// Commits on the workpad branch that trunk doesn't have yet.
+    commits_ahead: usize,
+    /// Paths an in-memory merge with trunk leaves conflicted.
+    conflicts: Vec<String>,
+    checks: Vec<GateCheck>,
+    files_changed: usize,
+    additions: usize,
+    deletions: usize,
+    /// Changes trunk would receive.
+    diff: Vec<FileDiff>,
+}
+
+fn check(name: &str, result: Result<String, String>) -> GateCheck {
+    let passed = result.is_ok();
+    GateCheck {
+        name: name.to_string(),
+        passed,
+        message: result.unwrap_or_else(|e| e),
+        cli_gate: CLI_GATE_CHECKS.contains(&name),
+    }
+}
+
+#[tauri::command]
+pub(crate) fn preview_promotion(workpad_id: String) -> Result<PromotionPreview, String> {
+    let workpad = load_workpad(&workpad_id)?;
+    let trunk_branch = load_repository(&workpad.repo_id)?.trunk_branch;
+    let repo = open_repository(&workpad.repo_id)?;
+    let trunk = resolve_commit(&repo, &trunk_branch)?;
+    let tip = resolve_commit(&repo, &workpad.branch_name)?;
+    let git_err = |e: git2::Error| e.message().to_string();
+
+    let fast_forward = trunk.id() == tip.id()
+        || repo
+            .graph_descendant_of(tip.id(), trunk.id())
+            .map_err(git_err)?;
+    let (commits_ahead, _) = repo
+        .graph_ahead_behind(tip.id(), trunk.id())
+        .map_err(git_err)?;
+
+    // merge_commits works on an in-memory index; nothing is written.
+    let merged = repo
+        .merge_commits(&trunk, &tip, None)
+        .map_err(|e| format!("In-memory merge failed: {}", e.message()))?;
+    let mut conflicts = Vec::new();
+    if merged.has_conflicts() {
+        for conflict in merged.conflicts().map_err(git_err)?.flatten() {
+            let entry = conflict.our.or(conflict.their).or(conflict.ancestor);
+            if let Some(entry) = entry {
+                conflicts.push(String::from_utf8_lossy(&entry.path).to_string());
+            }
+        }
+    }
+
+    // A fast-forward hands trunk the workpad tree as-is; otherwise estimate
+    // from what the workpad changed since it forked.
+    let base_tree = if fast_forward {
+        trunk.tree().map_err(git_err)?
+    } else {
+        let base = repo.merge_base(trunk.id(), tip.id()).map_err(git_err)?;
+        repo.find_commit(base)
+            .and_then(|commit| commit.tree())
+            .map_err(git_err)?
+    };
+    let diff = repo
+        .diff_tree_to_tree(Some(&base_tree), Some(&tip.tree().map_err(git_err)?), None)
+        .map_err(git_err)?;
+    let stats = diff.stats().map_err(git_err)?;
+
+    let mut checks = vec![
+        check(
+            "status",
+            lifecycle::ensure_promotable(&workpad).map(|_| "Tests passed".to_string()),
+        ),
+        check(
+            "fast_forward",
+            if fast_forward {
+                Ok(format!("Can fast-forward {}", trunk_branch))
+            } else {
+                Err(format!(
+                    "Cannot fast-forward - {} has diverged; rebase or merge required",
+                    trunk_branch
+                ))
+            },
+        ),
+        check(
+            "conflicts",
+            if conflicts.is_empty() {
+                Ok("Merges cleanly".to_string())
+            } else {
+                Err(format!("{} file(s) would conflict", conflicts.len()))
+            },
+        ),
+        check(
+            "changes",
+            if commits_ahead > 0 {
+                Ok(format!("{} commit(s) to promote", commits_ahead))
+            } else {
+                Err("Nothing to promote".to_string())
+            },
+        ),
+        check("secrets", secrets::scan(&workpad)?.gate_result()),
+    ];
+    let git_settings = get_settings()?.git;
+    if let Some(result) =
+        dependency_audit::gate_result(&workpad.repo_id, &git_settings.dependency_audit)
+    {
+        checks.push(check("dependencies", result));
+    }
+    if let Some(result) = review::gate_result(&workpad, &git_settings.review) {
+        checks.push(check("review", result));
+    }
+    let can_promote = checks.iter().all(|check| check.passed);
+    let cli_approves = checks
+        .iter()
+        .filter(|check| check.cli_gate)
+        .all(|check| check.passed);
+
+    Ok(PromotionPreview {
+        workpad_id,
+        trunk_branch,
+        trunk_sha: trunk.id().to_string(),
+        workpad_sha: tip.id().to_string(),
+        decision: if cli_approves {
+            PromotionDecision::Approve
+        } else {
+            PromotionDecision::Reject
+        },
+        can_promote,
+        fast_forward,
+        commits_ahead,
+        conflicts,
+        checks,
+        files_changed: stats.files_changed(),
+        additions: stats.insertions(),
+        deletions: stats.deletions(),
+        diff: structure_diff(&diff)?,
+    })
+}
+
+/// Local hours, `start_hour` inclusive to `end_hour` exclusive, during which
+/// a queued promotion may land. A window past midnight (22 to 6) wraps.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct WorkingHours {
+    start_hour: u32,
+    end_hour: u32,
+}
+
+impl WorkingHours {
+    fn contains(&self, hour: u32) -> bool {
+        if self.start_hour <= self.end_hour {
+            (self.start_hour..self.end_hour).contains(&hour)
+        } else {
+            hour >= self.start_hour || hour < self.end_hour
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub(crate) struct PromotionConditions {
+    tests_green: bool,
+    ci_green: bool,
+    working_hours: Option<WorkingHours>,
+}
+
+impl Default for PromotionConditions {
+    fn default() -> Self {
+        PromotionConditions {
+            tests_green: true,
+            ci_green: false,
+            working_hours: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct QueuedPromotion {
+    queue_id: String,
+    workpad_id: String,
+    repo_id: String,
+    conditions: PromotionConditions,
+    status: QueuedPromotionStatus,
+    /// Conditions still unmet as of the last check.
+    waiting_on: Vec<String>,
+    record: Option<PromotionRecord>,
+    error: Option<String>,
+    queued_at: String,
+    updated_at: String,
+}
+
+/// Held while a queue entry is read, changed and written back, so a
+/// cancellation can't race the scheduler promoting the same entry.
+static QUEUE: Mutex<()> = Mutex::new(());
+
+fn lock_queue() -> MutexGuard<'static, ()> {
+    QUEUE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn queue_dir() -> PathBuf {
+    get_state_dir().join("promotion_queue")
+}
+
+fn queue_path(queue_id: &str) -> PathBuf {
+    queue_dir().join(format!("{}.json", queue_id))
+}
+
+fn save_entry(entry: &mut QueuedPromotion) -> Result<(), String> {
+    entry.updated_at = Utc::now().to_rfc3339();
+    write_json(&queue_path(&entry.queue_id), entry)
+}
+
+fn load_queue() -> Result<Vec<QueuedPromotion>, String> {
+    let Ok(entries) = fs::read_dir(queue_dir()) else {
+        return Ok(Vec::new());
+    };
+    let mut queue = Vec::new();
+    for entry in entries.flatten() {
+        if let Some(queued) = read_json::<QueuedPromotion>(&entry.path())? {
+            queue.push(queued);
+        }
+    }
+    queue.sort_by(|a, b| a.queued_at.cmp(&b.queued_at));
+    Ok(queue)
+}
+
+/// The conditions `entry` is still waiting on; empty once it may land.
+fn unmet_conditions(entry: &QueuedPromotion) -> Result<Vec<String>, String> {
+    let workpad = load_workpad(&entry.workpad_id)?;
+    let conditions = &entry.conditions;
+    let mut waiting = Vec::new();
+    if conditions.tests_green && lifecycle::ensure_promotable(&workpad).is_err() {
+        waiting.push("tests_green".to_string());
+    }
+    if conditions.ci_green
+        && !get_ci_status(workpad.repo_id.clone(), workpad.branch_name.clone(), None)
+            .is_ok_and(|status| status.passed())
+    {
+        waiting.push("ci_green".to_string());
+    }
+    if let Some(hours) = &conditions.working_hours {
+        if !hours.contains(Local::now().hour()) {
+            waiting.push("working_hours".to_string());
+        }
+    }
+    Ok(waiting)
+}
+
+/// Check one queued entry, promoting it when every condition holds.
+/// Returns whether the entry changed.
+fn process(entry: &mut QueuedPromotion) -> Result<bool, String> {
+    let workpad = load_workpad(&entry.workpad_id)?;
+    if !is_open(&workpad) {
+        entry.status = QueuedPromotionStatus::Cancelled;
+        entry.error = Some(format!("Workpad is {}", workpad.status));
+        return Ok(true);
+    }
+    let waiting = unmet_conditions(entry)?;
+    if !waiting.is_empty() {
+        let changed = waiting != entry.waiting_on;
+        entry.waiting_on = waiting;
+        return Ok(changed);
+    }
+
+    entry.waiting_on.clear();
+    match promote_workpad(entry.workpad_id.clone()) {
+        Ok(record) => {
+            entry.status = QueuedPromotionStatus::Promoted;
+            entry.record = Some(record);
+        }
+        Err(error) => {
+            entry.status = QueuedPromotionStatus::Failed;
+            entry.error = Some(error);
+        }
+    }
+    Ok(true)
+}
+
+/// Work through the queue oldest first, landing at most one promotion per
+/// repository per pass so each sees the trunk the previous one left.
+fn process_queue(app: &tauri::AppHandle) -> Result<(), String> {
+    let mut promoted_repos = Vec::new();
+    for queued in load_queue()? {
+        if queued.status != QueuedPromotionStatus::Queued
+            || promoted_repos.contains(&queued.repo_id)
+        {
+            continue;
+        }
+        let _queue = lock_queue();
+        // Re-read under the lock; it may have been cancelled since.
+        let Some(mut entry) = read_json::<QueuedPromotion>(&queue_path(&queued.queue_id))? else {
+            continue;
+        };
+        if entry.status != QueuedPromotionStatus::Queued {
+            continue;
+        }
+        let changed = match process(&mut entry) {
+            Ok(changed) => changed,
+            Err(error) => {
+                entry.status = QueuedPromotionStatus::Failed;
+                entry.error = Some(error);
+                true
+            }
+        };
+        if entry.status == QueuedPromotionStatus::Promoted {
+            promoted_repos.push(entry.repo_id.clone());
+        }
+        if changed {
+            save_entry(&mut entry)?;
+            warn_on_err(
+                "Failed to emit promotion-queue-changed",
+                app.emit_all("promotion-queue-changed", &entry),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Background loop landing queued promotions once their conditions hold.
+pub(crate) fn start_promotion_queue(app: tauri::AppHandle) {
+    thread::spawn(move || {
+        as_actor("scheduler", || loop {
+            warn_on_err("Promotion queue pass failed", process_queue(&app));
+            thread::sleep(QUEUE_INTERVAL);
+        })
+    });
+}
+
+/// Defer promoting `workpad_id` until `conditions` hold (by default, until
+/// its tests are green). Promotion always needs green tests, so
+/// `tests_green` can't be turned off. The scheduler checks the queue every
+/// minute.
+#[tauri::command]
+pub(crate) fn queue_promotion(
+    workpad_id: String,
+    conditions: Option<PromotionConditions>,
+) -> Result<QueuedPromotion, String> {
+    audited(
+        "queue_promotion",
+        "workpad",
+        Some(workpad_id.clone()),
+        None,
+        move || {
+            let workpad = load_workpad(&workpad_id)?;
+            if !is_open(&workpad) {
+                return Err(format!(
+                    "Workpad {} is {} and cannot be promoted",
+                    workpad_id, workpad.status
+                ));
+            }
+            let conditions = conditions.unwrap_or_default();
+            if !conditions.tests_green {
+                return Err(
+                    "Promotion requires passing tests; tests_green can't be disabled".to_string(),
+                );
+            }
+            if let Some(hours) = &conditions.working_hours {
+                if hours.start_hour > 23 || hours.end_hour > 24 {
+                    return Err("Working hours must be between 0 and 24".to_string());
+                }
+                if hours.start_hour == hours.end_hour {
+                    return Err("Working hours must not start and end at the same hour".to_string());
+                }
+            }
+
+            let _queue = lock_queue();
+            if load_queue()?.iter().any(|entry| {
+                entry.workpad_id == workpad_id && entry.status == QueuedPromotionStatus::Queued
+            }) {
+                return Err(format!("Workpad {} is already queued", workpad_id));
+            }
+
+            let now = Utc::now().to_rfc3339();
+            let mut entry = QueuedPromotion {
+                queue_id: format!("queue-{}", Uuid::new_v4().simple()),
+                workpad_id,
+                repo_id: workpad.repo_id,
+                conditions,
+                status: QueuedPromotionStatus::Queued,
+                waiting_on: Vec::new(),
+                record: None,
+                error: None,
+                queued_at: now.clone(),
+                updated_at: now,
+            };
+            entry.waiting_on = unmet_conditions(&entry)?;
+            save_entry(&mut entry)?;
+            Ok(entry)
+        },
+    )
+}
+
+/// Queued promotions, oldest first. Finished entries are included unless
+/// `pending_only` is set.
+#[tauri::command]
+pub(crate) fn list_promotion_queue(
+    pending_only: Option<bool>,
+) -> Result<Vec<QueuedPromotion>, String> {
+    let pending_only = pending_only.unwrap_or(false);
+    Ok(load_queue()?
+        .into_iter()
+        .filter(|entry| !pending_only || entry.status == QueuedPromotionStatus::Queued)
+        .collect())
+}
+
+/// Take a promotion off the queue. Waits for the scheduler if it is
+/// promoting this entry right now.
+#[tauri::command]
+pub(crate) fn cancel_queued_promotion(queue_id: String) -> Result<QueuedPromotion, String> {
+    audited(
+        "cancel_queued_promotion",
+        "promotion_queue",
+        Some(queue_id.clone()),
+        None,
+        move || {
+            let _queue = lock_queue();
+            let mut entry: QueuedPromotion = read_json(&queue_path(&queue_id))?
+                .ok_or_else(|| format!("Queued promotion not found: {}", queue_id))?;
+            if entry.status != QueuedPromotionStatus::Queued {
+                return Err(format!(
+                    "Promotion {} is already {}",
+                    queue_id, entry.status
+                ));
+            }
+            entry.status = QueuedPromotionStatus::Cancelled;
+            save_entry(&mut entry)?;
+            Ok(entry)
+        },
+    )
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub(crate) struct PromotionFilter {
+    decision: Option<PromotionDecision>,
+    promoted: Option<bool>,
+    /// RFC 3339 bounds on `created_at`, inclusive.
+    since: Option<String>,
+    until: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct PromotionPage {
+    records: Vec<PromotionRecord>,
+    page: usize,
+    page_size: usize,
+    total: usize,
+}
+
+fn promotion_path(record_id: &str) -> PathBuf {
+    get_state_dir()
+        .join("promotions")
+        .join(format!("{}.json", record_id))
+}
+
+/// Every promotion record, newest first.
+pub(crate) fn load_promotions() -> Result<Vec<PromotionRecord>, String> {
+    let Ok(entries) = fs::read_dir(get_state_dir().join("promotions")) else {
+        return Ok(Vec::new());
+    };
+    let mut records = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        match read_json::<PromotionRecord>(&path) {
+            Ok(Some(record)) => records.push(record),
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Skipping {}: {}", path.display(), e),
+        }
+    }
+    records.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(records)
+}
+
+/// Newest-first page of promotion records, for the promotion timeline.
+#[tauri::command]
+pub(crate) fn list_promotions(
+    repo_id: Option<String>,
+    workpad_id: Option<String>,
+    filter: Option<PromotionFilter>,
+    page: Option<usize>,
+    page_size: Option<usize>,
+) -> Result<PromotionPage, String> {
+    let filter = filter.unwrap_or_default();
+    let page = page.unwrap_or(0);
+    let page_size = page_size.unwrap_or(DEFAULT_PAGE_SIZE).max(1);
+
+    let eq = |wanted: &Option<String>, actual: &String| {
+        wanted.is_none() || wanted.as_ref() == Some(actual)
+    };
+    let matched: Vec<PromotionRecord> = load_promotions()?
+        .into_iter()
+        .filter(|record| eq(&repo_id, &record.repo_id))
+        .filter(|record| eq(&workpad_id, &record.workpad_id))
+        .filter(|record| filter.decision.is_none() || filter.decision == Some(record.decision))
+        .filter(|record| filter.promoted.is_none() || filter.promoted == Some(record.promoted))
+        .filter(|record| in_range(&record.created_at, &filter.since, true))
+        .filter(|record| in_range(&record.created_at, &filter.until, false))
+        .collect();
+
+    let total = matched.len();
+    Ok(PromotionPage {
+        records: matched
+            .into_iter()
+            .skip(page * page_size)
+            .take(page_size)
+            .collect(),
+        page,
+        page_size,
+        total,
+    })
+}
+
+#[tauri::command]
+pub(crate) fn get_promotion(record_id: String) -> Result<PromotionRecord, String> {
+    read_json(&promotion_path(&record_id))?
+        .ok_or_else(|| format!("Promotion record not found: {}", record_id))
+}
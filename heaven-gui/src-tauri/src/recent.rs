@@ -0,0 +1,262 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::commands::{load_global_state, load_repository, load_workpad, read_json, write_json};
+use crate::lifecycle::WorkpadStatus;
+use crate::logging::warn_on_err;
+use crate::{get_state_dir, list_repositories, list_repository_files, list_workpads};
+
+const MAX_RECENT: usize = 50;
+const ENTITY_TYPES: &[&str] = &["repository", "workpad", "file"];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct RecentItem {
+    entity_type: String,
+    id: String,
+    label: String,
+    /// Repository a workpad or file belongs to.
+    repo_id: Option<String>,
+    opened_at: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct QuickOpenResult {
+    entity_type: String,
+    id: String,
+    label: String,
+    detail: Option<String>,
+    repo_id: Option<String>,
+    score: i64,
+}
+
+fn recent_path() -> std::path::PathBuf {
+    get_state_dir().join("recent.json")
+}
+
+fn load_recent() -> Result<Vec<RecentItem>, String> {
+    Ok(read_json(&recent_path())?.unwrap_or_default())
+}
+
+/// Score `candidate` against `query` as an in-order subsequence match,
+/// favouring consecutive runs, word starts and matches near the end (the
+/// file name). `None` means not every query character was found.
+pub(crate) fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    // Lowercase char by char so indices into `lower` and `chars` always agree,
+    // even where full lowercasing would change the length.
+    let fold = |c: char| c.to_lowercase().next().unwrap_or(c);
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(fold)
+        .collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+    let chars: Vec<char> = candidate.chars().collect();
+    let lower: Vec<char> = chars.iter().copied().map(fold).collect();
+
+    let mut score = 0i64;
+    let mut next = 0;
+    let mut previous: Option<usize> = None;
+    for wanted in query {
+        let index = (next..lower.len()).find(|&i| lower[i] == wanted)?;
+        score += 1;
+        if previous == Some(index.wrapping_sub(1)) {
+            score += 5;
+        }
+        let boundary = index == 0
+            || matches!(chars[index - 1], '/' | '\\' | '_' | '-' | '.' | ' ')
+            || (chars[index].is_uppercase() && chars[index - 1].is_lowercase());
+        if boundary {
+            score += 3;
+        }
+        previous = Some(index);
+        next = index + 1;
+    }
+
+    // Prefer shorter candidates and matches inside the last path segment.
+    let file_start = chars
+        .iter()
+        .rposition(|&c| c == '/')
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    if previous.unwrap_or(0) >= file_start {
+        score += 4;
+    }
+    score -= (chars.len() / 16) as i64;
+    Some(score)
+}
+
+/// Move `id` to the front of the recent list, evicting the oldest entry
+/// past the limit.
+#[tauri::command]
+pub(crate) fn record_recent(
+    entity_type: String,
+    id: String,
+    repo_id: Option<String>,
+) -> Result<Vec<RecentItem>, String> {
+    if !ENTITY_TYPES.contains(&entity_type.as_str()) {
+        return Err(format!(
+            "Unknown entity type {}; expected one of {}",
+            entity_type,
+            ENTITY_TYPES.join(", ")
+        ));
+    }
+
+    let (label, repo_id) = match entity_type.as_str() {
+        "repository" => (load_repository(&id)?.name, None),
+        "workpad" => {
+            let workpad = load_workpad(&id)?;
+            (workpad.title, Some(workpad.repo_id))
+        }
+        _ => {
+            let repo_id = repo_id
+                .or_else(|| load_global_state().ok().and_then(|g| g.active_repo))
+                .ok_or_else(|| "A repo_id is required for files".to_string())?;
+            (id.clone(), Some(repo_id))
+        }
+    };
+
+    let mut items = load_recent()?;
+    items.retain(|item| {
+        !(item.entity_type == entity_type && item.id == id && item.repo_id == repo_id)
+    });
+    items.insert(
+        0,
+        RecentItem {
+            entity_type,
+            id,
+            label,
+            repo_id,
+            opened_at: Utc::now().to_rfc3339(),
+        },
+    );
+    items.truncate(MAX_RECENT);
+    write_json(&recent_path(), &items)?;
+    Ok(items)
+}
+
+#[tauri::command]
+pub(crate) fn list_recent(entity_type: Option<String>) -> Result<Vec<RecentItem>, String> {
+    let items = load_recent()?;
+    Ok(match entity_type {
+        Some(kind) => items
+            .into_iter()
+            .filter(|item| item.entity_type == kind)
+            .collect(),
+        None => items,
+    })
+}
+
+/// Fuzzy-match `query` against repository names, workpad titles and file
+/// paths in the active repository. Recently opened items rank higher; an
+/// empty query returns just the recent list.
+#[tauri::command]
+pub(crate) fn quick_open(
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<QuickOpenResult>, String> {
+    let limit = limit.unwrap_or(50);
+    let recent = load_recent()?;
+    // File ids are paths relative to their repository, so a file only matches
+    // a recent entry from the same repository.
+    let recency_bonus = |entity_type: &str, id: &str, repo_id: Option<&str>| -> i64 {
+        recent
+            .iter()
+            .position(|item| {
+                item.entity_type == entity_type
+                    && item.id == id
+                    && (entity_type != "file" || item.repo_id.as_deref() == repo_id)
+            })
+            .map(|rank| 20 - (rank as i64).min(20))
+            .unwrap_or(0)
+    };
+
+    if query.trim().is_empty() {
+        return Ok(recent
+            .iter()
+            .take(limit)
+            .map(|item| QuickOpenResult {
+                entity_type: item.entity_type.clone(),
+                id: item.id.clone(),
+                label: item.label.clone(),
+                detail: item.repo_id.clone(),
+                repo_id: item.repo_id.clone(),
+                score: recency_bonus(&item.entity_type, &item.id, item.repo_id.as_deref()),
+            })
+            .collect());
+    }
+
+    let mut results = Vec::new();
+    for repo in list_repositories(None)? {
+        if let Some(score) = fuzzy_score(&query, &repo.name) {
+            results.push(QuickOpenResult {
+                entity_type: "repository".to_string(),
+                score: score + recency_bonus("repository", &repo.repo_id, None),
+                id: repo.repo_id,
+                label: repo.name,
+                detail: Some(repo.path),
+                repo_id: None,
+            });
+        }
+    }
+    for workpad in list_workpads(None, None, None)? {
+        if workpad.status == WorkpadStatus::Deleted {
+            continue;
+        }
+        if let Some(score) = fuzzy_score(&query, &workpad.title) {
+            results.push(QuickOpenResult {
+                entity_type: "workpad".to_string(),
+                score: score + recency_bonus("workpad", &workpad.workpad_id, None),
+                id: workpad.workpad_id,
+                label: workpad.title,
+                detail: Some(workpad.branch_name),
+                repo_id: Some(workpad.repo_id),
+            });
+        }
+    }
+    if let Some(repo_id) = load_global_state()?.active_repo {
+        let files = list_repository_files(repo_id.clone(), None, None);
+        warn_on_err("Failed to list files for quick open", files.as_ref());
+        for path in files.unwrap_or_default() {
+            if let Some(score) = fuzzy_score(&query, &path) {
+                results.push(QuickOpenResult {
+                    entity_type: "file".to_string(),
+                    score: score + recency_bonus("file", &path, Some(repo_id.as_str())),
+                    label: path.rsplit('/').next().unwrap_or(&path).to_string(),
+                    detail: Some(path.clone()),
+                    id: path,
+                    repo_id: Some(repo_id.clone()),
+                });
+            }
+        }
+    }
+
+    results.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.label.cmp(&b.label)));
+    results.truncate(limit);
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scores_multi_byte_candidates_by_char() {
+        // Same char count as the ASCII spelling, so the same score; byte
+        // offsets would misplace the file-name bonus and the length penalty.
+        assert_eq!(
+            fuzzy_score("main", "über/grüße/main.rs"),
+            fuzzy_score("main", "uber/gruse/main.rs")
+        );
+        assert_eq!(fuzzy_score("ü", "Über"), fuzzy_score("u", "Uber"));
+        assert_eq!(fuzzy_score("é", "cafe"), None);
+    }
+
+    #[test]
+    fn lowercasing_that_changes_length_keeps_indices_aligned() {
+        // "İ" lowercases to two chars; folding must not shift later matches.
+        assert_eq!(fuzzy_score("ix", "İx"), fuzzy_score("ix", "Ix"));
+        assert!(fuzzy_score("xy", "İİİx_y").is_some());
+    }
+}
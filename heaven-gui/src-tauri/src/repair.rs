@@ -0,0 +1,357 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::audit::audited;
+use crate::commands::{load_global_state, save_global_state, save_workpad, write_json};
+use crate::packs;
+use crate::{
+    get_state_dir, list_ai_operations, list_repositories, list_test_runs, list_workpads,
+    AIOperation, PromotionRecord, RepositoryState, TestRun, WorkpadState,
+};
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct QuarantinedFile {
+    original_path: String,
+    quarantined_path: String,
+    reason: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct RepairedRecord {
+    path: String,
+    fields_added: Vec<String>,
+}
+
+/// A record left in place that this version can't read, e.g. one with a
+/// status or field value it doesn't know yet.
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct UnrecognizedRecord {
+    path: String,
+    reason: String,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub(crate) struct ValidationReport {
+    checked: usize,
+    repaired: Vec<RepairedRecord>,
+    quarantined: Vec<QuarantinedFile>,
+    unrecognized: Vec<UnrecognizedRecord>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct ReconciledReference {
+    entity_type: String,
+    entity_id: String,
+    description: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct RepairReport {
+    validation: ValidationReport,
+    reconciled: Vec<ReconciledReference>,
+    completed_at: String,
+}
+
+fn corrupt_dir() -> PathBuf {
+    get_state_dir().join("corrupt")
+}
+
+/// Defaults for fields that may be missing from records written by older
+/// or partial CLI versions. Identity fields are deliberately absent: a
+/// record without its id or timestamps is reported, not invented.
+fn optional_defaults(kind: &str) -> Value {
+    match kind {
+        "repositories" => json!({
+            "current_commit": null,
+            "workpads": [],
+            "total_commits": 0,
+        }),
+        "workpads" => json!({
+            "current_commit": null,
+            "promoted_at": null,
+            "test_runs": [],
+            "ai_operations": [],
+            "patches_applied": 0,
+            "files_changed": [],
+        }),
+        "test_runs" => json!({
+            "workpad_id": null,
+            "completed_at": null,
+            "total_tests": 0,
+            "passed": 0,
+            "failed": 0,
+            "skipped": 0,
+            "duration_ms": 0,
+        }),
+        "ai_operations" => json!({
+            "workpad_id": null,
+            "response": null,
+            "cost_usd": 0.0,
+            "tokens_used": 0,
+            "completed_at": null,
+            "error": null,
+        }),
+        "promotions" => json!({
+            "commit_hash": null,
+            "test_run_id": null,
+            "ci_status": null,
+            "ci_message": null,
+        }),
+        _ => json!({}),
+    }
+}
+
+/// Move `path` under `state/corrupt/<kind>/`, never overwriting an earlier
+/// quarantined copy.
+fn quarantine(path: &Path, kind: &str, reason: String) -> Result<QuarantinedFile, String> {
+    let dir = corrupt_dir().join(kind);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let mut target = dir.join(file_name.as_ref());
+    if target.exists() {
+        target = dir.join(format!(
+            "{}.{}",
+            file_name,
+            Utc::now().format("%Y%m%dT%H%M%S%.3f")
+        ));
+    }
+    fs::rename(path, &target)
+        .map_err(|e| format!("Failed to quarantine {}: {}", path.display(), e))?;
+    tracing::warn!("quarantined {}: {}", path.display(), reason);
+
+    Ok(QuarantinedFile {
+        original_path: path.display().to_string(),
+        quarantined_path: target.display().to_string(),
+        reason,
+    })
+}
+
+fn validate_dir<T: DeserializeOwned>(
+    kind: &str,
+    report: &mut ValidationReport,
+) -> Result<(), String> {
+    let dir = get_state_dir().join(kind);
+    if !dir.exists() {
+        return Ok(());
+    }
+
+    let defaults = optional_defaults(kind);
+    for entry in fs::read_dir(&dir).map_err(|e| e.to_string())? {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("json") {
+            continue;
+        }
+        report.checked += 1;
+
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                report
+                    .quarantined
+                    .push(quarantine(&path, kind, format!("Unreadable: {}", e))?);
+                continue;
+            }
+        };
+        let mut value: Value = match serde_json::from_str(&contents) {
+            Ok(value) => value,
+            Err(e) => {
+                report
+                    .quarantined
+                    .push(quarantine(&path, kind, format!("Invalid JSON: {}", e))?);
+                continue;
+            }
+        };
+        if serde_json::from_value::<T>(value.clone()).is_ok() {
+            continue;
+        }
+
+        let mut fields_added = Vec::new();
+        if let (Some(record), Some(defaults)) = (value.as_object_mut(), defaults.as_object()) {
+            for (key, default) in defaults {
+                if !record.contains_key(key) {
+                    record.insert(key.clone(), default.clone());
+                    fields_added.push(key.clone());
+                }
+            }
+        }
+        // The value, not the parsed record, is written back so keys this
+        // version doesn't know about survive.
+        if !fields_added.is_empty() {
+            write_json(&path, &value)?;
+            report.repaired.push(RepairedRecord {
+                path: path.display().to_string(),
+                fields_added,
+            });
+        }
+        // Valid JSON stays put even when it doesn't match this schema: it may
+        // come from a newer CLI and is still the CLI's state.
+        if let Err(e) = serde_json::from_value::<T>(value) {
+            tracing::warn!("Leaving unrecognized record {}: {}", path.display(), e);
+            report.unrecognized.push(UnrecognizedRecord {
+                path: path.display().to_string(),
+                reason: e.to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Check every record in the state tree, filling in missing optional fields
+/// and quarantining files that aren't JSON at all. A report of each
+/// pass that moved files is kept alongside the quarantined copies.
+pub(crate) fn validate_state() -> Result<ValidationReport, String> {
+    let mut report = ValidationReport::default();
+    validate_dir::<RepositoryState>("repositories", &mut report)?;
+    validate_dir::<WorkpadState>("workpads", &mut report)?;
+    validate_dir::<TestRun>("test_runs", &mut report)?;
+    validate_dir::<AIOperation>("ai_operations", &mut report)?;
+    validate_dir::<PromotionRecord>("promotions", &mut report)?;
+
+    if !report.quarantined.is_empty() {
+        let path = corrupt_dir().join(format!(
+            "report-{}.json",
+            Utc::now().format("%Y%m%dT%H%M%S")
+        ));
+        write_json(&path, &report)?;
+    }
+    Ok(report)
+}
+
+fn reconcile(validation: &mut ValidationReport) -> Result<Vec<ReconciledReference>, String> {
+    let mut reconciled = Vec::new();
+    let repos = list_repositories(Some(true))?;
+    let repo_ids: HashSet<String> = repos.iter().map(|r| r.repo_id.clone()).collect();
+    let run_ids: HashSet<String> = list_test_runs(None, Some(true))?
+        .into_iter()
+        .map(|r| r.run_id)
+        .collect();
+    let mut op_ids: HashSet<String> = list_ai_operations(None, None, Some(true))?
+        .into_iter()
+        .map(|op| op.operation_id)
+        .collect();
+    op_ids.extend(packs::packed_ids("ai_operations"));
+
+    // Workpads whose repository is gone can't be opened or promoted.
+    let mut workpads = Vec::new();
+    for workpad in list_workpads(None, None, Some(true))? {
+        if repo_ids.contains(&workpad.repo_id) {
+            workpads.push(workpad);
+            continue;
+        }
+        let path = get_state_dir()
+            .join("workpads")
+            .join(format!("{}.json", workpad.workpad_id));
+        validation.quarantined.push(quarantine(
+            &path,
+            "workpads",
+            format!("Repository {} no longer exists", workpad.repo_id),
+        )?);
+        reconciled.push(ReconciledReference {
+            entity_type: "workpad".to_string(),
+            entity_id: workpad.workpad_id.clone(),
+            description: format!("Quarantined; repository {} is missing", workpad.repo_id),
+        });
+    }
+
+    for mut workpad in workpads.clone() {
+        let runs_before = workpad.test_runs.len();
+        let ops_before = workpad.ai_operations.len();
+        workpad.test_runs.retain(|id| run_ids.contains(id));
+        workpad.ai_operations.retain(|id| op_ids.contains(id));
+        let dropped =
+            (runs_before - workpad.test_runs.len()) + (ops_before - workpad.ai_operations.len());
+        if dropped > 0 {
+            reconciled.push(ReconciledReference {
+                entity_type: "workpad".to_string(),
+                entity_id: workpad.workpad_id.clone(),
+                description: format!(
+                    "Removed {} missing test run/AI operation references",
+                    dropped
+                ),
+            });
+            save_workpad(workpad)?;
+        }
+    }
+
+    for mut repo in repos {
+        let expected: Vec<String> = workpads
+            .iter()
+            .filter(|wp| wp.repo_id == repo.repo_id)
+            .map(|wp| wp.workpad_id.clone())
+            .collect();
+        let mut listed = repo.workpads.clone();
+        listed.retain(|id| expected.contains(id));
+        for id in &expected {
+            if !listed.contains(id) {
+                listed.push(id.clone());
+            }
+        }
+        if listed != repo.workpads {
+            reconciled.push(ReconciledReference {
+                entity_type: "repository".to_string(),
+                entity_id: repo.repo_id.clone(),
+                description: format!(
+                    "Workpad list updated from {} to {} entries",
+                    repo.workpads.len(),
+                    listed.len()
+                ),
+            });
+            repo.workpads = listed;
+            repo.updated_at = Utc::now().to_rfc3339();
+            let path = get_state_dir()
+                .join("repositories")
+                .join(format!("{}.json", repo.repo_id));
+            write_json(&path, &repo)?;
+        }
+    }
+
+    let mut global = load_global_state()?;
+    let mut global_changed = false;
+    if let Some(active) = global.active_repo.clone() {
+        if !repo_ids.contains(&active) {
+            global.active_repo = None;
+            global_changed = true;
+            reconciled.push(ReconciledReference {
+                entity_type: "global".to_string(),
+                entity_id: active,
+                description: "Cleared dangling active repository".to_string(),
+            });
+        }
+    }
+    if let Some(active) = global.active_workpad.clone() {
+        if !workpads.iter().any(|wp| wp.workpad_id == active) {
+            global.active_workpad = None;
+            global_changed = true;
+            reconciled.push(ReconciledReference {
+                entity_type: "global".to_string(),
+                entity_id: active,
+                description: "Cleared dangling active workpad".to_string(),
+            });
+        }
+    }
+    if global_changed {
+        save_global_state(global)?;
+    }
+
+    Ok(reconciled)
+}
+
+#[tauri::command]
+pub(crate) fn repair_state() -> Result<RepairReport, String> {
+    audited("repair_state", "state", None, None, move || {
+        let mut validation = validate_state()?;
+        let reconciled = reconcile(&mut validation)?;
+        Ok(RepairReport {
+            validation,
+            reconciled,
+            completed_at: Utc::now().to_rfc3339(),
+        })
+    })
+}
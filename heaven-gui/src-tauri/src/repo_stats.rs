@@ -0,0 +1,240 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+
+use chrono::{TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::commands::{load_repository, read_json, write_json};
+use crate::git::{open_repository, resolve_commit};
+use crate::statuses::TestRunStatus;
+use crate::timestamps;
+use crate::{get_state_dir, list_test_runs, list_workpads};
+
+const TOP_FILES: usize = 20;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub(crate) struct DayActivity {
+    commits: usize,
+    added: usize,
+    removed: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub(crate) struct FileChurn {
+    /// Empty in the cache, where files are keyed by path.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    path: String,
+    commits: usize,
+    added: usize,
+    removed: usize,
+}
+
+/// Per-commit totals folded so far, so a refresh only walks commits made
+/// since `tip`.
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct StatsCache {
+    trunk_branch: String,
+    tip: Option<String>,
+    total_commits: usize,
+    /// Keyed by UTC date, `YYYY-MM-DD`.
+    days: BTreeMap<String, DayActivity>,
+    files: HashMap<String, FileChurn>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct DayPassRate {
+    date: String,
+    runs: usize,
+    passed: usize,
+    pass_rate: f64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct RepoStats {
+    repo_id: String,
+    trunk_branch: String,
+    tip: Option<String>,
+    total_commits: usize,
+    /// Commits and line churn on trunk per day, for the heatmap and the
+    /// churn chart.
+    days: BTreeMap<String, DayActivity>,
+    most_changed_files: Vec<FileChurn>,
+    promoted_workpads: usize,
+    /// Mean time from workpad creation to promotion.
+    avg_hours_to_promotion: Option<f64>,
+    test_pass_rate: Vec<DayPassRate>,
+    /// How many commits this call had to read; zero when the cache was
+    /// already current.
+    new_commits: usize,
+}
+
+fn cache_path(repo_id: &str) -> PathBuf {
+    get_state_dir()
+        .join("repo_stats")
+        .join(format!("{}.json", repo_id))
+}
+
+fn day_of(seconds: i64) -> String {
+    Utc.timestamp_opt(seconds, 0)
+        .single()
+        .map(|at| at.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+/// Fold the trunk commits not yet in `cache` into it. Starts over when
+/// trunk no longer descends from the cached tip (rewritten history or a
+/// different trunk branch).
+fn refresh(repo_id: &str, cache: &mut StatsCache) -> Result<usize, String> {
+    let trunk_branch = load_repository(repo_id)?.trunk_branch;
+    let repo = open_repository(repo_id)?;
+    let tip = resolve_commit(&repo, &trunk_branch)?.id();
+    let git_err = |e: git2::Error| e.message().to_string();
+
+    let mut cached_tip = cache
+        .tip
+        .as_deref()
+        .and_then(|sha| git2::Oid::from_str(sha).ok());
+    let rewritten = cached_tip.is_some_and(|cached| {
+        cached != tip && !repo.graph_descendant_of(tip, cached).unwrap_or(false)
+    });
+    if cache.trunk_branch != trunk_branch || rewritten {
+        *cache = StatsCache::default();
+        cached_tip = None;
+    }
+    cache.trunk_branch = trunk_branch;
+    if cached_tip == Some(tip) {
+        return Ok(0);
+    }
+
+    let mut revwalk = repo.revwalk().map_err(git_err)?;
+    revwalk.push(tip).map_err(git_err)?;
+    revwalk.simplify_first_parent().map_err(git_err)?;
+    if let Some(cached) = cached_tip {
+        revwalk.hide(cached).map_err(git_err)?;
+    }
+
+    let mut walked = 0;
+    for oid in revwalk {
+        let commit = repo.find_commit(oid.map_err(git_err)?).map_err(git_err)?;
+        let tree = commit.tree().map_err(git_err)?;
+        let parent_tree = commit.parent(0).ok().and_then(|p| p.tree().ok());
+        let diff = repo
+            .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)
+            .map_err(git_err)?;
+
+        let day = cache
+            .days
+            .entry(day_of(commit.time().seconds()))
+            .or_default();
+        day.commits += 1;
+        for index in 0..diff.deltas().len() {
+            let Some(path) = diff.get_delta(index).and_then(|delta| {
+                delta
+                    .new_file()
+                    .path()
+                    .map(|p| p.to_string_lossy().to_string())
+            }) else {
+                continue;
+            };
+            // Binary files have no patch; they still count as changed.
+            let (added, removed) = git2::Patch::from_diff(&diff, index)
+                .ok()
+                .flatten()
+                .and_then(|patch| patch.line_stats().ok())
+                .map(|(_, added, removed)| (added, removed))
+                .unwrap_or_default();
+            day.added += added;
+            day.removed += removed;
+            let file = cache.files.entry(path).or_default();
+            file.commits += 1;
+            file.added += added;
+            file.removed += removed;
+        }
+        walked += 1;
+    }
+    cache.total_commits += walked;
+    cache.tip = Some(tip.to_string());
+    Ok(walked)
+}
+
+/// Commit activity, churn, hot files, promotion lead time and test pass
+/// rates for a repository. Git-derived numbers are cached under
+/// `state/repo_stats` and only new trunk commits are read on each call.
+#[tauri::command]
+pub(crate) fn get_repo_stats(repo_id: String) -> Result<RepoStats, String> {
+    let path = cache_path(&repo_id);
+    let mut cache: StatsCache = read_json(&path)?.unwrap_or_default();
+    let new_commits = refresh(&repo_id, &mut cache)?;
+    if new_commits > 0 {
+        write_json(&path, &cache)?;
+    }
+
+    let workpads = list_workpads(Some(repo_id.clone()), None, None)?;
+    let lead_times: Vec<f64> = workpads
+        .iter()
+        .filter_map(|workpad| {
+            let created = timestamps::parse(&workpad.created_at)?;
+            let promoted = timestamps::parse(workpad.promoted_at.as_deref()?)?;
+            Some((promoted - created).num_seconds() as f64 / 3600.0)
+        })
+        .collect();
+    let avg_hours_to_promotion =
+        (!lead_times.is_empty()).then(|| lead_times.iter().sum::<f64>() / lead_times.len() as f64);
+
+    let in_repo: HashSet<&str> = workpads.iter().map(|w| w.workpad_id.as_str()).collect();
+    let mut runs_by_day: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+    for run in list_test_runs(None, None)? {
+        let scoped = run
+            .workpad_id
+            .as_deref()
+            .is_some_and(|id| in_repo.contains(id));
+        if !scoped || !matches!(run.status, TestRunStatus::Passed | TestRunStatus::Failed) {
+            continue;
+        }
+        let Some(started) = timestamps::parse(&run.started_at) else {
+            continue;
+        };
+        let day = runs_by_day
+            .entry(started.format("%Y-%m-%d").to_string())
+            .or_default();
+        day.0 += 1;
+        if run.status == TestRunStatus::Passed {
+            day.1 += 1;
+        }
+    }
+    let test_pass_rate = runs_by_day
+        .into_iter()
+        .map(|(date, (runs, passed))| DayPassRate {
+            date,
+            runs,
+            passed,
+            pass_rate: passed as f64 / runs as f64,
+        })
+        .collect();
+
+    let mut most_changed_files: Vec<FileChurn> = cache
+        .files
+        .into_iter()
+        .map(|(path, churn)| FileChurn { path, ..churn })
+        .collect();
+    most_changed_files.sort_by(|a, b| {
+        b.commits
+            .cmp(&a.commits)
+            .then((b.added + b.removed).cmp(&(a.added + a.removed)))
+            .then(a.path.cmp(&b.path))
+    });
+    most_changed_files.truncate(TOP_FILES);
+
+    Ok(RepoStats {
+        repo_id,
+        trunk_branch: cache.trunk_branch,
+        tip: cache.tip,
+        total_commits: cache.total_commits,
+        days: cache.days,
+        most_changed_files,
+        promoted_workpads: lead_times.len(),
+        avg_hours_to_promotion,
+        test_pass_rate,
+        new_commits,
+    })
+}
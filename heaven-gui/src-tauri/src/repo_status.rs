@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+use std::path::{Component, Path};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use chrono::Utc;
+use git2::{Status, StatusOptions};
+use serde::Serialize;
+use tauri::Manager;
+
+use crate::api_server;
+use crate::commands::resolve_repo_path;
+use crate::files::DirectoryCache;
+use crate::git::open_repository;
+use crate::logging::warn_on_err;
+use crate::watcher::{FsWatch, WatchBatch};
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct StatusEntry {
+    path: String,
+    /// "staged", "modified", "untracked" or "conflicted"
+    state: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct RepoStatus {
+    repo_id: String,
+    branch: Option<String>,
+    staged: usize,
+    modified: usize,
+    untracked: usize,
+    conflicted: usize,
+    dirty: bool,
+    files: Vec<StatusEntry>,
+    updated_at: String,
+}
+
+impl RepoStatus {
+    fn same_counts(&self, other: &RepoStatus) -> bool {
+        (
+            self.staged,
+            self.modified,
+            self.untracked,
+            self.conflicted,
+            &self.branch,
+        ) == (
+            other.staged,
+            other.modified,
+            other.untracked,
+            other.conflicted,
+            &other.branch,
+        )
+    }
+}
+
+struct StatusWatch {
+    stop: Arc<AtomicBool>,
+}
+
+/// Working-directory watches and their last computed status, held in Tauri
+/// managed state.
+#[derive(Default)]
+pub(crate) struct RepoStatusRegistry {
+    watches: Mutex<HashMap<String, StatusWatch>>,
+    latest: Arc<Mutex<HashMap<String, RepoStatus>>>,
+}
+
+const STAGED: Status = Status::INDEX_NEW
+    .union(Status::INDEX_MODIFIED)
+    .union(Status::INDEX_DELETED)
+    .union(Status::INDEX_RENAMED)
+    .union(Status::INDEX_TYPECHANGE);
+const MODIFIED: Status = Status::WT_MODIFIED
+    .union(Status::WT_DELETED)
+    .union(Status::WT_RENAMED)
+    .union(Status::WT_TYPECHANGE);
+
+pub(crate) fn compute_repo_status(repo_id: &str) -> Result<RepoStatus, String> {
+    let repo = open_repository(repo_id)?;
+    let mut options = StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .exclude_submodules(true);
+    let statuses = repo
+        .statuses(Some(&mut options))
+        .map_err(|e| format!("Failed to read status: {}", e.message()))?;
+
+    let mut status = RepoStatus {
+        repo_id: repo_id.to_string(),
+        branch: repo
+            .head()
+            .ok()
+            .and_then(|head| head.shorthand().map(str::to_string)),
+        staged: 0,
+        modified: 0,
+        untracked: 0,
+        conflicted: 0,
+        dirty: false,
+        files: Vec::new(),
+        updated_at: Utc::now().to_rfc3339(),
+    };
+    for entry in statuses.iter() {
+        let flags = entry.status();
+        let path = entry.path().unwrap_or_default().to_string();
+        let state = if flags.is_conflicted() {
+            status.conflicted += 1;
+            "conflicted"
+        } else if flags.intersects(STAGED) {
+            status.staged += 1;
+            // A file can be staged and modified again on top.
+            if flags.intersects(MODIFIED) {
+                status.modified += 1;
+            }
+            "staged"
+        } else if flags.intersects(MODIFIED) {
+            status.modified += 1;
+            "modified"
+        } else if flags.is_wt_new() {
+            status.untracked += 1;
+            "untracked"
+        } else {
+            continue;
+        };
+        status.files.push(StatusEntry {
+            path,
+            state: state.to_string(),
+        });
+    }
+    status.dirty = !status.files.is_empty();
+    Ok(status)
+}
+
+/// Changes under `.git` only matter when they can move the status: the
+/// index, HEAD, or refs. Object and log churn is ignored.
+fn affects_status(repo_dir: &Path, path: &Path) -> bool {
+    let relative = match path.strip_prefix(repo_dir) {
+        Ok(relative) => relative,
+        Err(_) => return true,
+    };
+    let mut components = relative.components();
+    if components.next() != Some(Component::Normal(".git".as_ref())) {
+        return true;
+    }
+    matches!(
+        components.next().and_then(|c| c.as_os_str().to_str()),
+        Some("index") | Some("HEAD") | Some("refs") | Some("MERGE_HEAD")
+    )
+}
+
+fn watch_loop(
+    app: tauri::AppHandle,
+    repo_id: String,
+    repo_dir: std::path::PathBuf,
+    watch: FsWatch,
+    stop: Arc<AtomicBool>,
+    latest: Arc<Mutex<HashMap<String, RepoStatus>>>,
+) {
+    let repo_dir = repo_dir.canonicalize().unwrap_or(repo_dir);
+    while !stop.load(Ordering::SeqCst) {
+        let changed = match watch.next_batch(Duration::from_millis(500), Duration::from_millis(300))
+        {
+            WatchBatch::Changed(paths) => paths,
+            WatchBatch::Idle => continue,
+            WatchBatch::Disconnected => break,
+        };
+        app.state::<DirectoryCache>().invalidate(&changed);
+        if !changed.iter().any(|path| affects_status(&repo_dir, path)) {
+            continue;
+        }
+
+        let status = match compute_repo_status(&repo_id) {
+            Ok(status) => status,
+            Err(e) => {
+                tracing::warn!("status refresh for {} failed: {}", repo_id, e);
+                continue;
+            }
+        };
+        let previous = latest
+            .lock()
+            .ok()
+            .and_then(|mut latest| latest.insert(repo_id.clone(), status.clone()));
+        let changed = match previous {
+            Some(previous) => !previous.same_counts(&status),
+            None => true,
+        };
+        if changed {
+            warn_on_err(
+                "Failed to emit repo-dirty-changed",
+                app.emit_all("repo-dirty-changed", &status),
+            );
+            api_server::forward("repo-dirty-changed", &status);
+        }
+    }
+}
+
+pub(crate) fn ensure_watch(
+    app: &tauri::AppHandle,
+    registry: &RepoStatusRegistry,
+    repo_id: &str,
+) -> Result<(), String> {
+    let mut watches = registry
+        .watches
+        .lock()
+        .map_err(|_| "Repository status registry is poisoned".to_string())?;
+    if watches.contains_key(repo_id) {
+        return Ok(());
+    }
+
+    let repo_dir = resolve_repo_path(repo_id)?;
+    let watch = FsWatch::new(&repo_dir)?;
+    let stop = Arc::new(AtomicBool::new(false));
+    {
+        let app = app.clone();
+        let repo_id = repo_id.to_string();
+        let stop = stop.clone();
+        let latest = registry.latest.clone();
+        thread::spawn(move || watch_loop(app, repo_id, repo_dir, watch, stop, latest));
+    }
+    watches.insert(repo_id.to_string(), StatusWatch { stop });
+    Ok(())
+}
+
+/// Current working-directory status of a repository. The first call starts
+/// a watcher that emits "repo-dirty-changed" whenever the counts change.
+#[tauri::command]
+pub(crate) fn get_repo_status(
+    app: tauri::AppHandle,
+    registry: tauri::State<'_, RepoStatusRegistry>,
+    repo_id: String,
+) -> Result<RepoStatus, String> {
+    let status = compute_repo_status(&repo_id)?;
+    if let Ok(mut latest) = registry.latest.lock() {
+        latest.insert(repo_id.clone(), status.clone());
+    }
+    warn_on_err(
+        "Failed to watch repository status",
+        ensure_watch(&app, &registry, &repo_id),
+    );
+    Ok(status)
+}
+
+#[tauri::command]
+pub(crate) fn stop_repo_status_watch(
+    registry: tauri::State<'_, RepoStatusRegistry>,
+    repo_id: String,
+) -> Result<(), String> {
+    let watch = registry
+        .watches
+        .lock()
+        .map_err(|_| "Repository status registry is poisoned".to_string())?
+        .remove(&repo_id)
+        .ok_or_else(|| format!("Repository status is not being watched: {}", repo_id))?;
+    watch.stop.store(true, Ordering::SeqCst);
+    if let Ok(mut latest) = registry.latest.lock() {
+        latest.remove(&repo_id);
+    }
+    Ok(())
+}
@@ -0,0 +1,237 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::audit::audited;
+use crate::commands::{
+    create_workpad, load_repository, load_workpad, read_json, resolve_repo_path, save_repository,
+    write_json,
+};
+use crate::conflicts::{apply_three_way, FileConflict};
+use crate::git::{open_repository, resolve_commit, run_git, short_sha};
+use crate::logging::warn_on_err;
+use crate::promotion::load_promotions;
+use crate::signing::run_git_signed;
+use crate::statuses::PromotionDecision;
+use crate::{get_state_dir, PromotionRecord, WorkpadState};
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct RevertResult {
+    /// "trunk" or "workpad"
+    mode: String,
+    /// The revert commit, when it was made directly on trunk.
+    commit_sha: Option<String>,
+    workpad: Option<WorkpadState>,
+    conflicts: Vec<FileConflict>,
+    record: PromotionRecord,
+}
+
+fn abbreviate(sha: &str) -> &str {
+    &sha[..7.min(sha.len())]
+}
+
+/// The workpad whose promotion brought `sha` onto trunk, if one did.
+fn promoted_by(sha: &str) -> Option<String> {
+    load_promotions()
+        .ok()?
+        .into_iter()
+        .find(|record| record.commit_hash.as_deref() == Some(sha))
+        .map(|record| record.workpad_id)
+}
+
+/// Put the new trunk commit at the head of the cached commit graph.
+fn prepend_to_commit_cache(repo_id: &str, node: Value) -> Result<(), String> {
+    let path = get_state_dir()
+        .join("commits")
+        .join(format!("{}.json", repo_id));
+    let mut data: Value = read_json(&path)?.unwrap_or_else(|| json!({ "commits": [] }));
+    match data["commits"].as_array_mut() {
+        Some(commits) => commits.insert(0, node),
+        None => data["commits"] = json!([node]),
+    }
+    write_json(&path, &data)
+}
+
+/// The branch checked out in `repo_dir`, or the commit when HEAD is detached.
+fn checked_out(repo_dir: &Path) -> Result<String, String> {
+    match run_git(repo_dir, &["symbolic-ref", "--quiet", "--short", "HEAD"]) {
+        Ok(branch) => Ok(branch.trim().to_string()),
+        Err(_) => Ok(run_git(repo_dir, &["rev-parse", "HEAD"])?
+            .trim()
+            .to_string()),
+    }
+}
+
+fn revert_on_trunk(repo_id: &str, sha: &str) -> Result<String, String> {
+    let mut state = load_repository(repo_id)?;
+    let repo_dir = resolve_repo_path(repo_id)?;
+    let dirty = run_git(
+        &repo_dir,
+        &["status", "--porcelain", "--untracked-files=no"],
+    )?;
+    if !dirty.trim().is_empty() {
+        return Err(format!(
+            "Repository {} has uncommitted changes; commit or stash them before reverting on trunk",
+            repo_id
+        ));
+    }
+    let original = checked_out(&repo_dir)?;
+
+    run_git(&repo_dir, &["checkout", &state.trunk_branch])?;
+    let reverted = run_git_signed(&repo_dir, &["revert", "--no-edit", sha]).map_err(|error| {
+        // Leave trunk as it was rather than mid-revert.
+        match run_git(&repo_dir, &["revert", "--abort"]) {
+            Ok(_) => format!("Revert of {} does not apply cleanly: {}", sha, error),
+            Err(abort) => format!(
+                "Revert of {} does not apply cleanly ({}) and could not be aborted: {}",
+                sha, error, abort
+            ),
+        }
+    });
+    let restored = if original == state.trunk_branch {
+        Ok(())
+    } else {
+        run_git(&repo_dir, &["checkout", &original])
+            .map(|_| ())
+            .map_err(|e| format!("Failed to check {} out again: {}", original, e))
+    };
+    reverted?;
+    restored?;
+
+    let repo = open_repository(repo_id)?;
+    let head = resolve_commit(&repo, &state.trunk_branch)?;
+    let head_sha = head.id().to_string();
+    prepend_to_commit_cache(
+        repo_id,
+        json!({
+            "sha": head_sha,
+            "short_sha": short_sha(head.id()),
+            "message": head.summary().unwrap_or_default(),
+            "author": head.author().name().unwrap_or_default(),
+            "timestamp": Utc::now().to_rfc3339(),
+            "parent_sha": head.parent_id(0).ok().map(|id| id.to_string()),
+            "workpad_id": null,
+            "test_status": null,
+            "ci_status": null,
+            "is_trunk": true,
+        }),
+    )?;
+
+    state.current_commit = Some(head_sha.clone());
+    state.total_commits += 1;
+    save_repository(state)?;
+    Ok(head_sha)
+}
+
+/// Open a workpad holding the inverse of `sha`, to be tested and promoted
+/// like any other change.
+fn revert_in_workpad(
+    repo_id: &str,
+    sha: &str,
+    summary: &str,
+) -> Result<(WorkpadState, Vec<FileConflict>), String> {
+    let repo_dir = resolve_repo_path(repo_id)?;
+    let diff = run_git(&repo_dir, &["diff", "--binary", sha, &format!("{}^", sha)])?;
+    if diff.trim().is_empty() {
+        return Err(format!("{} has no changes to revert", sha));
+    }
+
+    let title = format!("Revert {}: {}", abbreviate(sha), summary);
+    let workpad = create_workpad(repo_id.to_string(), title.clone())?;
+    let patch_file =
+        env::temp_dir().join(format!("sologit_revert_{}.diff", Uuid::new_v4().simple()));
+    fs::write(&patch_file, &diff).map_err(|e| format!("Failed to write temporary patch: {}", e))?;
+    let outcome = apply_three_way(&workpad, &title, &diff, &patch_file, None);
+    warn_on_err(
+        "Failed to remove temporary patch",
+        fs::remove_file(&patch_file),
+    );
+    let conflicts = outcome?;
+    Ok((load_workpad(&workpad.workpad_id)?, conflicts))
+}
+
+/// Undo a trunk commit, either with a revert commit straight on trunk
+/// (`mode` "trunk", the default) or in a new workpad ("workpad"). Either
+/// way a promotion record with decision "revert" explains the rollback.
+#[tauri::command]
+pub(crate) fn revert_commit(
+    repo_id: String,
+    sha: String,
+    mode: Option<String>,
+) -> Result<RevertResult, String> {
+    let mode = mode.unwrap_or_else(|| "trunk".to_string());
+    if mode != "trunk" && mode != "workpad" {
+        return Err(format!(
+            "Unknown revert mode {} (expected trunk or workpad)",
+            mode
+        ));
+    }
+
+    audited(
+        "revert_commit",
+        "repository",
+        Some(repo_id.clone()),
+        None,
+        move || {
+            let (sha, summary) = {
+                let repo = open_repository(&repo_id)?;
+                let commit = resolve_commit(&repo, &sha)?;
+                match commit.parent_count() {
+                    0 => return Err(format!("Cannot revert the root commit {}", sha)),
+                    1 => {}
+                    _ => return Err(format!("Cannot revert merge commit {}", sha)),
+                }
+                (
+                    commit.id().to_string(),
+                    commit.summary().unwrap_or_default().to_string(),
+                )
+            };
+
+            let (commit_sha, workpad, conflicts) = if mode == "trunk" {
+                (Some(revert_on_trunk(&repo_id, &sha)?), None, Vec::new())
+            } else {
+                let (workpad, conflicts) = revert_in_workpad(&repo_id, &sha, &summary)?;
+                (None, Some(workpad), conflicts)
+            };
+
+            let record = PromotionRecord {
+                record_id: Uuid::new_v4().to_string(),
+                repo_id: repo_id.clone(),
+                workpad_id: workpad
+                    .as_ref()
+                    .map(|workpad| workpad.workpad_id.clone())
+                    .or_else(|| promoted_by(&sha))
+                    .unwrap_or_default(),
+                decision: PromotionDecision::Revert,
+                can_promote: false,
+                auto_promote_requested: false,
+                promoted: commit_sha.is_some(),
+                commit_hash: commit_sha.clone(),
+                message: format!("Reverted {} (\"{}\")", abbreviate(&sha), summary),
+                test_run_id: None,
+                ci_status: None,
+                ci_message: None,
+                created_at: Utc::now().to_rfc3339(),
+            };
+            write_json(
+                &get_state_dir()
+                    .join("promotions")
+                    .join(format!("{}.json", record.record_id)),
+                &record,
+            )?;
+
+            Ok(RevertResult {
+                mode,
+                commit_sha,
+                workpad,
+                conflicts,
+                record,
+            })
+        },
+    )
+}
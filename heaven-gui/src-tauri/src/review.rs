@@ -0,0 +1,269 @@
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::ai::{ai_settings, parse_json_response, run_operation, ChatMessage};
+use crate::commands::{load_workpad, read_json, write_json};
+use crate::context::build_context;
+use crate::diff_summary::workpad_head;
+use crate::git::workpad_diff;
+use crate::text::clip;
+use crate::{get_state_dir, WorkpadState};
+
+/// Ordered most to least severe.
+pub(crate) const REVIEW_SEVERITIES: &[&str] = &["critical", "major", "minor", "nit"];
+/// Upper bound on diff text sent to the reviewer; larger diffs are cut.
+const MAX_DIFF_CHARS: usize = 60_000;
+
+const REVIEW_SYSTEM_PROMPT: &str = "You are a careful senior engineer reviewing a change \
+before it is merged. Report bugs, security problems, missing error handling, unclear code \
+and missing tests. Only comment on lines the diff adds or changes, and cite line numbers in \
+the new version of the file. Reply with JSON only: {\"summary\": \"...\", \"comments\": \
+[{\"path\": \"...\", \"start_line\": 1, \"end_line\": 1, \"severity\": \
+\"critical|major|minor|nit\", \"message\": \"...\", \"suggestion\": \"replacement code or \
+null\"}]}. Return an empty comments list if the change looks good.";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub(crate) struct ReviewSettings {
+    /// Add the latest AI review to the promotion gate checks.
+    pub(crate) require_review: bool,
+    /// Findings at or above this severity fail the gate.
+    pub(crate) block_severity: String,
+}
+
+impl Default for ReviewSettings {
+    fn default() -> Self {
+        ReviewSettings {
+            require_review: false,
+            block_severity: "major".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct ReviewComment {
+    path: String,
+    start_line: u32,
+    end_line: u32,
+    /// One of `REVIEW_SEVERITIES`.
+    severity: String,
+    message: String,
+    /// Replacement code for the line range, when the reviewer offered one.
+    suggestion: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct ReviewReport {
+    review_id: String,
+    workpad_id: String,
+    repo_id: String,
+    /// Branch tip that was reviewed.
+    head: String,
+    focus: Option<String>,
+    summary: String,
+    comments: Vec<ReviewComment>,
+    operation_id: String,
+    cost_usd: f64,
+    created_at: String,
+}
+
+fn reviews_dir() -> PathBuf {
+    get_state_dir().join("reviews")
+}
+
+fn severity_rank(severity: &str) -> usize {
+    REVIEW_SEVERITIES
+        .iter()
+        .position(|s| *s == severity)
+        .unwrap_or(REVIEW_SEVERITIES.len())
+}
+
+/// Turn one entry of the model's `comments` array into a comment, dropping
+/// entries without a file or message.
+fn parse_comment(value: &Value) -> Option<ReviewComment> {
+    let text = |key: &str| {
+        value[key]
+            .as_str()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+    };
+    let line = |key: &str| value[key].as_u64().map(|n| n as u32);
+    let path = text("path")?;
+    let message = text("message")?;
+    let start_line = line("start_line")
+        .or_else(|| line("line"))
+        .unwrap_or(1)
+        .max(1);
+    let severity = text("severity")
+        .map(|s| s.to_lowercase())
+        .filter(|s| REVIEW_SEVERITIES.contains(&s.as_str()))
+        .unwrap_or_else(|| "minor".to_string());
+    Some(ReviewComment {
+        path: path.trim_start_matches("b/").to_string(),
+        start_line,
+        end_line: line("end_line").unwrap_or(start_line).max(start_line),
+        severity,
+        message,
+        suggestion: text("suggestion").filter(|s| s != "null"),
+    })
+}
+
+fn load_reviews(workpad_id: &str) -> Result<Vec<ReviewReport>, String> {
+    let mut reviews = Vec::new();
+    if let Ok(entries) = fs::read_dir(reviews_dir()) {
+        for entry in entries.flatten() {
+            if let Some(review) = read_json::<ReviewReport>(&entry.path())? {
+                if review.workpad_id == workpad_id {
+                    reviews.push(review);
+                }
+            }
+        }
+    }
+    reviews.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(reviews)
+}
+
+/// Promotion gate check for the workpad's latest review, or `None` when
+/// reviews aren't required. A review of an older branch tip doesn't count.
+pub(crate) fn gate_result(
+    workpad: &WorkpadState,
+    settings: &ReviewSettings,
+) -> Option<Result<String, String>> {
+    if !settings.require_review {
+        return None;
+    }
+    let latest = match load_reviews(&workpad.workpad_id) {
+        Ok(reviews) => reviews.into_iter().next(),
+        Err(e) => return Some(Err(e)),
+    };
+    let Some(review) = latest else {
+        return Some(Err("No AI review yet".to_string()));
+    };
+    match workpad_head(workpad) {
+        Ok(head) if head == review.head => {}
+        Ok(_) => return Some(Err("Changed since the last AI review".to_string())),
+        Err(e) => return Some(Err(e)),
+    }
+    let threshold = severity_rank(&settings.block_severity);
+    let blocking = review
+        .comments
+        .iter()
+        .filter(|comment| severity_rank(&comment.severity) <= threshold)
+        .count();
+    Some(if blocking == 0 {
+        Ok(format!(
+            "Reviewed with {} comment(s), none {} or worse",
+            review.comments.len(),
+            settings.block_severity
+        ))
+    } else {
+        Err(format!(
+            "{} {} or worse review finding(s)",
+            blocking, settings.block_severity
+        ))
+    })
+}
+
+/// Have the AI provider review a workpad's diff, with the changed files
+/// and their neighbours as context, and store its findings as a review
+/// report. `focus` (e.g. "security") narrows what the reviewer looks at.
+#[tauri::command]
+pub(crate) fn request_ai_review(
+    workpad_id: String,
+    focus: Option<String>,
+) -> Result<ReviewReport, String> {
+    let workpad = load_workpad(&workpad_id)?;
+    let head = workpad_head(&workpad)?;
+    let diff = workpad_diff(&workpad)?;
+    if diff.trim().is_empty() {
+        return Err(format!("Workpad {} has no changes", workpad_id));
+    }
+    let focus = focus
+        .map(|f| f.trim().to_string())
+        .filter(|f| !f.is_empty());
+    let context = build_context(
+        &workpad,
+        focus.as_deref().unwrap_or_default(),
+        ai_settings().context_token_budget,
+    )?;
+
+    let mut request = format!("Workpad: {}\n", workpad.title);
+    if let Some(focus) = &focus {
+        request.push_str(&format!("Focus the review on: {}\n", focus));
+    }
+    request.push_str(&format!("\n## Files\n{}", context.block()));
+    let clipped = clip(&diff, MAX_DIFF_CHARS);
+    request.push_str(&format!("\n## Diff\n```diff\n{}```\n", clipped));
+    if clipped.len() < diff.len() {
+        request.push_str("[diff truncated; review what is shown]\n");
+    }
+
+    let messages = [
+        ChatMessage::system(REVIEW_SYSTEM_PROMPT),
+        ChatMessage::user(request),
+    ];
+    let (operation, completion) = run_operation(
+        Some(workpad_id.clone()),
+        "review",
+        &format!("Review {}", workpad_id),
+        &messages,
+        None,
+    )?;
+    let completion = completion.ok_or_else(|| {
+        operation
+            .error
+            .clone()
+            .unwrap_or_else(|| "AI request failed".to_string())
+    })?;
+
+    let response = parse_json_response(&completion.content)?;
+    let mut comments: Vec<ReviewComment> = response["comments"]
+        .as_array()
+        .map(|comments| comments.iter().filter_map(parse_comment).collect())
+        .unwrap_or_default();
+    comments.sort_by(|a, b| {
+        severity_rank(&a.severity)
+            .cmp(&severity_rank(&b.severity))
+            .then(a.path.cmp(&b.path))
+            .then(a.start_line.cmp(&b.start_line))
+    });
+
+    let report = ReviewReport {
+        review_id: format!("review-{}", Uuid::new_v4().simple()),
+        workpad_id,
+        repo_id: workpad.repo_id.clone(),
+        head,
+        focus,
+        summary: response["summary"]
+            .as_str()
+            .unwrap_or_default()
+            .trim()
+            .to_string(),
+        comments,
+        operation_id: operation.operation_id,
+        cost_usd: operation.cost_usd,
+        created_at: Utc::now().to_rfc3339(),
+    };
+    write_json(
+        &reviews_dir().join(format!("{}.json", report.review_id)),
+        &report,
+    )?;
+    Ok(report)
+}
+
+/// A workpad's review reports, newest first.
+#[tauri::command]
+pub(crate) fn list_reviews(workpad_id: String) -> Result<Vec<ReviewReport>, String> {
+    load_reviews(&workpad_id)
+}
+
+#[tauri::command]
+pub(crate) fn get_review(review_id: String) -> Result<ReviewReport, String> {
+    read_json(&reviews_dir().join(format!("{}.json", review_id)))?
+        .ok_or_else(|| format!("Review not found: {}", review_id))
+}
@@ -0,0 +1,404 @@
+use std::env;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::audit::{audited, summarize};
+use crate::docker;
+use crate::logging::warn_on_err;
+use crate::{get_settings, write_settings};
+
+/// Environment variables passed through to sandboxed processes by default.
+const DEFAULT_ENV_ALLOWLIST: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LANG",
+    "LC_ALL",
+    "TERM",
+    "TMPDIR",
+    "CARGO_HOME",
+    "RUSTUP_HOME",
+    "GOPATH",
+    "GOCACHE",
+    "VIRTUAL_ENV",
+];
+
+/// Receives each output line as `(stream, line)` where stream is "stdout" or "stderr".
+pub(crate) type LineSink = Arc<dyn Fn(&str, &str) + Send + Sync>;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum SandboxBackend {
+    Subprocess,
+    Firejail,
+    Bubblewrap,
+    Docker,
+}
+
+impl SandboxBackend {
+    fn binary(&self) -> Option<&'static str> {
+        match self {
+            SandboxBackend::Subprocess => None,
+            SandboxBackend::Firejail => Some("firejail"),
+            SandboxBackend::Bubblewrap => Some("bwrap"),
+            SandboxBackend::Docker => Some("docker"),
+        }
+    }
+
+    fn is_available(&self) -> bool {
+        match self.binary() {
+            None => true,
+            Some(binary) => Command::new(binary)
+                .arg("--version")
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .map(|status| status.success())
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// Per-repository execution policy for test and tool commands.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub(crate) struct SandboxConfig {
+    pub(crate) backend: SandboxBackend,
+    /// Wall-clock limit; the process is killed once exceeded.
+    pub(crate) timeout_secs: u64,
+    /// CPU time limit (RLIMIT_CPU / docker --cpus is derived separately).
+    pub(crate) cpu_seconds: Option<u64>,
+    pub(crate) memory_mb: Option<u64>,
+    /// Fractional CPU share for the docker backend.
+    pub(crate) cpus: Option<f64>,
+    pub(crate) allow_network: bool,
+    pub(crate) env_allowlist: Vec<String>,
+    pub(crate) docker_image: Option<String>,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        SandboxConfig {
+            backend: SandboxBackend::Subprocess,
+            timeout_secs: 600,
+            cpu_seconds: None,
+            memory_mb: None,
+            cpus: None,
+            allow_network: true,
+            env_allowlist: DEFAULT_ENV_ALLOWLIST
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            docker_image: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct SandboxOutput {
+    pub(crate) exit_code: Option<i32>,
+    pub(crate) stdout: String,
+    pub(crate) stderr: String,
+    pub(crate) timed_out: bool,
+    pub(crate) duration_ms: i64,
+}
+
+impl SandboxConfig {
+    pub(crate) fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs.max(1))
+    }
+}
+
+impl SandboxOutput {
+    pub(crate) fn success(&self) -> bool {
+        !self.timed_out && self.exit_code == Some(0)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct SandboxBackendInfo {
+    backend: SandboxBackend,
+    available: bool,
+}
+
+fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+/// Wrap argv in a `sh -c` that applies ulimits before exec'ing the command.
+fn with_ulimits(config: &SandboxConfig, argv: &[String]) -> Vec<String> {
+    let mut limits = Vec::new();
+    if let Some(cpu) = config.cpu_seconds {
+        limits.push(format!("ulimit -t {}", cpu));
+    }
+    if let Some(memory) = config.memory_mb {
+        limits.push(format!("ulimit -v {}", memory * 1024));
+    }
+
+    if limits.is_empty() {
+        return argv.to_vec();
+    }
+
+    let quoted: Vec<String> = argv.iter().map(|a| shell_quote(a)).collect();
+    vec![
+        "sh".to_string(),
+        "-c".to_string(),
+        format!("{} && exec {}", limits.join(" && "), quoted.join(" ")),
+    ]
+}
+
+fn build_command(config: &SandboxConfig, argv: &[String], cwd: &Path) -> Result<Command, String> {
+    if argv.is_empty() {
+        return Err("Command cannot be empty".to_string());
+    }
+
+    let cwd_str = cwd
+        .to_str()
+        .ok_or_else(|| format!("Invalid working directory: {}", cwd.display()))?;
+
+    let full_argv: Vec<String> = match config.backend {
+        SandboxBackend::Subprocess => with_ulimits(config, argv),
+        SandboxBackend::Firejail => {
+            let mut args = vec![
+                "firejail".to_string(),
+                "--quiet".to_string(),
+                "--noprofile".to_string(),
+            ];
+            if let Some(cpu) = config.cpu_seconds {
+                args.push(format!("--rlimit-cpu={}", cpu));
+            }
+            if let Some(memory) = config.memory_mb {
+                args.push(format!("--rlimit-as={}", memory * 1024 * 1024));
+            }
+            if !config.allow_network {
+                args.push("--net=none".to_string());
+            }
+            args.push("--".to_string());
+            args.extend(argv.iter().cloned());
+            args
+        }
+        SandboxBackend::Bubblewrap => {
+            let mut args = vec![
+                "bwrap".to_string(),
+                "--ro-bind".to_string(),
+                "/".to_string(),
+                "/".to_string(),
+                "--dev".to_string(),
+                "/dev".to_string(),
+                "--proc".to_string(),
+                "/proc".to_string(),
+                "--tmpfs".to_string(),
+                "/tmp".to_string(),
+                "--bind".to_string(),
+                cwd_str.to_string(),
+                cwd_str.to_string(),
+                "--chdir".to_string(),
+                cwd_str.to_string(),
+                "--unshare-all".to_string(),
+                "--die-with-parent".to_string(),
+            ];
+            if config.allow_network {
+                args.push("--share-net".to_string());
+            }
+            args.push("--".to_string());
+            args.extend(with_ulimits(config, argv));
+            args
+        }
+        SandboxBackend::Docker => {
+            return Err("Docker sandboxes run through docker::run_sandboxed".to_string())
+        }
+    };
+
+    let mut command = Command::new(&full_argv[0]);
+    command.args(&full_argv[1..]).current_dir(cwd);
+
+    command.env_clear();
+    for key in &config.env_allowlist {
+        if let Ok(value) = env::var(key) {
+            command.env(key, value);
+        }
+    }
+
+    Ok(command)
+}
+
+fn drain<R: Read + Send + 'static>(
+    reader: Option<R>,
+    stream: &'static str,
+    sink: Option<LineSink>,
+) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut collected = String::new();
+        if let Some(reader) = reader {
+            let mut reader = BufReader::new(reader);
+            let mut buffer = Vec::new();
+            loop {
+                buffer.clear();
+                match reader.read_until(b'\n', &mut buffer) {
+                    Ok(0) | Err(_) => break,
+                    Ok(_) => {
+                        let line = String::from_utf8_lossy(&buffer);
+                        if let Some(sink) = &sink {
+                            sink(stream, line.trim_end_matches(&['\r', '\n'][..]));
+                        }
+                        collected.push_str(&line);
+                    }
+                }
+            }
+        }
+        collected
+    })
+}
+
+/// Kill `child` and everything it started; it leads its own process group.
+/// The caller still reaps it.
+pub(crate) fn kill_tree(child: &mut Child) {
+    if cfg!(windows) {
+        let pid = child.id().to_string();
+        let killed = Command::new("taskkill")
+            .args(["/PID", &pid, "/T", "/F"])
+            .status();
+        warn_on_err("Failed to kill timed-out process", killed);
+    } else {
+        let group = format!("-{}", child.id());
+        let killed = Command::new("kill").args(["-KILL", &group]).status();
+        warn_on_err("Failed to kill timed-out process", killed);
+    }
+    warn_on_err("Failed to kill timed-out process", child.kill());
+}
+
+fn wait_with_timeout(child: &mut Child, timeout: Duration) -> Result<(Option<i32>, bool), String> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => return Ok((status.code(), false)),
+            Ok(None) if Instant::now() >= deadline => {
+                kill_tree(child);
+                let status = child.wait().map_err(|e| e.to_string())?;
+                return Ok((status.code(), true));
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(50)),
+            Err(e) => return Err(format!("Failed to wait for process: {}", e)),
+        }
+    }
+}
+
+/// Spawn a prepared command, capturing (and optionally streaming) its output
+/// and killing it once `timeout` elapses.
+pub(crate) fn execute(
+    mut command: Command,
+    timeout: Duration,
+    sink: Option<LineSink>,
+) -> Result<SandboxOutput, String> {
+    let program = command.get_program().to_string_lossy().to_string();
+    let started = Instant::now();
+    // Its own process group, so a timeout also stops whatever it spawned.
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to execute {}: {}", program, e))?;
+
+    let stdout = drain(child.stdout.take(), "stdout", sink.clone());
+    let stderr = drain(child.stderr.take(), "stderr", sink);
+    let (exit_code, timed_out) = wait_with_timeout(&mut child, timeout)?;
+
+    Ok(SandboxOutput {
+        exit_code,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+        timed_out,
+        duration_ms: started.elapsed().as_millis() as i64,
+    })
+}
+
+/// Run `argv` in `cwd` under the configured sandbox, enforcing its limits.
+pub(crate) fn run_sandboxed(
+    config: &SandboxConfig,
+    argv: &[String],
+    cwd: &Path,
+) -> Result<SandboxOutput, String> {
+    run_sandboxed_streaming(config, argv, cwd, None)
+}
+
+pub(crate) fn run_sandboxed_streaming(
+    config: &SandboxConfig,
+    argv: &[String],
+    cwd: &Path,
+    sink: Option<LineSink>,
+) -> Result<SandboxOutput, String> {
+    if config.backend == SandboxBackend::Docker {
+        return docker::run_sandboxed(config, argv, cwd, sink);
+    }
+    let command = build_command(config, argv, cwd)?;
+    execute(command, config.timeout(), sink)
+}
+
+pub(crate) fn sandbox_config_for(repo_id: &str) -> SandboxConfig {
+    get_settings()
+        .ok()
+        .and_then(|settings| settings.tests.sandbox.get(repo_id).cloned())
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+pub(crate) fn list_sandbox_backends() -> Vec<SandboxBackendInfo> {
+    [
+        SandboxBackend::Subprocess,
+        SandboxBackend::Firejail,
+        SandboxBackend::Bubblewrap,
+        SandboxBackend::Docker,
+    ]
+    .iter()
+    .map(|backend| SandboxBackendInfo {
+        backend: *backend,
+        available: backend.is_available(),
+    })
+    .collect()
+}
+
+#[tauri::command]
+pub(crate) fn get_sandbox_config(repo_id: String) -> Result<SandboxConfig, String> {
+    Ok(sandbox_config_for(&repo_id))
+}
+
+#[tauri::command]
+pub(crate) fn set_sandbox_config(
+    repo_id: String,
+    config: SandboxConfig,
+) -> Result<SandboxConfig, String> {
+    let before = summarize(&sandbox_config_for(&repo_id));
+    audited(
+        "set_sandbox_config",
+        "repository",
+        Some(repo_id.clone()),
+        before,
+        move || store_sandbox_config(repo_id, config),
+    )
+}
+
+fn store_sandbox_config(repo_id: String, config: SandboxConfig) -> Result<SandboxConfig, String> {
+    if config.timeout_secs == 0 {
+        return Err("Sandbox timeout must be greater than zero".to_string());
+    }
+    if !config.backend.is_available() {
+        return Err(format!(
+            "Sandbox backend {:?} is not available on this system",
+            config.backend
+        ));
+    }
+
+    let mut settings = get_settings()?;
+    settings.tests.sandbox.insert(repo_id, config.clone());
+    write_settings(&settings)?;
+    Ok(config)
+}
@@ -0,0 +1,258 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::commands::{load_repository, load_workpad};
+use crate::get_settings;
+use crate::git::{open_repository, resolve_commit};
+use crate::WorkpadState;
+
+pub(crate) const SECRET_SCAN_MODES: &[&str] = &["block", "warn", "off"];
+
+/// Lines carrying this marker are never reported.
+const ALLOW_MARKER: &str = "sologit:allow-secret";
+
+/// (rule, pattern) pairs checked against every added line.
+const PATTERNS: &[(&str, &str)] = &[
+    ("aws_access_key_id", r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b"),
+    (
+        "aws_secret_access_key",
+        r#"(?i)aws.{0,20}secret.{0,20}['"=:\s]([0-9a-zA-Z/+]{40})\b"#,
+    ),
+    ("github_token", r"\bgh[pousr]_[A-Za-z0-9]{36,}\b"),
+    ("gitlab_token", r"\bglpat-[A-Za-z0-9_-]{20,}\b"),
+    ("slack_token", r"\bxox[abposr]-[A-Za-z0-9-]{10,}\b"),
+    ("stripe_secret_key", r"\b[rs]k_live_[0-9a-zA-Z]{24,}\b"),
+    ("google_api_key", r"\bAIza[0-9A-Za-z_-]{35}\b"),
+    (
+        "private_key",
+        r"-----BEGIN (?:[A-Z]+ )*PRIVATE KEY(?: BLOCK)?-----",
+    ),
+];
+
+/// A quoted value assigned to something that sounds secret. The value is
+/// only reported when its entropy suggests it isn't a placeholder.
+const ASSIGNMENT: &str = r#"(?i)(?:api[_-]?key|secret|token|passw(?:or)?d|credential|auth)[a-z0-9_-]*["']?\s*[:=]\s*["']([^"'\s]{16,})["']"#;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub(crate) struct SecretScanSettings {
+    /// "block" fails the promotion gate on findings, "warn" only reports
+    /// them and "off" skips the scan.
+    pub(crate) mode: String,
+    /// Shannon entropy, in bits per character, above which an assigned
+    /// value counts as a likely secret.
+    pub(crate) entropy_threshold: f64,
+    /// Glob patterns for paths that are never scanned, e.g. test fixtures.
+    pub(crate) ignore_paths: Vec<String>,
+}
+
+impl Default for SecretScanSettings {
+    fn default() -> Self {
+        SecretScanSettings {
+            mode: "block".to_string(),
+            entropy_threshold: 4.0,
+            ignore_paths: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct SecretFinding {
+    rule: String,
+    path: String,
+    line: u32,
+    /// The match with all but its first and last few characters masked.
+    redacted: String,
+    entropy: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct SecretScan {
+    workpad_id: String,
+    /// Settings mode the scan ran under.
+    mode: String,
+    /// False only when `mode` is "block" and something was found.
+    passed: bool,
+    findings: Vec<SecretFinding>,
+}
+
+impl SecretScan {
+    /// A one-line summary for the promotion gate.
+    pub(crate) fn gate_result(&self) -> Result<String, String> {
+        let found = self.findings.len();
+        match (found, self.passed) {
+            _ if self.mode == "off" => Ok("Secret scanning disabled".to_string()),
+            (0, _) => Ok("No secrets found".to_string()),
+            (_, true) => Ok(format!("{} possible secret(s) (warning only)", found)),
+            (_, false) => Err(format!("{} possible secret(s) in the diff", found)),
+        }
+    }
+}
+
+fn patterns() -> &'static [(&'static str, Regex)] {
+    static PATTERNS_RE: OnceLock<Vec<(&'static str, Regex)>> = OnceLock::new();
+    PATTERNS_RE.get_or_init(|| {
+        PATTERNS
+            .iter()
+            .map(|(rule, pattern)| (*rule, Regex::new(pattern).expect("valid secret pattern")))
+            .collect()
+    })
+}
+
+fn assignment() -> &'static Regex {
+    static ASSIGNMENT_RE: OnceLock<Regex> = OnceLock::new();
+    ASSIGNMENT_RE.get_or_init(|| Regex::new(ASSIGNMENT).expect("valid assignment pattern"))
+}
+
+fn shannon_entropy(value: &str) -> f64 {
+    let mut counts: HashMap<char, usize> = HashMap::new();
+    for c in value.chars() {
+        *counts.entry(c).or_default() += 1;
+    }
+    let len = value.chars().count() as f64;
+    counts
+        .values()
+        .map(|&count| {
+            let p = count as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+fn redact(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() <= 8 {
+        return "*".repeat(chars.len());
+    }
+    let head: String = chars[..4].iter().collect();
+    let tail: String = chars[chars.len() - 2..].iter().collect();
+    format!("{}{}{}", head, "*".repeat(chars.len() - 6), tail)
+}
+
+fn scan_line(path: &str, line_no: u32, line: &str, threshold: f64) -> Vec<SecretFinding> {
+    if line.contains(ALLOW_MARKER) {
+        return Vec::new();
+    }
+    let mut findings: Vec<SecretFinding> = patterns()
+        .iter()
+        .filter_map(|(rule, pattern)| {
+            let captures = pattern.captures(line)?;
+            let matched = captures.get(1).or_else(|| captures.get(0))?.as_str();
+            Some(SecretFinding {
+                rule: rule.to_string(),
+                path: path.to_string(),
+                line: line_no,
+                redacted: redact(matched),
+                entropy: None,
+            })
+        })
+        .collect();
+    if findings.is_empty() {
+        for captures in assignment().captures_iter(line) {
+            let value = &captures[1];
+            let entropy = shannon_entropy(value);
+            if entropy >= threshold {
+                findings.push(SecretFinding {
+                    rule: "high_entropy_assignment".to_string(),
+                    path: path.to_string(),
+                    line: line_no,
+                    redacted: redact(value),
+                    entropy: Some((entropy * 100.0).round() / 100.0),
+                });
+            }
+        }
+    }
+    findings
+}
+
+/// Findings in the lines `diff` adds. Binary files are skipped.
+pub(crate) fn scan_diff(
+    diff: &git2::Diff,
+    settings: &SecretScanSettings,
+) -> Result<Vec<SecretFinding>, String> {
+    let mut ignored = globset::GlobSetBuilder::new();
+    for pattern in &settings.ignore_paths {
+        ignored.add(
+            globset::Glob::new(pattern)
+                .map_err(|e| format!("Invalid secret scan ignore pattern {}: {}", pattern, e))?,
+        );
+    }
+    let ignored = ignored.build().map_err(|e| e.to_string())?;
+    let findings: RefCell<Vec<SecretFinding>> = RefCell::new(Vec::new());
+
+    diff.foreach(
+        &mut |_, _| true,
+        None,
+        None,
+        Some(&mut |delta, _, line| {
+            if line.origin() != '+' {
+                return true;
+            }
+            let Some(path) = delta.new_file().path() else {
+                return true;
+            };
+            if ignored.is_match(path) {
+                return true;
+            }
+            let content = String::from_utf8_lossy(line.content());
+            findings.borrow_mut().extend(scan_line(
+                &path.to_string_lossy(),
+                line.new_lineno().unwrap_or_default(),
+                &content,
+                settings.entropy_threshold,
+            ));
+            true
+        }),
+    )
+    .map_err(|e| format!("Failed to scan diff: {}", e.message()))?;
+    Ok(findings.into_inner())
+}
+
+/// Scan what the workpad adds on top of trunk, since the point it forked.
+pub(crate) fn scan(workpad: &WorkpadState) -> Result<SecretScan, String> {
+    let settings = get_settings()?.git.secrets;
+    let mut report = SecretScan {
+        workpad_id: workpad.workpad_id.clone(),
+        mode: settings.mode.clone(),
+        passed: true,
+        findings: Vec::new(),
+    };
+    if settings.mode == "off" {
+        return Ok(report);
+    }
+    let trunk_branch = load_repository(&workpad.repo_id)?.trunk_branch;
+    let repo = open_repository(&workpad.repo_id)?;
+    let trunk = resolve_commit(&repo, &trunk_branch)?;
+    let tip = resolve_commit(&repo, &workpad.branch_name)?;
+    let git_err = |e: git2::Error| e.message().to_string();
+    let base = repo
+        .merge_base(trunk.id(), tip.id())
+        .and_then(|base| repo.find_commit(base))
+        .and_then(|base| base.tree())
+        .map_err(git_err)?;
+    let diff = repo
+        .diff_tree_to_tree(Some(&base), Some(&tip.tree().map_err(git_err)?), None)
+        .map_err(git_err)?;
+    report.findings = scan_diff(&diff, &settings)?;
+    report.passed = report.findings.is_empty() || settings.mode != "block";
+    Ok(report)
+}
+
+/// Fail when the secret gate is blocking and the workpad adds a secret.
+pub(crate) fn ensure_clean(workpad: &WorkpadState) -> Result<(), String> {
+    scan(workpad)?
+        .gate_result()
+        .map(|_| ())
+        .map_err(|e| format!("Promotion blocked: {}; run scan_workpad for details", e))
+}
+
+/// Check a workpad's changes for likely secrets (cloud keys, tokens,
+/// private keys, high-entropy assignments) without promoting it.
+#[tauri::command]
+pub(crate) fn scan_workpad(workpad_id: String) -> Result<SecretScan, String> {
+    scan(&load_workpad(&workpad_id)?)
+}
@@ -0,0 +1,200 @@
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::audit::audited;
+use crate::commands::{load_global_state, read_json, save_global_state, write_json};
+use crate::ledger::{self, SessionSummary};
+use crate::lifecycle::WorkpadStatus;
+use crate::statuses::TestRunStatus;
+use crate::timestamps;
+use crate::{get_state_dir, list_test_runs, list_workpads, PromotionRecord};
+
+/// A named stretch of work. The active session is the one whose
+/// `started_at` matches `GlobalState.session_start`, which is what the
+/// operations ledger is keyed by.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct Session {
+    session_id: String,
+    name: String,
+    started_at: String,
+    #[serde(default)]
+    ended_at: Option<String>,
+    /// Filled in when the session ends; computed live while it's open.
+    #[serde(default)]
+    report: Option<SessionReport>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub(crate) struct SessionReport {
+    operations: usize,
+    cost_usd: f64,
+    test_runs: usize,
+    test_runs_failed: usize,
+    commits_promoted: Vec<String>,
+    workpads_completed: Vec<String>,
+}
+
+fn sessions_dir() -> PathBuf {
+    get_state_dir().join("sessions")
+}
+
+fn session_path(session_id: &str) -> PathBuf {
+    sessions_dir().join(format!("{}.json", session_id))
+}
+
+fn load_sessions() -> Result<Vec<Session>, String> {
+    let dir = sessions_dir();
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return Ok(Vec::new());
+    };
+    let mut sessions = Vec::new();
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.extension().and_then(|s| s.to_str()) != Some("json") {
+            continue;
+        }
+        if let Some(session) = read_json::<Session>(&path)? {
+            sessions.push(session);
+        }
+    }
+    sessions.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+    Ok(sessions)
+}
+
+fn current_session() -> Result<Option<Session>, String> {
+    let session_start = load_global_state()?.session_start;
+    Ok(load_sessions()?
+        .into_iter()
+        .find(|session| session.ended_at.is_none() && session.started_at == session_start))
+}
+
+fn within(at: &str, start: DateTime<Utc>, end: DateTime<Utc>) -> bool {
+    timestamps::parse(at).is_some_and(|at| at >= start && at <= end)
+}
+
+fn promotions_between(start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(get_state_dir().join("promotions")) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| read_json::<PromotionRecord>(&entry.path()).ok().flatten())
+        .filter(|record| record.promoted && within(&record.created_at, start, end))
+        .filter_map(|record| record.commit_hash)
+        .collect()
+}
+
+/// Summarize everything that happened between the session's start and
+/// `ended_at` (now, for an open session).
+fn build_report(session: &Session) -> Result<SessionReport, String> {
+    let start = timestamps::parse(&session.started_at)
+        .ok_or_else(|| format!("Invalid session start {}", session.started_at))?;
+    let end = session
+        .ended_at
+        .as_deref()
+        .and_then(timestamps::parse)
+        .unwrap_or_else(Utc::now);
+
+    let SessionSummary {
+        total_operations,
+        total_cost_usd,
+        ..
+    } = ledger::summarize(&session.started_at)?;
+    let mut report = SessionReport {
+        operations: total_operations,
+        cost_usd: total_cost_usd,
+        commits_promoted: promotions_between(start, end),
+        ..SessionReport::default()
+    };
+    for run in list_test_runs(None, None)? {
+        if within(&run.started_at, start, end) {
+            report.test_runs += 1;
+            if matches!(run.status, TestRunStatus::Failed | TestRunStatus::Error) {
+                report.test_runs_failed += 1;
+            }
+        }
+    }
+    report.workpads_completed = list_workpads(None, None, None)?
+        .into_iter()
+        .filter(|workpad| {
+            // Promotions from before `promoted_at` was recorded fall back to
+            // the last update of a promoted workpad.
+            let promoted =
+                (workpad.status == WorkpadStatus::Promoted).then_some(workpad.updated_at.as_str());
+            workpad
+                .promoted_at
+                .as_deref()
+                .or(promoted)
+                .is_some_and(|at| within(at, start, end))
+        })
+        .map(|workpad| workpad.workpad_id)
+        .collect();
+    Ok(report)
+}
+
+/// Close `session`, storing its final report, and point the global state at
+/// a fresh unnamed session starting now.
+fn close(mut session: Session) -> Result<Session, String> {
+    session.ended_at = Some(Utc::now().to_rfc3339());
+    session.report = Some(build_report(&session)?);
+    write_json(&session_path(&session.session_id), &session)?;
+
+    let mut global = load_global_state()?;
+    global.session_start = Utc::now().to_rfc3339();
+    save_global_state(global)?;
+    Ok(session)
+}
+
+/// Start a named session, ending the current one first if there is one.
+#[tauri::command]
+pub(crate) fn start_session(name: String) -> Result<Session, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Session name cannot be empty".to_string());
+    }
+    audited("start_session", "session", None, None, move || {
+        if let Some(open) = current_session()? {
+            close(open)?;
+        }
+        let session = Session {
+            session_id: format!("session-{}", &Uuid::new_v4().simple().to_string()[..8]),
+            name,
+            started_at: Utc::now().to_rfc3339(),
+            ended_at: None,
+            report: None,
+        };
+        write_json(&session_path(&session.session_id), &session)?;
+
+        let mut global = load_global_state()?;
+        global.session_start = session.started_at.clone();
+        save_global_state(global)?;
+        Ok(session)
+    })
+}
+
+/// End the current session and return it with its end-of-session report.
+#[tauri::command]
+pub(crate) fn end_session() -> Result<Session, String> {
+    let session = current_session()?.ok_or_else(|| "No session in progress".to_string())?;
+    audited(
+        "end_session",
+        "session",
+        Some(session.session_id.clone()),
+        None,
+        move || close(session),
+    )
+}
+
+/// Every named session, newest first. The open session's report is
+/// computed on the fly.
+#[tauri::command]
+pub(crate) fn list_sessions() -> Result<Vec<Session>, String> {
+    let mut sessions = load_sessions()?;
+    for session in sessions.iter_mut().filter(|s| s.ended_at.is_none()) {
+        session.report = Some(build_report(session)?);
+    }
+    Ok(sessions)
+}
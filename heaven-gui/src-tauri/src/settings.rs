@@ -0,0 +1,567 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::Manager;
+
+use crate::ai::PROVIDERS;
+use crate::api_server::ApiSettings;
+use crate::ci::CiSettings;
+use crate::cli_config::{self, effective_settings};
+use crate::commands::{merge_json, read_json};
+use crate::cost;
+use crate::dependency_audit::{DependencyAuditSettings, SEVERITIES};
+use crate::logging::{self, warn_on_err, LEVELS};
+use crate::lsp::LspSettings;
+use crate::privacy::HISTORY_MODES;
+use crate::review::{ReviewSettings, REVIEW_SEVERITIES};
+use crate::sandbox::SandboxConfig;
+use crate::secrets::{SecretScanSettings, SECRET_SCAN_MODES};
+use crate::signing::{SigningSettings, SIGNING_FORMATS};
+use crate::tools::OUTPUT_FORMATS;
+use crate::watcher::{FsWatch, WatchBatch};
+use crate::webhooks::{self, WebhookConfig};
+use crate::{get_settings_path, get_state_dir, profiles, Settings};
+
+/// `gui_settings.json` files without this version use the flat v1 layout.
+pub(crate) const SETTINGS_SCHEMA_VERSION: u32 = 2;
+
+const THEMES: &[&str] = &["dark", "light"];
+const FONT_SIZE_RANGE: std::ops::RangeInclusive<i32> = 8..=32;
+const WATCH_POLL: Duration = Duration::from_secs(2);
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub(crate) struct EditorSettings {
+    pub(crate) theme: String,
+    pub(crate) font_size: i32,
+    pub(crate) auto_save: bool,
+    pub(crate) show_line_numbers: bool,
+    /// Hide `.gitignore`d paths from the file tree and listings.
+    pub(crate) respect_gitignore: bool,
+    pub(crate) lsp: LspSettings,
+}
+
+impl Default for EditorSettings {
+    fn default() -> Self {
+        EditorSettings {
+            theme: "dark".to_string(),
+            font_size: 14,
+            auto_save: true,
+            show_line_numbers: true,
+            respect_gitignore: true,
+            lsp: LspSettings::default(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub(crate) struct TestSettings {
+    /// Don't fail runs whose only failures are known-flaky tests.
+    pub(crate) quarantine_flaky_tests: bool,
+    /// Per-repository execution sandbox, keyed by repo_id.
+    pub(crate) sandbox: HashMap<String, SandboxConfig>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub(crate) struct GitSettings {
+    pub(crate) ci: CiSettings,
+    pub(crate) signing: SigningSettings,
+    pub(crate) secrets: SecretScanSettings,
+    pub(crate) dependency_audit: DependencyAuditSettings,
+    pub(crate) review: ReviewSettings,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub(crate) struct IntegrationSettings {
+    pub(crate) api: ApiSettings,
+    pub(crate) webhooks: Vec<WebhookConfig>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub(crate) struct WorkpadSettings {
+    /// Archive promoted workpads this many days after promotion; `None`
+    /// disables the policy.
+    pub(crate) archive_promoted_after_days: Option<u32>,
+}
+
+impl Default for WorkpadSettings {
+    fn default() -> Self {
+        WorkpadSettings {
+            archive_promoted_after_days: Some(30),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub(crate) struct TelemetrySettings {
+    pub(crate) log_level: String,
+    /// Record command latencies, test durations and AI spend locally for
+    /// the performance panel. Off unless the user opts in.
+    pub(crate) metrics_enabled: bool,
+}
+
+impl Default for TelemetrySettings {
+    fn default() -> Self {
+        TelemetrySettings {
+            log_level: "info".to_string(),
+            metrics_enabled: false,
+        }
+    }
+}
+
+pub(crate) fn default_schema_version() -> u32 {
+    SETTINGS_SCHEMA_VERSION
+}
+
+/// Check values serde can't: enums, ranges and thresholds. All problems are
+/// reported together so the settings form can highlight each one.
+pub(crate) fn validate(settings: &Settings) -> Result<(), String> {
+    let mut problems = Vec::new();
+
+    let editor = &settings.editor;
+    if !THEMES.contains(&editor.theme.as_str()) {
+        problems.push(format!("editor.theme must be one of {}", THEMES.join(", ")));
+    }
+    if !FONT_SIZE_RANGE.contains(&editor.font_size) {
+        problems.push(format!(
+            "editor.font_size must be between {} and {}",
+            FONT_SIZE_RANGE.start(),
+            FONT_SIZE_RANGE.end()
+        ));
+    }
+    let lsp = &editor.lsp;
+    let overrides = lsp.repos.values().flat_map(|servers| servers.iter());
+    for (language, server) in lsp.servers.iter().chain(overrides) {
+        if server.command.trim().is_empty() {
+            problems.push(format!(
+                "editor.lsp server command for {} cannot be empty",
+                language
+            ));
+        }
+    }
+
+    let ai = &settings.ai;
+    if !(0.0..=2.0).contains(&ai.temperature) {
+        problems.push("ai.temperature must be between 0 and 2".to_string());
+    }
+    if ai.max_tokens == 0 {
+        problems.push("ai.max_tokens must be positive".to_string());
+    }
+    if ai.request_timeout_secs == 0 {
+        problems.push("ai.request_timeout_secs must be positive".to_string());
+    }
+    if ai.summary_chunk_tokens == 0 {
+        problems.push("ai.summary_chunk_tokens must be positive".to_string());
+    }
+    if ai.max_concurrent_requests == 0 {
+        problems.push("ai.max_concurrent_requests must be positive".to_string());
+    }
+    if let Some(provider) = &ai.provider {
+        if !PROVIDERS.contains(&provider.as_str()) {
+            problems.push(format!(
+                "ai.provider must be one of {}",
+                PROVIDERS.join(", ")
+            ));
+        }
+    }
+    for (operation_type, route) in &ai.routes {
+        if let Some(provider) = &route.provider {
+            if !PROVIDERS.contains(&provider.as_str()) {
+                problems.push(format!(
+                    "ai.routes.{}.provider must be one of {}",
+                    operation_type,
+                    PROVIDERS.join(", ")
+                ));
+            }
+        }
+        if route
+            .model
+            .as_ref()
+            .is_some_and(|model| model.trim().is_empty())
+        {
+            problems.push(format!(
+                "ai.routes.{}.model cannot be empty",
+                operation_type
+            ));
+        }
+        if route.monthly_budget_usd.is_some_and(|budget| budget < 0.0) {
+            problems.push(format!(
+                "ai.routes.{}.monthly_budget_usd cannot be negative",
+                operation_type
+            ));
+        }
+    }
+    if !HISTORY_MODES.contains(&ai.history.as_str()) {
+        problems.push(format!(
+            "ai.history must be one of {}",
+            HISTORY_MODES.join(", ")
+        ));
+    }
+
+    let cost = &settings.cost;
+    if cost.monthly_budget_usd.is_some_and(|budget| budget < 0.0) {
+        problems.push("cost.monthly_budget_usd cannot be negative".to_string());
+    }
+    if !(0.0..=1.0).contains(&cost.warning_threshold) {
+        problems.push("cost.warning_threshold must be between 0 and 1".to_string());
+    }
+
+    let signing = &settings.git.signing;
+    if !SIGNING_FORMATS.contains(&signing.format.as_str()) {
+        problems.push(format!(
+            "git.signing.format must be one of {}",
+            SIGNING_FORMATS.join(", ")
+        ));
+    }
+    if signing.enabled && signing.key.as_deref().unwrap_or_default().is_empty() {
+        problems.push("git.signing.key is required when signing is enabled".to_string());
+    }
+    let secrets = &settings.git.secrets;
+    if !SECRET_SCAN_MODES.contains(&secrets.mode.as_str()) {
+        problems.push(format!(
+            "git.secrets.mode must be one of {}",
+            SECRET_SCAN_MODES.join(", ")
+        ));
+    }
+    if !(0.0..=8.0).contains(&secrets.entropy_threshold) {
+        problems.push("git.secrets.entropy_threshold must be between 0 and 8".to_string());
+    }
+    let block_severity = settings.git.dependency_audit.block_severity.as_str();
+    if !SEVERITIES.contains(&block_severity) {
+        problems.push(format!(
+            "git.dependency_audit.block_severity must be one of {}",
+            SEVERITIES.join(", ")
+        ));
+    }
+    let review_severity = settings.git.review.block_severity.as_str();
+    if !REVIEW_SEVERITIES.contains(&review_severity) {
+        problems.push(format!(
+            "git.review.block_severity must be one of {}",
+            REVIEW_SEVERITIES.join(", ")
+        ));
+    }
+
+    let tools = &settings.tools;
+    for (name, tool) in tools.formatters.iter().chain(&tools.linters) {
+        if tool.argv.is_empty() {
+            problems.push(format!("tools.{} needs a command", name));
+        }
+        if !OUTPUT_FORMATS.contains(&tool.output.as_str()) {
+            problems.push(format!(
+                "tools.{}.output must be one of {}",
+                name,
+                OUTPUT_FORMATS.join(", ")
+            ));
+        }
+    }
+
+    if settings.maintenance.interval_hours == 0 {
+        problems.push("maintenance.interval_hours must be positive".to_string());
+    }
+
+    if !LEVELS.contains(&settings.telemetry.log_level.as_str()) {
+        problems.push(format!(
+            "telemetry.log_level must be one of {}",
+            LEVELS.join(", ")
+        ));
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Invalid settings: {}", problems.join("; ")))
+    }
+}
+
+fn move_key(from: &mut Map<String, Value>, key: &str, to: &mut Map<String, Value>, as_key: &str) {
+    if let Some(value) = from.remove(key) {
+        to.insert(as_key.to_string(), value);
+    }
+}
+
+/// Rewrite a flat v1 settings object into v2 sections, folding in the
+/// keys `update_config` used to keep in `state/config.json`.
+pub(crate) fn migrate_legacy(legacy: Value) -> Value {
+    let mut flat = match legacy {
+        Value::Object(map) => map,
+        _ => Map::new(),
+    };
+    if let Ok(Some(Value::Object(config))) =
+        read_json::<Value>(&get_state_dir().join("config.json"))
+    {
+        for (key, value) in config {
+            flat.entry(key).or_insert(value);
+        }
+    }
+
+    let mut editor = Map::new();
+    for key in ["theme", "font_size", "auto_save", "show_line_numbers"] {
+        move_key(&mut flat, key, &mut editor, key);
+    }
+
+    let mut ai = match flat.remove("ai") {
+        Some(Value::Object(ai)) => ai,
+        _ => Map::new(),
+    };
+    move_key(&mut flat, "enable_ai", &mut ai, "enabled");
+    move_key(&mut flat, "default_model", &mut ai, "model");
+
+    let mut tests = Map::new();
+    move_key(
+        &mut flat,
+        "quarantine_flaky_tests",
+        &mut tests,
+        "quarantine_flaky_tests",
+    );
+    move_key(&mut flat, "sandbox", &mut tests, "sandbox");
+
+    let mut git = Map::new();
+    move_key(&mut flat, "ci", &mut git, "ci");
+
+    let mut telemetry = Map::new();
+    move_key(&mut flat, "log_level", &mut telemetry, "log_level");
+
+    let mut migrated = Map::new();
+    migrated.insert(
+        "schema_version".to_string(),
+        Value::from(SETTINGS_SCHEMA_VERSION),
+    );
+    migrated.insert("editor".to_string(), Value::Object(editor));
+    migrated.insert("ai".to_string(), Value::Object(ai));
+    if let Some(cost) = flat.remove("cost") {
+        migrated.insert("cost".to_string(), cost);
+    }
+    migrated.insert("tests".to_string(), Value::Object(tests));
+    migrated.insert("git".to_string(), Value::Object(git));
+    migrated.insert("telemetry".to_string(), Value::Object(telemetry));
+    Value::Object(migrated)
+}
+
+/// Settings as shown to the frontend, with webhook secrets redacted.
+#[tauri::command]
+pub(crate) fn get_settings() -> Result<Settings, String> {
+    let mut settings = load_settings()?;
+    webhooks::redact_secrets(&mut settings.integrations.webhooks);
+    Ok(settings)
+}
+
+/// Load settings, upgrading a v1 file in place (the original is kept as
+/// `gui_settings.v1.json`).
+pub(crate) fn load_settings() -> Result<Settings, String> {
+    let path = get_settings_path();
+    let value: Value = match read_json(&path)? {
+        Some(value) => value,
+        None => return Ok(Settings::default()),
+    };
+
+    let version = value["schema_version"].as_u64().unwrap_or(1);
+    if version >= SETTINGS_SCHEMA_VERSION as u64 {
+        return serde_json::from_value(value)
+            .map_err(|e| format!("Failed to parse settings: {}", e));
+    }
+
+    fs::copy(&path, path.with_file_name("gui_settings.v1.json"))
+        .map_err(|e| format!("Failed to back up legacy settings: {}", e))?;
+    let settings: Settings = serde_json::from_value(migrate_legacy(value))
+        .map_err(|e| format!("Failed to migrate legacy settings: {}", e))?;
+    crate::write_settings(&settings)?;
+    tracing::info!(
+        "migrated {} to settings schema v{}",
+        path.display(),
+        SETTINGS_SCHEMA_VERSION
+    );
+    Ok(settings)
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct SettingChange {
+    /// Dotted path, e.g. "editor.theme".
+    key: String,
+    before: Value,
+    after: Value,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct SettingsChangedEvent {
+    changes: Vec<SettingChange>,
+    /// Set when the edited file doesn't validate; the previous settings
+    /// stay in effect until it is fixed.
+    error: Option<String>,
+}
+
+/// The settings last seen valid, to diff edits against.
+fn applied() -> &'static Mutex<Option<Value>> {
+    static APPLIED: OnceLock<Mutex<Option<Value>>> = OnceLock::new();
+    APPLIED.get_or_init(|| Mutex::new(None))
+}
+
+fn flatten(prefix: &str, value: &Value, out: &mut BTreeMap<String, Value>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                let key = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten(&key, value, out);
+            }
+        }
+        _ => {
+            out.insert(prefix.to_string(), value.clone());
+        }
+    }
+}
+
+fn diff(before: &Value, after: &Value) -> Vec<SettingChange> {
+    let (mut old, mut new) = (BTreeMap::new(), BTreeMap::new());
+    flatten("", before, &mut old);
+    flatten("", after, &mut new);
+    let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    keys.into_iter()
+        .filter_map(|key| {
+            let before = old.get(key).cloned().unwrap_or(Value::Null);
+            let after = new.get(key).cloned().unwrap_or(Value::Null);
+            (before != after).then(|| SettingChange {
+                key: key.clone(),
+                before,
+                after,
+            })
+        })
+        .collect()
+}
+
+/// Fold keys another tool wrote to the legacy `state/config.json` into
+/// the settings file, which the watcher then picks up like any edit.
+fn import_legacy_config() -> Result<(), String> {
+    let Value::Object(mut updates) = migrate_legacy(Value::Object(Map::new())) else {
+        return Ok(());
+    };
+    updates.remove("schema_version");
+    let current = serde_json::to_value(load_settings()?)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    let mut merged = current.clone();
+    if let Value::Object(ref mut target) = merged {
+        merge_json(target, updates);
+    }
+    if merged == current {
+        return Ok(());
+    }
+    let settings: Settings = serde_json::from_value(merged)
+        .map_err(|e| format!("Invalid settings in config.json: {}", e))?;
+    crate::write_settings(&settings)
+}
+
+/// Apply what can change without a restart. Everything else (AI provider,
+/// model, budgets) is read per use and is already live.
+fn hot_apply(settings: &Settings, changes: &[SettingChange]) {
+    if changes
+        .iter()
+        .any(|change| change.key == "telemetry.log_level")
+    {
+        warn_on_err(
+            "Failed to apply log level",
+            logging::apply_level(&settings.telemetry.log_level),
+        );
+    }
+    if changes.iter().any(|change| change.key.starts_with("cost.")) {
+        cost::reset_budget_alerts();
+    }
+}
+
+fn reload(app: &tauri::AppHandle) {
+    let loaded = effective_settings().and_then(|settings| {
+        validate(&settings)?;
+        Ok(settings)
+    });
+    let event = match loaded {
+        Ok(mut settings) => {
+            // The changes are sent to the frontend.
+            webhooks::redact_secrets(&mut settings.integrations.webhooks);
+            let Ok(value) = serde_json::to_value(&settings) else {
+                return;
+            };
+            let Ok(mut applied) = applied().lock() else {
+                return;
+            };
+            let changes = diff(applied.as_ref().unwrap_or(&Value::Null), &value);
+            if changes.is_empty() {
+                return;
+            }
+            hot_apply(&settings, &changes);
+            *applied = Some(value);
+            SettingsChangedEvent {
+                changes,
+                error: None,
+            }
+        }
+        Err(error) => {
+            tracing::warn!("Ignoring invalid settings edit: {}", error);
+            SettingsChangedEvent {
+                changes: Vec::new(),
+                error: Some(error),
+            }
+        }
+    };
+    warn_on_err(
+        "Failed to emit settings-changed",
+        app.emit_all("settings-changed", event),
+    );
+}
+
+/// Follow edits to `gui_settings.json`, the CLI's `config.yaml` and
+/// `state/config.json` made outside the app, re-arming when the active
+/// profile changes.
+pub(crate) fn start_settings_watcher(app: tauri::AppHandle) {
+    if let Ok(mut applied) = applied().lock() {
+        *applied = effective_settings()
+            .ok()
+            .and_then(|settings| serde_json::to_value(settings).ok());
+    }
+    thread::spawn(move || loop {
+        let home = profiles::active_home();
+        let state_dir = get_state_dir();
+        let watch = fs::create_dir_all(&state_dir)
+            .map_err(|e| e.to_string())
+            .and_then(|_| FsWatch::shallow(&[home.as_path(), state_dir.as_path()]));
+        let watch = match watch {
+            Ok(watch) => watch,
+            Err(e) => {
+                tracing::warn!("Settings changes won't be picked up live: {}", e);
+                return;
+            }
+        };
+        let settings_path = get_settings_path();
+        let cli_config_path = cli_config::config_path();
+        let config_path = state_dir.join("config.json");
+        loop {
+            match watch.next_batch(WATCH_POLL, WATCH_DEBOUNCE) {
+                WatchBatch::Changed(paths) => {
+                    if paths.contains(&config_path) {
+                        warn_on_err("Failed to import config.json", import_legacy_config());
+                    }
+                    if paths.contains(&settings_path) || paths.contains(&cli_config_path) {
+                        reload(&app);
+                    }
+                }
+                WatchBatch::Idle if profiles::active_home() != home => {
+                    reload(&app);
+                    break;
+                }
+                WatchBatch::Idle => {}
+                WatchBatch::Disconnected => return,
+            }
+        }
+    });
+}
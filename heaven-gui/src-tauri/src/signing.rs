@@ -0,0 +1,215 @@
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use serde::{Deserialize, Serialize};
+
+use crate::commands::resolve_repo_path;
+use crate::get_settings;
+use crate::git::{open_repository, resolve_commit, run_git_with_env};
+
+pub(crate) const SIGNING_FORMATS: &[&str] = &["openpgp", "ssh"];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub(crate) struct SigningSettings {
+    pub(crate) enabled: bool,
+    /// "openpgp" (gpg) or "ssh".
+    pub(crate) format: String,
+    /// GPG key id, or the path of an SSH public key.
+    pub(crate) key: Option<String>,
+    /// `allowed_signers` file used to verify SSH signatures.
+    pub(crate) allowed_signers: Option<String>,
+}
+
+impl Default for SigningSettings {
+    fn default() -> Self {
+        SigningSettings {
+            enabled: false,
+            format: "openpgp".to_string(),
+            key: None,
+            allowed_signers: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct SignatureStatus {
+    sha: String,
+    verified: bool,
+    /// "good", "bad", "unknown_key", "expired", "expired_key", "revoked",
+    /// "unsigned" or "error"
+    status: String,
+    signer: Option<String>,
+    key: Option<String>,
+}
+
+/// `GIT_CONFIG_*` variables that make git sign (and verify) with the
+/// configured key, without touching the repository's own config. Empty
+/// when signing is off.
+pub(crate) fn git_config_env() -> Vec<(String, String)> {
+    let signing = match get_settings() {
+        Ok(settings) => settings.git.signing,
+        Err(_) => return Vec::new(),
+    };
+    let mut config = Vec::new();
+    if signing.enabled {
+        if let Some(key) = &signing.key {
+            config.push(("commit.gpgsign", "true".to_string()));
+            config.push(("tag.gpgsign", "true".to_string()));
+            config.push(("gpg.format", signing.format.clone()));
+            config.push(("user.signingkey", key.clone()));
+        }
+    }
+    if let Some(allowed) = &signing.allowed_signers {
+        config.push(("gpg.ssh.allowedSignersFile", allowed.clone()));
+    }
+
+    if config.is_empty() {
+        return Vec::new();
+    }
+
+    let mut env = vec![("GIT_CONFIG_COUNT".to_string(), config.len().to_string())];
+    for (i, (key, value)) in config.into_iter().enumerate() {
+        env.push((format!("GIT_CONFIG_KEY_{}", i), key.to_string()));
+        env.push((format!("GIT_CONFIG_VALUE_{}", i), value));
+    }
+    env
+}
+
+/// Run git with the signing configuration applied.
+pub(crate) fn run_git_signed(repo_dir: &Path, args: &[&str]) -> Result<String, String> {
+    let env = git_config_env();
+    let env: Vec<(&str, &str)> = env.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+    run_git_with_env(repo_dir, args, &env)
+}
+
+/// Detached signature over `content` made with the configured key, the way
+/// git's own `gpg.program` / `ssh-keygen -Y sign` integration makes it.
+fn sign(signing: &SigningSettings, key: &str, content: &str) -> Result<String, String> {
+    let mut command = match signing.format.as_str() {
+        "ssh" => {
+            let mut command = Command::new("ssh-keygen");
+            command.args(["-Y", "sign", "-n", "git", "-f", key]);
+            command
+        }
+        _ => {
+            let mut command = Command::new("gpg");
+            command.args(["--batch", "--armor", "--detach-sign", "--local-user", key]);
+            command
+        }
+    };
+    let program = command.get_program().to_string_lossy().to_string();
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to execute {}: {}", program, e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(content.as_bytes())
+            .map_err(|e| format!("Failed to send commit to {}: {}", program, e))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to sign with {}: {}", program, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Signing with {} failed: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    String::from_utf8(output.stdout)
+        .map_err(|_| format!("{} returned a non-UTF-8 signature", program))
+}
+
+/// Commit the index in `repo_dir` and return the new commit's id. With
+/// signing enabled the commit is built with libgit2, signed with the
+/// configured key and written with `commit_signed`; otherwise it is a plain
+/// `git commit`, so repository hooks still run.
+pub(crate) fn commit(repo_dir: &Path, message: &str) -> Result<String, String> {
+    let signing = get_settings()?.git.signing;
+    let key = match signing.key.clone() {
+        Some(key) if signing.enabled => key,
+        _ => {
+            run_git_with_env(repo_dir, &["commit", "-m", message], &[])?;
+            return run_git_with_env(repo_dir, &["rev-parse", "HEAD"], &[])
+                .map(|sha| sha.trim().to_string());
+        }
+    };
+
+    let git_err = |e: git2::Error| format!("Failed to commit: {}", e.message());
+    let repo = git2::Repository::open(repo_dir).map_err(git_err)?;
+    let tree_id = repo
+        .index()
+        .and_then(|mut index| index.write_tree())
+        .map_err(git_err)?;
+    let tree = repo.find_tree(tree_id).map_err(git_err)?;
+    let author = repo.signature().map_err(git_err)?;
+    let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+
+    let buffer = repo
+        .commit_create_buffer(&author, &author, message, &tree, &parents)
+        .map_err(git_err)?;
+    let content = buffer
+        .as_str()
+        .ok_or_else(|| "Failed to commit: commit is not UTF-8".to_string())?;
+    let signature = sign(&signing, &key, content)?;
+    let oid = repo
+        .commit_signed(content, &signature, None)
+        .map_err(git_err)?;
+
+    // `commit_signed` only writes the object; move the branch HEAD points at
+    // (possibly unborn) or a detached HEAD onto it like `git commit` would.
+    let log = format!("commit: {}", message.lines().next().unwrap_or_default());
+    let head = repo.find_reference("HEAD").map_err(git_err)?;
+    match head.symbolic_target() {
+        Some(branch) => repo.reference(branch, oid, true, &log).map(|_| ()),
+        None => repo.set_head_detached(oid),
+    }
+    .map_err(git_err)?;
+    Ok(oid.to_string())
+}
+
+fn describe(code: &str) -> &'static str {
+    match code {
+        "G" => "good",
+        "B" => "bad",
+        "U" | "E" => "unknown_key",
+        "X" => "expired",
+        "Y" => "expired_key",
+        "R" => "revoked",
+        "N" => "unsigned",
+        _ => "error",
+    }
+}
+
+/// Check the signature on `sha` for the commit graph's verified badge.
+#[tauri::command]
+pub(crate) fn verify_commit_signature(
+    repo_id: String,
+    sha: String,
+) -> Result<SignatureStatus, String> {
+    // Resolved first so `sha` can only ever name a commit, never an option.
+    let oid = resolve_commit(&open_repository(&repo_id)?, &sha)?
+        .id()
+        .to_string();
+    let repo_dir = resolve_repo_path(&repo_id)?;
+    let output = run_git_signed(
+        &repo_dir,
+        &["log", "-1", "--format=%G?%x00%GS%x00%GK", &oid, "--"],
+    )?;
+    let mut fields = output.trim_end_matches('\n').split('\0');
+    let status = describe(fields.next().unwrap_or_default());
+    let non_empty = |field: Option<&str>| field.filter(|f| !f.is_empty()).map(str::to_string);
+    Ok(SignatureStatus {
+        sha,
+        verified: status == "good",
+        status: status.to_string(),
+        signer: non_empty(fields.next()),
+        key: non_empty(fields.next()),
+    })
+}
@@ -0,0 +1,219 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::audit::audited;
+use crate::commands::{load_workpad, read_json, workpad_checkout_dir, write_json};
+use crate::get_state_dir;
+use crate::git::{run_git, run_git_with_env};
+use crate::logging::warn_on_err;
+
+/// Point-in-time capture of a workpad's working tree, including untracked
+/// (non-ignored) files, stored as a dangling commit pinned by a ref.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct WorkpadSnapshot {
+    snapshot_id: String,
+    workpad_id: String,
+    repo_id: String,
+    label: String,
+    /// Commit object holding the captured tree.
+    commit_sha: String,
+    /// HEAD of the checkout when the snapshot was taken.
+    base_commit: String,
+    files_changed: Vec<String>,
+    created_at: String,
+}
+
+fn snapshot_path(snapshot_id: &str) -> PathBuf {
+    get_state_dir()
+        .join("snapshots")
+        .join(format!("{}.json", snapshot_id))
+}
+
+fn snapshot_ref(snapshot_id: &str) -> String {
+    format!("refs/sologit/snapshots/{}", snapshot_id)
+}
+
+fn lines(output: &str) -> impl Iterator<Item = &str> {
+    output.split('\0').filter(|line| !line.is_empty())
+}
+
+/// Write the whole working tree into a tree object via a throwaway index so
+/// the real index and HEAD are left untouched.
+fn capture_tree(checkout: &Path, scratch_index: &Path) -> Result<String, String> {
+    let index = scratch_index
+        .to_str()
+        .ok_or_else(|| "Invalid scratch index path".to_string())?;
+    let env = [("GIT_INDEX_FILE", index)];
+    // Seed from HEAD so tracked-but-ignored files are kept.
+    run_git_with_env(checkout, &["read-tree", "HEAD"], &env)?;
+    run_git_with_env(checkout, &["add", "-A", "."], &env)?;
+    Ok(run_git_with_env(checkout, &["write-tree"], &env)?
+        .trim()
+        .to_string())
+}
+
+#[tauri::command]
+pub(crate) fn snapshot_workpad(
+    workpad_id: String,
+    label: Option<String>,
+) -> Result<WorkpadSnapshot, String> {
+    audited(
+        "snapshot_workpad",
+        "workpad",
+        Some(workpad_id.clone()),
+        None,
+        move || take_snapshot(workpad_id, label),
+    )
+}
+
+fn take_snapshot(workpad_id: String, label: Option<String>) -> Result<WorkpadSnapshot, String> {
+    let workpad = load_workpad(&workpad_id)?;
+    let checkout = workpad_checkout_dir(&workpad)?;
+    let snapshot_id = format!("snap-{}", Uuid::new_v4().simple());
+
+    let head = run_git(&checkout, &["rev-parse", "HEAD"])?
+        .trim()
+        .to_string();
+    let scratch_index = get_state_dir()
+        .join("snapshots")
+        .join(format!("{}.index", snapshot_id));
+    if let Some(parent) = scratch_index.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let tree = capture_tree(&checkout, &scratch_index);
+    warn_on_err(
+        "Failed to remove scratch index",
+        fs::remove_file(&scratch_index),
+    );
+    let tree = tree?;
+
+    let label = label
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .unwrap_or_else(|| format!("Snapshot {}", Utc::now().format("%Y-%m-%d %H:%M:%S")));
+    let message = format!("sologit snapshot: {}", label);
+    let commit_sha = run_git(
+        &checkout,
+        &["commit-tree", &tree, "-p", &head, "-m", &message],
+    )?
+    .trim()
+    .to_string();
+    run_git(
+        &checkout,
+        &["update-ref", &snapshot_ref(&snapshot_id), &commit_sha],
+    )?;
+
+    let files_changed = lines(&run_git(
+        &checkout,
+        &["diff", "--name-only", "-z", &head, &commit_sha],
+    )?)
+    .map(|s| s.to_string())
+    .collect();
+
+    let snapshot = WorkpadSnapshot {
+        snapshot_id,
+        workpad_id,
+        repo_id: workpad.repo_id.clone(),
+        label,
+        commit_sha,
+        base_commit: head,
+        files_changed,
+        created_at: Utc::now().to_rfc3339(),
+    };
+    write_json(&snapshot_path(&snapshot.snapshot_id), &snapshot)?;
+    Ok(snapshot)
+}
+
+#[tauri::command]
+pub(crate) fn list_snapshots(workpad_id: String) -> Result<Vec<WorkpadSnapshot>, String> {
+    let dir = get_state_dir().join("snapshots");
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut snapshots = Vec::new();
+    for entry in fs::read_dir(&dir).map_err(|e| e.to_string())? {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("json") {
+            continue;
+        }
+        if let Some(snapshot) = read_json::<WorkpadSnapshot>(&path)? {
+            if snapshot.workpad_id == workpad_id {
+                snapshots.push(snapshot);
+            }
+        }
+    }
+
+    snapshots.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(snapshots)
+}
+
+/// Make the workpad's working tree match a snapshot exactly: restore its
+/// files and remove files that did not exist when it was taken. HEAD and
+/// test history are left alone.
+#[tauri::command]
+pub(crate) fn restore_snapshot(snapshot_id: String) -> Result<WorkpadSnapshot, String> {
+    audited(
+        "restore_snapshot",
+        "snapshot",
+        Some(snapshot_id.clone()),
+        None,
+        move || restore_from_snapshot(snapshot_id),
+    )
+}
+
+fn restore_from_snapshot(snapshot_id: String) -> Result<WorkpadSnapshot, String> {
+    let snapshot: WorkpadSnapshot = read_json(&snapshot_path(&snapshot_id))?
+        .ok_or_else(|| format!("Snapshot not found: {}", snapshot_id))?;
+    let workpad = load_workpad(&snapshot.workpad_id)?;
+    let checkout = workpad_checkout_dir(&workpad)?;
+
+    let snapshot_files: HashSet<String> = lines(&run_git(
+        &checkout,
+        &["ls-tree", "-r", "-z", "--name-only", &snapshot.commit_sha],
+    )?)
+    .map(|s| s.to_string())
+    .collect();
+    let current_files: Vec<String> = lines(&run_git(
+        &checkout,
+        &[
+            "ls-files",
+            "-z",
+            "--cached",
+            "--others",
+            "--exclude-standard",
+        ],
+    )?)
+    .map(|s| s.to_string())
+    .collect();
+
+    run_git(
+        &checkout,
+        &[
+            "restore",
+            "--source",
+            &snapshot.commit_sha,
+            "--worktree",
+            "--",
+            ".",
+        ],
+    )?;
+
+    for file in current_files {
+        if !snapshot_files.contains(&file) {
+            let path = checkout.join(&file);
+            if path.is_file() {
+                fs::remove_file(&path)
+                    .map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+            }
+        }
+    }
+
+    Ok(snapshot)
+}
@@ -0,0 +1,272 @@
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::audit::audited;
+use crate::diff::{structure_diff, FileDiff};
+use crate::git::open_repository;
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct WorkingDiff {
+    /// HEAD against the index.
+    staged: Vec<FileDiff>,
+    /// The index against the working tree, untracked files included.
+    unstaged: Vec<FileDiff>,
+}
+
+struct HunkLine {
+    origin: char,
+    /// Raw bytes including the line ending, if the line has one.
+    content: Vec<u8>,
+}
+
+fn git_err(e: git2::Error) -> String {
+    e.message().to_string()
+}
+
+fn options(path: Option<&str>) -> git2::DiffOptions {
+    let mut options = git2::DiffOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .show_untracked_content(true);
+    if let Some(path) = path {
+        options.pathspec(path).disable_pathspec_match(true);
+    }
+    options
+}
+
+fn staged_diff<'r>(
+    repo: &'r git2::Repository,
+    path: Option<&str>,
+) -> Result<git2::Diff<'r>, String> {
+    // An unborn branch has no HEAD tree; everything in the index is new.
+    let head = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+    repo.diff_tree_to_index(head.as_ref(), None, Some(&mut options(path)))
+        .map_err(git_err)
+}
+
+fn unstaged_diff<'r>(
+    repo: &'r git2::Repository,
+    path: Option<&str>,
+) -> Result<git2::Diff<'r>, String> {
+    repo.diff_index_to_workdir(None, Some(&mut options(path)))
+        .map_err(git_err)
+}
+
+fn working_diff(repo: &git2::Repository) -> Result<WorkingDiff, String> {
+    Ok(WorkingDiff {
+        staged: structure_diff(&staged_diff(repo, None)?)?,
+        unstaged: structure_diff(&unstaged_diff(repo, None)?)?,
+    })
+}
+
+/// Rebuild `base` with the selected lines of one hunk applied. Lines from
+/// `base_origin` exist in `base` and are dropped when selected; lines of the
+/// other side are inserted when selected. Unselected changes stay as they
+/// were in `base`.
+fn rebuild(
+    base: &[u8],
+    start: u32,
+    count: u32,
+    lines: &[HunkLine],
+    selected: impl Fn(usize) -> bool,
+    base_origin: char,
+) -> Result<Vec<u8>, String> {
+    let base_lines: Vec<&[u8]> = base.split_inclusive(|b| *b == b'\n').collect();
+    // A hunk that adds to an empty range starts after line `start`.
+    let skip = (if count == 0 { start } else { start - 1 }) as usize;
+    let end = skip + count as usize;
+    if end > base_lines.len() {
+        return Err("The diff changed since it was loaded; refresh and try again".to_string());
+    }
+
+    let mut out: Vec<u8> = base_lines[..skip].concat();
+    for (i, line) in lines.iter().enumerate() {
+        let keep = match line.origin {
+            ' ' => true,
+            origin if origin == base_origin => !selected(i),
+            _ => selected(i),
+        };
+        if keep {
+            out.extend_from_slice(&line.content);
+        }
+    }
+    out.extend(base_lines[end..].concat());
+    Ok(out)
+}
+
+/// Apply hunk `hunk_index` of the diff for `path` to the index, forwards
+/// (staging) or backwards (unstaging). `lines` picks individual lines by
+/// their index within the hunk; all of them by default.
+fn update_index(
+    repo: &git2::Repository,
+    path: &str,
+    hunk_index: usize,
+    lines: Option<Vec<usize>>,
+    stage: bool,
+) -> Result<(), String> {
+    let diff = if stage {
+        unstaged_diff(repo, Some(path))?
+    } else {
+        staged_diff(repo, Some(path))?
+    };
+    let delta = diff.deltas().next().ok_or_else(|| {
+        format!(
+            "No {} changes in {}",
+            if stage { "unstaged" } else { "staged" },
+            path
+        )
+    })?;
+    let status = delta.status();
+    // The index side: old for index-to-workdir, new for HEAD-to-index.
+    let (index_file, other_file) = if stage {
+        (delta.old_file(), delta.new_file())
+    } else {
+        (delta.new_file(), delta.old_file())
+    };
+    let mode = if other_file.mode() == git2::FileMode::BlobExecutable
+        || index_file.mode() == git2::FileMode::BlobExecutable
+    {
+        0o100755
+    } else {
+        0o100644
+    };
+    let index_blob = index_file.id();
+
+    let binary = || format!("{} is binary; stage the whole file instead", path);
+    if delta.flags().is_binary() {
+        return Err(binary());
+    }
+    let patch = git2::Patch::from_diff(&diff, 0)
+        .map_err(git_err)?
+        .ok_or_else(binary)?;
+    let (start, count, line_count) = {
+        let (hunk, line_count) = patch
+            .hunk(hunk_index)
+            .map_err(|_| format!("{} has no hunk {}", path, hunk_index))?;
+        if stage {
+            (hunk.old_start(), hunk.old_lines(), line_count)
+        } else {
+            (hunk.new_start(), hunk.new_lines(), line_count)
+        }
+    };
+    // Only the lines structure_diff reports, so indices match what the GUI
+    // shows; the "no newline at end of file" markers are dropped.
+    let mut hunk_lines = Vec::new();
+    for i in 0..line_count {
+        let line = patch.line_in_hunk(hunk_index, i).map_err(git_err)?;
+        if matches!(line.origin(), '+' | '-' | ' ') {
+            hunk_lines.push(HunkLine {
+                origin: line.origin(),
+                content: line.content().to_vec(),
+            });
+        }
+    }
+    if let Some(lines) = &lines {
+        if let Some(bad) = lines.iter().find(|&&i| i >= hunk_lines.len()) {
+            return Err(format!("Hunk {} has no line {}", hunk_index, bad));
+        }
+    }
+    let selected = |i: usize| match &lines {
+        Some(lines) => lines.contains(&i),
+        None => true,
+    };
+    let whole_hunk = lines.is_none();
+    let single_hunk = patch.num_hunks() == 1;
+
+    let base = if index_blob.is_zero() {
+        Vec::new()
+    } else {
+        repo.find_blob(index_blob)
+            .map_err(git_err)?
+            .content()
+            .to_vec()
+    };
+    let content = rebuild(
+        &base,
+        start,
+        count,
+        &hunk_lines,
+        selected,
+        if stage { '-' } else { '+' },
+    )?;
+
+    let mut index = repo.index().map_err(git_err)?;
+    let removes_file = whole_hunk
+        && single_hunk
+        && content.is_empty()
+        && ((stage && status == git2::Delta::Deleted) || (!stage && status == git2::Delta::Added));
+    if removes_file {
+        index.remove_path(Path::new(path)).map_err(git_err)?;
+    } else {
+        let entry = match index.get_path(Path::new(path), 0) {
+            Some(entry) => entry,
+            None => git2::IndexEntry {
+                ctime: git2::IndexTime::new(0, 0),
+                mtime: git2::IndexTime::new(0, 0),
+                dev: 0,
+                ino: 0,
+                mode,
+                uid: 0,
+                gid: 0,
+                file_size: 0,
+                id: git2::Oid::zero(),
+                flags: path.len().min(0xfff) as u16,
+                flags_extended: 0,
+                path: path.as_bytes().to_vec(),
+            },
+        };
+        index.add_frombuffer(&entry, &content).map_err(git_err)?;
+    }
+    index.write().map_err(git_err)
+}
+
+#[tauri::command]
+pub(crate) fn get_working_diff(repo_id: String) -> Result<WorkingDiff, String> {
+    working_diff(&open_repository(&repo_id)?)
+}
+
+/// Stage one hunk of `path`'s unstaged changes, or just the given `lines`
+/// of it (indices into the hunk's lines as `get_working_diff` lists them).
+#[tauri::command]
+pub(crate) fn stage_hunk(
+    repo_id: String,
+    path: String,
+    hunk_index: usize,
+    lines: Option<Vec<usize>>,
+) -> Result<WorkingDiff, String> {
+    audited(
+        "stage_hunk",
+        "repository",
+        Some(repo_id.clone()),
+        None,
+        move || {
+            let repo = open_repository(&repo_id)?;
+            update_index(&repo, &path, hunk_index, lines, true)?;
+            working_diff(&repo)
+        },
+    )
+}
+
+/// Move one staged hunk of `path`, or the given `lines` of it, back out of
+/// the index. The working tree is left alone.
+#[tauri::command]
+pub(crate) fn unstage_hunk(
+    repo_id: String,
+    path: String,
+    hunk_index: usize,
+    lines: Option<Vec<usize>>,
+) -> Result<WorkingDiff, String> {
+    audited(
+        "unstage_hunk",
+        "repository",
+        Some(repo_id.clone()),
+        None,
+        move || {
+            let repo = open_repository(&repo_id)?;
+            update_index(&repo, &path, hunk_index, lines, false)?;
+            working_diff(&repo)
+        },
+    )
+}
@@ -0,0 +1,146 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::de::DeserializeOwned;
+
+use crate::get_state_dir;
+use crate::watcher::{FsWatch, WatchBatch};
+
+/// Entity directories under `state/` whose listings are cached.
+const CACHED_ENTITIES: &[&str] = &["repositories", "workpads", "test_runs", "ai_operations"];
+/// Backstop for changes the watcher misses (e.g. on network filesystems).
+const TTL: Duration = Duration::from_secs(30);
+const WATCH_POLL: Duration = Duration::from_secs(2);
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(100);
+
+struct CachedRecords {
+    loaded_at: Instant,
+    records: Arc<dyn Any + Send + Sync>,
+}
+
+/// Parsed records per entity directory, keyed by state dir so switching
+/// profiles never serves another profile's records.
+#[derive(Default)]
+pub(crate) struct StateCache {
+    entries: Mutex<HashMap<(PathBuf, &'static str), CachedRecords>>,
+}
+
+static CACHE: OnceLock<StateCache> = OnceLock::new();
+
+pub(crate) fn state_cache() -> &'static StateCache {
+    CACHE.get_or_init(StateCache::default)
+}
+
+/// Parse every `<id>.json` in `state/<entity>`. Unparseable records are
+/// quarantined by repair_state; they're skipped here.
+fn read_records<T: DeserializeOwned>(dir: &Path) -> Result<Vec<T>, String> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut records = Vec::new();
+    for entry in fs::read_dir(dir).map_err(|e| e.to_string())? {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let path = entry.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("json") {
+            continue;
+        }
+        let contents = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        match serde_json::from_str(&contents) {
+            Ok(record) => records.push(record),
+            Err(e) => tracing::warn!("Skipping {}: {}", path.display(), e),
+        }
+    }
+    Ok(records)
+}
+
+impl StateCache {
+    /// All records of `entity`, from the cache unless it is stale or
+    /// `force_refresh` is set.
+    pub(crate) fn records<T>(
+        &self,
+        entity: &'static str,
+        force_refresh: bool,
+    ) -> Result<Arc<Vec<T>>, String>
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        let dir = get_state_dir();
+        let key = (dir.clone(), entity);
+        if !force_refresh {
+            let cached = self.entries.lock().ok().and_then(|entries| {
+                entries
+                    .get(&key)
+                    .filter(|cached| cached.loaded_at.elapsed() < TTL)
+                    .and_then(|cached| cached.records.clone().downcast::<Vec<T>>().ok())
+            });
+            if let Some(records) = cached {
+                return Ok(records);
+            }
+        }
+
+        let records = Arc::new(read_records::<T>(&dir.join(entity))?);
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert(
+                key,
+                CachedRecords {
+                    loaded_at: Instant::now(),
+                    records: records.clone(),
+                },
+            );
+        }
+        Ok(records)
+    }
+
+    pub(crate) fn invalidate(&self, entity: &str) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.retain(|(_, cached), _| *cached != entity);
+        }
+    }
+
+    /// Drop the listing a changed file belongs to, if it's a cached one.
+    pub(crate) fn invalidate_path(&self, path: &Path) {
+        let state_dir = get_state_dir();
+        let entity = path
+            .strip_prefix(&state_dir)
+            .ok()
+            .and_then(|relative| relative.components().next())
+            .and_then(|component| component.as_os_str().to_str());
+        if let Some(entity) = entity.filter(|entity| CACHED_ENTITIES.contains(entity)) {
+            self.invalidate(entity);
+        }
+    }
+}
+
+/// Invalidate listings as the CLI or this app change files in the state
+/// dir, re-arming the watch when the active profile moves it.
+pub(crate) fn start_state_watcher() {
+    thread::spawn(|| loop {
+        let dir = get_state_dir();
+        let watch = match FsWatch::new(&dir) {
+            Ok(watch) => watch,
+            Err(e) => {
+                tracing::warn!("State cache falls back to its TTL: {}", e);
+                return;
+            }
+        };
+        loop {
+            match watch.next_batch(WATCH_POLL, WATCH_DEBOUNCE) {
+                WatchBatch::Changed(paths) => {
+                    for path in paths {
+                        state_cache().invalidate_path(&path);
+                    }
+                }
+                WatchBatch::Idle if get_state_dir() != dir => break,
+                WatchBatch::Idle => {}
+                WatchBatch::Disconnected => return,
+            }
+        }
+    });
+}
@@ -0,0 +1,177 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Status of a test run or of one test in it. Serialized as the Python
+/// `TestStatus` values; the upper-case aliases accept records written with
+/// the enum names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum TestRunStatus {
+    #[serde(alias = "PENDING")]
+    Pending,
+    #[serde(alias = "RUNNING")]
+    Running,
+    #[serde(alias = "PASSED")]
+    Passed,
+    #[serde(alias = "FAILED")]
+    Failed,
+    #[serde(alias = "SKIPPED")]
+    Skipped,
+    /// Runs the orchestrator gave up on.
+    #[serde(alias = "TIMEOUT")]
+    Timeout,
+    /// The runner itself broke rather than a test.
+    #[serde(alias = "ERROR")]
+    Error,
+}
+
+impl TestRunStatus {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            TestRunStatus::Pending => "pending",
+            TestRunStatus::Running => "running",
+            TestRunStatus::Passed => "passed",
+            TestRunStatus::Failed => "failed",
+            TestRunStatus::Skipped => "skipped",
+            TestRunStatus::Timeout => "timeout",
+            TestRunStatus::Error => "error",
+        }
+    }
+}
+
+impl fmt::Display for TestRunStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Status of an AI operation: the Python `AIOperationStatus` values plus
+/// "cancelled" and "pending_review", which only the GUI writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum AIOperationStatus {
+    #[serde(alias = "PENDING")]
+    Pending,
+    #[serde(alias = "PLANNING")]
+    Planning,
+    #[serde(alias = "CODING")]
+    Coding,
+    #[serde(alias = "REVIEWING")]
+    Reviewing,
+    /// A patch proposal waiting to be accepted or rejected.
+    PendingReview,
+    #[serde(alias = "COMPLETED")]
+    Completed,
+    #[serde(alias = "FAILED")]
+    Failed,
+    Cancelled,
+}
+
+impl AIOperationStatus {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            AIOperationStatus::Pending => "pending",
+            AIOperationStatus::Planning => "planning",
+            AIOperationStatus::Coding => "coding",
+            AIOperationStatus::Reviewing => "reviewing",
+            AIOperationStatus::PendingReview => "pending_review",
+            AIOperationStatus::Completed => "completed",
+            AIOperationStatus::Failed => "failed",
+            AIOperationStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl fmt::Display for AIOperationStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Outcome recorded on a promotion. The first three are the Python
+/// `PromotionDecisionType` values; "manual" marks records synthesized
+/// after a CLI promotion and "revert" the rollback of a promotion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum PromotionDecision {
+    #[serde(alias = "APPROVE")]
+    Approve,
+    #[serde(alias = "REJECT")]
+    Reject,
+    #[serde(alias = "MANUAL_REVIEW")]
+    ManualReview,
+    Manual,
+    Revert,
+}
+
+impl PromotionDecision {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            PromotionDecision::Approve => "approve",
+            PromotionDecision::Reject => "reject",
+            PromotionDecision::ManualReview => "manual_review",
+            PromotionDecision::Manual => "manual",
+            PromotionDecision::Revert => "revert",
+        }
+    }
+}
+
+impl fmt::Display for PromotionDecision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Where a queued promotion stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum QueuedPromotionStatus {
+    Queued,
+    Promoted,
+    Failed,
+    Cancelled,
+}
+
+impl QueuedPromotionStatus {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            QueuedPromotionStatus::Queued => "queued",
+            QueuedPromotionStatus::Promoted => "promoted",
+            QueuedPromotionStatus::Failed => "failed",
+            QueuedPromotionStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl fmt::Display for QueuedPromotionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Progress of a bisect; "failed" covers both errors and bisects that
+/// couldn't isolate a single commit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum BisectStatus {
+    Running,
+    Found,
+    Failed,
+}
+
+impl BisectStatus {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            BisectStatus::Running => "running",
+            BisectStatus::Found => "found",
+            BisectStatus::Failed => "failed",
+        }
+    }
+}
+
+impl fmt::Display for BisectStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
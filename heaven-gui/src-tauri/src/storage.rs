@@ -0,0 +1,181 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use ignore::WalkBuilder;
+use serde::Serialize;
+
+use crate::commands::resolve_repo_path;
+use crate::logging::get_logs_dir;
+use crate::profiles::active_home;
+use crate::{get_state_dir, list_repositories};
+
+/// Sizing a large repo takes seconds; the panel refreshes far more often.
+const CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct CategoryUsage {
+    category: String,
+    path: String,
+    bytes: u64,
+    files: u64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct RepoUsage {
+    repo_id: String,
+    name: String,
+    path: String,
+    bytes: u64,
+    files: u64,
+    /// False for repositories registered from a path outside the Solo Git
+    /// home; they don't count towards its size.
+    in_home: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct StorageUsage {
+    home: String,
+    /// Everything under the Solo Git home, including categories not listed.
+    total_bytes: u64,
+    categories: Vec<CategoryUsage>,
+    repositories: Vec<RepoUsage>,
+    computed_at: String,
+    duration_ms: u64,
+}
+
+static CACHE: Mutex<Option<(PathBuf, Instant, StorageUsage)>> = Mutex::new(None);
+
+/// Total size and file count under `path`, not following symlinks and
+/// ignoring nothing.
+fn measure(path: &Path) -> (u64, u64) {
+    if !path.exists() {
+        return (0, 0);
+    }
+    WalkBuilder::new(path)
+        .standard_filters(false)
+        .follow_links(false)
+        .build()
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|kind| kind.is_file()))
+        .fold((0, 0), |(bytes, files), entry| {
+            let size = entry.metadata().map(|meta| meta.len()).unwrap_or(0);
+            (bytes + size, files + 1)
+        })
+}
+
+fn compute() -> Result<StorageUsage, String> {
+    let started = Instant::now();
+    let home = active_home();
+    let state_dir = get_state_dir();
+    let categories = [
+        ("patches", state_dir.join("patches")),
+        ("test_runs", state_dir.join("test_runs")),
+        ("ai_operations", state_dir.join("ai_operations")),
+        ("packs", state_dir.join("packs")),
+        ("commit_cache", state_dir.join("commits")),
+        ("archive", state_dir.join("archive")),
+        ("metrics", state_dir.join("metrics")),
+        ("logs", get_logs_dir()),
+        ("backups", home.join("backups")),
+        ("worktrees", home.join("data").join("worktrees")),
+    ];
+    let repositories: Vec<(crate::RepositoryState, Option<PathBuf>)> = list_repositories(None)?
+        .into_iter()
+        .map(|repo| {
+            let path = resolve_repo_path(&repo.repo_id).ok();
+            (repo, path)
+        })
+        .collect();
+
+    // Each directory is walked on its own thread; the home total runs
+    // alongside so the slowest single tree bounds the whole call.
+    let (total, category_sizes, repo_sizes) = thread::scope(|scope| {
+        let total = scope.spawn(|| measure(&home));
+        let category_sizes: Vec<_> = categories
+            .iter()
+            .map(|(_, path)| scope.spawn(move || measure(path)))
+            .collect();
+        let repo_sizes: Vec<_> = repositories
+            .iter()
+            .map(|(_, path)| scope.spawn(move || path.as_deref().map(measure).unwrap_or((0, 0))))
+            .collect();
+        let join = |handle: thread::ScopedJoinHandle<'_, (u64, u64)>| {
+            handle.join().unwrap_or_else(|_| {
+                tracing::warn!("Storage size worker panicked");
+                (0, 0)
+            })
+        };
+        (
+            join(total),
+            category_sizes.into_iter().map(join).collect::<Vec<_>>(),
+            repo_sizes.into_iter().map(join).collect::<Vec<_>>(),
+        )
+    });
+
+    let categories = categories
+        .iter()
+        .zip(category_sizes)
+        .map(|((category, path), (bytes, files))| CategoryUsage {
+            category: category.to_string(),
+            path: path.display().to_string(),
+            bytes,
+            files,
+        })
+        .collect();
+    let mut repositories: Vec<RepoUsage> = repositories
+        .into_iter()
+        .zip(repo_sizes)
+        .map(|((repo, path), (bytes, files))| RepoUsage {
+            in_home: path.as_ref().is_some_and(|path| path.starts_with(&home)),
+            path: path
+                .map(|path| path.display().to_string())
+                .unwrap_or(repo.path),
+            repo_id: repo.repo_id,
+            name: repo.name,
+            bytes,
+            files,
+        })
+        .collect();
+    repositories.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+
+    Ok(StorageUsage {
+        home: home.display().to_string(),
+        total_bytes: total.0,
+        categories,
+        repositories,
+        computed_at: Utc::now().to_rfc3339(),
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// Drop the cached figures, e.g. after a cleanup reclaimed space.
+pub(crate) fn invalidate_storage_usage() {
+    if let Ok(mut cache) = CACHE.lock() {
+        *cache = None;
+    }
+}
+
+/// Disk usage of the Solo Git home by category and repository. Results are
+/// cached for a few minutes per profile; pass `refresh` to recompute.
+#[tauri::command]
+pub(crate) fn get_storage_usage(refresh: Option<bool>) -> Result<StorageUsage, String> {
+    let home = active_home();
+    if !refresh.unwrap_or(false) {
+        if let Ok(cache) = CACHE.lock() {
+            if let Some((cached_home, at, usage)) = cache.as_ref() {
+                if *cached_home == home && at.elapsed() < CACHE_TTL {
+                    return Ok(usage.clone());
+                }
+            }
+        }
+    }
+
+    let usage = compute()?;
+    if let Ok(mut cache) = CACHE.lock() {
+        *cache = Some((home, Instant::now(), usage.clone()));
+    }
+    Ok(usage)
+}
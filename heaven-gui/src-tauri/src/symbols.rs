@@ -0,0 +1,169 @@
+use std::fs;
+
+use serde::Serialize;
+use tree_sitter::{Language, Parser, Query, QueryCursor};
+
+use crate::files::repo_file_path;
+use crate::languages::guess_language;
+
+/// Larger files are almost always generated; outlining them isn't useful.
+const MAX_SOURCE_BYTES: u64 = 2 * 1024 * 1024;
+
+const RUST_QUERY: &str = r#"
+(function_item name: (identifier) @name) @definition.function
+(struct_item name: (type_identifier) @name) @definition.struct
+(enum_item name: (type_identifier) @name) @definition.enum
+(trait_item name: (type_identifier) @name) @definition.trait
+(impl_item type: (_) @name) @definition.impl
+(mod_item name: (identifier) @name) @definition.module
+(const_item name: (identifier) @name) @definition.constant
+(macro_definition name: (identifier) @name) @definition.macro
+"#;
+
+const PYTHON_QUERY: &str = r#"
+(class_definition name: (identifier) @name) @definition.class
+(function_definition name: (identifier) @name) @definition.function
+"#;
+
+const JAVASCRIPT_QUERY: &str = r#"
+(class_declaration name: (identifier) @name) @definition.class
+(function_declaration name: (identifier) @name) @definition.function
+(method_definition name: (property_identifier) @name) @definition.method
+(variable_declarator name: (identifier) @name value: (arrow_function)) @definition.function
+"#;
+
+const TYPESCRIPT_QUERY: &str = r#"
+(class_declaration name: (type_identifier) @name) @definition.class
+(abstract_class_declaration name: (type_identifier) @name) @definition.class
+(interface_declaration name: (type_identifier) @name) @definition.interface
+(type_alias_declaration name: (type_identifier) @name) @definition.type
+(enum_declaration name: (identifier) @name) @definition.enum
+(function_declaration name: (identifier) @name) @definition.function
+(method_definition name: (property_identifier) @name) @definition.method
+(variable_declarator name: (identifier) @name value: (arrow_function)) @definition.function
+"#;
+
+const GO_QUERY: &str = r#"
+(function_declaration name: (identifier) @name) @definition.function
+(method_declaration name: (field_identifier) @name) @definition.method
+(type_spec name: (type_identifier) @name) @definition.type
+"#;
+
+/// Kinds whose functions are reported as methods.
+const CONTAINER_KINDS: &[&str] = &["class", "impl", "trait", "interface"];
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct Symbol {
+    name: String,
+    /// "function", "method", "class", "struct", "enum", "trait", "impl",
+    /// "interface", "type", "module", "constant" or "macro"
+    kind: String,
+    /// Zero-based, end exclusive.
+    start_line: usize,
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+    /// Name of the innermost enclosing symbol, e.g. a method's class.
+    container: Option<String>,
+}
+
+fn grammar(language_id: &str) -> Option<(Language, &'static str)> {
+    Some(match language_id {
+        "rust" => (tree_sitter_rust::language(), RUST_QUERY),
+        "python" => (tree_sitter_python::language(), PYTHON_QUERY),
+        "javascript" | "javascriptreact" => (tree_sitter_javascript::language(), JAVASCRIPT_QUERY),
+        "typescript" => (
+            tree_sitter_typescript::language_typescript(),
+            TYPESCRIPT_QUERY,
+        ),
+        "typescriptreact" => (tree_sitter_typescript::language_tsx(), TYPESCRIPT_QUERY),
+        "go" => (tree_sitter_go::language(), GO_QUERY),
+        _ => return None,
+    })
+}
+
+/// Definitions in `source`, in document order. `None` when there is no
+/// grammar for `language_id`.
+pub(crate) fn outline(language_id: &str, source: &str) -> Result<Option<Vec<Symbol>>, String> {
+    let Some((language, query_source)) = grammar(language_id) else {
+        return Ok(None);
+    };
+    let mut parser = Parser::new();
+    parser
+        .set_language(language)
+        .map_err(|e| format!("Failed to load {} grammar: {}", language_id, e))?;
+    let tree = parser
+        .parse(source, None)
+        .ok_or_else(|| format!("Failed to parse {} source", language_id))?;
+    let query = Query::new(language, query_source)
+        .map_err(|e| format!("Invalid {} symbol query: {}", language_id, e))?;
+    let names = query.capture_names();
+
+    // (byte range, symbol) so containers can be found by nesting.
+    let mut found: Vec<(std::ops::Range<usize>, Symbol)> = Vec::new();
+    let mut cursor = QueryCursor::new();
+    for m in cursor.matches(&query, tree.root_node(), source.as_bytes()) {
+        let mut name = None;
+        let mut definition = None;
+        for capture in m.captures {
+            let capture_name = names[capture.index as usize].as_str();
+            if capture_name == "name" {
+                name = capture.node.utf8_text(source.as_bytes()).ok();
+            } else if let Some(kind) = capture_name.strip_prefix("definition.") {
+                definition = Some((kind, capture.node));
+            }
+        }
+        let (Some(name), Some((kind, node))) = (name, definition) else {
+            continue;
+        };
+        let (start, end) = (node.start_position(), node.end_position());
+        found.push((
+            node.byte_range(),
+            Symbol {
+                name: name.to_string(),
+                kind: kind.to_string(),
+                start_line: start.row,
+                start_column: start.column,
+                end_line: end.row,
+                end_column: end.column,
+                container: None,
+            },
+        ));
+    }
+    found.sort_by_key(|(range, _)| (range.start, std::cmp::Reverse(range.end)));
+
+    let mut symbols = Vec::with_capacity(found.len());
+    for (i, (range, symbol)) in found.iter().enumerate() {
+        let parent = found[..i].iter().rev().find(|(outer, _)| {
+            outer.start <= range.start && range.end <= outer.end && outer != range
+        });
+        let mut symbol = symbol.clone();
+        if let Some((_, parent)) = parent {
+            symbol.container = Some(parent.name.clone());
+            if symbol.kind == "function" && CONTAINER_KINDS.contains(&parent.kind.as_str()) {
+                symbol.kind = "method".to_string();
+            }
+        }
+        symbols.push(symbol);
+    }
+    Ok(Some(symbols))
+}
+
+/// Functions, classes, methods and other definitions in a repository file,
+/// for the outline sidebar.
+#[tauri::command]
+pub(crate) fn get_file_symbols(repo_id: String, file_path: String) -> Result<Vec<Symbol>, String> {
+    let path = repo_file_path(&repo_id, &file_path)?;
+    let language =
+        guess_language(&path).ok_or_else(|| format!("Unrecognised language: {}", file_path))?;
+    let size = fs::metadata(&path)
+        .map_err(|e| format!("Failed to read {}: {}", file_path, e))?
+        .len();
+    if size > MAX_SOURCE_BYTES {
+        return Err(format!("{} is too large to outline", file_path));
+    }
+    let source =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", file_path, e))?;
+    outline(language, &source)?
+        .ok_or_else(|| format!("Symbol outline isn't available for {} files", language))
+}
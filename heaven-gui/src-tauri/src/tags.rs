@@ -0,0 +1,167 @@
+use serde::Serialize;
+
+use crate::audit::audited;
+use crate::commands::{load_repository, resolve_repo_path};
+use crate::git::{format_git_time, open_repository, resolve_commit, run_git, short_sha};
+use crate::list_workpads;
+use crate::signing::run_git_signed;
+use crate::timestamps;
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct TagInfo {
+    name: String,
+    /// Commit the tag points at.
+    sha: String,
+    short_sha: String,
+    annotated: bool,
+    message: Option<String>,
+    tagger: Option<String>,
+    /// Tagger date for annotated tags, commit date otherwise.
+    created_at: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct ReleaseNotes {
+    since_tag: Option<String>,
+    workpads: Vec<String>,
+    markdown: String,
+}
+
+#[tauri::command]
+pub(crate) fn list_tags(repo_id: String) -> Result<Vec<TagInfo>, String> {
+    let repo = open_repository(&repo_id)?;
+    let names = repo
+        .tag_names(None)
+        .map_err(|e| format!("Failed to list tags: {}", e.message()))?;
+
+    let mut tags = Vec::new();
+    for name in names.iter().flatten() {
+        let Ok(object) = repo.revparse_single(&format!("refs/tags/{}", name)) else {
+            continue;
+        };
+        let Ok(commit) = object.peel_to_commit() else {
+            continue;
+        };
+        let annotation = object.as_tag();
+        let tagger = annotation.and_then(|tag| tag.tagger());
+        tags.push(TagInfo {
+            name: name.to_string(),
+            sha: commit.id().to_string(),
+            short_sha: short_sha(commit.id()),
+            annotated: annotation.is_some(),
+            message: annotation
+                .and_then(|tag| tag.message())
+                .map(|message| message.trim().to_string()),
+            tagger: tagger
+                .as_ref()
+                .and_then(|tagger| tagger.name().map(str::to_string)),
+            created_at: format_git_time(
+                tagger
+                    .as_ref()
+                    .map(|tagger| tagger.when())
+                    .unwrap_or_else(|| commit.time()),
+            ),
+        });
+    }
+    tags.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(tags)
+}
+
+/// Release notes listing the workpads promoted since `since_tag` (the most
+/// recent tag by default, or all of history when there are none).
+#[tauri::command]
+pub(crate) fn generate_changelog(
+    repo_id: String,
+    since_tag: Option<String>,
+) -> Result<ReleaseNotes, String> {
+    let tags = list_tags(repo_id.clone())?;
+    let since = match since_tag {
+        Some(name) => Some(
+            tags.into_iter()
+                .find(|tag| tag.name == name)
+                .ok_or_else(|| format!("Tag not found: {}", name))?,
+        ),
+        None => tags.into_iter().next(),
+    };
+    let cutoff = since
+        .as_ref()
+        .and_then(|tag| timestamps::parse(&tag.created_at));
+
+    let mut promoted: Vec<(String, String)> = list_workpads(Some(repo_id), None, None)?
+        .into_iter()
+        .filter_map(|workpad| {
+            let at = timestamps::parse(workpad.promoted_at.as_deref()?)?;
+            let before_cutoff = cutoff.is_some_and(|cutoff| at <= cutoff);
+            (!before_cutoff).then_some((at.to_rfc3339(), workpad.title))
+        })
+        .collect();
+    promoted.sort();
+
+    let workpads: Vec<String> = promoted.into_iter().map(|(_, title)| title).collect();
+    let mut markdown = match &since {
+        Some(tag) => format!("## Changes since {}\n\n", tag.name),
+        None => "## Changes\n\n".to_string(),
+    };
+    if workpads.is_empty() {
+        markdown.push_str("No workpads promoted.\n");
+    }
+    for title in &workpads {
+        markdown.push_str(&format!("- {}\n", title));
+    }
+    Ok(ReleaseNotes {
+        since_tag: since.map(|tag| tag.name),
+        workpads,
+        markdown,
+    })
+}
+
+/// Tag `sha` (trunk by default). A `message` makes an annotated tag; with
+/// `changelog` set and no message, the generated release notes are used.
+/// Tags are signed when commit signing is enabled.
+#[tauri::command]
+pub(crate) fn create_tag(
+    repo_id: String,
+    name: String,
+    message: Option<String>,
+    sha: Option<String>,
+    changelog: Option<bool>,
+) -> Result<TagInfo, String> {
+    let name = name.trim().to_string();
+    if !git2::Reference::is_valid_name(&format!("refs/tags/{}", name)) {
+        return Err(format!("Invalid tag name: {}", name));
+    }
+    let message = match message.filter(|message| !message.trim().is_empty()) {
+        Some(message) => Some(message),
+        None if changelog.unwrap_or(false) => {
+            Some(generate_changelog(repo_id.clone(), None)?.markdown)
+        }
+        None => None,
+    };
+
+    audited(
+        "create_tag",
+        "repository",
+        Some(repo_id.clone()),
+        None,
+        move || {
+            let trunk = load_repository(&repo_id)?.trunk_branch;
+            let target = {
+                let repo = open_repository(&repo_id)?;
+                let target = sha.unwrap_or(trunk);
+                resolve_commit(&repo, &target)?.id().to_string()
+            };
+            let repo_dir = resolve_repo_path(&repo_id)?;
+            match &message {
+                Some(message) => {
+                    run_git_signed(&repo_dir, &["tag", "-a", &name, "-m", message, &target])?
+                }
+                // Lightweight tags can't carry a signature.
+                None => run_git(&repo_dir, &["tag", &name, &target])?,
+            };
+            list_tags(repo_id)?
+                .into_iter()
+                .find(|tag| tag.name == name)
+                .ok_or_else(|| format!("Tag not found: {}", name))
+        },
+    )
+}
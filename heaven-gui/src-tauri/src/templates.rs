@@ -0,0 +1,346 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::audit::audited;
+use crate::commands::{load_workpad, read_json, workpad_checkout_dir, write_json};
+use crate::files::contained_path;
+use crate::git::workpad_diff;
+use crate::paths::is_plain_id;
+use crate::statuses::TestRunStatus;
+use crate::{get_state_dir, list_test_runs};
+
+/// Reusable prompt with `{{variable}}` placeholders.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct PromptTemplate {
+    template_id: String,
+    name: String,
+    #[serde(default)]
+    description: String,
+    body: String,
+    #[serde(default)]
+    variables: Vec<String>,
+    #[serde(default)]
+    builtin: bool,
+    created_at: String,
+    updated_at: String,
+}
+
+const BUILTIN_TEMPLATES: &[(&str, &str, &str, &str)] = &[
+    (
+        "refactor",
+        "Refactor",
+        "Refactor a file without changing behaviour",
+        "Refactor {{file_path}} to improve readability and structure without changing its \
+behaviour. {{prompt}}\n\n```\n{{file}}\n```",
+    ),
+    (
+        "add-tests",
+        "Add tests",
+        "Write tests covering the current workpad changes",
+        "Write tests that cover the following changes, following the existing test layout. \
+{{prompt}}\n\n```diff\n{{diff}}\n```",
+    ),
+    (
+        "explain",
+        "Explain",
+        "Explain what a file does",
+        "Explain what {{file_path}} does and how it fits into the project. {{prompt}}\n\n\
+```\n{{file}}\n```",
+    ),
+    (
+        "fix-error",
+        "Fix error",
+        "Diagnose an error in the context of the current changes",
+        "Explain the cause of this error and how to fix it. {{prompt}}\n\n```\n{{error}}\n```\n\n\
+Current changes:\n```diff\n{{diff}}\n```",
+    ),
+];
+
+/// What prompt and workpad templates share: saved copies live one per JSON
+/// file and override the built-in with the same ID.
+pub(crate) trait Template: DeserializeOwned {
+    fn template_id(&self) -> &str;
+    fn name(&self) -> &str;
+}
+
+impl Template for PromptTemplate {
+    fn template_id(&self) -> &str {
+        &self.template_id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Templates saved in `dir` plus the built-ins without a saved copy, by name.
+pub(crate) fn list_saved<T: Template>(dir: &Path, builtins: Vec<T>) -> Result<Vec<T>, String> {
+    let mut templates: Vec<T> = Vec::new();
+    if dir.exists() {
+        for entry in fs::read_dir(dir).map_err(|e| format!("Failed to read templates: {}", e))? {
+            let path = entry
+                .map_err(|e| format!("Failed to read templates: {}", e))?
+                .path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            if let Some(template) = read_json::<T>(&path)? {
+                templates.push(template);
+            }
+        }
+    }
+
+    for builtin in builtins {
+        if !templates
+            .iter()
+            .any(|t| t.template_id() == builtin.template_id())
+        {
+            templates.push(builtin);
+        }
+    }
+    templates.sort_by_key(|template| template.name().to_lowercase());
+    Ok(templates)
+}
+
+/// Delete the saved template at `path`. Deleting an edited built-in restores
+/// its default; `kind` names the template type in errors.
+pub(crate) fn delete_saved(
+    path: &Path,
+    template_id: &str,
+    builtin: bool,
+    kind: &str,
+) -> Result<(), String> {
+    if path.exists() {
+        return fs::remove_file(path)
+            .map_err(|e| format!("Failed to delete template {}: {}", template_id, e));
+    }
+    if builtin {
+        return Err(format!(
+            "Built-in template {} cannot be deleted",
+            template_id
+        ));
+    }
+    Err(format!("{} not found: {}", kind, template_id))
+}
+
+fn templates_dir() -> PathBuf {
+    get_state_dir().join("prompt_templates")
+}
+
+fn template_path(template_id: &str) -> Result<PathBuf, String> {
+    if !is_plain_id(template_id) {
+        return Err(format!("Invalid prompt template ID: {}", template_id));
+    }
+    Ok(templates_dir().join(format!("{}.json", template_id)))
+}
+
+/// Names of `{{variable}}` placeholders in order of first appearance.
+fn template_variables(body: &str) -> Vec<String> {
+    let mut variables = Vec::new();
+    let mut rest = body;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            break;
+        };
+        let name = after[..end].trim().to_string();
+        if !name.is_empty() && !variables.contains(&name) {
+            variables.push(name);
+        }
+        rest = &after[end + 2..];
+    }
+    variables
+}
+
+fn builtin_templates() -> Vec<PromptTemplate> {
+    BUILTIN_TEMPLATES
+        .iter()
+        .map(|(id, name, description, body)| PromptTemplate {
+            template_id: id.to_string(),
+            name: name.to_string(),
+            description: description.to_string(),
+            body: body.to_string(),
+            variables: template_variables(body),
+            builtin: true,
+            created_at: String::new(),
+            updated_at: String::new(),
+        })
+        .collect()
+}
+
+fn load_template(template_id: &str) -> Result<PromptTemplate, String> {
+    if let Some(template) = read_json(&template_path(template_id)?)? {
+        return Ok(template);
+    }
+    builtin_templates()
+        .into_iter()
+        .find(|template| template.template_id == template_id)
+        .ok_or_else(|| format!("Prompt template not found: {}", template_id))
+}
+
+pub(crate) fn substitute(body: &str, values: &HashMap<String, String>) -> String {
+    let mut rendered = String::with_capacity(body.len());
+    let mut rest = body;
+    while let Some(start) = rest.find("{{") {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            rendered.push_str(&rest[start..]);
+            return rendered;
+        };
+        let name = after[..end].trim();
+        rendered.push_str(values.get(name).map(String::as_str).unwrap_or(""));
+        rest = &after[end + 2..];
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+/// Whether `template` uses `name` and the caller left it to be derived.
+fn needs(template: &PromptTemplate, values: &HashMap<String, String>, name: &str) -> bool {
+    template.variables.iter().any(|v| v == name) && !values.contains_key(name)
+}
+
+/// Fill a template's placeholders. Caller-supplied values win; `diff`,
+/// `file` (contents of `file_path`) and `error` (latest failing test output)
+/// are derived from the workpad when not supplied.
+pub(crate) fn render_template(
+    template_id: &str,
+    workpad_id: Option<&str>,
+    mut values: HashMap<String, String>,
+) -> Result<String, String> {
+    let template = load_template(template_id)?;
+
+    if let Some(workpad) = workpad_id.map(load_workpad).transpose()? {
+        if needs(&template, &values, "diff") {
+            values.insert("diff".to_string(), workpad_diff(&workpad)?);
+        }
+        if needs(&template, &values, "file") {
+            if let Some(file_path) = values.get("file_path").cloned() {
+                let checkout = workpad_checkout_dir(&workpad)?;
+                let contents = fs::read_to_string(contained_path(&checkout, &file_path)?)
+                    .map_err(|e| format!("Failed to read {}: {}", file_path, e))?;
+                values.insert("file".to_string(), contents);
+            }
+        }
+        if needs(&template, &values, "error") {
+            let latest_error = list_test_runs(Some(workpad.workpad_id.clone()), None)?
+                .into_iter()
+                .find(|run| run.status == TestRunStatus::Failed)
+                .and_then(|run| {
+                    run.tests
+                        .into_iter()
+                        .filter(|test| test.status == TestRunStatus::Failed)
+                        .find_map(|test| test.error.or(Some(test.output)))
+                });
+            if let Some(error) = latest_error {
+                values.insert("error".to_string(), error);
+            }
+        }
+    }
+
+    let missing: Vec<&String> = template
+        .variables
+        .iter()
+        .filter(|name| name.as_str() != "prompt" && !values.contains_key(name.as_str()))
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!(
+            "Template {} is missing values for: {}",
+            template_id,
+            missing
+                .iter()
+                .map(|name| name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+
+    Ok(substitute(&template.body, &values).trim().to_string())
+}
+
+#[tauri::command]
+pub(crate) fn list_prompt_templates() -> Result<Vec<PromptTemplate>, String> {
+    list_saved(&templates_dir(), builtin_templates())
+}
+
+#[tauri::command]
+pub(crate) fn save_prompt_template(
+    template_id: Option<String>,
+    name: String,
+    description: Option<String>,
+    body: String,
+) -> Result<PromptTemplate, String> {
+    audited(
+        "save_prompt_template",
+        "prompt_template",
+        template_id.clone(),
+        None,
+        move || store_template(template_id, name, description, body),
+    )
+}
+
+fn store_template(
+    template_id: Option<String>,
+    name: String,
+    description: Option<String>,
+    body: String,
+) -> Result<PromptTemplate, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Template name cannot be empty".to_string());
+    }
+    if body.trim().is_empty() {
+        return Err("Template body cannot be empty".to_string());
+    }
+
+    let template_id = template_id
+        .filter(|id| !id.trim().is_empty())
+        .unwrap_or_else(|| format!("tpl-{}", Uuid::new_v4().simple()));
+    let path = template_path(&template_id)?;
+    let now = Utc::now().to_rfc3339();
+    let existing = load_template(&template_id).ok();
+
+    let template = PromptTemplate {
+        variables: template_variables(&body),
+        builtin: existing.as_ref().is_some_and(|t| t.builtin),
+        created_at: existing
+            .map(|t| t.created_at)
+            .filter(|created| !created.is_empty())
+            .unwrap_or_else(|| now.clone()),
+        updated_at: now,
+        template_id,
+        name,
+        description: description.unwrap_or_default(),
+        body,
+    };
+    write_json(&path, &template)?;
+    Ok(template)
+}
+
+#[tauri::command]
+pub(crate) fn delete_prompt_template(template_id: String) -> Result<(), String> {
+    audited(
+        "delete_prompt_template",
+        "prompt_template",
+        Some(template_id.clone()),
+        None,
+        move || {
+            let builtin = builtin_templates()
+                .iter()
+                .any(|template| template.template_id == template_id);
+            delete_saved(
+                &template_path(&template_id)?,
+                &template_id,
+                builtin,
+                "Prompt template",
+            )
+        },
+    )
+}
@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+use std::env;
+use std::io::{Read, Write};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::thread;
+
+use chrono::Utc;
+use portable_pty::{native_pty_system, Child, CommandBuilder, MasterPty, PtySize};
+use serde::Serialize;
+use tauri::Manager;
+use uuid::Uuid;
+
+use crate::audit::audited;
+use crate::commands::{load_workpad, resolve_repo_path, workpad_checkout_dir};
+use crate::logging::warn_on_err;
+
+const DEFAULT_ROWS: u16 = 24;
+const DEFAULT_COLS: u16 = 80;
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct TerminalInfo {
+    term_id: String,
+    repo_id: String,
+    workpad_id: Option<String>,
+    shell: String,
+    cwd: String,
+    rows: u16,
+    cols: u16,
+    opened_at: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct TerminalOutput {
+    term_id: String,
+    data: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct TerminalExit {
+    term_id: String,
+    exit_code: Option<u32>,
+}
+
+struct Terminal {
+    info: TerminalInfo,
+    master: Box<dyn MasterPty + Send>,
+    writer: Box<dyn Write + Send>,
+    child: Box<dyn Child + Send + Sync>,
+}
+
+fn terminals() -> &'static Mutex<HashMap<String, Terminal>> {
+    static TERMINALS: OnceLock<Mutex<HashMap<String, Terminal>>> = OnceLock::new();
+    TERMINALS.get_or_init(Mutex::default)
+}
+
+fn lock_terminals() -> Result<MutexGuard<'static, HashMap<String, Terminal>>, String> {
+    terminals()
+        .lock()
+        .map_err(|_| "Terminal table poisoned".to_string())
+}
+
+fn default_shell() -> String {
+    let variable = if cfg!(windows) { "COMSPEC" } else { "SHELL" };
+    env::var(variable)
+        .ok()
+        .filter(|shell| !shell.trim().is_empty())
+        .unwrap_or_else(|| {
+            if cfg!(windows) {
+                "powershell.exe".to_string()
+            } else {
+                "/bin/sh".to_string()
+            }
+        })
+}
+
+fn size(rows: u16, cols: u16) -> PtySize {
+    PtySize {
+        rows,
+        cols,
+        pixel_width: 0,
+        pixel_height: 0,
+    }
+}
+
+/// Forward PTY output as "terminal-output" events until the shell exits.
+/// Reads can split a UTF-8 sequence, so an incomplete tail is held back for
+/// the next read.
+fn pump_output(app: tauri::AppHandle, term_id: String, mut reader: Box<dyn Read + Send>) {
+    thread::spawn(move || {
+        let mut buffer = [0u8; 8192];
+        let mut pending: Vec<u8> = Vec::new();
+        loop {
+            let read = match reader.read(&mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(read) => read,
+            };
+            pending.extend_from_slice(&buffer[..read]);
+            let valid = match std::str::from_utf8(&pending) {
+                Ok(text) => text.len(),
+                // Invalid bytes mid-stream won't become valid; send them lossily.
+                Err(e) if e.error_len().is_some() => pending.len(),
+                Err(e) => e.valid_up_to(),
+            };
+            let data = String::from_utf8_lossy(&pending[..valid]).to_string();
+            pending.drain(..valid);
+            if data.is_empty() {
+                continue;
+            }
+            warn_on_err(
+                "Failed to emit terminal-output",
+                app.emit_all(
+                    "terminal-output",
+                    TerminalOutput {
+                        term_id: term_id.clone(),
+                        data,
+                    },
+                ),
+            );
+        }
+
+        // Closed terminals were removed before their shell was killed.
+        let exit_code = lock_terminals()
+            .ok()
+            .and_then(|mut terminals| terminals.remove(&term_id))
+            .and_then(|mut terminal| terminal.child.wait().ok())
+            .map(|status| status.exit_code());
+        warn_on_err(
+            "Failed to emit terminal-exit",
+            app.emit_all("terminal-exit", TerminalExit { term_id, exit_code }),
+        );
+    });
+}
+
+/// Open a shell on a pseudo-terminal in the repository checkout, or in a
+/// workpad's worktree. Output arrives as "terminal-output" events and a
+/// "terminal-exit" event follows when the shell ends.
+#[tauri::command]
+pub(crate) fn open_terminal(
+    app: tauri::AppHandle,
+    repo_id: String,
+    workpad_id: Option<String>,
+    rows: Option<u16>,
+    cols: Option<u16>,
+) -> Result<TerminalInfo, String> {
+    audited(
+        "open_terminal",
+        "repository",
+        Some(repo_id.clone()),
+        None,
+        move || {
+            let cwd = match &workpad_id {
+                Some(workpad_id) => {
+                    let workpad = load_workpad(workpad_id)?;
+                    if workpad.repo_id != repo_id {
+                        return Err(format!(
+                            "Workpad {} doesn't belong to {}",
+                            workpad_id, repo_id
+                        ));
+                    }
+                    workpad_checkout_dir(&workpad)?
+                }
+                None => resolve_repo_path(&repo_id)?,
+            };
+            let (rows, cols) = (rows.unwrap_or(DEFAULT_ROWS), cols.unwrap_or(DEFAULT_COLS));
+            let pair = native_pty_system()
+                .openpty(size(rows, cols))
+                .map_err(|e| format!("Failed to open a terminal: {}", e))?;
+
+            let shell = default_shell();
+            let mut command = CommandBuilder::new(&shell);
+            command.cwd(&cwd);
+            command.env("TERM", "xterm-256color");
+            let child = pair
+                .slave
+                .spawn_command(command)
+                .map_err(|e| format!("Failed to start {}: {}", shell, e))?;
+            // The shell holds its own handle; ours would keep the PTY open
+            // after it exits.
+            drop(pair.slave);
+            let reader = pair
+                .master
+                .try_clone_reader()
+                .map_err(|e| format!("Failed to read from terminal: {}", e))?;
+            let writer = pair
+                .master
+                .take_writer()
+                .map_err(|e| format!("Failed to write to terminal: {}", e))?;
+
+            let info = TerminalInfo {
+                term_id: format!("term-{}", Uuid::new_v4().simple()),
+                repo_id,
+                workpad_id,
+                shell,
+                cwd: cwd.display().to_string(),
+                rows,
+                cols,
+                opened_at: Utc::now().to_rfc3339(),
+            };
+            lock_terminals()?.insert(
+                info.term_id.clone(),
+                Terminal {
+                    info: info.clone(),
+                    master: pair.master,
+                    writer,
+                    child,
+                },
+            );
+            pump_output(app, info.term_id.clone(), reader);
+            Ok(info)
+        },
+    )
+}
+
+/// Send keystrokes or pasted text to a terminal.
+#[tauri::command]
+pub(crate) fn write_terminal(term_id: String, data: String) -> Result<(), String> {
+    let mut terminals = lock_terminals()?;
+    let terminal = terminals
+        .get_mut(&term_id)
+        .ok_or_else(|| format!("Terminal not found: {}", term_id))?;
+    terminal
+        .writer
+        .write_all(data.as_bytes())
+        .and_then(|_| terminal.writer.flush())
+        .map_err(|e| format!("Failed to write to terminal: {}", e))
+}
+
+#[tauri::command]
+pub(crate) fn resize_terminal(term_id: String, rows: u16, cols: u16) -> Result<(), String> {
+    if rows == 0 || cols == 0 {
+        return Err("Terminal size must be positive".to_string());
+    }
+    let mut terminals = lock_terminals()?;
+    let terminal = terminals
+        .get_mut(&term_id)
+        .ok_or_else(|| format!("Terminal not found: {}", term_id))?;
+    terminal
+        .master
+        .resize(size(rows, cols))
+        .map_err(|e| format!("Failed to resize terminal: {}", e))?;
+    terminal.info.rows = rows;
+    terminal.info.cols = cols;
+    Ok(())
+}
+
+/// Kill a terminal's shell. Its "terminal-exit" event still follows.
+#[tauri::command]
+pub(crate) fn close_terminal(term_id: String) -> Result<(), String> {
+    let mut terminal = lock_terminals()?
+        .remove(&term_id)
+        .ok_or_else(|| format!("Terminal not found: {}", term_id))?;
+    warn_on_err("Failed to kill terminal shell", terminal.child.kill());
+    warn_on_err("Failed to reap terminal shell", terminal.child.wait());
+    Ok(())
+}
+
+/// Whether a terminal is open in `workpad_id`'s checkout.
+pub(crate) fn is_open_for(workpad_id: &str) -> Result<bool, String> {
+    Ok(lock_terminals()?
+        .values()
+        .any(|terminal| terminal.info.workpad_id.as_deref() == Some(workpad_id)))
+}
+
+#[tauri::command]
+pub(crate) fn list_terminals() -> Result<Vec<TerminalInfo>, String> {
+    let mut terminals: Vec<TerminalInfo> = lock_terminals()?
+        .values()
+        .map(|terminal| terminal.info.clone())
+        .collect();
+    terminals.sort_by(|a, b| a.opened_at.cmp(&b.opened_at));
+    Ok(terminals)
+}
@@ -0,0 +1,61 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+use tempfile::TempDir;
+
+use crate::paths::{self, Paths};
+use crate::{write_settings, Settings};
+
+const FAKE_CLI: &str = include_str!("../tests/fixtures/fake_evogitctl.py");
+
+/// Paths are process-wide, so environments are handed out one at a time.
+static EXCLUSIVE: Mutex<()> = Mutex::new(());
+
+/// A throwaway Solo Git home with a scripted `evogitctl` and the mock AI
+/// provider. Every command run while it is alive reads and writes only
+/// inside its temp directory; dropping it restores the real paths.
+pub(crate) struct TestEnv {
+    dir: TempDir,
+    _exclusive: MutexGuard<'static, ()>,
+}
+
+impl TestEnv {
+    pub(crate) fn new() -> TestEnv {
+        let exclusive = EXCLUSIVE.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        let cli = dir.path().join("evogitctl");
+        fs::write(&cli, FAKE_CLI).expect("failed to write fake evogitctl");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&cli, fs::Permissions::from_mode(0o755))
+                .expect("failed to make fake evogitctl executable");
+        }
+        paths::set_override(Some(Paths::new(dir.path().join("home"), cli)));
+
+        let mut settings = Settings::default();
+        settings.ai.provider = Some("mock".to_string());
+        write_settings(&settings).expect("failed to write test settings");
+
+        TestEnv {
+            dir,
+            _exclusive: exclusive,
+        }
+    }
+
+    pub(crate) fn home(&self) -> PathBuf {
+        self.dir.path().join("home")
+    }
+
+    /// Whether `path` lies inside this environment.
+    pub(crate) fn contains(&self, path: &Path) -> bool {
+        path.starts_with(self.dir.path())
+    }
+}
+
+impl Drop for TestEnv {
+    fn drop(&mut self) {
+        paths::set_override(None);
+    }
+}
@@ -0,0 +1,1056 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::api_server;
+use crate::audit::audited;
+use crate::commands::{
+    load_workpad, resolve_repo_path, save_workpad, workpad_checkout_dir, write_json,
+};
+use crate::coverage;
+use crate::docker;
+use crate::flaky::flaky_test_ids;
+use crate::git::run_git;
+use crate::lifecycle::{self, WorkpadStatus};
+use crate::logging::warn_on_err;
+use crate::metrics;
+use crate::notifications;
+use crate::sandbox::{
+    run_sandboxed_streaming, sandbox_config_for, LineSink, SandboxBackend, SandboxOutput,
+};
+use crate::statuses::TestRunStatus;
+use crate::watcher::{FsWatch, WatchBatch};
+use crate::webhooks;
+use crate::{get_settings, get_state_dir, TestCaseResult, TestRun};
+
+/// Lines `get_test_run_log` returns when no limit is given.
+const DEFAULT_LOG_LINES: usize = 500;
+
+/// A runnable test target discovered from a repository's build manifests.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct TestTarget {
+    target_id: String,
+    name: String,
+    framework: String,
+    /// Suggested command line, as argv (program first).
+    command: Vec<String>,
+    /// Directory the command should run from, relative to the repository root.
+    working_dir: String,
+    /// Manifest file the target was derived from.
+    source: String,
+    /// "fast" targets are suitable for the quick gate, "full" for promotion.
+    kind: String,
+}
+
+impl TestTarget {
+    fn new(
+        framework: &str,
+        name: &str,
+        command: &[&str],
+        working_dir: &str,
+        source: &str,
+        kind: &str,
+    ) -> Self {
+        let prefix = if working_dir.is_empty() || working_dir == "." {
+            String::new()
+        } else {
+            format!("{}:", working_dir)
+        };
+        TestTarget {
+            target_id: format!("{}{}:{}", prefix, framework, name),
+            name: name.to_string(),
+            framework: framework.to_string(),
+            command: command.iter().map(|s| s.to_string()).collect(),
+            working_dir: if working_dir.is_empty() {
+                ".".to_string()
+            } else {
+                working_dir.to_string()
+            },
+            source: source.to_string(),
+            kind: kind.to_string(),
+        }
+    }
+}
+
+fn read_optional(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok()
+}
+
+fn has_python_tests(dir: &Path) -> bool {
+    let tests_dir = dir.join("tests");
+    if !tests_dir.is_dir() {
+        return false;
+    }
+
+    fs::read_dir(&tests_dir)
+        .map(|entries| {
+            entries.flatten().any(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                name.ends_with(".py") && (name.starts_with("test_") || name.ends_with("_test.py"))
+            })
+        })
+        .unwrap_or(false)
+}
+
+fn detect_pytest(dir: &Path, rel: &str, targets: &mut Vec<TestTarget>) {
+    let source = if dir.join("pytest.ini").exists() {
+        Some("pytest.ini")
+    } else if read_optional(&dir.join("pyproject.toml"))
+        .map(|c| c.contains("[tool.pytest"))
+        .unwrap_or(false)
+    {
+        Some("pyproject.toml")
+    } else if read_optional(&dir.join("setup.cfg"))
+        .map(|c| c.contains("[tool:pytest]"))
+        .unwrap_or(false)
+    {
+        Some("setup.cfg")
+    } else if dir.join("conftest.py").exists() || has_python_tests(dir) {
+        Some("tests/")
+    } else {
+        None
+    };
+
+    if let Some(source) = source {
+        targets.push(TestTarget::new(
+            "pytest",
+            "fast",
+            &["python", "-m", "pytest", "-x", "-q", "-rA"],
+            rel,
+            source,
+            "fast",
+        ));
+        targets.push(TestTarget::new(
+            "pytest",
+            "full",
+            &["python", "-m", "pytest", "-rA"],
+            rel,
+            source,
+            "full",
+        ));
+    }
+}
+
+fn detect_cargo(dir: &Path, rel: &str, targets: &mut Vec<TestTarget>) {
+    let manifest = match read_optional(&dir.join("Cargo.toml")) {
+        Some(contents) => contents,
+        None => return,
+    };
+
+    if manifest.contains("[workspace]") {
+        targets.push(TestTarget::new(
+            "cargo",
+            "workspace",
+            &["cargo", "test", "--workspace"],
+            rel,
+            "Cargo.toml",
+            "full",
+        ));
+    } else {
+        targets.push(TestTarget::new(
+            "cargo",
+            "all",
+            &["cargo", "test"],
+            rel,
+            "Cargo.toml",
+            "full",
+        ));
+    }
+
+    if dir.join("src").join("lib.rs").exists() {
+        targets.push(TestTarget::new(
+            "cargo",
+            "lib",
+            &["cargo", "test", "--lib"],
+            rel,
+            "Cargo.toml",
+            "fast",
+        ));
+    }
+}
+
+fn detect_node(dir: &Path, rel: &str, targets: &mut Vec<TestTarget>) {
+    let manifest: Value = match read_optional(&dir.join("package.json"))
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+    {
+        Some(value) => value,
+        None => return,
+    };
+
+    let runner = if dir.join("pnpm-lock.yaml").exists() {
+        "pnpm"
+    } else if dir.join("yarn.lock").exists() {
+        "yarn"
+    } else {
+        "npm"
+    };
+
+    let scripts = match manifest.get("scripts").and_then(|s| s.as_object()) {
+        Some(scripts) => scripts,
+        None => return,
+    };
+
+    let mut names: Vec<&String> = scripts
+        .keys()
+        .filter(|name| name.as_str() == "test" || name.starts_with("test:"))
+        .collect();
+    names.sort();
+
+    for name in names {
+        // npm's default placeholder script always fails; don't offer it.
+        let script = scripts[name.as_str()].as_str().unwrap_or("");
+        if script.contains("no test specified") {
+            continue;
+        }
+
+        let kind = if name.contains("unit") || name.contains("fast") {
+            "fast"
+        } else {
+            "full"
+        };
+        targets.push(TestTarget::new(
+            runner,
+            name,
+            &[runner, "run", name.as_str()],
+            rel,
+            "package.json",
+            kind,
+        ));
+    }
+}
+
+fn detect_go(dir: &Path, rel: &str, targets: &mut Vec<TestTarget>) {
+    if !dir.join("go.mod").exists() {
+        return;
+    }
+
+    targets.push(TestTarget::new(
+        "go",
+        "short",
+        &["go", "test", "-short", "./..."],
+        rel,
+        "go.mod",
+        "fast",
+    ));
+    targets.push(TestTarget::new(
+        "go",
+        "all",
+        &["go", "test", "./..."],
+        rel,
+        "go.mod",
+        "full",
+    ));
+}
+
+fn detect_in_dir(dir: &Path, rel: &str, targets: &mut Vec<TestTarget>) {
+    detect_pytest(dir, rel, targets);
+    detect_cargo(dir, rel, targets);
+    detect_node(dir, rel, targets);
+    detect_go(dir, rel, targets);
+}
+
+/// Discover test targets in the repository root and its immediate
+/// subdirectories (enough to pick up a `frontend/` or `backend/` split).
+pub(crate) fn discover_test_targets(repo_dir: &Path) -> Result<Vec<TestTarget>, String> {
+    let mut targets = Vec::new();
+    detect_in_dir(repo_dir, ".", &mut targets);
+
+    let mut subdirs = Vec::new();
+    for entry in fs::read_dir(repo_dir).map_err(|e| e.to_string())? {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !path.is_dir() || name.starts_with('.') || name == "node_modules" || name == "target" {
+            continue;
+        }
+        subdirs.push((name, path));
+    }
+    subdirs.sort();
+
+    for (name, path) in subdirs {
+        detect_in_dir(&path, &name, &mut targets);
+    }
+
+    Ok(targets)
+}
+
+#[tauri::command]
+pub(crate) fn detect_test_targets(repo_id: String) -> Result<Vec<TestTarget>, String> {
+    let repo_dir = resolve_repo_path(&repo_id)?;
+    discover_test_targets(&repo_dir)
+}
+
+/// The target named `target` by ID or kind; an empty name picks the first.
+fn resolve_target(targets: &[TestTarget], target: &str) -> Result<TestTarget, String> {
+    if targets.is_empty() {
+        return Err("No test targets detected in this repository".to_string());
+    }
+    if target.is_empty() {
+        return Ok(targets[0].clone());
+    }
+    targets
+        .iter()
+        .find(|t| t.target_id == target)
+        .or_else(|| targets.iter().find(|t| t.kind == target))
+        .cloned()
+        .ok_or_else(|| {
+            format!(
+                "Unknown test target {}; detected: {}",
+                target,
+                targets
+                    .iter()
+                    .map(|t| t.target_id.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        })
+}
+
+/// Extract `<count> <word>` pairs from a summary line, e.g. "3 passed, 1 failed".
+fn count_pairs(line: &str) -> Vec<(i32, String)> {
+    let words: Vec<&str> = line
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    words
+        .windows(2)
+        .filter_map(|pair| {
+            pair[0]
+                .parse::<i32>()
+                .ok()
+                .map(|n| (n, pair[1].to_lowercase()))
+        })
+        .collect()
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct TestSummary {
+    pub(crate) total: i32,
+    pub(crate) passed: i32,
+    pub(crate) failed: i32,
+    pub(crate) skipped: i32,
+}
+
+/// Add the counts found in a summary line; returns whether anything matched.
+fn absorb_counts(line: &str, summary: &mut TestSummary) -> bool {
+    let mut matched = false;
+    for (count, word) in count_pairs(line) {
+        match word.as_str() {
+            "passed" => summary.passed += count,
+            "failed" | "error" | "errors" => summary.failed += count,
+            "skipped" | "ignored" | "todo" => summary.skipped += count,
+            _ => continue,
+        }
+        matched = true;
+    }
+    matched
+}
+
+/// Best-effort parse of runner output into pass/fail counts.
+pub(crate) fn parse_test_summary(framework: &str, output: &str) -> Option<TestSummary> {
+    let mut summary = TestSummary::default();
+    let mut matched = false;
+
+    match framework {
+        "cargo" => {
+            for line in output.lines().filter(|l| l.starts_with("test result:")) {
+                matched |= absorb_counts(line, &mut summary);
+            }
+        }
+        "go" => {
+            for line in output.lines().map(str::trim_start) {
+                if line.starts_with("--- PASS") {
+                    summary.passed += 1;
+                } else if line.starts_with("--- FAIL") {
+                    summary.failed += 1;
+                } else if line.starts_with("--- SKIP") {
+                    summary.skipped += 1;
+                } else {
+                    continue;
+                }
+                matched = true;
+            }
+        }
+        "npm" | "yarn" | "pnpm" => {
+            if let Some(line) = output
+                .lines()
+                .find(|l| l.trim_start().starts_with("Tests:"))
+            {
+                matched |= absorb_counts(line, &mut summary);
+            }
+        }
+        _ => {
+            if let Some(line) = output
+                .lines()
+                .rev()
+                .find(|l| l.contains(" passed") || l.contains(" failed") || l.contains(" error"))
+            {
+                matched |= absorb_counts(line, &mut summary);
+            }
+        }
+    }
+
+    if !matched {
+        return None;
+    }
+    summary.total = summary.passed + summary.failed + summary.skipped;
+    Some(summary)
+}
+
+fn case(test_id: &str, status: TestRunStatus) -> TestCaseResult {
+    TestCaseResult {
+        test_id: test_id.to_string(),
+        name: test_id
+            .rsplit(&[':', '/'][..])
+            .next()
+            .unwrap_or(test_id)
+            .to_string(),
+        status,
+        duration_ms: 0,
+        output: String::new(),
+        error: None,
+        timestamp: Utc::now().to_rfc3339(),
+    }
+}
+
+/// Extract individual test outcomes from runner output where the format
+/// exposes them (cargo, pytest -rA, go -v).
+pub(crate) fn parse_test_cases(framework: &str, output: &str) -> Vec<TestCaseResult> {
+    let mut cases = Vec::new();
+
+    for line in output.lines() {
+        let line = line.trim();
+        match framework {
+            "cargo" => {
+                // "test module::name ... ok"
+                if let Some(rest) = line.strip_prefix("test ") {
+                    if let Some((name, outcome)) = rest.rsplit_once(" ... ") {
+                        let status = match outcome {
+                            "ok" => TestRunStatus::Passed,
+                            "FAILED" => TestRunStatus::Failed,
+                            "ignored" => TestRunStatus::Skipped,
+                            _ => continue,
+                        };
+                        cases.push(case(name.trim(), status));
+                    }
+                }
+            }
+            "pytest" => {
+                // short test summary: "PASSED tests/test_x.py::test_y"
+                for (prefix, status) in [
+                    ("PASSED ", TestRunStatus::Passed),
+                    ("FAILED ", TestRunStatus::Failed),
+                    ("ERROR ", TestRunStatus::Failed),
+                    ("SKIPPED ", TestRunStatus::Skipped),
+                ] {
+                    if let Some(rest) = line.strip_prefix(prefix) {
+                        let (test_id, reason) = match rest.split_once(" - ") {
+                            Some((test_id, reason)) => (test_id.trim(), Some(reason.trim())),
+                            None => (rest.trim(), None),
+                        };
+                        if test_id.contains("::") {
+                            let mut result = case(test_id, status);
+                            if status == TestRunStatus::Failed {
+                                result.error = reason.map(str::to_string);
+                            }
+                            cases.push(result);
+                        }
+                    }
+                }
+            }
+            "go" => {
+                for (prefix, status) in [
+                    ("--- PASS: ", TestRunStatus::Passed),
+                    ("--- FAIL: ", TestRunStatus::Failed),
+                    ("--- SKIP: ", TestRunStatus::Skipped),
+                ] {
+                    if let Some(rest) = line.strip_prefix(prefix) {
+                        if let Some(name) = rest.split_whitespace().next() {
+                            cases.push(case(name, status));
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for (test_id, output) in failure_output(framework, output) {
+        let Some(result) = cases
+            .iter_mut()
+            .find(|result| result.status == TestRunStatus::Failed && result.test_id == test_id)
+        else {
+            continue;
+        };
+        if result.error.is_none() {
+            result.error = failure_message(framework, &output);
+        }
+        result.output = output;
+    }
+
+    cases
+}
+
+/// Output the runner printed for each failing test, keyed by test ID.
+fn failure_output(framework: &str, output: &str) -> Vec<(String, String)> {
+    let mut sections: Vec<(String, Vec<&str>)> = Vec::new();
+    let mut open = false;
+    for raw in output.lines() {
+        let line = raw.trim();
+        let header = match framework {
+            // "---- module::name stdout ----"
+            "cargo" => line
+                .strip_prefix("---- ")
+                .and_then(|rest| rest.strip_suffix(" stdout ----"))
+                .map(str::to_string),
+            // "______ TestClass.test_name ______", matched to the test ID below.
+            "pytest" if line.starts_with('_') && line.ends_with('_') => {
+                let name = line.trim_matches('_').trim();
+                // Skips the "_ _ _" frame separators and "ERROR at setup of" headers.
+                Some(name.replace('.', "::")).filter(|_| !name.is_empty() && !name.contains(' '))
+            }
+            // "--- FAIL: TestName (0.00s)", followed by its indented log lines.
+            "go" => line
+                .strip_prefix("--- FAIL: ")
+                .and_then(|rest| rest.split_whitespace().next())
+                .map(str::to_string),
+            _ => None,
+        };
+        if let Some(test_id) = header {
+            sections.push((test_id, Vec::new()));
+            open = true;
+            continue;
+        }
+        let closes = match framework {
+            "cargo" => line == "failures:" || line.starts_with("---- "),
+            "pytest" => line.starts_with('='),
+            "go" => !raw.starts_with("    "),
+            _ => true,
+        };
+        if closes {
+            open = false;
+        } else if open {
+            if let Some((_, lines)) = sections.last_mut() {
+                lines.push(raw);
+            }
+        }
+    }
+
+    let ids: Vec<String> = if framework == "pytest" {
+        // Section headers name the test without its file.
+        output
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("FAILED "))
+            .map(|rest| rest.split(" - ").next().unwrap_or(rest).trim().to_string())
+            .collect()
+    } else {
+        Vec::new()
+    };
+    sections
+        .into_iter()
+        .map(|(name, lines)| {
+            let test_id = ids
+                .iter()
+                .find(|id| id.ends_with(&format!("::{}", name)))
+                .cloned()
+                .unwrap_or(name);
+            (test_id, lines.join("\n").trim_matches('\n').to_string())
+        })
+        .collect()
+}
+
+/// The line(s) saying why a test failed, picked out of its output.
+fn failure_message(framework: &str, output: &str) -> Option<String> {
+    let lines: Vec<&str> = output.lines().map(str::trim).collect();
+    let message = match framework {
+        // "thread 'name' panicked at src/lib.rs:3:5:" then the message.
+        "cargo" => {
+            let start = lines.iter().position(|line| line.contains("panicked at"))?;
+            lines[start..]
+                .iter()
+                .take_while(|line| !line.is_empty() && !line.starts_with("note:"))
+                .copied()
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+        // pytest marks the failing lines with "E   ".
+        "pytest" => lines
+            .iter()
+            .filter_map(|line| line.strip_prefix("E "))
+            .map(str::trim)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => lines
+            .iter()
+            .find(|line| !line.is_empty())
+            .map(|line| line.to_string())
+            .unwrap_or_default(),
+    };
+    Some(message).filter(|message| !message.is_empty())
+}
+
+pub(crate) fn save_test_run(run: &TestRun) -> Result<(), String> {
+    let path = get_state_dir()
+        .join("test_runs")
+        .join(format!("{}.json", run.run_id));
+    write_json(&path, run)
+}
+
+/// Fill in a TestRun's counts and status from a finished sandbox execution.
+/// Failures limited to `quarantined` test ids don't fail the run.
+pub(crate) fn finish_test_run(
+    run: &mut TestRun,
+    framework: &str,
+    output: &SandboxOutput,
+    quarantined: &HashSet<String>,
+) {
+    let combined = format!("{}\n{}", output.stdout, output.stderr);
+    run.tests = parse_test_cases(framework, &combined);
+    let summary = parse_test_summary(framework, &combined).unwrap_or(TestSummary {
+        total: 1,
+        passed: if output.success() { 1 } else { 0 },
+        failed: if output.success() { 0 } else { 1 },
+        skipped: 0,
+    });
+
+    run.total_tests = summary.total;
+    run.passed = summary.passed;
+    run.failed = summary.failed;
+    run.skipped = summary.skipped;
+    run.duration_ms = output.duration_ms as i32;
+    run.completed_at = Some(Utc::now().to_rfc3339());
+    let failing: Vec<&TestCaseResult> = run
+        .tests
+        .iter()
+        .filter(|t| t.status == TestRunStatus::Failed)
+        .collect();
+    let only_quarantined = !output.timed_out
+        && !failing.is_empty()
+        && failing.len() as i32 == summary.failed
+        && failing.iter().all(|t| quarantined.contains(&t.test_id));
+
+    run.status = if (output.success() && summary.failed == 0) || only_quarantined {
+        TestRunStatus::Passed
+    } else {
+        TestRunStatus::Failed
+    };
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct TestOutputEvent {
+    run_id: String,
+    /// "stdout" or "stderr"
+    stream: String,
+    line: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct TestRunLog {
+    run_id: String,
+    /// Index of the first returned line.
+    offset: usize,
+    lines: Vec<String>,
+    /// Lines in the log so far; more may follow while the run is going.
+    total_lines: usize,
+}
+
+fn log_path(run_id: &str) -> PathBuf {
+    get_state_dir()
+        .join("test_runs")
+        .join(format!("{}.log", run_id))
+}
+
+/// Append each output line to the run's log and, with a window, emit it as
+/// a "test-output" event.
+fn output_sink(run_id: &str, window: Option<&tauri::Window>) -> Result<LineSink, String> {
+    let path = log_path(run_id);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let log = Mutex::new(
+        fs::File::create(&path)
+            .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?,
+    );
+    let run_id = run_id.to_string();
+    let window = window.cloned();
+    Ok(Arc::new(move |stream, line| {
+        if let Ok(mut log) = log.lock() {
+            warn_on_err("Failed to write test log", writeln!(log, "{}", line));
+        }
+        if let Some(window) = &window {
+            warn_on_err(
+                "Failed to emit test-output",
+                window.emit(
+                    "test-output",
+                    TestOutputEvent {
+                        run_id: run_id.clone(),
+                        stream: stream.to_string(),
+                        line: line.to_string(),
+                    },
+                ),
+            );
+        }
+    }))
+}
+
+/// Run `target` against whatever is checked out in `checkout`, inside the
+/// repository's configured sandbox, and persist the resulting TestRun.
+/// Container output is streamed to `window` when one is given.
+fn run_in_checkout(
+    window: Option<&tauri::Window>,
+    repo_id: &str,
+    workpad_id: Option<&str>,
+    checkout: &Path,
+    target: &str,
+) -> Result<TestRun, String> {
+    let targets = discover_test_targets(checkout)?;
+    let selected = resolve_target(&targets, target.trim())?;
+
+    let mut run = TestRun {
+        run_id: format!("run-{}", Uuid::new_v4().simple()),
+        workpad_id: workpad_id.map(str::to_string),
+        target: selected.target_id.clone(),
+        status: TestRunStatus::Running,
+        started_at: Utc::now().to_rfc3339(),
+        completed_at: None,
+        total_tests: 0,
+        passed: 0,
+        failed: 0,
+        skipped: 0,
+        duration_ms: 0,
+        image_digest: None,
+        commit_sha: run_git(checkout, &["rev-parse", "HEAD"])
+            .ok()
+            .map(|sha| sha.trim().to_string()),
+        tests: Vec::new(),
+    };
+    save_test_run(&run)?;
+
+    let quarantined = if get_settings()?.tests.quarantine_flaky_tests {
+        flaky_test_ids(repo_id)?
+    } else {
+        HashSet::new()
+    };
+
+    let config = sandbox_config_for(repo_id);
+    let started = SystemTime::now();
+    let sink = output_sink(&run.run_id, window)?;
+    let result = if config.backend == SandboxBackend::Docker {
+        docker::prepare_test_image(repo_id, &config, checkout).and_then(|image| {
+            run.image_digest = docker::image_digest(&image);
+            docker::run_in_container(
+                &run.run_id,
+                &image,
+                &config,
+                &selected.command,
+                checkout,
+                &selected.working_dir,
+                false,
+                Some(sink),
+            )
+        })
+    } else {
+        run_sandboxed_streaming(
+            &config,
+            &selected.command,
+            &checkout.join(&selected.working_dir),
+            Some(sink),
+        )
+    };
+
+    // Coverage is best-effort: a missing or malformed report shouldn't fail the run.
+    let report_dir = if config.backend == SandboxBackend::Docker {
+        docker::output_dir(&run.run_id)
+    } else {
+        checkout.join(&selected.working_dir)
+    };
+    if result.is_ok() {
+        warn_on_err(
+            "Coverage ingestion failed",
+            coverage::ingest_run_coverage(&run.run_id, checkout, &report_dir, started),
+        );
+    }
+    if config.backend == SandboxBackend::Docker {
+        docker::remove_scratch(&run.run_id);
+    }
+
+    match result {
+        Ok(output) => finish_test_run(&mut run, &selected.framework, &output, &quarantined),
+        Err(e) => {
+            run.status = TestRunStatus::Failed;
+            run.completed_at = Some(Utc::now().to_rfc3339());
+            save_test_run(&run)?;
+            return Err(e);
+        }
+    }
+    save_test_run(&run)?;
+    Ok(run)
+}
+
+/// Run a test target against a repository checkout at its current HEAD,
+/// outside any workpad (used by bisect).
+pub(crate) fn run_repo_target(
+    repo_id: &str,
+    checkout: &Path,
+    target: &str,
+) -> Result<TestRun, String> {
+    run_in_checkout(None, repo_id, None, checkout, target)
+}
+
+/// Run a discovered test target for a workpad natively (without the CLI),
+/// inside the repository's configured sandbox.
+pub(crate) fn execute_test_target(
+    window: &tauri::Window,
+    workpad_id: &str,
+    target: &str,
+) -> Result<TestRun, String> {
+    let workpad = load_workpad(workpad_id)?;
+    lifecycle::ensure_transition(&workpad, WorkpadStatus::Testing)?;
+    let checkout = workpad_checkout_dir(&workpad)?;
+    let run = run_in_checkout(
+        Some(window),
+        &workpad.repo_id,
+        Some(workpad_id),
+        &checkout,
+        target,
+    )?;
+
+    let mut workpad = load_workpad(workpad_id)?;
+    let outcome = match run.status {
+        TestRunStatus::Passed => WorkpadStatus::Passed,
+        _ => WorkpadStatus::Failed,
+    };
+    // The workpad may have been promoted or archived while the run went on.
+    lifecycle::transition(&mut workpad, outcome)?;
+    workpad.test_runs.insert(0, run.run_id.clone());
+    save_workpad(workpad)?;
+
+    webhooks::fire_test_run(&run);
+    notifications::test_run_finished(&run);
+    metrics::record_test_run(&run);
+    Ok(run)
+}
+
+#[tauri::command]
+pub(crate) fn run_test_target(
+    window: tauri::Window,
+    workpad_id: String,
+    target: String,
+) -> Result<TestRun, String> {
+    audited(
+        "run_test_target",
+        "workpad",
+        Some(workpad_id.clone()),
+        None,
+        move || execute_test_target(&window, &workpad_id, &target),
+    )
+}
+
+/// Captured output of a test run, `limit` lines from `offset`, for
+/// scrolling back through long logs.
+#[tauri::command]
+pub(crate) fn get_test_run_log(
+    run_id: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<TestRunLog, String> {
+    if run_id.is_empty() || run_id.contains(['/', '\\', '.']) {
+        return Err(format!("Invalid test run id: {}", run_id));
+    }
+    let path = log_path(&run_id);
+    let file = fs::File::open(&path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => format!("No log for test run {}", run_id),
+        _ => format!("Failed to read {}: {}", path.display(), e),
+    })?;
+    let offset = offset.unwrap_or(0);
+    let limit = limit.unwrap_or(DEFAULT_LOG_LINES);
+    let mut lines = Vec::new();
+    let mut total_lines = 0;
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if total_lines >= offset && lines.len() < limit {
+            lines.push(line);
+        }
+        total_lines += 1;
+    }
+    Ok(TestRunLog {
+        run_id,
+        offset,
+        lines,
+        total_lines,
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct TestWatchInfo {
+    watch_id: String,
+    workpad_id: String,
+    target: String,
+    started_at: String,
+}
+
+struct TestWatch {
+    info: TestWatchInfo,
+    stop: Arc<AtomicBool>,
+}
+
+/// Active test watches, held in Tauri managed state.
+#[derive(Default)]
+pub(crate) struct TestWatchRegistry {
+    watches: Mutex<HashMap<String, TestWatch>>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct TestWatchEvent {
+    watch_id: String,
+    workpad_id: String,
+    changed_files: Vec<String>,
+    run: Option<TestRun>,
+    error: Option<String>,
+}
+
+fn tracked_files(checkout: &Path) -> HashSet<PathBuf> {
+    run_git(checkout, &["ls-files", "-z"])
+        .map(|out| {
+            out.split('\0')
+                .filter(|p| !p.is_empty())
+                .map(|p| checkout.join(p))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn watch_loop(
+    window: tauri::Window,
+    info: TestWatchInfo,
+    checkout: PathBuf,
+    watch: FsWatch,
+    stop: Arc<AtomicBool>,
+) {
+    // Watcher events carry canonical paths; compare like with like.
+    let checkout = checkout.canonicalize().unwrap_or(checkout);
+    let mut tracked = tracked_files(&checkout);
+
+    while !stop.load(Ordering::SeqCst) {
+        let changed = match watch.next_batch(Duration::from_millis(500), Duration::from_millis(750))
+        {
+            WatchBatch::Changed(paths) => paths,
+            WatchBatch::Idle => continue,
+            WatchBatch::Disconnected => break,
+        };
+
+        let relevant: Vec<String> = changed
+            .iter()
+            .filter(|path| tracked.contains(*path))
+            .filter_map(|path| path.strip_prefix(&checkout).ok())
+            .map(|path| path.to_string_lossy().to_string())
+            .collect();
+        if relevant.is_empty() || stop.load(Ordering::SeqCst) {
+            continue;
+        }
+
+        let mut event = TestWatchEvent {
+            watch_id: info.watch_id.clone(),
+            workpad_id: info.workpad_id.clone(),
+            changed_files: relevant,
+            run: None,
+            error: None,
+        };
+        warn_on_err(
+            "Failed to emit test-watch-run-started",
+            window.emit("test-watch-run-started", event.clone()),
+        );
+        api_server::forward("test-watch-run-started", &event);
+
+        match execute_test_target(&window, &info.workpad_id, &info.target) {
+            Ok(run) => event.run = Some(run),
+            Err(e) => event.error = Some(e),
+        }
+        warn_on_err(
+            "Failed to emit test-watch-run-finished",
+            window.emit("test-watch-run-finished", &event),
+        );
+        api_server::forward("test-watch-run-finished", &event);
+
+        // Ignore churn produced by the run itself (caches, build output).
+        watch.drain();
+        tracked = tracked_files(&checkout);
+    }
+}
+
+#[tauri::command]
+pub(crate) fn start_test_watch(
+    window: tauri::Window,
+    registry: tauri::State<'_, TestWatchRegistry>,
+    workpad_id: String,
+    target: String,
+) -> Result<TestWatchInfo, String> {
+    let workpad = load_workpad(&workpad_id)?;
+    let checkout = workpad_checkout_dir(&workpad)?;
+    let watch = FsWatch::new(&checkout)?;
+
+    let info = TestWatchInfo {
+        watch_id: format!("watch-{}", Uuid::new_v4().simple()),
+        workpad_id,
+        target,
+        started_at: Utc::now().to_rfc3339(),
+    };
+    let stop = Arc::new(AtomicBool::new(false));
+
+    {
+        let thread_info = info.clone();
+        let thread_stop = stop.clone();
+        thread::spawn(move || watch_loop(window, thread_info, checkout, watch, thread_stop));
+    }
+
+    registry
+        .watches
+        .lock()
+        .map_err(|_| "Test watch registry is poisoned".to_string())?
+        .insert(
+            info.watch_id.clone(),
+            TestWatch {
+                info: info.clone(),
+                stop,
+            },
+        );
+
+    Ok(info)
+}
+
+#[tauri::command]
+pub(crate) fn stop_test_watch(
+    registry: tauri::State<'_, TestWatchRegistry>,
+    watch_id: String,
+) -> Result<(), String> {
+    let watch = registry
+        .watches
+        .lock()
+        .map_err(|_| "Test watch registry is poisoned".to_string())?
+        .remove(&watch_id)
+        .ok_or_else(|| format!("Test watch not found: {}", watch_id))?;
+    watch.stop.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) fn list_test_watches(
+    registry: tauri::State<'_, TestWatchRegistry>,
+) -> Result<Vec<TestWatchInfo>, String> {
+    let watches = registry
+        .watches
+        .lock()
+        .map_err(|_| "Test watch registry is poisoned".to_string())?;
+    Ok(watches.values().map(|watch| watch.info.clone()).collect())
+}
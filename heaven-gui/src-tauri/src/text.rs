@@ -0,0 +1,25 @@
+/// The longest prefix of `text` that is at most `limit` bytes and ends on a
+/// char boundary.
+pub(crate) fn clip(text: &str, limit: usize) -> &str {
+    if text.len() <= limit {
+        return text;
+    }
+    let mut end = limit;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clips_on_char_boundary() {
+        assert_eq!(clip("short", 10), "short");
+        assert_eq!(clip("abcdef", 3), "abc");
+        // "é" is two bytes; a limit inside it backs off to before it.
+        assert_eq!(clip("aé", 2), "a");
+    }
+}
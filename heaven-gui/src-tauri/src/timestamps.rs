@@ -0,0 +1,37 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+/// Parse a timestamp from the state tree. The GUI writes RFC 3339, while the
+/// CLI writes `datetime.utcnow().isoformat()`, which has no offset; those are
+/// read as UTC.
+pub(crate) fn parse(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(at) = DateTime::parse_from_rfc3339(value) {
+        return Some(at.with_timezone(&Utc));
+    }
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .map(|at| at.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rfc3339_and_naive_agree() {
+        let gui = parse("2024-03-01T12:30:00+00:00");
+        assert!(gui.is_some());
+        assert_eq!(parse("2024-03-01T12:30:00"), gui);
+        assert_eq!(parse("2024-03-01T14:30:00+02:00"), gui);
+        assert_eq!(
+            parse("2024-03-01T12:30:00.250000").map(|at| at.timestamp_millis()),
+            gui.map(|at| at.timestamp_millis() + 250)
+        );
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert_eq!(parse(""), None);
+        assert_eq!(parse("yesterday"), None);
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+use tiktoken_rs::CoreBPE;
+
+use crate::ai::{ai_settings, ChatMessage};
+
+/// Per-message framing overhead in OpenAI-style chat formats.
+const TOKENS_PER_MESSAGE: usize = 4;
+
+/// USD per 1k tokens for a model.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub(crate) struct ModelPricing {
+    pub(crate) input_per_1k: f64,
+    pub(crate) output_per_1k: f64,
+}
+
+impl ModelPricing {
+    pub(crate) fn cost(&self, prompt_tokens: usize, completion_tokens: usize) -> f64 {
+        prompt_tokens as f64 / 1000.0 * self.input_per_1k
+            + completion_tokens as f64 / 1000.0 * self.output_per_1k
+    }
+}
+
+/// Built-in prices, matched by longest model-name prefix.
+const DEFAULT_PRICING: &[(&str, f64, f64)] = &[
+    ("gpt-4o-mini", 0.00015, 0.0006),
+    ("gpt-4o", 0.0025, 0.01),
+    ("gpt-4-turbo", 0.01, 0.03),
+    ("gpt-4", 0.03, 0.06),
+    ("gpt-3.5-turbo", 0.0005, 0.0015),
+    ("o1-mini", 0.003, 0.012),
+    ("o1", 0.015, 0.06),
+    ("claude-3-5-sonnet", 0.003, 0.015),
+    ("claude-3-5-haiku", 0.0008, 0.004),
+    ("claude-3-opus", 0.015, 0.075),
+    ("deepseek-coder", 0.00014, 0.00028),
+    ("llama-3", 0.0002, 0.0002),
+];
+
+/// Used when nothing in the configured or built-in tables matches.
+const FALLBACK_PRICING: ModelPricing = ModelPricing {
+    input_per_1k: 0.0025,
+    output_per_1k: 0.01,
+};
+
+fn longest_prefix<'a, T>(model: &str, entries: impl Iterator<Item = (&'a str, T)>) -> Option<T> {
+    entries
+        .filter(|(prefix, _)| model.starts_with(prefix))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, value)| value)
+}
+
+/// Price for `model`, preferring the user's `ai.pricing` overrides.
+pub(crate) fn pricing_for(model: &str, overrides: &HashMap<String, ModelPricing>) -> ModelPricing {
+    let model = model.to_lowercase();
+    let model = model.rsplit('/').next().unwrap_or(&model);
+    longest_prefix(
+        model,
+        overrides
+            .iter()
+            .map(|(name, price)| (name.as_str(), *price)),
+    )
+    .or_else(|| {
+        longest_prefix(
+            model,
+            DEFAULT_PRICING.iter().map(|(name, input, output)| {
+                (
+                    *name,
+                    ModelPricing {
+                        input_per_1k: *input,
+                        output_per_1k: *output,
+                    },
+                )
+            }),
+        )
+    })
+    .unwrap_or(FALLBACK_PRICING)
+}
+
+fn encoder_for(model: &str) -> Option<&'static CoreBPE> {
+    static CL100K: OnceLock<Option<CoreBPE>> = OnceLock::new();
+    static O200K: OnceLock<Option<CoreBPE>> = OnceLock::new();
+
+    let model = model.to_lowercase();
+    let uses_o200k = model.contains("gpt-4o") || model.starts_with("o1") || model.starts_with("o3");
+    if uses_o200k {
+        O200K
+            .get_or_init(|| tiktoken_rs::o200k_base().ok())
+            .as_ref()
+    } else {
+        CL100K
+            .get_or_init(|| tiktoken_rs::cl100k_base().ok())
+            .as_ref()
+    }
+}
+
+/// BPE token count for `text` under `model`'s encoding. Models without a
+/// known tokenizer are counted with cl100k, which is close for most.
+pub(crate) fn count_tokens(text: &str, model: &str) -> usize {
+    match encoder_for(model) {
+        Some(bpe) => bpe.encode_with_special_tokens(text).len(),
+        None => text.len().div_ceil(4),
+    }
+}
+
+pub(crate) fn count_message_tokens(messages: &[ChatMessage], model: &str) -> usize {
+    messages
+        .iter()
+        .map(|message| TOKENS_PER_MESSAGE + count_tokens(&message.content, model))
+        .sum::<usize>()
+        + 3
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct CostEstimate {
+    model: String,
+    prompt_tokens: usize,
+    max_completion_tokens: usize,
+    pricing: ModelPricing,
+    prompt_cost_usd: f64,
+    /// Cost if the reply uses the full `max_tokens` allowance.
+    max_cost_usd: f64,
+}
+
+#[tauri::command]
+pub(crate) fn estimate_cost(prompt: String, model: Option<String>) -> Result<CostEstimate, String> {
+    let settings = ai_settings();
+    let model = model
+        .filter(|m| !m.trim().is_empty())
+        .unwrap_or_else(|| settings.model.clone());
+    let pricing = pricing_for(&model, &settings.pricing);
+    let prompt_tokens = count_message_tokens(&[ChatMessage::user(prompt)], &model);
+    let max_completion_tokens = settings.max_tokens as usize;
+
+    Ok(CostEstimate {
+        prompt_cost_usd: pricing.cost(prompt_tokens, 0),
+        max_cost_usd: pricing.cost(prompt_tokens, max_completion_tokens),
+        model,
+        prompt_tokens,
+        max_completion_tokens,
+        pricing,
+    })
+}
@@ -0,0 +1,424 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::audit::audited;
+use crate::commands::{apply_patch, load_workpad, workpad_checkout_dir};
+use crate::get_settings;
+use crate::git::run_git;
+use crate::sandbox::{run_sandboxed, sandbox_config_for, SandboxOutput};
+use crate::text::clip;
+use crate::unified_diff::parse_changed_files;
+
+/// How much of a tool's raw output is kept alongside the parsed issues.
+const MAX_OUTPUT_CHARS: usize = 20_000;
+pub(crate) const OUTPUT_FORMATS: &[&str] = &["text", "cargo", "eslint", "ruff"];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct ToolCommand {
+    pub(crate) argv: Vec<String>,
+    /// For linters, the variant that applies auto-fixes.
+    #[serde(default)]
+    pub(crate) fix_argv: Option<Vec<String>>,
+    /// How diagnostics are parsed: "text" (`path:line:col: message`),
+    /// "cargo", "eslint" or "ruff" JSON.
+    #[serde(default = "default_output")]
+    pub(crate) output: String,
+}
+
+fn default_output() -> String {
+    "text".to_string()
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub(crate) struct ToolSettings {
+    pub(crate) formatters: HashMap<String, ToolCommand>,
+    pub(crate) linters: HashMap<String, ToolCommand>,
+}
+
+fn tool(argv: &[&str], fix_argv: Option<&[&str]>, output: &str) -> ToolCommand {
+    let owned = |args: &[&str]| -> Vec<String> { args.iter().map(|arg| arg.to_string()).collect() };
+    ToolCommand {
+        argv: owned(argv),
+        fix_argv: fix_argv.map(owned),
+        output: output.to_string(),
+    }
+}
+
+impl Default for ToolSettings {
+    fn default() -> Self {
+        ToolSettings {
+            formatters: HashMap::from([
+                ("rustfmt".to_string(), tool(&["cargo", "fmt"], None, "text")),
+                (
+                    "prettier".to_string(),
+                    tool(&["npx", "prettier", "--write", "."], None, "text"),
+                ),
+                ("black".to_string(), tool(&["black", "."], None, "text")),
+                (
+                    "gofmt".to_string(),
+                    tool(&["gofmt", "-l", "-w", "."], None, "text"),
+                ),
+            ]),
+            linters: HashMap::from([
+                (
+                    "clippy".to_string(),
+                    tool(
+                        &["cargo", "clippy", "--message-format=json"],
+                        Some(&[
+                            "cargo",
+                            "clippy",
+                            "--fix",
+                            "--allow-dirty",
+                            "--allow-no-vcs",
+                        ]),
+                        "cargo",
+                    ),
+                ),
+                (
+                    "eslint".to_string(),
+                    tool(
+                        &["npx", "eslint", "-f", "json", "."],
+                        Some(&["npx", "eslint", "--fix", "."]),
+                        "eslint",
+                    ),
+                ),
+                (
+                    "ruff".to_string(),
+                    tool(
+                        &["ruff", "check", "--output-format=json", "."],
+                        Some(&["ruff", "check", "--fix", "."]),
+                        "ruff",
+                    ),
+                ),
+            ]),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct ToolIssue {
+    path: String,
+    line: Option<u32>,
+    column: Option<u32>,
+    /// "error", "warning" or "info"
+    severity: String,
+    code: Option<String>,
+    message: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct ToolRun {
+    workpad_id: String,
+    tool: String,
+    /// "formatter" or "linter"
+    kind: String,
+    exit_code: Option<i32>,
+    timed_out: bool,
+    issues: Vec<ToolIssue>,
+    /// Changes the tool made (or would make, when not applied), as a
+    /// unified diff.
+    patch: Option<String>,
+    /// Whether `patch` was committed to the workpad through `apply_patch`.
+    applied: bool,
+    output: String,
+    duration_ms: i64,
+}
+
+impl ToolIssue {
+    pub(crate) fn is_error(&self) -> bool {
+        self.severity == "error"
+    }
+}
+
+fn relative(path: &str, root: &Path) -> String {
+    Path::new(path)
+        .strip_prefix(root)
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or_else(|_| path.to_string())
+}
+
+fn text_pattern() -> &'static Regex {
+    static TEXT: OnceLock<Regex> = OnceLock::new();
+    TEXT.get_or_init(|| {
+        Regex::new(
+            r"^(?P<path>[^:\s][^:]*):(?P<line>\d+):(?:(?P<column>\d+):)?\s*(?:(?P<severity>error|warning|note|info)(?:\[(?P<code>[^\]]+)\])?:)?\s*(?P<message>.+)$",
+        )
+        .expect("valid diagnostic pattern")
+    })
+}
+
+fn parse_text(output: &str, root: &Path) -> Vec<ToolIssue> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let captures = text_pattern().captures(line.trim())?;
+            let number = |name: &str| captures.name(name)?.as_str().parse().ok();
+            Some(ToolIssue {
+                path: relative(&captures["path"], root),
+                line: number("line"),
+                column: number("column"),
+                severity: match captures.name("severity").map(|m| m.as_str()) {
+                    Some("note") | Some("info") => "info",
+                    Some("error") => "error",
+                    _ => "warning",
+                }
+                .to_string(),
+                code: captures.name("code").map(|m| m.as_str().to_string()),
+                message: captures["message"].to_string(),
+            })
+        })
+        .collect()
+}
+
+/// `cargo --message-format=json` emits one JSON object per line; only
+/// compiler messages with a primary span are issues.
+fn parse_cargo(output: &str) -> Vec<ToolIssue> {
+    output
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter(|value| value["reason"] == "compiler-message")
+        .filter_map(|value| {
+            let message = &value["message"];
+            let span = message["spans"]
+                .as_array()?
+                .iter()
+                .find(|span| span["is_primary"] == true)?;
+            Some(ToolIssue {
+                path: span["file_name"].as_str()?.to_string(),
+                line: span["line_start"].as_u64().map(|n| n as u32),
+                column: span["column_start"].as_u64().map(|n| n as u32),
+                severity: match message["level"].as_str() {
+                    Some("error") => "error",
+                    Some("warning") => "warning",
+                    _ => "info",
+                }
+                .to_string(),
+                code: message["code"]["code"].as_str().map(str::to_string),
+                message: message["message"].as_str()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+fn parse_eslint(output: &str, root: &Path) -> Vec<ToolIssue> {
+    let Ok(Value::Array(files)) = serde_json::from_str::<Value>(output.trim()) else {
+        return Vec::new();
+    };
+    let mut issues = Vec::new();
+    for file in &files {
+        let path = relative(file["filePath"].as_str().unwrap_or_default(), root);
+        for message in file["messages"].as_array().into_iter().flatten() {
+            issues.push(ToolIssue {
+                path: path.clone(),
+                line: message["line"].as_u64().map(|n| n as u32),
+                column: message["column"].as_u64().map(|n| n as u32),
+                severity: if message["severity"] == 2 {
+                    "error"
+                } else {
+                    "warning"
+                }
+                .to_string(),
+                code: message["ruleId"].as_str().map(str::to_string),
+                message: message["message"].as_str().unwrap_or_default().to_string(),
+            });
+        }
+    }
+    issues
+}
+
+fn parse_ruff(output: &str, root: &Path) -> Vec<ToolIssue> {
+    let Ok(Value::Array(findings)) = serde_json::from_str::<Value>(output.trim()) else {
+        return Vec::new();
+    };
+    findings
+        .iter()
+        .map(|finding| ToolIssue {
+            path: relative(finding["filename"].as_str().unwrap_or_default(), root),
+            line: finding["location"]["row"].as_u64().map(|n| n as u32),
+            column: finding["location"]["column"].as_u64().map(|n| n as u32),
+            severity: "error".to_string(),
+            code: finding["code"].as_str().map(str::to_string),
+            message: finding["message"].as_str().unwrap_or_default().to_string(),
+        })
+        .collect()
+}
+
+pub(crate) fn parse_issues(format: &str, output: &SandboxOutput, root: &Path) -> Vec<ToolIssue> {
+    match format {
+        "cargo" => parse_cargo(&output.stdout),
+        "eslint" => parse_eslint(&output.stdout, root),
+        "ruff" => parse_ruff(&output.stdout, root),
+        _ => {
+            let mut issues = parse_text(&output.stdout, root);
+            issues.extend(parse_text(&output.stderr, root));
+            issues
+        }
+    }
+}
+
+pub(crate) fn combined_output(output: &SandboxOutput) -> String {
+    let mut combined = format!("{}{}", output.stdout, output.stderr);
+    if combined.len() > MAX_OUTPUT_CHARS {
+        combined.truncate(clip(&combined, MAX_OUTPUT_CHARS).len());
+        combined.push_str("\n[output truncated]");
+    }
+    combined
+}
+
+/// What the tool changed in the checkout, after which the checkout is put
+/// back the way it was so the change can go through `apply_patch`.
+fn take_changes(dir: &Path) -> Result<Option<String>, String> {
+    run_git(dir, &["add", "--intent-to-add", "--all"])?;
+    let diff = run_git(dir, &["diff", "--binary", "HEAD"])?;
+    run_git(dir, &["reset", "--hard", "HEAD"])?;
+    run_git(dir, &["clean", "-fd"])?;
+    Ok(Some(diff).filter(|diff| !diff.trim().is_empty()))
+}
+
+/// Run `argv` in the workpad's checkout, which must be clean so the
+/// tool's own edits can be told apart and rolled back.
+fn run_in_checkout(
+    workpad_id: &str,
+    argv: &[String],
+) -> Result<(SandboxOutput, PathBuf, Option<String>), String> {
+    let workpad = load_workpad(workpad_id)?;
+    let dir = workpad_checkout_dir(&workpad)?;
+    if !run_git(&dir, &["status", "--porcelain"])?.trim().is_empty() {
+        return Err(format!(
+            "{} has uncommitted changes; commit or discard them first",
+            dir.display()
+        ));
+    }
+    let output = run_sandboxed(&sandbox_config_for(&workpad.repo_id), argv, &dir)?;
+    let changes = take_changes(&dir)?;
+    Ok((output, dir, changes))
+}
+
+fn apply_fixes(workpad_id: &str, tool: &str, patch: &str) -> Result<(), String> {
+    apply_patch(
+        workpad_id.to_string(),
+        format!("Apply {} fixes", tool),
+        patch.to_string(),
+    )?;
+    // The patch lands on the branch, not in this checkout; catch it up.
+    let workpad = load_workpad(workpad_id)?;
+    run_git(
+        &workpad_checkout_dir(&workpad)?,
+        &["reset", "--hard", &workpad.branch_name],
+    )?;
+    Ok(())
+}
+
+/// Run a configured formatter over the workpad. Every file it rewrites is
+/// reported as an issue; with `apply` the rewrite is committed as a patch.
+#[tauri::command]
+pub(crate) fn run_formatter(
+    workpad_id: String,
+    tool: String,
+    apply: Option<bool>,
+) -> Result<ToolRun, String> {
+    let command = get_settings()?
+        .tools
+        .formatters
+        .get(&tool)
+        .cloned()
+        .ok_or_else(|| format!("No formatter named {} is configured", tool))?;
+    audited(
+        "run_formatter",
+        "workpad",
+        Some(workpad_id.clone()),
+        None,
+        move || {
+            let (output, _, patch) = run_in_checkout(&workpad_id, &command.argv)?;
+            let issues = patch
+                .as_deref()
+                .map(parse_changed_files)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|path| ToolIssue {
+                    path,
+                    line: None,
+                    column: None,
+                    severity: "warning".to_string(),
+                    code: None,
+                    message: format!("Not formatted according to {}", tool),
+                })
+                .collect();
+            let applied = apply.unwrap_or(false) && output.success() && patch.is_some();
+            if applied {
+                apply_fixes(&workpad_id, &tool, patch.as_deref().unwrap_or_default())?;
+            }
+            Ok(ToolRun {
+                workpad_id,
+                tool,
+                kind: "formatter".to_string(),
+                exit_code: output.exit_code,
+                timed_out: output.timed_out,
+                issues,
+                patch,
+                applied,
+                output: combined_output(&output),
+                duration_ms: output.duration_ms,
+            })
+        },
+    )
+}
+
+/// Run a configured linter over the workpad and parse its diagnostics.
+/// With `fix`, the linter's auto-fix mode runs afterwards and its edits are
+/// committed as a patch.
+#[tauri::command]
+pub(crate) fn run_linter(
+    workpad_id: String,
+    tool: String,
+    fix: Option<bool>,
+) -> Result<ToolRun, String> {
+    let command = get_settings()?
+        .tools
+        .linters
+        .get(&tool)
+        .cloned()
+        .ok_or_else(|| format!("No linter named {} is configured", tool))?;
+    let fix_argv = match (fix.unwrap_or(false), &command.fix_argv) {
+        (false, _) => None,
+        (true, Some(argv)) => Some(argv.clone()),
+        (true, None) => return Err(format!("{} has no auto-fix command configured", tool)),
+    };
+    audited(
+        "run_linter",
+        "workpad",
+        Some(workpad_id.clone()),
+        None,
+        move || {
+            let (output, dir, mut patch) = run_in_checkout(&workpad_id, &command.argv)?;
+            let issues = parse_issues(&command.output, &output, &dir);
+            let mut applied = false;
+            if let Some(fix_argv) = fix_argv {
+                let (_, _, fixes) = run_in_checkout(&workpad_id, &fix_argv)?;
+                if let Some(fixes) = &fixes {
+                    apply_fixes(&workpad_id, &tool, fixes)?;
+                    applied = true;
+                }
+                patch = fixes;
+            }
+            Ok(ToolRun {
+                workpad_id,
+                tool,
+                kind: "linter".to_string(),
+                exit_code: output.exit_code,
+                timed_out: output.timed_out,
+                issues,
+                patch,
+                applied,
+                output: combined_output(&output),
+                duration_ms: output.duration_ms,
+            })
+        },
+    )
+}
@@ -0,0 +1,345 @@
+use std::env;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use chrono::{Local, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::audit::audited;
+use crate::commands::{
+    load_repository, load_workpad, resolve_repo_path, save_repository, save_workpad,
+    workpad_checkout_dir,
+};
+use crate::git::run_git;
+use crate::lifecycle::WorkpadStatus;
+use crate::logging::warn_on_err;
+use crate::profiles::active_home;
+use crate::WorkpadState;
+
+const METADATA_NAME: &str = "workpad.json";
+const BUNDLE_NAME: &str = "workpad.bundle";
+const SERIES_NAME: &str = "series.mbox";
+const EXPORT_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct WorkpadExportMetadata {
+    format_version: u32,
+    /// "bundle" or "mbox"
+    format: String,
+    workpad: WorkpadState,
+    source_repo_name: String,
+    /// Subjects of the exported commits, oldest first.
+    commits: Vec<String>,
+    exported_at: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct WorkpadExport {
+    path: String,
+    metadata: WorkpadExportMetadata,
+}
+
+fn exports_dir() -> PathBuf {
+    active_home().join("exports")
+}
+
+fn append_file(
+    builder: &mut tar::Builder<GzEncoder<File>>,
+    name: &str,
+    contents: &[u8],
+) -> Result<(), String> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(contents.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(Utc::now().timestamp().max(0) as u64);
+    header.set_cksum();
+    builder
+        .append_data(&mut header, name, contents)
+        .map_err(|e| format!("Failed to write {}: {}", name, e))
+}
+
+/// Package a workpad's commits with its metadata. "bundle" carries the full
+/// branch history so it can be imported anywhere; "mbox" is a
+/// `git format-patch` series that needs the base commit on the other side.
+#[tauri::command]
+pub(crate) fn export_workpad(
+    workpad_id: String,
+    format: String,
+    destination: Option<String>,
+) -> Result<WorkpadExport, String> {
+    let workpad = load_workpad(&workpad_id)?;
+    let checkout = workpad_checkout_dir(&workpad)?;
+    let branch_ref = format!("refs/heads/{}", workpad.branch_name);
+    let range = format!("{}..{}", workpad.base_commit, branch_ref);
+
+    let commits: Vec<String> = run_git(&checkout, &["log", "--reverse", "--format=%s", &range])?
+        .lines()
+        .map(str::to_string)
+        .collect();
+    if commits.is_empty() {
+        return Err(format!("Workpad {} has no commits to export", workpad_id));
+    }
+
+    let (payload_name, payload) = match format.as_str() {
+        "bundle" => {
+            let temp =
+                env::temp_dir().join(format!("sologit_export_{}.bundle", Uuid::new_v4().simple()));
+            let temp_arg = temp.to_string_lossy().to_string();
+            run_git(&checkout, &["bundle", "create", &temp_arg, &branch_ref])?;
+            let bytes = fs::read(&temp).map_err(|e| format!("Failed to read bundle: {}", e));
+            warn_on_err("Failed to remove temporary bundle", fs::remove_file(&temp));
+            (BUNDLE_NAME, bytes?)
+        }
+        "mbox" => {
+            let series = run_git(&checkout, &["format-patch", "--stdout", &range])?;
+            (SERIES_NAME, series.into_bytes())
+        }
+        other => {
+            return Err(format!(
+                "Unknown export format {}; expected bundle or mbox",
+                other
+            ))
+        }
+    };
+
+    let metadata = WorkpadExportMetadata {
+        format_version: EXPORT_FORMAT_VERSION,
+        format: format.clone(),
+        source_repo_name: load_repository(&workpad.repo_id)
+            .map(|repo| repo.name)
+            .unwrap_or_default(),
+        workpad,
+        commits,
+        exported_at: Utc::now().to_rfc3339(),
+    };
+
+    let path = match destination {
+        Some(destination) if !destination.trim().is_empty() => PathBuf::from(destination.trim()),
+        _ => {
+            let dir = exports_dir();
+            fs::create_dir_all(&dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+            dir.join(format!("{}-{}.tar.gz", workpad_id, format))
+        }
+    };
+    let file =
+        File::create(&path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    let metadata_bytes = serde_json::to_vec_pretty(&metadata).map_err(|e| e.to_string())?;
+    append_file(&mut builder, METADATA_NAME, &metadata_bytes)?;
+    append_file(&mut builder, payload_name, &payload)?;
+    builder
+        .into_inner()
+        .and_then(|encoder| encoder.finish())
+        .map_err(|e| format!("Failed to finish export: {}", e))?;
+
+    Ok(WorkpadExport {
+        path: path.display().to_string(),
+        metadata,
+    })
+}
+
+/// Unpack an export into `dir`, returning its metadata.
+fn unpack_export(file: &Path, dir: &Path) -> Result<WorkpadExportMetadata, String> {
+    let archive =
+        File::open(file).map_err(|e| format!("Failed to open {}: {}", file.display(), e))?;
+    let mut reader = tar::Archive::new(GzDecoder::new(archive));
+    let mut metadata = None;
+    for entry in reader
+        .entries()
+        .map_err(|e| format!("Failed to read export: {}", e))?
+    {
+        let mut entry = entry.map_err(|e| format!("Failed to read export: {}", e))?;
+        let name = entry
+            .path()
+            .map_err(|e| format!("Invalid path in export: {}", e))?
+            .to_string_lossy()
+            .to_string();
+        match name.as_str() {
+            METADATA_NAME => {
+                let mut contents = String::new();
+                entry
+                    .read_to_string(&mut contents)
+                    .map_err(|e| format!("Failed to read metadata: {}", e))?;
+                metadata = Some(
+                    serde_json::from_str::<WorkpadExportMetadata>(&contents)
+                        .map_err(|e| format!("Invalid workpad metadata: {}", e))?,
+                );
+            }
+            BUNDLE_NAME | SERIES_NAME => {
+                entry
+                    .unpack(dir.join(&name))
+                    .map_err(|e| format!("Failed to extract {}: {}", name, e))?;
+            }
+            _ => {}
+        }
+    }
+
+    let metadata = metadata.ok_or_else(|| "Export has no workpad metadata".to_string())?;
+    if metadata.format_version > EXPORT_FORMAT_VERSION {
+        return Err(format!(
+            "Export format {} is newer than this build supports",
+            metadata.format_version
+        ));
+    }
+    Ok(metadata)
+}
+
+fn commit_exists(repo_dir: &Path, rev: &str) -> bool {
+    run_git(
+        repo_dir,
+        &["cat-file", "-e", &format!("{}^{{commit}}", rev)],
+    )
+    .is_ok()
+}
+
+/// Replay an mbox series onto `base` in a throwaway worktree so the user's
+/// checkout is never touched, then point `branch` at the result.
+fn apply_series(repo_dir: &Path, series: &Path, base: &str, branch: &str) -> Result<(), String> {
+    let worktree = env::temp_dir().join(format!("sologit_import_wt_{}", Uuid::new_v4().simple()));
+    let worktree_arg = worktree.to_string_lossy().to_string();
+    run_git(
+        repo_dir,
+        &["worktree", "add", "--detach", &worktree_arg, base],
+    )?;
+
+    let series_arg = series.to_string_lossy().to_string();
+    let result = run_git(&worktree, &["am", "--3way", &series_arg])
+        .map_err(|e| {
+            warn_on_err(
+                "Failed to abort git am",
+                run_git(&worktree, &["am", "--abort"]),
+            );
+            format!("Patch series does not apply: {}", e)
+        })
+        .and_then(|_| run_git(&worktree, &["branch", branch, "HEAD"]).map(|_| ()));
+
+    warn_on_err(
+        "Failed to remove import worktree",
+        run_git(repo_dir, &["worktree", "remove", "--force", &worktree_arg]),
+    );
+    result
+}
+
+fn branch_name_for(title: &str) -> String {
+    let slug: String = title
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .take(30)
+        .collect();
+    format!(
+        "pads/{}-{}",
+        slug.trim_matches('-'),
+        Local::now().format("%Y%m%d-%H%M%S")
+    )
+}
+
+/// Recreate an exported workpad as a new workpad in `repo_id` (defaulting
+/// to the repository id recorded in the export).
+#[tauri::command]
+pub(crate) fn import_workpad(
+    file: String,
+    repo_id: Option<String>,
+) -> Result<WorkpadState, String> {
+    audited("import_workpad", "workpad", None, None, move || {
+        let temp = env::temp_dir().join(format!("sologit_import_{}", Uuid::new_v4().simple()));
+        fs::create_dir_all(&temp)
+            .map_err(|e| format!("Failed to create {}: {}", temp.display(), e))?;
+        let result = import_from(Path::new(file.trim()), &temp, repo_id);
+        warn_on_err("Failed to remove import scratch", fs::remove_dir_all(&temp));
+        result
+    })
+}
+
+fn import_from(file: &Path, temp: &Path, repo_id: Option<String>) -> Result<WorkpadState, String> {
+    let metadata = unpack_export(file, temp)?;
+    let source = metadata.workpad;
+    let repo_id = repo_id.unwrap_or_else(|| source.repo_id.clone());
+    let mut repo = load_repository(&repo_id)?;
+    let repo_dir = resolve_repo_path(&repo_id)?;
+
+    let branch = branch_name_for(&source.title);
+    let base = match metadata.format.as_str() {
+        "bundle" => {
+            let bundle = temp.join(BUNDLE_NAME).to_string_lossy().to_string();
+            run_git(&repo_dir, &["bundle", "verify", &bundle])?;
+            run_git(
+                &repo_dir,
+                &[
+                    "fetch",
+                    &bundle,
+                    &format!("refs/heads/{}:refs/heads/{}", source.branch_name, branch),
+                ],
+            )?;
+            if commit_exists(&repo_dir, &source.base_commit) {
+                source.base_commit.clone()
+            } else {
+                run_git(&repo_dir, &["merge-base", &repo.trunk_branch, &branch])
+                    .map(|out| out.trim().to_string())
+                    .unwrap_or_else(|_| source.base_commit.clone())
+            }
+        }
+        "mbox" => {
+            // Without the original base, replay onto trunk and let --3way sort it out.
+            let base = if commit_exists(&repo_dir, &source.base_commit) {
+                source.base_commit.clone()
+            } else {
+                run_git(&repo_dir, &["rev-parse", &repo.trunk_branch])?
+                    .trim()
+                    .to_string()
+            };
+            apply_series(&repo_dir, &temp.join(SERIES_NAME), &base, &branch)?;
+            base
+        }
+        other => return Err(format!("Unknown export format {}", other)),
+    };
+
+    let head = run_git(&repo_dir, &["rev-parse", &format!("refs/heads/{}", branch)])?
+        .trim()
+        .to_string();
+    let files_changed = run_git(
+        &repo_dir,
+        &["diff", "--name-only", &format!("{}..{}", base, head)],
+    )?
+    .lines()
+    .map(str::to_string)
+    .collect();
+
+    let now = Utc::now().to_rfc3339();
+    let workpad = WorkpadState {
+        workpad_id: format!("pad_{}", &Uuid::new_v4().simple().to_string()[..8]),
+        repo_id: repo_id.clone(),
+        title: source.title,
+        status: WorkpadStatus::Active,
+        branch_name: branch,
+        base_commit: base,
+        current_commit: Some(head),
+        created_at: now.clone(),
+        updated_at: now,
+        promoted_at: None,
+        test_runs: Vec::new(),
+        ai_operations: Vec::new(),
+        patches_applied: source.patches_applied,
+        files_changed,
+        pull_request: None,
+        template_id: None,
+        test_target: None,
+        checklist: Vec::new(),
+        tags: source.tags,
+        priority: source.priority,
+        description: source.description,
+        diff_summary: None,
+        archived_from: None,
+    };
+    let workpad = save_workpad(workpad)?;
+
+    repo.workpads.push(workpad.workpad_id.clone());
+    save_repository(repo)?;
+    Ok(workpad)
+}
@@ -0,0 +1,723 @@
+use serde::Serialize;
+
+/// How one section of a diff changes its file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum FileChange {
+    Added,
+    Deleted,
+    Modified,
+    Renamed,
+    Copied,
+}
+
+/// Header metadata and line counts for one file of a unified diff.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct FilePatch {
+    /// Path before the change; `None` for added files.
+    pub(crate) old_path: Option<String>,
+    /// Path after the change; `None` for deleted files.
+    pub(crate) new_path: Option<String>,
+    pub(crate) change: FileChange,
+    pub(crate) old_mode: Option<String>,
+    pub(crate) new_mode: Option<String>,
+    /// Percentage from `similarity index` on renames and copies.
+    pub(crate) similarity: Option<u8>,
+    pub(crate) is_binary: bool,
+    pub(crate) additions: usize,
+    pub(crate) deletions: usize,
+}
+
+impl FilePatch {
+    /// Where the file ends up, or where it was for deletions.
+    pub(crate) fn path(&self) -> &str {
+        self.new_path
+            .as_deref()
+            .or(self.old_path.as_deref())
+            .unwrap_or_default()
+    }
+}
+
+/// One side of a `---`/`+++` header.
+#[derive(Debug, Clone, PartialEq)]
+enum Side {
+    DevNull,
+    Path(String),
+}
+
+/// Everything seen of the current file so far.
+#[derive(Debug, Default)]
+struct Section {
+    git_old: Option<String>,
+    git_new: Option<String>,
+    old: Option<Side>,
+    new: Option<Side>,
+    rename_from: Option<String>,
+    rename_to: Option<String>,
+    copy_from: Option<String>,
+    copy_to: Option<String>,
+    new_file: bool,
+    deleted_file: bool,
+    old_mode: Option<String>,
+    new_mode: Option<String>,
+    similarity: Option<u8>,
+    is_binary: bool,
+    saw_hunk: bool,
+    additions: usize,
+    deletions: usize,
+}
+
+/// The hunk being read. Hunks with a well-formed `@@` header are read by
+/// their line counts, so `--- ` and `+++ ` content lines aren't mistaken
+/// for headers; malformed ones end at the first line that looks like one.
+#[derive(Debug)]
+struct Hunk {
+    old_left: u32,
+    new_left: u32,
+    counted: bool,
+}
+
+impl Section {
+    fn finish(self) -> Option<FilePatch> {
+        let side = |side: Option<Side>| match side {
+            Some(Side::Path(path)) => Some(Some(path)),
+            Some(Side::DevNull) => Some(None),
+            None => None,
+        };
+        let copied = self.copy_to.is_some();
+        let renamed = self.rename_to.is_some();
+        let old = self
+            .rename_from
+            .or(self.copy_from)
+            .map(Some)
+            .or_else(|| side(self.old))
+            .unwrap_or(self.git_old);
+        let new = self
+            .rename_to
+            .or(self.copy_to)
+            .map(Some)
+            .or_else(|| side(self.new))
+            .unwrap_or(self.git_new);
+        let (old, new) = (
+            old.filter(|path| !path.is_empty()),
+            new.filter(|path| !path.is_empty()),
+        );
+
+        let (old_path, new_path, change) = match (old, new) {
+            (None, None) => return None,
+            (_, Some(new)) if self.new_file => (None, Some(new), FileChange::Added),
+            (None, new) => (None, new, FileChange::Added),
+            (Some(old), _) if self.deleted_file => (Some(old), None, FileChange::Deleted),
+            (old, None) => (old, None, FileChange::Deleted),
+            (old, new) if copied => (old, new, FileChange::Copied),
+            (old, new) if renamed => (old, new, FileChange::Renamed),
+            (old, new) => (old, new, FileChange::Modified),
+        };
+        Some(FilePatch {
+            old_path,
+            new_path,
+            change,
+            old_mode: self.old_mode,
+            new_mode: self.new_mode,
+            similarity: self.similarity,
+            is_binary: self.is_binary,
+            additions: self.additions,
+            deletions: self.deletions,
+        })
+    }
+}
+
+/// Undo git's C-style quoting of a path starting at `text`'s opening quote,
+/// returning the path and whatever follows the closing quote.
+fn unquote(text: &str) -> Option<(String, &str)> {
+    let body = text.strip_prefix('"')?;
+    let mut bytes = Vec::new();
+    let mut chars = body.char_indices();
+    while let Some((at, c)) = chars.next() {
+        match c {
+            '"' => {
+                return Some((
+                    String::from_utf8_lossy(&bytes).into_owned(),
+                    &body[at + 1..],
+                ))
+            }
+            '\\' => {
+                let (_, escaped) = chars.next()?;
+                match escaped {
+                    'a' => bytes.push(0x07),
+                    'b' => bytes.push(0x08),
+                    't' => bytes.push(b'\t'),
+                    'n' => bytes.push(b'\n'),
+                    'v' => bytes.push(0x0b),
+                    'f' => bytes.push(0x0c),
+                    'r' => bytes.push(b'\r'),
+                    '0'..='7' => {
+                        let mut value = escaped.to_digit(8)?;
+                        for _ in 0..2 {
+                            let (_, digit) = chars.next()?;
+                            value = value * 8 + digit.to_digit(8)?;
+                        }
+                        bytes.push(u8::try_from(value).ok()?);
+                    }
+                    other => {
+                        let mut buffer = [0; 4];
+                        bytes.extend_from_slice(other.encode_utf8(&mut buffer).as_bytes());
+                    }
+                }
+            }
+            other => {
+                let mut buffer = [0; 4];
+                bytes.extend_from_slice(other.encode_utf8(&mut buffer).as_bytes());
+            }
+        }
+    }
+    None
+}
+
+fn strip_side_prefix(path: String, prefix: &str) -> String {
+    match path.strip_prefix(prefix) {
+        Some(rest) if !rest.is_empty() => rest.to_string(),
+        _ => path,
+    }
+}
+
+/// A path from a `---`/`+++` line. Unquoted paths end at a tab, which git
+/// adds after names containing spaces and other tools before a timestamp.
+fn header_side(text: &str, prefix: &str) -> Side {
+    let path = match unquote(text) {
+        Some((path, _)) => path,
+        None => text.split('\t').next().unwrap_or_default().to_string(),
+    };
+    if path == "/dev/null" {
+        Side::DevNull
+    } else {
+        Side::Path(strip_side_prefix(path, prefix))
+    }
+}
+
+/// A path from a `rename from`-style line, which has no `a/`/`b/` prefix.
+fn plain_path(text: &str) -> String {
+    match unquote(text) {
+        Some((path, _)) => path,
+        None => text.to_string(),
+    }
+}
+
+/// The two paths of `diff --git a/<old> b/<new>`. Unquoted names with
+/// spaces are ambiguous; the common case of an unchanged name is solved by
+/// splitting in the middle, and other headers override the guess anyway.
+fn git_line_paths(text: &str) -> (Option<String>, Option<String>) {
+    let split = |old: String, new: String| {
+        (
+            Some(strip_side_prefix(old, "a/")),
+            Some(strip_side_prefix(new, "b/")),
+        )
+    };
+    if let Some((old, rest)) = unquote(text) {
+        let rest = rest.strip_prefix(' ').unwrap_or(rest);
+        return split(old, plain_path(rest));
+    }
+    if text.ends_with('"') {
+        let quoted = text
+            .match_indices(" \"")
+            .map(|(at, _)| at)
+            .find(|at| unquote(&text[at + 1..]).is_some_and(|(_, rest)| rest.is_empty()));
+        if let Some(at) = quoted {
+            return split(text[..at].to_string(), plain_path(&text[at + 1..]));
+        }
+    }
+    let middle = text.len().saturating_sub(1) / 2;
+    if text.len() % 2 == 1 && text.is_char_boundary(middle) && text[middle..].starts_with(' ') {
+        let (old, new) = (&text[..middle], &text[middle + 1..]);
+        if old
+            .strip_prefix("a/")
+            .is_some_and(|old| Some(old) == new.strip_prefix("b/"))
+        {
+            return split(old.to_string(), new.to_string());
+        }
+    }
+    match text.find(" b/").or_else(|| text.find(' ')) {
+        Some(at) => split(text[..at].to_string(), text[at + 1..].to_string()),
+        None => (None, None),
+    }
+}
+
+/// Line counts of an `@@ -a,b +c,d @@` header; a missing count means 1.
+fn hunk_counts(header: &str) -> Option<(u32, u32)> {
+    let mut ranges = header.split_whitespace();
+    let count = |range: Option<&str>, sign: char| -> Option<u32> {
+        let range = range?.strip_prefix(sign)?;
+        match range.split_once(',') {
+            Some((start, count)) => {
+                start.parse::<u32>().ok()?;
+                count.parse().ok()
+            }
+            None => range.parse::<u32>().ok().map(|_| 1),
+        }
+    };
+    Some((count(ranges.next(), '-')?, count(ranges.next(), '+')?))
+}
+
+/// Parse the file sections of a unified diff: git's extended headers
+/// (new/deleted files, modes, renames and copies, binary markers), quoted
+/// paths, `/dev/null` sides and plain `---`/`+++` diffs without a
+/// `diff --git` line. Text that isn't part of a diff is skipped.
+pub(crate) fn parse_file_patches(diff: &str) -> Vec<FilePatch> {
+    let mut files = Vec::new();
+    let mut section: Option<Section> = None;
+    let mut hunk: Option<Hunk> = None;
+    let mut lines = diff.lines().peekable();
+
+    while let Some(line) = lines.next() {
+        if let (Some(current), Some(open)) = (section.as_mut(), hunk.as_mut()) {
+            let header_like = line.starts_with("diff --git ")
+                || line.starts_with("@@")
+                || (line.starts_with("--- ")
+                    && lines.peek().is_some_and(|next| next.starts_with("+++ ")));
+            let content = if open.counted {
+                open.old_left > 0 || open.new_left > 0
+            } else {
+                !header_like
+            };
+            if content {
+                match line.as_bytes().first() {
+                    Some(b'+') => {
+                        current.additions += 1;
+                        open.new_left = open.new_left.saturating_sub(1);
+                        continue;
+                    }
+                    Some(b'-') => {
+                        current.deletions += 1;
+                        open.old_left = open.old_left.saturating_sub(1);
+                        continue;
+                    }
+                    Some(b' ') | None => {
+                        open.old_left = open.old_left.saturating_sub(1);
+                        open.new_left = open.new_left.saturating_sub(1);
+                        continue;
+                    }
+                    Some(b'\\') => continue,
+                    _ => {}
+                }
+            }
+            if line.starts_with('\\') {
+                continue;
+            }
+            hunk = None;
+        }
+
+        if let Some(rest) = line.strip_prefix("diff --git ") {
+            files.extend(section.take().and_then(Section::finish));
+            let (git_old, git_new) = git_line_paths(rest);
+            section = Some(Section {
+                git_old,
+                git_new,
+                ..Section::default()
+            });
+            continue;
+        }
+        if let Some(next) = line.strip_prefix("--- ").zip(
+            lines
+                .peek()
+                .copied()
+                .and_then(|next| next.strip_prefix("+++ ")),
+        ) {
+            let (old, new) = (header_side(next.0, "a/"), header_side(next.1, "b/"));
+            lines.next();
+            // Without `diff --git` lines, each `---`/`+++` pair is a new file.
+            if section
+                .as_ref()
+                .is_none_or(|current| current.saw_hunk || current.old.is_some())
+            {
+                files.extend(section.take().and_then(Section::finish));
+                section = Some(Section::default());
+            }
+            if let Some(current) = section.as_mut() {
+                current.old = Some(old);
+                current.new = Some(new);
+            }
+            continue;
+        }
+        let Some(current) = section.as_mut() else {
+            continue;
+        };
+        if let Some(header) = line.strip_prefix("@@") {
+            current.saw_hunk = true;
+            hunk = Some(match hunk_counts(header) {
+                Some((old_left, new_left)) => Hunk {
+                    old_left,
+                    new_left,
+                    counted: true,
+                },
+                None => Hunk {
+                    old_left: 0,
+                    new_left: 0,
+                    counted: false,
+                },
+            });
+            continue;
+        }
+        if current.saw_hunk {
+            continue;
+        }
+
+        if let Some(mode) = line.strip_prefix("old mode ") {
+            current.old_mode = Some(mode.trim().to_string());
+        } else if let Some(mode) = line.strip_prefix("new mode ") {
+            current.new_mode = Some(mode.trim().to_string());
+        } else if let Some(mode) = line.strip_prefix("deleted file mode ") {
+            current.deleted_file = true;
+            current.old_mode = Some(mode.trim().to_string());
+        } else if let Some(mode) = line.strip_prefix("new file mode ") {
+            current.new_file = true;
+            current.new_mode = Some(mode.trim().to_string());
+        } else if let Some(index) = line.strip_prefix("similarity index ") {
+            current.similarity = index.trim().trim_end_matches('%').parse().ok();
+        } else if let Some(path) = line.strip_prefix("rename from ") {
+            current.rename_from = Some(plain_path(path));
+        } else if let Some(path) = line.strip_prefix("rename to ") {
+            current.rename_to = Some(plain_path(path));
+        } else if let Some(path) = line.strip_prefix("copy from ") {
+            current.copy_from = Some(plain_path(path));
+        } else if let Some(path) = line.strip_prefix("copy to ") {
+            current.copy_to = Some(plain_path(path));
+        } else if line == "GIT binary patch"
+            || (line.starts_with("Binary files ") && line.ends_with(" differ"))
+        {
+            current.is_binary = true;
+        }
+    }
+    files.extend(section.and_then(Section::finish));
+    files
+}
+
+/// Every path a diff touches, both sides of renames and copies included,
+/// sorted and without duplicates.
+pub(crate) fn parse_changed_files(diff: &str) -> Vec<String> {
+    let mut paths: Vec<String> = parse_file_patches(diff)
+        .into_iter()
+        .flat_map(|file| [file.old_path, file.new_path])
+        .flatten()
+        .collect();
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+/// Added and removed lines across all files of a diff.
+pub(crate) fn line_counts(diff: &str) -> (usize, usize) {
+    parse_file_patches(diff)
+        .iter()
+        .fold((0, 0), |(added, removed), file| {
+            (added + file.additions, removed + file.deletions)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    /// One line per file: change, both paths, added and removed lines.
+    fn summary(diff: &str) -> Vec<String> {
+        parse_file_patches(diff)
+            .iter()
+            .map(|file| {
+                format!(
+                    "{:?} {} -> {} +{} -{}",
+                    file.change,
+                    file.old_path.as_deref().unwrap_or("/dev/null"),
+                    file.new_path.as_deref().unwrap_or("/dev/null"),
+                    file.additions,
+                    file.deletions
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn modified_file() {
+        let diff = "diff --git a/src/lib.rs b/src/lib.rs\nindex 1111111..2222222 100644\n\
+--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,2 +1,2 @@\n fn main() {\n-    old();\n+    new();\n";
+        assert_eq!(
+            summary(diff),
+            vec!["Modified src/lib.rs -> src/lib.rs +1 -1"]
+        );
+    }
+
+    #[test]
+    fn added_and_deleted_files() {
+        let diff = "diff --git a/new.txt b/new.txt\nnew file mode 100644\nindex 0000000..e69de29\n\
+--- /dev/null\n+++ b/new.txt\n@@ -0,0 +1,2 @@\n+one\n+two\n\
+diff --git a/old.txt b/old.txt\ndeleted file mode 100755\nindex e69de29..0000000\n\
+--- a/old.txt\n+++ /dev/null\n@@ -1 +0,0 @@\n-gone\n";
+        let files = parse_file_patches(diff);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].change, FileChange::Added);
+        assert_eq!(files[0].old_path, None);
+        assert_eq!(files[0].new_mode.as_deref(), Some("100644"));
+        assert_eq!(files[0].additions, 2);
+        assert_eq!(files[1].change, FileChange::Deleted);
+        assert_eq!(files[1].path(), "old.txt");
+        assert_eq!(files[1].old_mode.as_deref(), Some("100755"));
+        assert_eq!(files[1].deletions, 1);
+    }
+
+    #[test]
+    fn empty_new_file_without_hunks() {
+        let diff = "diff --git a/empty b/empty\nnew file mode 100644\nindex 0000000..e69de29\n";
+        assert_eq!(summary(diff), vec!["Added /dev/null -> empty +0 -0"]);
+    }
+
+    #[test]
+    fn rename_and_copy() {
+        let diff = "diff --git a/old name.rs b/new name.rs\nsimilarity index 90%\n\
+rename from old name.rs\nrename to new name.rs\nindex 1111111..2222222 100644\n\
+--- a/old name.rs\t\n+++ b/new name.rs\t\n@@ -1 +1 @@\n-a\n+b\n\
+diff --git a/a.rs b/c.rs\nsimilarity index 100%\ncopy from a.rs\ncopy to c.rs\n";
+        let files = parse_file_patches(diff);
+        assert_eq!(files[0].change, FileChange::Renamed);
+        assert_eq!(files[0].old_path.as_deref(), Some("old name.rs"));
+        assert_eq!(files[0].new_path.as_deref(), Some("new name.rs"));
+        assert_eq!(files[0].similarity, Some(90));
+        assert_eq!(files[1].change, FileChange::Copied);
+        assert_eq!(files[1].old_path.as_deref(), Some("a.rs"));
+        assert_eq!(files[1].new_path.as_deref(), Some("c.rs"));
+        assert_eq!(
+            parse_changed_files(diff),
+            vec!["a.rs", "c.rs", "new name.rs", "old name.rs"]
+        );
+    }
+
+    #[test]
+    fn quoted_paths() {
+        let diff =
+            "diff --git \"a/caf\\303\\251 \\\"x\\\".txt\" \"b/caf\\303\\251 \\\"x\\\".txt\"\n\
+index 1111111..2222222 100644\n--- \"a/caf\\303\\251 \\\"x\\\".txt\"\n\
++++ \"b/caf\\303\\251 \\\"x\\\".txt\"\n@@ -1 +1 @@\n-a\n+b\n";
+        assert_eq!(parse_changed_files(diff), vec!["café \"x\".txt"]);
+    }
+
+    #[test]
+    fn mode_change_and_binary() {
+        let diff = "diff --git a/run.sh b/run.sh\nold mode 100644\nnew mode 100755\n\
+diff --git a/logo.png b/logo.png\nindex 1111111..2222222 100644\n\
+Binary files a/logo.png and b/logo.png differ\n";
+        let files = parse_file_patches(diff);
+        assert_eq!(files[0].change, FileChange::Modified);
+        assert_eq!(files[0].old_mode.as_deref(), Some("100644"));
+        assert_eq!(files[0].new_mode.as_deref(), Some("100755"));
+        assert_eq!(files[1].path(), "logo.png");
+        assert!(files[1].is_binary);
+    }
+
+    #[test]
+    fn header_lookalikes_inside_hunks() {
+        let diff = "--- a/notes.md\n+++ b/notes.md\n@@ -1,2 +1,2 @@\n--- a/other\n+++ b/other\n \
+context\n";
+        assert_eq!(summary(diff), vec!["Modified notes.md -> notes.md +1 -1"]);
+    }
+
+    #[test]
+    fn plain_diffs_without_git_lines() {
+        let diff = "--- a.txt\t2024-01-01 00:00:00\n+++ a.txt\t2024-01-02 00:00:00\n@@ -1 +1 @@\n\
+-x\n+y\n--- /dev/null\n+++ b/b.txt\n@@ -0,0 +1 @@\n+new\n";
+        assert_eq!(
+            summary(diff),
+            vec![
+                "Modified a.txt -> a.txt +1 -1",
+                "Added /dev/null -> b.txt +1 -0",
+            ]
+        );
+    }
+
+    #[test]
+    fn malformed_hunk_headers_read_loosely() {
+        let diff = "--- a/x.py\n+++ b/x.py\n@@ ... @@\n-old\n+new\n+more\n\
+--- a/y.py\n+++ b/y.py\n@@\n+y\n";
+        assert_eq!(
+            summary(diff),
+            vec!["Modified x.py -> x.py +2 -1", "Modified y.py -> y.py +1 -0"]
+        );
+    }
+
+    /// C-quote `path` the way git does for names with special characters.
+    fn quote(path: &str) -> String {
+        let special = |b: u8| b < 0x20 || b == 0x7f || b == b'"' || b == b'\\' || b >= 0x80;
+        if !path.bytes().any(special) {
+            return path.to_string();
+        }
+        let mut quoted = String::from("\"");
+        for byte in path.bytes() {
+            match byte {
+                b'"' => quoted.push_str("\\\""),
+                b'\\' => quoted.push_str("\\\\"),
+                b'\t' => quoted.push_str("\\t"),
+                b'\n' => quoted.push_str("\\n"),
+                b if special(b) => quoted.push_str(&format!("\\{:03o}", b)),
+                b => quoted.push(b as char),
+            }
+        }
+        quoted.push('"');
+        quoted
+    }
+
+    /// `prefix` + `path`, quoted as a whole like git does.
+    fn side(prefix: &str, path: &str) -> String {
+        let full = format!("{}{}", prefix, path);
+        let quoted = quote(&full);
+        if quoted == full && full.contains(' ') {
+            format!("{}\t", full)
+        } else {
+            quoted
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct Spec {
+        old: String,
+        new: String,
+        change: FileChange,
+        removed: Vec<String>,
+        added: Vec<String>,
+    }
+
+    fn path() -> impl Strategy<Value = String> {
+        prop::collection::vec("[a-zA-Z0-9_.é\" \\\\\t-]{1,8}", 1..4)
+            .prop_map(|parts| parts.join("/"))
+            .prop_filter("no /dev/null", |path| path != "dev/null")
+    }
+
+    fn spec() -> impl Strategy<Value = Spec> {
+        let lines = |min| prop::collection::vec("[ -~]{0,12}", min..4);
+        (
+            path(),
+            path(),
+            prop_oneof![
+                Just(FileChange::Added),
+                Just(FileChange::Deleted),
+                Just(FileChange::Modified),
+                Just(FileChange::Renamed),
+                Just(FileChange::Copied),
+            ],
+            lines(1),
+            lines(1),
+        )
+            .prop_map(|(old, new, change, removed, added)| {
+                let (removed, added) = match change {
+                    FileChange::Added => (Vec::new(), added),
+                    FileChange::Deleted => (removed, Vec::new()),
+                    _ => (removed, added),
+                };
+                let new = match change {
+                    FileChange::Renamed | FileChange::Copied if new == old => format!("{}2", new),
+                    FileChange::Renamed | FileChange::Copied => new,
+                    _ => old.clone(),
+                };
+                Spec {
+                    old,
+                    new,
+                    change,
+                    removed,
+                    added,
+                }
+            })
+    }
+
+    fn render(spec: &Spec) -> String {
+        let mut out = format!(
+            "diff --git {} {}\n",
+            quote(&format!("a/{}", spec.old)),
+            quote(&format!("b/{}", spec.new))
+        );
+        let (old_side, new_side) = match spec.change {
+            FileChange::Added => {
+                out.push_str("new file mode 100644\n");
+                ("/dev/null".to_string(), side("b/", &spec.new))
+            }
+            FileChange::Deleted => {
+                out.push_str("deleted file mode 100644\n");
+                (side("a/", &spec.old), "/dev/null".to_string())
+            }
+            FileChange::Renamed | FileChange::Copied => {
+                let verb = if spec.change == FileChange::Renamed {
+                    "rename"
+                } else {
+                    "copy"
+                };
+                out.push_str(&format!(
+                    "similarity index 50%\n{verb} from {}\n{verb} to {}\n",
+                    quote(&spec.old),
+                    quote(&spec.new)
+                ));
+                (side("a/", &spec.old), side("b/", &spec.new))
+            }
+            FileChange::Modified => (side("a/", &spec.old), side("b/", &spec.new)),
+        };
+        out.push_str("index 1111111..2222222 100644\n");
+        out.push_str(&format!("--- {}\n+++ {}\n", old_side, new_side));
+        out.push_str(&format!(
+            "@@ -1,{} +1,{} @@\n",
+            spec.removed.len(),
+            spec.added.len()
+        ));
+        for line in &spec.removed {
+            out.push_str(&format!("-{}\n", line));
+        }
+        for line in &spec.added {
+            out.push_str(&format!("+{}\n", line));
+        }
+        out
+    }
+
+    proptest! {
+        #[test]
+        fn rendered_diffs_round_trip(specs in prop::collection::vec(spec(), 1..5)) {
+            let diff: String = specs.iter().map(render).collect();
+            let files = parse_file_patches(&diff);
+            prop_assert_eq!(files.len(), specs.len());
+            for (file, spec) in files.iter().zip(&specs) {
+                prop_assert_eq!(file.change, spec.change);
+                let old = (spec.change != FileChange::Added).then(|| spec.old.clone());
+                let new = (spec.change != FileChange::Deleted).then(|| spec.new.clone());
+                prop_assert_eq!(&file.old_path, &old);
+                prop_assert_eq!(&file.new_path, &new);
+                prop_assert_eq!(file.deletions, spec.removed.len());
+                prop_assert_eq!(file.additions, spec.added.len());
+            }
+        }
+
+        #[test]
+        fn arbitrary_text_never_panics(text in "(?s).{0,400}") {
+            let files = parse_file_patches(&text);
+            let (added, removed) = line_counts(&text);
+            prop_assert!(added + removed <= text.lines().count());
+            prop_assert_eq!(added, files.iter().map(|file| file.additions).sum::<usize>());
+            let paths = parse_changed_files(&text);
+            prop_assert!(paths.windows(2).all(|pair| pair[0] < pair[1]));
+            prop_assert!(paths.iter().all(|path| !path.is_empty()));
+        }
+
+        #[test]
+        fn diff_shaped_noise_never_panics(
+            lines in prop::collection::vec(
+                prop_oneof![
+                    "diff --git [ -~]{0,20}",
+                    "--- [ -~]{0,12}",
+                    "\\+\\+\\+ [ -~]{0,12}",
+                    "@@ -[0-9]{1,2}(,[0-9]{1,2})? \\+[0-9]{1,2}(,[0-9]{1,2})? @@",
+                    "[-+ \\\\][ -~]{0,10}",
+                    "(rename|copy) (from|to) \"?[ -~]{0,10}",
+                    "(new|deleted) file mode 100644",
+                ],
+                0..40,
+            )
+        ) {
+            let diff = lines.join("\n");
+            for file in parse_file_patches(&diff) {
+                prop_assert!(file.old_path.is_some() || file.new_path.is_some());
+            }
+        }
+    }
+}
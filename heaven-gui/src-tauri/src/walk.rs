@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+use ignore::{DirEntry, WalkBuilder, WalkState};
+
+/// A walker over `root` that never descends into `.git` and, when
+/// `gitignore` is set, honours `.gitignore`, `.git/info/exclude` and the
+/// global excludes file.
+pub(crate) fn walk_builder(root: &Path, gitignore: bool, include_hidden: bool) -> WalkBuilder {
+    let mut builder = WalkBuilder::new(root);
+    builder
+        .standard_filters(false)
+        .hidden(!include_hidden)
+        .git_ignore(gitignore)
+        .git_exclude(gitignore)
+        .git_global(gitignore)
+        .parents(gitignore)
+        // Match the rules git applies even outside a work tree (e.g. bare exports).
+        .require_git(false)
+        // Symlinks are reported as leaves, never traversed.
+        .follow_links(false)
+        .filter_entry(|entry| entry.file_name() != ".git");
+    builder
+}
+
+/// Visit every entry under `root` on the walker's thread pool, sending what
+/// `visit` returns back to the caller. The first walk error stops the walk.
+pub(crate) fn walk_parallel<T: Send>(
+    builder: &WalkBuilder,
+    visit: impl Fn(&DirEntry) -> Option<T> + Sync,
+) -> Result<Vec<T>, String> {
+    let (sender, receiver) = mpsc::channel();
+    builder.build_parallel().run(|| {
+        let sender = sender.clone();
+        let visit = &visit;
+        Box::new(move |entry| match entry {
+            Ok(entry) => {
+                if let Some(item) = visit(&entry) {
+                    // The receiver outlives the walk, so sending can't fail.
+                    sender.send(Ok(item)).ok();
+                }
+                WalkState::Continue
+            }
+            Err(e) => {
+                sender.send(Err(e.to_string())).ok();
+                WalkState::Quit
+            }
+        })
+    });
+    drop(sender);
+    receiver.into_iter().collect()
+}
+
+/// One node of a `DirArena`; children are indices into the same arena.
+pub(crate) struct ArenaEntry {
+    pub(crate) path: PathBuf,
+    pub(crate) is_dir: bool,
+    pub(crate) depth: usize,
+    pub(crate) children: Vec<usize>,
+}
+
+/// A directory tree walked in parallel and held flat, with the root at
+/// index 0 and each directory's children in name order.
+pub(crate) struct DirArena {
+    pub(crate) entries: Vec<ArenaEntry>,
+}
+
+impl DirArena {
+    /// Walk `root` down to `max_depth`, filtered like `read_dir_filtered`.
+    pub(crate) fn walk(root: &Path, gitignore: bool, max_depth: usize) -> Result<Self, String> {
+        let mut found = walk_parallel(
+            walk_builder(root, gitignore, false).max_depth(Some(max_depth)),
+            |entry| {
+                (entry.depth() > 0).then(|| {
+                    let is_dir = entry.file_type().is_some_and(|kind| kind.is_dir());
+                    (entry.path().to_path_buf(), is_dir, entry.depth())
+                })
+            },
+        )?;
+        // Parents sort before their children, so each parent is already
+        // placed when its children arrive.
+        found.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+
+        let mut entries = Vec::with_capacity(found.len() + 1);
+        entries.push(ArenaEntry {
+            path: root.to_path_buf(),
+            is_dir: true,
+            depth: 0,
+            children: Vec::new(),
+        });
+        let mut index: HashMap<PathBuf, usize> = HashMap::new();
+        index.insert(root.to_path_buf(), 0);
+        for (path, is_dir, depth) in found {
+            let Some(&parent) = path.parent().and_then(|parent| index.get(parent)) else {
+                continue;
+            };
+            let id = entries.len();
+            entries[parent].children.push(id);
+            if is_dir {
+                index.insert(path.clone(), id);
+            }
+            entries.push(ArenaEntry {
+                path,
+                is_dir,
+                depth,
+                children: Vec::new(),
+            });
+        }
+        Ok(DirArena { entries })
+    }
+}
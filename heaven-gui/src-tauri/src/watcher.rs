@@ -0,0 +1,103 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+/// A recursive filesystem watch. Dropping it stops the underlying watcher.
+pub(crate) struct FsWatch {
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+}
+
+/// Outcome of waiting for a debounced batch of changes.
+pub(crate) enum WatchBatch {
+    Changed(Vec<PathBuf>),
+    Idle,
+    Disconnected,
+}
+
+fn is_content_change(kind: &EventKind) -> bool {
+    matches!(
+        kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    )
+}
+
+fn collect(event: notify::Result<Event>, paths: &mut Vec<PathBuf>) {
+    if let Ok(event) = event {
+        if is_content_change(&event.kind) {
+            for path in event.paths {
+                if !paths.contains(&path) {
+                    paths.push(path);
+                }
+            }
+        }
+    }
+}
+
+impl FsWatch {
+    pub(crate) fn new(path: &Path) -> Result<Self, String> {
+        Self::watching(&[path], RecursiveMode::Recursive)
+    }
+
+    /// Watch only the direct entries of each of `dirs`, for following a few
+    /// files without the churn of everything beneath them.
+    pub(crate) fn shallow(dirs: &[&Path]) -> Result<Self, String> {
+        Self::watching(dirs, RecursiveMode::NonRecursive)
+    }
+
+    fn watching(paths: &[&Path], mode: RecursiveMode) -> Result<Self, String> {
+        let (tx, rx) = channel();
+        let mut watcher = notify::recommended_watcher(tx)
+            .map_err(|e| format!("Failed to create watcher: {}", e))?;
+        for path in paths {
+            watcher
+                .watch(path, mode)
+                .map_err(|e| format!("Failed to watch {}: {}", path.display(), e))?;
+        }
+
+        Ok(FsWatch {
+            _watcher: watcher,
+            events: rx,
+        })
+    }
+
+    /// Wait up to `poll` for a change, then keep collecting until `debounce`
+    /// passes without further events. Paths are deduplicated.
+    pub(crate) fn next_batch(&self, poll: Duration, debounce: Duration) -> WatchBatch {
+        let mut paths: Vec<PathBuf> = Vec::new();
+
+        match self.events.recv_timeout(poll) {
+            Ok(event) => collect(event, &mut paths),
+            Err(RecvTimeoutError::Timeout) => return WatchBatch::Idle,
+            Err(RecvTimeoutError::Disconnected) => return WatchBatch::Disconnected,
+        }
+
+        let mut quiet_since = Instant::now();
+        while quiet_since.elapsed() < debounce {
+            match self
+                .events
+                .recv_timeout(debounce.saturating_sub(quiet_since.elapsed()))
+            {
+                Ok(event) => {
+                    collect(event, &mut paths);
+                    quiet_since = Instant::now();
+                }
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return WatchBatch::Disconnected,
+            }
+        }
+
+        if paths.is_empty() {
+            WatchBatch::Idle
+        } else {
+            WatchBatch::Changed(paths)
+        }
+    }
+
+    /// Discard any queued events (e.g. ones caused by our own test run).
+    pub(crate) fn drain(&self) {
+        while self.events.try_recv().is_ok() {}
+    }
+}
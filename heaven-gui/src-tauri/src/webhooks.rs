@@ -0,0 +1,282 @@
+use std::thread;
+use std::time::Duration;
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::audit::audited;
+use crate::http::agent;
+use crate::statuses::TestRunStatus;
+use crate::{get_settings, write_settings, TestRun};
+
+pub(crate) const WEBHOOK_EVENTS: &[&str] = &["promotion", "test_failure", "budget_threshold"];
+const MAX_ATTEMPTS: u32 = 4;
+const SIGNATURE_HEADER: &str = "X-SoloGit-Signature";
+const TIMESTAMP_HEADER: &str = "X-SoloGit-Timestamp";
+/// Stands in for a webhook secret in anything sent to the frontend.
+const REDACTED_SECRET: &str = "********";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct WebhookConfig {
+    webhook_id: String,
+    url: String,
+    /// Subset of `WEBHOOK_EVENTS`; empty means all of them.
+    #[serde(default)]
+    events: Vec<String>,
+    /// HMAC-SHA256 key for the signature header.
+    secret: String,
+    #[serde(default = "default_enabled")]
+    enabled: bool,
+    #[serde(default)]
+    created_at: String,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl WebhookConfig {
+    fn wants(&self, event: &str) -> bool {
+        self.enabled && (self.events.is_empty() || self.events.iter().any(|e| e == event))
+    }
+}
+
+/// Hide webhook secrets before settings leave the backend.
+pub(crate) fn redact_secrets(webhooks: &mut [WebhookConfig]) {
+    for webhook in webhooks {
+        webhook.secret = REDACTED_SECRET.to_string();
+    }
+}
+
+/// Put back the stored secret of any webhook the frontend sent back still
+/// redacted.
+pub(crate) fn restore_secrets(webhooks: &mut [WebhookConfig], saved: &[WebhookConfig]) {
+    for webhook in webhooks
+        .iter_mut()
+        .filter(|webhook| webhook.secret == REDACTED_SECRET)
+    {
+        if let Some(stored) = saved.iter().find(|s| s.webhook_id == webhook.webhook_id) {
+            webhook.secret = stored.secret.clone();
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct WebhookDelivery {
+    webhook_id: String,
+    event: String,
+    delivered: bool,
+    attempts: u32,
+    status_code: Option<u16>,
+    error: Option<String>,
+}
+
+/// Signature over `<timestamp>.<body>`, so receivers can reject replays of
+/// an old delivery by checking the timestamp header.
+fn sign(secret: &str, timestamp: i64, body: &str) -> Result<String, String> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .map_err(|e| format!("Invalid webhook secret: {}", e))?;
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+    let digest = mac.finalize().into_bytes();
+    let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+    Ok(format!("sha256={}", hex))
+}
+
+/// POST one payload, retrying server errors and network failures with
+/// exponential backoff.
+fn deliver(webhook: &WebhookConfig, event: &str, payload: &Value) -> WebhookDelivery {
+    let body = json!({
+        "event": event,
+        "delivery_id": Uuid::new_v4().to_string(),
+        "sent_at": Utc::now().to_rfc3339(),
+        "data": payload,
+    })
+    .to_string();
+
+    let mut delivery = WebhookDelivery {
+        webhook_id: webhook.webhook_id.clone(),
+        event: event.to_string(),
+        delivered: false,
+        attempts: 0,
+        status_code: None,
+        error: None,
+    };
+    let timestamp = Utc::now().timestamp();
+    let signature = match sign(&webhook.secret, timestamp, &body) {
+        Ok(signature) => signature,
+        Err(e) => {
+            tracing::warn!("Webhook {} not delivered: {}", webhook.webhook_id, e);
+            delivery.error = Some(e);
+            return delivery;
+        }
+    };
+    let timestamp = timestamp.to_string();
+    let agent = agent();
+    while delivery.attempts < MAX_ATTEMPTS {
+        if delivery.attempts > 0 {
+            thread::sleep(Duration::from_secs(2u64.pow(delivery.attempts)));
+        }
+        delivery.attempts += 1;
+        let result = agent
+            .post(&webhook.url)
+            .set("Content-Type", "application/json")
+            .set("X-SoloGit-Event", event)
+            .set(TIMESTAMP_HEADER, &timestamp)
+            .set(SIGNATURE_HEADER, &signature)
+            .send_string(&body);
+        match result {
+            Ok(response) => {
+                delivery.status_code = Some(response.status());
+                delivery.delivered = true;
+                delivery.error = None;
+                break;
+            }
+            Err(ureq::Error::Status(code, _)) => {
+                delivery.status_code = Some(code);
+                delivery.error = Some(format!("{} returned {}", webhook.url, code));
+                // Client errors won't fix themselves on retry.
+                if code < 500 && code != 429 {
+                    break;
+                }
+            }
+            Err(e) => delivery.error = Some(e.to_string()),
+        }
+    }
+    if let Some(error) = &delivery.error {
+        tracing::warn!(
+            "Webhook {} delivery of {} failed after {} attempts: {}",
+            webhook.webhook_id,
+            event,
+            delivery.attempts,
+            error
+        );
+    }
+    delivery
+}
+
+/// Send `event` to every subscribed webhook in the background.
+pub(crate) fn fire(event: &'static str, payload: Value) {
+    let webhooks: Vec<WebhookConfig> = match get_settings() {
+        Ok(settings) => settings
+            .integrations
+            .webhooks
+            .into_iter()
+            .filter(|webhook| webhook.wants(event))
+            .collect(),
+        Err(e) => {
+            tracing::warn!("Skipping {} webhooks: {}", event, e);
+            return;
+        }
+    };
+    for webhook in webhooks {
+        let payload = payload.clone();
+        thread::spawn(move || deliver(&webhook, event, &payload));
+    }
+}
+
+pub(crate) fn fire_test_run(run: &TestRun) {
+    if run.status == TestRunStatus::Failed {
+        fire("test_failure", json!(run));
+    }
+}
+
+/// Fire "budget_threshold"; `cost::notify_budget` limits it to once a month.
+pub(crate) fn fire_budget_threshold(payload: Value) {
+    fire("budget_threshold", payload);
+}
+
+fn validate_events(events: &[String]) -> Result<(), String> {
+    match events
+        .iter()
+        .find(|event| !WEBHOOK_EVENTS.contains(&event.as_str()))
+    {
+        Some(unknown) => Err(format!(
+            "Unknown webhook event {} (expected one of {})",
+            unknown,
+            WEBHOOK_EVENTS.join(", ")
+        )),
+        None => Ok(()),
+    }
+}
+
+#[tauri::command]
+pub(crate) fn list_webhooks() -> Result<Vec<WebhookConfig>, String> {
+    let mut webhooks = get_settings()?.integrations.webhooks;
+    redact_secrets(&mut webhooks);
+    Ok(webhooks)
+}
+
+/// Register a webhook. A signing secret is generated when none is given;
+/// this is the only response that includes it.
+#[tauri::command]
+pub(crate) fn register_webhook(
+    url: String,
+    events: Option<Vec<String>>,
+    secret: Option<String>,
+) -> Result<WebhookConfig, String> {
+    audited("register_webhook", "webhook", None, None, move || {
+        let url = url.trim().to_string();
+        if !url.starts_with("https://") && !url.starts_with("http://") {
+            return Err("Webhook URL must be http(s)".to_string());
+        }
+        let events = events.unwrap_or_default();
+        validate_events(&events)?;
+
+        let webhook = WebhookConfig {
+            webhook_id: format!("wh_{}", &Uuid::new_v4().simple().to_string()[..8]),
+            url,
+            events,
+            secret: secret
+                .filter(|secret| !secret.is_empty())
+                .unwrap_or_else(|| Uuid::new_v4().simple().to_string()),
+            enabled: true,
+            created_at: Utc::now().to_rfc3339(),
+        };
+        let mut settings = get_settings()?;
+        settings.integrations.webhooks.push(webhook.clone());
+        write_settings(&settings)?;
+        Ok(webhook)
+    })
+}
+
+#[tauri::command]
+pub(crate) fn delete_webhook(webhook_id: String) -> Result<(), String> {
+    audited(
+        "delete_webhook",
+        "webhook",
+        Some(webhook_id.clone()),
+        None,
+        move || {
+            let mut settings = get_settings()?;
+            let before = settings.integrations.webhooks.len();
+            settings
+                .integrations
+                .webhooks
+                .retain(|webhook| webhook.webhook_id != webhook_id);
+            if settings.integrations.webhooks.len() == before {
+                return Err(format!("Webhook not found: {}", webhook_id));
+            }
+            write_settings(&settings)
+        },
+    )
+}
+
+/// Send a sample payload synchronously so the user can check the endpoint.
+#[tauri::command]
+pub(crate) fn test_webhook(webhook_id: String) -> Result<WebhookDelivery, String> {
+    let webhook = get_settings()?
+        .integrations
+        .webhooks
+        .into_iter()
+        .find(|webhook| webhook.webhook_id == webhook_id)
+        .ok_or_else(|| format!("Webhook not found: {}", webhook_id))?;
+    Ok(deliver(
+        &webhook,
+        "test",
+        &json!({ "message": "Test delivery from Solo Git" }),
+    ))
+}
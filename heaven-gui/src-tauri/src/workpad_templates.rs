@@ -0,0 +1,347 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::audit::audited;
+use crate::commands::{
+    apply_patch, create_workpad, delete_workpad, load_workpad, read_json, save_workpad, write_json,
+};
+use crate::logging::warn_on_err;
+use crate::paths::is_plain_id;
+use crate::templates::{delete_saved, list_saved, substitute, Template};
+use crate::{get_state_dir, WorkpadState};
+
+/// File created on the workpad branch when a template is instantiated.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct TemplateFile {
+    path: String,
+    #[serde(default)]
+    content: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct ChecklistItem {
+    text: String,
+    #[serde(default)]
+    done: bool,
+}
+
+/// Recipe for a recurring kind of workpad. `title_pattern` accepts the
+/// `{{title}}`, `{{date}}` and `{{repo}}` placeholders.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct WorkpadTemplate {
+    template_id: String,
+    name: String,
+    #[serde(default)]
+    description: String,
+    title_pattern: String,
+    #[serde(default)]
+    files: Vec<TemplateFile>,
+    /// Unified diff applied after `files` are created.
+    #[serde(default)]
+    scaffold_patch: Option<String>,
+    #[serde(default)]
+    test_target: Option<String>,
+    #[serde(default)]
+    checklist: Vec<String>,
+    #[serde(default)]
+    builtin: bool,
+    #[serde(default)]
+    created_at: String,
+    #[serde(default)]
+    updated_at: String,
+}
+
+impl Template for WorkpadTemplate {
+    fn template_id(&self) -> &str {
+        &self.template_id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+const BUILTIN_TEMPLATES: &[(&str, &str, &str, &str, &str, &[&str])] = &[
+    (
+        "bugfix",
+        "Bugfix",
+        "Reproduce, fix and verify a defect",
+        "Fix: {{title}}",
+        "fast",
+        &[
+            "Add a test that reproduces the bug",
+            "Fix the defect",
+            "Run the full test suite",
+            "Note the root cause in the commit message",
+        ],
+    ),
+    (
+        "new-endpoint",
+        "New endpoint",
+        "Add an API endpoint with tests and docs",
+        "Endpoint: {{title}}",
+        "full",
+        &[
+            "Define the request and response shapes",
+            "Implement the handler",
+            "Add tests for success and error cases",
+            "Document the endpoint",
+        ],
+    ),
+    (
+        "refactor",
+        "Refactor",
+        "Restructure code without changing behaviour",
+        "Refactor: {{title}}",
+        "full",
+        &[
+            "Confirm existing tests cover the code being moved",
+            "Refactor in small patches",
+            "Run the full test suite",
+        ],
+    ),
+];
+
+fn templates_dir() -> PathBuf {
+    get_state_dir().join("workpad_templates")
+}
+
+fn template_path(template_id: &str) -> Result<PathBuf, String> {
+    if !is_plain_id(template_id) {
+        return Err(format!("Invalid workpad template ID: {}", template_id));
+    }
+    Ok(templates_dir().join(format!("{}.json", template_id)))
+}
+
+fn builtin_templates() -> Vec<WorkpadTemplate> {
+    BUILTIN_TEMPLATES
+        .iter()
+        .map(
+            |(id, name, description, title_pattern, test_target, checklist)| WorkpadTemplate {
+                template_id: id.to_string(),
+                name: name.to_string(),
+                description: description.to_string(),
+                title_pattern: title_pattern.to_string(),
+                files: Vec::new(),
+                scaffold_patch: None,
+                test_target: Some(test_target.to_string()),
+                checklist: checklist.iter().map(|item| item.to_string()).collect(),
+                builtin: true,
+                created_at: String::new(),
+                updated_at: String::new(),
+            },
+        )
+        .collect()
+}
+
+fn load_template(template_id: &str) -> Result<WorkpadTemplate, String> {
+    if let Some(template) = read_json(&template_path(template_id)?)? {
+        return Ok(template);
+    }
+    builtin_templates()
+        .into_iter()
+        .find(|template| template.template_id == template_id)
+        .ok_or_else(|| format!("Workpad template not found: {}", template_id))
+}
+
+/// Render `files` as a single "new file" diff so they go through the same
+/// apply path as any other patch.
+fn files_diff(files: &[TemplateFile]) -> String {
+    let mut diff = String::new();
+    for file in files {
+        let path = file.path.trim_start_matches('/');
+        diff.push_str(&format!(
+            "diff --git a/{path} b/{path}\nnew file mode 100644\n--- /dev/null\n+++ b/{path}\n"
+        ));
+        let lines: Vec<&str> = file.content.lines().collect();
+        if lines.is_empty() {
+            continue;
+        }
+        diff.push_str(&format!("@@ -0,0 +1,{} @@\n", lines.len()));
+        for line in &lines {
+            diff.push('+');
+            diff.push_str(line);
+            diff.push('\n');
+        }
+        if !file.content.ends_with('\n') {
+            diff.push_str("\\ No newline at end of file\n");
+        }
+    }
+    diff
+}
+
+#[tauri::command]
+pub(crate) fn list_workpad_templates() -> Result<Vec<WorkpadTemplate>, String> {
+    list_saved(&templates_dir(), builtin_templates())
+}
+
+#[tauri::command]
+pub(crate) fn save_workpad_template(template: WorkpadTemplate) -> Result<WorkpadTemplate, String> {
+    let template_id = Some(template.template_id.clone()).filter(|id| !id.trim().is_empty());
+    audited(
+        "save_workpad_template",
+        "workpad_template",
+        template_id,
+        None,
+        move || {
+            let mut template = template;
+            template.name = template.name.trim().to_string();
+            if template.name.is_empty() {
+                return Err("Template name cannot be empty".to_string());
+            }
+            if !template.title_pattern.contains("{{title}}") {
+                return Err("Title pattern must contain {{title}}".to_string());
+            }
+            if template.files.iter().any(|file| {
+                file.path.trim().is_empty() || file.path.split('/').any(|part| part == "..")
+            }) {
+                return Err("Template file paths must be relative to the repository".to_string());
+            }
+
+            if template.template_id.trim().is_empty() {
+                template.template_id = format!("wpt-{}", Uuid::new_v4().simple());
+            }
+            let path = template_path(&template.template_id)?;
+            let now = Utc::now().to_rfc3339();
+            let existing = load_template(&template.template_id).ok();
+            template.builtin = existing.as_ref().is_some_and(|t| t.builtin);
+            template.created_at = existing
+                .map(|t| t.created_at)
+                .filter(|created| !created.is_empty())
+                .unwrap_or_else(|| now.clone());
+            template.updated_at = now;
+            write_json(&path, &template)?;
+            Ok(template)
+        },
+    )
+}
+
+#[tauri::command]
+pub(crate) fn delete_workpad_template(template_id: String) -> Result<(), String> {
+    audited(
+        "delete_workpad_template",
+        "workpad_template",
+        Some(template_id.clone()),
+        None,
+        move || {
+            let builtin = builtin_templates()
+                .iter()
+                .any(|template| template.template_id == template_id);
+            delete_saved(
+                &template_path(&template_id)?,
+                &template_id,
+                builtin,
+                "Workpad template",
+            )
+        },
+    )
+}
+
+/// Create a workpad titled from the template's pattern (the template name
+/// stands in for a missing title), seed it with the
+/// template's files and scaffold patch, and attach its checklist.
+#[tauri::command]
+pub(crate) fn create_workpad_from_template(
+    repo_id: String,
+    template_id: String,
+    title: Option<String>,
+) -> Result<WorkpadState, String> {
+    audited(
+        "create_workpad_from_template",
+        "workpad",
+        None,
+        None,
+        move || {
+            let template = load_template(&template_id)?;
+            let title = title
+                .as_deref()
+                .map(str::trim)
+                .filter(|title| !title.is_empty())
+                .unwrap_or(&template.name);
+
+            let values = HashMap::from([
+                ("title".to_string(), title.to_string()),
+                (
+                    "date".to_string(),
+                    Utc::now().format("%Y-%m-%d").to_string(),
+                ),
+                ("repo".to_string(), repo_id.clone()),
+            ]);
+            let full_title = substitute(&template.title_pattern, &values)
+                .trim()
+                .to_string();
+
+            let workpad = create_workpad(repo_id, full_title)?;
+            let workpad_id = workpad.workpad_id.clone();
+            let seeded = seed(workpad, &template);
+            if seeded.is_err() {
+                // Don't leave a half-scaffolded workpad behind.
+                warn_on_err(
+                    "Failed to delete workpad after template error",
+                    delete_workpad(workpad_id),
+                );
+            }
+            seeded
+        },
+    )
+}
+
+/// Scaffold a new workpad from `template` and attach its test target and
+/// checklist.
+fn seed(mut workpad: WorkpadState, template: &WorkpadTemplate) -> Result<WorkpadState, String> {
+    let mut scaffold = files_diff(&template.files);
+    if let Some(patch) = template.scaffold_patch.as_deref() {
+        if !patch.trim().is_empty() {
+            scaffold.push_str(patch);
+            if !patch.ends_with('\n') {
+                scaffold.push('\n');
+            }
+        }
+    }
+    if !scaffold.is_empty() {
+        workpad = apply_patch(
+            workpad.workpad_id.clone(),
+            format!("Scaffold from template {}", template.name),
+            scaffold,
+        )?;
+    }
+
+    workpad.template_id = Some(template.template_id.clone());
+    workpad.test_target = template.test_target.clone();
+    workpad.checklist = template
+        .checklist
+        .iter()
+        .map(|text| ChecklistItem {
+            text: text.clone(),
+            done: false,
+        })
+        .collect();
+    save_workpad(workpad)
+}
+
+#[tauri::command]
+pub(crate) fn set_checklist_item(
+    workpad_id: String,
+    index: usize,
+    done: bool,
+) -> Result<WorkpadState, String> {
+    audited(
+        "set_checklist_item",
+        "workpad",
+        Some(workpad_id.clone()),
+        None,
+        move || {
+            let mut workpad = load_workpad(&workpad_id)?;
+            let item = workpad
+                .checklist
+                .get_mut(index)
+                .ok_or_else(|| format!("Workpad {} has no checklist item {}", workpad_id, index))?;
+            item.done = done;
+            save_workpad(workpad)
+        },
+    )
+}
@@ -0,0 +1,266 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::audit::audited;
+use crate::commands::{load_repository, read_json, write_json};
+use crate::dashboard::is_open;
+use crate::statuses::TestRunStatus;
+use crate::{
+    get_state_dir, list_ai_operations, list_test_runs, list_workpads, RepositoryState, TestRun,
+    WorkpadState,
+};
+
+/// Named group of repositories shown as one unit in the GUI.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct Workspace {
+    workspace_id: String,
+    name: String,
+    #[serde(default)]
+    repo_ids: Vec<String>,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub(crate) struct WorkspaceTestStatus {
+    /// "failed" if any open workpad's latest run failed, "running" if any is
+    /// still running, "passed" if all passed, otherwise "unknown".
+    overall: String,
+    passed: usize,
+    failed: usize,
+    running: usize,
+    untested: usize,
+    latest_runs: Vec<TestRun>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct WorkspaceSummary {
+    workspace: Workspace,
+    repositories: Vec<RepositoryState>,
+    /// Members whose repository record no longer exists.
+    missing_repo_ids: Vec<String>,
+    open_workpads: Vec<WorkpadState>,
+    test_status: WorkspaceTestStatus,
+    total_cost_usd: f64,
+    cost_by_repo: BTreeMap<String, f64>,
+}
+
+fn workspaces_dir() -> PathBuf {
+    get_state_dir().join("workspaces")
+}
+
+fn workspace_path(workspace_id: &str) -> PathBuf {
+    workspaces_dir().join(format!("{}.json", workspace_id))
+}
+
+fn load_workspace(workspace_id: &str) -> Result<Workspace, String> {
+    read_json(&workspace_path(workspace_id))?
+        .ok_or_else(|| format!("Workspace not found: {}", workspace_id))
+}
+
+fn save_workspace(mut workspace: Workspace) -> Result<Workspace, String> {
+    workspace.updated_at = Utc::now().to_rfc3339();
+    write_json(&workspace_path(&workspace.workspace_id), &workspace)?;
+    Ok(workspace)
+}
+
+#[tauri::command]
+pub(crate) fn list_workspaces() -> Result<Vec<Workspace>, String> {
+    let dir = workspaces_dir();
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut workspaces: Vec<Workspace> = Vec::new();
+    for entry in fs::read_dir(&dir).map_err(|e| format!("Failed to read workspaces: {}", e))? {
+        let path = entry
+            .map_err(|e| format!("Failed to read workspaces: {}", e))?
+            .path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        if let Some(workspace) = read_json::<Workspace>(&path)? {
+            workspaces.push(workspace);
+        }
+    }
+    workspaces.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    Ok(workspaces)
+}
+
+#[tauri::command]
+pub(crate) fn create_workspace(
+    name: String,
+    repo_ids: Option<Vec<String>>,
+) -> Result<Workspace, String> {
+    audited("create_workspace", "workspace", None, None, move || {
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return Err("Workspace name cannot be empty".to_string());
+        }
+        if list_workspaces()?
+            .iter()
+            .any(|workspace| workspace.name.eq_ignore_ascii_case(&name))
+        {
+            return Err(format!("A workspace named {} already exists", name));
+        }
+
+        let mut members: Vec<String> = Vec::new();
+        for repo_id in repo_ids.unwrap_or_default() {
+            load_repository(&repo_id)?;
+            if !members.contains(&repo_id) {
+                members.push(repo_id);
+            }
+        }
+
+        let now = Utc::now().to_rfc3339();
+        save_workspace(Workspace {
+            workspace_id: format!("ws_{}", &Uuid::new_v4().simple().to_string()[..8]),
+            name,
+            repo_ids: members,
+            created_at: now.clone(),
+            updated_at: now,
+        })
+    })
+}
+
+#[tauri::command]
+pub(crate) fn add_repo_to_workspace(
+    workspace_id: String,
+    repo_id: String,
+) -> Result<Workspace, String> {
+    audited(
+        "add_repo_to_workspace",
+        "workspace",
+        Some(workspace_id.clone()),
+        None,
+        move || {
+            let mut workspace = load_workspace(&workspace_id)?;
+            load_repository(&repo_id)?;
+            if workspace.repo_ids.contains(&repo_id) {
+                return Ok(workspace);
+            }
+            workspace.repo_ids.push(repo_id);
+            save_workspace(workspace)
+        },
+    )
+}
+
+#[tauri::command]
+pub(crate) fn remove_repo_from_workspace(
+    workspace_id: String,
+    repo_id: String,
+) -> Result<Workspace, String> {
+    audited(
+        "remove_repo_from_workspace",
+        "workspace",
+        Some(workspace_id.clone()),
+        None,
+        move || {
+            let mut workspace = load_workspace(&workspace_id)?;
+            workspace.repo_ids.retain(|id| *id != repo_id);
+            save_workspace(workspace)
+        },
+    )
+}
+
+/// Delete the grouping only; member repositories are untouched.
+#[tauri::command]
+pub(crate) fn delete_workspace(workspace_id: String) -> Result<(), String> {
+    audited(
+        "delete_workspace",
+        "workspace",
+        Some(workspace_id.clone()),
+        None,
+        move || {
+            let path = workspace_path(&workspace_id);
+            if !path.exists() {
+                return Err(format!("Workspace not found: {}", workspace_id));
+            }
+            fs::remove_file(&path)
+                .map_err(|e| format!("Failed to delete workspace {}: {}", workspace_id, e))
+        },
+    )
+}
+
+fn combined_test_status(open_workpads: &[WorkpadState], runs: &[TestRun]) -> WorkspaceTestStatus {
+    let mut status = WorkspaceTestStatus::default();
+    for workpad in open_workpads {
+        // Runs are newest first, so the first match is the latest.
+        let latest = runs
+            .iter()
+            .find(|run| run.workpad_id.as_deref() == Some(workpad.workpad_id.as_str()));
+        match latest.map(|run| run.status) {
+            Some(TestRunStatus::Passed) => status.passed += 1,
+            Some(TestRunStatus::Failed | TestRunStatus::Error) => status.failed += 1,
+            Some(TestRunStatus::Running | TestRunStatus::Pending) => status.running += 1,
+            _ => status.untested += 1,
+        }
+        status.latest_runs.extend(latest.cloned());
+    }
+
+    status.overall = if status.failed > 0 {
+        "failed"
+    } else if status.running > 0 {
+        "running"
+    } else if status.passed > 0 && status.untested == 0 {
+        "passed"
+    } else {
+        "unknown"
+    }
+    .to_string();
+    status
+}
+
+#[tauri::command]
+pub(crate) fn get_workspace_summary(workspace_id: String) -> Result<WorkspaceSummary, String> {
+    let workspace = load_workspace(&workspace_id)?;
+
+    let mut repositories = Vec::new();
+    let mut missing_repo_ids = Vec::new();
+    let mut open_workpads = Vec::new();
+    let mut repo_of_workpad: HashMap<String, String> = HashMap::new();
+    for repo_id in &workspace.repo_ids {
+        match load_repository(repo_id) {
+            Ok(repo) => repositories.push(repo),
+            Err(_) => {
+                missing_repo_ids.push(repo_id.clone());
+                continue;
+            }
+        }
+        for workpad in list_workpads(Some(repo_id.clone()), None, None)? {
+            repo_of_workpad.insert(workpad.workpad_id.clone(), repo_id.clone());
+            if is_open(&workpad) {
+                open_workpads.push(workpad);
+            }
+        }
+    }
+    open_workpads.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+
+    let test_status = combined_test_status(&open_workpads, &list_test_runs(None, None)?);
+
+    let mut cost_by_repo: BTreeMap<String, f64> = BTreeMap::new();
+    for operation in list_ai_operations(None, None, None)? {
+        let repo_id = operation
+            .workpad_id
+            .as_ref()
+            .and_then(|id| repo_of_workpad.get(id));
+        if let Some(repo_id) = repo_id {
+            *cost_by_repo.entry(repo_id.clone()).or_default() += operation.cost_usd;
+        }
+    }
+
+    Ok(WorkspaceSummary {
+        workspace,
+        repositories,
+        missing_repo_ids,
+        open_workpads,
+        test_status,
+        total_cost_usd: cost_by_repo.values().sum(),
+        cost_by_repo,
+    })
+}
@@ -0,0 +1,196 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::commands::{load_repository, resolve_repo_path};
+use crate::dashboard::is_open;
+use crate::git::run_git;
+use crate::logging::warn_on_err;
+use crate::{dev_processes, list_workpads, profiles, terminal, WorkpadState};
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct WorktreeInfo {
+    workpad_id: String,
+    repo_id: String,
+    branch: String,
+    path: String,
+    /// Whether the worktree has been materialized yet; it is created on
+    /// first use.
+    exists: bool,
+}
+
+fn worktrees_dir() -> PathBuf {
+    profiles::active_home().join("data").join("worktrees")
+}
+
+fn worktree_dir(workpad_id: &str) -> PathBuf {
+    worktrees_dir().join(workpad_id)
+}
+
+/// Create the worktree for `workpad` if it isn't there yet. The shared
+/// repository checkout is moved back to trunk first if it had the workpad
+/// branch checked out, since git won't check a branch out twice.
+fn ensure_worktree(workpad: &WorkpadState) -> Result<PathBuf, String> {
+    let dir = worktree_dir(&workpad.workpad_id);
+    if dir.join(".git").exists() {
+        return Ok(dir);
+    }
+
+    let repo_dir = resolve_repo_path(&workpad.repo_id)?;
+    let current = run_git(&repo_dir, &["rev-parse", "--abbrev-ref", "HEAD"])?;
+    if current.trim() == workpad.branch_name {
+        let trunk = load_repository(&workpad.repo_id)?.trunk_branch;
+        run_git(&repo_dir, &["checkout", &trunk])?;
+    }
+    // A directory left over from an interrupted add would make git refuse.
+    if dir.exists() {
+        fs::remove_dir_all(&dir)
+            .map_err(|e| format!("Failed to clear {}: {}", dir.display(), e))?;
+    }
+    fs::create_dir_all(worktrees_dir())
+        .map_err(|e| format!("Failed to create worktrees directory: {}", e))?;
+    let dir_arg = dir.to_string_lossy().to_string();
+    run_git(&repo_dir, &["worktree", "prune"])?;
+    run_git(
+        &repo_dir,
+        &["worktree", "add", &dir_arg, &workpad.branch_name],
+    )?;
+    tracing::info!(
+        "Created worktree for {} at {}",
+        workpad.workpad_id,
+        dir.display()
+    );
+    Ok(dir)
+}
+
+/// Where git operations for `workpad` run: its own worktree while it's open,
+/// the repository checkout once it has been promoted or deleted.
+pub(crate) fn checkout_for(workpad: &WorkpadState) -> Result<PathBuf, String> {
+    if is_open(workpad) {
+        ensure_worktree(workpad)
+    } else {
+        resolve_repo_path(&workpad.repo_id)
+    }
+}
+
+/// Remove the workpad's worktree, if it has one. Called before the CLI
+/// promotes or deletes the workpad, which needs the branch free. Refuses
+/// while the checkout has uncommitted changes or a terminal or dev process
+/// is still running in it, rather than throwing that work away.
+pub(crate) fn remove_worktree(workpad: &WorkpadState) -> Result<(), String> {
+    let dir = worktree_dir(&workpad.workpad_id);
+    if !dir.exists() {
+        return Ok(());
+    }
+    let repo_dir = resolve_repo_path(&workpad.repo_id)?;
+    // A directory left over from an interrupted add has nothing to lose.
+    if !dir.join(".git").exists() {
+        fs::remove_dir_all(&dir)
+            .map_err(|e| format!("Failed to remove {}: {}", dir.display(), e))?;
+        run_git(&repo_dir, &["worktree", "prune"])?;
+        return Ok(());
+    }
+
+    if !run_git(&dir, &["status", "--porcelain"])?.trim().is_empty() {
+        return Err(format!(
+            "Workpad {} has uncommitted changes in {}; commit or discard them first",
+            workpad.workpad_id,
+            dir.display()
+        ));
+    }
+    if terminal::is_open_for(&workpad.workpad_id)? {
+        return Err(format!(
+            "Close the terminals open in workpad {} first",
+            workpad.workpad_id
+        ));
+    }
+    if dev_processes::is_running_for(&workpad.workpad_id)? {
+        return Err(format!(
+            "Stop the dev processes running in workpad {} first",
+            workpad.workpad_id
+        ));
+    }
+    let dir_arg = dir.to_string_lossy().to_string();
+    run_git(&repo_dir, &["worktree", "remove", &dir_arg])?;
+    Ok(())
+}
+
+/// Detached worktrees for jobs that move HEAD around, kept apart from the
+/// per-workpad ones so `prune_worktrees` leaves them alone.
+fn scratch_dir() -> PathBuf {
+    profiles::active_home().join("data").join("scratch")
+}
+
+/// Check `rev` out detached in a new worktree named `name`, so a long job
+/// (e.g. a bisect) never touches the shared repository checkout.
+pub(crate) fn add_scratch(repo_id: &str, name: &str, rev: &str) -> Result<PathBuf, String> {
+    let repo_dir = resolve_repo_path(repo_id)?;
+    let dir = scratch_dir().join(name);
+    fs::create_dir_all(scratch_dir())
+        .map_err(|e| format!("Failed to create scratch directory: {}", e))?;
+    let dir_arg = dir.to_string_lossy().to_string();
+    run_git(&repo_dir, &["worktree", "add", "--detach", &dir_arg, rev])?;
+    Ok(dir)
+}
+
+pub(crate) fn remove_scratch(repo_id: &str, dir: &Path) -> Result<(), String> {
+    let repo_dir = resolve_repo_path(repo_id)?;
+    let dir_arg = dir.to_string_lossy().to_string();
+    if let Err(error) = run_git(&repo_dir, &["worktree", "remove", "--force", &dir_arg]) {
+        tracing::warn!("git worktree remove failed, deleting directly: {}", error);
+        if dir.exists() {
+            fs::remove_dir_all(dir)
+                .map_err(|e| format!("Failed to remove {}: {}", dir.display(), e))?;
+        }
+        run_git(&repo_dir, &["worktree", "prune"])?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) fn list_worktrees() -> Result<Vec<WorktreeInfo>, String> {
+    Ok(list_workpads(None, None, None)?
+        .into_iter()
+        .filter(is_open)
+        .map(|workpad| {
+            let dir = worktree_dir(&workpad.workpad_id);
+            WorktreeInfo {
+                exists: dir.join(".git").exists(),
+                path: dir.to_string_lossy().to_string(),
+                workpad_id: workpad.workpad_id,
+                repo_id: workpad.repo_id,
+                branch: workpad.branch_name,
+            }
+        })
+        .collect())
+}
+
+/// Remove worktrees whose workpads are closed or gone. Returns the ids of
+/// the workpads whose worktrees were removed.
+#[tauri::command]
+pub(crate) fn prune_worktrees() -> Result<Vec<String>, String> {
+    let Ok(entries) = fs::read_dir(worktrees_dir()) else {
+        return Ok(Vec::new());
+    };
+    let workpads = list_workpads(None, None, None)?;
+    let mut removed = Vec::new();
+    for entry in entries.flatten() {
+        let workpad_id = entry.file_name().to_string_lossy().to_string();
+        match workpads.iter().find(|w| w.workpad_id == workpad_id) {
+            Some(workpad) if is_open(workpad) => continue,
+            Some(workpad) => {
+                if let Err(error) = remove_worktree(workpad) {
+                    tracing::warn!("Kept worktree for {}: {}", workpad_id, error);
+                    continue;
+                }
+            }
+            None => warn_on_err(
+                "Failed to remove orphaned worktree",
+                fs::remove_dir_all(entry.path()),
+            ),
+        }
+        removed.push(workpad_id);
+    }
+    Ok(removed)
+}
@@ -0,0 +1,264 @@
+#!/usr/bin/env python3
+"""Stand-in for `evogitctl` used by the integration tests.
+
+Implements just enough of `repo init`, `workpad-integrated create`,
+`apply-patch` and `promote` and `test run` to write the same state files as
+the real CLI under $SOLOGIT_HOME. `test run` passes unless the target is
+"fail". Anything else (including `serve`) exits non-zero.
+"""
+
+import json
+import os
+import subprocess
+import sys
+import uuid
+from datetime import datetime, timezone
+
+HOME = os.environ["SOLOGIT_HOME"]
+STATE = os.path.join(HOME, "state")
+GIT_ENV = dict(
+    os.environ,
+    GIT_AUTHOR_NAME="Solo Git Tests",
+    GIT_AUTHOR_EMAIL="tests@sologit.invalid",
+    GIT_COMMITTER_NAME="Solo Git Tests",
+    GIT_COMMITTER_EMAIL="tests@sologit.invalid",
+)
+
+
+def now():
+    return datetime.now(timezone.utc).isoformat()
+
+
+def git(cwd, *args):
+    return subprocess.run(
+        ["git", "-C", cwd, *args], env=GIT_ENV, check=True, capture_output=True, text=True
+    ).stdout.strip()
+
+
+def path(kind, record_id):
+    return os.path.join(STATE, kind, record_id + ".json")
+
+
+def load(kind, record_id):
+    with open(path(kind, record_id)) as f:
+        return json.load(f)
+
+
+def save(kind, record_id, record):
+    os.makedirs(os.path.join(STATE, kind), exist_ok=True)
+    with open(path(kind, record_id), "w") as f:
+        json.dump(record, f, indent=2)
+
+
+def load_global():
+    try:
+        with open(os.path.join(STATE, "global.json")) as f:
+            return json.load(f)
+    except FileNotFoundError:
+        return {
+            "version": "1",
+            "last_updated": now(),
+            "active_repo": None,
+            "active_workpad": None,
+            "session_start": now(),
+            "total_operations": 0,
+            "total_cost_usd": 0.0,
+        }
+
+
+def set_active(**active):
+    state = load_global()
+    state.update(active, last_updated=now())
+    os.makedirs(STATE, exist_ok=True)
+    with open(os.path.join(STATE, "global.json"), "w") as f:
+        json.dump(state, f, indent=2)
+
+
+def option(args, name):
+    index = args.index(name)
+    value = args[index + 1]
+    del args[index : index + 2]
+    return value
+
+
+def repo_init(args):
+    name = option(args, "--name")
+    repo_id = "repo_" + uuid.uuid4().hex[:8]
+    repo_dir = option(args, "--path") if "--path" in args else os.path.join(
+        HOME, "data", "repos", repo_id
+    )
+    os.makedirs(repo_dir, exist_ok=True)
+    git(repo_dir, "init", "-q", "-b", "main")
+    git(repo_dir, "commit", "-q", "--allow-empty", "-m", "Initial commit")
+    save(
+        "repositories",
+        repo_id,
+        {
+            "repo_id": repo_id,
+            "name": name,
+            "path": repo_dir,
+            "trunk_branch": "main",
+            "current_commit": git(repo_dir, "rev-parse", "HEAD"),
+            "created_at": now(),
+            "updated_at": now(),
+            "workpads": [],
+            "total_commits": 1,
+        },
+    )
+    set_active(active_repo=repo_id)
+
+
+def workpad_create(args):
+    repo_id = option(args, "--repo")
+    title = args[0]
+    repo = load("repositories", repo_id)
+    workpad_id = "pad_" + uuid.uuid4().hex[:8]
+    branch = "pads/" + workpad_id
+    base = git(repo["path"], "rev-parse", repo["trunk_branch"])
+    git(repo["path"], "branch", branch, base)
+    save(
+        "workpads",
+        workpad_id,
+        {
+            "workpad_id": workpad_id,
+            "repo_id": repo_id,
+            "title": title,
+            "status": "active",
+            "branch_name": branch,
+            "base_commit": base,
+            "current_commit": base,
+            "created_at": now(),
+            "updated_at": now(),
+            "promoted_at": None,
+            "test_runs": [],
+            "ai_operations": [],
+            "patches_applied": 0,
+            "files_changed": [],
+        },
+    )
+    repo["workpads"].append(workpad_id)
+    save("repositories", repo_id, repo)
+    set_active(active_repo=repo_id, active_workpad=workpad_id)
+
+
+def workpad_checkout(repo, workpad):
+    """Like the real CLI: the linked worktree holding the workpad branch, or
+    the repository itself with the branch checked out."""
+    ref = "branch refs/heads/" + workpad["branch_name"]
+    worktree = None
+    for line in git(repo["path"], "worktree", "list", "--porcelain").splitlines():
+        if line.startswith("worktree "):
+            worktree = line[len("worktree "):]
+        elif line == ref and os.path.realpath(worktree) != os.path.realpath(repo["path"]):
+            return worktree
+    git(repo["path"], "checkout", "-q", workpad["branch_name"])
+    return repo["path"]
+
+
+def workpad_apply_patch(args):
+    workpad_id = option(args, "--pad")
+    message = option(args, "--message")
+    patch = args[0]
+    workpad = load("workpads", workpad_id)
+    repo = load("repositories", workpad["repo_id"])
+    checkout = workpad_checkout(repo, workpad)
+    git(checkout, "apply", "--index", patch)
+    git(checkout, "commit", "-q", "-m", message)
+    changed = git(checkout, "diff", "--name-only", workpad["base_commit"], "HEAD")
+    workpad.update(
+        current_commit=git(checkout, "rev-parse", "HEAD"),
+        patches_applied=workpad["patches_applied"] + 1,
+        files_changed=changed.splitlines(),
+        updated_at=now(),
+    )
+    save("workpads", workpad_id, workpad)
+
+
+def workpad_promote(args):
+    workpad_id = args[0]
+    workpad = load("workpads", workpad_id)
+    repo = load("repositories", workpad["repo_id"])
+    git(repo["path"], "checkout", "-q", repo["trunk_branch"])
+    git(repo["path"], "merge", "-q", "--ff-only", workpad["branch_name"])
+    commit = git(repo["path"], "rev-parse", "HEAD")
+    record_id = "pr-" + uuid.uuid4().hex
+    save(
+        "promotions",
+        record_id,
+        {
+            "record_id": record_id,
+            "repo_id": repo["repo_id"],
+            "workpad_id": workpad_id,
+            "decision": "approve",
+            "can_promote": True,
+            "auto_promote_requested": False,
+            "promoted": True,
+            "commit_hash": commit,
+            "message": "Workpad '%s' promoted to trunk" % workpad["title"],
+            "test_run_id": (workpad["test_runs"] or [None])[0],
+            "ci_status": None,
+            "ci_message": None,
+            "created_at": now(),
+        },
+    )
+    workpad.update(status="promoted", promoted_at=now(), updated_at=now())
+    save("workpads", workpad_id, workpad)
+    repo.update(current_commit=commit, updated_at=now())
+    save("repositories", repo["repo_id"], repo)
+
+
+def test_run(args):
+    target = option(args, "--target")
+    workpad_id = args[0]
+    workpad = load("workpads", workpad_id)
+    status = "failed" if target == "fail" else "passed"
+    run_id = "run_" + uuid.uuid4().hex[:8]
+    save(
+        "test_runs",
+        run_id,
+        {
+            "run_id": run_id,
+            "workpad_id": workpad_id,
+            "target": target,
+            "status": status,
+            "started_at": now(),
+            "completed_at": now(),
+            "total_tests": 1,
+            "passed": int(status == "passed"),
+            "failed": int(status == "failed"),
+            "skipped": 0,
+            "duration_ms": 1,
+            "commit_sha": workpad["current_commit"],
+            "tests": [],
+        },
+    )
+    workpad["test_runs"].insert(0, run_id)
+    workpad.update(status=status, updated_at=now())
+    save("workpads", workpad_id, workpad)
+
+
+COMMANDS = {
+    ("repo", "init"): repo_init,
+    ("workpad-integrated", "create"): workpad_create,
+    ("workpad-integrated", "apply-patch"): workpad_apply_patch,
+    ("workpad-integrated", "promote"): workpad_promote,
+    ("test", "run"): test_run,
+}
+
+
+def main():
+    args = [arg for arg in sys.argv[1:] if arg != "--empty"]
+    command = COMMANDS.get(tuple(args[:2]))
+    if command is None:
+        print("fake evogitctl: unsupported command: %s" % " ".join(args), file=sys.stderr)
+        return 1
+    try:
+        command(args[2:])
+    except subprocess.CalledProcessError as error:
+        print(error.stderr, file=sys.stderr)
+        return 1
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -6,6 +6,9 @@ Provides the evogitctl command-line interface with subcommands for
 repository management, workpad operations, testing, and AI pairing.
 """
 
+import contextlib
+import io
+import json
 import os
 import click
 import subprocess
@@ -130,7 +133,7 @@ def abort_with_error(
 @click.option(
     "--config", "-c",
     type=click.Path(exists=True, path_type=Path),
-    help="Path to config file (default: ~/.sologit/config.yaml)"
+    help="Path to config file (default: $SOLOGIT_CONFIG_PATH or $SOLOGIT_HOME/config.yaml)"
 )
 @click.pass_context
 def cli(ctx, verbose, config):
@@ -312,6 +315,87 @@ def gui(ctx, dev: bool):
         abort_with_error("GUI launch failed", str(exc))
 
 
+def _serve_request(request: dict) -> dict:
+    """Handle one `serve --json` request and build its response."""
+    method = request.get("method")
+    params = request.get("params") or {}
+
+    if method == "hello":
+        return {"result": {"version": __version__}}
+    if method != "cli":
+        return {"error": {"code": "unknown_method", "message": f"Unknown method: {method}"}}
+
+    args = params.get("args")
+    if not isinstance(args, list) or not all(isinstance(arg, str) for arg in args):
+        return {"error": {"code": "invalid_params", "message": "cli expects a list of string args"}}
+    if args[:1] == ["serve"]:
+        return {"error": {"code": "invalid_params", "message": "serve cannot be nested"}}
+
+    stdout, stderr = io.StringIO(), io.StringIO()
+    try:
+        with contextlib.redirect_stdout(stdout), contextlib.redirect_stderr(stderr):
+            exit_code = _execute_cli_command(args, record_cli_history=False)
+    except Exception as exc:
+        message = stderr.getvalue().strip() or stdout.getvalue().strip() or str(exc)
+        return {
+            "error": {
+                "code": "cli_failed",
+                "message": message or type(exc).__name__,
+                # Click errors carry the status the command would exit with (2 for usage).
+                "exit_code": getattr(exc, "exit_code", 1),
+            }
+        }
+
+    if exit_code:
+        if not isinstance(exit_code, int):
+            exit_code = 1
+        message = stderr.getvalue().strip() or stdout.getvalue().strip()
+        return {
+            "error": {
+                "code": "cli_failed",
+                "message": message or f"evogitctl exited with status {exit_code}",
+                "exit_code": exit_code,
+            }
+        }
+    return {"result": {"stdout": stdout.getvalue(), "stderr": stderr.getvalue()}}
+
+
+@cli.command()
+@click.option("--json", "json_mode", is_flag=True, help="Speak newline-delimited JSON on stdin/stdout")
+def serve(json_mode: bool):
+    """Run commands for the Heaven Interface GUI over stdin/stdout.
+
+    Each input line is a request {"id", "method", "params"} and each output
+    line the matching {"id", "result"} or {"id", "error": {"code", "message"}};
+    failed "cli" requests add the command's "exit_code" to the error.
+    "hello" returns the CLI version; "cli" runs {"args": [...]} as if passed
+    to evogitctl and returns its stdout. Requests are handled one at a time.
+    """
+    if not json_mode:
+        abort_with_error("serve only supports --json.", "Run: evogitctl serve --json")
+
+    out = sys.stdout
+    for line in sys.stdin:
+        if not line.strip():
+            continue
+        try:
+            request = json.loads(line)
+            if not isinstance(request, dict):
+                raise ValueError("request must be an object")
+        except ValueError as exc:
+            logger.warning(f"serve: ignoring malformed request: {exc}")
+            continue
+
+        try:
+            response = _serve_request(request)
+        except Exception as exc:  # pragma: no cover - keep serving after bugs
+            logger.exception("serve: request failed")
+            response = {"error": {"code": "internal", "message": str(exc)}}
+        response["id"] = request.get("id")
+        out.write(json.dumps(response) + "\n")
+        out.flush()
+
+
 # Register command groups
 cli.add_command(config_commands.config_group)
 
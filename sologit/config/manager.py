@@ -16,6 +16,7 @@ from dataclasses import dataclass, field, asdict
 import yaml
 
 from sologit.utils.logger import get_logger
+from sologit.utils.paths import default_config_path, sologit_home
 
 logger = get_logger(__name__)
 
@@ -472,8 +473,8 @@ class SoloGitConfig:
 class ConfigManager:
     """Manages Solo Git configuration."""
 
-    DEFAULT_CONFIG_DIR = Path.home() / ".sologit"
-    DEFAULT_CONFIG_FILE = DEFAULT_CONFIG_DIR / "config.yaml"
+    DEFAULT_CONFIG_DIR = sologit_home()
+    DEFAULT_CONFIG_FILE = default_config_path()
 
     def __init__(self, config_path: Optional[Path] = None):
         """
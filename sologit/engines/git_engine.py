@@ -20,6 +20,7 @@ from git import Repo, GitCommandError
 from sologit.core.repository import Repository
 from sologit.core.workpad import Workpad, Checkpoint
 from sologit.utils.logger import get_logger
+from sologit.utils.paths import sologit_home
 
 logger = get_logger(__name__)
 
@@ -56,7 +57,7 @@ class GitEngine:
                      (default: ~/.sologit/data)
         """
         if data_dir is None:
-            data_dir = Path.home() / ".sologit" / "data"
+            data_dir = sologit_home() / "data"
         
         self.data_dir = Path(data_dir)
         self.repos_path = self.data_dir / "repos"
@@ -349,12 +350,8 @@ class GitEngine:
         repository = self.repo_db[workpad.repo_id]
         
         try:
-            # Open repository
-            repo = Repo(repository.path)
-            
-            # Ensure on correct branch
-            branch = getattr(repo.heads, workpad.branch_name)
-            branch.checkout()
+            # Open the workpad's checkout
+            repo = self._open_workpad(repository, workpad)
             
             # Write patch to temporary file
             patch_file = repository.path / ".git" / "solo-git-patch.diff"
@@ -677,18 +674,16 @@ class GitEngine:
             raise RepositoryNotFoundError(f"Repository {repo_id} not found")
         
         try:
-            repo = Repo(repository.path)
-            
-            # Checkout appropriate branch
+            # Open the checkout for the appropriate branch
             if pad_id:
                 workpad = self.workpad_db.get(pad_id)
                 if not workpad:
                     raise WorkpadNotFoundError(f"Workpad {pad_id} not found")
-                branch = getattr(repo.heads, workpad.branch_name)
+                repo = self._open_workpad(repository, workpad)
             else:
-                branch = getattr(repo.heads, repository.trunk_branch)
-            
-            branch.checkout()
+                repo = Repo(repository.path)
+                getattr(repo.heads, repository.trunk_branch).checkout()
+            branch = repo.active_branch
             
             # Get status
             changed_files = [item.a_path for item in repo.index.diff(None)]
@@ -770,11 +765,7 @@ class GitEngine:
         repository = self.repo_db[workpad.repo_id]
         
         try:
-            repo = Repo(repository.path)
-            
-            # Ensure on workpad branch
-            branch = getattr(repo.heads, workpad.branch_name)
-            branch.checkout()
+            repo = self._open_workpad(repository, workpad)
             
             # Get tag name
             tag_name = f"{workpad.branch_name}@{checkpoint_id}"
@@ -1061,11 +1052,7 @@ class GitEngine:
             raise RepositoryNotFoundError(f"Repository {repo_id} not found")
         
         try:
-            repo = Repo(repository.path)
-            
-            # Ensure on workpad branch
-            branch = getattr(repo.heads, workpad.branch_name)
-            branch.checkout()
+            repo = self._open_workpad(repository, workpad)
             
             # Stage files
             if files:
@@ -1445,6 +1432,42 @@ class GitEngine:
         if not pad_id or not pad_id.startswith('pad_'):
             raise GitEngineError(f"Invalid workpad ID format: {pad_id}")
     
+    def _linked_worktree(self, repo: Repo, branch_name: str) -> Optional[Path]:
+        """Path of another worktree that has `branch_name` checked out, if any."""
+        main = Path(repo.working_tree_dir).resolve()
+        path = None
+        for line in repo.git.worktree('list', '--porcelain').splitlines():
+            if line.startswith('worktree '):
+                path = Path(line[len('worktree '):])
+            elif line == f"branch refs/heads/{branch_name}" and path is not None:
+                if path.resolve() != main:
+                    return path
+        return None
+    
+    def _open_workpad(self, repository: Repository, workpad: Workpad) -> Repo:
+        """
+        Open the checkout that holds the workpad branch.
+        
+        The GUI keeps each open workpad in its own linked worktree; work
+        happens there when it exists. Otherwise the branch is checked out in
+        the repository path.
+        """
+        repo = Repo(repository.path)
+        worktree = self._linked_worktree(repo, workpad.branch_name)
+        if worktree is not None:
+            return Repo(worktree)
+        getattr(repo.heads, workpad.branch_name).checkout()
+        return repo
+    
+    def workpad_path(self, pad_id: str) -> Path:
+        """Directory the workpad's files are checked out in."""
+        workpad = self.workpad_db.get(pad_id)
+        if not workpad:
+            raise WorkpadNotFoundError(f"Workpad {pad_id} not found")
+        repository = self.repo_db[workpad.repo_id]
+        worktree = self._linked_worktree(Repo(repository.path), workpad.branch_name)
+        return worktree if worktree is not None else repository.path
+    
     def _update_repo_metadata(
         self, 
         repo_id: str, 
@@ -22,6 +22,7 @@ from docker.errors import DockerException
 
 from sologit.engines.git_engine import GitEngine, WorkpadNotFoundError
 from sologit.utils.logger import get_logger
+from sologit.utils.paths import sologit_home
 from sologit.ui.formatter import RichFormatter
 
 logger = get_logger(__name__)
@@ -96,7 +97,7 @@ class TestOrchestrator:
         self.git_engine = git_engine
         self.sandbox_image = sandbox_image
         self.requested_mode = TestExecutionMode(execution_mode)
-        self.log_dir = Path(log_dir or (Path.home() / ".sologit" / "data" / "test_runs"))
+        self.log_dir = Path(log_dir or (sologit_home() / "data" / "test_runs"))
         self.log_dir.mkdir(parents=True, exist_ok=True)
         self.formatter = formatter or RichFormatter()
 
@@ -233,9 +234,9 @@ class TestOrchestrator:
             execution_task = None
             active_tasks: Dict[str, int] = {}
 
-            repository = None
+            checkout = None
             with self._progress_stage(progress, overall_task, "Preparing test workspace", 1):
-                repository = self.git_engine.get_repo(workpad.repo_id)
+                checkout = self.git_engine.workpad_path(pad_id)
 
             if progress and total_tests:
                 progress.update(overall_task, description="Executing tests")
@@ -243,7 +244,7 @@ class TestOrchestrator:
 
             if parallel:
                 results = await self._run_parallel(
-                    repository.path,
+                    checkout,
                     tests,
                     on_output=on_output,
                     on_test_complete=on_test_complete,
@@ -254,7 +255,7 @@ class TestOrchestrator:
                 )
             else:
                 results = await self._run_sequential(
-                    repository.path,
+                    checkout,
                     tests,
                     on_output=on_output,
                     on_test_complete=on_test_complete,
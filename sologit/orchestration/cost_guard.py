@@ -12,6 +12,7 @@ import json
 from pathlib import Path
 
 from sologit.utils.logger import get_logger
+from sologit.utils.paths import sologit_home
 
 logger = get_logger(__name__)
 
@@ -82,7 +83,7 @@ class CostTracker:
         Args:
             storage_path: Path to store usage data
         """
-        self.storage_path = storage_path or Path.home() / '.sologit' / 'usage.json'
+        self.storage_path = storage_path or sologit_home() / 'usage.json'
         self.storage_path.parent.mkdir(parents=True, exist_ok=True)
         
         self.usage_history: Dict[date, DailyUsage] = {}
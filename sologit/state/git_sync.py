@@ -292,10 +292,12 @@ class GitStateSync:
         merge_commit = self.git_engine.promote_workpad(pad_id)
         
         # Update workpad state
+        now = datetime.utcnow().isoformat()
         self.state_manager.update_workpad(
             pad_id,
             status="promoted",
-            updated_at=datetime.utcnow().isoformat()
+            promoted_at=now,
+            updated_at=now
         )
         
         # Sync commits
@@ -28,6 +28,7 @@ from sologit.state.schema import (
     EventType,
 )
 from sologit.utils.logger import get_logger
+from sologit.utils.paths import sologit_home
 
 # Import for type annotation - avoid circular import at runtime
 if TYPE_CHECKING:
@@ -427,7 +428,7 @@ class StateManager:
     def __init__(self, backend: Optional[StateBackend] = None, state_dir: Optional[Path] = None) -> None:
         if backend is None:
             if state_dir is None:
-                state_dir = Path.home() / ".sologit" / "state"
+                state_dir = sologit_home() / "state"
             backend = JSONStateBackend(state_dir)
         
         self.backend = backend
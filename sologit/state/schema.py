@@ -6,13 +6,33 @@ Defines the data structures for the shared state layer that connects
 the CLI/TUI with the GUI companion app.
 """
 
-from dataclasses import dataclass, asdict, field
+from dataclasses import dataclass, asdict, field, fields
 from datetime import datetime
 from enum import Enum
 from typing import List, Dict, Optional, Any
 import json
 
 
+def _known_fields(cls, data: Dict[str, Any]) -> Dict[str, Any]:
+    """Keyword arguments for `cls`: its own fields, with every other key
+    (e.g. fields added by the GUI backend) collected into `extra`."""
+    names = {f.name for f in fields(cls)} - {'extra'}
+    known = {k: v for k, v in data.items() if k in names}
+    known['extra'] = {k: v for k, v in data.items() if k not in names}
+    return known
+
+
+def _with_extra(result: Dict[str, Any]) -> Dict[str, Any]:
+    """Flatten `extra` back into an `asdict` result so saving keeps the keys
+    this side doesn't know about."""
+    extra = result.pop('extra', {})
+    return {**extra, **result}
+
+
+def _extra_field():
+    return field(default_factory=dict, repr=False, compare=False)
+
+
 class WorkpadStatus(Enum):
     """Status of a workpad."""
     ACTIVE = "active"
@@ -55,13 +75,14 @@ class CommitNode:
     test_status: Optional[str] = None  # "passed", "failed", "pending"
     ci_status: Optional[str] = None  # "passed", "failed", "running", None
     is_trunk: bool = False
+    extra: Dict[str, Any] = _extra_field()
     
     def to_dict(self) -> Dict[str, Any]:
-        return asdict(self)
+        return _with_extra(asdict(self))
     
     @staticmethod
     def from_dict(data: Dict[str, Any]) -> 'CommitNode':
-        return CommitNode(**data)
+        return CommitNode(**_known_fields(CommitNode, data))
 
 
 @dataclass
@@ -74,13 +95,14 @@ class TestResult:
     output: str = ""
     error: Optional[str] = None
     timestamp: str = field(default_factory=lambda: datetime.utcnow().isoformat())
+    extra: Dict[str, Any] = _extra_field()
     
     def to_dict(self) -> Dict[str, Any]:
-        return asdict(self)
+        return _with_extra(asdict(self))
     
     @staticmethod
     def from_dict(data: Dict[str, Any]) -> 'TestResult':
-        return TestResult(**data)
+        return TestResult(**_known_fields(TestResult, data))
 
 
 @dataclass
@@ -98,16 +120,17 @@ class TestRun:
     skipped: int = 0
     duration_ms: int = 0
     tests: List[TestResult] = field(default_factory=list)
+    extra: Dict[str, Any] = _extra_field()
     
     def to_dict(self) -> Dict[str, Any]:
         result = asdict(self)
         result['tests'] = [t.to_dict() for t in self.tests]
-        return result
+        return _with_extra(result)
     
     @staticmethod
     def from_dict(data: Dict[str, Any]) -> 'TestRun':
         tests = [TestResult.from_dict(t) for t in data.pop('tests', [])]
-        run = TestRun(**data)
+        run = TestRun(**_known_fields(TestRun, data))
         run.tests = tests
         return run
 
@@ -129,13 +152,14 @@ class PromotionRecord:
     ci_status: Optional[str] = None
     ci_message: Optional[str] = None
     created_at: str = field(default_factory=lambda: datetime.utcnow().isoformat())
+    extra: Dict[str, Any] = _extra_field()
 
     def to_dict(self) -> Dict[str, Any]:
-        return asdict(self)
+        return _with_extra(asdict(self))
 
     @staticmethod
     def from_dict(data: Dict[str, Any]) -> 'PromotionRecord':
-        return PromotionRecord(**data)
+        return PromotionRecord(**_known_fields(PromotionRecord, data))
 
 
 @dataclass
@@ -153,13 +177,14 @@ class AIOperation:
     started_at: str = field(default_factory=lambda: datetime.utcnow().isoformat())
     completed_at: Optional[str] = None
     error: Optional[str] = None
+    extra: Dict[str, Any] = _extra_field()
     
     def to_dict(self) -> Dict[str, Any]:
-        return asdict(self)
+        return _with_extra(asdict(self))
     
     @staticmethod
     def from_dict(data: Dict[str, Any]) -> 'AIOperation':
-        return AIOperation(**data)
+        return AIOperation(**_known_fields(AIOperation, data))
 
 
 @dataclass
@@ -179,13 +204,14 @@ class WorkpadState:
     ai_operations: List[str] = field(default_factory=list)  # AIOperation IDs
     patches_applied: int = 0
     files_changed: List[str] = field(default_factory=list)
+    extra: Dict[str, Any] = _extra_field()
     
     def to_dict(self) -> Dict[str, Any]:
-        return asdict(self)
+        return _with_extra(asdict(self))
     
     @staticmethod
     def from_dict(data: Dict[str, Any]) -> 'WorkpadState':
-        return WorkpadState(**data)
+        return WorkpadState(**_known_fields(WorkpadState, data))
 
 
 @dataclass
@@ -200,13 +226,14 @@ class RepositoryState:
     updated_at: str = field(default_factory=lambda: datetime.utcnow().isoformat())
     workpads: List[str] = field(default_factory=list)  # Workpad IDs
     total_commits: int = 0
+    extra: Dict[str, Any] = _extra_field()
     
     def to_dict(self) -> Dict[str, Any]:
-        return asdict(self)
+        return _with_extra(asdict(self))
     
     @staticmethod
     def from_dict(data: Dict[str, Any]) -> 'RepositoryState':
-        return RepositoryState(**data)
+        return RepositoryState(**_known_fields(RepositoryState, data))
 
 
 @dataclass
@@ -219,13 +246,14 @@ class GlobalState:
     session_start: str = field(default_factory=lambda: datetime.utcnow().isoformat())
     total_operations: int = 0
     total_cost_usd: float = 0.0
+    extra: Dict[str, Any] = _extra_field()
     
     def to_dict(self) -> Dict[str, Any]:
-        return asdict(self)
+        return _with_extra(asdict(self))
     
     @staticmethod
     def from_dict(data: Dict[str, Any]) -> 'GlobalState':
-        return GlobalState(**data)
+        return GlobalState(**_known_fields(GlobalState, data))
 
 
 # Event types for real-time updates
@@ -0,0 +1,17 @@
+"""Locations of Solo Git's files on disk."""
+
+import os
+from pathlib import Path
+
+
+def sologit_home() -> Path:
+    """Root of Solo Git's state and data: ``$SOLOGIT_HOME`` when set (the GUI
+    points it at the active profile), otherwise ``~/.sologit``."""
+    home = os.environ.get("SOLOGIT_HOME", "").strip()
+    return Path(home).expanduser() if home else Path.home() / ".sologit"
+
+
+def default_config_path() -> Path:
+    """``$SOLOGIT_CONFIG_PATH`` when set, otherwise ``config.yaml`` in the home."""
+    path = os.environ.get("SOLOGIT_CONFIG_PATH", "").strip()
+    return Path(path).expanduser() if path else sologit_home() / "config.yaml"
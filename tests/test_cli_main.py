@@ -1,4 +1,6 @@
 import importlib
+import io
+import json
 import sys
 import types
 from pathlib import Path
@@ -200,3 +202,51 @@ def test_main_uses_interactive_shell(cli_main, monkeypatch):
     assert called["shell"] == 0
     assert "cmd" not in called
     assert exit_codes == [0]
+
+
+def _serve(cli_main, monkeypatch, *lines):
+    """Feed `lines` to `serve --json` on stdin and parse its stdout responses."""
+    monkeypatch.setattr(cli_main, "get_command_history", lambda: DummyHistory())
+    monkeypatch.setattr(cli_main, "ConfigManager", DummyConfigManager)
+    stdout = io.StringIO()
+    monkeypatch.setattr(cli_main.sys, "stdin", io.StringIO("".join(f"{line}\n" for line in lines)))
+    monkeypatch.setattr(cli_main.sys, "stdout", stdout)
+
+    cli_main.serve.callback(json_mode=True)
+
+    return [json.loads(line) for line in stdout.getvalue().splitlines()]
+
+
+def test_serve_runs_cli_requests(cli_main, monkeypatch):
+    request = {"id": 1, "method": "cli", "params": {"args": ["hello"]}}
+
+    responses = _serve(cli_main, monkeypatch, json.dumps(request))
+
+    assert len(responses) == 1
+    assert responses[0]["id"] == 1
+    assert "Solo Git is ready!" in responses[0]["result"]["stdout"]
+
+
+def test_serve_reports_usage_errors_with_exit_code(cli_main, monkeypatch):
+    request = {"id": 2, "method": "cli", "params": {"args": ["no-such-command"]}}
+
+    responses = _serve(cli_main, monkeypatch, json.dumps(request))
+
+    assert len(responses) == 1
+    error = responses[0]["error"]
+    assert responses[0]["id"] == 2
+    assert error["code"] == "cli_failed"
+    assert error["exit_code"] == 2
+    assert "no-such-command" in error["message"]
+
+
+def test_serve_skips_malformed_request_lines(cli_main, monkeypatch):
+    responses = _serve(
+        cli_main,
+        monkeypatch,
+        "not json",
+        "[1, 2]",
+        json.dumps({"id": "h", "method": "hello"}),
+    )
+
+    assert responses == [{"result": {"version": cli_main.__version__}, "id": "h"}]
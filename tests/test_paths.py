@@ -0,0 +1,49 @@
+from pathlib import Path
+
+import pytest
+
+from sologit.utils.paths import default_config_path, sologit_home
+
+
+@pytest.fixture(autouse=True)
+def fake_home(monkeypatch, tmp_path):
+    monkeypatch.setattr(Path, "home", lambda: tmp_path)
+    monkeypatch.delenv("SOLOGIT_HOME", raising=False)
+    monkeypatch.delenv("SOLOGIT_CONFIG_PATH", raising=False)
+    return tmp_path
+
+
+def test_sologit_home_defaults_to_dot_sologit(fake_home):
+    assert sologit_home() == fake_home / ".sologit"
+
+
+def test_sologit_home_uses_env(monkeypatch, tmp_path):
+    monkeypatch.setenv("SOLOGIT_HOME", str(tmp_path / "profile"))
+
+    assert sologit_home() == tmp_path / "profile"
+
+
+@pytest.mark.parametrize("value", ["", "   ", "\t\n"])
+def test_sologit_home_ignores_blank_env(monkeypatch, fake_home, value):
+    monkeypatch.setenv("SOLOGIT_HOME", value)
+
+    assert sologit_home() == fake_home / ".sologit"
+
+
+def test_default_config_path_follows_home(monkeypatch, tmp_path):
+    monkeypatch.setenv("SOLOGIT_HOME", str(tmp_path / "profile"))
+
+    assert default_config_path() == tmp_path / "profile" / "config.yaml"
+
+
+def test_default_config_path_override(monkeypatch, tmp_path):
+    monkeypatch.setenv("SOLOGIT_HOME", str(tmp_path / "profile"))
+    monkeypatch.setenv("SOLOGIT_CONFIG_PATH", str(tmp_path / "custom.yaml"))
+
+    assert default_config_path() == tmp_path / "custom.yaml"
+
+
+def test_default_config_path_ignores_blank_override(monkeypatch, fake_home):
+    monkeypatch.setenv("SOLOGIT_CONFIG_PATH", "  ")
+
+    assert default_config_path() == fake_home / ".sologit" / "config.yaml"
@@ -31,6 +31,7 @@ def mock_git_engine(repo_path: Path) -> Mock:
     repo = SimpleNamespace(path=repo_path)
     engine.get_workpad.return_value = workpad
     engine.get_repo.return_value = repo
+    engine.workpad_path.return_value = repo_path
     return engine
 
 